@@ -11,6 +11,7 @@
 use crate::ai::chat::context::{hydrate_context, ContextItem, HydratedContext};
 use crate::ai::chat::tools::{execute_chat_tool, get_chat_tools, ChatToolResult};
 use crate::ai::credentials::CredentialManager;
+use crate::events::EventSink;
 use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -18,7 +19,6 @@ use serde_json::{json, Value};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
 use tokio::time::sleep;
 
 /// Maximum ReAct loop iterations
@@ -74,7 +74,7 @@ enum StreamEvent {
 /// Run the chat agent loop with streaming
 ///
 /// # Arguments
-/// * `app` - Tauri app handle for emitting events
+/// * `events` - Sink for streaming events (the Tauri `AppHandle` in the app)
 /// * `message` - User's message
 /// * `context_items` - Drag-dropped/mentioned context
 /// * `model` - Model ID ("claude-haiku-4-5" or "claude-sonnet-4-5")
@@ -90,7 +90,7 @@ enum StreamEvent {
 /// * `chat:error` - Error occurred
 /// * `chat:aborted` - Aborted by user
 pub async fn run_chat_agent(
-    app: &AppHandle,
+    events: &dyn EventSink,
     message: &str,
     context_items: &[ContextItem],
     model: &str,
@@ -112,7 +112,7 @@ pub async fn run_chat_agent(
     // Check abort at start
     if is_aborted() {
         eprintln!("[ChatAgent] Aborted before starting");
-        app.emit("chat:aborted", json!({"reason": "User requested abort"})).ok();
+        events.emit_json("chat:aborted", json!({"reason": "User requested abort"}));
         return Ok(String::new());
     }
 
@@ -150,7 +150,7 @@ pub async fn run_chat_agent(
         // Check abort at start of each iteration
         if is_aborted() {
             eprintln!("[ChatAgent] Aborted at iteration {}", iteration + 1);
-            app.emit("chat:aborted", json!({"reason": "User requested abort"})).ok();
+            events.emit_json("chat:aborted", json!({"reason": "User requested abort"}));
             return Ok(final_response);
         }
 
@@ -203,7 +203,7 @@ pub async fn run_chat_agent(
 
         // Process streaming response
         let (stop_reason, has_tool_use, assistant_content, tool_results, _iteration_text) =
-            process_stream(app, response, &mut final_response).await?;
+            process_stream(events, response, &mut final_response).await?;
 
         // Add assistant message to history
         messages.push(json!({
@@ -230,15 +230,14 @@ pub async fn run_chat_agent(
     }
 
     // 9. Emit completion
-    app.emit("chat:complete", json!({}))
-        .map_err(|e| format!("Event emit failed: {}", e))?;
+    events.emit_json("chat:complete", json!({}));
 
     Ok(final_response)
 }
 
 /// Process the streaming response from Anthropic API
 async fn process_stream(
-    app: &AppHandle,
+    events: &dyn EventSink,
     response: reqwest::Response,
     final_response: &mut String,
 ) -> Result<(Option<String>, bool, Vec<Value>, Vec<Value>, String), String> {
@@ -293,14 +292,13 @@ async fn process_stream(
                                         eprintln!("[ChatAgent] Extended thinking started");
 
                                         // Emit thinking started event
-                                        app.emit(
+                                        events.emit_json(
                                             "chat:thinking",
                                             json!({
                                                 "status": "started",
                                                 "timestamp": chrono::Utc::now().timestamp_millis(),
                                             }),
-                                        )
-                                        .ok();
+                                        );
                                     }
                                     "text" => {
                                         current_text_block = Some(String::new());
@@ -319,7 +317,7 @@ async fn process_stream(
                                             .to_string();
 
                                         // Emit thought step (running) - input will be updated when complete
-                                        app.emit(
+                                        events.emit_json(
                                             "chat:thought",
                                             json!({
                                                 "id": &id,
@@ -328,8 +326,7 @@ async fn process_stream(
                                                 "status": "running",
                                                 "timestamp": chrono::Utc::now().timestamp_millis(),
                                             }),
-                                        )
-                                        .ok();
+                                        );
 
                                         current_tool_block = Some((id, name, String::new()));
                                     }
@@ -349,14 +346,13 @@ async fn process_stream(
                                             delta.get("thinking").and_then(|t| t.as_str())
                                         {
                                             // Emit thinking chunk for streaming
-                                            app.emit(
+                                            events.emit_json(
                                                 "chat:thinking",
                                                 json!({
                                                     "status": "streaming",
                                                     "chunk": thinking,
                                                 }),
-                                            )
-                                            .ok();
+                                            );
 
                                             // Accumulate thinking
                                             if let Some(ref mut block) = current_thinking_block {
@@ -381,7 +377,7 @@ async fn process_stream(
                                             delta.get("text").and_then(|t| t.as_str())
                                         {
                                             // Emit text chunk for streaming
-                                            app.emit("chat:token", json!({ "chunk": text })).ok();
+                                            events.emit_json("chat:token", json!({ "chunk": text }));
 
                                             // Accumulate text
                                             if let Some(ref mut block) = current_text_block {
@@ -414,14 +410,13 @@ async fn process_stream(
                                     );
 
                                     // Emit thinking completed event
-                                    app.emit(
+                                    events.emit_json(
                                         "chat:thinking",
                                         json!({
                                             "status": "complete",
                                             "content": &thinking,
                                         }),
-                                    )
-                                    .ok();
+                                    );
 
                                     // Add to assistant content for conversation history
                                     // Note: Thinking blocks should be preserved for context
@@ -479,7 +474,7 @@ async fn process_stream(
                                         input_display
                                     };
 
-                                    app.emit(
+                                    events.emit_json(
                                         "chat:thought",
                                         json!({
                                             "id": &id,
@@ -489,8 +484,7 @@ async fn process_stream(
                                             "status": if is_error { "error" } else { "complete" },
                                             "timestamp": chrono::Utc::now().timestamp_millis(),
                                         }),
-                                    )
-                                    .ok();
+                                    );
 
                                     tool_results.push(json!({
                                         "type": "tool_result",
@@ -546,6 +540,7 @@ fn build_chat_system_prompt(context_addition: &str, previous_context: &str) -> S
 - **search_hybrid**: Semantic + keyword search in files
 - **read_file**: Read file contents
 - **list_directory**: List directory contents
+- **file_history**: Files added, moved or removed in watched folders (e.g. "what did I download this week", "where did report.pdf go")
 - **inspect_pattern**: Sample files matching a regex pattern
 - **bash**: Execute shell commands (ls, find, cat, head, wc, git status, etc.)
 - **grep**: Search file contents with regex (uses ripgrep for speed)
@@ -8,6 +8,7 @@
 use crate::ai::grok::document_parser::{is_parseable, DocumentParser};
 use crate::ai::rules::VirtualFile;
 use crate::ai::v2::compression::generate_hologram;
+use crate::scan::{EntryKind, ScanOptions};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -149,8 +150,7 @@ fn hydrate_folder_hologram(path: &str, name: &str) -> Result<String, String> {
     }
 
     // Scan folder into VirtualFiles
-    let mut files: Vec<VirtualFile> = Vec::new();
-    scan_folder_recursive(folder_path, &mut files, MAX_SCAN_DEPTH)?;
+    let files = scan_folder(folder_path)?;
 
     // Generate hologram using V5 compression
     let hologram = generate_hologram(&files);
@@ -263,52 +263,26 @@ fn hydrate_image(
     }))
 }
 
-/// Recursively scan folder into VirtualFiles
-fn scan_folder_recursive(
-    path: &Path,
-    files: &mut Vec<VirtualFile>,
-    max_depth: usize,
-) -> Result<(), String> {
-    if max_depth == 0 {
-        return Ok(());
-    }
-
-    let entries =
-        fs::read_dir(path).map_err(|e| format!("Failed to read directory {}: {}", path.display(), e))?;
-
-    for entry in entries.flatten() {
-        let entry_path = entry.path();
-
-        match VirtualFile::from_path(&entry_path) {
-            Ok(vfile) => {
-                let is_dir = vfile.is_directory;
-                files.push(vfile);
-
-                // Recurse into subdirectories
-                if is_dir {
-                    scan_folder_recursive(&entry_path, files, max_depth - 1)?;
-                }
-            }
-            Err(e) => {
-                eprintln!(
-                    "[ChatContext] Skipping file {}: {}",
-                    entry_path.display(),
-                    e
-                );
-            }
-        }
+/// Scan folder into VirtualFiles with the shared scanner
+fn scan_folder(path: &Path) -> Result<Vec<VirtualFile>, String> {
+    let snapshot = ScanOptions::new()
+        .with_max_depth(MAX_SCAN_DEPTH)
+        .with_max_entries(MAX_FILES_PER_FOLDER)
+        .scan(path)?;
 
-        // Limit total files scanned
-        if files.len() >= MAX_FILES_PER_FOLDER {
-            eprintln!(
-                "[ChatContext] Folder scan limit reached: {} files",
-                files.len()
-            );
-            break;
-        }
+    if snapshot.stats().truncated {
+        eprintln!(
+            "[ChatContext] Folder scan limit reached: {} files",
+            snapshot.entries().len()
+        );
     }
 
-    Ok(())
+    Ok(snapshot
+        .entries()
+        .iter()
+        .filter(|entry| entry.kind != EntryKind::Symlink)
+        .map(VirtualFile::from_scan_entry)
+        .collect())
 }
 
 #[cfg(test)]
@@ -5,12 +5,15 @@
 //! - read_file: Read file contents
 //! - inspect_pattern: Sample files from hologram pattern
 //! - list_directory: List directory contents
+//! - file_history: Query the journal of changes seen in watched folders
 //! - shell: Execute safe shell commands (allowlist only)
 //! - grep: Search file contents with regex
 
 use super::tools_terminal::{execute_bash, execute_grep, execute_shell, get_terminal_tools};
 use crate::ai::grok::document_parser::{is_parseable, parse_document};
 use crate::security::{safe_regex, PathValidator};
+use crate::services::watch_journal::{ChangeKind, JournalQuery, WatchJournal};
+use crate::utils::format_size;
 use regex::Regex;
 use serde_json::{json, Value};
 use std::fs;
@@ -156,6 +159,37 @@ pub fn get_chat_tools() -> Vec<Value> {
                 "required": ["path"]
             }
         }),
+        json!({
+            "name": "file_history",
+            "description": "Look up files added, moved or removed in watched folders (e.g. Downloads). Use when the user asks what arrived in a folder recently or where a file went.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "folder": {
+                        "type": "string",
+                        "description": "Optional: Folder path, or a standard folder name like 'Downloads' or 'Desktop'"
+                    },
+                    "kind": {
+                        "type": "string",
+                        "enum": ["created", "moved", "removed"],
+                        "description": "Optional: Only this kind of change"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Optional: Start time as YYYY-MM-DD, RFC 3339, or an age like '24h' or '7d'"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Optional: Part of the file name (matches old names of moved files too)"
+                    },
+                    "max_results": {
+                        "type": "integer",
+                        "default": 50,
+                        "description": "Maximum results to return"
+                    }
+                }
+            }
+        }),
     ];
 
     // Add terminal tools (bash, grep)
@@ -172,6 +206,7 @@ pub async fn execute_chat_tool(name: &str, input: &Value) -> ChatToolResult {
         "read_file" => execute_read_file(input),
         "inspect_pattern" => execute_inspect_pattern(input),
         "list_directory" => execute_list_directory(input),
+        "file_history" => execute_file_history(input),
         // "shell" is the new safe command; "bash" kept for backward compatibility
         "shell" => match execute_shell(input).await {
             Ok(output) => ChatToolResult::Success(output),
@@ -843,6 +878,72 @@ fn execute_list_directory(input: &Value) -> ChatToolResult {
     }
 }
 
+fn execute_file_history(input: &Value) -> ChatToolResult {
+    let text = |key: &str| input.get(key).and_then(|v| v.as_str()).filter(|v| !v.trim().is_empty());
+
+    let kind = match text("kind").map(ChangeKind::parse).transpose() {
+        Ok(kind) => kind,
+        Err(e) => return ChatToolResult::Error(e),
+    };
+    let since = match text("since").map(crate::api::parse_since).transpose() {
+        Ok(since) => since,
+        Err(e) => return ChatToolResult::Error(e),
+    };
+    let folder = text("folder").map(resolve_named_folder);
+    let query = JournalQuery {
+        folder: folder.map(|f| f.to_string_lossy().to_string()),
+        kind,
+        since,
+        until: None,
+        name: text("name").map(str::to_string),
+        limit: Some(input.get("max_results").and_then(|m| m.as_u64()).unwrap_or(50) as usize),
+    };
+
+    let entries = match WatchJournal::open_default().and_then(|journal| journal.query(&query)) {
+        Ok(entries) => entries,
+        Err(e) => return ChatToolResult::Error(e),
+    };
+    if entries.is_empty() {
+        return ChatToolResult::Success(
+            "No matching changes recorded. Only folders Sentinel watches are tracked.".to_string(),
+        );
+    }
+
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let when = entry.occurred_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+            let size = entry.size.map(|s| format!(" ({})", format_size(s))).unwrap_or_default();
+            match (&entry.kind, &entry.from_path) {
+                (ChangeKind::Moved, Some(from)) => {
+                    format!("{}  moved    {} -> {}{}", when, from, entry.path, size)
+                }
+                (kind, _) => format!("{}  {:<8} {}{}", when, kind.as_str(), entry.path, size),
+            }
+        })
+        .collect();
+
+    ChatToolResult::Success(format!(
+        "{} change(s), newest first:\n{}",
+        entries.len(),
+        lines.join("\n")
+    ))
+}
+
+/// Resolve "Downloads", "desktop", ... to the user's folder; other values are paths
+fn resolve_named_folder(name: &str) -> PathBuf {
+    let standard = match name.trim().to_lowercase().as_str() {
+        "downloads" => dirs::download_dir(),
+        "desktop" => dirs::desktop_dir(),
+        "documents" => dirs::document_dir(),
+        "pictures" | "photos" => dirs::picture_dir(),
+        "music" => dirs::audio_dir(),
+        "videos" | "movies" => dirs::video_dir(),
+        _ => None,
+    };
+    standard.unwrap_or_else(|| PathBuf::from(name.trim()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -850,7 +951,7 @@ mod tests {
     #[test]
     fn test_get_chat_tools() {
         let tools = get_chat_tools();
-        assert_eq!(tools.len(), 6); // 4 original + 2 terminal tools (shell, grep)
+        assert_eq!(tools.len(), 7); // 5 original + 2 terminal tools (shell, grep)
 
         // Verify tool names
         let names: Vec<&str> = tools
@@ -861,6 +962,7 @@ mod tests {
         assert!(names.contains(&"read_file"));
         assert!(names.contains(&"inspect_pattern"));
         assert!(names.contains(&"list_directory"));
+        assert!(names.contains(&"file_history"));
         assert!(names.contains(&"shell")); // Renamed from "bash" to "shell"
         assert!(names.contains(&"grep"));
     }
@@ -146,6 +146,16 @@ pub fn get_terminal_tools() -> Vec<Value> {
 /// This function only allows specific safe commands (ls, find, file, du, wc, head, tail,
 /// cat, less, grep, rg, git status, git log) and validates paths against protected directories.
 pub async fn execute_shell(input: &Value) -> Result<String, String> {
+    run_shell(input, true).await
+}
+
+/// Execute a shell command with the plain allowlist, ignoring `force` and the
+/// user's pre-approved commands (for remote clients such as MCP)
+pub async fn execute_shell_unapproved(input: &Value) -> Result<String, String> {
+    run_shell(input, false).await
+}
+
+async fn run_shell(input: &Value, honor_approvals: bool) -> Result<String, String> {
     let command = input
         .get("command")
         .and_then(|c| c.as_str())
@@ -165,14 +175,14 @@ pub async fn execute_shell(input: &Value) -> Result<String, String> {
     }
 
     // Check if force mode is requested (user approved the command)
-    let force_mode = input
-        .get("force")
-        .and_then(|f| f.as_bool())
-        .unwrap_or(false);
+    let force_mode = honor_approvals
+        && input
+            .get("force")
+            .and_then(|f| f.as_bool())
+            .unwrap_or(false);
 
     // Load shell permissions to check if command is pre-approved
-    let permissions = ShellPermissions::load();
-    let is_pre_approved = permissions.is_allowed(command);
+    let is_pre_approved = honor_approvals && ShellPermissions::load().is_allowed(command);
 
     // Create sandbox with working directory as root
     let sandbox = CommandSandbox::new(working_dir.map(|d| d.into()))
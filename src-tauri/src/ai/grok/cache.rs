@@ -1,22 +1,166 @@
 //! Content Cache System
 //!
 //! Persistent SQLite cache for document analyses.
-//! Uses content hash (SHA-256) as key so analyses survive file moves.
+//! Uses content hash (SHA-256) as key so analyses survive file moves. Video
+//! and audio are keyed by a sampled hash instead (see [`ContentCache::media_key`]).
+//!
+//! Entries are also keyed by the analyzer version (models + prompt revisions),
+//! so changing a prompt or model re-analyzes files instead of serving stale
+//! results. Old versions stay on disk until invalidated or evicted (LRU,
+//! bounded by entry count and payload size), and the cache can be exported
+//! to a portable JSON file and imported on another machine.
 
-use super::types::{AnalysisMethod, DocumentAnalysis, DocumentType};
+use super::types::{AnalysisMethod, DocumentAnalysis, DocumentType, ExtractedFields};
+use super::{explore_agent, openai_worker};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Version tag given to entries cached before analyzer versioning existed
+pub const LEGACY_VERSION: &str = "legacy";
+
+/// Format identifier written to exported cache files
+pub const EXPORT_FORMAT: &str = "sentinel-content-cache";
+
+/// Exported file layout version
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Check size limits every N writes
+const EVICTION_CHECK_INTERVAL: usize = 100;
+
+/// Bytes read from each end of a file by [`ContentCache::sample_hash`]
+const SAMPLE_BYTES: u64 = 1024 * 1024;
+
+const DOCUMENT_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS document_analysis (
+        content_hash TEXT NOT NULL,
+        analyzer_version TEXT NOT NULL DEFAULT 'legacy',
+        file_path TEXT,
+        file_name TEXT,
+        content_summary TEXT,
+        document_type TEXT,
+        key_entities TEXT,
+        suggested_name TEXT,
+        confidence REAL,
+        method TEXT,
+        analyzed_at TEXT DEFAULT CURRENT_TIMESTAMP,
+        token_cost INTEGER DEFAULT 0,
+        extracted_fields TEXT,
+        last_accessed TEXT DEFAULT CURRENT_TIMESTAMP,
+        size_bytes INTEGER DEFAULT 0,
+        PRIMARY KEY (content_hash, analyzer_version)
+    );
+"#;
+
+/// Identifies the models and prompts that produce analyses.
+/// Bump `PROMPT_VERSION` next to a prompt when changing it.
+pub fn analyzer_version() -> String {
+    format!(
+        "{}/p{}+{}/p{}",
+        explore_agent::ANALYSIS_MODEL,
+        explore_agent::PROMPT_VERSION,
+        openai_worker::WORKER_MODEL,
+        openai_worker::PROMPT_VERSION
+    )
+}
+
+/// Size bounds enforced by LRU eviction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheLimits {
+    /// Maximum number of cached analyses (all versions)
+    pub max_entries: usize,
+    /// Maximum total payload size in bytes (summaries, entities, fields)
+    pub max_bytes: u64,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 100_000,
+            max_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// Which entries to drop in [`ContentCache::invalidate`]. Conditions are combined with AND.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidationFilter {
+    /// Only entries produced by this analyzer version
+    pub analyzer_version: Option<String>,
+    /// Only entries NOT produced by the current analyzer version
+    #[serde(default)]
+    pub stale_only: bool,
+    /// Only entries analyzed at or after this date (YYYY-MM-DD or a timestamp, e.g. RFC 3339)
+    pub analyzed_after: Option<String>,
+    /// Only entries analyzed before this date (YYYY-MM-DD or a timestamp, e.g. RFC 3339)
+    pub analyzed_before: Option<String>,
+    /// Only entries of this document type (e.g. "invoice")
+    pub document_type: Option<String>,
+}
+
+/// Entry count per analyzer version
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionSummary {
+    pub analyzer_version: String,
+    pub entries: usize,
+    /// Whether this is the version the app currently produces
+    pub current: bool,
+}
+
+/// Result of importing a cache file
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Entries added to the local cache
+    pub imported: usize,
+    /// Entries already present locally (local copy kept)
+    pub skipped: usize,
+    /// Imported entries from other analyzer versions (not served until versions match)
+    pub other_versions: usize,
+}
+
+/// One analysis in an exported cache file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub content_hash: String,
+    pub analyzer_version: String,
+    pub analyzed_at: Option<String>,
+    #[serde(default)]
+    pub token_cost: i64,
+    pub analysis: DocumentAnalysis,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheExport {
+    format: String,
+    format_version: u32,
+    exported_at: String,
+    entries: Vec<CacheEntry>,
+}
 
 /// SQLite-backed content cache
 pub struct ContentCache {
     db_path: std::path::PathBuf,
+    analyzer_version: String,
+    limits: CacheLimits,
+    writes: AtomicUsize,
 }
 
 impl ContentCache {
-    /// Open or create the cache database
+    /// Open or create the cache database for the current analyzer version
     pub fn open(cache_dir: &Path) -> Result<Self, String> {
+        Self::open_with_version(cache_dir, &analyzer_version())
+    }
+
+    /// Open or create the cache database, serving entries of `version` only
+    pub fn open_with_version(cache_dir: &Path, version: &str) -> Result<Self, String> {
         std::fs::create_dir_all(cache_dir)
             .map_err(|e| format!("Failed to create cache directory: {}", e))?;
 
@@ -24,24 +168,17 @@ impl ContentCache {
 
         // Initialize database
         let conn = Self::connect(&db_path)?;
+        conn.execute_batch(DOCUMENT_TABLE_SQL)
+            .map_err(|e| format!("Failed to initialize database: {}", e))?;
+
+        Self::migrate(&conn)?;
+
         conn.execute_batch(
             r#"
-            CREATE TABLE IF NOT EXISTS document_analysis (
-                content_hash TEXT PRIMARY KEY,
-                file_path TEXT,
-                file_name TEXT,
-                content_summary TEXT,
-                document_type TEXT,
-                key_entities TEXT,
-                suggested_name TEXT,
-                confidence REAL,
-                method TEXT,
-                analyzed_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                token_cost INTEGER DEFAULT 0
-            );
-
             CREATE INDEX IF NOT EXISTS idx_file_path ON document_analysis(file_path);
             CREATE INDEX IF NOT EXISTS idx_analyzed_at ON document_analysis(analyzed_at);
+            CREATE INDEX IF NOT EXISTS idx_last_accessed ON document_analysis(last_accessed);
+            CREATE INDEX IF NOT EXISTS idx_analyzer_version ON document_analysis(analyzer_version);
 
             CREATE TABLE IF NOT EXISTS cache_stats (
                 id INTEGER PRIMARY KEY CHECK (id = 1),
@@ -57,7 +194,84 @@ impl ContentCache {
         )
         .map_err(|e| format!("Failed to initialize database: {}", e))?;
 
-        Ok(Self { db_path })
+        let cache = Self {
+            db_path,
+            analyzer_version: version.to_string(),
+            limits: CacheLimits::default(),
+            writes: AtomicUsize::new(0),
+        };
+        cache.evict_to_limits()?;
+
+        Ok(cache)
+    }
+
+    /// Use custom size limits (applied immediately)
+    pub fn with_limits(mut self, limits: CacheLimits) -> Result<Self, String> {
+        self.limits = limits;
+        self.evict_to_limits()?;
+        Ok(self)
+    }
+
+    /// Analyzer version this cache reads and writes
+    pub fn version(&self) -> &str {
+        &self.analyzer_version
+    }
+
+    /// Bring databases created by older releases up to the current schema
+    fn migrate(conn: &rusqlite::Connection) -> Result<(), String> {
+        let has_column = |name: &str| -> Result<bool, String> {
+            conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('document_analysis') WHERE name = ?",
+                [name],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n > 0)
+            .map_err(|e| format!("Failed to inspect cache schema: {}", e))
+        };
+
+        if !has_column("extracted_fields")? {
+            conn.execute(
+                "ALTER TABLE document_analysis ADD COLUMN extracted_fields TEXT",
+                [],
+            )
+            .map_err(|e| format!("Failed to migrate cache schema: {}", e))?;
+        }
+
+        // The primary key changed to (content_hash, analyzer_version), which
+        // SQLite can only do by rebuilding the table
+        if !has_column("analyzer_version")? {
+            tracing::info!("[ContentCache] Migrating cache to versioned keys");
+            conn.execute_batch(&format!(
+                r#"
+                BEGIN;
+                ALTER TABLE document_analysis RENAME TO document_analysis_old;
+                DROP INDEX IF EXISTS idx_file_path;
+                DROP INDEX IF EXISTS idx_analyzed_at;
+                {}
+                INSERT INTO document_analysis
+                    (content_hash, analyzer_version, file_path, file_name, content_summary,
+                     document_type, key_entities, suggested_name, confidence, method,
+                     analyzed_at, token_cost, extracted_fields, last_accessed, size_bytes)
+                SELECT content_hash, '{}', file_path, file_name, content_summary,
+                       document_type, key_entities, suggested_name, confidence, method,
+                       analyzed_at, token_cost, extracted_fields, analyzed_at,
+                       length(coalesce(content_summary, '')) + length(coalesce(key_entities, ''))
+                         + length(coalesce(extracted_fields, ''))
+                FROM document_analysis_old;
+                DROP TABLE document_analysis_old;
+                COMMIT;
+                "#,
+                DOCUMENT_TABLE_SQL, LEGACY_VERSION
+            ))
+            .map_err(|e| format!("Failed to migrate cache schema: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Default cache location used by the desktop app (`<app cache>/grok_cache`)
+    pub fn default_dir() -> Option<std::path::PathBuf> {
+        dirs::cache_dir().map(|p| p.join("com.sentinel.filemanager").join("grok_cache"))
     }
 
     /// Connect to the database
@@ -92,56 +306,164 @@ impl ContentCache {
         Ok(format!("{:x}", hasher.finalize()))
     }
 
+    /// Cheap fingerprint for large files: SHA-256 of the size and the first
+    /// and last MiB. Prefixed with `sample-` so it never equals a full
+    /// [`hash_file`](Self::hash_file) hash.
+    pub fn sample_hash(path: &Path) -> Result<String, String> {
+        let mut file = File::open(path)
+            .map_err(|e| format!("Failed to open file for hashing: {}", e))?;
+        let size = file
+            .metadata()
+            .map_err(|e| format!("Failed to read metadata: {}", e))?
+            .len();
+
+        let mut hasher = Sha256::new();
+        hasher.update(size.to_le_bytes());
+
+        let mut sample = Vec::new();
+        (&mut file)
+            .take(SAMPLE_BYTES)
+            .read_to_end(&mut sample)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        hasher.update(&sample);
+
+        if size > SAMPLE_BYTES {
+            sample.clear();
+            file.seek(SeekFrom::Start(size.saturating_sub(SAMPLE_BYTES).max(SAMPLE_BYTES)))
+                .map_err(|e| format!("Failed to seek file: {}", e))?;
+            file.take(SAMPLE_BYTES)
+                .read_to_end(&mut sample)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            hasher.update(&sample);
+        }
+
+        Ok(format!("sample-{:x}", hasher.finalize()))
+    }
+
+    /// Cache key for video and audio: the [`sample_hash`](Self::sample_hash)
+    /// and modification time, so media is never hashed in full
+    pub fn media_key(path: &Path) -> Result<String, String> {
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let secs = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Ok(format!("{}-{}", Self::sample_hash(path)?, secs))
+    }
+
     /// Check if a file is already analyzed (by content hash)
     pub fn get_cached(&self, path: &Path) -> Result<Option<DocumentAnalysis>, String> {
         let hash = Self::hash_file(path)?;
         self.get_by_hash(&hash)
     }
 
+    /// Look up a file's analysis without counting it as a cache hit
+    pub fn peek_cached(&self, path: &Path) -> Result<Option<DocumentAnalysis>, String> {
+        let hash = Self::hash_file(path)?;
+        let conn = self.conn()?;
+        Self::query_by_hash(&conn, &hash, &self.analyzer_version)
+    }
+
     /// Get analysis by content hash
     pub fn get_by_hash(&self, hash: &str) -> Result<Option<DocumentAnalysis>, String> {
         let conn = self.conn()?;
+        let result = Self::query_by_hash(&conn, hash, &self.analyzer_version)?;
+
+        // Update cache hit stats and recency for LRU eviction
+        if result.is_some() {
+            let _ = conn.execute(
+                "UPDATE cache_stats SET cache_hits = cache_hits + 1, last_updated = CURRENT_TIMESTAMP WHERE id = 1",
+                [],
+            );
+            let _ = conn.execute(
+                "UPDATE document_analysis SET last_accessed = CURRENT_TIMESTAMP WHERE content_hash = ? AND analyzer_version = ?",
+                [hash, &self.analyzer_version],
+            );
+        }
 
+        Ok(result)
+    }
+
+    fn query_by_hash(
+        conn: &rusqlite::Connection,
+        hash: &str,
+        version: &str,
+    ) -> Result<Option<DocumentAnalysis>, String> {
         let mut stmt = conn
             .prepare(
                 r#"
                 SELECT file_path, file_name, content_summary, document_type,
-                       key_entities, suggested_name, confidence, method
+                       key_entities, suggested_name, confidence, method, extracted_fields
                 FROM document_analysis
-                WHERE content_hash = ?
+                WHERE content_hash = ? AND analyzer_version = ?
                 "#,
             )
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
-        let result = stmt
-            .query_row([hash], |row| {
-                let entities_json: String = row.get(4)?;
-                let entities: Vec<String> =
-                    serde_json::from_str(&entities_json).unwrap_or_default();
-
-                Ok(DocumentAnalysis {
-                    file_path: row.get(0)?,
-                    file_name: row.get(1)?,
-                    content_summary: row.get(2)?,
-                    document_type: DocumentType::from_str(&row.get::<_, String>(3)?),
-                    key_entities: entities,
-                    suggested_name: row.get(5)?,
-                    confidence: row.get(6)?,
-                    method: AnalysisMethod::Cached,
-                })
-            })
+        stmt.query_row([hash, version], Self::row_to_analysis)
             .optional()
-            .map_err(|e| format!("Query failed: {}", e))?;
+            .map_err(|e| format!("Query failed: {}", e))
+    }
 
-        // Update cache hit stats
-        if result.is_some() {
-            let _ = conn.execute(
-                "UPDATE cache_stats SET cache_hits = cache_hits + 1, last_updated = CURRENT_TIMESTAMP WHERE id = 1",
-                [],
-            );
+    /// Map a row of the standard analysis columns (in SELECT order above)
+    fn row_to_analysis(row: &rusqlite::Row) -> Result<DocumentAnalysis, rusqlite::Error> {
+        let entities_json: String = row.get(4)?;
+        let entities: Vec<String> = serde_json::from_str(&entities_json).unwrap_or_default();
+
+        Ok(DocumentAnalysis {
+            file_path: row.get(0)?,
+            file_name: row.get(1)?,
+            content_summary: row.get(2)?,
+            document_type: DocumentType::from_str(&row.get::<_, String>(3)?),
+            key_entities: entities,
+            suggested_name: row.get(5)?,
+            confidence: row.get(6)?,
+            method: AnalysisMethod::Cached,
+            extracted_fields: Self::decode_fields(row.get(8)?),
+        })
+    }
+
+    fn decode_fields(json: Option<String>) -> Option<ExtractedFields> {
+        json.and_then(|j| serde_json::from_str(&j).ok())
+    }
+
+    /// Extracted fields for cached analyses whose last known path is under `folder`,
+    /// keyed by file path. Cheap lookup for rule evaluation (no hashing).
+    pub fn fields_under(
+        &self,
+        folder: &Path,
+    ) -> Result<std::collections::HashMap<String, ExtractedFields>, String> {
+        let conn = self.conn()?;
+        // Match whole path components, so `/Docs/Receipts2` is not under `/Docs/Receipts`
+        let mut prefix = folder.to_string_lossy().to_string();
+        if !prefix.ends_with(std::path::MAIN_SEPARATOR) {
+            prefix.push(std::path::MAIN_SEPARATOR);
         }
 
-        Ok(result)
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT file_path, extracted_fields
+                FROM document_analysis
+                WHERE extracted_fields IS NOT NULL
+                  AND analyzer_version = ?2
+                  AND substr(file_path, 1, length(?1)) = ?1
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let rows = stmt
+            .query_map([&prefix, &self.analyzer_version], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(|e| format!("Query failed: {}", e))?;
+
+        Ok(rows
+            .filter_map(|r| r.ok())
+            .filter_map(|(path, json)| Self::decode_fields(json).map(|f| (path, f)))
+            .collect())
     }
 
     /// Store analysis result
@@ -151,33 +473,27 @@ impl ContentCache {
         analysis: &DocumentAnalysis,
         tokens: u32,
     ) -> Result<(), String> {
-        let hash = Self::hash_file(path)?;
-        let conn = self.conn()?;
+        self.store_by_hash(&Self::hash_file(path)?, analysis, tokens)
+    }
 
-        let entities_json = serde_json::to_string(&analysis.key_entities)
-            .map_err(|e| format!("Failed to serialize entities: {}", e))?;
+    /// Store analysis result under a precomputed key (e.g. a [`media_key`](Self::media_key))
+    pub fn store_by_hash(
+        &self,
+        hash: &str,
+        analysis: &DocumentAnalysis,
+        tokens: u32,
+    ) -> Result<(), String> {
+        let conn = self.conn()?;
 
-        conn.execute(
-            r#"
-            INSERT OR REPLACE INTO document_analysis
-            (content_hash, file_path, file_name, content_summary, document_type,
-             key_entities, suggested_name, confidence, method, token_cost, analyzed_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
-            "#,
-            rusqlite::params![
-                hash,
-                analysis.file_path,
-                analysis.file_name,
-                analysis.content_summary,
-                analysis.document_type.as_str(),
-                entities_json,
-                analysis.suggested_name,
-                analysis.confidence,
-                format!("{:?}", analysis.method),
-                tokens,
-            ],
-        )
-        .map_err(|e| format!("Failed to store analysis: {}", e))?;
+        Self::insert(
+            &conn,
+            "INSERT OR REPLACE",
+            hash,
+            &self.analyzer_version,
+            analysis,
+            None,
+            tokens as i64,
+        )?;
 
         // Update stats
         let cost_cents = (tokens as f64 * 0.00035 * 100.0) as i64; // Rough estimate
@@ -194,9 +510,71 @@ impl ContentCache {
         )
         .map_err(|e| format!("Failed to update stats: {}", e))?;
 
+        if self.writes.fetch_add(1, Ordering::Relaxed) % EVICTION_CHECK_INTERVAL
+            == EVICTION_CHECK_INTERVAL - 1
+        {
+            self.evict_to_limits()?;
+        }
+
         Ok(())
     }
 
+    /// Insert one analysis row. `verb` is "INSERT OR REPLACE" or "INSERT OR IGNORE".
+    /// Returns the number of rows written.
+    fn insert(
+        conn: &rusqlite::Connection,
+        verb: &str,
+        hash: &str,
+        version: &str,
+        analysis: &DocumentAnalysis,
+        analyzed_at: Option<&str>,
+        tokens: i64,
+    ) -> Result<usize, String> {
+        let entities_json = serde_json::to_string(&analysis.key_entities)
+            .map_err(|e| format!("Failed to serialize entities: {}", e))?;
+        let fields_json = analysis
+            .extracted_fields
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| format!("Failed to serialize extracted fields: {}", e))?;
+
+        let size_bytes = analysis.content_summary.len()
+            + entities_json.len()
+            + fields_json.as_ref().map_or(0, |f| f.len());
+
+        conn.execute(
+            &format!(
+                r#"
+                {} INTO document_analysis
+                (content_hash, analyzer_version, file_path, file_name, content_summary,
+                 document_type, key_entities, suggested_name, confidence, method, token_cost,
+                 extracted_fields, size_bytes, analyzed_at, last_accessed)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                        coalesce(?, CURRENT_TIMESTAMP), CURRENT_TIMESTAMP)
+                "#,
+                verb
+            ),
+            rusqlite::params![
+                hash,
+                version,
+                analysis.file_path,
+                analysis.file_name,
+                analysis.content_summary,
+                analysis.document_type.as_str(),
+                entities_json,
+                analysis.suggested_name,
+                analysis.confidence,
+                format!("{:?}", analysis.method),
+                tokens,
+                fields_json,
+                size_bytes as i64,
+                analyzed_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to store analysis: {}", e))
+    }
+
     /// Filter paths to only those not in cache
     pub fn filter_uncached(&self, paths: &[std::path::PathBuf]) -> Result<Vec<std::path::PathBuf>, String> {
         let conn = self.conn()?;
@@ -206,8 +584,8 @@ impl ContentCache {
             if let Ok(hash) = Self::hash_file(path) {
                 let exists: bool = conn
                     .query_row(
-                        "SELECT 1 FROM document_analysis WHERE content_hash = ?",
-                        [&hash],
+                        "SELECT 1 FROM document_analysis WHERE content_hash = ? AND analyzer_version = ?",
+                        [&hash, &self.analyzer_version],
                         |_| Ok(true),
                     )
                     .unwrap_or(false);
@@ -228,6 +606,14 @@ impl ContentCache {
     pub fn get_stats(&self) -> Result<CacheStats, String> {
         let conn = self.conn()?;
 
+        let (entries, size_bytes): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM document_analysis",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to get stats: {}", e))?;
+
         conn.query_row(
             "SELECT total_files_analyzed, total_tokens_used, total_cost_cents, cache_hits FROM cache_stats WHERE id = 1",
             [],
@@ -237,6 +623,8 @@ impl ContentCache {
                     tokens_used: row.get(1)?,
                     cost_cents: row.get(2)?,
                     cache_hits: row.get(3)?,
+                    entries,
+                    size_bytes,
                 })
             },
         )
@@ -256,6 +644,259 @@ impl ContentCache {
         Ok(())
     }
 
+    /// Delete the entries matching `filter`. Returns how many were removed.
+    pub fn invalidate(&self, filter: &InvalidationFilter) -> Result<usize, String> {
+        let conn = self.conn()?;
+
+        // Dates are compared as SQLite datetimes, so "2024-05-01",
+        // "2024-05-01 10:00:00" and RFC 3339 timestamps all work
+        for date in [&filter.analyzed_after, &filter.analyzed_before].into_iter().flatten() {
+            let parsed: Option<String> = conn
+                .query_row("SELECT datetime(?)", [date], |row| row.get(0))
+                .map_err(|e| format!("Failed to parse date: {}", e))?;
+            if parsed.is_none() {
+                return Err(format!("Invalid date: {}", date));
+            }
+        }
+
+        let mut conditions = Vec::new();
+        let mut params: Vec<String> = Vec::new();
+
+        if let Some(ref version) = filter.analyzer_version {
+            conditions.push("analyzer_version = ?");
+            params.push(version.clone());
+        }
+        if filter.stale_only {
+            conditions.push("analyzer_version != ?");
+            params.push(self.analyzer_version.clone());
+        }
+        if let Some(ref after) = filter.analyzed_after {
+            conditions.push("datetime(analyzed_at) >= datetime(?)");
+            params.push(after.clone());
+        }
+        if let Some(ref before) = filter.analyzed_before {
+            conditions.push("datetime(analyzed_at) < datetime(?)");
+            params.push(before.clone());
+        }
+        if let Some(ref doc_type) = filter.document_type {
+            conditions.push("document_type = ?");
+            params.push(DocumentType::from_str(doc_type).as_str().to_string());
+        }
+
+        let sql = if conditions.is_empty() {
+            "DELETE FROM document_analysis".to_string()
+        } else {
+            format!("DELETE FROM document_analysis WHERE {}", conditions.join(" AND "))
+        };
+
+        let removed = conn
+            .execute(&sql, rusqlite::params_from_iter(params.iter()))
+            .map_err(|e| format!("Failed to invalidate cache: {}", e))?;
+
+        tracing::info!("[ContentCache] Invalidated {} entries ({:?})", removed, filter);
+        Ok(removed)
+    }
+
+    /// Entry counts per analyzer version, current version first
+    pub fn versions(&self) -> Result<Vec<VersionSummary>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT analyzer_version, COUNT(*) FROM document_analysis GROUP BY analyzer_version",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let mut versions: Vec<VersionSummary> = stmt
+            .query_map([], |row| {
+                let version: String = row.get(0)?;
+                Ok(VersionSummary {
+                    current: version == self.analyzer_version,
+                    analyzer_version: version,
+                    entries: row.get::<_, i64>(1)? as usize,
+                })
+            })
+            .map_err(|e| format!("Query failed: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        versions.sort_by(|a, b| {
+            b.current
+                .cmp(&a.current)
+                .then_with(|| a.analyzer_version.cmp(&b.analyzer_version))
+        });
+        Ok(versions)
+    }
+
+    /// Evict least recently used entries until the cache fits its limits.
+    /// Returns how many entries were removed.
+    pub fn evict_to_limits(&self) -> Result<usize, String> {
+        let mut conn = self.conn()?;
+
+        let (mut entries, mut bytes): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM document_analysis",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to measure cache: {}", e))?;
+
+        let over = |entries: i64, bytes: i64| {
+            entries as usize > self.limits.max_entries || bytes as u64 > self.limits.max_bytes
+        };
+        if !over(entries, bytes) {
+            return Ok(0);
+        }
+
+        let victims: Vec<(String, String, i64)> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT content_hash, analyzer_version, size_bytes FROM document_analysis ORDER BY last_accessed ASC, analyzed_at ASC",
+                )
+                .map_err(|e| format!("Failed to prepare query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Query failed: {}", e))?;
+
+            let mut victims = Vec::new();
+            for row in rows.filter_map(|r| r.ok()) {
+                if !over(entries, bytes) {
+                    break;
+                }
+                entries -= 1;
+                bytes -= row.2;
+                victims.push(row);
+            }
+            victims
+        };
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start eviction: {}", e))?;
+        for (hash, version, _) in &victims {
+            tx.execute(
+                "DELETE FROM document_analysis WHERE content_hash = ? AND analyzer_version = ?",
+                [hash, version],
+            )
+            .map_err(|e| format!("Failed to evict entry: {}", e))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit eviction: {}", e))?;
+
+        tracing::info!("[ContentCache] Evicted {} least recently used entries", victims.len());
+        Ok(victims.len())
+    }
+
+    /// Write the cache to a portable JSON file. Only the current analyzer
+    /// version is exported unless `all_versions` is set. Returns the entry count.
+    pub fn export(&self, path: &Path, all_versions: bool) -> Result<usize, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT file_path, file_name, content_summary, document_type,
+                       key_entities, suggested_name, confidence, method, extracted_fields,
+                       content_hash, analyzer_version, analyzed_at, token_cost
+                FROM document_analysis
+                WHERE ?1 OR analyzer_version = ?2
+                ORDER BY analyzed_at
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let entries: Vec<CacheEntry> = stmt
+            .query_map(rusqlite::params![all_versions, self.analyzer_version], |row| {
+                Ok(CacheEntry {
+                    analysis: Self::row_to_analysis(row)?,
+                    content_hash: row.get(9)?,
+                    analyzer_version: row.get(10)?,
+                    analyzed_at: row.get(11)?,
+                    token_cost: row.get::<_, Option<i64>>(12)?.unwrap_or(0),
+                })
+            })
+            .map_err(|e| format!("Query failed: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let export = CacheExport {
+            format: EXPORT_FORMAT.to_string(),
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            entries,
+        };
+
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        serde_json::to_writer(BufWriter::new(file), &export)
+            .map_err(|e| format!("Failed to write cache export: {}", e))?;
+
+        tracing::info!(
+            "[ContentCache] Exported {} entries to {}",
+            export.entries.len(),
+            path.display()
+        );
+        Ok(export.entries.len())
+    }
+
+    /// Merge a file written by [`ContentCache::export`]. Entries already
+    /// present locally are kept as they are.
+    pub fn import(&self, path: &Path) -> Result<ImportSummary, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let export: CacheExport = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Not a valid cache export: {}", e))?;
+
+        if export.format != EXPORT_FORMAT {
+            return Err(format!("Unknown cache export format: {}", export.format));
+        }
+        if export.format_version > EXPORT_FORMAT_VERSION {
+            return Err(format!(
+                "Cache export version {} is newer than supported ({})",
+                export.format_version, EXPORT_FORMAT_VERSION
+            ));
+        }
+
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start import: {}", e))?;
+
+        let mut summary = ImportSummary::default();
+        for entry in &export.entries {
+            let written = Self::insert(
+                &tx,
+                "INSERT OR IGNORE",
+                &entry.content_hash,
+                &entry.analyzer_version,
+                &entry.analysis,
+                entry.analyzed_at.as_deref(),
+                entry.token_cost,
+            )?;
+
+            if written == 0 {
+                summary.skipped += 1;
+            } else {
+                summary.imported += 1;
+                if entry.analyzer_version != self.analyzer_version {
+                    summary.other_versions += 1;
+                }
+            }
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit import: {}", e))?;
+
+        tracing::info!(
+            "[ContentCache] Imported {} entries from {} ({} skipped, {} from other versions)",
+            summary.imported,
+            path.display(),
+            summary.skipped,
+            summary.other_versions
+        );
+
+        self.evict_to_limits()?;
+        Ok(summary)
+    }
+
     /// Get count of cached analyses
     #[allow(dead_code)]
     pub fn count(&self) -> Result<usize, String> {
@@ -274,6 +915,10 @@ pub struct CacheStats {
     pub tokens_used: i64,
     pub cost_cents: i64,
     pub cache_hits: i64,
+    /// Entries currently stored (all analyzer versions)
+    pub entries: i64,
+    /// Payload size of stored entries in bytes
+    pub size_bytes: i64,
 }
 
 // Add rusqlite feature for optional
@@ -318,4 +963,297 @@ mod tests {
         let hash3 = ContentCache::hash_file(&test_file).unwrap();
         assert_ne!(hash1, hash3);
     }
+
+    fn invoice_analysis(path: &Path) -> DocumentAnalysis {
+        DocumentAnalysis {
+            file_path: path.to_string_lossy().to_string(),
+            file_name: "invoice.txt".to_string(),
+            content_summary: "Invoice from ACME".to_string(),
+            document_type: DocumentType::Invoice,
+            key_entities: vec!["ACME".to_string()],
+            suggested_name: None,
+            confidence: 0.9,
+            method: AnalysisMethod::TextExtraction,
+            extracted_fields: Some(ExtractedFields {
+                vendor: Some("ACME".to_string()),
+                total: Some(612.5),
+                currency: Some("USD".to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Write `count` files and cache an analysis for each
+    fn fill(cache: &ContentCache, dir: &Path, count: usize) -> Vec<std::path::PathBuf> {
+        (0..count)
+            .map(|i| {
+                let file = dir.join(format!("doc{}.txt", i));
+                std::fs::write(&file, format!("document {}", i)).unwrap();
+                cache.store(&file, &invoice_analysis(&file), 0).unwrap();
+                file
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extracted_fields_roundtrip() {
+        let dir = tempdir().unwrap();
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        let file = dir.path().join("invoice.txt");
+        std::fs::write(&file, "Invoice ACME total $612.50").unwrap();
+
+        cache.store(&file, &invoice_analysis(&file), 10).unwrap();
+
+        let cached = cache.peek_cached(&file).unwrap().unwrap();
+        assert_eq!(cached.extracted_fields.unwrap().total, Some(612.5));
+        // Peeking does not count as a cache hit
+        assert_eq!(cache.get_stats().unwrap().cache_hits, 0);
+
+        let by_path = cache.fields_under(dir.path()).unwrap();
+        assert_eq!(by_path.len(), 1);
+        assert!(cache.fields_under(&dir.path().join("elsewhere")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fields_under_skips_sibling_with_same_prefix() {
+        let dir = tempdir().unwrap();
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        let receipts = dir.path().join("Receipts");
+        let sibling = dir.path().join("Receipts2");
+        for (folder, name) in [(&receipts, "inside.txt"), (&sibling, "outside.txt")] {
+            std::fs::create_dir_all(folder).unwrap();
+            let file = folder.join(name);
+            std::fs::write(&file, name).unwrap();
+            cache.store(&file, &invoice_analysis(&file), 0).unwrap();
+        }
+
+        let by_path = cache.fields_under(&receipts).unwrap();
+        assert_eq!(by_path.len(), 1);
+        assert!(by_path.contains_key(&receipts.join("inside.txt").to_string_lossy().to_string()));
+        assert_eq!(cache.fields_under(dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_sample_hash_and_media_key() {
+        let dir = tempdir().unwrap();
+        let big = |name: &str, middle: u8| {
+            let path = dir.path().join(name);
+            let mut bytes = vec![0u8; 3 * SAMPLE_BYTES as usize];
+            bytes[SAMPLE_BYTES as usize + 10] = middle;
+            std::fs::write(&path, bytes).unwrap();
+            path
+        };
+        let a = big("a.mp4", 1);
+        let b = big("b.mp4", 2);
+
+        // Only the ends are sampled
+        let sample = ContentCache::sample_hash(&a).unwrap();
+        assert!(sample.starts_with("sample-"));
+        assert_eq!(sample, ContentCache::sample_hash(&b).unwrap());
+        assert_ne!(ContentCache::hash_file(&a).unwrap(), ContentCache::hash_file(&b).unwrap());
+
+        let small = dir.path().join("memo.m4a");
+        std::fs::write(&small, "short").unwrap();
+        assert_ne!(ContentCache::sample_hash(&small).unwrap(), sample);
+
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        let key = ContentCache::media_key(&a).unwrap();
+        assert!(key.starts_with(&sample));
+        cache.store_by_hash(&key, &invoice_analysis(&a), 0).unwrap();
+        assert!(cache.get_by_hash(&key).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_invalidate_by_rfc3339_date() {
+        let dir = tempdir().unwrap();
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        fill(&cache, dir.path(), 1);
+        cache
+            .conn()
+            .unwrap()
+            .execute("UPDATE document_analysis SET analyzed_at = '2024-05-01 10:00:00'", [])
+            .unwrap();
+
+        let before = InvalidationFilter {
+            analyzed_before: Some("2024-05-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&before).unwrap(), 0);
+
+        let invalid = InvalidationFilter {
+            analyzed_after: Some("last tuesday".to_string()),
+            ..Default::default()
+        };
+        assert!(cache.invalidate(&invalid).is_err());
+
+        // 12:00 at +02:00 is 10:00 UTC
+        let after = InvalidationFilter {
+            analyzed_after: Some("2024-05-01T12:00:00+02:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&after).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_migrates_old_schema() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("invoice.txt");
+        std::fs::write(&file, "Invoice").unwrap();
+        let hash = ContentCache::hash_file(&file).unwrap();
+
+        let conn = rusqlite::Connection::open(dir.path().join("content_cache.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE document_analysis (
+                content_hash TEXT PRIMARY KEY, file_path TEXT, file_name TEXT,
+                content_summary TEXT, document_type TEXT, key_entities TEXT,
+                suggested_name TEXT, confidence REAL, method TEXT,
+                analyzed_at TEXT DEFAULT CURRENT_TIMESTAMP, token_cost INTEGER DEFAULT 0
+            );",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO document_analysis VALUES (?, 'a', 'b', 'old', 'invoice', '[]', NULL, 0.9, 'Cached', CURRENT_TIMESTAMP, 0)",
+            [&hash],
+        )
+        .unwrap();
+        drop(conn);
+
+        let cache = ContentCache::open(dir.path()).unwrap();
+
+        // Old rows are kept under the legacy version but no longer served
+        assert_eq!(cache.count().unwrap(), 1);
+        assert!(cache.get_cached(&file).unwrap().is_none());
+        assert_eq!(cache.versions().unwrap()[0].analyzer_version, LEGACY_VERSION);
+
+        cache.store(&file, &invoice_analysis(&file), 0).unwrap();
+        assert!(cache.get_cached(&file).unwrap().unwrap().extracted_fields.is_some());
+        assert_eq!(cache.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_version_is_part_of_key() {
+        let dir = tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let v1 = ContentCache::open_with_version(&cache_dir, "v1").unwrap();
+        let files = fill(&v1, dir.path(), 1);
+
+        let v2 = ContentCache::open_with_version(&cache_dir, "v2").unwrap();
+        assert!(v2.get_cached(&files[0]).unwrap().is_none());
+        assert_eq!(v2.filter_uncached(&files).unwrap().len(), 1);
+        assert!(v1.get_cached(&files[0]).unwrap().is_some());
+
+        let versions = v2.versions().unwrap();
+        assert_eq!(versions.len(), 1);
+        assert!(!versions[0].current);
+    }
+
+    #[test]
+    fn test_invalidate_filters() {
+        let dir = tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        fill(&ContentCache::open_with_version(&cache_dir, "old").unwrap(), dir.path(), 2);
+        let cache = ContentCache::open_with_version(&cache_dir, "new").unwrap();
+        let photo = dir.path().join("photo.jpg");
+        std::fs::write(&photo, "jpeg").unwrap();
+        let mut analysis = invoice_analysis(&photo);
+        analysis.document_type = DocumentType::Photo;
+        cache.store(&photo, &analysis, 0).unwrap();
+        let invoice = dir.path().join("new-invoice.txt");
+        std::fs::write(&invoice, "new invoice").unwrap();
+        cache.store(&invoice, &invoice_analysis(&invoice), 0).unwrap();
+
+        let by_type = InvalidationFilter {
+            document_type: Some("photo".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&by_type).unwrap(), 1);
+
+        let future = InvalidationFilter {
+            analyzed_after: Some("2999-01-01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&future).unwrap(), 0);
+
+        let stale = InvalidationFilter {
+            stale_only: true,
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&stale).unwrap(), 2);
+        assert!(cache.get_cached(&invoice).unwrap().is_some());
+
+        let by_version = InvalidationFilter {
+            analyzer_version: Some("new".to_string()),
+            analyzed_before: Some("2999-01-01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cache.invalidate(&by_version).unwrap(), 1);
+        assert_eq!(cache.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let dir = tempdir().unwrap();
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        let files = fill(&cache, dir.path(), 5);
+
+        // Make the first file the most recently used
+        {
+            let conn = cache.conn().unwrap();
+            conn.execute("UPDATE document_analysis SET last_accessed = '2000-01-01 00:00:00'", [])
+                .unwrap();
+        }
+        assert!(cache.get_cached(&files[0]).unwrap().is_some());
+
+        let cache = cache
+            .with_limits(CacheLimits {
+                max_entries: 2,
+                max_bytes: u64::MAX,
+            })
+            .unwrap();
+        assert_eq!(cache.count().unwrap(), 2);
+        assert!(cache.get_cached(&files[0]).unwrap().is_some());
+
+        let cache = cache
+            .with_limits(CacheLimits {
+                max_entries: 100,
+                max_bytes: 1,
+            })
+            .unwrap();
+        assert_eq!(cache.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let dir = tempdir().unwrap();
+        let source = ContentCache::open(&dir.path().join("a")).unwrap();
+        let files = fill(&source, dir.path(), 3);
+        fill(&ContentCache::open_with_version(&dir.path().join("a"), "old").unwrap(), dir.path(), 1);
+
+        let export_path = dir.path().join("shared.json");
+        assert_eq!(source.export(&export_path, false).unwrap(), 3);
+
+        let target = ContentCache::open(&dir.path().join("b")).unwrap();
+        let summary = target.import(&export_path).unwrap();
+        assert_eq!(summary.imported, 3);
+        assert_eq!(summary.other_versions, 0);
+
+        let imported = target.get_cached(&files[1]).unwrap().unwrap();
+        assert_eq!(imported.extracted_fields.unwrap().vendor.as_deref(), Some("ACME"));
+
+        // Re-importing keeps the local copies; exporting everything includes old versions
+        assert_eq!(source.export(&export_path, true).unwrap(), 4);
+        let summary = target.import(&export_path).unwrap();
+        assert_eq!(summary.skipped, 3);
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.other_versions, 1);
+    }
+
+    #[test]
+    fn test_import_rejects_other_files() {
+        let dir = tempdir().unwrap();
+        let cache = ContentCache::open(&dir.path().join("cache")).unwrap();
+        let bogus = dir.path().join("bogus.json");
+        std::fs::write(&bogus, r#"{"format":"something-else","formatVersion":1,"exportedAt":"","entries":[]}"#).unwrap();
+        assert!(cache.import(&bogus).is_err());
+    }
 }
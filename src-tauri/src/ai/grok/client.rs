@@ -212,6 +212,7 @@ FOCUS ON: Company/client names, project names, people names, specific dates, dol
             suggested_name: raw.suggested_name,
             confidence: raw.confidence,
             method: AnalysisMethod::GrokVision,
+            extracted_fields: None,
         })
     }
 }
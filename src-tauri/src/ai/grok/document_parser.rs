@@ -8,11 +8,17 @@
 //! - Excel: .xlsx, .xls via calamine
 //! - Word: .docx via docx-rs
 //! - Text: .txt, .md, .csv, .json, .xml, .html (direct read)
+//! - Images and scanned PDFs: local OCR (only with the `ocr` feature)
 //!
 //! ## Strategy
 //! 1. Try text extraction first (fast, pure Rust)
-//! 2. For scanned/image PDFs, fall back to Vision API
+//! 2. For scanned PDFs and images, try local OCR when available
+//! 3. Otherwise fall back to Vision API
 
+use super::ocr;
+use super::pdf_renderer::PdfRenderer;
+use super::vision;
+use crate::services::pdf_raster::PdfRasterBackend;
 use calamine::{open_workbook, Reader, Xlsx, Xls};
 use std::path::Path;
 
@@ -22,6 +28,9 @@ const MAX_TEXT_LENGTH: usize = 500_000; // ~500KB of text
 /// Minimum text length to consider extraction successful
 const MIN_TEXT_LENGTH: usize = 50;
 
+/// Minimum OCR text length (receipts are short, but a few words is noise)
+const MIN_OCR_TEXT_LENGTH: usize = 20;
+
 /// Result of document parsing
 #[derive(Debug, Clone)]
 pub struct ParsedDocument {
@@ -29,7 +38,7 @@ pub struct ParsedDocument {
     pub text: String,
     /// Document metadata (title, author, etc.)
     pub metadata: DocumentMetadata,
-    /// Whether OCR was used (always false for pure Rust - Vision API handles OCR)
+    /// Whether local OCR was used
     #[allow(dead_code)]
     pub used_ocr: bool,
     /// Extraction method used
@@ -55,8 +64,8 @@ pub struct DocumentMetadata {
 pub enum ExtractionMethod {
     /// Native text extraction (fastest)
     NativeText,
-    /// OCR (handled by Vision API, not this module)
-    Ocr,
+    /// Local OCR (scanned PDFs and images, `ocr` feature)
+    Ocr { confidence: f32 },
     /// Simple file read (for plain text files)
     DirectRead,
     /// Failed to extract
@@ -97,6 +106,9 @@ impl DocumentParser {
             // HTML files
             Some("html") | Some("htm") => self.read_plain_text(path),
 
+            // Images - local OCR when available
+            Some(e) if vision::is_image_extension(Some(e)) => self.extract_image_ocr(path),
+
             // Unsupported - let Vision API handle it
             _ => Err(format!("Unsupported file type for text extraction: {:?}", ext)),
         }
@@ -193,6 +205,25 @@ impl DocumentParser {
                 MIN_TEXT_LENGTH,
                 path.display()
             );
+
+            // Scanned PDF - try local OCR before handing off to Vision API.
+            // The basic vector render greeks text, so there is nothing to read.
+            if ocr::is_available() {
+                match PdfRenderer::rasterize_page_blocking(path, 0).and_then(|page| {
+                    if page.backend == PdfRasterBackend::VectorBasic {
+                        return Err("only a basic vector render is available".to_string());
+                    }
+                    Self::ocr_image(&page.image, path)
+                }) {
+                    Ok(parsed) => return Ok(parsed),
+                    Err(e) => tracing::debug!(
+                        "[DocumentParser] OCR fallback failed for {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+
             return Err(format!(
                 "PDF text too short ({} chars) - likely scanned/image-based",
                 text.len()
@@ -225,6 +256,58 @@ impl DocumentParser {
         })
     }
 
+    /// Extract text from an image using local OCR
+    fn extract_image_ocr(&self, path: &Path) -> Result<ParsedDocument, String> {
+        if !ocr::is_available() {
+            return Err("Local OCR not available for images".to_string());
+        }
+
+        let image = image::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+        Self::ocr_image(&image, path)
+    }
+
+    /// Run OCR on a rendered page or image, rejecting low-confidence results
+    fn ocr_image(image: &image::DynamicImage, path: &Path) -> Result<ParsedDocument, String> {
+        Self::accept_ocr_output(ocr::recognize(image)?, path)
+    }
+
+    /// Turn OCR output into a parsed document if it is good enough to trust
+    fn accept_ocr_output(output: ocr::OcrOutput, path: &Path) -> Result<ParsedDocument, String> {
+        let text = Self::clean_text(&output.text);
+
+        if text.len() < MIN_OCR_TEXT_LENGTH || output.confidence < ocr::MIN_OCR_CONFIDENCE {
+            return Err(format!(
+                "OCR result unusable ({} chars, confidence {:.2})",
+                text.len(),
+                output.confidence
+            ));
+        }
+
+        let text = Self::truncate_text(&text);
+        let word_count = text.split_whitespace().count() as u32;
+
+        tracing::info!(
+            "[DocumentParser] OCR SUCCESS: {} chars, {} words, confidence {:.2} from {}",
+            text.len(),
+            word_count,
+            output.confidence,
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+
+        Ok(ParsedDocument {
+            text,
+            metadata: DocumentMetadata {
+                word_count: Some(word_count),
+                page_count: Some(1),
+                ..Default::default()
+            },
+            used_ocr: true,
+            method: ExtractionMethod::Ocr {
+                confidence: output.confidence,
+            },
+        })
+    }
+
     /// Extract text from XLSX using calamine
     fn extract_xlsx(&self, path: &Path) -> Result<ParsedDocument, String> {
         tracing::debug!("[DocumentParser] Extracting XLSX: {}", path.display());
@@ -487,6 +570,11 @@ impl DocumentParser {
 
     /// Check if a file type is supported for text extraction
     pub fn is_supported(ext: Option<&str>) -> bool {
+        // Images only have text to extract when local OCR is set up
+        if vision::is_image_extension(ext) {
+            return ocr::is_available();
+        }
+
         match ext {
             Some(e) => matches!(
                 e.to_lowercase().as_str(),
@@ -586,6 +674,48 @@ mod tests {
         assert!(!DocumentParser::is_supported(Some("mp4")));
     }
 
+    #[test]
+    fn test_images_need_local_ocr() {
+        assert_eq!(DocumentParser::is_supported(Some("png")), ocr::is_available());
+        assert_eq!(DocumentParser::is_supported(Some("JPG")), ocr::is_available());
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_image_without_ocr_defers_to_vision() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ocr/receipt.png");
+        let parser = DocumentParser::new();
+        assert!(parser.parse(&fixture).is_err());
+    }
+
+    #[cfg(feature = "ocr")]
+    #[test]
+    #[ignore = "needs the OCR models (SENTINEL_OCR_MODELS)"]
+    fn test_image_ocr_fixture() {
+        assert!(ocr::is_available(), "OCR models not found in {:?}", ocr::models_dir());
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ocr/receipt.png");
+        let parser = DocumentParser::new();
+        let parsed = parser.parse(&fixture).unwrap();
+
+        assert!(parsed.used_ocr);
+        assert!(matches!(parsed.method, ExtractionMethod::Ocr { confidence } if confidence >= ocr::MIN_OCR_CONFIDENCE));
+    }
+
+    #[test]
+    fn test_ocr_confidence_gating() {
+        let path = Path::new("scan.png");
+        let receipt = "ACME HARDWARE\nInvoice INV-2024-0042\nDate 2024-03-15\nHammer 12.50\nTotal $15.75";
+        let output = |text: &str, confidence: f32| ocr::OcrOutput { text: text.to_string(), confidence };
+
+        let parsed = DocumentParser::accept_ocr_output(output(receipt, 0.9), path).unwrap();
+        assert!(parsed.used_ocr);
+        assert_eq!(parsed.method, ExtractionMethod::Ocr { confidence: 0.9 });
+
+        // Low confidence and too little text both defer to the Vision API
+        assert!(DocumentParser::accept_ocr_output(output(receipt, ocr::MIN_OCR_CONFIDENCE - 0.01), path).is_err());
+        assert!(DocumentParser::accept_ocr_output(output("Total", 0.9), path).is_err());
+    }
+
     #[test]
     fn test_truncate_text() {
         let long_text = "a ".repeat(300_000);
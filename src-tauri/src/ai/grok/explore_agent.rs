@@ -4,13 +4,14 @@
 //! Each explore agent:
 //! 1. Receives a batch of files
 //! 2. Checks cache for existing analyses
-//! 3. **NEW**: Tries text extraction first (PDF, Office docs)
+//! 3. **NEW**: Tries text extraction first (PDF, Office docs, local OCR)
 //! 4. Falls back to Grok Vision for scanned/image docs
 //! 5. Returns summaries in format: "filename | summary | suggested_name"
 
 use super::cache::ContentCache;
 use super::client::GrokClient;
 use super::document_parser::{DocumentParser, ExtractionMethod, ParsedDocument};
+use super::fields;
 use super::pdf_renderer::PdfRenderer;
 use super::types::*;
 use super::vision;
@@ -21,6 +22,12 @@ use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::Semaphore;
 
+/// Model used for text analysis (part of the cache's analyzer version)
+pub(super) const ANALYSIS_MODEL: &str = "grok-4-1-fast";
+
+/// Bump when the analysis prompts change so cached analyses are re-run
+pub(super) const PROMPT_VERSION: u32 = 1;
+
 /// Explore agent for analyzing document batches
 pub struct ExploreAgent {
     client: Arc<GrokClient>,
@@ -261,7 +268,8 @@ impl ExploreAgent {
             .map_err(|e| format!("Parse task failed: {}", e))?;
 
             if let Ok(parsed) = parsed_result {
-                if parsed.text.len() >= 100 && parsed.method != ExtractionMethod::Failed {
+                let is_ocr = matches!(parsed.method, ExtractionMethod::Ocr { .. });
+                if (parsed.text.len() >= 100 || is_ocr) && parsed.method != ExtractionMethod::Failed {
                     // Successfully extracted text - analyze with Grok text API
                     let text_preview: String = parsed.text.chars().take(4000).collect();
 
@@ -272,7 +280,7 @@ impl ExploreAgent {
                         filename
                     );
 
-                    return Self::analyze_text_content_static(
+                    let (mut analysis, tokens) = Self::analyze_text_content_static(
                         path,
                         &filename,
                         &text_preview,
@@ -281,7 +289,15 @@ impl ExploreAgent {
                         cache,
                         batch_id,
                     )
-                    .await;
+                    .await?;
+
+                    if let ExtractionMethod::Ocr { confidence } = parsed.method {
+                        analysis.method = AnalysisMethod::Ocr;
+                        analysis.confidence = analysis.confidence.min(confidence);
+                        let _ = cache.store(path, &analysis, tokens);
+                    }
+
+                    return Ok((analysis, tokens));
                 }
             }
         }
@@ -353,7 +369,7 @@ Respond with ONLY this JSON format:
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Content-Type", "application/json")
             .json(&json!({
-                "model": "grok-4-1-fast",
+                "model": ANALYSIS_MODEL,
                 "messages": [{"role": "user", "content": prompt}],
                 "max_tokens": 500,
                 "temperature": 0.1
@@ -387,7 +403,8 @@ Respond with ONLY this JSON format:
             .ok_or("No response from Grok")?;
 
         // Parse JSON from response
-        let analysis = Self::parse_json_analysis_response(path, filename, &content)?;
+        let mut analysis = Self::parse_json_analysis_response(path, filename, &content)?;
+        fields::attach(&mut analysis, Some(text));
 
         // Estimate tokens: ~input/4 + output/4 + overhead
         let estimated_tokens = ((text.len() / 4) + (content.len() / 4) + 100) as u32;
@@ -459,6 +476,7 @@ Respond with ONLY this JSON format:
             confidence: 0.85,
             suggested_name: parsed.suggested_name,
             method: AnalysisMethod::TextExtraction,
+            extracted_fields: None,
         })
     }
 
@@ -489,6 +507,7 @@ Respond with ONLY this JSON format:
         // Update file path (client doesn't know the path)
         analysis.file_path = path.to_string_lossy().to_string();
         analysis.method = AnalysisMethod::GrokVision;
+        fields::attach(&mut analysis, None);
 
         // Vision API tokens estimate
         let estimated_tokens = 1500u32;
@@ -613,8 +632,8 @@ Respond with ONLY this JSON format:
         .await
         .map_err(|e| format!("Task failed: {}", e))??;
 
-        // Check if we got meaningful content
-        if parsed.text.len() < 100 {
+        // Check if we got meaningful content (OCR output is accepted even when short)
+        if parsed.text.len() < 100 && !parsed.used_ocr {
             return Err("Extracted text too short, likely scanned document".to_string());
         }
 
@@ -622,9 +641,10 @@ Respond with ONLY this JSON format:
         let content_preview = self.document_parser.get_analysis_preview(&parsed, 10_000);
 
         // Send extracted text to Grok for intelligent analysis
-        let analysis = self
+        let mut analysis = self
             .analyze_text_content_with_grok(path, filename, &content_preview, &parsed)
             .await?;
+        fields::attach(&mut analysis, Some(&content_preview));
 
         // Estimate tokens used (text is cheaper than vision)
         let tokens_used = (content_preview.len() / 4) as u32 + 500; // rough estimate
@@ -710,7 +730,7 @@ The key_entities array is CRITICAL for folder organization. Include every specif
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Content-Type", "application/json")
             .json(&json!({
-                "model": "grok-4-1-fast",
+                "model": ANALYSIS_MODEL,
                 "messages": [{"role": "user", "content": prompt}],
                 "max_tokens": 1000,
                 "temperature": 0.1
@@ -779,12 +799,17 @@ The key_entities array is CRITICAL for folder organization. Include every specif
             document_type: DocumentType::from_str(&analysis_resp.document_type),
             key_entities: analysis_resp.key_entities,
             suggested_name: analysis_resp.suggested_name,
-            confidence: if parsed.method == ExtractionMethod::NativeText {
-                0.9
+            confidence: match parsed.method {
+                ExtractionMethod::NativeText => 0.9,
+                ExtractionMethod::Ocr { confidence } => confidence.min(0.9),
+                _ => 0.7,
+            },
+            method: if parsed.used_ocr {
+                AnalysisMethod::Ocr
             } else {
-                0.7
+                AnalysisMethod::TextExtraction
             },
-            method: AnalysisMethod::TextExtraction,
+            extracted_fields: None,
         })
     }
 
@@ -813,6 +838,7 @@ The key_entities array is CRITICAL for folder organization. Include every specif
             .await?;
 
         analysis.file_path = path.to_string_lossy().to_string();
+        fields::attach(&mut analysis, None);
 
         // Store in cache
         let _ = self.cache.store(path, &analysis, estimated_tokens);
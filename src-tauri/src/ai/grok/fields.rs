@@ -0,0 +1,564 @@
+//! Structured Field Extraction
+//!
+//! Pulls typed fields (vendor, total, currency, invoice number, issue and due
+//! dates) out of invoices, receipts and statements so they can be queried by
+//! rules (`file.doc.total > 500`) and exported as CSV for expense reports.
+//!
+//! Extraction is deterministic and runs on the document text when we have it,
+//! falling back to the AI summary and key entities for Vision-only analyses.
+
+use super::types::{DocumentAnalysis, DocumentType, ExtractedFields};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Labels that introduce the grand total, strongest first
+const TOTAL_LABELS: &[&str] = &[
+    "grand total",
+    "amount due",
+    "total due",
+    "balance due",
+    "amount payable",
+    "total amount",
+    "total",
+];
+
+/// Labels that introduce the payment due date
+const DUE_LABELS: &[&str] = &["due date", "payment due", "due on", "due by", "pay by", "due:"];
+
+/// Labels that introduce the issue date
+const ISSUE_LABELS: &[&str] = &[
+    "invoice date",
+    "date of issue",
+    "issue date",
+    "issued",
+    "receipt date",
+    "statement date",
+    "date:",
+    "date",
+];
+
+/// Labels that introduce the vendor name
+const VENDOR_LABELS: &[&str] = &["vendor:", "seller:", "supplier:", "merchant:", "from:", "sold by:"];
+
+/// Header lines that are never the vendor name
+const NON_VENDOR_WORDS: &[&str] = &[
+    "invoice", "receipt", "statement", "bill to", "ship to", "tax", "page", "date", "total",
+];
+
+/// Column order for CSV export
+pub const CSV_HEADER: &str =
+    "file_path,file_name,document_type,vendor,total,currency,invoice_number,issue_date,due_date";
+
+/// Whether typed fields are extracted for this document type
+pub fn is_financial(doc_type: &DocumentType) -> bool {
+    matches!(
+        doc_type,
+        DocumentType::Invoice | DocumentType::Receipt | DocumentType::Statement
+    )
+}
+
+/// Attach extracted fields to a financial analysis.
+///
+/// `source_text` is the extracted document text when available; the summary
+/// and key entities fill in anything the text did not yield.
+pub fn attach(analysis: &mut DocumentAnalysis, source_text: Option<&str>) {
+    if !is_financial(&analysis.document_type) || analysis.extracted_fields.is_some() {
+        return;
+    }
+
+    let mut fields = source_text.map(extract_fields).unwrap_or_default();
+
+    let fallback_text = format!(
+        "{}\n{}",
+        analysis.content_summary,
+        analysis.key_entities.join("\n")
+    );
+    let fallback = extract_fields(&fallback_text);
+
+    fields.total = fields.total.or(fallback.total);
+    fields.currency = fields.currency.or(fallback.currency);
+    fields.invoice_number = fields.invoice_number.or(fallback.invoice_number);
+    fields.issue_date = fields.issue_date.or(fallback.issue_date);
+    fields.due_date = fields.due_date.or(fallback.due_date);
+    if fields.vendor.is_none() {
+        fields.vendor = vendor_from_entities(&analysis.key_entities);
+    }
+
+    if !fields.is_empty() {
+        analysis.extracted_fields = Some(fields);
+    }
+}
+
+/// Extract typed fields from document text
+pub fn extract_fields(text: &str) -> ExtractedFields {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let due_date = find_labeled(&lines, DUE_LABELS, parse_first_date);
+    let issue_date = find_labeled(&lines, ISSUE_LABELS, |line| {
+        // "Due date" lines also contain "date"
+        if contains_any(&line.to_lowercase(), DUE_LABELS) {
+            None
+        } else {
+            parse_first_date(line)
+        }
+    })
+    .or_else(|| {
+        lines
+            .iter()
+            .filter_map(|l| parse_first_date(l))
+            .find(|d| Some(d) != due_date.as_ref())
+    });
+
+    let (total, total_currency) = match find_total(&lines).or_else(|| largest_marked_amount(text)) {
+        Some((amount, currency)) => (Some(amount), currency),
+        None => (None, None),
+    };
+
+    ExtractedFields {
+        vendor: find_vendor(&lines),
+        total,
+        currency: total_currency.or_else(|| detect_currency(text)),
+        invoice_number: find_invoice_number(text),
+        issue_date,
+        due_date,
+    }
+}
+
+/// Escape a value for CSV output
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render one CSV row for an analysis (without trailing newline)
+pub fn csv_row(analysis: &DocumentAnalysis) -> String {
+    let fields = analysis.extracted_fields.clone().unwrap_or_default();
+    let opt = |v: &Option<String>| csv_escape(v.as_deref().unwrap_or(""));
+
+    [
+        csv_escape(&analysis.file_path),
+        csv_escape(&analysis.file_name),
+        analysis.document_type.as_str().to_string(),
+        opt(&fields.vendor),
+        fields.total.map(|t| format!("{:.2}", t)).unwrap_or_default(),
+        opt(&fields.currency),
+        opt(&fields.invoice_number),
+        opt(&fields.issue_date),
+        opt(&fields.due_date),
+    ]
+    .join(",")
+}
+
+/// Render a CSV document (header + rows) for a set of analyses
+pub fn to_csv(analyses: &[DocumentAnalysis]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for analysis in analyses {
+        out.push_str(&csv_row(analysis));
+        out.push('\n');
+    }
+    out
+}
+
+// ============================================================================
+// Field finders
+// ============================================================================
+
+fn contains_any(haystack: &str, needles: &[&str]) -> bool {
+    needles.iter().any(|n| haystack.contains(n))
+}
+
+/// Byte offset of a lowercase `label` in `line`. Falls back to the start of
+/// the line when lowercasing changed byte offsets (non-ASCII text).
+fn label_position(line: &str, label: &str) -> Option<usize> {
+    let lower = line.to_lowercase();
+    let pos = lower.find(label)?;
+    if lower.len() == line.len() && line.is_char_boundary(pos) {
+        Some(pos)
+    } else {
+        Some(0)
+    }
+}
+
+/// Apply `parse` to the first line carrying one of `labels` (in label order),
+/// also trying the following line for "Label:\nValue" layouts
+fn find_labeled<T>(lines: &[&str], labels: &[&str], parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    for label in labels {
+        for (i, line) in lines.iter().enumerate() {
+            let Some(pos) = label_position(line, label) else {
+                continue;
+            };
+            if let Some(value) = parse(&line[pos..]) {
+                return Some(value);
+            }
+            if let Some(value) = lines.get(i + 1).and_then(|next| parse(next)) {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+fn find_total(lines: &[&str]) -> Option<(f64, Option<String>)> {
+    for label in TOTAL_LABELS {
+        // The grand total is usually the last matching line
+        for (i, line) in lines.iter().enumerate().rev() {
+            let Some(pos) = label_position(line, label) else {
+                continue;
+            };
+            let lower = line.to_lowercase();
+            if lower.contains("subtotal") || lower.contains("sub-total") || lower.contains("sub total") {
+                continue;
+            }
+            let after = line.get(pos + label.len()..).unwrap_or(line);
+            if let Some(found) = last_amount(after) {
+                return Some(found);
+            }
+            if let Some(found) = lines.get(i + 1).and_then(|next| last_amount(next)) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn find_vendor(lines: &[&str]) -> Option<String> {
+    if let Some(vendor) = find_labeled(lines, VENDOR_LABELS, |line| {
+        let value = line.split_once(':').map(|(_, v)| v.trim()).unwrap_or("");
+        is_vendor_candidate(value).then(|| value.to_string())
+    }) {
+        return Some(vendor);
+    }
+
+    // Receipts and invoices usually open with the issuer's name
+    lines
+        .iter()
+        .take(3)
+        .find(|l| is_vendor_candidate(l))
+        .map(|l| l.to_string())
+}
+
+fn is_vendor_candidate(line: &str) -> bool {
+    let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+    let digits = line.chars().filter(|c| c.is_ascii_digit()).count();
+    let lower = line.to_lowercase();
+
+    (2..=60).contains(&line.chars().count())
+        && letters >= 2
+        && digits * 3 < letters
+        && !line.contains('@')
+        && !lower.starts_with("www.")
+        && !contains_any(&lower, NON_VENDOR_WORDS)
+}
+
+/// First key entity that looks like an organization rather than a date or amount
+fn vendor_from_entities(entities: &[String]) -> Option<String> {
+    entities
+        .iter()
+        .map(|e| e.trim())
+        .find(|e| {
+            is_vendor_candidate(e)
+                && parse_first_date(e).is_none()
+                && last_amount(e).is_none()
+        })
+        .map(|e| e.replace('-', " "))
+}
+
+fn find_invoice_number(text: &str) -> Option<String> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:invoice|inv|receipt|bill|order)[ \t]*(?:no\.?|number|num|#|id)?[ \t]*[:#.]?[ \t]*#?[ \t]*([A-Z0-9][A-Z0-9\-/]{2,30})",
+        )
+        .unwrap()
+    });
+
+    re.captures_iter(text)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .find(|v| v.chars().any(|c| c.is_ascii_digit()) && parse_first_date(v).is_none())
+        .map(|v| v.trim_end_matches(['-', '/']).to_string())
+}
+
+// ============================================================================
+// Amounts and currencies
+// ============================================================================
+
+fn amount_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(?P<pre>[$€£¥]|\b(?:USD|EUR|GBP|CAD|AUD|CHF|JPY|MXN|INR)\b)?\s?(?P<num>-?\d{1,3}(?:[,.\s]\d{3})+(?:[.,]\d{1,2})?|-?\d+(?:[.,]\d{1,2})?)\s?(?P<post>[€£]|\b(?:USD|EUR|GBP|CAD|AUD|CHF|JPY|MXN|INR)\b)?",
+        )
+        .unwrap()
+    })
+}
+
+/// Last monetary amount on a line, with its currency when marked.
+/// Bare integers without a currency marker are ignored (quantities, years).
+fn last_amount(line: &str) -> Option<(f64, Option<String>)> {
+    amount_regex()
+        .captures_iter(line)
+        .filter_map(|c| {
+            let num = c.name("num")?.as_str();
+            let marker = c.name("pre").or_else(|| c.name("post")).map(|m| m.as_str());
+            let has_decimals = num.contains(['.', ',']);
+            if marker.is_none() && !has_decimals {
+                return None;
+            }
+            Some((parse_amount(num)?, marker.and_then(currency_code)))
+        })
+        .last()
+}
+
+/// Largest amount carrying a currency marker, for documents without a total label
+fn largest_marked_amount(text: &str) -> Option<(f64, Option<String>)> {
+    amount_regex()
+        .captures_iter(text)
+        .filter_map(|c| {
+            let marker = c.name("pre").or_else(|| c.name("post"))?;
+            Some((parse_amount(c.name("num")?.as_str())?, currency_code(marker.as_str())))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Parse "1,234.56", "1.234,56", "1 234,56" or "12.50" into a number
+pub fn parse_amount(raw: &str) -> Option<f64> {
+    let cleaned: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+    let last_dot = cleaned.rfind('.');
+    let last_comma = cleaned.rfind(',');
+
+    let decimal_sep = match (last_dot, last_comma) {
+        (Some(d), Some(c)) => Some(if d > c { '.' } else { ',' }),
+        (Some(d), None) => (cleaned.len() - d - 1 <= 2).then_some('.'),
+        (None, Some(c)) => (cleaned.len() - c - 1 <= 2).then_some(','),
+        (None, None) => None,
+    };
+
+    let normalized: String = cleaned
+        .chars()
+        .filter_map(|c| match c {
+            '.' | ',' if Some(c) == decimal_sep => Some('.'),
+            '.' | ',' => None,
+            other => Some(other),
+        })
+        .collect();
+
+    normalized.parse().ok()
+}
+
+fn currency_code(marker: &str) -> Option<String> {
+    let code = match marker {
+        "$" => "USD",
+        "€" => "EUR",
+        "£" => "GBP",
+        "¥" => "JPY",
+        other => return Some(other.to_uppercase()),
+    };
+    Some(code.to_string())
+}
+
+/// Most frequent currency marker in the text
+fn detect_currency(text: &str) -> Option<String> {
+    let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for caps in amount_regex().captures_iter(text) {
+        if let Some(code) = caps
+            .name("pre")
+            .or_else(|| caps.name("post"))
+            .and_then(|m| currency_code(m.as_str()))
+        {
+            *counts.entry(code).or_default() += 1;
+        }
+    }
+    counts.into_iter().max_by_key(|(_, n)| *n).map(|(code, _)| code)
+}
+
+// ============================================================================
+// Dates
+// ============================================================================
+
+/// First date on a line, normalized to YYYY-MM-DD
+pub fn parse_first_date(line: &str) -> Option<String> {
+    static ISO: OnceLock<Regex> = OnceLock::new();
+    static NUMERIC: OnceLock<Regex> = OnceLock::new();
+    static WORDY: OnceLock<Regex> = OnceLock::new();
+
+    let iso = ISO.get_or_init(|| Regex::new(r"\b(\d{4})[-/.](\d{1,2})[-/.](\d{1,2})\b").unwrap());
+    let numeric = NUMERIC.get_or_init(|| Regex::new(r"\b(\d{1,2})([/.\-])(\d{1,2})[/.\-](\d{4})\b").unwrap());
+    let wordy = WORDY.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:(\d{1,2})(?:st|nd|rd|th)?\s+([a-z]{3,9})\.?,?\s+(\d{4})|([a-z]{3,9})\.?\s+(\d{1,2})(?:st|nd|rd|th)?,?\s+(\d{4}))\b",
+        )
+        .unwrap()
+    });
+
+    let mut candidates: Vec<(usize, NaiveDate)> = Vec::new();
+
+    for c in iso.captures_iter(line) {
+        if let Some(date) = ymd(&c[1], &c[2], &c[3]) {
+            candidates.push((c.get(0).unwrap().start(), date));
+        }
+    }
+
+    for c in numeric.captures_iter(line) {
+        let a: u32 = c[1].parse().ok()?;
+        let b: u32 = c[3].parse().ok()?;
+        // Dotted dates are day-first; slashes are month-first unless impossible
+        let (day, month) = if &c[2] == "." || a > 12 { (a, b) } else { (b, a) };
+        if let Some(date) = NaiveDate::from_ymd_opt(c[4].parse().ok()?, month, day) {
+            candidates.push((c.get(0).unwrap().start(), date));
+        }
+    }
+
+    for c in wordy.captures_iter(line) {
+        let (day, month, year) = match (c.get(1), c.get(4)) {
+            (Some(d), _) => (d.as_str(), &c[2], &c[3]),
+            (None, Some(m)) => (&c[5], m.as_str(), &c[6]),
+            _ => continue,
+        };
+        let Some(month) = month_number(month) else {
+            continue;
+        };
+        if let Some(date) = NaiveDate::from_ymd_opt(year.parse().ok()?, month, day.parse().ok()?) {
+            candidates.push((c.get(0).unwrap().start(), date));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .min_by_key(|(pos, _)| *pos)
+        .map(|(_, d)| d.format("%Y-%m-%d").to_string())
+}
+
+fn ymd(y: &str, m: &str, d: &str) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(y.parse().ok()?, m.parse().ok()?, d.parse().ok()?)
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let lower = name.to_lowercase();
+    if lower.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| lower.starts_with(m))
+        .map(|i| i as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::grok::types::AnalysisMethod;
+
+    const INVOICE: &str = "ACME Hardware Supply\n\
+        123 Main Street, Springfield\n\
+        INVOICE\n\
+        Invoice No: INV-2024-0042\n\
+        Invoice Date: March 15, 2024\n\
+        Due Date: 04/14/2024\n\
+        Hammer            2 x 12.50     25.00\n\
+        Subtotal                       $25.00\n\
+        Tax                             $2.06\n\
+        Total Due                      $27.06\n";
+
+    fn analysis(doc_type: DocumentType) -> DocumentAnalysis {
+        DocumentAnalysis {
+            file_path: "/receipts/scan.pdf".to_string(),
+            file_name: "scan.pdf".to_string(),
+            content_summary: "Receipt from Corner Cafe for EUR 14,50 on 2024-05-02".to_string(),
+            document_type: doc_type,
+            key_entities: vec!["2024-05-02".to_string(), "Corner-Cafe".to_string()],
+            suggested_name: None,
+            confidence: 0.9,
+            method: AnalysisMethod::GrokVision,
+            extracted_fields: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_invoice_fields() {
+        let fields = extract_fields(INVOICE);
+        assert_eq!(fields.vendor.as_deref(), Some("ACME Hardware Supply"));
+        assert_eq!(fields.total, Some(27.06));
+        assert_eq!(fields.currency.as_deref(), Some("USD"));
+        assert_eq!(fields.invoice_number.as_deref(), Some("INV-2024-0042"));
+        assert_eq!(fields.issue_date.as_deref(), Some("2024-03-15"));
+        assert_eq!(fields.due_date.as_deref(), Some("2024-04-14"));
+    }
+
+    #[test]
+    fn test_european_receipt() {
+        let text = "Bäckerei Müller\nBeleg-Nr. 88121\nDatum: 02.05.2024\nSUMME EUR 1.234,50\nTotal 1.234,50 €";
+        let fields = extract_fields(text);
+        assert_eq!(fields.total, Some(1234.5));
+        assert_eq!(fields.currency.as_deref(), Some("EUR"));
+        assert_eq!(fields.issue_date.as_deref(), Some("2024-05-02"));
+    }
+
+    #[test]
+    fn test_parse_amount_formats() {
+        assert_eq!(parse_amount("1,234.56"), Some(1234.56));
+        assert_eq!(parse_amount("1.234,56"), Some(1234.56));
+        assert_eq!(parse_amount("1 234,56"), Some(1234.56));
+        assert_eq!(parse_amount("12.50"), Some(12.5));
+        assert_eq!(parse_amount("1,200"), Some(1200.0));
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        assert_eq!(parse_first_date("on 2024-01-31"), Some("2024-01-31".to_string()));
+        assert_eq!(parse_first_date("31/01/2024"), Some("2024-01-31".to_string()));
+        assert_eq!(parse_first_date("1st Feb 2024"), Some("2024-02-01".to_string()));
+        assert_eq!(parse_first_date("no date here"), None);
+    }
+
+    #[test]
+    fn test_subtotal_is_not_total() {
+        let fields = extract_fields("Shop\nSubtotal $10.00\nTotal $12.00");
+        assert_eq!(fields.total, Some(12.0));
+    }
+
+    #[test]
+    fn test_attach_falls_back_to_summary() {
+        let mut a = analysis(DocumentType::Receipt);
+        attach(&mut a, None);
+        let fields = a.extracted_fields.unwrap();
+        assert_eq!(fields.total, Some(14.5));
+        assert_eq!(fields.currency.as_deref(), Some("EUR"));
+        assert_eq!(fields.issue_date.as_deref(), Some("2024-05-02"));
+        assert_eq!(fields.vendor.as_deref(), Some("Corner Cafe"));
+    }
+
+    #[test]
+    fn test_attach_skips_non_financial() {
+        let mut a = analysis(DocumentType::Photo);
+        attach(&mut a, Some(INVOICE));
+        assert!(a.extracted_fields.is_none());
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let mut a = analysis(DocumentType::Invoice);
+        a.file_name = "scan, \"final\".pdf".to_string();
+        a.extracted_fields = Some(ExtractedFields {
+            vendor: Some("ACME, Inc.".to_string()),
+            total: Some(27.0),
+            ..Default::default()
+        });
+        let csv = to_csv(&[a]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(csv.starts_with(CSV_HEADER));
+        assert!(row.contains("\"scan, \"\"final\"\".pdf\""));
+        assert!(row.contains("\"ACME, Inc.\",27.00,"));
+    }
+}
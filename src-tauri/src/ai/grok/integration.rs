@@ -7,12 +7,15 @@
 //! 2. Extract text from documents (pure Rust)
 //! 3. OpenAI GPT-5-nano workers analyze in parallel (5 files/batch, 2-20 workers)
 //! 4. Grok grok-4-1-fast summarizes outputs (temp=0.1)
-//! 5. Grok orchestrator creates folder structure + assignments
+//! 5. Video/audio: ffprobe metadata, keyframes and transcripts (media stage)
+//! 6. Grok orchestrator creates folder structure + assignments
 
-use super::cache::ContentCache;
+use super::cache::{ContentCache, ImportSummary, InvalidationFilter, VersionSummary};
 use super::client::GrokClient;
-use super::document_parser::{is_parseable, DocumentParser};
+use super::document_parser::{is_parseable, DocumentParser, ExtractionMethod, ParsedDocument};
 use super::explore_agent::{create_batches, run_parallel_explores, ExploreAgent};
+use super::fields;
+use super::media::{self, MediaAnalyzer};
 use super::openai_worker::{
     calculate_worker_count, create_file_batches, run_parallel_workers, FileContent,
 };
@@ -21,7 +24,9 @@ use super::pdf_renderer::PdfRenderer;
 use super::summarizer::GrokSummarizer;
 use super::types::*;
 use super::vision;
+use crate::services::pdf_raster::PdfRasterBackend;
 use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -78,6 +83,7 @@ impl GrokOrganizer {
     pub async fn scan_folder(&self, folder: &Path) -> Result<ScanResult, String> {
         let mut analyzable_files = Vec::new();
         let mut text_files = Vec::new();
+        let mut media_files = Vec::new();
         let mut other_files = Vec::new();
         let mut total_size = 0u64;
 
@@ -99,6 +105,8 @@ impl GrokOrganizer {
                 analyzable_files.push(path);
             } else if vision::is_text_extension(ext) {
                 text_files.push(path);
+            } else if media::is_media_extension(ext) {
+                media_files.push(path);
             } else {
                 other_files.push(path);
             }
@@ -117,15 +125,21 @@ impl GrokOrganizer {
         let estimated_cost_cents = (needs_analysis as f64 * 0.035) as u32; // ~$0.035 per doc
 
         Ok(ScanResult {
-            total_files: analyzable_files.len() + text_files.len() + other_files.len(),
+            total_files: analyzable_files.len()
+                + text_files.len()
+                + media_files.len()
+                + other_files.len(),
             analyzable_files: analyzable_files.len(),
             text_files: text_files.len(),
+            media_files: media_files.len(),
             other_files: other_files.len(),
             cached_files: cached_count,
             needs_analysis,
             total_size_bytes: total_size,
             estimated_cost_cents,
+            pdf_renderer: self.pdf_renderer.capabilities().active_pdf_backend,
             file_paths: analyzable_files,
+            media_paths: media_files,
         })
     }
 
@@ -243,6 +257,8 @@ impl GrokOrganizer {
         enum ExtractionResult {
             Cached,
             Extracted(FileContent),
+            /// Text recovered by local OCR, with its confidence
+            ExtractedOcr(FileContent, f32),
             NeedsVision(PathBuf),
             Skip,
         }
@@ -288,6 +304,22 @@ impl GrokOrganizer {
                         .ok()?;
 
                         match parsed_result {
+                            // OCR output is accepted even when short (receipts)
+                            Ok(ParsedDocument {
+                                text,
+                                method: ExtractionMethod::Ocr { confidence },
+                                ..
+                            }) => {
+                                return Some(ExtractionResult::ExtractedOcr(
+                                    FileContent {
+                                        path: path.clone(),
+                                        filename,
+                                        content: text.chars().take(2000).collect(),
+                                        extension: ext_str.unwrap_or_default(),
+                                    },
+                                    confidence,
+                                ));
+                            }
                             Ok(parsed) if parsed.text.len() >= 100 => {
                                 return Some(ExtractionResult::Extracted(FileContent {
                                     path: path.clone(),
@@ -316,6 +348,8 @@ impl GrokOrganizer {
         // Execute all extractions concurrently with progress updates
         let mut file_contents: Vec<FileContent> = Vec::new();
         let mut vision_files: Vec<PathBuf> = Vec::new();
+        let mut ocr_confidence: std::collections::HashMap<String, f32> =
+            std::collections::HashMap::new();
 
         // Use buffered stream to process results as they complete
         // Add timeout per task to prevent hanging
@@ -351,6 +385,10 @@ impl GrokOrganizer {
                 Some(ExtractionResult::Extracted(content)) => {
                     file_contents.push(content);
                 }
+                Some(ExtractionResult::ExtractedOcr(content, confidence)) => {
+                    ocr_confidence.insert(content.path.to_string_lossy().to_string(), confidence);
+                    file_contents.push(content);
+                }
                 Some(ExtractionResult::NeedsVision(path)) => {
                     vision_files.push(path);
                 }
@@ -372,8 +410,9 @@ impl GrokOrganizer {
         let cache_hits = all_file_paths.len() - file_contents.len() - vision_files.len();
 
         tracing::info!(
-            "[GrokOrganizer] Extracted text from {} files, {} need Vision API, {} cached",
+            "[GrokOrganizer] Extracted text from {} files ({} via local OCR), {} need Vision API, {} cached",
             file_contents.len(),
+            ocr_confidence.len(),
             vision_files.len(),
             cache_hits
         );
@@ -419,6 +458,12 @@ impl GrokOrganizer {
                 batch_size
             );
 
+            // Keep the extracted text for typed field extraction after analysis
+            let source_texts: std::collections::HashMap<String, String> = file_contents
+                .iter()
+                .map(|c| (c.path.to_string_lossy().to_string(), c.content.clone()))
+                .collect();
+
             let batches = create_file_batches(file_contents, batch_size);
             let worker_results = run_parallel_workers(openai_key, batches, worker_count).await;
 
@@ -453,7 +498,20 @@ impl GrokOrganizer {
             });
 
             let summarizer = GrokSummarizer::new(self.grok_api_key.clone());
-            let formatted = summarizer.format_for_orchestrator(file_analyses).await?;
+            let mut formatted = summarizer.format_for_orchestrator(file_analyses).await?;
+
+            // Analyses built on OCR text are only as reliable as the OCR;
+            // invoices, receipts and statements also get typed fields
+            for analysis in &mut formatted {
+                if let Some(&confidence) = ocr_confidence.get(&analysis.file_path) {
+                    analysis.method = AnalysisMethod::Ocr;
+                    analysis.confidence = analysis.confidence.min(confidence);
+                }
+                fields::attach(
+                    analysis,
+                    source_texts.get(&analysis.file_path).map(String::as_str),
+                );
+            }
 
             // Cache the formatted analyses
             for analysis in &formatted {
@@ -540,7 +598,13 @@ impl GrokOrganizer {
             }
         }
 
-        // 7. Load any cached analyses we skipped
+        // 7. Video and audio files
+        all_analyses.extend(
+            self.analyze_media_files(&scan.media_paths, progress_callback.clone())
+                .await,
+        );
+
+        // 8. Load any cached analyses we skipped
         for path in &scan.file_paths {
             if let Ok(Some(cached)) = self.cache.get_cached(path) {
                 // Check if we already have this file
@@ -555,7 +619,7 @@ impl GrokOrganizer {
             all_analyses.len()
         );
 
-        // 8. Run orchestrator to create plan
+        // 9. Run orchestrator to create plan
         progress_callback(AnalysisProgress {
             phase: AnalysisPhase::Planning,
             current: 0,
@@ -584,7 +648,7 @@ impl GrokOrganizer {
 
         let plan = orchestrator.create_plan(vec![explore_result]).await?;
 
-        // 9. Complete
+        // 10. Complete
         progress_callback(AnalysisProgress {
             phase: AnalysisPhase::Complete,
             current: plan.assignments.len(),
@@ -753,12 +817,19 @@ impl GrokOrganizer {
                         suggested_name: None,
                         confidence: 0.5,
                         method: AnalysisMethod::TextExtraction,
+                        extracted_fields: None,
                     });
                 }
             }
         }
 
-        // 6. Run orchestrator to create plan
+        // 6. Video and audio files
+        all_analyses.extend(
+            self.analyze_media_files(&scan.media_paths, progress_callback.clone())
+                .await,
+        );
+
+        // 7. Run orchestrator to create plan
         progress_callback(AnalysisProgress {
             phase: AnalysisPhase::Planning,
             current: 0,
@@ -784,7 +855,7 @@ impl GrokOrganizer {
 
         let plan = orchestrator.create_plan(vec![explore_result]).await?;
 
-        // 7. Complete
+        // 8. Complete
         progress_callback(AnalysisProgress {
             phase: AnalysisPhase::Complete,
             current: plan.assignments.len(),
@@ -800,6 +871,118 @@ impl GrokOrganizer {
         Ok(plan)
     }
 
+    /// Media analysis stage: probe, keyframes and transcripts for video/audio.
+    /// Failures are logged and skipped so media never blocks the plan.
+    async fn analyze_media_files<F>(
+        &self,
+        media_paths: &[PathBuf],
+        progress_callback: F,
+    ) -> Vec<DocumentAnalysis>
+    where
+        F: Fn(AnalysisProgress) + Send + Sync + Clone + 'static,
+    {
+        if media_paths.is_empty() {
+            return Vec::new();
+        }
+
+        let total = media_paths.len();
+        progress_callback(AnalysisProgress {
+            phase: AnalysisPhase::AnalyzingMedia,
+            current: 0,
+            total,
+            current_file: None,
+            message: format!("Analyzing {} video/audio files...", total),
+        });
+
+        let analyzer = Arc::new(MediaAnalyzer::new(Arc::clone(&self.client)));
+
+        // List each folder's transcripts once instead of once per media file
+        let mut transcripts: HashMap<PathBuf, Arc<Vec<PathBuf>>> = HashMap::new();
+        for path in media_paths {
+            if let Some(parent) = path.parent() {
+                transcripts
+                    .entry(parent.to_path_buf())
+                    .or_insert_with(|| Arc::new(media::list_transcripts(parent)));
+            }
+        }
+
+        // ffmpeg is CPU-heavy; keep media concurrency low
+        let semaphore = Arc::new(Semaphore::new(2));
+        let progress_counter = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = media_paths
+            .iter()
+            .cloned()
+            .map(|path| {
+                let analyzer = Arc::clone(&analyzer);
+                let folder_transcripts = path
+                    .parent()
+                    .and_then(|parent| transcripts.get(parent))
+                    .cloned()
+                    .unwrap_or_default();
+                let cache = Arc::clone(&self.cache);
+                let semaphore = Arc::clone(&semaphore);
+                let counter = Arc::clone(&progress_counter);
+                let progress_callback = progress_callback.clone();
+
+                async move {
+                    let _permit = semaphore.acquire().await.ok()?;
+
+                    // Videos are too large to hash in full before probing
+                    let key = ContentCache::media_key(&path).ok();
+                    let cached = key
+                        .as_deref()
+                        .and_then(|key| cache.get_by_hash(key).ok().flatten());
+                    let result = if let Some(cached) = cached {
+                        Ok(cached)
+                    } else {
+                        analyzer.analyze(&path, &folder_transcripts).await.map(|(analysis, tokens)| {
+                            if let Some(ref key) = key {
+                                let _ = cache.store_by_hash(key, &analysis, tokens);
+                            }
+                            analysis
+                        })
+                    };
+
+                    let current = counter.fetch_add(1, Ordering::Relaxed) + 1;
+                    progress_callback(AnalysisProgress {
+                        phase: AnalysisPhase::AnalyzingMedia,
+                        current,
+                        total,
+                        current_file: path.file_name().map(|n| n.to_string_lossy().to_string()),
+                        message: format!("Analyzing media: {}/{}", current, total),
+                    });
+
+                    match result {
+                        Ok(analysis) => Some(analysis),
+                        Err(e) => {
+                            tracing::warn!(
+                                "[GrokOrganizer] Media analysis failed for {}: {}",
+                                path.display(),
+                                e
+                            );
+                            None
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        let analyses: Vec<DocumentAnalysis> = stream::iter(tasks)
+            .buffer_unordered(4)
+            .filter_map(|a| async move { a })
+            .collect()
+            .await;
+
+        tracing::info!(
+            "[GrokOrganizer] Media stage complete: {}/{} files analyzed",
+            analyses.len(),
+            total
+        );
+
+        analyses
+    }
+
     /// Get cache statistics
     pub fn cache_stats(&self) -> Result<super::cache::CacheStats, String> {
         self.cache.get_stats()
@@ -810,6 +993,74 @@ impl GrokOrganizer {
         self.cache.clear()
     }
 
+    /// Analyzer version (models + prompt revisions) the cache is serving
+    pub fn cache_version(&self) -> &str {
+        self.cache.version()
+    }
+
+    /// Cached entry counts per analyzer version
+    pub fn cache_versions(&self) -> Result<Vec<VersionSummary>, String> {
+        self.cache.versions()
+    }
+
+    /// Drop cached analyses matching a filter
+    pub fn invalidate_cache(&self, filter: &InvalidationFilter) -> Result<usize, String> {
+        self.cache.invalidate(filter)
+    }
+
+    /// Export cached analyses to a portable JSON file
+    pub fn export_cache(&self, path: &Path, all_versions: bool) -> Result<usize, String> {
+        self.cache.export(path, all_versions)
+    }
+
+    /// Merge cached analyses exported on another machine
+    pub fn import_cache(&self, path: &Path) -> Result<ImportSummary, String> {
+        self.cache.import(path)
+    }
+
+    /// Typed fields of every cached invoice, receipt and statement in a folder, as CSV.
+    /// Returns the CSV text and the number of data rows.
+    pub fn export_fields_csv(&self, folder: &Path) -> Result<(String, usize), String> {
+        let mut rows = Vec::new();
+
+        for entry in WalkDir::new(folder)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            let ext = path.extension().and_then(|e| e.to_str());
+            if !entry.file_type().is_file()
+                || !(vision::is_analyzable_extension(ext) || vision::is_text_extension(ext))
+            {
+                continue;
+            }
+
+            let Ok(Some(mut analysis)) = self.cache.peek_cached(path) else {
+                continue;
+            };
+            if !fields::is_financial(&analysis.document_type) {
+                continue;
+            }
+            // Analyses cached before field extraction existed
+            fields::attach(&mut analysis, None);
+
+            // Report where the file is now, not where it was first analyzed
+            analysis.file_path = path.to_string_lossy().to_string();
+            analysis.file_name = entry.file_name().to_string_lossy().to_string();
+            rows.push(analysis);
+        }
+
+        rows.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        tracing::info!(
+            "[GrokOrganizer] Exported fields for {} documents in {}",
+            rows.len(),
+            folder.display()
+        );
+
+        Ok((fields::to_csv(&rows), rows.len()))
+    }
+
     /// Analyze a single file
     pub async fn analyze_single(&self, path: &Path) -> Result<DocumentAnalysis, String> {
         // Check cache first
@@ -843,13 +1094,19 @@ pub struct ScanResult {
     pub total_files: usize,
     pub analyzable_files: usize,
     pub text_files: usize,
+    /// Video and audio files (analyzed by the media stage)
+    pub media_files: usize,
     pub other_files: usize,
     pub cached_files: usize,
     pub needs_analysis: usize,
     pub total_size_bytes: u64,
     pub estimated_cost_cents: u32,
+    /// PDF backend that will render scanned pages for Vision analysis
+    pub pdf_renderer: PdfRasterBackend,
     #[serde(skip)]
     pub file_paths: Vec<PathBuf>,
+    #[serde(skip)]
+    pub media_paths: Vec<PathBuf>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,753 @@
+//! Media Analysis Module
+//!
+//! Video and audio analysis stage for the organize pipeline.
+//! For each media file:
+//! 1. Probes duration, resolution and codecs with ffprobe
+//! 2. Samples keyframes at a few timestamps with ffmpeg (video only)
+//! 3. Reads a sidecar subtitle/transcript (.srt, .vtt, .txt) when present
+//!    (each folder is listed once, see [`list_transcripts`])
+//! 4. Sends one contact sheet of the keyframes to Grok Vision
+//!
+//! Without ffmpeg on PATH the stage degrades to filename + metadata analysis.
+
+use super::client::GrokClient;
+use super::types::{AnalysisMethod, DocumentAnalysis, DocumentType};
+use image::{imageops::FilterType, DynamicImage, ImageFormat, RgbImage};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+/// Number of keyframes sampled per video
+const KEYFRAME_COUNT: usize = 3;
+
+/// Height of each keyframe in the contact sheet
+const KEYFRAME_HEIGHT: u32 = 360;
+
+/// Maximum transcript characters passed on for analysis
+const MAX_TRANSCRIPT_CHARS: usize = 3000;
+
+/// Check if a file extension is a video
+pub fn is_video_extension(ext: Option<&str>) -> bool {
+    match ext {
+        Some(e) => matches!(
+            e.to_lowercase().as_str(),
+            "mp4" | "mov" | "m4v" | "avi" | "mkv" | "webm" | "wmv" | "flv" | "mpg" | "mpeg" | "3gp"
+        ),
+        None => false,
+    }
+}
+
+/// Check if a file extension is audio
+pub fn is_audio_extension(ext: Option<&str>) -> bool {
+    match ext {
+        Some(e) => matches!(
+            e.to_lowercase().as_str(),
+            "mp3" | "m4a" | "wav" | "aac" | "flac" | "ogg" | "opus" | "aiff" | "aif" | "wma"
+        ),
+        None => false,
+    }
+}
+
+/// Check if a file extension is video or audio
+pub fn is_media_extension(ext: Option<&str>) -> bool {
+    is_video_extension(ext) || is_audio_extension(ext)
+}
+
+/// Technical metadata collected by ffprobe
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaInfo {
+    pub duration_secs: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub frame_rate: Option<f64>,
+    pub creation_time: Option<String>,
+    pub title: Option<String>,
+    /// Software that wrote the file (`encoder` / `software` tags)
+    pub encoder: Option<String>,
+    /// Handler name of the video track (e.g. "Core Media Video")
+    pub handler: Option<String>,
+}
+
+impl MediaInfo {
+    pub fn has_video(&self) -> bool {
+        self.video_codec.is_some()
+    }
+
+    /// Short human-readable description, e.g. "3m12s, 1920x1080, h264/aac"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(duration) = self.duration_secs {
+            parts.push(format_duration(duration));
+        }
+        if let (Some(w), Some(h)) = (self.width, self.height) {
+            parts.push(format!("{}x{}", w, h));
+        }
+        let codecs: Vec<&str> = [self.video_codec.as_deref(), self.audio_codec.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if !codecs.is_empty() {
+            parts.push(codecs.join("/"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Format seconds as "1h02m03s" / "3m12s" / "45s"
+pub fn format_duration(secs: f64) -> String {
+    let total = secs.round().max(0.0) as u64;
+    let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+    if h > 0 {
+        format!("{}h{:02}m{:02}s", h, m, s)
+    } else if m > 0 {
+        format!("{}m{:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+/// Whether ffprobe/ffmpeg are installed
+pub fn ffmpeg_available() -> bool {
+    Command::new("ffprobe")
+        .arg("-version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Probe a media file with ffprobe
+pub fn probe(path: &Path) -> Result<MediaInfo, String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    parse_ffprobe_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse `ffprobe -print_format json -show_format -show_streams` output
+pub fn parse_ffprobe_output(json: &str) -> Result<MediaInfo, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid ffprobe output: {}", e))?;
+
+    let mut info = MediaInfo::default();
+    let format = &value["format"];
+
+    info.duration_secs = format["duration"].as_str().and_then(|d| d.parse().ok());
+    info.creation_time = format["tags"]["creation_time"].as_str().map(String::from);
+    info.title = format["tags"]["title"].as_str().map(String::from);
+    info.encoder = ["encoder", "software", "com.apple.quicktime.software"]
+        .iter()
+        .find_map(|tag| format["tags"][tag].as_str())
+        .map(String::from);
+
+    for stream in value["streams"].as_array().into_iter().flatten() {
+        match stream["codec_type"].as_str() {
+            // Cover art in audio files shows up as a video stream
+            Some("video") if info.video_codec.is_none() && stream["disposition"]["attached_pic"] != 1 => {
+                info.video_codec = stream["codec_name"].as_str().map(String::from);
+                info.width = stream["width"].as_u64().map(|w| w as u32);
+                info.height = stream["height"].as_u64().map(|h| h as u32);
+                info.frame_rate = stream["avg_frame_rate"].as_str().and_then(parse_frame_rate);
+                info.handler = stream["tags"]["handler_name"].as_str().map(String::from);
+                if info.encoder.is_none() {
+                    info.encoder = stream["tags"]["encoder"].as_str().map(String::from);
+                }
+                if info.duration_secs.is_none() {
+                    info.duration_secs = stream["duration"].as_str().and_then(|d| d.parse().ok());
+                }
+            }
+            Some("audio") if info.audio_codec.is_none() => {
+                info.audio_codec = stream["codec_name"].as_str().map(String::from);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+/// Parse ffprobe rational frame rates like "30000/1001"
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    if den == 0.0 || num == 0.0 {
+        return None;
+    }
+    Some(num / den)
+}
+
+/// Timestamps to sample, spread over the middle of the video
+pub fn sample_timestamps(duration_secs: Option<f64>, count: usize) -> Vec<f64> {
+    match duration_secs {
+        Some(d) if d > 1.0 && count > 0 => (0..count)
+            .map(|i| d * (i as f64 + 1.0) / (count as f64 + 1.0))
+            .collect(),
+        // Unknown or very short: just the first frame
+        _ => vec![0.0],
+    }
+}
+
+/// Extract a single frame at `timestamp` as JPEG bytes
+pub fn extract_keyframe(path: &Path, timestamp: f64) -> Result<Vec<u8>, String> {
+    let output = Command::new("ffmpeg")
+        .args(["-v", "quiet", "-ss", &format!("{:.2}", timestamp), "-i"])
+        .arg(path)
+        .args([
+            "-vframes",
+            "1",
+            "-vf",
+            &format!("scale=-2:{}", KEYFRAME_HEIGHT),
+            "-f",
+            "image2pipe",
+            "-vcodec",
+            "mjpeg",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!("ffmpeg could not extract frame at {:.1}s", timestamp));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Combine keyframes side by side into one JPEG (one Vision call per video)
+pub fn compose_contact_sheet(frames: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let images: Vec<DynamicImage> = frames
+        .iter()
+        .filter_map(|f| image::load_from_memory(f).ok())
+        .map(|img| img.resize(u32::MAX, KEYFRAME_HEIGHT, FilterType::Triangle))
+        .collect();
+
+    if images.is_empty() {
+        return Err("No decodable keyframes".to_string());
+    }
+
+    let width: u32 = images.iter().map(|i| i.width()).sum();
+    let height = images.iter().map(|i| i.height()).max().unwrap_or(KEYFRAME_HEIGHT);
+    let mut sheet = RgbImage::new(width, height);
+
+    let mut x = 0;
+    for img in &images {
+        image::imageops::overlay(&mut sheet, &img.to_rgb8(), x as i64, 0);
+        x += img.width();
+    }
+
+    let mut buffer = Vec::new();
+    DynamicImage::ImageRgb8(sheet)
+        .write_to(&mut Cursor::new(&mut buffer), ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to encode contact sheet: {}", e))?;
+    Ok(buffer)
+}
+
+/// Subtitle and transcript files in a folder. List each folder once and pass
+/// the result to [`find_sidecar_transcript`] for every media file in it.
+pub fn list_transcripts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|e| e.path())
+        .filter(|p| {
+            let ext = p.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+            matches!(ext.as_deref(), Some("srt") | Some("vtt") | Some("txt"))
+        })
+        .collect()
+}
+
+/// Find the subtitle or transcript of a media file among `transcripts`
+/// (its folder's [`list_transcripts`])
+///
+/// Matches `clip.srt`, `clip.en.srt`, `clip.vtt`, `clip.txt`, `clip.transcript.txt`.
+pub fn find_sidecar_transcript(path: &Path, transcripts: &[PathBuf]) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy().to_string();
+    let prefix = format!("{}.", stem);
+
+    let mut candidates: Vec<&PathBuf> = transcripts
+        .iter()
+        .filter(|p| p.as_path() != path && p.parent() == path.parent())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().starts_with(&prefix))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    // Prefer real subtitles over plain text, then the shortest (least suffixed) name
+    candidates.sort_by_key(|p| {
+        let is_txt = p.extension().map(|e| e.eq_ignore_ascii_case("txt")).unwrap_or(false);
+        (is_txt, p.as_os_str().len())
+    });
+    candidates.into_iter().next().cloned()
+}
+
+/// Read a transcript, stripping SRT/VTT cue numbers, timings and markup
+pub fn read_transcript(path: &Path) -> Result<String, String> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read transcript: {}", e))?;
+    Ok(strip_subtitle_markup(&raw))
+}
+
+/// Turn SRT/VTT content into plain text (plain transcripts pass through)
+pub fn strip_subtitle_markup(raw: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in raw.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty()
+            || line == "WEBVTT"
+            || line.starts_with("NOTE")
+            || line.contains("-->")
+            || line.chars().all(|c| c.is_ascii_digit())
+        {
+            continue;
+        }
+
+        // Drop inline tags like <i>, <c.yellow>, {\an8}
+        let mut text = String::with_capacity(line.len());
+        let mut depth = 0;
+        for c in line.chars() {
+            match c {
+                '<' | '{' => depth += 1,
+                '>' | '}' if depth > 0 => depth -= 1,
+                _ if depth == 0 => text.push(c),
+                _ => {}
+            }
+        }
+        let text = text.trim();
+
+        // Subtitles repeat lines across cues
+        if !text.is_empty() && lines.last().map(|l| l != text).unwrap_or(true) {
+            lines.push(text.to_string());
+        }
+    }
+
+    lines.join(" ")
+}
+
+/// Classify a media file from its metadata and name
+pub fn classify(path: &Path, info: &MediaInfo) -> DocumentType {
+    if !info.has_video() {
+        // Unprobed videos keep their container type; audio-only containers
+        // (e.g. a voice memo saved as .mp4) are audio
+        let ext = path.extension().and_then(|e| e.to_str());
+        return if is_video_extension(ext) && info.audio_codec.is_none() {
+            DocumentType::Video
+        } else {
+            DocumentType::Audio
+        };
+    }
+
+    if is_screen_recording(path, info) {
+        DocumentType::ScreenRecording
+    } else {
+        DocumentType::Video
+    }
+}
+
+/// Screen recordings need a positive signal: the file name or the recorder
+/// named in the encoder/handler tags. A display resolution without audio
+/// only breaks the tie for Apple's generic capture handler, which screen
+/// recordings share with phone footage.
+fn is_screen_recording(path: &Path, info: &MediaInfo) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    const NAME_HINTS: &[&str] = &[
+        "screen recording",
+        "screen-recording",
+        "screen_recording",
+        "screenrecording",
+        "screencast",
+        "screen capture",
+        "bildschirmaufnahme",
+    ];
+    // Recorder names only count as whole words ("loom" is in "heirloom")
+    const RECORDER_NAMES: &[&str] = &[
+        "loom", "obs", "screenflow", "camtasia", "bandicam", "sharex", "cleanshot", "kap",
+    ];
+    let names_recorder = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric())
+            .any(|word| RECORDER_NAMES.contains(&word))
+    };
+
+    if NAME_HINTS.iter().any(|hint| name.contains(hint)) || names_recorder(&name) {
+        return true;
+    }
+
+    let tags: Vec<String> = [info.encoder.as_deref(), info.handler.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::to_lowercase)
+        .collect();
+    if tags
+        .iter()
+        .any(|tag| NAME_HINTS.iter().any(|hint| tag.contains(hint)) || names_recorder(tag))
+    {
+        return true;
+    }
+
+    const DISPLAY_RESOLUTIONS: &[(u32, u32)] = &[
+        (1440, 900),
+        (1680, 1050),
+        (2560, 1600),
+        (2880, 1800),
+        (3024, 1964),
+        (3456, 2234),
+        (2560, 1440),
+        (5120, 2880),
+    ];
+    let apple_capture = tags.iter().any(|tag| tag.contains("core media"));
+    match (info.width, info.height) {
+        (Some(w), Some(h)) => {
+            apple_capture
+                && info.audio_codec.is_none()
+                && DISPLAY_RESOLUTIONS.contains(&(w.max(h), w.min(h)))
+        }
+        _ => false,
+    }
+}
+
+/// Analyzes video and audio files for the organizer
+pub struct MediaAnalyzer {
+    client: Arc<GrokClient>,
+    ffmpeg_available: bool,
+}
+
+impl MediaAnalyzer {
+    pub fn new(client: Arc<GrokClient>) -> Self {
+        let ffmpeg_available = ffmpeg_available();
+        if !ffmpeg_available {
+            tracing::warn!("[MediaAnalyzer] ffmpeg/ffprobe not found - media analysis limited to metadata");
+        }
+        Self {
+            client,
+            ffmpeg_available,
+        }
+    }
+
+    /// Analyze one media file. `transcripts` is its folder's [`list_transcripts`].
+    /// Returns the analysis and estimated tokens used.
+    pub async fn analyze(
+        &self,
+        path: &Path,
+        transcripts: &[PathBuf],
+    ) -> Result<(DocumentAnalysis, u32), String> {
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        // 1. Probe metadata
+        let info = if self.ffmpeg_available {
+            let probe_path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || probe(&probe_path))
+                .await
+                .map_err(|e| format!("Probe task failed: {}", e))?
+                .unwrap_or_else(|e| {
+                    tracing::debug!("[MediaAnalyzer] Probe failed for {}: {}", filename, e);
+                    MediaInfo::default()
+                })
+        } else {
+            MediaInfo::default()
+        };
+        let document_type = classify(path, &info);
+
+        // 2. Sidecar transcript
+        let transcript = find_sidecar_transcript(path, transcripts)
+            .and_then(|p| read_transcript(&p).ok())
+            .filter(|t| !t.is_empty())
+            .map(|t| t.chars().take(MAX_TRANSCRIPT_CHARS).collect::<String>());
+
+        // 3. Keyframes (video only)
+        let contact_sheet = if self.ffmpeg_available && info.has_video() {
+            let frame_path = path.to_path_buf();
+            let timestamps = sample_timestamps(info.duration_secs, KEYFRAME_COUNT);
+            tokio::task::spawn_blocking(move || {
+                let frames: Vec<Vec<u8>> = timestamps
+                    .iter()
+                    .filter_map(|&t| extract_keyframe(&frame_path, t).ok())
+                    .collect();
+                compose_contact_sheet(&frames).map(|sheet| (sheet, frames.len()))
+            })
+            .await
+            .ok()
+            .and_then(|r| r.ok())
+        } else {
+            None
+        };
+
+        let mut facts = vec![document_type.as_str().replace('_', " ")];
+        let described = info.describe();
+        if !described.is_empty() {
+            facts.push(described);
+        }
+        if let Some(ref created) = info.creation_time {
+            facts.push(format!("recorded {}", created));
+        }
+        let facts = facts.join(", ");
+
+        // 4. Vision on the contact sheet, with metadata and transcript as context
+        if let Some((sheet, frame_count)) = contact_sheet {
+            // Some keyframes may have failed to extract
+            let mut context = format!(
+                "This image shows {} keyframes sampled from a {}.",
+                frame_count, facts
+            );
+            if let Some(ref t) = transcript {
+                context.push_str(&format!(" Transcript excerpt: {}", t));
+            }
+
+            match self
+                .client
+                .analyze_document_image(&sheet, &filename, Some(&context))
+                .await
+            {
+                Ok(mut analysis) => {
+                    analysis.file_path = path.to_string_lossy().to_string();
+                    analysis.document_type = document_type;
+                    analysis.content_summary = format!("[{}] {}", facts, analysis.content_summary);
+                    Self::add_media_entities(&mut analysis.key_entities, &info);
+                    let tokens = super::vision::estimate_image_tokens(sheet.len(), "low") + 300;
+                    return Ok((analysis, tokens));
+                }
+                Err(e) => {
+                    tracing::warn!("[MediaAnalyzer] Vision failed for {}: {}", filename, e);
+                }
+            }
+        }
+
+        // 5. No vision result: metadata (+ transcript) only
+        let mut content_summary = format!("{} ({}).", filename, facts);
+        if let Some(ref title) = info.title {
+            content_summary.push_str(&format!(" Title: {}.", title));
+        }
+        if let Some(ref t) = transcript {
+            let excerpt: String = t.chars().take(500).collect();
+            content_summary.push_str(&format!(" Transcript: {}", excerpt));
+        }
+
+        let mut key_entities = Vec::new();
+        Self::add_media_entities(&mut key_entities, &info);
+
+        Ok((
+            DocumentAnalysis {
+                file_path: path.to_string_lossy().to_string(),
+                file_name: filename,
+                content_summary,
+                document_type,
+                key_entities,
+                suggested_name: info.title.clone(),
+                confidence: if transcript.is_some() { 0.7 } else { 0.5 },
+                method: if transcript.is_some() {
+                    AnalysisMethod::TextExtraction
+                } else {
+                    AnalysisMethod::MetadataOnly
+                },
+                extracted_fields: None,
+            },
+            0,
+        ))
+    }
+
+    /// Recording date and year help the orchestrator group media by time
+    fn add_media_entities(entities: &mut Vec<String>, info: &MediaInfo) {
+        if let Some(date) = info.creation_time.as_deref().and_then(|c| c.get(..10)) {
+            if !entities.iter().any(|e| e == date) {
+                entities.push(date.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const FFPROBE_VIDEO: &str = r#"{
+        "streams": [
+            {"codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+             "avg_frame_rate": "30000/1001", "disposition": {"attached_pic": 0},
+             "tags": {"handler_name": "Core Media Video", "encoder": "H.264"}},
+            {"codec_type": "audio", "codec_name": "aac"}
+        ],
+        "format": {"duration": "192.4", "tags": {"creation_time": "2024-05-01T10:00:00.000000Z"}}
+    }"#;
+
+    const FFPROBE_AUDIO_WITH_COVER: &str = r#"{
+        "streams": [
+            {"codec_type": "audio", "codec_name": "mp3"},
+            {"codec_type": "video", "codec_name": "mjpeg", "width": 600, "height": 600,
+             "disposition": {"attached_pic": 1}}
+        ],
+        "format": {"duration": "61.0", "tags": {"title": "Weekly Sync"}}
+    }"#;
+
+    #[test]
+    fn test_parse_ffprobe_video() {
+        let info = parse_ffprobe_output(FFPROBE_VIDEO).unwrap();
+        assert_eq!(info.video_codec.as_deref(), Some("h264"));
+        assert_eq!(info.audio_codec.as_deref(), Some("aac"));
+        assert_eq!((info.width, info.height), (Some(1920), Some(1080)));
+        assert!((info.frame_rate.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(info.handler.as_deref(), Some("Core Media Video"));
+        assert_eq!(info.encoder.as_deref(), Some("H.264"));
+        assert_eq!(info.describe(), "3m12s, 1920x1080, h264/aac");
+    }
+
+    #[test]
+    fn test_parse_ffprobe_ignores_cover_art() {
+        let info = parse_ffprobe_output(FFPROBE_AUDIO_WITH_COVER).unwrap();
+        assert!(!info.has_video());
+        assert_eq!(info.title.as_deref(), Some("Weekly Sync"));
+        assert_eq!(classify(Path::new("sync.mp3"), &info), DocumentType::Audio);
+    }
+
+    #[test]
+    fn test_classify_screen_recording() {
+        let mut info = parse_ffprobe_output(FFPROBE_VIDEO).unwrap();
+        assert_eq!(classify(Path::new("holiday.mp4"), &info), DocumentType::Video);
+        assert_eq!(
+            classify(Path::new("Screen Recording 2024-05-01 at 10.00.00.mov"), &info),
+            DocumentType::ScreenRecording
+        );
+        assert_eq!(
+            classify(Path::new("Loom Recording - Onboarding.mp4"), &info),
+            DocumentType::ScreenRecording
+        );
+        assert_eq!(classify(Path::new("obs-2024-05-01.mkv"), &info), DocumentType::ScreenRecording);
+        for name in ["heirloom-tomatoes.mp4", "bloom.mov", "jobs-fair.mp4", "knobs-demo.mov"] {
+            assert_eq!(classify(Path::new(name), &info), DocumentType::Video, "{}", name);
+        }
+
+        // Recorder named in the tags
+        info.encoder = Some("OBS Studio (30.1.2)".to_string());
+        assert_eq!(classify(Path::new("clip.mp4"), &info), DocumentType::ScreenRecording);
+        info.encoder = Some("Lavf60.3.100".to_string());
+        assert_eq!(classify(Path::new("clip.mp4"), &info), DocumentType::Video);
+
+        // A silent display-sized video alone is not enough (drone footage)
+        info.width = Some(2560);
+        info.height = Some(1440);
+        info.audio_codec = None;
+        info.handler = Some("DJI.AVC".to_string());
+        assert_eq!(classify(Path::new("DJI_0042.mp4"), &info), DocumentType::Video);
+        info.width = Some(3840);
+        info.height = Some(2160);
+        assert_eq!(classify(Path::new("DJI_0042.mp4"), &info), DocumentType::Video);
+
+        // Apple's capture handler at a Retina display size without audio
+        info.width = Some(2880);
+        info.height = Some(1800);
+        info.handler = Some("Core Media Video".to_string());
+        assert_eq!(classify(Path::new("clip.mov"), &info), DocumentType::ScreenRecording);
+        info.audio_codec = Some("aac".to_string());
+        assert_eq!(classify(Path::new("clip.mov"), &info), DocumentType::Video);
+    }
+
+    #[test]
+    fn test_classify_without_probe() {
+        let info = MediaInfo::default();
+        assert_eq!(classify(Path::new("memo.m4a"), &info), DocumentType::Audio);
+        assert_eq!(classify(Path::new("clip.mkv"), &info), DocumentType::Video);
+    }
+
+    #[test]
+    fn test_sample_timestamps() {
+        assert_eq!(sample_timestamps(Some(100.0), 3), vec![25.0, 50.0, 75.0]);
+        assert_eq!(sample_timestamps(None, 3), vec![0.0]);
+        assert_eq!(sample_timestamps(Some(0.5), 3), vec![0.0]);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45.2), "45s");
+        assert_eq!(format_duration(192.4), "3m12s");
+        assert_eq!(format_duration(3723.0), "1h02m03s");
+    }
+
+    #[test]
+    fn test_strip_srt() {
+        let srt = "1\n00:00:01,000 --> 00:00:03,000\n<i>Welcome to the</i> quarterly review\n\n2\n00:00:03,500 --> 00:00:05,000\nLet's look at revenue\n";
+        assert_eq!(
+            strip_subtitle_markup(srt),
+            "Welcome to the quarterly review Let's look at revenue"
+        );
+    }
+
+    #[test]
+    fn test_strip_vtt_dedupes_lines() {
+        let vtt = "WEBVTT\n\nNOTE generated\n\n00:01.000 --> 00:02.000\nHello\n\n00:02.000 --> 00:03.000\nHello\n\n00:03.000 --> 00:04.000\n{\\an8}World\n";
+        assert_eq!(strip_subtitle_markup(vtt), "Hello World");
+    }
+
+    #[test]
+    fn test_find_sidecar_transcript() {
+        let dir = tempdir().unwrap();
+        let video = dir.path().join("talk.mp4");
+        std::fs::write(&video, b"").unwrap();
+        std::fs::write(dir.path().join("talk.txt"), "notes").unwrap();
+        std::fs::write(dir.path().join("talk.en.srt"), "1\n00:00:01,000 --> 00:00:02,000\nHi\n").unwrap();
+        std::fs::write(dir.path().join("talking-points.srt"), "other").unwrap();
+
+        let transcripts = list_transcripts(dir.path());
+        assert_eq!(transcripts.len(), 3);
+        let found = find_sidecar_transcript(&video, &transcripts).unwrap();
+        assert_eq!(found.file_name().unwrap(), "talk.en.srt");
+        assert_eq!(read_transcript(&found).unwrap(), "Hi");
+    }
+
+    #[test]
+    fn test_no_sidecar_transcript() {
+        let dir = tempdir().unwrap();
+        let audio = dir.path().join("memo.m4a");
+        std::fs::write(&audio, b"").unwrap();
+        assert!(find_sidecar_transcript(&audio, &list_transcripts(dir.path())).is_none());
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let frame = |w: u32, h: u32| {
+            let mut buf = Vec::new();
+            DynamicImage::new_rgb8(w, h)
+                .write_to(&mut Cursor::new(&mut buf), ImageFormat::Jpeg)
+                .unwrap();
+            buf
+        };
+
+        let sheet = compose_contact_sheet(&[frame(640, 360), frame(640, 360), b"junk".to_vec()]).unwrap();
+        let img = image::load_from_memory(&sheet).unwrap();
+        assert_eq!((img.width(), img.height()), (1280, KEYFRAME_HEIGHT));
+
+        assert!(compose_contact_sheet(&[]).is_err());
+    }
+
+    #[test]
+    fn test_media_extensions() {
+        assert!(is_media_extension(Some("MP4")));
+        assert!(is_media_extension(Some("m4a")));
+        assert!(is_video_extension(Some("mov")));
+        assert!(is_audio_extension(Some("flac")));
+        assert!(!is_media_extension(Some("pdf")));
+        assert!(!is_media_extension(None));
+    }
+}
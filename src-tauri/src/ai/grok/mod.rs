@@ -6,8 +6,10 @@
 //! ┌─────────────────────────────────────────────────────────────────┐
 //! │  1. SCAN: Identify all files (PDFs, images, Office docs, text) │
 //! │  2. EXTRACT: Pure Rust text extraction (pdf-extract, calamine) │
+//! │     + optional local OCR for scans (`ocr` feature)             │
 //! │  3. OPENAI WORKERS: GPT-5-nano (2-20 workers, 5 files/batch)   │
 //! │  4. GROK SUMMARIZER: grok-4-1-fast (temp=0.1)                  │
+//! │  4b. MEDIA: ffprobe + keyframes + transcripts for video/audio  │
 //! │  5. GROK ORCHESTRATOR: Creates folder structure + assignments  │
 //! │  6. EXECUTE: grok_execute_plan → WAL → Filesystem              │
 //! └─────────────────────────────────────────────────────────────────┘
@@ -16,6 +18,9 @@
 mod cache;
 mod client;
 mod explore_agent;
+mod fields;
+mod media;
+mod ocr;
 mod openai_worker;
 mod orchestrator;
 mod pdf_renderer;
@@ -28,7 +33,9 @@ pub mod integration;
 pub mod types;
 
 // Public API - used by commands/grok.rs
+pub use cache::{ContentCache, ImportSummary, InvalidationFilter, VersionSummary};
 pub use integration::{GrokOrganizer, ScanResult};
 pub use types::{
-    sanitize_filename, sanitize_folder_path, AnalysisPhase, DocumentAnalysis, OrganizationPlan,
+    sanitize_filename, sanitize_folder_path, AnalysisPhase, DocumentAnalysis, ExtractedFields,
+    OrganizationPlan,
 };
@@ -0,0 +1,271 @@
+//! Local OCR Module
+//!
+//! Optional on-device text recognition for scanned PDFs and images, used by
+//! the document parser before any Vision API call is made.
+//!
+//! Built on the pure Rust `ocrs` engine and only compiled with the `ocr`
+//! cargo feature. The engine needs two model files, looked up in
+//! `$SENTINEL_OCR_MODELS` or `<data dir>/com.sentinel.app/ocr-models`:
+//! - `text-detection.rten`
+//! - `text-recognition.rten`
+//!
+//! Without the feature (or without the models) every call returns an error
+//! and the pipeline falls through to the Vision API exactly as before.
+
+use image::DynamicImage;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// OCR results below this confidence are not trusted over the Vision API
+pub const MIN_OCR_CONFIDENCE: f32 = 0.55;
+
+/// Images are downscaled to this maximum dimension before recognition
+#[allow(dead_code)]
+const MAX_OCR_DIMENSION: u32 = 2400;
+
+/// Environment variable overriding the model directory
+const MODELS_ENV_VAR: &str = "SENTINEL_OCR_MODELS";
+
+const DETECTION_MODEL: &str = "text-detection.rten";
+const RECOGNITION_MODEL: &str = "text-recognition.rten";
+
+/// Text recognized from a single image
+#[derive(Debug, Clone)]
+pub struct OcrOutput {
+    /// Recognized text, one line per detected text line
+    pub text: String,
+    /// Estimated quality of the recognition (0.0-1.0)
+    pub confidence: f32,
+}
+
+/// Directory containing the OCR model files
+pub fn models_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var(MODELS_ENV_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::data_dir().map(|p| p.join("com.sentinel.app").join("ocr-models"))
+}
+
+/// Whether local OCR can be used (feature compiled in and models present,
+/// probed once per process)
+pub fn is_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+    *AVAILABLE.get_or_init(|| {
+        cfg!(feature = "ocr")
+            && models_dir()
+                .map(|dir| dir.join(DETECTION_MODEL).exists() && dir.join(RECOGNITION_MODEL).exists())
+                .unwrap_or(false)
+    })
+}
+
+/// Recognize text in an image
+pub fn recognize(image: &DynamicImage) -> Result<OcrOutput, String> {
+    let text = recognize_lines(image)?.join("\n");
+    let confidence = estimate_confidence(&text);
+
+    tracing::debug!(
+        "[OCR] Recognized {} chars (confidence {:.2})",
+        text.len(),
+        confidence
+    );
+
+    Ok(OcrOutput { text, confidence })
+}
+
+#[cfg(feature = "ocr")]
+fn recognize_lines(image: &DynamicImage) -> Result<Vec<String>, String> {
+    let engine = engine()?;
+
+    let image = if image.width().max(image.height()) > MAX_OCR_DIMENSION {
+        image.resize(
+            MAX_OCR_DIMENSION,
+            MAX_OCR_DIMENSION,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        image.clone()
+    };
+    let rgb = image.into_rgb8();
+
+    let source = ocrs::ImageSource::from_bytes(rgb.as_raw(), rgb.dimensions())
+        .map_err(|e| format!("Invalid image for OCR: {}", e))?;
+    let input = engine
+        .prepare_input(source)
+        .map_err(|e| format!("Failed to prepare OCR input: {}", e))?;
+    let words = engine
+        .detect_words(&input)
+        .map_err(|e| format!("OCR word detection failed: {}", e))?;
+    let lines = engine.find_text_lines(&input, &words);
+    let recognized = engine
+        .recognize_text(&input, &lines)
+        .map_err(|e| format!("OCR recognition failed: {}", e))?;
+
+    Ok(recognized
+        .iter()
+        .flatten()
+        .map(|line| line.to_string())
+        .filter(|line| !line.trim().is_empty())
+        .collect())
+}
+
+#[cfg(not(feature = "ocr"))]
+fn recognize_lines(_image: &DynamicImage) -> Result<Vec<String>, String> {
+    Err("Local OCR not available (build with the `ocr` feature)".to_string())
+}
+
+/// Lazily loaded engine shared by all parser instances
+#[cfg(feature = "ocr")]
+fn engine() -> Result<&'static ocrs::OcrEngine, String> {
+    static ENGINE: OnceLock<Result<ocrs::OcrEngine, String>> = OnceLock::new();
+
+    ENGINE
+        .get_or_init(|| {
+            let dir = models_dir().ok_or("No OCR model directory")?;
+            let load = |name: &str| {
+                let bytes = std::fs::read(dir.join(name))
+                    .map_err(|e| format!("Failed to read OCR model {}: {}", name, e))?;
+                rten::Model::load(bytes)
+                    .map_err(|e| format!("Failed to load OCR model {}: {}", name, e))
+            };
+
+            let engine = ocrs::OcrEngine::new(ocrs::OcrEngineParams {
+                detection_model: Some(load(DETECTION_MODEL)?),
+                recognition_model: Some(load(RECOGNITION_MODEL)?),
+                ..Default::default()
+            })
+            .map_err(|e| format!("Failed to initialize OCR engine: {}", e))?;
+
+            tracing::info!("[OCR] Engine initialized from {}", dir.display());
+            Ok(engine)
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+/// Estimate recognition quality from the recognized text.
+///
+/// The engine does not report per-character scores, so this scores how much
+/// of the output looks like real words and numbers rather than noise.
+pub fn estimate_confidence(text: &str) -> f32 {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if tokens.is_empty() {
+        return 0.0;
+    }
+
+    let plausible = tokens.iter().filter(|t| is_plausible_token(t)).count();
+    let token_score = plausible as f32 / tokens.len() as f32;
+
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let clean = chars
+        .iter()
+        .filter(|c| c.is_alphanumeric() || ".,:;-/$€£%#()&'@".contains(**c))
+        .count();
+    let char_score = clean as f32 / chars.len() as f32;
+
+    // Very short outputs are rarely a full document
+    let length_factor = (tokens.len() as f32 / 10.0).min(1.0);
+
+    (token_score * 0.6 + char_score * 0.4) * (0.5 + 0.5 * length_factor)
+}
+
+/// A token that is a word, number, amount or date rather than OCR noise
+fn is_plausible_token(token: &str) -> bool {
+    let core = token.trim_matches(|c: char| !c.is_alphanumeric());
+    if core.is_empty() {
+        return false;
+    }
+
+    let letters = core.chars().filter(|c| c.is_alphabetic()).count();
+    let digits = core.chars().filter(|c| c.is_ascii_digit()).count();
+    let other = core.chars().count() - letters - digits;
+
+    // Amounts and dates: "12.50", "2024-01-31", "$1,200"
+    if digits > 0 && letters == 0 {
+        return true;
+    }
+    // Words: mostly letters, allowing a hyphen or apostrophe
+    if letters > 0 && digits == 0 {
+        return other <= 1 && (letters >= 2 || core.chars().count() == 1);
+    }
+    // Codes like "INV-2024-0042" are fine, "a1#b2#c3" is not
+    other * 4 < core.chars().count() && (letters >= 2 || digits >= 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidence_clean_receipt_text() {
+        let text = "ACME HARDWARE\nInvoice INV-2024-0042\nDate 2024-03-15\nHammer 12.50\nNails 3.25\nTotal $15.75\nThank you";
+        assert!(estimate_confidence(text) > 0.8);
+    }
+
+    #[test]
+    fn test_confidence_noise() {
+        let text = "~~ |/\\ ;;# @@ %^&* ,,, ]]";
+        assert!(estimate_confidence(text) < MIN_OCR_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_confidence_empty() {
+        assert_eq!(estimate_confidence(""), 0.0);
+        assert_eq!(estimate_confidence("   \n "), 0.0);
+    }
+
+    #[test]
+    fn test_short_output_is_penalized() {
+        assert!(estimate_confidence("Total") < estimate_confidence("Total due on receipt 42.00 paid by card thank you for shopping"));
+    }
+
+    #[test]
+    fn test_plausible_tokens() {
+        assert!(is_plausible_token("Invoice"));
+        assert!(is_plausible_token("$1,200.00"));
+        assert!(is_plausible_token("2024-01-31"));
+        assert!(is_plausible_token("INV-2024"));
+        assert!(!is_plausible_token("#@!"));
+        assert!(is_plausible_token("INV-2024-0042"));
+        assert!(!is_plausible_token("a1#b2#c3"));
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_unavailable_without_feature() {
+        assert!(!is_available());
+    }
+
+    fn fixture(name: &str) -> DynamicImage {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/ocr")
+            .join(name);
+        image::open(&path).expect("fixture image")
+    }
+
+    /// Fixture tests need the model files; see the README for how to run them
+    #[cfg(feature = "ocr")]
+    #[test]
+    #[ignore = "needs the OCR models (SENTINEL_OCR_MODELS)"]
+    fn test_recognize_fixture_receipt() {
+        assert!(is_available(), "OCR models not found in {:?}", models_dir());
+        let output = recognize(&fixture("receipt.png")).unwrap();
+        assert!(output.text.to_lowercase().contains("total"));
+        assert!(output.confidence >= MIN_OCR_CONFIDENCE);
+    }
+
+    #[cfg(feature = "ocr")]
+    #[test]
+    #[ignore = "needs the OCR models (SENTINEL_OCR_MODELS)"]
+    fn test_recognize_fixture_blank_page() {
+        assert!(is_available(), "OCR models not found in {:?}", models_dir());
+        let output = recognize(&fixture("blank.png")).unwrap();
+        assert!(output.confidence < MIN_OCR_CONFIDENCE);
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_fixture_without_feature() {
+        assert!(recognize(&fixture("receipt.png")).is_err());
+    }
+}
@@ -43,6 +43,12 @@ pub struct FileContent {
     pub extension: String,
 }
 
+/// Model used by the workers (part of the cache's analyzer version)
+pub(super) const WORKER_MODEL: &str = "gpt-5-nano-2025-08-07";
+
+/// Bump when the worker prompt changes so cached analyses are re-run
+pub(super) const PROMPT_VERSION: u32 = 1;
+
 /// OpenAI worker for parallel file analysis
 pub struct OpenAIWorker {
     client: Client,
@@ -59,7 +65,7 @@ impl OpenAIWorker {
                 .build()
                 .expect("Failed to create HTTP client"),
             api_key,
-            model: "gpt-5-nano-2025-08-07".to_string(),
+            model: WORKER_MODEL.to_string(),
         }
     }
 
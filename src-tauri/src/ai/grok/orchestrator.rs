@@ -499,6 +499,7 @@ Output ONLY valid JSON. No markdown, no explanation, no code blocks."#,
                 })
                 .collect(),
             unassigned_files: raw.unassigned_files,
+            baseline: None,
         })
     }
 }
@@ -169,6 +169,7 @@ Return ONLY the JSON array, no other text."#,
                 suggested_name: f.suggested_name,
                 confidence: f.confidence.unwrap_or(0.85),
                 method: AnalysisMethod::TextExtraction,
+                extracted_fields: None,
             })
             .collect())
     }
@@ -200,6 +201,7 @@ Return ONLY the JSON array, no other text."#,
                     suggested_name,
                     confidence: 0.85,
                     method: AnalysisMethod::TextExtraction,
+                    extracted_fields: None,
                 }
             })
             .collect()
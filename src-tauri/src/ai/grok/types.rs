@@ -30,6 +30,40 @@ pub struct DocumentAnalysis {
 
     /// Analysis method used
     pub method: AnalysisMethod,
+
+    /// Typed fields for invoices, receipts and statements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extracted_fields: Option<ExtractedFields>,
+}
+
+/// Typed fields extracted from financial documents (invoices, receipts, statements)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedFields {
+    /// Issuing company or store
+    pub vendor: Option<String>,
+    /// Grand total / amount due
+    pub total: Option<f64>,
+    /// ISO 4217 currency code (e.g. "USD")
+    pub currency: Option<String>,
+    /// Invoice or receipt number
+    pub invoice_number: Option<String>,
+    /// Issue date (YYYY-MM-DD)
+    pub issue_date: Option<String>,
+    /// Payment due date (YYYY-MM-DD)
+    pub due_date: Option<String>,
+}
+
+impl ExtractedFields {
+    /// True when no field could be extracted
+    pub fn is_empty(&self) -> bool {
+        self.vendor.is_none()
+            && self.total.is_none()
+            && self.currency.is_none()
+            && self.invoice_number.is_none()
+            && self.issue_date.is_none()
+            && self.due_date.is_none()
+    }
 }
 
 /// Document type classification
@@ -55,6 +89,9 @@ pub enum DocumentType {
     Photo,
     Diagram,
     Drawing,
+    Video,
+    Audio,
+    ScreenRecording,
     Unknown,
 }
 
@@ -80,6 +117,9 @@ impl DocumentType {
             Self::Photo => "photo",
             Self::Diagram => "diagram",
             Self::Drawing => "drawing",
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::ScreenRecording => "screen_recording",
             Self::Unknown => "unknown",
         }
     }
@@ -105,6 +145,9 @@ impl DocumentType {
             "photo" | "image" => Self::Photo,
             "diagram" => Self::Diagram,
             "drawing" => Self::Drawing,
+            "video" | "movie" => Self::Video,
+            "audio" | "recording" | "podcast" => Self::Audio,
+            "screen_recording" | "screen recording" | "screencast" => Self::ScreenRecording,
             _ => Self::Unknown,
         }
     }
@@ -270,6 +313,10 @@ pub struct OrganizationPlan {
     pub folder_structure: Vec<PlannedFolder>,
     pub assignments: Vec<FolderAssignment>,
     pub unassigned_files: Vec<String>,
+    /// The folder as it was when the plan was made, for re-basing before
+    /// execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<crate::jobs::PlanBaseline>,
 }
 
 /// A folder in the planned structure
@@ -343,6 +390,7 @@ pub enum AnalysisPhase {
     CheckingCache,
     RenderingPdf,
     AnalyzingContent,
+    AnalyzingMedia,
     Aggregating,
     Planning,
     Complete,
@@ -389,6 +437,14 @@ mod tests {
         assert_eq!(result, "Report-2024.pdf");
     }
 
+    #[test]
+    fn test_media_document_types_round_trip() {
+        for doc_type in [DocumentType::Video, DocumentType::Audio, DocumentType::ScreenRecording] {
+            assert_eq!(DocumentType::from_str(doc_type.as_str()), doc_type);
+        }
+        assert_eq!(DocumentType::from_str("Screencast"), DocumentType::ScreenRecording);
+    }
+
     #[test]
     fn test_sanitize_folder_path() {
         let result = sanitize_folder_path("Clients/Acme Corp/2024 Q1/Invoices");
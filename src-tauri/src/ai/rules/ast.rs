@@ -89,12 +89,63 @@ pub enum Field {
     FileMimeType,
     /// Whether file is hidden: file.isHidden
     FileIsHidden,
+    /// Typed field extracted from invoices, receipts and statements: file.doc.total
+    Doc(DocField),
+}
+
+/// Typed document fields available under `file.doc.*`.
+/// Only set for financial documents that have been analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DocField {
+    /// Issuing company or store: file.doc.vendor
+    Vendor,
+    /// Grand total / amount due: file.doc.total
+    Total,
+    /// ISO currency code: file.doc.currency
+    Currency,
+    /// Invoice or receipt number: file.doc.invoiceNumber
+    InvoiceNumber,
+    /// Issue date (YYYY-MM-DD): file.doc.issueDate
+    IssueDate,
+    /// Payment due date (YYYY-MM-DD): file.doc.dueDate
+    DueDate,
+}
+
+impl DocField {
+    /// Parse document field from string identifier.
+    /// Supports both camelCase and snake_case variants.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "vendor" | "merchant" => Some(DocField::Vendor),
+            "total" | "amount" => Some(DocField::Total),
+            "currency" => Some(DocField::Currency),
+            "invoicenumber" | "invoice_number" | "number" => Some(DocField::InvoiceNumber),
+            "issuedate" | "issue_date" | "date" => Some(DocField::IssueDate),
+            "duedate" | "due_date" => Some(DocField::DueDate),
+            _ => None,
+        }
+    }
+
+    /// Get the canonical name for this field (including the `doc.` prefix)
+    pub fn canonical_name(&self) -> &'static str {
+        match self {
+            DocField::Vendor => "doc.vendor",
+            DocField::Total => "doc.total",
+            DocField::Currency => "doc.currency",
+            DocField::InvoiceNumber => "doc.invoiceNumber",
+            DocField::IssueDate => "doc.issueDate",
+            DocField::DueDate => "doc.dueDate",
+        }
+    }
 }
 
 impl Field {
     /// Parse field from string identifier.
     /// Supports both camelCase and snake_case variants.
     pub fn from_str(s: &str) -> Option<Self> {
+        if let Some(doc_field) = s.strip_prefix("doc.") {
+            return DocField::from_str(doc_field).map(Field::Doc);
+        }
         match s.to_lowercase().as_str() {
             "name" | "filename" => Some(Field::FileName),
             "ext" | "extension" => Some(Field::FileExt),
@@ -119,6 +170,7 @@ impl Field {
             Field::FileCreatedAt => "createdAt",
             Field::FileMimeType => "mimeType",
             Field::FileIsHidden => "isHidden",
+            Field::Doc(doc_field) => doc_field.canonical_name(),
         }
     }
 }
@@ -256,6 +308,12 @@ mod tests {
         assert_eq!(Field::from_str("modified_at"), Some(Field::FileModifiedAt));
         assert_eq!(Field::from_str("isHidden"), Some(Field::FileIsHidden));
         assert_eq!(Field::from_str("unknown"), None);
+        assert_eq!(Field::from_str("doc.total"), Some(Field::Doc(DocField::Total)));
+        assert_eq!(
+            Field::from_str("doc.due_date"),
+            Some(Field::Doc(DocField::DueDate))
+        );
+        assert_eq!(Field::from_str("doc.unknown"), None);
     }
 
     #[test]
@@ -4,6 +4,9 @@
 //! to determine if they match the rule criteria.
 
 use super::ast::*;
+use crate::ai::grok::ExtractedFields;
+use crate::scan::ignore::{enclosing_repo, IgnoreRules};
+use crate::scan::ScanEntry;
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
@@ -52,54 +55,40 @@ pub struct VirtualFile {
     pub is_hidden: bool,
     /// Whether this is a directory
     pub is_directory: bool,
+    /// Typed fields from a cached invoice/receipt/statement analysis
+    pub doc: Option<ExtractedFields>,
 }
 
 impl VirtualFile {
-    /// Create a VirtualFile from a path
-    pub fn from_path(path: &Path) -> std::io::Result<Self> {
-        let metadata = std::fs::metadata(path)?;
-        let name = path
+    /// Create a VirtualFile from a scan snapshot entry (no extra stat)
+    pub fn from_scan_entry(entry: &ScanEntry) -> Self {
+        let name = entry
+            .path
             .file_stem()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_default();
-        let ext = path
+        let ext = entry
+            .path
             .extension()
             .map(|s| s.to_string_lossy().to_lowercase());
-
-        let modified_at = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_millis() as i64);
-
-        let created_at = metadata
-            .created()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_millis() as i64);
-
         let mime_type = ext.as_ref().and_then(|e| {
             mime_guess::from_ext(e)
                 .first()
                 .map(|m| m.to_string())
         });
 
-        let file_name = path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        Ok(Self {
+        Self {
             name,
             ext,
-            size: metadata.len(),
-            path: path.to_string_lossy().to_string(),
-            modified_at,
-            created_at,
+            size: entry.size,
+            path: entry.path.to_string_lossy().to_string(),
+            modified_at: entry.modified_at,
+            created_at: entry.created_at,
             mime_type,
-            is_hidden: file_name.starts_with('.'),
-            is_directory: metadata.is_dir(),
-        })
+            is_hidden: entry.name.starts_with('.'),
+            is_directory: entry.is_dir(),
+            doc: None,
+        }
     }
 
     /// Create a VirtualFile from raw data (for testing or VFS)
@@ -124,8 +113,15 @@ impl VirtualFile {
             mime_type,
             is_hidden,
             is_directory,
+            doc: None,
         }
     }
+
+    /// Attach typed document fields (enables `file.doc.*` rules)
+    pub fn with_doc(mut self, doc: ExtractedFields) -> Self {
+        self.doc = Some(doc);
+        self
+    }
 }
 
 /// Vector index for semantic similarity queries.
@@ -207,12 +203,40 @@ impl VectorIndex for SimpleVectorIndex {
 /// Rule evaluator that matches files against rule expressions.
 pub struct RuleEvaluator<'a, V: VectorIndex> {
     vector_index: &'a V,
+    /// Files these rules (or a git checkout) cover never match
+    ignore_rules: Option<&'a IgnoreRules>,
 }
 
 impl<'a, V: VectorIndex> RuleEvaluator<'a, V> {
     /// Create a new rule evaluator with the given vector index
     pub fn new(vector_index: &'a V) -> Self {
-        Self { vector_index }
+        Self {
+            vector_index,
+            ignore_rules: None,
+        }
+    }
+
+    /// Exclude ignored files and files inside git checkouts from [`RuleEvaluator::matches`]
+    pub fn with_ignore_rules(mut self, rules: &'a IgnoreRules) -> Self {
+        self.ignore_rules = Some(rules);
+        self
+    }
+
+    /// Whether a file is out of bounds for organizing
+    pub fn is_excluded(&self, file: &VirtualFile) -> bool {
+        let Some(rules) = self.ignore_rules else {
+            return false;
+        };
+        let path = Path::new(&file.path);
+        rules.is_ignored(path, file.is_directory) || enclosing_repo(path, rules.root()).is_some()
+    }
+
+    /// Evaluate an expression against a file, never matching excluded files
+    pub fn matches(&self, expr: &Expression, file: &VirtualFile) -> Result<bool, RuleError> {
+        if self.is_excluded(file) {
+            return Ok(false);
+        }
+        self.evaluate(expr, file)
     }
 
     /// Evaluate an expression against a file
@@ -376,6 +400,20 @@ impl<'a, V: VectorIndex> RuleEvaluator<'a, V> {
                 .map(Value::String)
                 .unwrap_or(Value::Null),
             Field::FileIsHidden => Value::Boolean(file.is_hidden),
+            Field::Doc(doc_field) => {
+                let Some(doc) = file.doc.as_ref() else {
+                    return Value::Null;
+                };
+                let text = |v: &Option<String>| v.clone().map(Value::String).unwrap_or(Value::Null);
+                match doc_field {
+                    DocField::Vendor => text(&doc.vendor),
+                    DocField::Total => doc.total.map(Value::Number).unwrap_or(Value::Null),
+                    DocField::Currency => text(&doc.currency),
+                    DocField::InvoiceNumber => text(&doc.invoice_number),
+                    DocField::IssueDate => text(&doc.issue_date),
+                    DocField::DueDate => text(&doc.due_date),
+                }
+            }
         }
     }
 
@@ -398,6 +436,11 @@ impl<'a, V: VectorIndex> RuleEvaluator<'a, V> {
     where
         F: Fn(f64, f64) -> bool,
     {
+        // Missing values (e.g. file.doc.total on a photo) never satisfy an ordering
+        if left.is_null() {
+            return Ok(false);
+        }
+
         let left_num = left.as_number();
         let right_num = right.as_number();
 
@@ -600,4 +643,57 @@ mod tests {
         assert_eq!(matches.len(), 2);
         assert!(matches.iter().all(|f| f.ext.as_deref() == Some("pdf")));
     }
+
+    #[test]
+    fn test_doc_fields() {
+        let index = SimpleVectorIndex::new();
+        let evaluator = RuleEvaluator::new(&index);
+
+        let invoice = create_test_file("scan001", Some("pdf"), 1024).with_doc(ExtractedFields {
+            vendor: Some("ACME Hardware".to_string()),
+            total: Some(612.5),
+            currency: Some("USD".to_string()),
+            due_date: Some("2024-04-14".to_string()),
+            ..Default::default()
+        });
+        let photo = create_test_file("beach", Some("jpg"), 1024);
+
+        let expr = RuleParser::parse("file.doc.total > 500").unwrap();
+        assert!(evaluator.evaluate(&expr, &invoice).unwrap());
+        assert!(!evaluator.evaluate(&expr, &photo).unwrap());
+
+        let expr = RuleParser::parse("file.doc.vendor.contains('acme') AND file.doc.currency == 'usd'").unwrap();
+        assert!(evaluator.evaluate(&expr, &invoice).unwrap());
+
+        let expr = RuleParser::parse("file.doc.dueDate < '2024-05-01'").unwrap();
+        assert!(evaluator.evaluate(&expr, &invoice).unwrap());
+
+        let expr = RuleParser::parse("NOT file.doc.total > 500").unwrap();
+        assert!(evaluator.evaluate(&expr, &photo).unwrap());
+    }
+
+    #[test]
+    fn test_ignored_and_repo_files_never_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("code/app/.git")).unwrap();
+        std::fs::write(root.join(".sentinelignore"), "private/\n").unwrap();
+
+        let index = SimpleVectorIndex::new();
+        let rules = IgnoreRules::with_global_file(root, None);
+        let evaluator = RuleEvaluator::new(&index).with_ignore_rules(&rules);
+        let expr = RuleParser::parse("file.ext == 'pdf'").unwrap();
+
+        let at = |rel: &str| {
+            let mut file = create_test_file("doc", Some("pdf"), 1024);
+            file.path = root.join(rel).to_string_lossy().to_string();
+            file
+        };
+        assert!(evaluator.matches(&expr, &at("inbox/doc.pdf")).unwrap());
+        assert!(!evaluator.matches(&expr, &at("private/doc.pdf")).unwrap());
+        assert!(!evaluator.matches(&expr, &at("code/app/docs/doc.pdf")).unwrap());
+        assert!(!evaluator.matches(&expr, &at("web/node_modules/x/doc.pdf")).unwrap());
+        // Plain evaluation is unaffected
+        assert!(evaluator.evaluate(&expr, &at("private/doc.pdf")).unwrap());
+    }
 }
@@ -11,6 +11,7 @@
 //! - `file.name.contains('invoice') AND file.size > 10KB`
 //! - `NOT file.isHidden AND file.modifiedAt > '2024-01-01'`
 //! - `(file.ext == 'jpg' OR file.ext == 'png') AND file.size < 5MB`
+//! - `file.doc.total > 500 AND file.doc.currency == 'USD'`
 
 #![allow(dead_code)]
 #![allow(unused_imports)]
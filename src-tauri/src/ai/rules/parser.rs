@@ -542,10 +542,14 @@ impl RuleParser {
             }));
         }
 
-        // This should be a field reference
-        let field = Field::from_str(&name).ok_or_else(|| {
-            ParseError::new(format!("Unknown field: '{}'", name), self.position - 1)
-        })?;
+        // This should be a field reference (file.doc.* fields have a second segment)
+        let field = if name.eq_ignore_ascii_case("doc") {
+            self.parse_doc_field()?
+        } else {
+            Field::from_str(&name).ok_or_else(|| {
+                ParseError::new(format!("Unknown field: '{}'", name), self.position - 1)
+            })?
+        };
 
         // Check for method chain: file.field.function()
         if matches!(self.current(), Token::Dot) {
@@ -621,6 +625,34 @@ impl RuleParser {
         ))
     }
 
+    /// Parse the `.total` part of `file.doc.total`
+    fn parse_doc_field(&mut self) -> Result<Field, ParseError> {
+        self.consume(&Token::Dot, "Expected '.' after 'file.doc'")?;
+
+        let name = match self.current().clone() {
+            Token::Identifier(n) => {
+                self.advance();
+                n
+            }
+            _ => {
+                return Err(ParseError::new(
+                    "Expected document field name after 'file.doc.'",
+                    self.position,
+                ));
+            }
+        };
+
+        DocField::from_str(&name).map(Field::Doc).ok_or_else(|| {
+            ParseError::new(
+                format!(
+                    "Unknown document field: '{}' (expected vendor, total, currency, invoiceNumber, issueDate or dueDate)",
+                    name
+                ),
+                self.position - 1,
+            )
+        })
+    }
+
     fn try_parse_comparison_op(&mut self) -> Option<ComparisonOp> {
         let op = match self.current() {
             Token::Eq => Some(ComparisonOp::Eq),
@@ -839,4 +871,28 @@ mod tests {
         let expr = RuleParser::parse("file.ext !\t= 'doc'").unwrap();
         assert!(matches!(expr, Expression::Comparison(_)));
     }
+
+    #[test]
+    fn test_doc_fields() {
+        let expr = RuleParser::parse("file.doc.total > 500").unwrap();
+        match expr {
+            Expression::Comparison(cmp) => {
+                assert_eq!(cmp.field, Field::Doc(DocField::Total));
+                assert_eq!(cmp.op, ComparisonOp::Gt);
+                assert_eq!(cmp.value, Value::Number(500.0));
+            }
+            _ => panic!("Expected comparison expression"),
+        }
+
+        let expr = RuleParser::parse("file.doc.vendor.contains('acme')").unwrap();
+        match expr {
+            Expression::FunctionCall(func) => {
+                assert_eq!(func.receiver, "file.doc.vendor");
+            }
+            _ => panic!("Expected function call expression"),
+        }
+
+        assert!(RuleParser::parse("file.doc.colour == 'red'").is_err());
+        assert!(RuleParser::parse("file.doc == 'x'").is_err());
+    }
 }
@@ -13,13 +13,14 @@
 //! - **LocalVectorIndex**: Real semantic search via fastembed
 //!
 //! This module implements the main agent loop that:
-//! 1. Builds a ShadowVFS from the target folder
+//! 1. Builds a ShadowVFS from the target folder (plus any source folders)
 //! 2. Checks file count to decide between full tree or sampling mode
 //! 3. Runs the coverage loop with Claude using V2 tools
 //! 4. Returns the finalized OrganizePlan
 
 use crate::ai::client::{CacheControl, ClaudeModel};
 use crate::ai::credentials::CredentialManager;
+use crate::ai::grok::ContentCache;
 use crate::jobs::OrganizePlan;
 
 use super::analytics::DigestGenerator;
@@ -37,7 +38,7 @@ use super::vfs::ShadowVFS;
 
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
@@ -254,16 +255,96 @@ where
     F: Fn(&str, &str, Option<Vec<ExpandableDetail>>),
     P: Fn(ProgressEvent),
 {
-    // 1. Build ShadowVFS from target folder
-    event_emitter("indexing", "Scanning folder structure...", Some(vec![
-        ExpandableDetail { label: "Path".to_string(), value: target_folder.to_string_lossy().to_string() },
-    ]));
+    run_v2_agentic_organize_with_blueprint(
+        target_folder,
+        user_request,
+        None,
+        event_emitter,
+        progress_emitter,
+    )
+    .await
+}
+
+/// Same as [`run_v2_agentic_organize`], but with a fixed Blueprint the
+/// Architect phase is skipped (scheduled jobs reuse one layout every run)
+pub async fn run_v2_agentic_organize_with_blueprint<F, P>(
+    target_folder: &Path,
+    user_request: &str,
+    blueprint: Option<Blueprint>,
+    event_emitter: F,
+    progress_emitter: Option<P>,
+) -> Result<OrganizePlan, String>
+where
+    F: Fn(&str, &str, Option<Vec<ExpandableDetail>>),
+    P: Fn(ProgressEvent),
+{
+    run_v2_agentic_organize_across(
+        target_folder,
+        &[],
+        user_request,
+        blueprint,
+        event_emitter,
+        progress_emitter,
+    )
+    .await
+}
+
+/// Organize the files of several folders into `target_folder` as one plan.
+///
+/// Files in `source_folders` (Desktop, Downloads, ...) and in the target
+/// itself share one VFS, so collisions between roots are caught while
+/// planning, and the plan journals as a single WAL job whose DAG spans every
+/// root. New folders are always created inside the target.
+pub async fn run_v2_agentic_organize_across<F, P>(
+    target_folder: &Path,
+    source_folders: &[PathBuf],
+    user_request: &str,
+    blueprint: Option<Blueprint>,
+    event_emitter: F,
+    progress_emitter: Option<P>,
+) -> Result<OrganizePlan, String>
+where
+    F: Fn(&str, &str, Option<Vec<ExpandableDetail>>),
+    P: Fn(ProgressEvent),
+{
+    // 1. Build ShadowVFS from target folder (and the folders gathered into it)
+    let mut scanned = vec![ExpandableDetail { label: "Path".to_string(), value: target_folder.to_string_lossy().to_string() }];
+    scanned.extend(source_folders.iter().map(|source| ExpandableDetail {
+        label: "Source".to_string(),
+        value: source.to_string_lossy().to_string(),
+    }));
+    event_emitter("indexing", "Scanning folder structure...", Some(scanned));
     eprintln!("[V4AgentLoop] Building VFS for: {}", target_folder.display());
 
-    let mut vfs = ShadowVFS::new(target_folder).map_err(|e| {
+    let mut vfs = ShadowVFS::with_sources(target_folder, source_folders).map_err(|e| {
         format!("Failed to scan folder: {}", e)
     })?;
 
+    // Typed invoice/receipt fields from earlier analyses power `file.doc.*` rules
+    if let Some(cache) = ContentCache::default_dir()
+        .filter(|dir| dir.exists())
+        .and_then(|dir| ContentCache::open(&dir).ok())
+    {
+        let roots: Vec<PathBuf> = vfs.roots().into_iter().map(Path::to_path_buf).collect();
+        for root in roots {
+            let Ok(fields) = cache.fields_under(&root) else {
+                continue;
+            };
+            let attached = vfs.attach_document_fields(fields);
+            if attached > 0 {
+                eprintln!("[V4AgentLoop] Attached document fields to {} files", attached);
+            }
+        }
+    }
+
+    let request_with_sources;
+    let user_request = if vfs.source_roots().is_empty() {
+        user_request
+    } else {
+        request_with_sources = format!("{}{}", user_request, format_source_roots_context(&vfs));
+        request_with_sources.as_str()
+    };
+
     let file_count = vfs.file_count();
     let dir_count = vfs.directory_count();
 
@@ -278,10 +359,11 @@ where
     }
 
     // V5: Check if hologram compression should be used (pattern-heavy folders)
-    // V4: Fall back to sampling for large folders without patterns
+    // V4: Fall back to sampling for large folders without patterns, and for
+    // several roots (the full-tree digest describes a single folder on disk)
     let all_files = vfs.all_files_vec();
     let use_hologram = compression::should_use_hologram(&all_files, 300);
-    let use_sampling = should_use_sampling(file_count);
+    let use_sampling = should_use_sampling(file_count) || !vfs.source_roots().is_empty();
 
     let mode_str = if use_hologram {
         "V5 Hologram (pattern-folded)"
@@ -301,12 +383,18 @@ where
 
     // V6: Run Architect phase to generate Blueprint from user instruction
     // This designs the high-level organization strategy before any agent loops
-    let blueprint = architect::run_architect(
-        target_folder,
-        user_request,
-        &vfs,
-        &event_emitter,
-    ).await?;
+    let blueprint = match blueprint {
+        Some(blueprint) => {
+            event_emitter("thinking", &format!("Using Blueprint: {}", blueprint.strategy_name), None);
+            blueprint
+        }
+        None => architect::run_architect(
+            target_folder,
+            user_request,
+            &vfs,
+            &event_emitter,
+        ).await?,
+    };
 
     // Embed Blueprint folder descriptions for vector matching in Builder phase
     let blueprint = architect::embed_blueprint(&blueprint, &vfs)?;
@@ -686,10 +774,15 @@ where
                             destination: op.destination.clone(),
                             path: op.path.clone(),
                             new_name: op.new_name.clone(),
+                            attributes: Default::default(),
                         })
                         .collect(),
                     // organization_root is the target folder - all organization stays within it
                     target_folder: vfs.organization_root().to_string_lossy().to_string(),
+                    source_roots: plan_source_roots(&vfs),
+                    rules: vfs.applied_rules().to_vec(),
+                    baseline: None,
+                    explanations: Default::default(),
                 };
                 return Ok(plan);
             }
@@ -959,10 +1052,15 @@ where
                     destination: op.destination.clone(),
                     path: op.path.clone(),
                     new_name: op.new_name.clone(),
+                    attributes: Default::default(),
                 })
                 .collect(),
             // organization_root is the target folder - all organization stays within it
             target_folder: vfs.organization_root().to_string_lossy().to_string(),
+            source_roots: plan_source_roots(vfs),
+            rules: vfs.applied_rules().to_vec(),
+            baseline: None,
+            explanations: Default::default(),
         };
 
         event_emitter("committing", &format!("Plan ready: {} operations", plan.operations.len()), Some(vec![
@@ -1301,10 +1399,15 @@ where
                     destination: op.destination.clone(),
                     path: op.path.clone(),
                     new_name: op.new_name.clone(),
+                    attributes: Default::default(),
                 })
                 .collect(),
             // organization_root is the target folder - all organization stays within it
             target_folder: vfs.organization_root().to_string_lossy().to_string(),
+            source_roots: plan_source_roots(vfs),
+            rules: vfs.applied_rules().to_vec(),
+            baseline: None,
+            explanations: Default::default(),
         };
 
         event_emitter(
@@ -1338,6 +1441,25 @@ where
 // V6 Blueprint-Aware Wrappers
 // ============================================================================
 
+/// Source folders of a plan built from `vfs` (see [`OrganizePlan::source_roots`])
+fn plan_source_roots(vfs: &ShadowVFS) -> Vec<String> {
+    vfs.source_roots()
+        .iter()
+        .map(|root| root.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Tell the model where files come from when several folders are gathered
+fn format_source_roots_context(vfs: &ShadowVFS) -> String {
+    let mut context = String::from("\n\n## Source Folders\n\n");
+    context.push_str("Files are gathered from these folders as well as the target folder:\n");
+    for root in vfs.source_roots() {
+        context.push_str(&format!("- `{}`\n", root.display()));
+    }
+    context.push_str("\nDestinations stay relative to the target folder; files are moved out of the source folders into it.\n");
+    context
+}
+
 /// Format Blueprint as additional context for the user request
 fn format_blueprint_context(blueprint: &Blueprint) -> String {
     let mut context = String::new();
@@ -17,6 +17,7 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use super::local_vector_index::LocalVectorIndex;
+use crate::scan::ScanOptions;
 use crate::utils::format_size;
 
 /// Maximum content preview length per file (characters)
@@ -25,7 +26,7 @@ const MAX_PREVIEW_LENGTH: usize = 200;
 /// Maximum files to sample for content previews
 const MAX_PREVIEW_FILES: usize = 10;
 
-/// Maximum recursion depth for folder scanning
+/// Maximum entry depth for folder scanning
 const MAX_SCAN_DEPTH: usize = 20;
 
 /// Comprehensive folder analysis for AI context
@@ -144,6 +145,8 @@ impl DigestGenerator {
 
         eprintln!("[DigestGenerator] Analyzing folder: {:?}", root);
 
+        let snapshot = ScanOptions::new().with_max_depth(MAX_SCAN_DEPTH).scan(root)?;
+
         let mut file_count = 0;
         let mut dir_count = 0;
         let mut total_size: u64 = 0;
@@ -153,28 +156,49 @@ impl DigestGenerator {
         let mut max_timestamp: i64 = i64::MIN;
         let mut filename_prefixes: HashMap<String, usize> = HashMap::new();
         let mut max_depth: usize = 0;
-        let mut hidden_count = 0;
 
         // Files suitable for content preview
         let mut preview_candidates: Vec<(PathBuf, u64, Option<String>)> = Vec::new();
 
-        // Recursive scan
-        self.scan_directory(
-            root,
-            root,
-            0,
-            &mut file_count,
-            &mut dir_count,
-            &mut total_size,
-            &mut ext_counts,
-            &mut mime_breakdown,
-            &mut min_timestamp,
-            &mut max_timestamp,
-            &mut filename_prefixes,
-            &mut max_depth,
-            &mut hidden_count,
-            &mut preview_candidates,
-        )?;
+        for entry in snapshot.entries() {
+            if entry.is_dir() {
+                dir_count += 1;
+                max_depth = max_depth.max(entry.depth);
+                continue;
+            }
+            if !entry.is_file() {
+                continue;
+            }
+
+            file_count += 1;
+            total_size += entry.size;
+
+            // Extension counting
+            let ext = entry.extension().unwrap_or_else(|| "none".to_string());
+            *ext_counts.entry(ext.clone()).or_insert(0) += 1;
+
+            // MIME breakdown
+            let mime_category = get_mime_category(&ext);
+            *mime_breakdown.entry(mime_category).or_insert(0) += 1;
+
+            // Timestamp tracking
+            if let Some(ts) = entry.modified_at {
+                min_timestamp = min_timestamp.min(ts);
+                max_timestamp = max_timestamp.max(ts);
+            }
+
+            // Filename prefix extraction
+            if let Some(prefix) = extract_prefix(&entry.name) {
+                *filename_prefixes.entry(prefix).or_insert(0) += 1;
+            }
+
+            // Content preview candidates (text-like files, reasonable size)
+            if is_previewable(&ext) && entry.size < 1_000_000 {
+                let mime = mime_guess::from_ext(&ext).first().map(|m| m.to_string());
+                preview_candidates.push((entry.path.clone(), entry.size, mime));
+            }
+        }
+        let hidden_count = snapshot.stats().hidden_skipped;
 
         eprintln!(
             "[DigestGenerator] Scanned {} files in {} directories",
@@ -221,104 +245,6 @@ impl DigestGenerator {
         })
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn scan_directory(
-        &self,
-        path: &Path,
-        root: &Path,
-        depth: usize,
-        file_count: &mut usize,
-        dir_count: &mut usize,
-        total_size: &mut u64,
-        ext_counts: &mut HashMap<String, usize>,
-        mime_breakdown: &mut HashMap<String, usize>,
-        min_timestamp: &mut i64,
-        max_timestamp: &mut i64,
-        filename_prefixes: &mut HashMap<String, usize>,
-        max_depth: &mut usize,
-        hidden_count: &mut usize,
-        preview_candidates: &mut Vec<(PathBuf, u64, Option<String>)>,
-    ) -> Result<(), String> {
-        *max_depth = (*max_depth).max(depth);
-
-        let entries = fs::read_dir(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
-
-        for entry in entries.filter_map(|e| e.ok()) {
-            let entry_path = entry.path();
-            let name = entry.file_name().to_string_lossy().to_string();
-
-            // Track hidden files but skip processing them
-            if name.starts_with('.') {
-                *hidden_count += 1;
-                continue;
-            }
-
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-
-            if metadata.is_dir() {
-                *dir_count += 1;
-                // Recurse with depth limit
-                if depth < MAX_SCAN_DEPTH {
-                    let _ = self.scan_directory(
-                        &entry_path,
-                        root,
-                        depth + 1,
-                        file_count,
-                        dir_count,
-                        total_size,
-                        ext_counts,
-                        mime_breakdown,
-                        min_timestamp,
-                        max_timestamp,
-                        filename_prefixes,
-                        max_depth,
-                        hidden_count,
-                        preview_candidates,
-                    );
-                }
-            } else if metadata.is_file() {
-                *file_count += 1;
-                *total_size += metadata.len();
-
-                // Extension counting
-                let ext = entry_path
-                    .extension()
-                    .map(|e| e.to_string_lossy().to_lowercase())
-                    .unwrap_or_else(|| "none".to_string());
-                *ext_counts.entry(ext.clone()).or_insert(0) += 1;
-
-                // MIME breakdown
-                let mime_category = get_mime_category(&ext);
-                *mime_breakdown.entry(mime_category).or_insert(0) += 1;
-
-                // Timestamp tracking
-                if let Ok(modified) = metadata.modified() {
-                    if let Ok(duration) = modified.duration_since(std::time::UNIX_EPOCH) {
-                        let ts = duration.as_millis() as i64;
-                        *min_timestamp = (*min_timestamp).min(ts);
-                        *max_timestamp = (*max_timestamp).max(ts);
-                    }
-                }
-
-                // Filename prefix extraction
-                if let Some(prefix) = extract_prefix(&name) {
-                    *filename_prefixes.entry(prefix).or_insert(0) += 1;
-                }
-
-                // Content preview candidates (text-like files, reasonable size)
-                if is_previewable(&ext) && metadata.len() < 1_000_000 {
-                    let mime = mime_guess::from_ext(&ext).first().map(|m| m.to_string());
-                    preview_candidates.push((entry_path, metadata.len(), mime));
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     fn compute_common_prefixes(
         &self,
         prefixes: &HashMap<String, usize>,
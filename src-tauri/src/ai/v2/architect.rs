@@ -17,6 +17,9 @@
 use crate::ai::client::ClaudeModel;
 use crate::ai::credentials::CredentialManager;
 use super::agent_loop::ExpandableDetail;
+use super::builder::MatchThresholds;
+use super::corrections::{self, few_shot_examples, Correction};
+use super::local_vector_index::LocalVectorIndex;
 use super::rate_limiter::RateLimitManager;
 use super::sampling;
 use super::vfs::ShadowVFS;
@@ -34,6 +37,9 @@ const ANTHROPIC_VERSION: &str = "2023-06-01";
 /// Maximum file header size to read (1KB)
 const MAX_HEADER_SIZE: usize = 1024;
 
+/// Maximum past corrections included in the prompt
+const MAX_PROMPT_EXAMPLES: usize = 20;
+
 /// Maximum retries for rate limit errors
 const MAX_RETRIES: u32 = 3;
 
@@ -58,6 +64,10 @@ pub struct Blueprint {
     /// Confidence score from the Architect (0.0-1.0)
     #[serde(default = "default_confidence")]
     pub confidence: f32,
+
+    /// Builder match thresholds (the defaults when omitted)
+    #[serde(default)]
+    pub thresholds: MatchThresholds,
 }
 
 fn default_confidence() -> f32 {
@@ -179,7 +189,7 @@ where
     );
 
     // 1. Generate stratified sample with file headers
-    let (file_samples, folder_stats) = build_architect_context(target_folder, vfs)?;
+    let (file_samples, folder_stats) = build_architect_context(vfs)?;
 
     eprintln!(
         "[Architect] Built context with {} samples, {} total files",
@@ -187,8 +197,10 @@ where
         folder_stats.total_files
     );
 
-    // 2. Build prompt and call Sonnet
-    let blueprint = call_architect_llm(user_instruction, &file_samples, &folder_stats).await?;
+    // 2. Build prompt (with the user's past corrections) and call Sonnet
+    let corrections = corrections::load_corrections(target_folder, None);
+    let blueprint =
+        call_architect_llm(user_instruction, &file_samples, &folder_stats, &corrections).await?;
 
     eprintln!(
         "[Architect] Blueprint created: {} folders, confidence {:.0}%",
@@ -219,11 +231,16 @@ where
     Ok(blueprint)
 }
 
-/// Build context for the Architect from VFS
-fn build_architect_context(
-    target_folder: &Path,
-    vfs: &ShadowVFS,
-) -> Result<(Vec<FileSample>, FolderStats), String> {
+/// The prompt the Architect would send for a folder, without calling the
+/// LLM or loading corrections (used by the evaluation harness)
+pub(super) fn architect_prompt(user_instruction: &str, vfs: &ShadowVFS) -> Result<String, String> {
+    let (file_samples, folder_stats) = build_architect_context(vfs)?;
+    Ok(build_architect_prompt(user_instruction, &file_samples, &folder_stats, &[]))
+}
+
+/// Build context for the Architect from VFS (headers are read from
+/// whichever of its roots holds the sampled file)
+fn build_architect_context(vfs: &ShadowVFS) -> Result<(Vec<FileSample>, FolderStats), String> {
     let all_files = vfs.all_files_vec();
 
     // Use existing stratified sampling (max 60 files)
@@ -234,7 +251,10 @@ fn build_architect_context(
         .samples
         .iter()
         .map(|s| {
-            let header = read_file_header(&s.name, target_folder, s.ext.as_deref());
+            let header = vfs
+                .roots()
+                .into_iter()
+                .find_map(|root| read_file_header(&s.name, root, s.ext.as_deref()));
             FileSample {
                 name: s.name.clone(),
                 extension: s.ext.clone(),
@@ -341,6 +361,7 @@ async fn call_architect_llm(
     user_instruction: &str,
     file_samples: &[FileSample],
     folder_stats: &FolderStats,
+    corrections: &[Correction],
 ) -> Result<Blueprint, String> {
     // Get API key
     let api_key = CredentialManager::get_api_key("anthropic")?;
@@ -352,7 +373,7 @@ async fn call_architect_llm(
     let mut rate_limiter = RateLimitManager::new();
 
     // Build the prompt
-    let prompt = build_architect_prompt(user_instruction, file_samples, folder_stats);
+    let prompt = build_architect_prompt(user_instruction, file_samples, folder_stats, corrections);
 
     eprintln!("[Architect] Prompt length: {} chars", prompt.len());
 
@@ -490,6 +511,7 @@ fn build_architect_prompt(
     user_instruction: &str,
     file_samples: &[FileSample],
     folder_stats: &FolderStats,
+    corrections: &[Correction],
 ) -> String {
     let mut prompt = String::new();
 
@@ -536,6 +558,13 @@ fn build_architect_prompt(
         }
     }
 
+    // Placements the user corrected in earlier plans of this folder
+    let examples = few_shot_examples(corrections, MAX_PROMPT_EXAMPLES);
+    if !examples.is_empty() {
+        prompt.push_str("\n## Past Corrections\nThe user changed these placements in earlier plans for this folder. Design folders that put such files where the user wants them:\n");
+        prompt.push_str(&examples);
+    }
+
     prompt.push_str("\n## Instructions\nBased on the user's request and the file samples above, output a Blueprint JSON for organizing these files. Follow the JSON schema exactly.");
 
     prompt
@@ -546,9 +575,16 @@ fn build_architect_prompt(
 pub fn embed_blueprint(
     blueprint: &Blueprint,
     vfs: &ShadowVFS,
+) -> Result<Blueprint, String> {
+    embed_blueprint_with_index(blueprint, vfs.vector_index())
+}
+
+/// [`embed_blueprint`] without a scanned folder
+pub fn embed_blueprint_with_index(
+    blueprint: &Blueprint,
+    index: &LocalVectorIndex,
 ) -> Result<Blueprint, String> {
     let mut embedded = blueprint.clone();
-    let index = vfs.vector_index();
 
     // Collect all semantic descriptions
     let descriptions: Vec<&str> = embedded
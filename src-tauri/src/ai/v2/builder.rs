@@ -7,17 +7,32 @@
 //! - **Tier 2**: LLM read (Haiku) - for ambiguous files
 //!
 //! This approach minimizes expensive LLM calls by using fast vector
-//! matching for the majority of files.
+//! matching for the majority of files. Past user corrections of the folder
+//! (see [`super::corrections`]) are checked before both tiers.
+//!
+//! The thresholds can be tuned per Blueprint ([`MatchThresholds`]), and
+//! every decision carries a [`MatchExplanation`] that ends up in the plan,
+//! so the preview can say why a file goes where it goes.
+//!
+//! With a saved Blueprint, [`organize_with_blueprint`] skips the Architect
+//! and the agent loop entirely: files are matched and slotted directly,
+//! which is deterministic unless Tier 2 files are sent to Haiku.
 
 use crate::ai::client::ClaudeModel;
 use crate::ai::credentials::CredentialManager;
 use crate::ai::rules::VirtualFile;
 
+use crate::jobs::OrganizePlan;
+
 use super::agent_loop::ExpandableDetail;
-use super::architect::Blueprint;
+use super::architect::{self, Blueprint};
+use super::corrections::{self, few_shot_examples, Correction, CorrectionPriors};
 use super::local_vector_index::LocalVectorIndex;
+use super::vfs::ShadowVFS;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Duration;
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
@@ -29,12 +44,201 @@ const TIER1_THRESHOLD: f32 = 0.85;
 /// Minimum confidence to consider a match at all
 const MIN_MATCH_THRESHOLD: f32 = 0.4;
 
+/// Candidates offered to Haiku per ambiguous file
+const MAX_CANDIDATES: usize = 3;
+
+/// Folder similarities kept in a [`MatchExplanation`]
+const EXPLAIN_TOP_K: usize = 5;
+
 /// Maximum files per Haiku batch call
 const HAIKU_BATCH_SIZE: usize = 10;
 
+/// Maximum past corrections shown to Haiku per batch
+const MAX_PROMPT_EXAMPLES: usize = 10;
+
 /// Maximum retries for rate limit errors
 const MAX_RETRIES: u32 = 3;
 
+/// Thresholds for the tiered match, set per Blueprint. The defaults are
+/// the Builder's long-standing fixed values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchThresholds {
+    /// Best folder similarity at which a file is slotted without the LLM
+    pub tier1: f32,
+    /// Folders below this similarity are not candidates at all
+    pub min_match: f32,
+    /// Candidates offered to Haiku for an ambiguous file
+    pub max_candidates: usize,
+    /// Added to a folder's similarity when it expects the file's extension
+    pub extension_bonus: f32,
+    /// Similarity to a past correction at which the correction decides
+    pub correction: f32,
+}
+
+impl Default for MatchThresholds {
+    fn default() -> Self {
+        Self {
+            tier1: TIER1_THRESHOLD,
+            min_match: MIN_MATCH_THRESHOLD,
+            max_candidates: MAX_CANDIDATES,
+            extension_bonus: 0.0,
+            correction: corrections::PRIOR_MATCH_THRESHOLD,
+        }
+    }
+}
+
+impl MatchThresholds {
+    /// Reject values that would make a tier unreachable
+    pub fn validate(&self) -> Result<(), String> {
+        let bounded = [
+            ("tier1", self.tier1),
+            ("minMatch", self.min_match),
+            ("extensionBonus", self.extension_bonus),
+            ("correction", self.correction),
+        ];
+        for (name, value) in bounded {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("Threshold {} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if self.min_match > self.tier1 {
+            return Err(format!(
+                "Threshold minMatch ({}) must not exceed tier1 ({})",
+                self.min_match, self.tier1
+            ));
+        }
+        if self.max_candidates == 0 {
+            return Err("Threshold maxCandidates must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Which step of the match decided where a file goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchTier {
+    /// A past correction of a near-identical file
+    Correction,
+    /// Tier 1: best folder similarity at or above the threshold
+    Vector,
+    /// Tier 2: Haiku chose among the candidates
+    Llm,
+    /// Tier 2 without a usable LLM answer: the best candidate
+    BestCandidate,
+    /// No folder reached the minimum similarity
+    Unmatched,
+}
+
+/// Similarity of a file to one Blueprint folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSimilarity {
+    pub folder: String,
+    /// Includes the extension bonus, if any
+    pub similarity: f32,
+    /// The folder lists the file's extension among its expected ones
+    pub extension_match: bool,
+}
+
+/// The past correction that decided a match or ruled a folder out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionEvidence {
+    pub file_name: String,
+    pub planned_folder: String,
+    /// None when the user kept that file where it was
+    pub chosen_folder: Option<String>,
+    pub similarity: f32,
+}
+
+/// Why a file was matched the way it was
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchExplanation {
+    pub tier: MatchTier,
+    /// Folder the file goes to (None when it stays in place)
+    pub folder: Option<String>,
+    /// Most similar Blueprint folders, best first, before any threshold
+    pub top_folders: Vec<FolderSimilarity>,
+    pub extension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correction: Option<CorrectionEvidence>,
+    pub thresholds: MatchThresholds,
+}
+
+impl MatchExplanation {
+    /// One line for plan previews, e.g.
+    /// "Tier 1: 91% similar to Receipts (threshold 85%), .pdf expected there"
+    pub fn summary(&self) -> String {
+        let percent = |value: f32| format!("{:.0}%", value * 100.0);
+        let folder = self.folder.as_deref().unwrap_or("");
+        let chosen = self.top_folders.iter().find(|f| f.folder == folder);
+        let extension_note = match (&self.extension, chosen) {
+            (Some(ext), Some(f)) if f.extension_match => format!(", .{} expected there", ext),
+            _ => String::new(),
+        };
+        let candidates = || {
+            self.top_folders
+                .iter()
+                .filter(|f| f.similarity >= self.thresholds.min_match)
+                .take(self.thresholds.max_candidates)
+                .map(|f| format!("{} {}", f.folder, percent(f.similarity)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self.tier {
+            MatchTier::Correction => match &self.correction {
+                Some(c) => format!(
+                    "Past correction: {} ({} similar) was moved to {}",
+                    c.file_name,
+                    percent(c.similarity),
+                    folder
+                ),
+                None => format!("Past correction: moved to {}", folder),
+            },
+            MatchTier::Vector => format!(
+                "Tier 1: {} similar to {} (threshold {}){}",
+                chosen.map_or_else(|| "?".to_string(), |f| percent(f.similarity)),
+                folder,
+                percent(self.thresholds.tier1),
+                extension_note
+            ),
+            MatchTier::Llm => format!(
+                "Tier 2: Haiku chose {} from {}{}",
+                folder,
+                candidates(),
+                extension_note
+            ),
+            MatchTier::BestCandidate => format!(
+                "Tier 2: best candidate {} of {} (below {}){}",
+                folder,
+                candidates(),
+                percent(self.thresholds.tier1),
+                extension_note
+            ),
+            MatchTier::Unmatched => {
+                let best = match self.top_folders.first() {
+                    Some(f) => format!(" (best {} {})", f.folder, percent(f.similarity)),
+                    None => String::new(),
+                };
+                let fallback = match &self.folder {
+                    Some(folder) => format!(", moved to {}", folder),
+                    None => String::new(),
+                };
+                format!(
+                    "Unmatched: no folder reached {}{}{}",
+                    percent(self.thresholds.min_match),
+                    best,
+                    fallback
+                )
+            }
+        }
+    }
+}
+
 /// Result of matching a file to the Blueprint
 #[derive(Debug, Clone)]
 pub enum MatchResult {
@@ -43,15 +247,30 @@ pub enum MatchResult {
         file_path: String,
         destination_folder: String,
         confidence: f32,
+        explanation: MatchExplanation,
     },
     /// Tier 2: Needs LLM disambiguation
     Tier2Ambiguous {
         file_path: String,
         file_name: String,
         candidates: Vec<(String, f32)>, // (folder_path, score)
+        explanation: MatchExplanation,
     },
     /// No match found - goes to Misc/Unsorted
-    NoMatch { file_path: String },
+    NoMatch {
+        file_path: String,
+        explanation: MatchExplanation,
+    },
+}
+
+impl MatchResult {
+    pub fn explanation(&self) -> &MatchExplanation {
+        match self {
+            MatchResult::Tier1Match { explanation, .. }
+            | MatchResult::Tier2Ambiguous { explanation, .. }
+            | MatchResult::NoMatch { explanation, .. } => explanation,
+        }
+    }
 }
 
 /// Result of batch matching all files
@@ -63,6 +282,152 @@ pub struct BatchMatchResult {
     pub tier2_ambiguous: Vec<(String, String, Vec<(String, f32)>)>,
     /// Files with no match
     pub no_matches: Vec<String>,
+    /// Explanation of every file's match, by file path
+    pub explanations: HashMap<String, MatchExplanation>,
+}
+
+/// Options for planning straight from a saved Blueprint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuilderOptions {
+    /// Ask Haiku to place Tier 2 files; otherwise each takes its best
+    /// candidate and the run makes no LLM calls
+    #[serde(default)]
+    pub resolve_with_llm: bool,
+    /// Folder for files that match nothing (they stay in place when unset)
+    #[serde(default)]
+    pub unmatched_folder: Option<String>,
+}
+
+/// Plan a folder from a saved Blueprint without running the Architect:
+/// embed the Blueprint, match every file with [`batch_match_files`] and
+/// slot the matches into the VFS
+pub async fn organize_with_blueprint<F>(
+    target_folder: &Path,
+    blueprint: &Blueprint,
+    options: &BuilderOptions,
+    event_emitter: F,
+) -> Result<OrganizePlan, String>
+where
+    F: Fn(&str, &str, Option<Vec<ExpandableDetail>>),
+{
+    if blueprint.structure.is_empty() {
+        return Err("Blueprint has no folders".to_string());
+    }
+    event_emitter("indexing", "Scanning folder structure...", None);
+    let mut vfs = ShadowVFS::new(target_folder).map_err(|e| format!("Failed to scan folder: {}", e))?;
+    let blueprint = architect::embed_blueprint(blueprint, &vfs)?;
+
+    // Sorted so the same folder always yields the same plan
+    let mut files = vfs.all_files_vec();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let corrections = corrections::load_corrections(target_folder, Some(vfs.vector_index()));
+    let priors = CorrectionPriors::new(&corrections);
+    let mut matched = batch_match_files(&files, &blueprint, vfs.vector_index(), &priors)?;
+
+    event_emitter(
+        "builder",
+        &format!("Matched {} files to {}", matched.tier1_matches.len(), blueprint.strategy_name),
+        Some(vec![
+            ExpandableDetail {
+                label: "Tier 1".to_string(),
+                value: matched.tier1_matches.len().to_string(),
+            },
+            ExpandableDetail {
+                label: "Tier 2".to_string(),
+                value: matched.tier2_ambiguous.len().to_string(),
+            },
+            ExpandableDetail {
+                label: "Unmatched".to_string(),
+                value: matched.no_matches.len().to_string(),
+            },
+            ExpandableDetail {
+                label: "Past corrections".to_string(),
+                value: priors.len().to_string(),
+            },
+        ]),
+    );
+
+    let resolved = if options.resolve_with_llm {
+        resolve_tier2_with_llm(&matched.tier2_ambiguous, &blueprint, &corrections, &event_emitter).await?
+    } else {
+        Vec::new()
+    };
+    let tier2 = settle_tier2(&matched.tier2_ambiguous, &resolved, &blueprint, &mut matched.explanations);
+
+    let (unmatched, misc_folder) = match &options.unmatched_folder {
+        Some(folder) => (matched.no_matches.as_slice(), folder.as_str()),
+        None => (&[][..], ""),
+    };
+    let placements = generate_operations_from_matches(&matched.tier1_matches, &tier2, unmatched, misc_folder);
+    let moves = vfs.apply_placements(&placements, &format!("Blueprint: {}", blueprint.strategy_name))?;
+
+    eprintln!(
+        "[Builder] Planned {} moves from Blueprint {} ({} files)",
+        moves,
+        blueprint.strategy_name,
+        files.len()
+    );
+    let mut plan = vfs.to_plan(&format!("Organized with Blueprint: {}", blueprint.strategy_name));
+    explain_plan(&mut plan, &placements, &matched.explanations);
+    Ok(plan)
+}
+
+/// Final folder for each Tier 2 file: the LLM's answer when it named one of
+/// the Blueprint's folders, otherwise the best vector candidate. The files'
+/// explanations are updated to the tier that decided.
+pub(super) fn settle_tier2(
+    ambiguous: &[(String, String, Vec<(String, f32)>)],
+    resolved: &[(String, String)],
+    blueprint: &Blueprint,
+    explanations: &mut HashMap<String, MatchExplanation>,
+) -> Vec<(String, String)> {
+    ambiguous
+        .iter()
+        .filter_map(|(file_path, _, candidates)| {
+            let answer = resolved
+                .iter()
+                .find(|(path, _)| path == file_path)
+                .map(|(_, folder)| folder.trim_matches('/'))
+                .filter(|folder| blueprint.structure.iter().any(|f| f.path == *folder));
+            let tier = match answer {
+                Some(_) => MatchTier::Llm,
+                None => MatchTier::BestCandidate,
+            };
+            let folder = answer
+                .map(str::to_string)
+                .or_else(|| candidates.first().map(|(folder, _)| folder.clone()))?;
+            if let Some(explanation) = explanations.get_mut(file_path) {
+                explanation.tier = tier;
+                explanation.folder = Some(folder.clone());
+            }
+            Some((file_path.clone(), folder))
+        })
+        .collect()
+}
+
+/// Attach the explanation of each placed file to the plan's move of it,
+/// with the folder it was placed in (unmatched files may go to a fallback)
+pub(super) fn explain_plan(
+    plan: &mut OrganizePlan,
+    placements: &[(String, String)],
+    explanations: &HashMap<String, MatchExplanation>,
+) {
+    let placed: HashMap<&str, &str> = placements
+        .iter()
+        .map(|(file_path, folder)| (file_path.as_str(), folder.as_str()))
+        .collect();
+    for op in plan.operations.iter().filter(|op| op.op_type == "move") {
+        let Some(source) = op.source.as_deref() else {
+            continue;
+        };
+        let (Some(explanation), Some(folder)) = (explanations.get(source), placed.get(source)) else {
+            continue;
+        };
+        let mut explanation = explanation.clone();
+        explanation.folder = Some(folder.to_string());
+        plan.explanations.insert(op.op_id.clone(), explanation);
+    }
 }
 
 /// Match a single file against the Blueprint structure
@@ -70,6 +435,7 @@ pub fn match_file_to_blueprint(
     file: &VirtualFile,
     blueprint: &Blueprint,
     index: &LocalVectorIndex,
+    priors: &CorrectionPriors,
 ) -> Result<MatchResult, String> {
     // Build searchable text from file
     let file_text = format!(
@@ -88,50 +454,136 @@ pub fn match_file_to_blueprint(
         .next()
         .ok_or("No embedding generated for file")?;
 
-    // Score against all Blueprint folders
-    let mut scores: Vec<(String, f32)> = blueprint
+    Ok(match_embedding(
+        &file.path,
+        &file.name,
+        file.ext.as_deref(),
+        &file_embedding,
+        blueprint,
+        priors,
+    ))
+}
+
+/// Match an embedded file against the Blueprint structure, using the
+/// Blueprint's [`MatchThresholds`].
+///
+/// A past correction close enough to the file (see [`CorrectionPriors`])
+/// comes first: the folder the user chose is a Tier 1 match, and a folder
+/// the user moved such a file out of is no longer a candidate.
+pub fn match_embedding(
+    file_path: &str,
+    file_name: &str,
+    extension: Option<&str>,
+    file_embedding: &[f32],
+    blueprint: &Blueprint,
+    priors: &CorrectionPriors,
+) -> MatchResult {
+    let thresholds = blueprint.thresholds;
+    let extension = extension
+        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty());
+
+    // Score against all Blueprint folders, best first
+    let mut similarities: Vec<FolderSimilarity> = blueprint
         .structure
         .iter()
         .filter_map(|folder| {
-            folder.embedding.as_ref().map(|folder_emb| {
-                let score = cosine_similarity(&file_embedding, folder_emb);
-                (folder.path.clone(), score)
+            let folder_emb = folder.embedding.as_ref()?;
+            let extension_match = extension.as_deref().is_some_and(|ext| {
+                folder
+                    .expected_extensions
+                    .iter()
+                    .any(|expected| expected.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            });
+            let mut similarity = cosine_similarity(file_embedding, folder_emb);
+            if extension_match {
+                similarity = (similarity + thresholds.extension_bonus).min(1.0);
+            }
+            Some(FolderSimilarity {
+                folder: folder.path.clone(),
+                similarity,
+                extension_match,
             })
         })
-        .filter(|(_, score)| *score >= MIN_MATCH_THRESHOLD)
         .collect();
-
-    // Sort by score descending
-    scores.sort_by(|a, b| {
-        b.1.partial_cmp(&a.1)
+    similarities.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
             .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    if scores.is_empty() {
-        return Ok(MatchResult::NoMatch {
-            file_path: file.path.clone(),
-        });
+    let mut explanation = MatchExplanation {
+        tier: MatchTier::Unmatched,
+        folder: None,
+        top_folders: similarities.iter().take(EXPLAIN_TOP_K).cloned().collect(),
+        extension,
+        correction: None,
+        thresholds,
+    };
+    let mut scores: Vec<(String, f32)> = similarities
+        .into_iter()
+        .filter(|f| f.similarity >= thresholds.min_match)
+        .map(|f| (f.folder, f.similarity))
+        .collect();
+
+    // Nearest past correction
+    if let Some(prior) = priors.nearest(file_embedding, thresholds.correction) {
+        let evidence = CorrectionEvidence {
+            file_name: prior.file_name.to_string(),
+            planned_folder: prior.planned_folder.to_string(),
+            chosen_folder: prior.chosen_folder.map(str::to_string),
+            similarity: prior.similarity,
+        };
+        match prior.chosen_folder {
+            Some(chosen) if blueprint.structure.iter().any(|f| f.path == chosen) => {
+                explanation.tier = MatchTier::Correction;
+                explanation.folder = Some(chosen.to_string());
+                explanation.correction = Some(evidence);
+                return MatchResult::Tier1Match {
+                    file_path: file_path.to_string(),
+                    destination_folder: chosen.to_string(),
+                    confidence: prior.similarity,
+                    explanation,
+                };
+            }
+            // Moved somewhere this Blueprint doesn't have
+            Some(_) => {}
+            None => {
+                scores.retain(|(folder, _)| folder != prior.planned_folder);
+                explanation.correction = Some(evidence);
+            }
+        }
     }
 
-    let (best_folder, best_score) = &scores[0];
+    let Some((best_folder, best_score)) = scores.first().cloned() else {
+        return MatchResult::NoMatch {
+            file_path: file_path.to_string(),
+            explanation,
+        };
+    };
+    explanation.folder = Some(best_folder.clone());
 
     // Tier 1: High confidence match
-    if *best_score >= TIER1_THRESHOLD {
-        return Ok(MatchResult::Tier1Match {
-            file_path: file.path.clone(),
-            destination_folder: best_folder.clone(),
-            confidence: *best_score,
-        });
+    if best_score >= thresholds.tier1 {
+        explanation.tier = MatchTier::Vector;
+        return MatchResult::Tier1Match {
+            file_path: file_path.to_string(),
+            destination_folder: best_folder,
+            confidence: best_score,
+            explanation,
+        };
     }
 
-    // Tier 2: Ambiguous - needs LLM
-    let candidates: Vec<(String, f32)> = scores.into_iter().take(3).collect();
+    // Tier 2: Ambiguous - needs LLM (best candidate until settled)
+    explanation.tier = MatchTier::BestCandidate;
+    let candidates: Vec<(String, f32)> = scores.into_iter().take(thresholds.max_candidates).collect();
 
-    Ok(MatchResult::Tier2Ambiguous {
-        file_path: file.path.clone(),
-        file_name: file.name.clone(),
+    MatchResult::Tier2Ambiguous {
+        file_path: file_path.to_string(),
+        file_name: file_name.to_string(),
         candidates,
-    })
+        explanation,
+    }
 }
 
 /// Batch match all files, returning Tier 1 matches and Tier 2 candidates
@@ -139,32 +591,42 @@ pub fn batch_match_files(
     files: &[VirtualFile],
     blueprint: &Blueprint,
     index: &LocalVectorIndex,
+    priors: &CorrectionPriors,
 ) -> Result<BatchMatchResult, String> {
     let mut tier1_matches = Vec::new();
     let mut tier2_ambiguous = Vec::new();
     let mut no_matches = Vec::new();
+    let mut explanations = HashMap::new();
 
     for file in files {
         if file.is_directory {
             continue;
         }
 
-        match match_file_to_blueprint(file, blueprint, index)? {
+        match match_file_to_blueprint(file, blueprint, index, priors)? {
             MatchResult::Tier1Match {
                 file_path,
                 destination_folder,
                 confidence,
+                explanation,
             } => {
+                explanations.insert(file_path.clone(), explanation);
                 tier1_matches.push((file_path, destination_folder, confidence));
             }
             MatchResult::Tier2Ambiguous {
                 file_path,
                 file_name,
                 candidates,
+                explanation,
             } => {
+                explanations.insert(file_path.clone(), explanation);
                 tier2_ambiguous.push((file_path, file_name, candidates));
             }
-            MatchResult::NoMatch { file_path } => {
+            MatchResult::NoMatch {
+                file_path,
+                explanation,
+            } => {
+                explanations.insert(file_path.clone(), explanation);
                 no_matches.push(file_path);
             }
         }
@@ -174,13 +636,16 @@ pub fn batch_match_files(
         tier1_matches,
         tier2_ambiguous,
         no_matches,
+        explanations,
     })
 }
 
-/// Resolve Tier 2 ambiguous files using Haiku LLM
+/// Resolve Tier 2 ambiguous files using Haiku LLM, with past corrections
+/// of the folder as examples
 pub async fn resolve_tier2_with_llm<F>(
     ambiguous_files: &[(String, String, Vec<(String, f32)>)],
     blueprint: &Blueprint,
+    corrections: &[Correction],
     event_emitter: F,
 ) -> Result<Vec<(String, String)>, String>
 where
@@ -208,7 +673,7 @@ where
     let mut resolved = Vec::new();
 
     for chunk in ambiguous_files.chunks(HAIKU_BATCH_SIZE) {
-        let resolutions = call_haiku_for_disambiguation(chunk, blueprint).await?;
+        let resolutions = call_haiku_for_disambiguation(chunk, blueprint, corrections).await?;
         resolved.extend(resolutions);
     }
 
@@ -219,6 +684,7 @@ where
 async fn call_haiku_for_disambiguation(
     files: &[(String, String, Vec<(String, f32)>)],
     blueprint: &Blueprint,
+    corrections: &[Correction],
 ) -> Result<Vec<(String, String)>, String> {
     // Get API key
     let api_key = CredentialManager::get_api_key("anthropic")?;
@@ -229,7 +695,7 @@ async fn call_haiku_for_disambiguation(
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
     // Build context with file details and candidate folders
-    let prompt = build_disambiguation_prompt(files, blueprint);
+    let prompt = build_disambiguation_prompt(files, blueprint, corrections);
 
     let request = HaikuApiRequest {
         model: ClaudeModel::Haiku.as_str().to_string(),
@@ -307,6 +773,7 @@ async fn call_haiku_for_disambiguation(
 fn build_disambiguation_prompt(
     files: &[(String, String, Vec<(String, f32)>)],
     blueprint: &Blueprint,
+    corrections: &[Correction],
 ) -> String {
     let mut prompt = String::new();
 
@@ -321,6 +788,26 @@ fn build_disambiguation_prompt(
     }
     prompt.push_str("\n");
 
+    // Past corrections between the folders in question
+    let candidate_folders: Vec<&str> = files
+        .iter()
+        .flat_map(|(_, _, candidates)| candidates.iter().map(|(folder, _)| folder.as_str()))
+        .collect();
+    let examples = few_shot_examples(
+        corrections.iter().filter(|c| {
+            candidate_folders.contains(&c.planned_folder.as_str())
+                || c.chosen_folder
+                    .as_deref()
+                    .is_some_and(|folder| candidate_folders.contains(&folder))
+        }),
+        MAX_PROMPT_EXAMPLES,
+    );
+    if !examples.is_empty() {
+        prompt.push_str("The user corrected these placements before; follow their preferences:\n");
+        prompt.push_str(&examples);
+        prompt.push_str("\n");
+    }
+
     prompt.push_str("Files to categorize:\n");
     for (i, (file_path, file_name, candidates)) in files.iter().enumerate() {
         prompt.push_str(&format!("{}. {} (path: {})\n", i + 1, file_name, file_path));
@@ -376,7 +863,7 @@ fn parse_disambiguation_response(
 }
 
 /// Compute cosine similarity between two vectors
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+pub(super) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
@@ -485,6 +972,167 @@ mod tests {
         assert!((cosine_similarity(&a, &d) - expected).abs() < 0.001);
     }
 
+    #[test]
+    fn test_settle_tier2() {
+        let blueprint = Blueprint {
+            strategy_name: "Receipts".to_string(),
+            structure: ["Receipts/2024", "Receipts/2023"]
+                .iter()
+                .map(|path| architect::BlueprintFolder {
+                    path: path.to_string(),
+                    semantic_description: String::new(),
+                    expected_extensions: Vec::new(),
+                    embedding: None,
+                })
+                .collect(),
+            extraction_rules: String::new(),
+            description: None,
+            confidence: 0.8,
+            thresholds: MatchThresholds::default(),
+        };
+        let candidates = vec![("Receipts/2023".to_string(), 0.7), ("Receipts/2024".to_string(), 0.6)];
+        let ambiguous = vec![
+            ("/a.pdf".to_string(), "a".to_string(), candidates.clone()),
+            ("/b.pdf".to_string(), "b".to_string(), candidates.clone()),
+            ("/c.pdf".to_string(), "c".to_string(), candidates),
+        ];
+        // The LLM answers one file well and invents a folder for another
+        let resolved = vec![
+            ("/a.pdf".to_string(), "Receipts/2024".to_string()),
+            ("/b.pdf".to_string(), "Invoices".to_string()),
+        ];
+
+        let mut explanations: HashMap<String, MatchExplanation> = ["/a.pdf", "/b.pdf"]
+            .iter()
+            .map(|path| {
+                let explanation = MatchExplanation {
+                    tier: MatchTier::BestCandidate,
+                    folder: Some("Receipts/2023".to_string()),
+                    top_folders: Vec::new(),
+                    extension: Some("pdf".to_string()),
+                    correction: None,
+                    thresholds: MatchThresholds::default(),
+                };
+                (path.to_string(), explanation)
+            })
+            .collect();
+
+        let settled = settle_tier2(&ambiguous, &resolved, &blueprint, &mut explanations);
+        assert_eq!(explanations["/a.pdf"].tier, MatchTier::Llm);
+        assert_eq!(explanations["/a.pdf"].folder.as_deref(), Some("Receipts/2024"));
+        assert_eq!(explanations["/b.pdf"].tier, MatchTier::BestCandidate);
+        assert_eq!(
+            settled,
+            vec![
+                ("/a.pdf".to_string(), "Receipts/2024".to_string()),
+                ("/b.pdf".to_string(), "Receipts/2023".to_string()),
+                ("/c.pdf".to_string(), "Receipts/2023".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_embedding_skips_rejected_folder() {
+        let folder = |path: &str, embedding: Vec<f32>| architect::BlueprintFolder {
+            path: path.to_string(),
+            semantic_description: String::new(),
+            expected_extensions: Vec::new(),
+            embedding: Some(embedding),
+        };
+        let blueprint = Blueprint {
+            strategy_name: "By type".to_string(),
+            structure: vec![folder("Receipts", vec![1.0, 0.0]), folder("Finance", vec![0.0, 1.0])],
+            extraction_rules: String::new(),
+            description: None,
+            confidence: 0.8,
+            thresholds: MatchThresholds::default(),
+        };
+        let file = [0.8, 0.6];
+        let candidates = |result: MatchResult| match result {
+            MatchResult::Tier2Ambiguous { candidates, .. } => {
+                candidates.into_iter().map(|(folder, _)| folder).collect::<Vec<_>>()
+            }
+            other => panic!("expected Tier 2, got {:?}", other),
+        };
+
+        let no_priors = CorrectionPriors::default();
+        let plain = match_embedding("/a.pdf", "a", Some("pdf"), &file, &blueprint, &no_priors);
+        assert_eq!(candidates(plain), vec!["Receipts", "Finance"]);
+
+        // A near-identical file was planned for Receipts and left in place
+        let rejected = Correction {
+            id: 1,
+            target_folder: "/data".to_string(),
+            file_name: "b.pdf".to_string(),
+            extension: Some("pdf".to_string()),
+            size: None,
+            planned_folder: "Receipts".to_string(),
+            chosen_folder: None,
+            embedding: Some(vec![0.81, 0.59]),
+            recorded_at: chrono::Utc::now(),
+        };
+        let priors = CorrectionPriors::new([&rejected]);
+        let learned = match_embedding("/a.pdf", "a", Some("pdf"), &file, &blueprint, &priors);
+        assert_eq!(learned.explanation().correction.as_ref().unwrap().file_name, "b.pdf");
+        assert_eq!(candidates(learned), vec!["Finance"]);
+    }
+
+    #[test]
+    fn test_match_thresholds_and_explanation() {
+        let folder = |path: &str, extensions: &[&str], embedding: Vec<f32>| architect::BlueprintFolder {
+            path: path.to_string(),
+            semantic_description: String::new(),
+            expected_extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            embedding: Some(embedding),
+        };
+        let mut blueprint = Blueprint {
+            strategy_name: "By type".to_string(),
+            structure: vec![
+                folder("Receipts", &[".pdf"], vec![1.0, 0.0]),
+                folder("Finance", &[], vec![0.0, 1.0]),
+            ],
+            extraction_rules: String::new(),
+            description: None,
+            confidence: 0.8,
+            thresholds: MatchThresholds::default(),
+        };
+        let file = [0.8, 0.6];
+        let priors = CorrectionPriors::default();
+
+        // 0.8 is below the default Tier 1 threshold
+        let ambiguous = match_embedding("/r.pdf", "r", Some("PDF"), &file, &blueprint, &priors);
+        let explanation = ambiguous.explanation();
+        assert!(matches!(ambiguous, MatchResult::Tier2Ambiguous { .. }));
+        assert_eq!(explanation.tier, MatchTier::BestCandidate);
+        let top: Vec<(&str, bool)> = explanation
+            .top_folders
+            .iter()
+            .map(|f| (f.folder.as_str(), f.extension_match))
+            .collect();
+        assert_eq!(top, vec![("Receipts", true), ("Finance", false)]);
+
+        blueprint.thresholds.tier1 = 0.75;
+        let vector = match_embedding("/r.pdf", "r", Some("pdf"), &file, &blueprint, &priors);
+        assert!(matches!(vector, MatchResult::Tier1Match { .. }));
+        assert_eq!(
+            vector.explanation().summary(),
+            "Tier 1: 80% similar to Receipts (threshold 75%), .pdf expected there"
+        );
+
+        // The extension hint alone lifts Receipts over the default threshold
+        blueprint.thresholds = MatchThresholds {
+            extension_bonus: 0.1,
+            ..MatchThresholds::default()
+        };
+        let hinted = match_embedding("/r.pdf", "r", Some("pdf"), &file, &blueprint, &priors);
+        assert_eq!(hinted.explanation().tier, MatchTier::Vector);
+        let plain = match_embedding("/r.txt", "r", Some("txt"), &file, &blueprint, &priors);
+        assert_eq!(plain.explanation().tier, MatchTier::BestCandidate);
+
+        blueprint.thresholds.min_match = 0.9;
+        assert!(blueprint.thresholds.validate().is_err());
+    }
+
     #[test]
     fn test_parse_disambiguation_response() {
         let text = "1: Documents/Invoices\n2: Media/Photos\n3: Misc";
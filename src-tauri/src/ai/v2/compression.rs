@@ -432,6 +432,7 @@ mod tests {
             modified_at: Some(0),
             created_at: Some(0),
             mime_type: None,
+            doc: None,
         }
     }
 
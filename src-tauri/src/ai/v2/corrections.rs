@@ -0,0 +1,586 @@
+//! Corrections - learning from plan edits.
+//!
+//! When the user retargets or excludes a planned move, the edit is kept as a
+//! [`Correction`] for the target folder: the file's features, the folder the
+//! plan chose and the folder the user chose instead (none when they kept the
+//! file where it was). Corrections are used two ways:
+//!
+//! - **Few-shot examples** in the Architect and Haiku prompts
+//! - **Nearest-neighbour priors** in the Builder: a file that embeds close to
+//!   a corrected one goes where the user put that one, before any LLM call
+//!
+//! [`evaluate_priors`] replays the recorded corrections in order to measure
+//! how much the priors improve match accuracy.
+
+use super::architect::Blueprint;
+use super::builder::{cosine_similarity, match_embedding, MatchResult};
+use super::local_vector_index::LocalVectorIndex;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A past correction this close to a file decides where the file goes
+pub const PRIOR_MATCH_THRESHOLD: f32 = 0.9;
+
+/// Corrections kept per target folder (oldest are dropped first)
+const MAX_PER_FOLDER: usize = 500;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS corrections (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        target_folder TEXT NOT NULL,
+        file_name TEXT NOT NULL,
+        extension TEXT,
+        size INTEGER,
+        planned_folder TEXT NOT NULL,
+        chosen_folder TEXT,
+        embedding BLOB,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_corrections_folder ON corrections(target_folder, recorded_at);
+"#;
+
+/// One planned placement the user changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Correction {
+    /// Row id (0 until recorded)
+    pub id: i64,
+    /// Folder that was organized
+    pub target_folder: String,
+    pub file_name: String,
+    pub extension: Option<String>,
+    pub size: Option<u64>,
+    /// Folder the plan moved the file into, relative to the target ("" = top level)
+    pub planned_folder: String,
+    /// Folder the user moved it into instead; `None` when they rejected the move
+    pub chosen_folder: Option<String>,
+    /// Embedding of [`Correction::embedding_text`], filled in the first time
+    /// the Builder needs it
+    #[serde(skip)]
+    pub embedding: Option<Vec<f32>>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl Correction {
+    /// Text embedded for the file, the same way the Builder embeds files
+    pub fn embedding_text(&self) -> String {
+        let stem = Path::new(&self.file_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.file_name.clone());
+        format!("{} {}", stem, self.extension.as_deref().unwrap_or(""))
+    }
+
+    /// One line for a few-shot prompt section
+    pub fn describe(&self) -> String {
+        let planned = display_folder(&self.planned_folder);
+        match &self.chosen_folder {
+            Some(chosen) => format!(
+                "- {}: planned for {}, the user moved it to {}",
+                self.file_name,
+                planned,
+                display_folder(chosen)
+            ),
+            None => format!(
+                "- {}: planned for {}, the user kept it where it was",
+                self.file_name, planned
+            ),
+        }
+    }
+}
+
+fn display_folder(folder: &str) -> &str {
+    if folder.is_empty() {
+        "the top level"
+    } else {
+        folder
+    }
+}
+
+/// Few-shot lines for the `limit` most recent corrections, or an empty
+/// string when there are none
+pub fn few_shot_examples<'a>(
+    corrections: impl IntoIterator<Item = &'a Correction>,
+    limit: usize,
+) -> String {
+    corrections
+        .into_iter()
+        .take(limit)
+        .map(|c| format!("{}\n", c.describe()))
+        .collect()
+}
+
+/// SQLite-backed correction history
+pub struct CorrectionStore {
+    db_path: PathBuf,
+}
+
+impl CorrectionStore {
+    /// Default store directory (next to the watch journal)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("com.sentinel.filemanager"))
+    }
+
+    /// Open the store in the default location
+    pub fn open_default() -> Result<Self, String> {
+        let dir = Self::default_dir().ok_or("Could not determine cache directory")?;
+        Self::open(&dir)
+    }
+
+    /// Open or create the corrections database in `dir`
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create corrections directory: {}", e))?;
+
+        let store = Self {
+            db_path: dir.join("corrections.db"),
+        };
+        store
+            .conn()?
+            .execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize corrections: {}", e))?;
+        Ok(store)
+    }
+
+    fn conn(&self) -> Result<rusqlite::Connection, String> {
+        rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open corrections: {}", e))
+    }
+
+    /// Store corrections and drop the oldest past the per-folder limit
+    pub fn record(&self, corrections: &[Correction]) -> Result<usize, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to record corrections: {}", e);
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(db_err)?;
+        for correction in corrections {
+            tx.execute(
+                "INSERT INTO corrections (target_folder, file_name, extension, size,
+                    planned_folder, chosen_folder, embedding, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    correction.target_folder,
+                    correction.file_name,
+                    correction.extension,
+                    correction.size.map(|s| s as i64),
+                    correction.planned_folder,
+                    correction.chosen_folder,
+                    correction.embedding.as_deref().map(to_blob),
+                    correction.recorded_at.timestamp_millis(),
+                ],
+            )
+            .map_err(db_err)?;
+        }
+
+        let mut folders: Vec<&str> = corrections
+            .iter()
+            .map(|c| c.target_folder.as_str())
+            .collect();
+        folders.sort_unstable();
+        folders.dedup();
+        for folder in folders {
+            tx.execute(
+                "DELETE FROM corrections WHERE target_folder = ?1 AND id NOT IN (
+                    SELECT id FROM corrections WHERE target_folder = ?1
+                    ORDER BY recorded_at DESC, id DESC LIMIT ?2)",
+                params![folder, MAX_PER_FOLDER as i64],
+            )
+            .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)?;
+        Ok(corrections.len())
+    }
+
+    /// Corrections recorded for a target folder, newest first
+    pub fn for_folder(&self, target_folder: &str, limit: usize) -> Result<Vec<Correction>, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to query corrections: {}", e);
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, target_folder, file_name, extension, size, planned_folder,
+                    chosen_folder, embedding, recorded_at
+                 FROM corrections WHERE target_folder = ?1
+                 ORDER BY recorded_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![target_folder, limit as i64], |row| {
+                let size: Option<i64> = row.get(4)?;
+                let embedding: Option<Vec<u8>> = row.get(7)?;
+                Ok(Correction {
+                    id: row.get(0)?,
+                    target_folder: row.get(1)?,
+                    file_name: row.get(2)?,
+                    extension: row.get(3)?,
+                    size: size.map(|s| s.max(0) as u64),
+                    planned_folder: row.get(5)?,
+                    chosen_folder: row.get(6)?,
+                    embedding: embedding.as_deref().map(from_blob),
+                    recorded_at: from_millis(row.get(8)?),
+                })
+            })
+            .map_err(db_err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+    }
+
+    /// Cache the embedding of a recorded correction
+    pub fn set_embedding(&self, id: i64, embedding: &[f32]) -> Result<(), String> {
+        self.conn()?
+            .execute(
+                "UPDATE corrections SET embedding = ?1 WHERE id = ?2",
+                params![to_blob(embedding), id],
+            )
+            .map_err(|e| format!("Failed to store correction embedding: {}", e))?;
+        Ok(())
+    }
+
+    /// Forget the corrections of a target folder; returns how many were removed
+    pub fn clear(&self, target_folder: &str) -> Result<usize, String> {
+        self.conn()?
+            .execute(
+                "DELETE FROM corrections WHERE target_folder = ?1",
+                params![target_folder],
+            )
+            .map_err(|e| format!("Failed to clear corrections: {}", e))
+    }
+}
+
+/// Corrections of a target folder, newest first, with embeddings filled in
+/// when an index is given. Failures are logged and yield no corrections, so
+/// planning never fails because of its history.
+pub fn load_corrections(target_folder: &Path, index: Option<&LocalVectorIndex>) -> Vec<Correction> {
+    let loaded = CorrectionStore::open_default().and_then(|store| {
+        let mut corrections = store.for_folder(&target_folder.to_string_lossy(), MAX_PER_FOLDER)?;
+        if let Some(index) = index {
+            embed_missing(&store, &mut corrections, index)?;
+        }
+        Ok(corrections)
+    });
+    match loaded {
+        Ok(corrections) => corrections,
+        Err(e) => {
+            eprintln!("[Corrections] Failed to load corrections: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Embed corrections recorded without an embedding and cache the result
+fn embed_missing(
+    store: &CorrectionStore,
+    corrections: &mut [Correction],
+    index: &LocalVectorIndex,
+) -> Result<(), String> {
+    let missing: Vec<usize> = (0..corrections.len())
+        .filter(|&i| corrections[i].embedding.is_none())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let texts: Vec<String> = missing
+        .iter()
+        .map(|&i| corrections[i].embedding_text())
+        .collect();
+    let text_refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+    let embeddings = index
+        .embed_texts(&text_refs)
+        .map_err(|e| format!("Failed to embed corrections: {}", e))?;
+
+    for (&i, embedding) in missing.iter().zip(embeddings) {
+        store.set_embedding(corrections[i].id, &embedding)?;
+        corrections[i].embedding = Some(embedding);
+    }
+    eprintln!("[Corrections] Embedded {} corrections", missing.len());
+    Ok(())
+}
+
+/// The closest past correction to a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriorMatch<'a> {
+    pub file_name: &'a str,
+    pub planned_folder: &'a str,
+    pub chosen_folder: Option<&'a str>,
+    pub similarity: f32,
+}
+
+/// Nearest-neighbour lookup over embedded corrections
+#[derive(Debug, Clone, Default)]
+pub struct CorrectionPriors {
+    examples: Vec<Correction>,
+}
+
+impl CorrectionPriors {
+    /// Priors from the corrections that have an embedding
+    pub fn new<'a>(corrections: impl IntoIterator<Item = &'a Correction>) -> Self {
+        Self {
+            examples: corrections
+                .into_iter()
+                .filter(|c| c.embedding.is_some())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    /// The most similar correction at or above `threshold` (usually
+    /// [`PRIOR_MATCH_THRESHOLD`]); on a tie the most recent one wins
+    pub fn nearest(&self, embedding: &[f32], threshold: f32) -> Option<PriorMatch<'_>> {
+        let mut best: Option<(&Correction, f32)> = None;
+        for example in &self.examples {
+            let Some(example_embedding) = &example.embedding else {
+                continue;
+            };
+            let similarity = cosine_similarity(embedding, example_embedding);
+            let better = match best {
+                None => true,
+                Some((current, score)) => {
+                    similarity > score
+                        || (similarity == score && example.recorded_at > current.recorded_at)
+                }
+            };
+            if similarity >= threshold && better {
+                best = Some((example, similarity));
+            }
+        }
+        best.map(|(example, similarity)| PriorMatch {
+            file_name: &example.file_name,
+            planned_folder: &example.planned_folder,
+            chosen_folder: example.chosen_folder.as_deref(),
+            similarity,
+        })
+    }
+}
+
+/// Accuracy of Blueprint matching with and without correction priors
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorEvaluation {
+    /// Corrections replayed (those moved into one of the Blueprint's folders)
+    pub cases: usize,
+    /// Cases where plain vector matching picks the user's folder
+    pub baseline_correct: usize,
+    /// Cases where matching with the earlier corrections picks it
+    pub with_priors_correct: usize,
+    pub baseline_accuracy: f32,
+    pub with_priors_accuracy: f32,
+}
+
+/// Replay corrections oldest first: each one is matched against an embedded
+/// Blueprint as a fresh file, once without priors and once with the
+/// corrections recorded before it, and counts as correct when the top
+/// folder is the one the user chose. Tier 2 files count their best
+/// candidate, as if planned without the LLM.
+pub fn evaluate_priors(corrections: &[Correction], blueprint: &Blueprint) -> PriorEvaluation {
+    let mut ordered: Vec<&Correction> = corrections.iter().collect();
+    ordered.sort_by_key(|c| (c.recorded_at, c.id));
+
+    let no_priors = CorrectionPriors::default();
+    let mut evaluation = PriorEvaluation::default();
+    for (i, correction) in ordered.iter().enumerate() {
+        let (Some(embedding), Some(chosen)) = (&correction.embedding, &correction.chosen_folder)
+        else {
+            continue;
+        };
+        if !blueprint.structure.iter().any(|f| &f.path == chosen) {
+            continue;
+        }
+
+        let earlier = CorrectionPriors::new(ordered[..i].iter().copied());
+        let predict = |priors: &CorrectionPriors| {
+            let result = match_embedding(
+                &correction.file_name,
+                &correction.file_name,
+                correction.extension.as_deref(),
+                embedding,
+                blueprint,
+                priors,
+            );
+            top_folder(&result).map(str::to_string)
+        };
+
+        evaluation.cases += 1;
+        if predict(&no_priors).as_deref() == Some(chosen.as_str()) {
+            evaluation.baseline_correct += 1;
+        }
+        if predict(&earlier).as_deref() == Some(chosen.as_str()) {
+            evaluation.with_priors_correct += 1;
+        }
+    }
+
+    if evaluation.cases > 0 {
+        evaluation.baseline_accuracy = evaluation.baseline_correct as f32 / evaluation.cases as f32;
+        evaluation.with_priors_accuracy =
+            evaluation.with_priors_correct as f32 / evaluation.cases as f32;
+    }
+    evaluation
+}
+
+fn top_folder(result: &MatchResult) -> Option<&str> {
+    match result {
+        MatchResult::Tier1Match {
+            destination_folder, ..
+        } => Some(destination_folder),
+        MatchResult::Tier2Ambiguous { candidates, .. } => {
+            candidates.first().map(|(folder, _)| folder.as_str())
+        }
+        MatchResult::NoMatch { .. } => None,
+    }
+}
+
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::v2::architect::BlueprintFolder;
+    use tempfile::TempDir;
+
+    fn correction(
+        name: &str,
+        planned: &str,
+        chosen: Option<&str>,
+        embedding: Vec<f32>,
+        minute: u32,
+    ) -> Correction {
+        Correction {
+            id: 0,
+            target_folder: "/data".to_string(),
+            file_name: name.to_string(),
+            extension: Path::new(name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_string()),
+            size: Some(1024),
+            planned_folder: planned.to_string(),
+            chosen_folder: chosen.map(str::to_string),
+            embedding: Some(embedding),
+            recorded_at: Utc.with_ymd_and_hms(2024, 5, 1, 9, minute, 0).unwrap(),
+        }
+    }
+
+    fn folder(path: &str, embedding: Vec<f32>) -> BlueprintFolder {
+        BlueprintFolder {
+            path: path.to_string(),
+            semantic_description: path.to_string(),
+            expected_extensions: Vec::new(),
+            embedding: Some(embedding),
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = CorrectionStore::open(dir.path()).unwrap();
+
+        let mut first = correction(
+            "invoice_march.pdf",
+            "Receipts",
+            Some("Finance/Invoices"),
+            vec![1.0, 0.0],
+            0,
+        );
+        first.embedding = None;
+        let second = correction("notes.txt", "", None, vec![0.25, -0.5], 1);
+        let mut other = correction("a.jpg", "Photos", Some("Media"), vec![0.0, 1.0], 2);
+        other.target_folder = "/other".to_string();
+        assert_eq!(store.record(&[first, second, other]).unwrap(), 3);
+
+        let recorded = store.for_folder("/data", 10).unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].file_name, "notes.txt");
+        assert_eq!(recorded[0].chosen_folder, None);
+        assert_eq!(recorded[0].embedding, Some(vec![0.25, -0.5]));
+        assert_eq!(recorded[1].embedding, None);
+        assert_eq!(recorded[1].embedding_text(), "invoice_march pdf");
+
+        store.set_embedding(recorded[1].id, &[0.5, 0.5]).unwrap();
+        let recorded = store.for_folder("/data", 10).unwrap();
+        assert_eq!(recorded[1].embedding, Some(vec![0.5, 0.5]));
+
+        assert_eq!(store.clear("/data").unwrap(), 2);
+        assert!(store.for_folder("/data", 10).unwrap().is_empty());
+        assert_eq!(store.for_folder("/other", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_priors_improve_accuracy() {
+        // Invoices embed closer to Receipts than to Finance, so plain
+        // matching keeps misfiling them
+        let blueprint = Blueprint {
+            strategy_name: "By type".to_string(),
+            structure: vec![
+                folder("Receipts", vec![1.0, 0.0, 0.0]),
+                folder("Finance", vec![0.0, 1.0, 0.0]),
+            ],
+            extraction_rules: String::new(),
+            description: None,
+            confidence: 0.9,
+            thresholds: Default::default(),
+        };
+        let invoice = vec![0.9, 0.3, 0.1];
+        let corrections = vec![
+            correction(
+                "invoice-01.pdf",
+                "Receipts",
+                Some("Finance"),
+                invoice.clone(),
+                0,
+            ),
+            correction(
+                "invoice-02.pdf",
+                "Receipts",
+                Some("Finance"),
+                vec![0.88, 0.32, 0.1],
+                1,
+            ),
+            correction(
+                "invoice-03.pdf",
+                "Receipts",
+                Some("Finance"),
+                vec![0.91, 0.29, 0.12],
+                2,
+            ),
+            correction("draft.txt", "Receipts", None, vec![0.0, 0.0, 1.0], 3),
+        ];
+
+        let priors = CorrectionPriors::new(&corrections);
+        assert_eq!(priors.len(), 4);
+        let nearest = priors.nearest(&invoice, PRIOR_MATCH_THRESHOLD).unwrap();
+        assert_eq!(nearest.chosen_folder, Some("Finance"));
+        assert!(priors.nearest(&[0.0, 1.0, 0.0], PRIOR_MATCH_THRESHOLD).is_none());
+
+        let evaluation = evaluate_priors(&corrections, &blueprint);
+        assert_eq!(evaluation.cases, 3);
+        assert_eq!(evaluation.baseline_correct, 0);
+        // The first invoice has nothing to learn from yet
+        assert_eq!(evaluation.with_priors_correct, 2);
+        assert!(evaluation.with_priors_accuracy > evaluation.baseline_accuracy);
+
+        let prompt = few_shot_examples(&corrections[2..], 5);
+        assert!(
+            prompt.contains("invoice-03.pdf: planned for Receipts, the user moved it to Finance")
+        );
+        assert!(prompt.contains("draft.txt: planned for Receipts, the user kept it where it was"));
+    }
+}
@@ -0,0 +1,598 @@
+//! Eval - offline organize quality against golden layouts.
+//!
+//! An [`EvalCase`] is a JSON file naming a fixture folder and the folder
+//! every fixture file should end up in. [`run_eval_case`] plans the fixture
+//! the way the app does, without LLM calls (fastembed downloads the local
+//! embedding model on first use; later runs are fully offline):
+//!
+//! 1. **Rules**: the case's rules through the rules DSL
+//! 2. **Blueprint**: the case's Blueprint (a recorded Architect answer) is
+//!    embedded locally
+//! 3. **Builder**: files no rule matched are slotted with Tier 1/Tier 2
+//!    matching; Tier 2 files take the recorded Haiku answer when the case has
+//!    one and their best candidate otherwise. Past corrections are not used.
+//!
+//! Misplaced files carry the Builder's explanation of their match, so a
+//! regression can be traced to a threshold or a folder description.
+//!
+//! The [`EvalReport`] has precision/recall per destination folder, coverage
+//! from `ShadowVFS::coverage`, the operation count, and hologram, sample and
+//! Architect prompt sizes. Maps are sorted and scores rounded, so reports of
+//! two runs can be diffed or compared with [`EvalReport::compare`].
+
+use super::architect::{self, Blueprint};
+use super::builder::{
+    batch_match_files, explain_plan, generate_operations_from_matches, settle_tier2,
+};
+use super::compression;
+use super::corrections::CorrectionPriors;
+use super::sampling;
+use super::vfs::{OrganizationRule, ShadowVFS};
+use crate::jobs::OrganizePlan;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Folder key for files at the top level of the fixture
+const TOP_LEVEL: &str = ".";
+
+/// A fixture folder with its golden layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalCase {
+    pub name: String,
+    /// Fixture folder, relative to the case file
+    pub fixture: String,
+    /// Where each file should end up: file path -> folder, both relative to
+    /// the fixture ("." or "" for the top level)
+    pub expected: BTreeMap<String, String>,
+    /// Rules applied before Blueprint matching
+    #[serde(default)]
+    pub rules: Vec<OrganizationRule>,
+    /// Recorded Architect output for the Builder stage
+    #[serde(default)]
+    pub blueprint: Option<Blueprint>,
+    /// User request, only used to measure the Architect prompt
+    #[serde(default)]
+    pub instruction: Option<String>,
+    /// Recorded Haiku answers for Tier 2 files: file path -> folder
+    #[serde(default)]
+    pub tier2_answers: BTreeMap<String, String>,
+    /// Folder for files that match nothing (they stay in place when unset)
+    #[serde(default)]
+    pub unmatched_folder: Option<String>,
+}
+
+impl EvalCase {
+    pub fn read_from(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read eval case {}: {}", path.display(), e))?;
+        serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid eval case {}: {}", path.display(), e))
+    }
+}
+
+/// Precision and recall of one destination folder (labeled files only)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScore {
+    /// Files the golden layout puts here
+    pub expected: usize,
+    /// Files the plan puts here
+    pub predicted: usize,
+    pub correct: usize,
+    /// None when nothing was predicted here
+    pub precision: Option<f64>,
+    /// None when nothing is expected here
+    pub recall: Option<f64>,
+}
+
+/// A labeled file the plan put in the wrong folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Misplacement {
+    pub file: String,
+    pub expected: String,
+    pub predicted: String,
+    /// How the Builder matched the file (none for rule matches)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub why: Option<String>,
+}
+
+/// What each stage did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageCounts {
+    /// Files matched by a rule
+    pub rule_matches: usize,
+    /// Rules that failed to parse, as "name: error"
+    pub rule_errors: Vec<String>,
+    pub tier1: usize,
+    pub tier2: usize,
+    /// Tier 2 files placed by a recorded answer (the rest took their best candidate)
+    pub tier2_replayed: usize,
+    pub unmatched: usize,
+}
+
+/// Size of the context the LLM would see
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextStats {
+    pub sample_size: usize,
+    pub hologram_patterns: usize,
+    pub hologram_outliers: usize,
+    pub hologram_coverage: f64,
+    /// Only measured when the case has an instruction
+    pub architect_prompt_chars: Option<usize>,
+}
+
+/// Result of [`run_eval_case`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalReport {
+    pub case: String,
+    pub files: usize,
+    /// Files listed in the golden layout
+    pub labeled: usize,
+    pub correct: usize,
+    /// `correct / labeled`
+    pub accuracy: f64,
+    /// Share of files matched by a rule or placed by the Builder
+    pub coverage: f64,
+    pub operations: usize,
+    pub moves: usize,
+    pub stages: StageCounts,
+    pub context: ContextStats,
+    pub folders: BTreeMap<String, FolderScore>,
+    pub misplaced: Vec<Misplacement>,
+    /// Planned folder of every fixture file
+    pub layout: BTreeMap<String, String>,
+}
+
+impl EvalReport {
+    /// Write the report as pretty JSON
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize eval report: {}", e))?;
+        std::fs::write(path, json + "\n")
+            .map_err(|e| format!("Failed to write eval report to {}: {}", path.display(), e))
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read eval report {}: {}", path.display(), e))?;
+        serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid eval report {}: {}", path.display(), e))
+    }
+
+    /// Metrics that changed since `baseline`, one line each
+    pub fn compare(&self, baseline: &EvalReport) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut changed = |label: &str, before: Option<f64>, after: Option<f64>| {
+            if before != after {
+                changes.push(format!(
+                    "{}: {} -> {}",
+                    label,
+                    fmt_ratio(before),
+                    fmt_ratio(after)
+                ));
+            }
+        };
+        changed("accuracy", Some(baseline.accuracy), Some(self.accuracy));
+        changed("coverage", Some(baseline.coverage), Some(self.coverage));
+
+        let mut folders: Vec<&String> =
+            baseline.folders.keys().chain(self.folders.keys()).collect();
+        folders.sort();
+        folders.dedup();
+        for folder in folders {
+            let before = baseline.folders.get(folder).cloned().unwrap_or_default();
+            let after = self.folders.get(folder).cloned().unwrap_or_default();
+            changed(
+                &format!("{} precision", folder),
+                before.precision,
+                after.precision,
+            );
+            changed(&format!("{} recall", folder), before.recall, after.recall);
+        }
+
+        for (label, before, after) in [
+            ("operations", baseline.operations, self.operations),
+            ("moves", baseline.moves, self.moves),
+        ] {
+            if before != after {
+                changes.push(format!("{}: {} -> {}", label, before, after));
+            }
+        }
+
+        let was_misplaced: HashSet<&str> =
+            baseline.misplaced.iter().map(|m| m.file.as_str()).collect();
+        let is_misplaced: HashSet<&str> = self.misplaced.iter().map(|m| m.file.as_str()).collect();
+        for m in &self.misplaced {
+            if !was_misplaced.contains(m.file.as_str()) {
+                changes.push(format!(
+                    "now misplaced: {} in {} (expected {})",
+                    m.file, m.predicted, m.expected
+                ));
+            }
+        }
+        for m in &baseline.misplaced {
+            if !is_misplaced.contains(m.file.as_str()) {
+                changes.push(format!("fixed: {} in {}", m.file, m.expected));
+            }
+        }
+        changes
+    }
+}
+
+fn fmt_ratio(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.4}", v))
+}
+
+/// Plan a case's fixture offline and score it against the golden layout.
+///
+/// Scanning builds the local embedding index, so the embedding model must
+/// be available; nothing else leaves the machine.
+pub fn run_eval_case(case_path: &Path) -> Result<EvalReport, String> {
+    let case = EvalCase::read_from(case_path)?;
+    let fixture = case_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(&case.fixture);
+    let root = std::fs::canonicalize(&fixture)
+        .map_err(|e| format!("Fixture folder {} not found: {}", fixture.display(), e))?;
+    let mut vfs = ShadowVFS::new(&root).map_err(|e| format!("Failed to scan fixture: {}", e))?;
+
+    // Sorted so the same fixture always yields the same plan
+    let mut files = vfs.all_files_vec();
+    files.retain(|f| !f.is_directory);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let hologram = compression::generate_hologram(&files);
+    let mut context = ContextStats {
+        sample_size: sampling::generate_sample(&files, 0).samples.len(),
+        hologram_patterns: hologram.stats.pattern_count,
+        hologram_outliers: hologram.stats.outlier_count,
+        hologram_coverage: round(hologram.stats.pattern_coverage),
+        architect_prompt_chars: None,
+    };
+    if let Some(instruction) = &case.instruction {
+        context.architect_prompt_chars = Some(
+            architect::architect_prompt(instruction, &vfs)?
+                .chars()
+                .count(),
+        );
+    }
+
+    // 1. Rules
+    let mut stages = StageCounts::default();
+    if !case.rules.is_empty() {
+        let result = vfs.apply_rules(&case.rules, "replace")?;
+        stages.rule_matches = vfs.organized_count();
+        stages.rule_errors = result
+            .parsing_errors
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect();
+    }
+
+    // 2. + 3. Blueprint and Builder, for the files no rule matched
+    let mut placements = Vec::new();
+    let mut explanations = HashMap::new();
+    if let Some(blueprint) = &case.blueprint {
+        let blueprint = architect::embed_blueprint(blueprint, &vfs)?;
+        let unmatched: Vec<_> = files
+            .iter()
+            .filter(|f| !vfs.matched_paths().contains(&f.path))
+            .cloned()
+            .collect();
+        let mut matched = batch_match_files(
+            &unmatched,
+            &blueprint,
+            vfs.vector_index(),
+            &CorrectionPriors::default(),
+        )?;
+
+        let replayed: Vec<(String, String)> = matched
+            .tier2_ambiguous
+            .iter()
+            .filter_map(|(path, _, _)| {
+                let answer = case.tier2_answers.get(&relative(&root, Path::new(path)))?;
+                Some((path.clone(), answer.clone()))
+            })
+            .collect();
+        stages.tier1 = matched.tier1_matches.len();
+        stages.tier2 = matched.tier2_ambiguous.len();
+        stages.tier2_replayed = replayed.len();
+        stages.unmatched = matched.no_matches.len();
+
+        let tier2 = settle_tier2(
+            &matched.tier2_ambiguous,
+            &replayed,
+            &blueprint,
+            &mut matched.explanations,
+        );
+        let (no_matches, misc_folder) = match &case.unmatched_folder {
+            Some(folder) => (matched.no_matches.as_slice(), folder.as_str()),
+            None => (&[][..], ""),
+        };
+        placements = generate_operations_from_matches(
+            &matched.tier1_matches,
+            &tier2,
+            no_matches,
+            misc_folder,
+        );
+        vfs.apply_placements(
+            &placements,
+            &format!("Blueprint: {}", blueprint.strategy_name),
+        )?;
+        explanations = matched.explanations;
+    }
+
+    let mut plan = vfs.to_plan(&format!("Eval: {}", case.name));
+    explain_plan(&mut plan, &placements, &explanations);
+    let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+    let layout = predicted_layout(&plan, &root, &paths);
+
+    let mut report = score(&case.expected, &layout)?;
+    let mut why: HashMap<String, String> = explanations
+        .iter()
+        .map(|(path, explanation)| (relative(&root, Path::new(path)), explanation.summary()))
+        .collect();
+    for op in &plan.operations {
+        if let (Some(explanation), Some(source)) = (plan.explanations.get(&op.op_id), &op.source) {
+            why.insert(relative(&root, Path::new(source)), explanation.summary());
+        }
+    }
+    for misplaced in &mut report.misplaced {
+        misplaced.why = why.remove(&misplaced.file);
+    }
+    report.case = case.name;
+    report.files = files.len();
+    report.coverage = round(vfs.coverage());
+    report.operations = plan.operations.len();
+    report.moves = plan
+        .operations
+        .iter()
+        .filter(|op| op.op_type == "move")
+        .count();
+    report.stages = stages;
+    report.context = context;
+    report.layout = layout;
+    Ok(report)
+}
+
+/// Folder each file ends up in after the plan's moves, relative to `root`
+fn predicted_layout(
+    plan: &OrganizePlan,
+    root: &Path,
+    files: &[String],
+) -> BTreeMap<String, String> {
+    let moves: HashMap<&str, &str> = plan
+        .operations
+        .iter()
+        .filter(|op| op.op_type == "move")
+        .filter_map(|op| Some((op.source.as_deref()?, op.destination.as_deref()?)))
+        .collect();
+
+    files
+        .iter()
+        .map(|file| {
+            let now_at = PathBuf::from(moves.get(file.as_str()).copied().unwrap_or(file));
+            let folder = now_at
+                .parent()
+                .map(|dir| relative(root, dir))
+                .unwrap_or_else(|| TOP_LEVEL.to_string());
+            (relative(root, Path::new(file)), folder)
+        })
+        .collect()
+}
+
+/// Score a predicted layout against the golden one. Files the golden layout
+/// lists must exist; files it leaves out are not scored.
+fn score(
+    expected: &BTreeMap<String, String>,
+    layout: &BTreeMap<String, String>,
+) -> Result<EvalReport, String> {
+    let missing: Vec<&str> = expected
+        .keys()
+        .filter(|file| !layout.contains_key(*file))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Golden layout lists files missing from the fixture: {}",
+            missing.join(", ")
+        ));
+    }
+
+    let mut report = EvalReport::default();
+    for (file, expected_folder) in expected {
+        let expected_folder = normalize_folder(expected_folder);
+        let predicted = &layout[file];
+        report.labeled += 1;
+        report
+            .folders
+            .entry(expected_folder.clone())
+            .or_default()
+            .expected += 1;
+        report
+            .folders
+            .entry(predicted.clone())
+            .or_default()
+            .predicted += 1;
+        if *predicted == expected_folder {
+            report.correct += 1;
+            report.folders.entry(expected_folder).or_default().correct += 1;
+        } else {
+            report.misplaced.push(Misplacement {
+                file: file.clone(),
+                expected: expected_folder,
+                predicted: predicted.clone(),
+                why: None,
+            });
+        }
+    }
+
+    for folder in report.folders.values_mut() {
+        folder.precision = (folder.predicted > 0).then(|| ratio(folder.correct, folder.predicted));
+        folder.recall = (folder.expected > 0).then(|| ratio(folder.correct, folder.expected));
+    }
+    if report.labeled > 0 {
+        report.accuracy = ratio(report.correct, report.labeled);
+    }
+    Ok(report)
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    let relative = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    normalize_folder(&relative)
+}
+
+fn normalize_folder(folder: &str) -> String {
+    match folder.trim_matches('/') {
+        "" => TOP_LEVEL.to_string(),
+        folder => folder.to_string(),
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    round(part as f64 / whole as f64)
+}
+
+/// Four decimals, so float noise doesn't show up as a change between runs
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::OrganizeOperation;
+
+    fn plan(moves: &[(&str, &str)]) -> OrganizePlan {
+        OrganizePlan {
+            plan_id: "eval".to_string(),
+            description: String::new(),
+            operations: moves
+                .iter()
+                .enumerate()
+                .map(|(i, (source, destination))| OrganizeOperation {
+                    op_id: format!("op-{}", i),
+                    op_type: "move".to_string(),
+                    source: Some(source.to_string()),
+                    destination: Some(destination.to_string()),
+                    path: None,
+                    new_name: None,
+                    attributes: Default::default(),
+                })
+                .collect(),
+            target_folder: "/fixture".to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_score_layout() {
+        let files: Vec<String> = [
+            "/fixture/a.pdf",
+            "/fixture/b.pdf",
+            "/fixture/c.jpg",
+            "/fixture/old/d.txt",
+            "/fixture/e.tmp",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+        let plan = plan(&[
+            ("/fixture/a.pdf", "/fixture/Docs/a.pdf"),
+            ("/fixture/b.pdf", "/fixture/Photos/b.pdf"),
+            ("/fixture/c.jpg", "/fixture/Photos/c.jpg"),
+        ]);
+        let layout = predicted_layout(&plan, Path::new("/fixture"), &files);
+        assert_eq!(layout["a.pdf"], "Docs");
+        assert_eq!(layout["old/d.txt"], "old");
+        assert_eq!(layout["e.tmp"], ".");
+
+        let expected: BTreeMap<String, String> = [
+            ("a.pdf", "Docs"),
+            ("b.pdf", "Docs/"),
+            ("c.jpg", "Photos"),
+            ("old/d.txt", "Docs"),
+        ]
+        .iter()
+        .map(|(file, folder)| (file.to_string(), folder.to_string()))
+        .collect();
+        let report = score(&expected, &layout).unwrap();
+        assert_eq!(
+            (report.labeled, report.correct, report.accuracy),
+            (4, 2, 0.5)
+        );
+        assert_eq!(
+            report.folders["Docs"],
+            FolderScore {
+                expected: 3,
+                predicted: 1,
+                correct: 1,
+                precision: Some(1.0),
+                recall: Some(0.3333)
+            }
+        );
+        assert_eq!(report.folders["Photos"].precision, Some(0.5));
+        assert_eq!(report.folders["old"].recall, None);
+        assert_eq!(report.misplaced.len(), 2);
+
+        // Fixing b.pdf shows up against the old report
+        let mut fixed_layout = layout.clone();
+        fixed_layout.insert("b.pdf".to_string(), "Docs".to_string());
+        let fixed = score(&expected, &fixed_layout).unwrap();
+        let changes = fixed.compare(&report);
+        assert!(changes.contains(&"accuracy: 0.5000 -> 0.7500".to_string()));
+        assert!(changes.contains(&"Photos precision: 0.5000 -> 1.0000".to_string()));
+        assert!(changes.contains(&"fixed: b.pdf in Docs".to_string()));
+        assert!(report.compare(&report).is_empty());
+
+        let mut stale = expected.clone();
+        stale.insert("gone.pdf".to_string(), "Docs".to_string());
+        assert!(score(&stale, &layout).is_err());
+    }
+
+    #[test]
+    fn test_fixture_case_matches_fixture() {
+        let case_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/eval/downloads.json");
+        let case = EvalCase::read_from(&case_path).unwrap();
+        let fixture = case_path.parent().unwrap().join(&case.fixture);
+        for file in case.expected.keys().chain(case.tier2_answers.keys()) {
+            assert!(
+                fixture.join(file).is_file(),
+                "{} missing from the fixture",
+                file
+            );
+        }
+        assert_eq!(case.blueprint.unwrap().structure.len(), 4);
+    }
+
+    /// Run with `cargo test -- --ignored eval`
+    #[test]
+    #[ignore = "needs the embedding model (downloaded by fastembed on first use)"]
+    fn test_fixture_report_is_stable() {
+        let case_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/eval/downloads.json");
+        let first = run_eval_case(&case_path).unwrap();
+        let second = run_eval_case(&case_path).unwrap();
+
+        assert!(second.compare(&first).is_empty());
+        assert_eq!(
+            serde_json::to_string_pretty(&first).unwrap(),
+            serde_json::to_string_pretty(&second).unwrap()
+        );
+    }
+}
@@ -10,6 +10,8 @@
 //! V6 Features (new):
 //! - **Architect module**: Generates Blueprint from user instruction + semantic sample
 //! - **Builder module**: Tiered file matching (vector first, LLM fallback)
+//! - **Corrections module**: Learns from the user's plan edits (few-shot examples and matching priors)
+//! - **Eval module**: Offline organize quality against golden layouts
 //!
 //! Tools available to the agent:
 //! - `query_semantic_index`: Search files by semantic similarity
@@ -23,23 +25,31 @@ mod analytics;
 pub mod architect;
 pub mod builder;
 pub mod compression;
+pub mod corrections;
+pub mod eval;
 mod local_vector_index;
 mod prompts;
 mod rate_limiter;
 mod sampling;
-mod tools;
-mod vfs;
+pub(crate) mod tools;
+pub(crate) mod vfs;
 
 pub mod agent_loop;
 
 // Public exports
-pub use agent_loop::{run_v2_agentic_organize, ExpandableDetail, ProgressEvent};
+pub use agent_loop::{
+    run_v2_agentic_organize, run_v2_agentic_organize_across,
+    run_v2_agentic_organize_with_blueprint, ExpandableDetail, ProgressEvent,
+};
 #[allow(unused_imports)]
 pub use analytics::{ContentPreview, DigestGenerator, FolderDigest, SemanticTag};
 #[allow(unused_imports)]
 pub use architect::{Blueprint, BlueprintFolder};
 #[allow(unused_imports)]
-pub use builder::{BatchMatchResult, MatchResult};
+pub use builder::{
+    organize_with_blueprint, BatchMatchResult, BuilderOptions, MatchExplanation, MatchResult,
+    MatchThresholds, MatchTier,
+};
 #[allow(unused_imports)]
 pub use local_vector_index::{LocalVectorConfig, LocalVectorIndex};
 #[allow(unused_imports)]
@@ -42,6 +42,13 @@ Rules match files using a simple expression language:
 - `file.mimeType` - MIME type
 - `file.isHidden` - Whether hidden (starts with .)
 
+### Document Fields (analyzed invoices, receipts, statements only)
+- `file.doc.vendor` - Issuing company or store
+- `file.doc.total` - Grand total / amount due (number)
+- `file.doc.currency` - ISO currency code (e.g. 'USD')
+- `file.doc.invoiceNumber` - Invoice or receipt number
+- `file.doc.issueDate`, `file.doc.dueDate` - Dates as 'YYYY-MM-DD'
+
 ### Operators
 - `==`, `!=` - Equality
 - `>`, `<`, `>=`, `<=` - Comparison
@@ -72,6 +79,7 @@ file.name.contains('invoice') AND file.size > 10KB
 NOT file.isHidden AND file.ext == 'txt'
 (file.ext == 'jpg' OR file.ext == 'png') AND file.size < 5MB
 file.vector_similarity('tax document') > 0.7
+file.doc.total > 500 AND file.doc.currency == 'USD'
 ```
 
 ## COMMON MISTAKES TO AVOID
@@ -105,7 +113,7 @@ file.name.has('test')             # Should be: file.name.contains('test')
 file.name.start('test')           # Should be: file.name.startsWith('test')
 ```
 
-Valid fields: `name`, `ext`, `size`, `path`, `modifiedAt`, `createdAt`, `mimeType`, `isHidden`
+Valid fields: `name`, `ext`, `size`, `path`, `modifiedAt`, `createdAt`, `mimeType`, `isHidden`, `doc.vendor`, `doc.total`, `doc.currency`, `doc.invoiceNumber`, `doc.issueDate`, `doc.dueDate`
 Valid functions (on file.name only): `contains()`, `startsWith()`, `endsWith()`, `matches()`
 
 ## WORKFLOW
@@ -336,6 +336,7 @@ mod tests {
             modified_at: Some(modified),
             created_at: Some(modified),
             mime_type: None,
+            doc: None,
         }
     }
 
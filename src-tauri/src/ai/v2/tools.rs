@@ -236,7 +236,7 @@ fn execute_apply_rules(input: &serde_json::Value, vfs: &mut ShadowVFS) -> V2Tool
                 }
                 output.push_str("\n### How to fix:\n");
                 output.push_str("- Fields must come after 'file.' (e.g., `file.ext`, `file.name`)\n");
-                output.push_str("- Valid fields: `name`, `ext`, `size`, `path`, `modifiedAt`, `createdAt`, `mimeType`, `isHidden`\n");
+                output.push_str("- Valid fields: `name`, `ext`, `size`, `path`, `modifiedAt`, `createdAt`, `mimeType`, `isHidden`, `doc.total` (and other `doc.*` fields)\n");
                 output.push_str("- Use `==` not `=` for comparison\n");
                 output.push_str("- String values must be quoted: `file.ext == 'pdf'`\n");
                 output.push_str("- Functions only work on `file.name`: `file.name.contains('text')`\n");
@@ -384,38 +384,13 @@ fn execute_commit(input: &serde_json::Value, vfs: &ShadowVFS) -> V2ToolResult {
         description, dry_run
     );
 
-    let operations = vfs.operations();
+    let plan = vfs.to_plan(description);
 
-    if operations.is_empty() {
+    if plan.operations.is_empty() {
         // Return an empty plan - folder is already organized
-        return V2ToolResult::Commit(OrganizePlan {
-            plan_id: format!("plan-{}", chrono::Utc::now().timestamp_millis()),
-            description: description.to_string(),
-            operations: Vec::new(),
-            target_folder: vfs.root().to_string_lossy().to_string(),
-        });
+        return V2ToolResult::Commit(plan);
     }
 
-    // Convert to OrganizeOperation format
-    let organize_ops: Vec<crate::jobs::OrganizeOperation> = operations
-        .iter()
-        .map(|op| crate::jobs::OrganizeOperation {
-            op_id: op.op_id.clone(),
-            op_type: op.op_type.to_string(),
-            source: op.source.clone(),
-            destination: op.destination.clone(),
-            path: op.path.clone(),
-            new_name: op.new_name.clone(),
-        })
-        .collect();
-
-    let plan = OrganizePlan {
-        plan_id: format!("plan-{}", chrono::Utc::now().timestamp_millis()),
-        description: description.to_string(),
-        operations: organize_ops,
-        target_folder: vfs.root().to_string_lossy().to_string(),
-    };
-
     if dry_run {
         // Return as a preview
         let output = format!(
@@ -6,7 +6,11 @@
 //! - Conflict detection before execution
 //! - Rule-based bulk operations
 
+use crate::ai::grok::ExtractedFields;
 use crate::ai::rules::{RuleEvaluator, VirtualFile, VectorIndex};
+use crate::jobs::{OrganizeOperation, OrganizePlan};
+use crate::scan::ignore::{enclosing_repo, is_repo_root, IgnoreRules};
+use crate::scan::{EntryKind, ScanOptions};
 use crate::security::PathValidator;
 use crate::utils::format_size;
 use super::local_vector_index::{LocalVectorConfig, LocalVectorIndex};
@@ -96,6 +100,8 @@ pub struct ShadowVFS {
     /// Organization root - where new folders are created (parent of target folder)
     /// This allows organizing `cuero/2025/` to create `cuero/NewFolder/` instead of `cuero/2025/NewFolder/`
     organization_root: PathBuf,
+    /// Other folders whose files are gathered into the root (outside of it)
+    source_roots: Vec<PathBuf>,
     /// Virtual files indexed by path
     files: HashMap<String, VirtualFile>,
     /// Planned operations
@@ -109,6 +115,10 @@ pub struct ShadowVFS {
     /// V5: Tracks destination paths to detect collisions during planning
     /// Maps destination path -> source path that claimed it
     destination_registry: HashMap<String, String>,
+    /// Ignore rules for the root; ignored paths and git checkouts are never planned
+    ignore_rules: IgnoreRules,
+    /// Rules behind the planned operations (kept with the plan for re-basing)
+    applied_rules: Vec<OrganizationRule>,
 }
 
 impl ShadowVFS {
@@ -116,11 +126,48 @@ impl ShadowVFS {
     ///
     /// V3: Uses LocalVectorIndex with fastembed for real semantic search
     pub fn new(root: &Path) -> std::io::Result<Self> {
-        let mut files = HashMap::new();
-        let mut file_list = Vec::new();
+        Self::with_sources(root, &[])
+    }
+
+    /// Create a ShadowVFS that gathers files from `sources` (and from the
+    /// destination itself) into `destination`.
+    ///
+    /// All roots share one file table and one destination registry, so two
+    /// `invoice.pdf`s from different roots sent to the same folder still get
+    /// distinct names. Sources inside the destination are already covered by
+    /// its scan and are dropped.
+    pub fn with_sources(destination: &Path, sources: &[PathBuf]) -> std::io::Result<Self> {
+        let mut source_roots: Vec<PathBuf> = Vec::new();
+        for source in sources {
+            if !source.starts_with(destination) && !source_roots.contains(source) {
+                source_roots.push(source.clone());
+            }
+        }
 
-        // Recursively scan the folder
-        Self::scan_directory(root, &mut files, &mut file_list)?;
+        // Scan each root once; a root nested in another (say the destination
+        // inside a source) is covered by the outer scan
+        let roots: Vec<&Path> = std::iter::once(destination)
+            .chain(source_roots.iter().map(PathBuf::as_path))
+            .collect();
+        let mut files: HashMap<String, VirtualFile> = HashMap::new();
+        for root in &roots {
+            if roots.iter().any(|other| other != root && root.starts_with(other)) {
+                continue;
+            }
+            // Symlinks are listed but never followed, so skip them to keep
+            // the plan inside the roots
+            let snapshot = ScanOptions::new()
+                .scan(root)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            for vf in snapshot
+                .entries()
+                .iter()
+                .filter(|entry| entry.kind != EntryKind::Symlink)
+                .map(VirtualFile::from_scan_entry)
+            {
+                files.insert(vf.path.clone(), vf);
+            }
+        }
 
         // Build the LocalVectorIndex with batch indexing
         let config = LocalVectorConfig::default();
@@ -133,8 +180,8 @@ impl ShadowVFS {
 
         // Prepare batch data: (path, searchable_text)
         // searchable_text combines filename and extension for better semantic matching
-        let batch_data: Vec<(PathBuf, String)> = file_list
-            .iter()
+        let batch_data: Vec<(PathBuf, String)> = files
+            .values()
             .filter(|f| !f.is_directory)
             .map(|f| {
                 let text = format!(
@@ -157,59 +204,52 @@ impl ShadowVFS {
 
         // Organization root is the target folder itself
         // All organization happens WITHIN the selected folder, not at the parent level
-        let organization_root = root.to_path_buf();
+        let organization_root = destination.to_path_buf();
 
         Ok(Self {
-            root: root.to_path_buf(),
+            root: destination.to_path_buf(),
             organization_root,
+            source_roots,
             files,
             operations: Vec::new(),
             op_counter: 0,
             vector_index,
             matched_files: std::collections::HashSet::new(),
             destination_registry: HashMap::new(),
+            ignore_rules: IgnoreRules::for_root(destination),
+            applied_rules: Vec::new(),
         })
     }
 
+    /// Whether moving files into `dir` would touch ignored or version-controlled content
+    fn is_protected_destination(&self, dir: &Path) -> bool {
+        self.ignore_rules.is_ignored(dir, true)
+            || is_repo_root(dir)
+            || enclosing_repo(dir, &self.root).is_some()
+    }
+
     /// Get the organization root (parent folder where new structure is created)
     pub fn organization_root(&self) -> &Path {
         &self.organization_root
     }
 
-    fn scan_directory(
-        dir: &Path,
-        files: &mut HashMap<String, VirtualFile>,
-        file_list: &mut Vec<VirtualFile>,
-    ) -> std::io::Result<()> {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Security: Skip symlinks to prevent path traversal attacks
-            // and avoid following symlinks to directories outside the root
-            if PathValidator::is_symlink(&path) {
-                tracing::debug!(path = %path.display(), "Skipping symlink during VFS scan");
-                continue;
-            }
-
-            if let Ok(vf) = VirtualFile::from_path(&path) {
-                let path_str = path.to_string_lossy().to_string();
-                file_list.push(vf.clone());
-                files.insert(path_str, vf);
-
-                if path.is_dir() {
-                    Self::scan_directory(&path, files, file_list)?;
-                }
-            }
-        }
-        Ok(())
-    }
-
     /// Get the root path
     pub fn root(&self) -> &Path {
         &self.root
     }
 
+    /// Folders files are gathered from besides the root
+    pub fn source_roots(&self) -> &[PathBuf] {
+        &self.source_roots
+    }
+
+    /// Every folder the VFS covers, the root first
+    pub fn roots(&self) -> Vec<&Path> {
+        std::iter::once(self.root.as_path())
+            .chain(self.source_roots.iter().map(PathBuf::as_path))
+            .collect()
+    }
+
     /// Get all files (not directories)
     pub fn files(&self) -> Vec<&VirtualFile> {
         self.files
@@ -250,6 +290,12 @@ impl ShadowVFS {
     pub fn clear_operations(&mut self) {
         self.operations.clear();
         self.destination_registry.clear();
+        self.applied_rules.clear();
+    }
+
+    /// Rules applied so far (since the last "replace")
+    pub fn applied_rules(&self) -> &[OrganizationRule] {
+        &self.applied_rules
     }
 
     /// Generate a unique destination path by appending a counter suffix
@@ -331,6 +377,19 @@ impl ShadowVFS {
         self.matched_files.clear();
     }
 
+    /// Attach typed invoice/receipt fields (keyed by file path) so rules can
+    /// use `file.doc.*`. Returns how many files received fields.
+    pub fn attach_document_fields(&mut self, mut fields: HashMap<String, ExtractedFields>) -> usize {
+        let mut attached = 0;
+        for (path, file) in self.files.iter_mut() {
+            if let Some(doc) = fields.remove(path) {
+                file.doc = Some(doc);
+                attached += 1;
+            }
+        }
+        attached
+    }
+
     /// Get all files as a Vec (for sampling)
     pub fn all_files_vec(&self) -> Vec<VirtualFile> {
         self.files
@@ -407,6 +466,7 @@ impl ShadowVFS {
         if mode == "replace" {
             self.operations.clear();
             self.destination_registry.clear();
+            self.applied_rules.clear();
         }
 
         // Sort rules by priority (descending)
@@ -437,15 +497,17 @@ impl ShadowVFS {
             };
 
             rules_applied += 1;
-            let evaluator = RuleEvaluator::new(&self.vector_index);
+            self.applied_rules.push((*rule).clone());
+            let evaluator =
+                RuleEvaluator::new(&self.vector_index).with_ignore_rules(&self.ignore_rules);
 
-            // Find matching files
+            // Find matching files (ignored files and git checkouts never match)
             let matching_files: Vec<VirtualFile> = self
                 .files()
                 .iter()
                 .filter(|f| {
                     !processed_files.contains(&f.path)
-                        && evaluator.evaluate(&expr, f).unwrap_or(false)
+                        && evaluator.matches(&expr, f).unwrap_or(false)
                 })
                 .cloned()
                 .cloned()
@@ -458,81 +520,9 @@ impl ShadowVFS {
 
                 // Handle move operation
                 if let Some(ref dest_folder) = rule.then_move_to {
-                    // Security: Validate destination path using PathValidator
-                    // Disallow absolute paths - all destinations must be relative to organization_root
-                    // organization_root is the target folder itself, so all organized files stay within it
-                    let dest_path = match PathValidator::validate_destination(
-                        dest_folder,
-                        &self.organization_root,
-                        false, // Disallow absolute paths in organization rules
-                    ) {
-                        Ok(p) => p,
-                        Err(e) => {
-                            // Log warning and skip this rule for this file
-                            tracing::warn!(
-                                rule = %rule.name,
-                                file = %file.path,
-                                error = %e,
-                                "Skipping move operation due to invalid destination"
-                            );
-                            continue;
-                        }
-                    };
-
-                    // Track folder creation
-                    let dest_str = dest_path.to_string_lossy().to_string();
-                    if !folders_to_create.contains(&dest_str)
-                        && !self.files.contains_key(&dest_str)
-                    {
-                        folders_to_create.insert(dest_str.clone());
-                    }
-
-                    // Create move operation with collision detection
-                    let file_name = Path::new(&file.path)
-                        .file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_default();
-
-                    // V5: Check if destination is already claimed or exists on disk
-                    let initial_dest = dest_path.join(&file_name);
-                    let initial_dest_str = initial_dest.to_string_lossy().to_string();
-
-                    // Skip if source == destination (file already in correct location)
-                    let source_path = PathBuf::from(&file.path);
-                    if source_path == initial_dest {
-                        continue; // Already at destination, no move needed
+                    if !self.plan_move(&file.path, dest_folder, &rule.name, &mut folders_to_create) {
+                        continue;
                     }
-
-                    let final_dest = if self.destination_registry.contains_key(&initial_dest_str)
-                        || initial_dest.exists()
-                    {
-                        // Collision detected - generate unique destination
-                        tracing::debug!(
-                            source = %file.path,
-                            destination = %initial_dest_str,
-                            "Collision detected, generating unique name"
-                        );
-                        let (unique_path, _) = self.generate_unique_destination(&dest_path, &file_name);
-                        unique_path
-                    } else {
-                        initial_dest
-                    };
-
-                    // Register this destination as claimed
-                    let final_dest_str = final_dest.to_string_lossy().to_string();
-                    self.destination_registry
-                        .insert(final_dest_str.clone(), file.path.clone());
-
-                    let op_id = self.next_op_id();
-                    self.operations.push(PlannedOperation {
-                        op_id,
-                        op_type: OperationType::Move,
-                        source: Some(file.path.clone()),
-                        destination: Some(final_dest_str),
-                        path: None,
-                        new_name: None,
-                        rule_name: Some(rule.name.clone()),
-                    });
                     operations_created += 1;
                 }
 
@@ -565,7 +555,147 @@ impl ShadowVFS {
         }
 
         // Add folder creation operations at the beginning
-        let folder_ops: Vec<PlannedOperation> = folders_to_create
+        self.prepend_folder_operations(folders_to_create);
+
+        Ok(ApplyRulesResult {
+            operations_created,
+            parsing_errors,
+            rules_applied,
+        })
+    }
+
+    /// Move each file into a folder relative to the organization root, as
+    /// decided outside the rule engine (e.g. by the Builder from a Blueprint).
+    /// Placements are `(file path, destination folder)`; returns the number of
+    /// moves planned.
+    pub fn apply_placements(
+        &mut self,
+        placements: &[(String, String)],
+        rule_name: &str,
+    ) -> Result<usize, String> {
+        let mut folders_to_create: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut operations_created = 0;
+
+        for (file_path, dest_folder) in placements {
+            if !self.files.contains_key(file_path) {
+                continue;
+            }
+            self.matched_files.insert(file_path.clone());
+            if self.plan_move(file_path, dest_folder, rule_name, &mut folders_to_create) {
+                operations_created += 1;
+            }
+            if self.operations.len() > MAX_OPERATIONS {
+                return Err(format!(
+                    "Operation limit exceeded ({} > {}). Try organizing smaller subfolders separately.",
+                    self.operations.len(),
+                    MAX_OPERATIONS
+                ));
+            }
+        }
+
+        self.prepend_folder_operations(folders_to_create);
+        Ok(operations_created)
+    }
+
+    /// Plan a move of `file_path` into `dest_folder` (relative to the
+    /// organization root), renaming on collisions. Returns false when the
+    /// destination is invalid or protected, or the file is already there.
+    fn plan_move(
+        &mut self,
+        file_path: &str,
+        dest_folder: &str,
+        rule_name: &str,
+        folders_to_create: &mut std::collections::HashSet<String>,
+    ) -> bool {
+        // Security: Validate destination path using PathValidator
+        // Disallow absolute paths - all destinations must be relative to organization_root
+        // organization_root is the target folder itself, so all organized files stay within it
+        let dest_path = match PathValidator::validate_destination(
+            dest_folder,
+            &self.organization_root,
+            false, // Disallow absolute paths in organization rules
+        ) {
+            Ok(p) => p,
+            Err(e) => {
+                // Log warning and skip this rule for this file
+                tracing::warn!(
+                    rule = %rule_name,
+                    file = %file_path,
+                    error = %e,
+                    "Skipping move operation due to invalid destination"
+                );
+                return false;
+            }
+        };
+        if self.is_protected_destination(&dest_path) {
+            tracing::warn!(
+                rule = %rule_name,
+                destination = %dest_path.display(),
+                "Skipping move into an ignored folder or git checkout"
+            );
+            return false;
+        }
+
+        // Track folder creation
+        let dest_str = dest_path.to_string_lossy().to_string();
+        if !folders_to_create.contains(&dest_str)
+            && !self.files.contains_key(&dest_str)
+        {
+            folders_to_create.insert(dest_str.clone());
+        }
+
+        // Create move operation with collision detection
+        let file_name = Path::new(file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // V5: Check if destination is already claimed or exists on disk
+        let initial_dest = dest_path.join(&file_name);
+        let initial_dest_str = initial_dest.to_string_lossy().to_string();
+
+        // Skip if source == destination (file already in correct location)
+        let source_path = PathBuf::from(file_path);
+        if source_path == initial_dest {
+            return false; // Already at destination, no move needed
+        }
+
+        let final_dest = if self.destination_registry.contains_key(&initial_dest_str)
+            || initial_dest.exists()
+        {
+            // Collision detected - generate unique destination
+            tracing::debug!(
+                source = %file_path,
+                destination = %initial_dest_str,
+                "Collision detected, generating unique name"
+            );
+            let (unique_path, _) = self.generate_unique_destination(&dest_path, &file_name);
+            unique_path
+        } else {
+            initial_dest
+        };
+
+        // Register this destination as claimed
+        let final_dest_str = final_dest.to_string_lossy().to_string();
+        self.destination_registry
+            .insert(final_dest_str.clone(), file_path.to_string());
+
+        let op_id = self.next_op_id();
+        self.operations.push(PlannedOperation {
+            op_id,
+            op_type: OperationType::Move,
+            source: Some(file_path.to_string()),
+            destination: Some(final_dest_str),
+            path: None,
+            new_name: None,
+            rule_name: Some(rule_name.to_string()),
+        });
+        true
+    }
+
+    /// Put folder creation operations ahead of the moves that need them
+    fn prepend_folder_operations(&mut self, folders: std::collections::HashSet<String>) {
+        let folder_ops: Vec<PlannedOperation> = folders
             .into_iter()
             .map(|path| {
                 self.op_counter += 1;
@@ -585,12 +715,6 @@ impl ShadowVFS {
         let mut combined_ops = folder_ops;
         combined_ops.append(&mut self.operations);
         self.operations = combined_ops;
-
-        Ok(ApplyRulesResult {
-            operations_created,
-            parsing_errors,
-            rules_applied,
-        })
     }
 
     /// Apply a rename pattern to a file
@@ -663,6 +787,36 @@ impl ShadowVFS {
         }
     }
 
+    /// Package the planned operations as an executable organize plan
+    pub fn to_plan(&self, description: &str) -> OrganizePlan {
+        OrganizePlan {
+            plan_id: format!("plan-{}", chrono::Utc::now().timestamp_millis()),
+            description: description.to_string(),
+            operations: self
+                .operations
+                .iter()
+                .map(|op| OrganizeOperation {
+                    op_id: op.op_id.clone(),
+                    op_type: op.op_type.to_string(),
+                    source: op.source.clone(),
+                    destination: op.destination.clone(),
+                    path: op.path.clone(),
+                    new_name: op.new_name.clone(),
+                    attributes: Default::default(),
+                })
+                .collect(),
+            target_folder: self.root.to_string_lossy().to_string(),
+            source_roots: self
+                .source_roots
+                .iter()
+                .map(|root| root.to_string_lossy().to_string())
+                .collect(),
+            rules: self.applied_rules.clone(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
     /// Add a single operation manually
     pub fn add_operation(&mut self, op_type: OperationType, params: OperationParams) {
         let op_id = self.next_op_id();
@@ -680,7 +834,7 @@ impl ShadowVFS {
     /// Generate a compressed tree representation for context
     ///
     /// Uses the TreeCompressor for intelligent folder collapsing
-    /// based on Shannon entropy analysis.
+    /// based on Shannon entropy analysis. Each root gets its own tree.
     pub fn generate_compressed_tree(&self) -> String {
         use crate::tree::{TreeCompressor, TreeConfig, to_xml, to_compact_xml};
 
@@ -707,30 +861,38 @@ impl ShadowVFS {
 
         let compressor = TreeCompressor::new(config);
 
-        match compressor.compress(&self.root, None) {
-            Ok(compressed) => {
-                // Use compact XML for large trees, full XML for smaller ones
-                if compressed.node_count() > 50 {
-                    to_compact_xml(&compressed)
-                } else {
-                    to_xml(&compressed)
+        let trees: Vec<String> = self
+            .roots()
+            .into_iter()
+            .map(|root| match compressor.compress(root, None) {
+                Ok(compressed) => {
+                    // Use compact XML for large trees, full XML for smaller ones
+                    if compressed.node_count() > 50 {
+                        to_compact_xml(&compressed)
+                    } else {
+                        to_xml(&compressed)
+                    }
                 }
-            }
-            Err(e) => {
-                eprintln!("[ShadowVFS] TreeCompressor failed: {}, using fallback", e);
-                self.generate_fallback_tree()
-            }
-        }
+                Err(e) => {
+                    eprintln!("[ShadowVFS] TreeCompressor failed: {}, using fallback", e);
+                    self.generate_fallback_tree(root)
+                }
+            })
+            .collect();
+        trees.join("\n")
     }
 
-    /// Fallback tree generation if TreeCompressor fails
-    fn generate_fallback_tree(&self) -> String {
+    /// Fallback tree generation for `root` if TreeCompressor fails
+    fn generate_fallback_tree(&self, root: &Path) -> String {
         let mut lines = Vec::new();
-        lines.push(format!("<folder path=\"{}\">", self.root.display()));
+        lines.push(format!("<folder path=\"{}\">", root.display()));
 
         // Group files by directory
         let mut dirs: HashMap<String, Vec<&VirtualFile>> = HashMap::new();
         for file in self.files() {
+            if !Path::new(&file.path).starts_with(root) {
+                continue;
+            }
             let parent = Path::new(&file.path)
                 .parent()
                 .map(|p| p.to_string_lossy().to_string())
@@ -745,7 +907,7 @@ impl ShadowVFS {
         for dir_path in sorted_dirs {
             let files = &dirs[dir_path];
             let rel_path = Path::new(dir_path)
-                .strip_prefix(&self.root)
+                .strip_prefix(root)
                 .unwrap_or(Path::new("."));
 
             if !rel_path.as_os_str().is_empty() && rel_path != Path::new(".") {
@@ -796,6 +958,7 @@ pub struct OperationPreview {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wal::WALOperationType;
     use std::fs;
     use tempfile::tempdir;
 
@@ -843,6 +1006,103 @@ mod tests {
         assert!(result.parsing_errors.is_empty()); // No parsing errors
     }
 
+    #[test]
+    fn test_apply_rules_with_document_fields() {
+        let (mut vfs, temp) = create_test_vfs();
+
+        let doc1 = temp.path().join("doc1.pdf").to_string_lossy().to_string();
+        let mut fields = HashMap::new();
+        fields.insert(
+            doc1.clone(),
+            ExtractedFields {
+                total: Some(750.0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(vfs.attach_document_fields(fields), 1);
+
+        let rules = vec![OrganizationRule {
+            name: "Large invoices".to_string(),
+            condition: "file.doc.total > 500".to_string(),
+            then_move_to: Some("Invoices/Large".to_string()),
+            then_rename_to: None,
+            priority: Some(1),
+        }];
+
+        let result = vfs.apply_rules(&rules, "replace").unwrap();
+        assert!(result.parsing_errors.is_empty());
+        assert_eq!(result.operations_created, 1);
+        assert!(vfs
+            .operations()
+            .iter()
+            .any(|op| op.source.as_deref() == Some(doc1.as_str())));
+    }
+
+    #[test]
+    fn test_gather_from_several_roots() {
+        let temp = tempdir().unwrap();
+        let (desktop, downloads, sorted) = (
+            temp.path().join("Desktop"),
+            temp.path().join("Downloads"),
+            temp.path().join("Sorted"),
+        );
+        for dir in [&desktop, &downloads, &sorted] {
+            fs::create_dir(dir).unwrap();
+        }
+        fs::write(desktop.join("invoice.pdf"), "a").unwrap();
+        fs::write(downloads.join("invoice.pdf"), "b").unwrap();
+        fs::write(downloads.join("photo.jpg"), "c").unwrap();
+
+        let mut vfs = ShadowVFS::with_sources(
+            &sorted,
+            &[desktop.clone(), downloads.clone(), sorted.join("inner")],
+        )
+        .unwrap();
+        assert_eq!(vfs.source_roots(), &[desktop.clone(), downloads.clone()]);
+        assert_eq!(vfs.files().len(), 3);
+
+        let rules = vec![OrganizationRule {
+            name: "PDFs".to_string(),
+            condition: "file.ext == 'pdf'".to_string(),
+            then_move_to: Some("Documents".to_string()),
+            then_rename_to: None,
+            priority: Some(1),
+        }];
+        vfs.apply_rules(&rules, "replace").unwrap();
+
+        // Same-named files from different roots land side by side
+        let mut destinations: Vec<String> = vfs
+            .operations()
+            .iter()
+            .filter_map(|op| op.destination.clone())
+            .collect();
+        destinations.sort();
+        let documents = sorted.join("Documents");
+        assert_eq!(
+            destinations,
+            vec![
+                documents.join("invoice.pdf").to_string_lossy().to_string(),
+                documents.join("invoice_1.pdf").to_string_lossy().to_string(),
+            ]
+        );
+
+        // One journal: both moves wait for the folder they land in
+        let plan = vfs.to_plan("Gather PDFs");
+        assert_eq!(plan.source_roots.len(), 2);
+        let journal = plan.to_wal_journal().unwrap();
+        assert_eq!(journal.roots().len(), 3);
+        let create = journal
+            .entries
+            .iter()
+            .find(|e| matches!(e.operation, WALOperationType::CreateFolder { .. }))
+            .unwrap();
+        assert!(journal
+            .entries
+            .iter()
+            .filter(|e| matches!(e.operation, WALOperationType::Move { .. }))
+            .all(|e| e.depends_on == vec![create.id]));
+    }
+
     #[test]
     fn test_preview_operations() {
         let (mut vfs, _temp) = create_test_vfs();
@@ -0,0 +1,375 @@
+//! Grok multi-model pipeline: AI-generated plans and their execution.
+//!
+//! Initialise a [`GrokOrganizer`] with an API key and cache directory, then
+//! generate a plan with [`grok_plan`] (review it like any `OrganizePlan`) or
+//! execute a raw `OrganizationPlan` with [`execute_grok_plan`]. To edit a raw
+//! plan first, convert it with [`grok_to_organize_plan`] and run the edited
+//! result with [`execute_grok_organize_plan`].
+
+use crate::ai::grok::{
+    sanitize_filename, sanitize_folder_path, AnalysisPhase, GrokOrganizer, OrganizationPlan,
+};
+use crate::events::SharedEventSink;
+use crate::execution::{ExecutionEngine, ProgressCallback};
+use crate::jobs::{OrganizeOperation, OrganizePlan};
+use crate::wal::WALManager;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Generate an organization plan for `folder` with the Grok pipeline.
+///
+/// Events: `analysis-progress` for every progress update and `ai-thought` on
+/// phase changes. Setting `abort_flag` silences progress and cancels the
+/// result once the pipeline returns.
+pub async fn grok_plan(
+    organizer: &GrokOrganizer,
+    folder: &Path,
+    user_instruction: &str,
+    events: SharedEventSink,
+    abort_flag: Arc<AtomicBool>,
+) -> Result<OrganizePlan, String> {
+    let events_clone = Arc::clone(&events);
+
+    // Emit progress as ai-thought events (for compatibility with ChangesPanel)
+    let emit_thought = |phase: &str, message: &str, details: Option<Vec<(&str, String)>>| {
+        let expandable_details: Option<Vec<serde_json::Value>> = details.map(|d| {
+            d.into_iter()
+                .map(|(label, value)| serde_json::json!({"label": label, "value": value}))
+                .collect()
+        });
+
+        events_clone.emit_json(
+            "ai-thought",
+            serde_json::json!({
+                "type": phase,
+                "content": message,
+                "expandableDetails": expandable_details,
+            }),
+        );
+    };
+
+    emit_thought("scanning", "Analyzing folder contents with AI...", None);
+
+    // Track last phase to only emit ai-thought on phase changes
+    let last_phase = std::sync::Arc::new(std::sync::Mutex::new(None::<String>));
+
+    // Clone abort flag for use in callback
+    let abort_flag_for_callback = Arc::clone(&abort_flag);
+
+    let plan = organizer
+        .organize(folder, user_instruction, move |progress| {
+            // Check if aborted - skip all emissions if so
+            if abort_flag_for_callback.load(Ordering::SeqCst) {
+                return;
+            }
+
+            // Map Grok progress phases to names
+            let phase_name = match progress.phase {
+                AnalysisPhase::Scanning => "scanning",
+                AnalysisPhase::CheckingCache => "extracting",
+                AnalysisPhase::RenderingPdf => "rendering",
+                AnalysisPhase::AnalyzingContent => "analyzing",
+                AnalysisPhase::AnalyzingMedia => "analyzing_media",
+                AnalysisPhase::Aggregating => "summarizing",
+                AnalysisPhase::Planning => "planning",
+                AnalysisPhase::Complete => "complete",
+                AnalysisPhase::Failed => "error",
+            };
+
+            // Always emit analysis-progress for the progress bar
+            events.emit_json(
+                "analysis-progress",
+                serde_json::json!({
+                    "phase": phase_name,
+                    "current": progress.current,
+                    "total": progress.total,
+                    "message": progress.message,
+                }),
+            );
+
+            // Only emit ai-thought on phase transitions (not every progress update)
+            let mut last = last_phase.lock().unwrap();
+            let should_emit_thought = match &*last {
+                None => true,
+                Some(prev) => prev != phase_name,
+            };
+
+            if should_emit_thought {
+                *last = Some(phase_name.to_string());
+                drop(last); // Release lock before emit
+
+                let thought_type = match progress.phase {
+                    AnalysisPhase::Scanning => "scanning",
+                    AnalysisPhase::CheckingCache
+                    | AnalysisPhase::RenderingPdf
+                    | AnalysisPhase::AnalyzingContent
+                    | AnalysisPhase::AnalyzingMedia => "analyzing",
+                    AnalysisPhase::Aggregating => "thinking",
+                    AnalysisPhase::Planning => "planning",
+                    AnalysisPhase::Complete => "complete",
+                    AnalysisPhase::Failed => "error",
+                };
+
+                events.emit_json(
+                    "ai-thought",
+                    serde_json::json!({
+                        "type": thought_type,
+                        "content": progress.message,
+                    }),
+                );
+            }
+        })
+        .await?;
+
+    // Check if aborted after organize completes
+    if abort_flag.load(Ordering::SeqCst) {
+        tracing::info!("[Grok] Plan generation was aborted by user");
+        return Err("Organization cancelled by user".to_string());
+    }
+
+    // Convert OrganizationPlan to OrganizePlan (frontend format)
+    let frontend_plan =
+        super::attach_baseline(convert_to_frontend_plan(plan, &folder.to_string_lossy())).await?;
+
+    emit_thought("complete", &format!(
+        "Created plan with {} operations",
+        frontend_plan.operations.len()
+    ), None);
+
+    Ok(frontend_plan)
+}
+
+/// Convert Grok's OrganizationPlan to frontend's OrganizePlan format
+fn convert_to_frontend_plan(plan: OrganizationPlan, target_folder: &str) -> OrganizePlan {
+    let mut operations = Vec::new();
+    let target_path = PathBuf::from(target_folder);
+
+    // Convert folder creations to create_folder operations
+    for folder in &plan.folder_structure {
+        let sanitized_path = sanitize_folder_path(&folder.path);
+        let full_path = target_path.join(&sanitized_path);
+
+        operations.push(OrganizeOperation {
+            op_id: uuid::Uuid::new_v4().to_string(),
+            op_type: "create_folder".to_string(),
+            source: None,
+            destination: None,
+            path: Some(full_path.to_string_lossy().to_string()),
+            new_name: None,
+            attributes: Default::default(),
+        });
+    }
+
+    // Convert file assignments to move operations
+    for assignment in &plan.assignments {
+        let source = PathBuf::from(&assignment.file_path);
+        let sanitized_folder = sanitize_folder_path(&assignment.destination_folder);
+
+        // Get the new filename (sanitized)
+        let new_filename = match &assignment.new_name {
+            Some(name) if !name.is_empty() => {
+                sanitize_filename(name, &assignment.original_name)
+            }
+            _ => assignment.original_name.clone(),
+        };
+
+        let destination = target_path.join(&sanitized_folder).join(&new_filename);
+
+        // Check if this is a rename (same directory, different name)
+        let source_parent = source.parent().map(|p| p.to_string_lossy().to_string());
+        let dest_parent = destination.parent().map(|p| p.to_string_lossy().to_string());
+        let is_same_dir = source_parent == dest_parent;
+        let is_rename = is_same_dir && new_filename != assignment.original_name;
+
+        if is_rename {
+            // Rename operation
+            operations.push(OrganizeOperation {
+                op_id: uuid::Uuid::new_v4().to_string(),
+                op_type: "rename".to_string(),
+                source: None,
+                destination: None,
+                path: Some(source.to_string_lossy().to_string()),
+                new_name: Some(new_filename),
+                attributes: Default::default(),
+            });
+        } else {
+            // Move operation
+            operations.push(OrganizeOperation {
+                op_id: uuid::Uuid::new_v4().to_string(),
+                op_type: "move".to_string(),
+                source: Some(source.to_string_lossy().to_string()),
+                destination: Some(destination.to_string_lossy().to_string()),
+                path: None,
+                new_name: None,
+                attributes: Default::default(),
+            });
+        }
+    }
+
+    OrganizePlan {
+        plan_id: uuid::Uuid::new_v4().to_string(),
+        description: format!(
+            "{}: {}",
+            plan.strategy_name,
+            plan.description
+        ),
+        operations,
+        target_folder: target_folder.to_string(),
+        source_roots: Vec::new(),
+        rules: Vec::new(),
+        baseline: plan.baseline,
+        explanations: Default::default(),
+    }
+}
+
+/// Execute a Grok plan under `target_folder` through the WAL.
+///
+/// This converts the Grok plan into executable filesystem operations:
+/// 1. Creates all planned folders
+/// 2. Moves files to their destinations with sanitized names
+///
+/// A plan with a baseline is re-based first when the folder changed since
+/// it was made. Events: `grok:execution` (phases and progress),
+/// `plan-rebased` and `execution-progress`.
+pub async fn execute_grok_plan(
+    plan: &OrganizationPlan,
+    target_folder: &Path,
+    events: SharedEventSink,
+) -> Result<GrokExecutionResult, String> {
+    let organize_plan = grok_to_organize_plan(plan, target_folder);
+    execute_grok_organize_plan(&organize_plan, events).await
+}
+
+/// Convert a Grok plan into an `OrganizePlan` under `target_folder`.
+///
+/// Store the result in a job to review and edit it like any other plan, then
+/// run the edited plan with [`execute_grok_organize_plan`].
+pub fn grok_to_organize_plan(plan: &OrganizationPlan, target_folder: &Path) -> OrganizePlan {
+    convert_to_frontend_plan(plan.clone(), &target_folder.to_string_lossy())
+}
+
+/// Execute a converted (and possibly edited) Grok plan through the WAL, with
+/// the same re-basing and events as [`execute_grok_plan`].
+pub async fn execute_grok_organize_plan(
+    plan: &OrganizePlan,
+    events: SharedEventSink,
+) -> Result<GrokExecutionResult, String> {
+    // Generate a unique job ID
+    let job_id = format!("grok-{}", uuid::Uuid::new_v4());
+
+    let folder_count = plan
+        .operations
+        .iter()
+        .filter(|op| op.op_type == "create_folder")
+        .count();
+    let move_count = plan.operations.len() - folder_count;
+    tracing::info!(
+        "[Grok] Executing plan: {} folders, {} moves",
+        folder_count,
+        move_count
+    );
+
+    // Emit start event
+    events.emit_json("grok:execution", serde_json::json!({
+        "phase": "starting",
+        "message": format!("Creating {} folders, moving {} files", folder_count, move_count)
+    }));
+
+    // Journal the plan so a stale plan is re-based first
+    let mut organize_plan = plan.clone();
+    organize_plan.plan_id = job_id.clone();
+    let organize_plan = super::rebase_for_execution(&organize_plan, &events).await?;
+    let journal = organize_plan.to_wal_journal()?;
+
+    let folder_count = organize_plan
+        .operations
+        .iter()
+        .filter(|op| op.op_type == "create_folder")
+        .count();
+    let op_count = organize_plan.operations.len();
+    tracing::info!(
+        "[Grok] Created WAL journal with {} folder ops and {} other ops",
+        folder_count,
+        op_count - folder_count
+    );
+
+    // Emit progress
+    events.emit_json("grok:execution", serde_json::json!({
+        "phase": "executing",
+        "message": format!("Executing {} operations...", op_count)
+    }));
+
+    // Save and execute the journal
+    let wal_manager = WALManager::new();
+    wal_manager.save_journal(&journal).map_err(|e| e.message)?;
+
+    let engine = ExecutionEngine::new();
+    let progress_events = Arc::clone(&events);
+
+    // Execute with progress callback
+    // Emit both grok:execution (for Grok-specific UIs) and execution-progress (for ChangesPanel)
+    let progress_callback: Arc<ProgressCallback> = Arc::new(Box::new(move |current: usize, total: usize| {
+        // Emit execution-progress for ChangesPanel compatibility
+        progress_events.emit_json("execution-progress", serde_json::json!({
+            "completed": current,
+            "total": total,
+        }));
+        // Also emit grok:execution for richer Grok-specific details
+        progress_events.emit_json("grok:execution", serde_json::json!({
+            "phase": "progress",
+            "current": current,
+            "total": total,
+            "message": format!("Completed {}/{} operations", current, total)
+        }));
+    }));
+
+    let result = engine
+        .execute_journal_with_progress(&job_id, Some(progress_callback))
+        .await?;
+
+    // Clean up journal after successful execution
+    if result.success {
+        let _ = wal_manager.discard_journal(&job_id);
+    }
+
+    // Emit completion
+    events.emit_json("grok:execution", serde_json::json!({
+        "phase": if result.success { "complete" } else { "failed" },
+        "message": format!(
+            "Completed: {} succeeded, {} failed, {} skipped",
+            result.completed_count,
+            result.failed_count,
+            result.skipped_count
+        )
+    }));
+
+    tracing::info!(
+        "[Grok] Execution complete: {} completed, {} failed",
+        result.completed_count,
+        result.failed_count
+    );
+
+    Ok(GrokExecutionResult {
+        job_id,
+        completed_count: result.completed_count,
+        failed_count: result.failed_count,
+        skipped_count: result.skipped_count,
+        renamed_count: result.renamed_count,
+        errors: result.errors,
+        success: result.success,
+    })
+}
+
+/// Result of executing a Grok organization plan
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrokExecutionResult {
+    pub job_id: String,
+    pub completed_count: usize,
+    pub failed_count: usize,
+    pub skipped_count: usize,
+    pub renamed_count: usize,
+    pub errors: Vec<String>,
+    pub success: bool,
+}
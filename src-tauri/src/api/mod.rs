@@ -0,0 +1,60 @@
+//! Stable library API for driving Sentinel without the desktop shell.
+//!
+//! The Tauri commands and the `sentinel` CLI are thin layers over these
+//! functions. Anything that reports progress takes an [`EventSink`]; the app
+//! passes its `AppHandle`, embedders pass their own sink (or [`NoopSink`]).
+//!
+//! | Area | Entry points |
+//! |------|--------------|
+//! | Scanning | [`scan_folder`] |
+//! | Planning | [`load_rules`], [`plan_with_rules`], [`plan_with_rules_across`], [`grok_plan`], [`grok_to_organize_plan`], [`attach_baseline`], [`rebase_plan`] |
+//! | Preview | [`preview_plan`], [`diff_plan`], [`plan_changes`], [`check_plan_paths`], [`preflight_plan`] |
+//! | Executing | [`execute_plan`], [`execute_grok_plan`], [`execute_grok_organize_plan`] |
+//! | Recovery | [`list_journals`], [`check_for_recovery`], [`rollback_journal`], [`resume_journal`], [`discard_journal`] |
+//! | Snapshots | [`capture_snapshot`], [`save_snapshot`], [`list_snapshots`], [`diff_snapshots`], [`changes_since`] |
+//! | Search | [`build_search_index`], [`search`] |
+//! | Chat | [`run_chat_agent`] |
+//! | Evaluation | [`run_eval_case`] |
+//!
+//! Everything returns `Result<_, String>` like the rest of the crate.
+
+mod grok;
+mod organize;
+mod recovery;
+mod search;
+mod snapshots;
+
+pub use grok::{
+    execute_grok_organize_plan, execute_grok_plan, grok_plan, grok_to_organize_plan,
+    GrokExecutionResult,
+};
+pub(crate) use organize::open_rules_vfs;
+pub use organize::{
+    attach_baseline, check_plan_paths, diff_plan, execute_plan, load_rules, parse_rules,
+    plan_changes, plan_with_rules, plan_with_rules_across, preflight_plan, preview_plan,
+    rebase_for_execution, scan_folder, validate_folder, PlanChange, PlanPreview, RulesPlan,
+};
+pub use recovery::{journal_dir, list_journals, JournalSummary};
+pub use search::{build_search_index, search, SEARCH_INDEX_DEPTH};
+pub use snapshots::{
+    capture_snapshot, changes_since, diff_snapshots, list_snapshots, parse_since, save_snapshot,
+};
+
+pub use crate::ai::chat::{run_chat_agent, ContextItem, ConversationMessage};
+pub use crate::ai::grok::{GrokOrganizer, OrganizationPlan};
+pub use crate::ai::v2::eval::{run_eval_case, EvalCase, EvalReport};
+pub use crate::ai::v2::vfs::OrganizationRule;
+pub use crate::events::{EventSink, FnSink, NoopSink, RecordingSink, SharedEventSink};
+pub use crate::execution::{
+    ConflictPolicy, ExecutionConfig, ExecutionResult, PredictedOutcome, PreflightReport,
+};
+pub use crate::jobs::{
+    rebase_plan, OrganizeOperation, OrganizePlan, PlanBaseline, RebaseSummary, RebasedPlan,
+};
+pub use crate::vector::VectorIndex;
+pub use crate::vfs::{DiffFormat, PlanDiff, ScanStats, ShadowVFS, SnapshotDiff, SnapshotInfo};
+pub use crate::wal::recovery::{
+    check_for_recovery, discard_journal, get_journal_details, resume_journal, rollback_journal,
+    RecoveryInfo, RecoveryResult,
+};
+pub use crate::wal::WALJournal;
@@ -0,0 +1,569 @@
+//! Scanning, rule-based planning, preview and execution of `OrganizePlan`s.
+
+use crate::ai::grok::ContentCache;
+use crate::ai::v2::vfs::{OrganizationRule, ShadowVFS as RulesVFS};
+use crate::events::SharedEventSink;
+use crate::execution::{
+    preflight_journal, ExecutionConfig, ExecutionEngine, ExecutionResult, PreflightReport,
+    ProgressCallback,
+};
+use crate::jobs::{rebase_plan, OrganizeOperation, OrganizePlan};
+use crate::security::PathValidator;
+use crate::vfs::{simulate_plan, FileNode, JWalkScanner, PlanDiff, PlanDiffStats, ScanStats, ShadowVFS};
+use crate::wal::WALManager;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+// ============================================================================
+// Scanning
+// ============================================================================
+
+/// Validate that `folder` is a readable directory and return its canonical path
+pub fn validate_folder(folder: &Path) -> Result<PathBuf, String> {
+    let path = PathValidator::validate_for_read(folder, None)?;
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", folder.display()));
+    }
+    Ok(path)
+}
+
+/// Scan a folder into a fresh shadow VFS (`max_depth` 0 = unlimited)
+pub async fn scan_folder(folder: &Path, max_depth: usize) -> Result<(ShadowVFS, ScanStats), String> {
+    let root = validate_folder(folder)?;
+    let mut vfs = ShadowVFS::new(root.clone());
+    let stats = JWalkScanner::new()
+        .with_max_depth(max_depth)
+        .with_extract_previews(false)
+        .scan(&root, &mut vfs)
+        .await?;
+    Ok((vfs, stats))
+}
+
+// ============================================================================
+// Planning
+// ============================================================================
+
+/// Parse organization rules: a JSON list of rules or `{"rules": [...]}`
+pub fn parse_rules(json: &str) -> Result<Vec<OrganizationRule>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Rules are not valid JSON: {}", e))?;
+
+    let list = match value {
+        serde_json::Value::Object(mut obj) => obj
+            .remove("rules")
+            .ok_or("Rules must be a list of rules or {\"rules\": [...]}")?,
+        other => other,
+    };
+
+    let rules: Vec<OrganizationRule> =
+        serde_json::from_value(list).map_err(|e| format!("Invalid rule: {}", e))?;
+    if rules.is_empty() {
+        return Err("No rules given".to_string());
+    }
+    Ok(rules)
+}
+
+/// Read and parse a rules file (see [`parse_rules`])
+pub fn load_rules(path: &Path) -> Result<Vec<OrganizationRule>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read rules file {}: {}", path.display(), e))?;
+    parse_rules(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Plan produced by [`plan_with_rules`] plus coverage statistics
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesPlan {
+    pub plan: OrganizePlan,
+    pub rules_applied: usize,
+    pub files_matched: usize,
+    pub total_files: usize,
+    /// Rules that failed to parse, as "name: error" (they are skipped, not fatal)
+    pub rule_errors: Vec<String>,
+}
+
+/// Scan validated folders into the rules VFS (builds the semantic index);
+/// files from `sources` are gathered into `root`.
+///
+/// Typed invoice/receipt fields from earlier content analyses are attached
+/// when a content cache exists, so `file.doc.*` rules work headless too.
+pub(crate) fn open_rules_vfs(root: &Path, sources: &[PathBuf]) -> Result<RulesVFS, String> {
+    let mut vfs = RulesVFS::with_sources(root, sources)
+        .map_err(|e| format!("Failed to scan folder: {}", e))?;
+
+    if let Some(cache) = ContentCache::default_dir()
+        .filter(|dir| dir.exists())
+        .and_then(|dir| ContentCache::open(&dir).ok())
+    {
+        let roots: Vec<PathBuf> = vfs.roots().into_iter().map(Path::to_path_buf).collect();
+        for root in roots {
+            if let Ok(fields) = cache.fields_under(&root) {
+                vfs.attach_document_fields(fields);
+            }
+        }
+    }
+    Ok(vfs)
+}
+
+/// Apply organization rules (DSL) to a folder and build a plan.
+pub fn plan_with_rules(
+    folder: &Path,
+    rules: &[OrganizationRule],
+    description: &str,
+) -> Result<RulesPlan, String> {
+    plan_with_rules_across(folder, &[], rules, description)
+}
+
+/// Apply organization rules to the files of `destination` and of every
+/// source folder, moving matches into `destination`, as one plan.
+pub fn plan_with_rules_across(
+    destination: &Path,
+    sources: &[PathBuf],
+    rules: &[OrganizationRule],
+    description: &str,
+) -> Result<RulesPlan, String> {
+    let sources = sources
+        .iter()
+        .map(|source| validate_folder(source))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut vfs = open_rules_vfs(&validate_folder(destination)?, &sources)?;
+    let result = vfs.apply_rules(rules, "replace")?;
+
+    Ok(RulesPlan {
+        plan: vfs.to_plan(description),
+        rules_applied: result.rules_applied,
+        files_matched: vfs.organized_count(),
+        total_files: vfs.file_count(),
+        rule_errors: result
+            .parsing_errors
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect(),
+    })
+}
+
+// ============================================================================
+// Preview
+// ============================================================================
+
+/// One line of a plan diff
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanChange {
+    /// "+" create, "~" move/rename, "@" link, "*" metadata, "-" quarantine/delete
+    pub marker: char,
+    pub path: String,
+    pub to: Option<String>,
+    /// Why the Builder chose the destination of a move, when it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub why: Option<String>,
+}
+
+impl std::fmt::Display for PlanChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.to {
+            Some(to) => write!(f, "{} {} -> {}", self.marker, self.path, to),
+            None => write!(f, "{} {}", self.marker, self.path),
+        }
+    }
+}
+
+/// Result of [`preview_plan`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanPreview {
+    pub plan_id: String,
+    pub target_folder: String,
+    pub changes: Vec<PlanChange>,
+    /// Simulation problems (conflicts, missing sources, ...)
+    pub errors: Vec<String>,
+    pub valid: bool,
+    /// Files and bytes moved, folders created, no-ops, ...
+    pub stats: PlanDiffStats,
+}
+
+/// Simulate a plan against a fresh scan of its target (and source) folders
+pub async fn preview_plan(plan: &OrganizePlan) -> Result<PlanPreview, String> {
+    let mut vfs = scan_target(plan).await?;
+
+    let operations = plan.to_simulated_operations();
+    let stats = PlanDiff::build(&vfs, &operations).stats;
+    let errors = simulate_plan(&mut vfs, operations).err().unwrap_or_default();
+
+    Ok(PlanPreview {
+        plan_id: plan.plan_id.clone(),
+        target_folder: plan.target_folder.clone(),
+        changes: plan_changes(plan),
+        valid: errors.is_empty(),
+        errors,
+        stats,
+    })
+}
+
+/// Before/after diff of a plan against a fresh scan of its target (and
+/// source) folders, ready to render as a tree, JSON, Markdown or HTML report
+pub async fn diff_plan(plan: &OrganizePlan) -> Result<PlanDiff, String> {
+    let vfs = scan_target(plan).await?;
+
+    Ok(PlanDiff::build(&vfs, &plan.to_simulated_operations())
+        .with_plan(&plan.plan_id, &plan.description))
+}
+
+async fn scan_target(plan: &OrganizePlan) -> Result<ShadowVFS, String> {
+    let root = validate_folder(Path::new(&plan.target_folder))?;
+    let scanner = JWalkScanner::new().with_extract_previews(false);
+    let mut vfs = ShadowVFS::new(root.clone());
+    scanner.scan(&root, &mut vfs).await?;
+
+    // Source folders outside the target join the graph as extra roots
+    for source in &plan.source_roots {
+        let source = validate_folder(Path::new(source))?;
+        if vfs.exists(&source) {
+            continue;
+        }
+        vfs.insert(FileNode::directory(source.clone()));
+        scanner.scan(&source, &mut vfs).await?;
+    }
+    Ok(vfs)
+}
+
+/// Render a plan as diff lines, folder creations first.
+/// Paths are shown relative to the plan's target folder. Moves planned by
+/// the Builder carry its explanation, unless the move was retargeted since.
+pub fn plan_changes(plan: &OrganizePlan) -> Vec<PlanChange> {
+    let root = Path::new(&plan.target_folder);
+    let rel = |p: &str| -> String {
+        Path::new(p)
+            .strip_prefix(root)
+            .map(|r| r.to_string_lossy().to_string())
+            .unwrap_or_else(|_| p.to_string())
+    };
+
+    let mut changes: Vec<PlanChange> = plan
+        .operations
+        .iter()
+        .filter_map(|op| match op.op_type.as_str() {
+            "create_folder" => Some(PlanChange {
+                marker: '+',
+                path: format!("{}/", rel(op.path.as_ref()?)),
+                to: None,
+                why: None,
+            }),
+            "move" => {
+                let to = rel(op.destination.as_ref()?);
+                let folder = Path::new(&to).parent().map(|p| p.to_string_lossy().to_string());
+                let why = plan
+                    .explanations
+                    .get(&op.op_id)
+                    .filter(|explanation| {
+                        explanation.folder.as_deref().map(|f| f.trim_matches('/')) == folder.as_deref()
+                    })
+                    .map(|explanation| explanation.summary());
+                Some(PlanChange {
+                    marker: '~',
+                    path: rel(op.source.as_ref()?),
+                    to: Some(to),
+                    why,
+                })
+            }
+            "rename" => {
+                let path = op.path.as_ref()?;
+                let renamed = Path::new(path).with_file_name(op.new_name.as_ref()?);
+                Some(PlanChange {
+                    marker: '~',
+                    path: rel(path),
+                    to: Some(rel(&renamed.to_string_lossy())),
+                    why: None,
+                })
+            }
+            "trash" | "quarantine" | "delete" => Some(PlanChange {
+                marker: '-',
+                path: rel(op.path.as_ref().or(op.source.as_ref())?),
+                to: None,
+                why: None,
+            }),
+            "symlink" | "alias" => Some(PlanChange {
+                marker: '@',
+                path: rel(op.path.as_ref()?),
+                to: Some(rel(op.attributes.target.as_ref()?)),
+                why: None,
+            }),
+            "set_timestamps" | "set_permissions" | "set_tags" | "set_xattr" => Some(PlanChange {
+                marker: '*',
+                path: format!("{} ({})", rel(op.path.as_ref()?), metadata_change(op)),
+                to: None,
+                why: None,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    changes.sort_by_key(|c| match c.marker {
+        '+' => 0,
+        '~' => 1,
+        '@' => 2,
+        '*' => 3,
+        _ => 4,
+    });
+    changes
+}
+
+/// Short description of a `set_*` operation for plan diffs
+fn metadata_change(op: &OrganizeOperation) -> String {
+    let attributes = &op.attributes;
+    match op.op_type.as_str() {
+        "set_timestamps" => {
+            let date = |t: &chrono::DateTime<chrono::Utc>| t.format("%Y-%m-%d").to_string();
+            match (&attributes.modified, &attributes.accessed) {
+                (Some(m), _) => format!("modified {}", date(m)),
+                (None, Some(a)) => format!("accessed {}", date(a)),
+                (None, None) => "times".to_string(),
+            }
+        }
+        "set_permissions" => format!("mode {}", attributes.mode.as_deref().unwrap_or("?")),
+        "set_tags" => match attributes.tags.as_deref() {
+            Some([]) | None => "clear tags".to_string(),
+            Some(tags) => format!("tags {}", tags.join(", ")),
+        },
+        _ => format!("xattr {}", attributes.xattr_name.as_deref().unwrap_or("?")),
+    }
+}
+
+/// Refuse plans that touch anything outside their folders, the target and
+/// any source folders (plan files are plain JSON and may have been edited
+/// by hand)
+pub fn check_plan_paths(plan: &OrganizePlan, roots: &[PathBuf]) -> Result<(), String> {
+    let inside = |p: &str| {
+        let path = Path::new(p);
+        roots.iter().any(|root| path.starts_with(root))
+            && !path.components().any(|c| c == Component::ParentDir)
+    };
+
+    for op in &plan.operations {
+        for p in [&op.source, &op.destination, &op.path].into_iter().flatten() {
+            if !inside(p) {
+                let folders: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+                return Err(format!(
+                    "Operation '{}' touches {} outside the plan folder {}",
+                    op.op_id,
+                    p,
+                    folders.join(", ")
+                ));
+            }
+        }
+        if let Some(ref name) = op.new_name {
+            if name.contains('/') || name.contains('\\') || name == ".." || name == "." {
+                return Err(format!(
+                    "Operation '{}' has an invalid new name: {}",
+                    op.op_id, name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+/// Predict how a plan would execute against the disk as it is now.
+///
+/// The plan is journaled in memory only (nothing is saved or touched), then
+/// checked entry by entry under `config`'s conflict policy, with free space
+/// per volume. See [`PreflightReport`].
+pub fn preflight_plan(plan: &OrganizePlan, config: &ExecutionConfig) -> Result<PreflightReport, String> {
+    let journal = plan.to_wal_journal()?;
+    preflight_journal(&journal, config)
+}
+
+/// Capture a baseline of the plan folder (off the async runtime) so the
+/// plan can be re-based before it runs. See [`OrganizePlan::with_baseline`].
+pub async fn attach_baseline(plan: OrganizePlan) -> Result<OrganizePlan, String> {
+    tokio::task::spawn_blocking(move || plan.with_baseline())
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Re-base a plan that carries a baseline, emitting `plan-rebased` (a
+/// [`RebaseSummary`](crate::jobs::RebaseSummary)) when the folder changed
+/// since it was made. Plans without a baseline are returned as they are.
+pub async fn rebase_for_execution(
+    plan: &OrganizePlan,
+    events: &SharedEventSink,
+) -> Result<OrganizePlan, String> {
+    if plan.baseline.is_none() {
+        return Ok(plan.clone());
+    }
+    let stale = plan.clone();
+    let rebased = tokio::task::spawn_blocking(move || rebase_plan(&stale))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+
+    if !rebased.summary.is_unchanged() {
+        tracing::info!(plan = %plan.plan_id, "Re-based plan: {}", rebased.summary);
+        events.emit("plan-rebased", &rebased.summary);
+    }
+    Ok(rebased.plan)
+}
+
+/// Journal a plan in the WAL and execute it through the DAG executor.
+///
+/// A plan with a baseline is re-based first (see [`rebase_for_execution`]).
+/// Events: `plan-rebased`, `execution-progress` `{completed, total}` and
+/// `execution-op-complete` `{affectedDirs}`. The journal (id = plan id) is
+/// left in place; callers discard it on success or keep it for undo.
+pub async fn execute_plan(
+    plan: &OrganizePlan,
+    config: ExecutionConfig,
+    events: SharedEventSink,
+) -> Result<ExecutionResult, String> {
+    let plan = &rebase_for_execution(plan, &events).await?;
+    let journal = plan.to_wal_journal()?;
+    tracing::debug!(entries = journal.entries.len(), "Created WAL journal");
+
+    WALManager::new()
+        .save_journal(&journal)
+        .map_err(|e| format!("Failed to save WAL journal: {}", e.message))?;
+
+    let progress_events = Arc::clone(&events);
+    let progress_callback: Arc<ProgressCallback> = Arc::new(Box::new(move |completed, total| {
+        progress_events.emit_json(
+            "execution-progress",
+            serde_json::json!({
+                "completed": completed,
+                "total": total
+            }),
+        );
+        tracing::debug!(completed = completed, total = total, "Execution progress");
+    }));
+
+    ExecutionEngine::new()
+        .execute_journal_with_config_and_events(
+            &plan.plan_id,
+            Some(progress_callback),
+            config,
+            Some(events),
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::OrganizeOperation;
+
+    fn op(op_type: &str, source: Option<&str>, destination: Option<&str>, path: Option<&str>, new_name: Option<&str>) -> OrganizeOperation {
+        OrganizeOperation {
+            op_id: format!("op-{}", op_type),
+            op_type: op_type.to_string(),
+            source: source.map(String::from),
+            destination: destination.map(String::from),
+            path: path.map(String::from),
+            new_name: new_name.map(String::from),
+            attributes: Default::default(),
+        }
+    }
+
+    fn sample_plan() -> OrganizePlan {
+        OrganizePlan {
+            plan_id: "plan-1".to_string(),
+            description: String::new(),
+            operations: vec![
+                op("move", Some("/srv/share/a.pdf"), Some("/srv/share/Docs/a.pdf"), None, None),
+                op("trash", None, None, Some("/srv/share/old.tmp"), None),
+                op("create_folder", None, None, Some("/srv/share/Docs"), None),
+                op("rename", None, None, Some("/srv/share/b.txt"), Some("c.txt")),
+            ],
+            target_folder: "/srv/share".to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_plan_changes() {
+        let lines: Vec<String> = plan_changes(&sample_plan())
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "+ Docs/",
+                "~ a.pdf -> Docs/a.pdf",
+                "~ b.txt -> c.txt",
+                "- old.tmp",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_changes_explain_moves() {
+        use crate::ai::v2::{MatchExplanation, MatchThresholds, MatchTier};
+
+        let mut plan = sample_plan();
+        let explanation = MatchExplanation {
+            tier: MatchTier::Unmatched,
+            folder: Some("Docs".to_string()),
+            top_folders: Vec::new(),
+            extension: Some("pdf".to_string()),
+            correction: None,
+            thresholds: MatchThresholds::default(),
+        };
+        plan.explanations.insert("op-move".to_string(), explanation.clone());
+
+        let changes = plan_changes(&plan);
+        let moved = changes.iter().find(|c| c.path == "a.pdf").unwrap();
+        assert_eq!(moved.why, Some(explanation.summary()));
+        assert!(changes.iter().filter(|c| c.path != "a.pdf").all(|c| c.why.is_none()));
+
+        // Retargeted after planning: the explanation no longer applies
+        plan.operations[0].destination = Some("/srv/share/Other/a.pdf".to_string());
+        assert!(plan_changes(&plan).iter().all(|c| c.why.is_none()));
+    }
+
+    #[test]
+    fn test_check_plan_paths() {
+        let target = &[PathBuf::from("/srv/share")];
+        assert!(check_plan_paths(&sample_plan(), target).is_ok());
+
+        let mut escaping = sample_plan();
+        escaping.operations.push(op("move", Some("/srv/share/a"), Some("/etc/a"), None, None));
+        assert!(check_plan_paths(&escaping, target).is_err());
+
+        let mut dotdot = sample_plan();
+        dotdot.operations.push(op("create_folder", None, None, Some("/srv/share/../x"), None));
+        assert!(check_plan_paths(&dotdot, target).is_err());
+
+        let mut bad_name = sample_plan();
+        bad_name.operations.push(op("rename", None, None, Some("/srv/share/a"), Some("../a")));
+        assert!(check_plan_paths(&bad_name, target).is_err());
+
+        // Moves out of a source folder are fine once that folder is a root
+        let mut gathering = sample_plan();
+        gathering.operations.push(op(
+            "move",
+            Some("/home/me/Desktop/b.pdf"),
+            Some("/srv/share/Docs/b.pdf"),
+            None,
+            None,
+        ));
+        assert!(check_plan_paths(&gathering, target).is_err());
+        let roots = [PathBuf::from("/srv/share"), PathBuf::from("/home/me/Desktop")];
+        assert!(check_plan_paths(&gathering, &roots).is_ok());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule = r#"{"name": "PDFs", "if": "file.ext == 'pdf'", "thenMoveTo": "Documents/PDF"}"#;
+        assert_eq!(parse_rules(&format!("[{}]", rule)).unwrap().len(), 1);
+        assert_eq!(parse_rules(&format!("{{\"rules\": [{}]}}", rule)).unwrap().len(), 1);
+        assert!(parse_rules("[]").is_err());
+        assert!(parse_rules("{\"other\": []}").is_err());
+        assert!(parse_rules("not json").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+//! Crash recovery and undo over the WAL journals.
+//!
+//! The heavy lifting lives in `wal::recovery`; this adds the compact journal
+//! listing shown by `sentinel journals`.
+
+use crate::wal::{WALJournal, WALManager};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// One journal in [`list_journals`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalSummary {
+    pub job_id: String,
+    pub target_folder: String,
+    pub started_at: DateTime<Utc>,
+    /// "interrupted", "failed", "complete" or "rolled_back"
+    pub state: &'static str,
+    pub total: usize,
+    pub pending: usize,
+    pub complete: usize,
+    pub failed: usize,
+}
+
+impl From<&WALJournal> for JournalSummary {
+    fn from(journal: &WALJournal) -> Self {
+        let (pending, in_progress, complete, failed) = journal.status_counts();
+        let state = if pending + in_progress > 0 {
+            "interrupted"
+        } else if failed > 0 {
+            "failed"
+        } else if complete > 0 {
+            "complete"
+        } else {
+            "rolled_back"
+        };
+
+        Self {
+            job_id: journal.job_id.clone(),
+            target_folder: journal.target_folder.to_string_lossy().to_string(),
+            started_at: journal.started_at,
+            state,
+            total: journal.entries.len(),
+            pending: pending + in_progress,
+            complete,
+            failed,
+        }
+    }
+}
+
+/// Summaries of all WAL journals, newest first. Unreadable journals are skipped.
+pub fn list_journals() -> Result<Vec<JournalSummary>, String> {
+    let manager = WALManager::new();
+    let mut summaries: Vec<JournalSummary> = manager
+        .list_journals()
+        .map_err(|e| e.message)?
+        .iter()
+        .filter_map(|id| manager.load_journal(id).ok().flatten())
+        .map(|journal| JournalSummary::from(&journal))
+        .collect();
+    summaries.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(summaries)
+}
+
+/// Directory the WAL journals are stored in
+pub fn journal_dir() -> PathBuf {
+    WALManager::new().get_wal_dir()
+}
@@ -0,0 +1,95 @@
+//! Semantic search over a folder with the local fastembed index.
+
+use crate::models::FileEntry;
+use crate::scan::ScanOptions;
+use crate::vector::{VectorConfig, VectorIndex};
+use std::path::{Path, PathBuf};
+
+/// Directory depth indexed by [`build_search_index`]
+pub const SEARCH_INDEX_DEPTH: usize = 5;
+
+/// Build a vector index over the files in `folder`.
+///
+/// Returns the index and the number of documents indexed.
+/// Note: This downloads the embedding model on first use (~100MB)
+pub fn build_search_index(folder: &Path) -> Result<(VectorIndex, usize), String> {
+    if !folder.is_dir() {
+        return Err(format!("Invalid folder path: {}", folder.display()));
+    }
+
+    let mut index = VectorIndex::new(VectorConfig::default())?;
+
+    let files = collect_files(folder, SEARCH_INDEX_DEPTH)?;
+    eprintln!("[Search] Found {} files to index", files.len());
+
+    // Prepare batch for indexing
+    let batch: Vec<(PathBuf, String, Option<String>)> = files
+        .into_iter()
+        .map(|entry| {
+            let file_path = PathBuf::from(&entry.path);
+            let content_preview = get_content_preview(&file_path);
+            (file_path, entry.name, content_preview)
+        })
+        .collect();
+
+    // Index in batches of 100 for memory efficiency
+    let mut total_indexed = 0;
+    for chunk in batch.chunks(100) {
+        match index.index_batch(chunk.to_vec()) {
+            Ok(count) => {
+                total_indexed += count;
+                eprintln!("[Search] Indexed {} files (total: {})", count, total_indexed);
+            }
+            Err(e) => {
+                eprintln!("[Search] Warning: Batch indexing failed: {}", e);
+            }
+        }
+    }
+
+    Ok((index, total_indexed))
+}
+
+/// Search an index with a natural language query.
+///
+/// Returns (path, similarity score) pairs sorted by relevance.
+pub fn search(index: &VectorIndex, query: &str) -> Result<Vec<(String, f32)>, String> {
+    Ok(index
+        .search(query)?
+        .into_iter()
+        .map(|(path, score)| (path.to_string_lossy().to_string(), score))
+        .collect())
+}
+
+/// Collect the files below a directory with the shared scanner
+fn collect_files(path: &Path, max_depth: usize) -> Result<Vec<FileEntry>, String> {
+    let snapshot = ScanOptions::new().with_max_depth(max_depth).scan(path)?;
+    Ok(snapshot.files().map(FileEntry::from_scan_entry).collect())
+}
+
+/// Get a content preview for a file (for better semantic matching)
+///
+/// Currently supports text files; returns None for binary files
+fn get_content_preview(path: &Path) -> Option<String> {
+    // Only read text files
+    let extension = path.extension()?.to_str()?;
+
+    let text_extensions = [
+        "txt", "md", "json", "yaml", "yml", "toml", "xml", "html", "css",
+        "js", "ts", "jsx", "tsx", "py", "rs", "go", "java", "c", "cpp",
+        "h", "hpp", "swift", "kt", "rb", "php", "sh", "bash", "zsh",
+        "sql", "csv", "log", "conf", "ini", "env", "gitignore",
+    ];
+
+    if !text_extensions.contains(&extension.to_lowercase().as_str()) {
+        return None;
+    }
+
+    // Read first 500 bytes
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            let preview_len = bytes.len().min(500);
+            String::from_utf8(bytes[..preview_len].to_vec()).ok()
+        }
+        Err(_) => None,
+    }
+}
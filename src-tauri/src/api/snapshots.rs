@@ -0,0 +1,116 @@
+//! Persisted folder snapshots and change reports.
+//!
+//! Storage and diffing live in `vfs::snapshots`; this adds the scan-and-save
+//! and "what changed since ..." flows used by the CLI and the app.
+
+use super::organize::scan_folder;
+use crate::vfs::{ShadowVFS, SnapshotDiff, SnapshotInfo, SnapshotStore};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::path::Path;
+
+/// Save an already scanned VFS as a snapshot (hashing runs off the async runtime)
+pub async fn save_snapshot(vfs: ShadowVFS, label: Option<String>) -> Result<SnapshotInfo, String> {
+    tokio::task::spawn_blocking(move || {
+        SnapshotStore::open_default()?.capture_vfs(&vfs, label.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Scan `folder` and save the result as a new snapshot
+pub async fn capture_snapshot(folder: &Path, label: Option<&str>) -> Result<SnapshotInfo, String> {
+    let (vfs, _) = scan_folder(folder, 0).await?;
+    save_snapshot(vfs, label.map(str::to_string)).await
+}
+
+/// Snapshots of `folder` (all folders when None), newest first
+pub fn list_snapshots(folder: Option<&Path>) -> Result<Vec<SnapshotInfo>, String> {
+    SnapshotStore::open_default()?.list(folder)
+}
+
+/// Changes between two saved snapshots
+pub fn diff_snapshots(from: i64, to: i64) -> Result<SnapshotDiff, String> {
+    SnapshotStore::open_default()?.diff(from, to)
+}
+
+/// Scan `folder` now and report what changed since the newest snapshot taken
+/// at or before `since` (or since the newest snapshot when None).
+///
+/// The fresh scan is saved too, so the next report is incremental.
+pub async fn changes_since(
+    folder: &Path,
+    since: Option<DateTime<Utc>>,
+) -> Result<SnapshotDiff, String> {
+    let store = SnapshotStore::open_default()?;
+    let baseline = store
+        .latest_before(folder, since.unwrap_or_else(Utc::now))?
+        .ok_or_else(|| match since {
+            Some(since) => format!(
+                "No snapshot of {} taken before {}",
+                folder.display(),
+                since.format("%Y-%m-%d %H:%M")
+            ),
+            None => format!("No snapshot of {} yet", folder.display()),
+        })?;
+
+    let current = capture_snapshot(folder, None).await?;
+    store.diff(baseline.id, current.id)
+}
+
+/// Parse a `since` argument: RFC 3339, `YYYY-MM-DD[ HH:MM]` in local time, or
+/// a relative age like `36h` / `7d`
+pub fn parse_since(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let local = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        });
+    if let Some(local) = local {
+        return Local
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| format!("Invalid local time: {}", text));
+    }
+
+    let hours = text.strip_suffix('h').and_then(|n| n.parse::<i64>().ok());
+    let days = text.strip_suffix('d').and_then(|n| n.parse::<i64>().ok());
+    let age = match (hours, days) {
+        (Some(n), _) => Duration::hours(n),
+        (_, Some(n)) => Duration::days(n),
+        _ => {
+            return Err(format!(
+                "Invalid date: {} (use YYYY-MM-DD, RFC 3339, or an age like 7d)",
+                text
+            ))
+        }
+    };
+    Ok(Utc::now() - age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        let utc = parse_since("2024-03-01T12:00:00Z").unwrap();
+        assert_eq!(utc.to_rfc3339(), "2024-03-01T12:00:00+00:00");
+
+        let day = parse_since("2024-03-01").unwrap();
+        assert_eq!(day.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(), "2024-03-01 00:00");
+
+        let week = parse_since("7d").unwrap();
+        assert!((Utc::now() - week - Duration::days(7)).num_seconds().abs() < 5);
+
+        assert!(parse_since("last friday").is_err());
+        assert!(parse_since("").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! Headless `sentinel` CLI: scan, plan, preview, execute and undo without the desktop UI.
+
+fn main() {
+    std::process::exit(tauri_app_lib::cli::run())
+}
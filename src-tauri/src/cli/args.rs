@@ -0,0 +1,371 @@
+//! Command-line argument parsing for the `sentinel` binary.
+//!
+//! Hand-rolled to keep the headless build free of extra dependencies.
+
+use crate::execution::ConflictPolicy;
+use crate::vfs::DiffFormat;
+use std::path::PathBuf;
+
+/// Usage text printed by `sentinel help` and on argument errors
+pub const USAGE: &str = "\
+sentinel - headless file organizer
+
+USAGE:
+    sentinel <COMMAND> [OPTIONS]
+
+COMMANDS:
+    scan <folder> [--max-depth N]         Scan a folder into the VFS and print statistics
+    plan <folder> --rules <file> [--from <folder>]... [--out <plan.json>]
+                                          Apply organization rules (DSL) and write a plan
+                                          (stdout when --out is omitted); --from gathers the
+                                          files of other folders into <folder> in the same plan
+    rebase <plan.json> [--out <plan.json>]
+                                          Bring a plan up to date with its folder: follow moved
+                                          sources, drop deleted ones, plan newly added files
+    preview <plan.json> [--format text|tree|json|markdown|html] [--out <file>]
+                                          Simulate a plan on the VFS and print the changes, or a
+                                          before/after report with folder counts, sizes, no-ops
+                                          and conflicts (for attaching to an approval ticket)
+    execute <plan.json> [--conflict auto_rename|skip|fail] [--force] [--dry-run]
+                                          Execute a plan through the WAL (journal kept for undo);
+                                          --dry-run only checks it against the disk (missing
+                                          sources, conflicts, permissions, free space); plans
+                                          are re-based first when the folder changed
+    snapshot <folder> [--label <text>]    Scan a folder and save a snapshot (path, size, mtime, hash)
+    snapshots [<folder>]                  List saved snapshots
+    changes <folder> [--since <date>]     Rescan and report files added, removed, modified or moved
+                                          since a date (YYYY-MM-DD, RFC 3339, 7d) or the last snapshot
+    diff <from-id> <to-id>                Compare two saved snapshots
+    journals                              List WAL journals
+    undo <job-id>                         Roll back a journal
+    resume <job-id>                       Finish the pending operations of a journal
+    discard <job-id>                      Delete a journal without touching files
+    mcp --root <folder> [--root ...]      Serve the file tools over MCP (JSON-RPC on stdio),
+                                          limited to the given folders
+    eval <case.json> [--out <report.json>] [--baseline <report.json>]
+                                          Plan a fixture folder offline and score it against its
+                                          golden layout; with --baseline, print what changed and
+                                          fail when accuracy dropped
+    help                                  Show this message
+
+GLOBAL OPTIONS:
+    --json                                Print machine-readable JSON instead of text
+
+Rules files are JSON: a list (or {\"rules\": [...]}) of
+    {\"name\": \"PDFs\", \"if\": \"file.ext == 'pdf'\", \"thenMoveTo\": \"Documents/PDF\"}
+";
+
+/// A parsed `sentinel` subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Scan { folder: PathBuf, max_depth: usize },
+    Plan { folder: PathBuf, sources: Vec<PathBuf>, rules: PathBuf, output: Option<PathBuf> },
+    Rebase { plan: PathBuf, output: Option<PathBuf> },
+    /// `format: None` is the plain change list (`--format text`)
+    Preview { plan: PathBuf, format: Option<DiffFormat>, output: Option<PathBuf> },
+    Execute { plan: PathBuf, conflict: ConflictPolicy, force: bool, dry_run: bool },
+    Snapshot { folder: PathBuf, label: Option<String> },
+    Snapshots { folder: Option<PathBuf> },
+    Changes { folder: PathBuf, since: Option<String> },
+    Diff { from: i64, to: i64 },
+    Journals,
+    Undo { job_id: String },
+    Resume { job_id: String },
+    Discard { job_id: String },
+    Mcp { roots: Vec<PathBuf> },
+    Eval { case: PathBuf, output: Option<PathBuf>, baseline: Option<PathBuf> },
+    Help,
+}
+
+/// Parsed command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliArgs {
+    pub command: Command,
+    /// Emit JSON on stdout
+    pub json: bool,
+}
+
+/// Parse arguments (without the program name)
+pub fn parse<I, S>(args: I) -> Result<CliArgs, String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut json = false;
+    let mut help = false;
+    let mut positional = Vec::new();
+    let mut options: Vec<(String, Option<String>)> = Vec::new();
+
+    let mut iter = args.into_iter().map(Into::into);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--force" | "--dry-run" => options.push((arg, None)),
+            "-h" | "--help" => help = true,
+            flag if flag.starts_with("--") => {
+                let (name, value) = match flag.split_once('=') {
+                    Some((name, value)) => (name.to_string(), value.to_string()),
+                    None => {
+                        let value = iter
+                            .next()
+                            .ok_or_else(|| format!("Missing value for {}", flag))?;
+                        (flag.to_string(), value)
+                    }
+                };
+                options.push((name, Some(value)));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    if help {
+        return Ok(CliArgs { command: Command::Help, json });
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional.next().unwrap_or_else(|| "help".to_string());
+    let mut take_option = |name: &str| -> Option<String> {
+        let idx = options.iter().position(|(n, _)| n == name)?;
+        options.remove(idx).1.or_else(|| Some(String::new()))
+    };
+
+    let command = match name.as_str() {
+        "scan" => Command::Scan {
+            folder: required(positional.next(), "scan", "<folder>")?.into(),
+            max_depth: match take_option("--max-depth") {
+                Some(depth) => depth
+                    .parse()
+                    .map_err(|_| format!("Invalid --max-depth: {}", depth))?,
+                None => 0,
+            },
+        },
+        "plan" => Command::Plan {
+            folder: required(positional.next(), "plan", "<folder>")?.into(),
+            sources: std::iter::from_fn(|| take_option("--from"))
+                .map(PathBuf::from)
+                .collect(),
+            rules: required(take_option("--rules"), "plan", "--rules <file>")?.into(),
+            output: take_option("--out").map(PathBuf::from),
+        },
+        "rebase" => Command::Rebase {
+            plan: required(positional.next(), "rebase", "<plan.json>")?.into(),
+            output: take_option("--out").map(PathBuf::from),
+        },
+        "preview" => Command::Preview {
+            plan: required(positional.next(), "preview", "<plan.json>")?.into(),
+            format: match take_option("--format").as_deref() {
+                Some("text") | None => None,
+                Some(other) => Some(DiffFormat::parse(other)?),
+            },
+            output: take_option("--out").map(PathBuf::from),
+        },
+        "execute" => Command::Execute {
+            plan: required(positional.next(), "execute", "<plan.json>")?.into(),
+            conflict: match take_option("--conflict").as_deref() {
+                Some("auto_rename") | None => ConflictPolicy::AutoRename,
+                Some("skip") => ConflictPolicy::Skip,
+                Some("fail") => ConflictPolicy::Fail,
+                Some(other) => return Err(format!("Unknown conflict policy: {}", other)),
+            },
+            force: take_option("--force").is_some(),
+            dry_run: take_option("--dry-run").is_some(),
+        },
+        "snapshot" => Command::Snapshot {
+            folder: required(positional.next(), "snapshot", "<folder>")?.into(),
+            label: take_option("--label"),
+        },
+        "snapshots" => Command::Snapshots {
+            folder: positional.next().map(PathBuf::from),
+        },
+        "changes" => Command::Changes {
+            folder: required(positional.next(), "changes", "<folder>")?.into(),
+            since: take_option("--since"),
+        },
+        "diff" => Command::Diff {
+            from: snapshot_id(positional.next(), "<from-id>")?,
+            to: snapshot_id(positional.next(), "<to-id>")?,
+        },
+        "journals" => Command::Journals,
+        "undo" => Command::Undo {
+            job_id: required(positional.next(), "undo", "<job-id>")?,
+        },
+        "resume" => Command::Resume {
+            job_id: required(positional.next(), "resume", "<job-id>")?,
+        },
+        "discard" => Command::Discard {
+            job_id: required(positional.next(), "discard", "<job-id>")?,
+        },
+        "mcp" => Command::Mcp {
+            roots: std::iter::from_fn(|| take_option("--root"))
+                .map(PathBuf::from)
+                .collect(),
+        },
+        "eval" => Command::Eval {
+            case: required(positional.next(), "eval", "<case.json>")?.into(),
+            output: take_option("--out").map(PathBuf::from),
+            baseline: take_option("--baseline").map(PathBuf::from),
+        },
+        "help" => Command::Help,
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+
+    if let Command::Preview { format: None, output: Some(_), .. } = command {
+        return Err("`preview --out` needs --format tree|json|markdown|html".to_string());
+    }
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument: {}", extra));
+    }
+    if let Some((flag, _)) = options.first() {
+        return Err(format!("Unknown option for {}: {}", name, flag));
+    }
+
+    Ok(CliArgs { command, json })
+}
+
+fn required(value: Option<String>, command: &str, what: &str) -> Result<String, String> {
+    value
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("`{}` requires {}", command, what))
+}
+
+fn snapshot_id(value: Option<String>, what: &str) -> Result<i64, String> {
+    let value = required(value, "diff", what)?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid snapshot id: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan() {
+        let args = parse(["plan", "/srv/share", "--rules", "rules.json", "--json"]).unwrap();
+        assert!(args.json);
+        assert_eq!(
+            args.command,
+            Command::Plan {
+                folder: PathBuf::from("/srv/share"),
+                sources: vec![],
+                rules: PathBuf::from("rules.json"),
+                output: None,
+            }
+        );
+
+        let args = parse([
+            "plan", "/srv/sorted", "--from", "/home/me/Desktop", "--from=/home/me/Downloads",
+            "--rules", "rules.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Plan {
+                folder: PathBuf::from("/srv/sorted"),
+                sources: vec![PathBuf::from("/home/me/Desktop"), PathBuf::from("/home/me/Downloads")],
+                rules: PathBuf::from("rules.json"),
+                output: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_preview_format() {
+        let args =
+            parse(["preview", "plan.json", "--format", "markdown", "--out", "plan.md"]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Preview {
+                plan: PathBuf::from("plan.json"),
+                format: Some(DiffFormat::Markdown),
+                output: Some(PathBuf::from("plan.md")),
+            }
+        );
+
+        let args = parse(["preview", "plan.json", "--format=text"]).unwrap();
+        assert!(matches!(args.command, Command::Preview { format: None, output: None, .. }));
+        assert!(parse(["preview", "plan.json", "--format", "pdf"]).is_err());
+        assert!(parse(["preview", "plan.json", "--out", "plan.md"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_execute_options() {
+        let args = parse(["execute", "plan.json", "--conflict=skip", "--force"]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Execute {
+                plan: PathBuf::from("plan.json"),
+                conflict: ConflictPolicy::Skip,
+                force: true,
+                dry_run: false,
+            }
+        );
+
+        let args = parse(["execute", "plan.json"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Command::Execute { conflict: ConflictPolicy::AutoRename, force: false, .. }
+        ));
+
+        let args = parse(["execute", "plan.json", "--dry-run"]).unwrap();
+        assert!(matches!(args.command, Command::Execute { dry_run: true, .. }));
+    }
+
+    #[test]
+    fn test_parse_mcp_roots() {
+        let args = parse(["mcp", "--root", "/srv/a", "--root=/srv/b"]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Mcp { roots: vec![PathBuf::from("/srv/a"), PathBuf::from("/srv/b")] }
+        );
+        assert_eq!(parse(["mcp"]).unwrap().command, Command::Mcp { roots: vec![] });
+    }
+
+    #[test]
+    fn test_parse_snapshot_commands() {
+        let args = parse(["changes", "/srv/share", "--since", "2024-03-01"]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Changes {
+                folder: PathBuf::from("/srv/share"),
+                since: Some("2024-03-01".to_string()),
+            }
+        );
+        assert_eq!(parse(["diff", "3", "7"]).unwrap().command, Command::Diff { from: 3, to: 7 });
+        assert_eq!(parse(["snapshots"]).unwrap().command, Command::Snapshots { folder: None });
+        assert!(parse(["diff", "3"]).is_err());
+        assert!(parse(["diff", "3", "latest"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_eval() {
+        let args = parse(["eval", "cases/downloads.json", "--baseline", "last.json"]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Eval {
+                case: PathBuf::from("cases/downloads.json"),
+                output: None,
+                baseline: Some(PathBuf::from("last.json")),
+            }
+        );
+        assert!(parse(["eval"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(["plan", "/srv/share"]).is_err());
+        assert!(parse(["undo"]).is_err());
+        assert!(parse(["scan", "/a", "/b"]).is_err());
+        assert!(parse(["scan", "/a", "--rules", "x"]).is_err());
+        assert!(parse(["scan", "/a", "--max-depth"]).is_err());
+        assert!(parse(["frobnicate"]).is_err());
+        assert!(parse(["execute", "p.json", "--conflict", "overwrite"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_help() {
+        assert_eq!(parse(Vec::<String>::new()).unwrap().command, Command::Help);
+        assert_eq!(parse(["--help"]).unwrap().command, Command::Help);
+        assert_eq!(parse(["scan", "-h"]).unwrap().command, Command::Help);
+    }
+}
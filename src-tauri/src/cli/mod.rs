@@ -0,0 +1,758 @@
+//! Headless command-line interface (the `sentinel` binary).
+//!
+//! Drives the same pipeline as the desktop app through `crate::api`, so
+//! organize rules can run from cron on a server:
+//! 1. `scan` - JWalkScanner into the shadow VFS
+//! 2. `plan` - apply a rules file through the rules DSL
+//! 3. `preview` - validate with `vfs::simulate_plan` and print the changes,
+//!    or render a `vfs::PlanDiff` report (`--format tree|json|markdown|html`)
+//! 4. `execute` - run the plan through the WAL and `ExecutionEngine`
+//!    (`--dry-run` only preflights it against the disk)
+//! 5. `journals` / `undo` / `resume` / `discard` - `wal::recovery`
+//!
+//! `snapshot`, `snapshots`, `changes` and `diff` save scans to the snapshot
+//! store and report what changed between them. `eval` scores the planner
+//! against golden fixture layouts (see `ai::v2::eval`).
+//!
+//! Plans are plain `OrganizePlan` JSON files, so plans exported by the app
+//! can be previewed and executed here too. Journals of successful runs are
+//! kept so they can be undone later.
+
+mod args;
+
+use args::{parse, CliArgs, Command, USAGE};
+
+use crate::api::{
+    self, check_plan_paths, discard_journal, resume_journal, rollback_journal, validate_folder,
+    ConflictPolicy, DiffFormat, EvalReport, ExecutionConfig, ExecutionResult, NoopSink,
+    OrganizePlan, PredictedOutcome, PreflightReport, RebaseSummary, RecoveryResult, ScanStats,
+    SnapshotDiff,
+};
+use crate::utils::format_size;
+use crate::wal::WALManager;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
+
+/// Exit code for invalid arguments
+const EXIT_USAGE: i32 = 2;
+
+/// Run the CLI with the process arguments. Returns the exit code.
+pub fn run() -> i32 {
+    // Logs go to stderr so stdout stays clean for plans and JSON
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .init();
+
+    let args = match parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    match dispatch(args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn dispatch(args: CliArgs) -> Result<i32, String> {
+    let json = args.json;
+    let runtime = || {
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start runtime: {}", e))
+    };
+
+    match args.command {
+        Command::Scan { folder, max_depth } => runtime()?.block_on(scan(&folder, max_depth, json)),
+        Command::Plan { folder, sources, rules, output } => {
+            plan(&folder, &sources, &rules, output.as_deref(), json)
+        }
+        Command::Rebase { plan, output } => rebase(&plan, output.as_deref(), json),
+        Command::Preview { plan, format: None, .. } => runtime()?.block_on(preview(&plan, json)),
+        Command::Preview { plan, format: Some(format), output } => {
+            runtime()?.block_on(preview_report(&plan, format, output.as_deref()))
+        }
+        Command::Execute { plan, conflict, force, dry_run } => {
+            runtime()?.block_on(execute(&plan, conflict, force, dry_run, json))
+        }
+        Command::Snapshot { folder, label } => {
+            runtime()?.block_on(snapshot(&folder, label.as_deref(), json))
+        }
+        Command::Snapshots { folder } => snapshots(folder.as_deref(), json),
+        Command::Changes { folder, since } => {
+            let since = since.as_deref().map(api::parse_since).transpose()?;
+            let diff = runtime()?.block_on(api::changes_since(&folder, since))?;
+            report_diff(&diff, json)
+        }
+        Command::Diff { from, to } => report_diff(&api::diff_snapshots(from, to)?, json),
+        Command::Journals => journals(json),
+        Command::Undo { job_id } => {
+            report_recovery("Rolled back", &job_id, rollback_journal(&job_id)?, json)
+        }
+        Command::Resume { job_id } => {
+            report_recovery("Resumed", &job_id, resume_journal(&job_id)?, json)
+        }
+        Command::Discard { job_id } => {
+            discard_journal(&job_id)?;
+            if !json {
+                println!("Discarded journal {}", job_id);
+            }
+            Ok(0)
+        }
+        Command::Mcp { roots } => {
+            runtime()?.block_on(crate::mcp::serve_stdio(&roots))?;
+            Ok(0)
+        }
+        Command::Eval { case, output, baseline } => {
+            eval(&case, output.as_deref(), baseline.as_deref(), json)
+        }
+        Command::Help => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+    }
+}
+
+// ============================================================================
+// scan
+// ============================================================================
+
+async fn scan(folder: &Path, max_depth: usize, json: bool) -> Result<i32, String> {
+    let (vfs, stats) = api::scan_folder(folder, max_depth).await?;
+
+    if json {
+        print_json(&stats)?;
+    } else {
+        print_scan_stats(vfs.root(), &stats);
+    }
+    Ok(0)
+}
+
+fn print_scan_stats(root: &Path, stats: &ScanStats) {
+    println!("Scanned {}", root.display());
+    println!("  Files:        {}", stats.total_files);
+    println!("  Directories:  {}", stats.total_dirs);
+    println!("  Total size:   {}", format_size(stats.total_size_bytes));
+    println!("  Duration:     {} ms", stats.scan_duration_ms);
+    if stats.errors > 0 {
+        println!("  Errors:       {}", stats.errors);
+    }
+}
+
+// ============================================================================
+// plan
+// ============================================================================
+
+/// Summary printed by `plan --json --out`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlanSummary {
+    plan_id: String,
+    output: String,
+    operations: usize,
+    rules_applied: usize,
+    files_matched: usize,
+    total_files: usize,
+    rule_errors: Vec<String>,
+}
+
+fn plan(
+    folder: &Path,
+    sources: &[PathBuf],
+    rules_path: &Path,
+    output: Option<&Path>,
+    json: bool,
+) -> Result<i32, String> {
+    let rules = api::load_rules(rules_path)?;
+    let planned = api::plan_with_rules_across(
+        folder,
+        sources,
+        &rules,
+        &format!("Rules from {}", rules_path.display()),
+    )?;
+    for error in &planned.rule_errors {
+        eprintln!("warning: rule skipped - {}", error);
+    }
+
+    // Baseline for re-basing if the folder changes before the plan runs
+    let plan = &planned.plan.clone().with_baseline();
+    let plan_json = serde_json::to_string_pretty(plan)
+        .map_err(|e| format!("Failed to serialize plan: {}", e))?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, plan_json)
+                .map_err(|e| format!("Failed to write plan to {}: {}", path.display(), e))?;
+
+            if json {
+                print_json(&PlanSummary {
+                    plan_id: plan.plan_id.clone(),
+                    output: path.to_string_lossy().to_string(),
+                    operations: plan.operations.len(),
+                    rules_applied: planned.rules_applied,
+                    files_matched: planned.files_matched,
+                    total_files: planned.total_files,
+                    rule_errors: planned.rule_errors.clone(),
+                })?;
+            } else {
+                println!(
+                    "Wrote plan {} ({} operations) to {}",
+                    plan.plan_id,
+                    plan.operations.len(),
+                    path.display()
+                );
+            }
+        }
+        None => println!("{}", plan_json),
+    }
+
+    eprintln!(
+        "{} of {} rules applied, {} of {} files matched, {} operations",
+        planned.rules_applied,
+        rules.len(),
+        planned.files_matched,
+        planned.total_files,
+        plan.operations.len()
+    );
+
+    // Broken rules fail the run so `plan && execute` stops in cron
+    Ok(if planned.rule_errors.is_empty() { 0 } else { 1 })
+}
+
+// ============================================================================
+// preview
+// ============================================================================
+
+async fn preview(plan_path: &Path, json: bool) -> Result<i32, String> {
+    let plan = load_plan(plan_path)?;
+    let preview = api::preview_plan(&plan).await?;
+
+    if json {
+        print_json(&preview)?;
+    } else {
+        println!(
+            "Plan {} - {} operations in {}",
+            plan.plan_id,
+            plan.operations.len(),
+            plan.target_folder
+        );
+        if !plan.description.is_empty() {
+            println!("{}", plan.description);
+        }
+        println!();
+        for change in &preview.changes {
+            println!("  {}", change);
+            if let Some(why) = &change.why {
+                println!("      {}", why);
+            }
+        }
+        println!();
+        println!("{}", preview.stats.summary());
+        if preview.valid {
+            println!("Simulation OK: plan can be executed");
+        } else {
+            println!("Simulation found {} problems:", preview.errors.len());
+            for error in &preview.errors {
+                println!("  ! {}", error);
+            }
+        }
+    }
+
+    Ok(if preview.valid { 0 } else { 1 })
+}
+
+/// `preview --format`: render the before/after diff to stdout or a file
+async fn preview_report(
+    plan_path: &Path,
+    format: DiffFormat,
+    output: Option<&Path>,
+) -> Result<i32, String> {
+    let plan = load_plan(plan_path)?;
+    let diff = api::diff_plan(&plan).await?;
+    let report = diff.render(format)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, report)
+                .map_err(|e| format!("Failed to write report to {}: {}", path.display(), e))?;
+            println!("Wrote diff of plan {} to {}", plan.plan_id, path.display());
+        }
+        None => print!("{}", report),
+    }
+    eprintln!("{}", diff.stats.summary());
+
+    Ok(if diff.has_conflicts() { 1 } else { 0 })
+}
+
+// ============================================================================
+// rebase
+// ============================================================================
+
+fn rebase(plan_path: &Path, output: Option<&Path>, json: bool) -> Result<i32, String> {
+    let plan = load_plan(plan_path)?;
+    if plan.baseline.is_none() {
+        eprintln!("warning: plan has no baseline; only operations on missing sources are dropped");
+    }
+    let rebased = api::rebase_plan(&plan)?;
+    let plan_json = serde_json::to_string_pretty(&rebased.plan)
+        .map_err(|e| format!("Failed to serialize plan: {}", e))?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, plan_json)
+                .map_err(|e| format!("Failed to write plan to {}: {}", path.display(), e))?;
+            if json {
+                print_json(&rebased.summary)?;
+            } else {
+                print_rebase_summary(&rebased.summary);
+                println!("Wrote plan {} to {}", rebased.plan.plan_id, path.display());
+            }
+        }
+        None => {
+            println!("{}", plan_json);
+            eprintln!("{}", rebased.summary);
+        }
+    }
+    Ok(0)
+}
+
+fn print_rebase_summary(summary: &RebaseSummary) {
+    println!("{}", summary);
+    for moved in &summary.relocated {
+        println!("  ~ {} (now {})", moved.from, moved.to);
+    }
+    for dropped in &summary.dropped {
+        println!("  - {}: {}", dropped.op_id, dropped.reason);
+    }
+    for file in &summary.added {
+        println!("  + {}", file);
+    }
+    for file in &summary.unplaced {
+        println!("  ? {} (not planned)", file);
+    }
+}
+
+// ============================================================================
+// execute
+// ============================================================================
+
+/// Result of `execute --json`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteReport {
+    job_id: String,
+    #[serde(flatten)]
+    result: ExecutionResult,
+}
+
+async fn execute(
+    plan_path: &Path,
+    conflict: ConflictPolicy,
+    force: bool,
+    dry_run: bool,
+    json: bool,
+) -> Result<i32, String> {
+    let mut plan = load_plan(plan_path)?;
+    let roots = plan.roots();
+    for root in &roots {
+        validate_folder(root)?;
+    }
+    check_plan_paths(&plan, &roots)?;
+
+    // Checks and the journal work on the plan as it fits the folder now
+    if plan.baseline.is_some() {
+        let rebased = api::rebase_plan(&plan)?;
+        if !rebased.summary.is_unchanged() && !json {
+            print_rebase_summary(&rebased.summary);
+        }
+        plan = rebased.plan;
+    }
+
+    if plan.operations.is_empty() {
+        if !json {
+            println!("Nothing to do: plan {} has no operations", plan.plan_id);
+        }
+        return Ok(0);
+    }
+
+    let config = ExecutionConfig {
+        on_destination_exists: conflict,
+    };
+    if dry_run {
+        let report = api::preflight_plan(&plan, &config)?;
+        return print_preflight(&report, json);
+    }
+
+    if !force {
+        let errors = api::preview_plan(&plan).await?.errors;
+        if !errors.is_empty() {
+            for error in &errors {
+                eprintln!("  ! {}", error);
+            }
+            return Err(format!(
+                "Plan failed simulation with {} problems; fix it or pass --force",
+                errors.len()
+            ));
+        }
+
+        let problems = api::preflight_plan(&plan, &config)?.problems();
+        if !problems.is_empty() {
+            for problem in &problems {
+                eprintln!("  ! {}", problem);
+            }
+            return Err(format!(
+                "Plan failed preflight with {} problems; fix it or pass --force",
+                problems.len()
+            ));
+        }
+    }
+
+    let manager = WALManager::new();
+    if manager
+        .load_journal(&plan.plan_id)
+        .map_err(|e| e.message)?
+        .is_some()
+    {
+        return Err(format!(
+            "Plan {} already has a journal. Run `sentinel undo {}` or `sentinel discard {}` first.",
+            plan.plan_id, plan.plan_id, plan.plan_id
+        ));
+    }
+
+    tracing::info!(
+        operations = plan.operations.len(),
+        job_id = %plan.plan_id,
+        "Executing plan"
+    );
+
+    let result = api::execute_plan(&plan, config, Arc::new(NoopSink)).await?;
+
+    let success = result.success;
+    if json {
+        print_json(&ExecuteReport {
+            job_id: plan.plan_id.clone(),
+            result,
+        })?;
+    } else {
+        println!(
+            "Executed plan {}: {} completed, {} renamed, {} skipped, {} failed",
+            plan.plan_id,
+            result.completed_count,
+            result.renamed_count,
+            result.skipped_count,
+            result.failed_count
+        );
+        for reason in &result.skipped {
+            println!("  skipped: {}", reason);
+        }
+        for error in &result.errors {
+            println!("  failed: {}", error);
+        }
+        println!("Undo with: sentinel undo {}", plan.plan_id);
+    }
+
+    Ok(if success { 0 } else { 1 })
+}
+
+/// Print a preflight report; exit code 1 when execution would not succeed
+fn print_preflight(report: &PreflightReport, json: bool) -> Result<i32, String> {
+    if json {
+        print_json(report)?;
+        return Ok(if report.ok { 0 } else { 1 });
+    }
+
+    println!("Preflight of plan {}: {}", report.job_id, report.summary());
+    for entry in &report.entries {
+        match &entry.outcome {
+            PredictedOutcome::Proceed => {}
+            PredictedOutcome::Rename { path } => {
+                println!("  ~ {} (as {})", entry.description, path.display())
+            }
+            PredictedOutcome::Skip { reason } => {
+                println!("  - {} (skipped: {})", entry.description, reason)
+            }
+            PredictedOutcome::Fail { reason } => println!("  ! {}: {}", entry.description, reason),
+            PredictedOutcome::Blocked { .. } => {
+                println!("  . {} (blocked by a failing operation)", entry.description)
+            }
+        }
+    }
+    for volume in &report.volumes {
+        println!(
+            "  {} needs {}{}{}",
+            volume
+                .mount_point
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| volume.volume.clone()),
+            format_size(volume.required_bytes),
+            match volume.available_bytes {
+                Some(free) => format!(", {} free", format_size(free)),
+                None => String::new(),
+            },
+            if volume.sufficient { "" } else { " - not enough space" }
+        );
+    }
+    if let Some(ref refused) = report.refused {
+        println!("  ! {}", refused);
+    }
+    Ok(if report.ok { 0 } else { 1 })
+}
+
+// ============================================================================
+// snapshots / changes / diff
+// ============================================================================
+
+async fn snapshot(folder: &Path, label: Option<&str>, json: bool) -> Result<i32, String> {
+    let info = api::capture_snapshot(folder, label).await?;
+
+    if json {
+        print_json(&info)?;
+    } else {
+        println!(
+            "Saved snapshot {} of {}: {} files, {} ({} hashed, {} unchanged)",
+            info.id,
+            info.root,
+            info.file_count,
+            format_size(info.total_size),
+            info.files_hashed,
+            info.hashes_reused
+        );
+    }
+    Ok(0)
+}
+
+fn snapshots(folder: Option<&Path>, json: bool) -> Result<i32, String> {
+    let snapshots = api::list_snapshots(folder)?;
+
+    if json {
+        print_json(&snapshots)?;
+    } else if snapshots.is_empty() {
+        println!("No snapshots yet. Take one with `sentinel snapshot <folder>`.");
+    } else {
+        println!(
+            "{:>6} {:<17} {:>8} {:>10}  {:<16} FOLDER",
+            "ID", "TAKEN", "FILES", "SIZE", "LABEL"
+        );
+        for s in &snapshots {
+            println!(
+                "{:>6} {:<17} {:>8} {:>10}  {:<16} {}",
+                s.id,
+                s.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                s.file_count,
+                format_size(s.total_size),
+                s.label.as_deref().unwrap_or("-"),
+                s.root
+            );
+        }
+    }
+    Ok(0)
+}
+
+fn report_diff(diff: &SnapshotDiff, json: bool) -> Result<i32, String> {
+    if json {
+        return print_json(diff).map(|_| 0);
+    }
+
+    let taken = |at: &chrono::DateTime<chrono::Utc>| {
+        at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+    };
+    println!(
+        "Changes in {} from snapshot {} ({}) to {} ({})",
+        diff.to.root,
+        diff.from.id,
+        taken(&diff.from.created_at),
+        diff.to.id,
+        taken(&diff.to.created_at)
+    );
+    println!("{}", diff.summary());
+    if diff.is_empty() {
+        return Ok(0);
+    }
+
+    println!();
+    for file in &diff.added {
+        println!("  + {} ({})", file.path, format_size(file.size));
+    }
+    for file in &diff.removed {
+        println!("  - {}", file.path);
+    }
+    for file in &diff.modified {
+        println!(
+            "  ~ {} ({} -> {})",
+            file.path,
+            format_size(file.old_size),
+            format_size(file.new_size)
+        );
+    }
+    for file in &diff.moved {
+        println!("  > {} -> {}", file.from, file.to);
+    }
+    Ok(0)
+}
+
+// ============================================================================
+// journals / undo / resume
+// ============================================================================
+
+fn journals(json: bool) -> Result<i32, String> {
+    let summaries = api::list_journals()?;
+
+    if json {
+        print_json(&summaries)?;
+    } else if summaries.is_empty() {
+        println!("No journals in {}", api::journal_dir().display());
+    } else {
+        println!(
+            "{:<28} {:<17} {:<12} {:>9}  FOLDER",
+            "JOB ID", "STARTED", "STATE", "DONE"
+        );
+        for s in &summaries {
+            let done = format!("{}/{}", s.complete, s.total);
+            println!(
+                "{:<28} {:<17} {:<12} {:>9}  {}",
+                s.job_id,
+                s.started_at.format("%Y-%m-%d %H:%M"),
+                s.state,
+                done,
+                s.target_folder
+            );
+        }
+    }
+    Ok(0)
+}
+
+fn report_recovery(
+    action: &str,
+    job_id: &str,
+    result: RecoveryResult,
+    json: bool,
+) -> Result<i32, String> {
+    if json {
+        print_json(&result)?;
+    } else {
+        println!(
+            "{} journal {}: {} operations done, {} failed",
+            action, job_id, result.completed_count, result.failed_count
+        );
+        for error in &result.errors {
+            println!("  failed: {}", error);
+        }
+    }
+    Ok(if result.success { 0 } else { 1 })
+}
+
+// ============================================================================
+// eval
+// ============================================================================
+
+fn eval(
+    case: &Path,
+    output: Option<&Path>,
+    baseline: Option<&Path>,
+    json: bool,
+) -> Result<i32, String> {
+    let report = api::run_eval_case(case)?;
+    if let Some(path) = output {
+        report.write_to(path)?;
+    }
+    let baseline = baseline.map(EvalReport::read_from).transpose()?;
+
+    if json {
+        print_json(&report)?;
+    } else {
+        print_eval_report(&report);
+        if let Some(baseline) = &baseline {
+            let changes = report.compare(baseline);
+            println!();
+            if changes.is_empty() {
+                println!("No changes since the baseline");
+            } else {
+                println!("Changes since the baseline:");
+                for change in &changes {
+                    println!("  {}", change);
+                }
+            }
+        }
+    }
+
+    // A drop in accuracy fails the run, so it can gate CI
+    Ok(match baseline {
+        Some(baseline) if report.accuracy < baseline.accuracy => 1,
+        _ => 0,
+    })
+}
+
+fn print_eval_report(report: &EvalReport) {
+    println!(
+        "Eval {}: {} of {} labeled files in the expected folder (accuracy {:.4})",
+        report.case, report.correct, report.labeled, report.accuracy
+    );
+    println!(
+        "  Coverage {:.4}, {} operations ({} moves), {} files",
+        report.coverage, report.operations, report.moves, report.files
+    );
+    let stages = &report.stages;
+    println!(
+        "  Stages: {} rule matches, {} tier 1, {} tier 2 ({} replayed), {} unmatched",
+        stages.rule_matches, stages.tier1, stages.tier2, stages.tier2_replayed, stages.unmatched
+    );
+    for error in &stages.rule_errors {
+        println!("  ! rule skipped - {}", error);
+    }
+
+    println!();
+    println!(
+        "{:<32} {:>8} {:>9} {:>9} {:>7}",
+        "FOLDER", "EXPECTED", "PREDICTED", "PRECISION", "RECALL"
+    );
+    let ratio =
+        |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{:.4}", v));
+    for (folder, score) in &report.folders {
+        println!(
+            "{:<32} {:>8} {:>9} {:>9} {:>7}",
+            folder,
+            score.expected,
+            score.predicted,
+            ratio(score.precision),
+            ratio(score.recall)
+        );
+    }
+
+    if !report.misplaced.is_empty() {
+        println!();
+        println!("Misplaced:");
+        for m in &report.misplaced {
+            println!("  {} in {} (expected {})", m.file, m.predicted, m.expected);
+            if let Some(why) = &m.why {
+                println!("      {}", why);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn load_plan(path: &Path) -> Result<OrganizePlan, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read plan {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid plan file {}: {}", path.display(), e))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
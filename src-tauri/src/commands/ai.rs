@@ -1,6 +1,11 @@
-use crate::ai::{run_v2_agentic_organize, ExpandableDetail, ProgressEvent, AnthropicClient, CredentialManager};
+use crate::ai::{
+    run_v2_agentic_organize, run_v2_agentic_organize_across, ExpandableDetail, ProgressEvent,
+    AnthropicClient, CredentialManager,
+};
+use crate::api;
 use crate::jobs::OrganizePlan;
-use std::path::Path;
+use crate::security::PathValidator;
+use std::path::{Path, PathBuf};
 
 /// Rename suggestion response
 #[derive(serde::Serialize)]
@@ -132,38 +137,13 @@ pub struct RenameResult {
     pub new_path: String,
 }
 
-/// Validate that a filename is safe (no path traversal)
-fn validate_filename(name: &str) -> Result<(), String> {
-    // Reject path separators
-    if name.contains('/') || name.contains('\\') || name.contains("..") {
-        return Err("Invalid filename: path separators not allowed".to_string());
-    }
-
-    // Reject control characters and null bytes
-    if name.chars().any(|c| c.is_control() || c == '\0') {
-        return Err("Invalid filename: control characters not allowed".to_string());
-    }
-
-    // Reject empty or whitespace-only names
-    if name.trim().is_empty() {
-        return Err("Invalid filename: cannot be empty".to_string());
-    }
-
-    // Reject names that are too long (filesystem limit)
-    if name.len() > 255 {
-        return Err("Invalid filename: name too long".to_string());
-    }
-
-    Ok(())
-}
-
 #[tauri::command]
 pub async fn apply_rename(
     old_path: String,
     new_name: String,
 ) -> Result<RenameResult, String> {
     // SECURITY: Validate filename before any operations
-    validate_filename(&new_name)?;
+    PathValidator::validate_filename(&new_name)?;
 
     let old = std::path::Path::new(&old_path);
 
@@ -238,7 +218,7 @@ pub async fn undo_rename(
     let original_name = original.file_name()
         .ok_or("Invalid original filename")?
         .to_string_lossy();
-    validate_filename(&original_name)?;
+    PathValidator::validate_filename(&original_name)?;
 
     // Use atomic rename
     match std::fs::rename(&current, &original) {
@@ -277,7 +257,58 @@ pub async fn generate_organize_plan_agentic(
         let _ = app_handle_clone.emit("analysis-progress", &progress);
     };
 
-    run_v2_agentic_organize(Path::new(&folder_path), &user_request, emit, Some(progress_emit)).await
+    let plan =
+        run_v2_agentic_organize(Path::new(&folder_path), &user_request, emit, Some(progress_emit))
+            .await?;
+    api::attach_baseline(plan).await
+}
+
+/// Agentic organize across several folders: files from `source_folders`
+/// (and the destination itself) are organized into `destination_folder` as
+/// one plan, journaled and undone as a single job
+#[tauri::command]
+pub async fn generate_organize_plan_across(
+    source_folders: Vec<String>,
+    destination_folder: String,
+    user_request: String,
+    app_handle: tauri::AppHandle,
+) -> Result<OrganizePlan, String> {
+    use tauri::Emitter;
+
+    let destination = api::validate_folder(Path::new(&destination_folder))?;
+    let sources = source_folders
+        .iter()
+        .map(|folder| api::validate_folder(Path::new(folder)))
+        .collect::<Result<Vec<PathBuf>, String>>()?;
+    if sources.is_empty() {
+        return Err("Choose at least one folder to gather files from".to_string());
+    }
+
+    let emit = |thought_type: &str, content: &str, expandable_details: Option<Vec<ExpandableDetail>>| {
+        let _ = app_handle.emit(
+            "ai-thought",
+            serde_json::json!({
+                "type": thought_type,
+                "content": content,
+                "expandableDetails": expandable_details,
+            }),
+        );
+    };
+    let app_handle_clone = app_handle.clone();
+    let progress_emit = move |progress: ProgressEvent| {
+        let _ = app_handle_clone.emit("analysis-progress", &progress);
+    };
+
+    let plan = run_v2_agentic_organize_across(
+        &destination,
+        &sources,
+        &user_request,
+        None,
+        emit,
+        Some(progress_emit),
+    )
+    .await?;
+    api::attach_baseline(plan).await
 }
 
 /// Suggest naming conventions for a folder
@@ -374,5 +405,8 @@ pub async fn generate_organize_plan_with_convention(
         user_request
     };
 
-    run_v2_agentic_organize(Path::new(&folder_path), &full_request, emit, Some(progress_emit)).await
+    let plan =
+        run_v2_agentic_organize(Path::new(&folder_path), &full_request, emit, Some(progress_emit))
+            .await?;
+    api::attach_baseline(plan).await
 }
@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+use crate::ai::v2::architect::run_architect;
+use crate::ai::v2::vfs::ShadowVFS;
+use crate::ai::{organize_with_blueprint, Blueprint, BuilderOptions, ExpandableDetail};
+use crate::api;
+use crate::jobs::OrganizePlan;
+use crate::services::blueprint_library::{BlueprintDocument, BlueprintLibrary, SavedBlueprint};
+
+/// Forward Architect/Builder progress as `ai-thought` events
+fn thought_emitter(
+    app_handle: &tauri::AppHandle,
+) -> impl Fn(&str, &str, Option<Vec<ExpandableDetail>>) + '_ {
+    move |thought_type: &str, content: &str, expandable_details: Option<Vec<ExpandableDetail>>| {
+        let _ = app_handle.emit(
+            "ai-thought",
+            serde_json::json!({
+                "type": thought_type,
+                "content": content,
+                "expandableDetails": expandable_details,
+            }),
+        );
+    }
+}
+
+/// List saved Blueprints with all their versions
+#[tauri::command]
+pub fn list_blueprints() -> Vec<SavedBlueprint> {
+    BlueprintLibrary::load().blueprints
+}
+
+/// Save a Blueprint as a new library entry, or as the next version of `id`
+#[tauri::command]
+pub async fn save_blueprint(
+    id: Option<String>,
+    name: String,
+    blueprint: Blueprint,
+    note: Option<String>,
+) -> Result<SavedBlueprint, String> {
+    let mut library = BlueprintLibrary::load();
+    let saved = library
+        .save_version(id.as_deref(), &name, blueprint, note)?
+        .clone();
+    library.save()?;
+    Ok(saved)
+}
+
+/// Delete a saved Blueprint with all its versions
+#[tauri::command]
+pub async fn delete_blueprint(id: String) -> Result<(), String> {
+    let mut library = BlueprintLibrary::load();
+    if !library.remove(&id) {
+        return Err(format!("No saved Blueprint with id {}", id));
+    }
+    library.save()
+}
+
+/// Export a saved Blueprint to a `.json` or `.toml` file
+#[tauri::command]
+pub async fn export_blueprint(
+    id: String,
+    version: Option<u32>,
+    path: String,
+) -> Result<(), String> {
+    let library = BlueprintLibrary::load();
+    let saved = library
+        .get(&id)
+        .ok_or_else(|| format!("No saved Blueprint with id {}", id))?;
+    BlueprintDocument::from_saved(saved, version)?.write_to(&PathBuf::from(path))
+}
+
+/// Import a Blueprint from a `.json` or `.toml` file into the library
+#[tauri::command]
+pub async fn import_blueprint(path: String) -> Result<SavedBlueprint, String> {
+    let document = BlueprintDocument::read_from(&PathBuf::from(&path))?;
+    let note = Some(format!("Imported from {}", path));
+
+    let mut library = BlueprintLibrary::load();
+    let saved = library
+        .save_version(None, &document.name, document.blueprint, note)?
+        .clone();
+    library.save()?;
+    Ok(saved)
+}
+
+/// Run only the Architect on a folder and return its Blueprint, so it can be
+/// reviewed, edited and saved before anything is planned
+#[tauri::command]
+pub async fn design_blueprint(
+    folder_path: String,
+    user_request: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Blueprint, String> {
+    let folder = api::validate_folder(Path::new(&folder_path))?;
+    let vfs = ShadowVFS::new(&folder).map_err(|e| format!("Failed to scan folder: {}", e))?;
+    run_architect(&folder, &user_request, &vfs, thought_emitter(&app_handle)).await
+}
+
+/// Plan a folder with a saved Blueprint, skipping the Architect and the
+/// agent loop
+#[tauri::command]
+pub async fn generate_organize_plan_from_blueprint(
+    folder_path: String,
+    blueprint_id: String,
+    version: Option<u32>,
+    options: Option<BuilderOptions>,
+    app_handle: tauri::AppHandle,
+) -> Result<OrganizePlan, String> {
+    let folder = api::validate_folder(Path::new(&folder_path))?;
+    let saved = BlueprintLibrary::load()
+        .resolve(&blueprint_id, version)?
+        .clone();
+
+    let plan = organize_with_blueprint(
+        &folder,
+        &saved.blueprint,
+        &options.unwrap_or_default(),
+        thought_emitter(&app_handle),
+    )
+    .await?;
+    api::attach_baseline(plan).await
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use crate::ai::v2::architect::embed_blueprint_with_index;
+use crate::ai::v2::corrections::{
+    self, evaluate_priors, Correction, CorrectionStore, PriorEvaluation,
+};
+use crate::ai::v2::LocalVectorIndex;
+use crate::api;
+use crate::services::blueprint_library::BlueprintLibrary;
+
+/// Corrections recorded for a folder from earlier plan edits, newest first
+#[tauri::command]
+pub async fn list_corrections(
+    folder_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<Correction>, String> {
+    let folder = api::validate_folder(Path::new(&folder_path))?;
+    tokio::task::spawn_blocking(move || {
+        CorrectionStore::open_default()?.for_folder(&folder.to_string_lossy(), limit.unwrap_or(100))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Forget what was learned from a folder's plan edits
+#[tauri::command]
+pub async fn clear_corrections(folder_path: String) -> Result<usize, String> {
+    let folder = api::validate_folder(Path::new(&folder_path))?;
+    tokio::task::spawn_blocking(move || {
+        CorrectionStore::open_default()?.clear(&folder.to_string_lossy())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Measure how much a folder's corrections improve matching with a saved
+/// Blueprint (see [`evaluate_priors`])
+#[tauri::command]
+pub async fn evaluate_correction_priors(
+    folder_path: String,
+    blueprint_id: String,
+    version: Option<u32>,
+) -> Result<PriorEvaluation, String> {
+    let folder = api::validate_folder(Path::new(&folder_path))?;
+    let saved = BlueprintLibrary::load()
+        .resolve(&blueprint_id, version)?
+        .clone();
+
+    tokio::task::spawn_blocking(move || {
+        let index = LocalVectorIndex::new_default()?;
+        let blueprint = embed_blueprint_with_index(&saved.blueprint, &index)?;
+        let corrections = corrections::load_corrections(&folder, Some(&index));
+        Ok(evaluate_priors(&corrections, &blueprint))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
@@ -4,15 +4,14 @@
 //! Provides commands for scanning, organizing, and executing file organization plans.
 
 use crate::ai::grok::{
-    DocumentAnalysis, GrokOrganizer, OrganizationPlan,
-    ScanResult, sanitize_filename, sanitize_folder_path,
+    DocumentAnalysis, GrokOrganizer, ImportSummary, InvalidationFilter, OrganizationPlan,
+    ScanResult, VersionSummary,
 };
-use crate::ai::grok::AnalysisPhase;
-use crate::execution::executor::{ExecutionEngine, ProgressCallback};
-use crate::jobs::{OrganizeOperation, OrganizePlan};
-use crate::wal::entry::{WALJournal, WALOperationType};
-use crate::wal::journal::WALManager;
-use std::path::PathBuf;
+use crate::api::{self, GrokExecutionResult};
+use crate::commands::jobs::load_job_by_id;
+use crate::jobs::{JobManager, OrganizeJob, OrganizePlan, PlanBaseline};
+use crate::security::PathValidator;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, State};
@@ -115,13 +114,28 @@ pub async fn grok_organize(
     let path = PathBuf::from(path);
     let app_clone = app.clone();
 
-    let plan = organizer
+    let mut plan = organizer
         .organize(&path, &user_instruction, move |progress| {
             // Emit progress events to frontend
             let _ = app_clone.emit("grok:progress", &progress);
         })
         .await?;
 
+    // Baseline so grok_execute_plan can re-base the plan if the folder changes
+    let sources: Vec<PathBuf> = plan
+        .assignments
+        .iter()
+        .map(|a| PathBuf::from(&a.file_path))
+        .collect();
+    let baseline =
+        tokio::task::spawn_blocking(move || PlanBaseline::capture_sources(&path, &sources))
+            .await
+            .map_err(|e| format!("Task failed: {}", e))?;
+    match baseline {
+        Ok(baseline) => plan.baseline = Some(baseline),
+        Err(e) => tracing::warn!("[Grok] Could not capture plan baseline: {}", e),
+    }
+
     Ok(plan)
 }
 
@@ -163,188 +177,14 @@ pub async fn grok_generate_plan(
         .clone();
     drop(guard);
 
-    let folder_path = PathBuf::from(&path);
-    let app_clone = app.clone();
-    let abort_flag_clone = Arc::clone(&abort_flag.0);
-
-    // Emit progress as ai-thought events (for compatibility with ChangesPanel)
-    let emit_thought = |phase: &str, message: &str, details: Option<Vec<(&str, String)>>| {
-        let expandable_details: Option<Vec<serde_json::Value>> = details.map(|d| {
-            d.into_iter()
-                .map(|(label, value)| serde_json::json!({"label": label, "value": value}))
-                .collect()
-        });
-
-        let _ = app_clone.emit(
-            "ai-thought",
-            serde_json::json!({
-                "type": phase,
-                "content": message,
-                "expandableDetails": expandable_details,
-            }),
-        );
-    };
-
-    emit_thought("scanning", "Analyzing folder contents with AI...", None);
-
-    // Track last phase to only emit ai-thought on phase changes
-    let last_phase = std::sync::Arc::new(std::sync::Mutex::new(None::<String>));
-
-    // Clone abort flag for use in callback
-    let abort_flag_for_callback = Arc::clone(&abort_flag_clone);
-
-    let plan = organizer
-        .organize(&folder_path, &user_instruction, move |progress| {
-            // Check if aborted - skip all emissions if so
-            if abort_flag_for_callback.load(Ordering::SeqCst) {
-                return;
-            }
-
-            // Map Grok progress phases to names
-            let phase_name = match progress.phase {
-                AnalysisPhase::Scanning => "scanning",
-                AnalysisPhase::CheckingCache => "extracting",
-                AnalysisPhase::RenderingPdf => "rendering",
-                AnalysisPhase::AnalyzingContent => "analyzing",
-                AnalysisPhase::Aggregating => "summarizing",
-                AnalysisPhase::Planning => "planning",
-                AnalysisPhase::Complete => "complete",
-                AnalysisPhase::Failed => "error",
-            };
-
-            // Always emit analysis-progress for the progress bar
-            let _ = app.emit(
-                "analysis-progress",
-                serde_json::json!({
-                    "phase": phase_name,
-                    "current": progress.current,
-                    "total": progress.total,
-                    "message": progress.message,
-                }),
-            );
-
-            // Only emit ai-thought on phase transitions (not every progress update)
-            let mut last = last_phase.lock().unwrap();
-            let should_emit_thought = match &*last {
-                None => true,
-                Some(prev) => prev != phase_name,
-            };
-
-            if should_emit_thought {
-                *last = Some(phase_name.to_string());
-                drop(last); // Release lock before emit
-
-                let thought_type = match progress.phase {
-                    AnalysisPhase::Scanning => "scanning",
-                    AnalysisPhase::CheckingCache | AnalysisPhase::RenderingPdf | AnalysisPhase::AnalyzingContent => "analyzing",
-                    AnalysisPhase::Aggregating => "thinking",
-                    AnalysisPhase::Planning => "planning",
-                    AnalysisPhase::Complete => "complete",
-                    AnalysisPhase::Failed => "error",
-                };
-
-                let _ = app.emit(
-                    "ai-thought",
-                    serde_json::json!({
-                        "type": thought_type,
-                        "content": progress.message,
-                    }),
-                );
-            }
-        })
-        .await?;
-
-    // Check if aborted after organize completes
-    if abort_flag_clone.load(Ordering::SeqCst) {
-        tracing::info!("[Grok] Plan generation was aborted by user");
-        return Err("Organization cancelled by user".to_string());
-    }
-
-    // Convert OrganizationPlan to OrganizePlan (frontend format)
-    let frontend_plan = convert_to_frontend_plan(plan, &path);
-
-    emit_thought("complete", &format!(
-        "Created plan with {} operations",
-        frontend_plan.operations.len()
-    ), None);
-
-    Ok(frontend_plan)
-}
-
-/// Convert Grok's OrganizationPlan to frontend's OrganizePlan format
-fn convert_to_frontend_plan(plan: OrganizationPlan, target_folder: &str) -> OrganizePlan {
-    let mut operations = Vec::new();
-    let target_path = PathBuf::from(target_folder);
-
-    // Convert folder creations to create_folder operations
-    for folder in &plan.folder_structure {
-        let sanitized_path = sanitize_folder_path(&folder.path);
-        let full_path = target_path.join(&sanitized_path);
-
-        operations.push(OrganizeOperation {
-            op_id: uuid::Uuid::new_v4().to_string(),
-            op_type: "create_folder".to_string(),
-            source: None,
-            destination: None,
-            path: Some(full_path.to_string_lossy().to_string()),
-            new_name: None,
-        });
-    }
-
-    // Convert file assignments to move operations
-    for assignment in &plan.assignments {
-        let source = PathBuf::from(&assignment.file_path);
-        let sanitized_folder = sanitize_folder_path(&assignment.destination_folder);
-
-        // Get the new filename (sanitized)
-        let new_filename = match &assignment.new_name {
-            Some(name) if !name.is_empty() => {
-                sanitize_filename(name, &assignment.original_name)
-            }
-            _ => assignment.original_name.clone(),
-        };
-
-        let destination = target_path.join(&sanitized_folder).join(&new_filename);
-
-        // Check if this is a rename (same directory, different name)
-        let source_parent = source.parent().map(|p| p.to_string_lossy().to_string());
-        let dest_parent = destination.parent().map(|p| p.to_string_lossy().to_string());
-        let is_same_dir = source_parent == dest_parent;
-        let is_rename = is_same_dir && new_filename != assignment.original_name;
-
-        if is_rename {
-            // Rename operation
-            operations.push(OrganizeOperation {
-                op_id: uuid::Uuid::new_v4().to_string(),
-                op_type: "rename".to_string(),
-                source: None,
-                destination: None,
-                path: Some(source.to_string_lossy().to_string()),
-                new_name: Some(new_filename),
-            });
-        } else {
-            // Move operation
-            operations.push(OrganizeOperation {
-                op_id: uuid::Uuid::new_v4().to_string(),
-                op_type: "move".to_string(),
-                source: Some(source.to_string_lossy().to_string()),
-                destination: Some(destination.to_string_lossy().to_string()),
-                path: None,
-                new_name: None,
-            });
-        }
-    }
-
-    OrganizePlan {
-        plan_id: uuid::Uuid::new_v4().to_string(),
-        description: format!(
-            "{}: {}",
-            plan.strategy_name,
-            plan.description
-        ),
-        operations,
-        target_folder: target_folder.to_string(),
-    }
+    api::grok_plan(
+        &organizer,
+        &PathBuf::from(&path),
+        &user_instruction,
+        Arc::new(app),
+        Arc::clone(&abort_flag.0),
+    )
+    .await
 }
 
 /// Analyze a single file
@@ -379,7 +219,80 @@ pub async fn grok_cache_stats(
         tokens_used: stats.tokens_used as usize,
         cost_cents: stats.cost_cents as usize,
         cache_hits: stats.cache_hits as usize,
+        entries: stats.entries as usize,
+        size_bytes: stats.size_bytes as u64,
+        analyzer_version: organizer.cache_version().to_string(),
+    })
+}
+
+/// List cached analyzer versions (models + prompt revisions) with entry counts
+#[tauri::command]
+pub async fn grok_cache_versions(
+    state: State<'_, GrokState>,
+) -> Result<Vec<VersionSummary>, String> {
+    let guard = state.organizer.lock().await;
+    let organizer = guard
+        .as_ref()
+        .ok_or("Grok not initialized. Call grok_init first.")?;
+
+    organizer.cache_versions()
+}
+
+/// Drop cached analyses by analyzer version, analysis date or document type.
+/// Returns the number of entries removed.
+#[tauri::command]
+pub async fn grok_invalidate_cache(
+    filter: InvalidationFilter,
+    state: State<'_, GrokState>,
+) -> Result<usize, String> {
+    let guard = state.organizer.lock().await;
+    let organizer = guard
+        .as_ref()
+        .ok_or("Grok not initialized. Call grok_init first.")?;
+
+    organizer.invalidate_cache(&filter)
+}
+
+/// Export the content cache to a JSON file that can be imported on another machine.
+/// Only the current analyzer version is exported unless `all_versions` is set.
+#[tauri::command]
+pub async fn grok_export_cache(
+    output_path: String,
+    all_versions: Option<bool>,
+    state: State<'_, GrokState>,
+) -> Result<usize, String> {
+    let guard = state.organizer.lock().await;
+    let organizer = guard
+        .as_ref()
+        .ok_or("Grok not initialized. Call grok_init first.")?
+        .clone();
+    drop(guard);
+
+    let output = PathValidator::validate_for_write(Path::new(&output_path), None)?;
+    tokio::task::spawn_blocking(move || {
+        organizer.export_cache(&output, all_versions.unwrap_or(false))
     })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+/// Import a content cache exported with `grok_export_cache`
+#[tauri::command]
+pub async fn grok_import_cache(
+    input_path: String,
+    state: State<'_, GrokState>,
+) -> Result<ImportSummary, String> {
+    let guard = state.organizer.lock().await;
+    let organizer = guard
+        .as_ref()
+        .ok_or("Grok not initialized. Call grok_init first.")?
+        .clone();
+    drop(guard);
+
+    let input = PathBuf::from(input_path);
+    tokio::task::spawn_blocking(move || organizer.import_cache(&input))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
 }
 
 /// Clear the content cache
@@ -395,6 +308,34 @@ pub async fn grok_clear_cache(state: State<'_, GrokState>) -> Result<(), String>
     Ok(())
 }
 
+/// Export extracted invoice/receipt fields (vendor, total, dates...) for a folder as CSV.
+/// Returns the number of documents written.
+#[tauri::command]
+pub async fn grok_export_fields_csv(
+    path: String,
+    output_path: String,
+    state: State<'_, GrokState>,
+) -> Result<usize, String> {
+    let guard = state.organizer.lock().await;
+    let organizer = guard
+        .as_ref()
+        .ok_or("Grok not initialized. Call grok_init first.")?
+        .clone();
+    drop(guard);
+
+    let output = PathValidator::validate_for_write(Path::new(&output_path), None)?;
+    let folder = PathBuf::from(path);
+    let (csv, rows) = tokio::task::spawn_blocking(move || organizer.export_fields_csv(&folder))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))??;
+
+    std::fs::write(&output, csv)
+        .map_err(|e| format!("Failed to write CSV to {}: {}", output.display(), e))?;
+
+    tracing::info!("[Grok] Exported {} documents to {}", rows, output_path);
+    Ok(rows)
+}
+
 /// Cache statistics for frontend
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -403,6 +344,9 @@ pub struct GrokCacheStats {
     pub tokens_used: usize,
     pub cost_cents: usize,
     pub cache_hits: usize,
+    pub entries: usize,
+    pub size_bytes: u64,
+    pub analyzer_version: String,
 }
 
 /// Check if Grok API key is configured
@@ -533,178 +477,35 @@ pub async fn grok_get_api_key() -> Result<Option<String>, String> {
 /// This converts the Grok plan into executable filesystem operations:
 /// 1. Creates all planned folders
 /// 2. Moves files to their destinations with sanitized names
+///
+/// To edit the plan first, use `grok_set_job_plan` and `grok_execute_job_plan`.
 #[tauri::command]
 pub async fn grok_execute_plan(
     plan: OrganizationPlan,
     target_folder: String,
     app: AppHandle,
 ) -> Result<GrokExecutionResult, String> {
-    use tauri::Emitter;
-
-    let target_path = PathBuf::from(&target_folder);
-
-    // Generate a unique job ID
-    let job_id = format!("grok-{}", uuid::Uuid::new_v4());
-
-    tracing::info!(
-        "[Grok] Executing plan: {} folders, {} assignments",
-        plan.folder_structure.len(),
-        plan.assignments.len()
-    );
-
-    // Emit start event
-    let _ = app.emit("grok:execution", serde_json::json!({
-        "phase": "starting",
-        "message": format!("Creating {} folders, moving {} files",
-            plan.folder_structure.len(),
-            plan.assignments.len())
-    }));
-
-    // Create WAL journal
-    let mut journal = WALJournal::new(job_id.clone(), target_path.clone());
-
-    // Track folder operation IDs for dependencies
-    let mut folder_op_ids: std::collections::HashMap<String, uuid::Uuid> = std::collections::HashMap::new();
-
-    // Step 1: Add CreateFolder operations for all planned folders
-    for planned_folder in &plan.folder_structure {
-        let sanitized_path = sanitize_folder_path(&planned_folder.path);
-        let full_path = target_path.join(&sanitized_path);
-
-        let op = WALOperationType::CreateFolder { path: full_path };
-        match journal.add_operation(op) {
-            Ok(op_id) => {
-                folder_op_ids.insert(sanitized_path, op_id);
-            }
-            Err(e) => {
-                tracing::warn!("[Grok] Failed to add folder operation: {}", e);
-            }
-        }
-    }
-
-    // Step 2: Add Move operations for all file assignments
-    let mut move_count = 0;
-    for assignment in &plan.assignments {
-        let source = PathBuf::from(&assignment.file_path);
-
-        // Sanitize destination folder path
-        let sanitized_folder = sanitize_folder_path(&assignment.destination_folder);
-
-        // Get sanitized filename (uses the utility we created)
-        let new_filename = match &assignment.new_name {
-            Some(name) if !name.is_empty() => {
-                sanitize_filename(name, &assignment.original_name)
-            }
-            _ => assignment.original_name.clone(),
-        };
-
-        // Build full destination path
-        let destination = target_path.join(&sanitized_folder).join(&new_filename);
-
-        // Find dependency on parent folder creation
-        let mut depends_on = Vec::new();
-        if let Some(&folder_op_id) = folder_op_ids.get(&sanitized_folder) {
-            depends_on.push(folder_op_id);
-        }
-
-        let op = WALOperationType::Move {
-            source,
-            destination,
-        };
-
-        match journal.add_operation_with_deps(op, depends_on) {
-            Ok(_) => {
-                move_count += 1;
-            }
-            Err(e) => {
-                tracing::warn!("[Grok] Failed to add move operation for {}: {}",
-                    assignment.original_name, e);
-            }
-        }
-    }
-
-    tracing::info!(
-        "[Grok] Created WAL journal with {} folder ops and {} move ops",
-        folder_op_ids.len(),
-        move_count
-    );
-
-    // Emit progress
-    let _ = app.emit("grok:execution", serde_json::json!({
-        "phase": "executing",
-        "message": format!("Executing {} operations...", folder_op_ids.len() + move_count)
-    }));
-
-    // Save and execute the journal
-    let wal_manager = WALManager::new();
-    wal_manager.save_journal(&journal).map_err(|e| e.message)?;
-
-    let engine = ExecutionEngine::new();
-    let app_clone = app.clone();
-
-    // Execute with progress callback
-    // Emit both grok:execution (for Grok-specific UIs) and execution-progress (for ChangesPanel)
-    let progress_callback: Arc<ProgressCallback> = Arc::new(Box::new(move |current: usize, total: usize| {
-        // Emit execution-progress for ChangesPanel compatibility
-        let _ = app_clone.emit("execution-progress", serde_json::json!({
-            "completed": current,
-            "total": total,
-        }));
-        // Also emit grok:execution for richer Grok-specific details
-        let _ = app_clone.emit("grok:execution", serde_json::json!({
-            "phase": "progress",
-            "current": current,
-            "total": total,
-            "message": format!("Completed {}/{} operations", current, total)
-        }));
-    }));
-
-    let result = engine
-        .execute_journal_with_progress(&job_id, Some(progress_callback))
-        .await?;
-
-    // Clean up journal after successful execution
-    if result.success {
-        let _ = wal_manager.discard_journal(&job_id);
-    }
+    api::execute_grok_plan(&plan, &PathBuf::from(&target_folder), Arc::new(app)).await
+}
 
-    // Emit completion
-    let _ = app.emit("grok:execution", serde_json::json!({
-        "phase": if result.success { "complete" } else { "failed" },
-        "message": format!(
-            "Completed: {} succeeded, {} failed, {} skipped",
-            result.completed_count,
-            result.failed_count,
-            result.skipped_count
-        )
-    }));
-
-    tracing::info!(
-        "[Grok] Execution complete: {} completed, {} failed",
-        result.completed_count,
-        result.failed_count
-    );
-
-    Ok(GrokExecutionResult {
-        job_id,
-        completed_count: result.completed_count,
-        failed_count: result.failed_count,
-        skipped_count: result.skipped_count,
-        renamed_count: result.renamed_count,
-        errors: result.errors,
-        success: result.success,
-    })
+/// Store a Grok plan as the job's plan so it can be edited with
+/// `edit_job_plan` / `revert_job_plan_edits` before execution
+#[tauri::command]
+pub fn grok_set_job_plan(job_id: String, plan: OrganizationPlan) -> Result<OrganizeJob, String> {
+    let mut job = load_job_by_id(&job_id)?;
+    let organize_plan = api::grok_to_organize_plan(&plan, Path::new(&job.target_folder));
+    job.set_plan(organize_plan);
+    JobManager::save_job(&job)?;
+    Ok(job)
 }
 
-/// Result of executing a Grok organization plan
-#[derive(Debug, Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GrokExecutionResult {
-    pub job_id: String,
-    pub completed_count: usize,
-    pub failed_count: usize,
-    pub skipped_count: usize,
-    pub renamed_count: usize,
-    pub errors: Vec<String>,
-    pub success: bool,
+/// Execute the job's Grok plan with its edits applied
+#[tauri::command]
+pub async fn grok_execute_job_plan(
+    job_id: String,
+    app: AppHandle,
+) -> Result<GrokExecutionResult, String> {
+    let job = load_job_by_id(&job_id)?;
+    let plan = job.plan.ok_or("Job has no plan to execute")?;
+    api::execute_grok_organize_plan(&plan, Arc::new(app)).await
 }
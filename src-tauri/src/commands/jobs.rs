@@ -1,11 +1,16 @@
-use crate::execution::{ConflictPolicy, ExecutionConfig, ExecutionEngine, ExecutionResult, ProgressCallback};
-use crate::jobs::{JobManager, JobStatus, OrganizeJob, OrganizeOperation, OrganizePlan};
+use crate::ai::v2::corrections::CorrectionStore;
+use crate::api;
+use crate::execution::{ConflictPolicy, ExecutionConfig, ExecutionResult};
+use crate::commands::vfs::VFSState;
+use crate::jobs::{
+    rebase_plan, validate_plan, JobManager, JobStatus, OperationAttributes, OrganizeJob,
+    OrganizeOperation, OrganizePlan, PlanEdit, RebasedPlan,
+};
 use crate::security::PathValidator;
-use crate::wal::entry::{WALJournal, WALOperationType};
 use crate::wal::journal::WALManager;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, State};
 
 /// Start a new organize job
 #[tauri::command]
@@ -24,7 +29,8 @@ pub fn start_organize_job(target_folder: String) -> Result<OrganizeJob, String>
     Ok(job)
 }
 
-/// Update job with the generated plan
+/// Update job with the generated plan (`source_roots`: the folders a
+/// multi-folder plan gathers files from)
 #[tauri::command]
 pub fn set_job_plan(
     job_id: String,
@@ -32,6 +38,7 @@ pub fn set_job_plan(
     description: String,
     operations: Vec<serde_json::Value>,
     target_folder: String,
+    source_roots: Option<Vec<String>>,
 ) -> Result<OrganizeJob, String> {
     let mut job = JobManager::load_job()?
         .ok_or_else(|| format!("Job not found: {}", job_id))?;
@@ -67,7 +74,8 @@ pub fn set_job_plan(
                     return Err(format!("Operation '{}' (move) missing required field 'destination'", op_id));
                 }
             }
-            "create_folder" | "trash" | "quarantine" => {
+            "create_folder" | "trash" | "quarantine" | "delete" | "symlink" | "alias"
+            | "set_timestamps" | "set_permissions" | "set_tags" | "set_xattr" => {
                 if op.get("path").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).is_none() {
                     return Err(format!("Operation '{}' ({}) missing required field 'path'", op_id, op_type));
                 }
@@ -92,6 +100,8 @@ pub fn set_job_plan(
             destination: op.get("destination").and_then(|v| v.as_str()).map(String::from),
             path: op.get("path").and_then(|v| v.as_str()).map(String::from),
             new_name: op.get("newName").and_then(|v| v.as_str()).map(String::from),
+            attributes: serde_json::from_value::<OperationAttributes>(op.clone())
+                .map_err(|e| format!("Operation '{}' has invalid attributes: {}", op_id, e))?,
         });
     }
 
@@ -100,6 +110,10 @@ pub fn set_job_plan(
         description,
         operations: ops,
         target_folder,
+        source_roots: source_roots.unwrap_or_default(),
+        rules: Vec::new(),
+        baseline: None,
+        explanations: Default::default(),
     };
 
     job.set_plan(plan);
@@ -136,6 +150,15 @@ pub fn complete_organize_job(job_id: String) -> Result<(), String> {
     job.mark_completed();
     JobManager::save_job(&job)?;
 
+    // Remember how the user corrected the plan for the next one
+    let corrections = job.corrections();
+    if !corrections.is_empty() {
+        match CorrectionStore::open_default().and_then(|store| store.record(&corrections)) {
+            Ok(count) => eprintln!("[Corrections] Recorded {} corrections for {}", count, job.target_folder),
+            Err(e) => eprintln!("[Corrections] {}", e),
+        }
+    }
+
     // Clear the job file after a short delay (let frontend read final state)
     // In production, you might want to keep history
     Ok(())
@@ -156,6 +179,57 @@ pub fn fail_organize_job(job_id: String, error: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Edit the job's plan before execution (exclude operations or folders,
+/// retarget or merge folders, change a destination).
+///
+/// The edit is stored in the job file and the edited plan is re-validated
+/// against the scanned VFS when it covers the job's folder; problems are
+/// returned in `planErrors` rather than rejecting the edit.
+#[tauri::command]
+pub async fn edit_job_plan(
+    job_id: String,
+    edit: PlanEdit,
+    vfs_state: State<'_, VFSState>,
+) -> Result<OrganizeJob, String> {
+    let mut job = load_job_by_id(&job_id)?;
+    job.edit_plan(edit)?;
+    validate_job_plan(&mut job, &vfs_state).await;
+    JobManager::save_job(&job)?;
+    Ok(job)
+}
+
+/// Undo the last `count` plan edits, or all of them when `count` is omitted
+#[tauri::command]
+pub async fn revert_job_plan_edits(
+    job_id: String,
+    count: Option<usize>,
+    vfs_state: State<'_, VFSState>,
+) -> Result<OrganizeJob, String> {
+    let mut job = load_job_by_id(&job_id)?;
+    job.revert_plan_edits(count)?;
+    validate_job_plan(&mut job, &vfs_state).await;
+    JobManager::save_job(&job)?;
+    Ok(job)
+}
+
+pub(crate) fn load_job_by_id(job_id: &str) -> Result<OrganizeJob, String> {
+    let job = JobManager::load_job()?.ok_or_else(|| format!("Job not found: {}", job_id))?;
+    if job.job_id != job_id {
+        return Err(format!("Job ID mismatch: expected {}, got {}", job.job_id, job_id));
+    }
+    Ok(job)
+}
+
+async fn validate_job_plan(job: &mut OrganizeJob, vfs_state: &VFSState) {
+    let state = vfs_state.read().await;
+    let (Some(vfs), Some(plan)) = (state.as_ref(), job.plan.as_ref()) else {
+        return;
+    };
+    if *vfs.root() == PathBuf::from(&job.target_folder) {
+        job.plan_errors = validate_plan(vfs, plan);
+    }
+}
+
 /// Check for interrupted jobs on app startup
 #[tauri::command]
 pub fn check_interrupted_job() -> Result<Option<OrganizeJob>, String> {
@@ -195,6 +269,15 @@ pub fn resume_organize_job(job_id: String) -> Result<OrganizeJob, String> {
     Ok(job)
 }
 
+/// Bring a plan up to date with its folder before review or execution:
+/// follow moved sources, drop deleted ones and plan newly added files
+#[tauri::command]
+pub async fn rebase_organize_plan(plan: OrganizePlan) -> Result<RebasedPlan, String> {
+    tokio::task::spawn_blocking(move || rebase_plan(&plan))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
 /// Execute an organize plan using parallel DAG-based execution
 ///
 /// V5: Now emits 'execution-progress' events for clean UI updates.
@@ -202,7 +285,9 @@ pub fn resume_organize_job(job_id: String) -> Result<OrganizeJob, String> {
 /// V7: Now accepts original_folder for post-execution cleanup of empty directories.
 ///
 /// This command:
-/// 1. Converts the OrganizePlan to WAL entries
+/// 1. Re-bases the plan if it carries a baseline and the folder changed
+///    since it was made (emits 'plan-rebased' with the summary), then
+///    converts it to WAL entries
 /// 2. Builds a dependency DAG for parallel execution
 /// 3. Executes operations in parallel within each level
 /// 4. Handles destination conflicts according to policy (skip, auto_rename, fail)
@@ -235,133 +320,7 @@ pub async fn execute_plan_parallel(
         "Starting parallel plan execution"
     );
 
-    // Create a WAL journal from the plan
-    let target_folder = PathBuf::from(&plan.target_folder);
-    let mut journal = WALJournal::new(plan.plan_id.clone(), target_folder.clone());
-
-    // Track folder creation operations for dependencies
-    let mut folder_op_ids: std::collections::HashMap<String, uuid::Uuid> =
-        std::collections::HashMap::new();
-
-    // Convert operations to WAL entries with dependencies
-    for op in &plan.operations {
-        let wal_op = match op.op_type.as_str() {
-            "create_folder" => {
-                let path = op.path.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' (create_folder) missing required field 'path'", op.op_id)
-                })?;
-                WALOperationType::CreateFolder {
-                    path: PathBuf::from(path),
-                }
-            }
-            "move" => {
-                let src = op.source.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' (move) missing required field 'source'", op.op_id)
-                })?;
-                let dst = op.destination.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' (move) missing required field 'destination'", op.op_id)
-                })?;
-                WALOperationType::Move {
-                    source: PathBuf::from(src),
-                    destination: PathBuf::from(dst),
-                }
-            }
-            "rename" => {
-                let path = op.path.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' (rename) missing required field 'path'", op.op_id)
-                })?;
-                let new_name = op.new_name.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' (rename) missing required field 'newName'", op.op_id)
-                })?;
-                WALOperationType::Rename {
-                    path: PathBuf::from(path),
-                    new_name: new_name.clone(),
-                }
-            }
-            "trash" | "quarantine" => {
-                let path = op.path.as_ref().ok_or_else(|| {
-                    format!("Operation '{}' ({}) missing required field 'path'", op.op_id, op.op_type)
-                })?;
-                let quarantine_path = dirs::data_dir()
-                    .unwrap_or_else(|| PathBuf::from("/tmp"))
-                    .join("sentinel")
-                    .join("quarantine")
-                    .join(&op.op_id);
-                WALOperationType::Quarantine {
-                    path: PathBuf::from(path),
-                    quarantine_path,
-                }
-            }
-            unknown_type => {
-                return Err(format!("Operation '{}' has unknown type '{}'", op.op_id, unknown_type));
-            }
-        };
-
-        // Check for dependencies - moves depend on their destination folder being created
-        let mut depends_on = Vec::new();
-        if let WALOperationType::Move { destination, .. } = &wal_op {
-            if let Some(parent) = destination.parent() {
-                let parent_str = parent.to_string_lossy().to_string();
-                if let Some(&folder_op_id) = folder_op_ids.get(&parent_str) {
-                    depends_on.push(folder_op_id);
-                }
-            }
-        }
-
-        // Track folder creation for dependency resolution
-        if let WALOperationType::CreateFolder { ref path } = wal_op {
-            let path_str = path.to_string_lossy().to_string();
-            let op_id = if depends_on.is_empty() {
-                journal.add_operation(wal_op)
-                    .map_err(|e| format!("Failed to add operation: {}", e))?
-            } else {
-                journal.add_operation_with_deps(wal_op, depends_on)
-                    .map_err(|e| format!("Failed to add operation: {}", e))?
-            };
-            folder_op_ids.insert(path_str, op_id);
-        } else if depends_on.is_empty() {
-            journal.add_operation(wal_op)
-                .map_err(|e| format!("Failed to add operation: {}", e))?;
-        } else {
-            journal.add_operation_with_deps(wal_op, depends_on)
-                .map_err(|e| format!("Failed to add operation: {}", e))?;
-        }
-    }
-
-    tracing::debug!(
-        entries = journal.entries.len(),
-        "Created WAL journal"
-    );
-
-    // Save the journal
-    let wal_manager = WALManager::new();
-    wal_manager
-        .save_journal(&journal)
-        .map_err(|e| format!("Failed to save WAL journal: {}", e.message))?;
-
-    // V5: Create progress callback that emits Tauri events
-    let app_handle_clone = app_handle.clone();
-    let progress_callback: Arc<ProgressCallback> = Arc::new(Box::new(move |completed, total| {
-        let _ = app_handle_clone.emit(
-            "execution-progress",
-            serde_json::json!({
-                "completed": completed,
-                "total": total
-            }),
-        );
-        tracing::debug!(completed = completed, total = total, "Execution progress");
-    }));
-
-    // Execute using the parallel DAG executor with progress callback, conflict config, and events
-    let engine = ExecutionEngine::new();
-    let result = engine
-        .execute_journal_with_config_and_events(
-            &plan.plan_id,
-            Some(progress_callback),
-            config,
-            Some(app_handle.clone()),
-        )
-        .await?;
+    let result = api::execute_plan(&plan, config, Arc::new(app_handle)).await?;
 
     tracing::info!(
         completed = result.completed_count,
@@ -373,7 +332,7 @@ pub async fn execute_plan_parallel(
 
     // Clean up the journal if all succeeded
     if result.success {
-        let _ = wal_manager.discard_journal(&plan.plan_id);
+        let _ = WALManager::new().discard_journal(&plan.plan_id);
 
         // V7: Clean up empty directories in the original folder
         if let Some(ref original) = original_folder {
@@ -1,11 +1,14 @@
 pub mod ai;
 pub mod billing;
+pub mod blueprints;
 pub mod chat;
+pub mod corrections;
 pub mod filesystem;
 pub mod grok;
 pub mod jobs;
 pub mod permissions;
 pub mod photos;
+pub mod scheduler;
 pub mod thumbnails;
 pub mod vector;
 pub mod vfs;
@@ -14,14 +17,29 @@ pub mod watcher;
 
 pub use ai::*;
 pub use billing::*;
+pub use blueprints::*;
 pub use chat::*;
+pub use corrections::*;
 pub use filesystem::*;
 pub use grok::*;
 pub use jobs::*;
 pub use permissions::*;
 pub use photos::*;
+pub use scheduler::*;
 pub use thumbnails::*;
 pub use vector::*;
 pub use vfs::*;
 pub use wal::*;
 pub use watcher::*;
+
+use crate::events::EventSink;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// The desktop app forwards core events to the webview unchanged
+impl<R: Runtime> EventSink for AppHandle<R> {
+    fn emit_json(&self, event: &str, payload: serde_json::Value) {
+        if let Err(e) = Emitter::emit(self, event, payload) {
+            tracing::warn!(event, error = %e, "Failed to emit event");
+        }
+    }
+}
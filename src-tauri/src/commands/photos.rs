@@ -1,3 +1,4 @@
+use crate::scan::{ScanEntry, ScanOptions};
 use std::path::Path;
 
 const IMAGE_EXTENSIONS: &[&str] = &[
@@ -96,85 +97,37 @@ pub fn get_photo_directories() -> Vec<(String, String)> {
     dirs
 }
 
-/// Recursively scan a directory for photos
-fn scan_directory_for_photos(dir: &Path, max_depth: usize) -> std::io::Result<Vec<PhotoEntry>> {
-    let mut photos = Vec::new();
-
+/// Scan a directory for photos with the shared scanner
+fn scan_directory_for_photos(dir: &Path, max_depth: usize) -> Result<Vec<PhotoEntry>, String> {
     if max_depth == 0 {
-        return Ok(photos);
+        return Ok(Vec::new());
     }
 
-    let entries = match std::fs::read_dir(dir) {
-        Ok(entries) => entries,
-        Err(e) => {
-            // Skip directories we can't read (permission denied, etc.)
-            eprintln!("Cannot read directory {:?}: {}", dir, e);
-            return Ok(photos);
-        }
-    };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-
-        // Skip hidden files/directories
-        if path
-            .file_name()
-            .map(|n| n.to_string_lossy().starts_with('.'))
-            .unwrap_or(false)
-        {
-            continue;
-        }
-
-        if path.is_dir() {
-            // Recursively scan subdirectories
-            if let Ok(sub_photos) = scan_directory_for_photos(&path, max_depth - 1) {
-                photos.extend(sub_photos);
-            }
-        } else if path.is_file() {
-            // Check if it's an image file
-            if let Some(ext) = path.extension() {
-                let ext_lower = ext.to_string_lossy().to_lowercase();
-                if IMAGE_EXTENSIONS.contains(&ext_lower.as_str()) {
-                    if let Ok(photo) = create_photo_entry(&path) {
-                        photos.push(photo);
-                    }
-                }
-            }
-        }
+    let snapshot = ScanOptions::new().with_max_depth(max_depth).scan(dir)?;
+    for error in snapshot.errors() {
+        // Skip directories we can't read (permission denied, etc.)
+        eprintln!("Cannot read directory {:?}: {}", error.path, error.message);
     }
 
-    Ok(photos)
+    Ok(snapshot
+        .files()
+        .filter(|entry| {
+            entry
+                .extension()
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+        })
+        .map(create_photo_entry)
+        .collect())
 }
 
-/// Create a PhotoEntry from a file path
-fn create_photo_entry(path: &Path) -> std::io::Result<PhotoEntry> {
-    let metadata = std::fs::metadata(path)?;
-
-    let name = path
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_default();
-
-    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
-
-    let modified_at = metadata
-        .modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as i64);
-
-    let created_at = metadata
-        .created()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as i64);
-
-    Ok(PhotoEntry {
-        path: path.to_string_lossy().to_string(),
-        name,
-        size: metadata.len(),
-        created_at,
-        modified_at,
-        extension,
-    })
+/// Create a PhotoEntry from a snapshot entry
+fn create_photo_entry(entry: &ScanEntry) -> PhotoEntry {
+    PhotoEntry {
+        path: entry.path.to_string_lossy().to_string(),
+        name: entry.name.clone(),
+        size: entry.size,
+        created_at: entry.created_at,
+        modified_at: entry.modified_at,
+        extension: entry.path.extension().map(|e| e.to_string_lossy().to_string()),
+    }
 }
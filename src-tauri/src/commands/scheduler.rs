@@ -0,0 +1,68 @@
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+use crate::api;
+use crate::services::schedule_config::{ScheduleConfig, ScheduledJob};
+use crate::services::schedule_history::{RunTrigger, ScheduleHistory, ScheduleRun};
+use crate::services::scheduler::{self, ScheduledJobStatus, SchedulerHandle};
+
+/// List scheduled jobs with their next run, last run and running state
+#[tauri::command]
+pub fn list_scheduled_jobs(handle: State<'_, SchedulerHandle>) -> Vec<ScheduledJobStatus> {
+    scheduler::job_statuses(handle.inner())
+}
+
+/// Create or update a scheduled job (an empty id creates a new one)
+#[tauri::command]
+pub async fn save_scheduled_job(mut job: ScheduledJob) -> Result<ScheduledJob, String> {
+    job.validate()?;
+    api::validate_folder(Path::new(&job.folder))?;
+    if job.id.trim().is_empty() {
+        job.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let mut config = ScheduleConfig::load();
+    config.upsert(job.clone());
+    config.save()?;
+    Ok(job)
+}
+
+/// Remove a scheduled job (its run history is kept)
+#[tauri::command]
+pub async fn remove_scheduled_job(id: String) -> Result<(), String> {
+    let mut config = ScheduleConfig::load();
+    if !config.remove(&id) {
+        return Err(format!("No scheduled job with id {}", id));
+    }
+    config.save()
+}
+
+/// Run a scheduled job now, regardless of its schedule
+#[tauri::command]
+pub async fn run_scheduled_job_now(
+    app: AppHandle,
+    handle: State<'_, SchedulerHandle>,
+    id: String,
+) -> Result<ScheduleRun, String> {
+    let job = ScheduleConfig::load()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("No scheduled job with id {}", id))?;
+
+    let events: crate::events::SharedEventSink = Arc::new(app);
+    scheduler::run_job(handle.inner(), &job, RunTrigger::Manual, None, &events).await
+}
+
+/// Past runs of one scheduled job (or all of them), newest first
+#[tauri::command]
+pub async fn get_schedule_history(
+    schedule_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ScheduleRun>, String> {
+    tokio::task::spawn_blocking(move || {
+        ScheduleHistory::open_default()?.runs(schedule_id.as_deref(), limit.unwrap_or(50))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
@@ -1,3 +1,4 @@
+use crate::services::pdf_raster::{self, RendererCapabilities};
 use crate::services::thumbnails;
 
 #[tauri::command]
@@ -21,3 +22,11 @@ pub async fn get_thumbnail_cache_stats() -> Result<thumbnails::CacheStats, Strin
         .await
         .map_err(|e| format!("Task failed: {}", e))?
 }
+
+/// Report which PDF/video renderers are available on this machine
+#[tauri::command]
+pub async fn get_renderer_capabilities() -> Result<RendererCapabilities, String> {
+    tokio::task::spawn_blocking(pdf_raster::capabilities)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
@@ -3,9 +3,9 @@
 //! Provides Tauri commands for initializing and querying the vector index,
 //! as well as generating compressed tree XML for AI context.
 
-use crate::models::FileEntry;
+use crate::api;
 use crate::tree::{to_xml, TreeCompressor, TreeConfig};
-use crate::vector::{VectorConfig, VectorIndex};
+use crate::vector::VectorIndex;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use tauri::State;
@@ -45,50 +45,7 @@ pub async fn init_vector_index(
 ) -> Result<usize, String> {
     eprintln!("[VectorCommand] Initializing vector index for: {}", folder_path);
 
-    let path = PathBuf::from(&folder_path);
-    if !path.exists() || !path.is_dir() {
-        return Err(format!("Invalid folder path: {}", folder_path));
-    }
-
-    // Create the vector index
-    let config = VectorConfig::default();
-    let mut index = VectorIndex::new(config)?;
-
-    // Collect files to index
-    let files = collect_files_recursive(&path, 5)?;
-    eprintln!("[VectorCommand] Found {} files to index", files.len());
-
-    if files.is_empty() {
-        // Store empty index
-        let mut state_guard = state.0.write().map_err(|e| e.to_string())?;
-        *state_guard = Some(index);
-        return Ok(0);
-    }
-
-    // Prepare batch for indexing
-    let batch: Vec<(PathBuf, String, Option<String>)> = files
-        .into_iter()
-        .map(|entry| {
-            let file_path = PathBuf::from(&entry.path);
-            let content_preview = get_content_preview(&file_path);
-            (file_path, entry.name, content_preview)
-        })
-        .collect();
-
-    // Index in batches of 100 for memory efficiency
-    let mut total_indexed = 0;
-    for chunk in batch.chunks(100) {
-        let chunk_vec: Vec<(PathBuf, String, Option<String>)> = chunk.to_vec();
-        match index.index_batch(chunk_vec) {
-            Ok(count) => {
-                total_indexed += count;
-                eprintln!("[VectorCommand] Indexed {} files (total: {})", count, total_indexed);
-            }
-            Err(e) => {
-                eprintln!("[VectorCommand] Warning: Batch indexing failed: {}", e);
-            }
-        }
-    }
+    let (index, total_indexed) = api::build_search_index(&PathBuf::from(&folder_path))?;
 
     // Store the index in state
     let mut state_guard = state.0.write().map_err(|e| e.to_string())?;
@@ -113,12 +70,7 @@ pub async fn vector_search(
         .as_ref()
         .ok_or_else(|| "Vector index not initialized. Call init_vector_index first.".to_string())?;
 
-    let results = index.search(&query)?;
-
-    let string_results: Vec<(String, f32)> = results
-        .into_iter()
-        .map(|(path, score)| (path.to_string_lossy().to_string(), score))
-        .collect();
+    let string_results = api::search(index, &query)?;
 
     eprintln!("[VectorCommand] Found {} results", string_results.len());
     Ok(string_results)
@@ -316,76 +268,3 @@ pub async fn clear_vector_index(
     eprintln!("[VectorCommand] Vector index cleared");
     Ok(())
 }
-
-// === Helper Functions ===
-
-/// Recursively collect files from a directory
-fn collect_files_recursive(path: &PathBuf, max_depth: usize) -> Result<Vec<FileEntry>, String> {
-    let mut files = Vec::new();
-    collect_files_recursive_inner(path, &mut files, 0, max_depth)?;
-    Ok(files)
-}
-
-fn collect_files_recursive_inner(
-    path: &PathBuf,
-    files: &mut Vec<FileEntry>,
-    depth: usize,
-    max_depth: usize,
-) -> Result<(), String> {
-    if depth > max_depth {
-        return Ok(());
-    }
-
-    let entries = std::fs::read_dir(path)
-        .map_err(|e| format!("Failed to read directory {:?}: {}", path, e))?;
-
-    for entry in entries.filter_map(|e| e.ok()) {
-        let entry_path = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
-
-        // Skip hidden files
-        if name.starts_with('.') {
-            continue;
-        }
-
-        if let Ok(file_type) = entry.file_type() {
-            if file_type.is_file() {
-                if let Ok(file_entry) = FileEntry::from_path(&entry_path) {
-                    files.push(file_entry);
-                }
-            } else if file_type.is_dir() {
-                collect_files_recursive_inner(&entry_path, files, depth + 1, max_depth)?;
-            }
-        }
-    }
-
-    Ok(())
-}
-
-/// Get a content preview for a file (for better semantic matching)
-///
-/// Currently supports text files; returns None for binary files
-fn get_content_preview(path: &PathBuf) -> Option<String> {
-    // Only read text files
-    let extension = path.extension()?.to_str()?;
-
-    let text_extensions = [
-        "txt", "md", "json", "yaml", "yml", "toml", "xml", "html", "css",
-        "js", "ts", "jsx", "tsx", "py", "rs", "go", "java", "c", "cpp",
-        "h", "hpp", "swift", "kt", "rb", "php", "sh", "bash", "zsh",
-        "sql", "csv", "log", "conf", "ini", "env", "gitignore",
-    ];
-
-    if !text_extensions.contains(&extension.to_lowercase().as_str()) {
-        return None;
-    }
-
-    // Read first 500 bytes
-    match std::fs::read(path) {
-        Ok(bytes) => {
-            let preview_len = bytes.len().min(500);
-            String::from_utf8(bytes[..preview_len].to_vec()).ok()
-        }
-        Err(_) => None,
-    }
-}
@@ -8,9 +8,16 @@ use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
 
+use crate::api;
 use crate::jobs::OrganizePlan;
-use crate::quarantine::{CleanupStats, QuarantineManager, QuarantinedItem};
-use crate::vfs::{FileNode, JWalkScanner, ScanStats, ShadowVFS, SimulatedOperation};
+use crate::quarantine::{
+    CleanupStats, QuarantineArea, QuarantineManager, QuarantinePolicy, QuarantinedItem,
+    RebuildStats,
+};
+use crate::vfs::{
+    DiffFormat, FileNode, JWalkScanner, PlanDiff, ScanStats, ShadowVFS, SnapshotDiff,
+    SnapshotInfo, SnapshotStore,
+};
 
 /// Thread-safe VFS state managed by Tauri
 pub type VFSState = Arc<RwLock<Option<ShadowVFS>>>;
@@ -152,45 +159,7 @@ pub async fn vfs_simulate_plan(
         .as_mut()
         .ok_or("VFS not initialized. Call scan_folder_vfs first.")?;
 
-    let operations: Vec<SimulatedOperation> = plan
-        .operations
-        .iter()
-        .filter_map(|op| {
-            match op.op_type.as_str() {
-                "move" => {
-                    let src = op.source.as_ref()?;
-                    let dest = op.destination.as_ref()?;
-                    Some(SimulatedOperation::Move {
-                        source: src.clone(),
-                        destination: dest.clone(),
-                    })
-                }
-                "create_folder" => {
-                    let path = op.path.as_ref()?;
-                    Some(SimulatedOperation::CreateFolder { path: path.clone() })
-                }
-                "delete" => {
-                    let path = op.path.as_ref().or(op.source.as_ref())?;
-                    Some(SimulatedOperation::Delete { path: path.clone() })
-                }
-                "rename" => {
-                    // Rename is a move to the same directory with a new name
-                    let path = op.path.as_ref()?;
-                    let new_name = op.new_name.as_ref()?;
-                    let path_buf = PathBuf::from(path);
-                    let new_path = path_buf
-                        .parent()
-                        .map(|p| p.join(new_name))
-                        .unwrap_or_else(|| PathBuf::from(new_name));
-                    Some(SimulatedOperation::Move {
-                        source: path.clone(),
-                        destination: new_path.to_string_lossy().to_string(),
-                    })
-                }
-                _ => None,
-            }
-        })
-        .collect();
+    let operations = plan.to_simulated_operations();
 
     match crate::vfs::simulate_plan(vfs, operations) {
         Ok(()) => Ok(Vec::new()),
@@ -198,6 +167,26 @@ pub async fn vfs_simulate_plan(
     }
 }
 
+/// Render the before/after diff of an organize plan against the scanned VFS
+///
+/// `format` is `tree`, `json`, `markdown` or `html`. The VFS is not modified.
+#[tauri::command]
+pub async fn vfs_render_plan_diff(
+    plan: OrganizePlan,
+    format: String,
+    vfs_state: State<'_, VFSState>,
+) -> Result<String, String> {
+    let format = DiffFormat::parse(&format)?;
+    let state = vfs_state.read().await;
+    let vfs = state
+        .as_ref()
+        .ok_or("VFS not initialized. Call scan_folder_vfs first.")?;
+
+    PlanDiff::build(vfs, &plan.to_simulated_operations())
+        .with_plan(&plan.plan_id, &plan.description)
+        .render(format)
+}
+
 /// Stage a move operation in the VFS
 #[tauri::command]
 pub async fn vfs_stage_move(
@@ -300,6 +289,66 @@ pub async fn vfs_clear(
     Ok(())
 }
 
+// ============================================================================
+// Snapshot Commands
+// ============================================================================
+
+/// Persist the current VFS scan as a snapshot
+///
+/// Survives `vfs_clear` and restarts; compare snapshots with `snapshot_diff`.
+#[tauri::command]
+pub async fn vfs_save_snapshot(
+    label: Option<String>,
+    vfs_state: State<'_, VFSState>,
+) -> Result<SnapshotInfo, String> {
+    let vfs = vfs_state
+        .read()
+        .await
+        .as_ref()
+        .ok_or("VFS not initialized. Call scan_folder_vfs first.")?
+        .clone();
+
+    api::save_snapshot(vfs, label).await
+}
+
+/// Scan a folder and save it as a snapshot (does not touch the VFS state)
+#[tauri::command]
+pub async fn snapshot_capture(
+    folder_path: String,
+    label: Option<String>,
+) -> Result<SnapshotInfo, String> {
+    api::capture_snapshot(&PathBuf::from(folder_path), label.as_deref()).await
+}
+
+/// List saved snapshots, newest first (all folders when no path is given)
+#[tauri::command]
+pub async fn snapshot_list(folder_path: Option<String>) -> Result<Vec<SnapshotInfo>, String> {
+    api::list_snapshots(folder_path.map(PathBuf::from).as_deref())
+}
+
+/// Compare two snapshots
+#[tauri::command]
+pub async fn snapshot_diff(from_id: i64, to_id: i64) -> Result<SnapshotDiff, String> {
+    api::diff_snapshots(from_id, to_id)
+}
+
+/// Rescan a folder and report changes since a date (YYYY-MM-DD, RFC 3339 or
+/// an age like "7d"), or since the last snapshot when no date is given
+#[tauri::command]
+pub async fn snapshot_changes_since(
+    folder_path: String,
+    since: Option<String>,
+) -> Result<SnapshotDiff, String> {
+    let since = since.as_deref().map(api::parse_since).transpose()?;
+    api::changes_since(&PathBuf::from(folder_path), since).await
+}
+
+/// Delete a saved snapshot
+#[tauri::command]
+pub async fn snapshot_delete(id: i64) -> Result<bool, String> {
+    SnapshotStore::open_default()?.delete(id)
+}
+
 // ============================================================================
 // Quarantine Commands
 // ============================================================================
@@ -370,3 +419,40 @@ pub async fn quarantine_check(
     let path = PathBuf::from(&original_path);
     Ok(manager.is_quarantined(&path))
 }
+
+/// Get the quarantine retention and quota policy
+#[tauri::command]
+pub async fn quarantine_get_policy(
+    quarantine_state: State<'_, QuarantineState>,
+) -> Result<QuarantinePolicy, String> {
+    let manager = quarantine_state.read().await;
+    Ok(manager.policy().clone())
+}
+
+/// Replace the quarantine retention and quota policy
+#[tauri::command]
+pub async fn quarantine_set_policy(
+    policy: QuarantinePolicy,
+    quarantine_state: State<'_, QuarantineState>,
+) -> Result<(), String> {
+    let mut manager = quarantine_state.write().await;
+    manager.set_policy(policy)
+}
+
+/// Space used by each volume's quarantine area
+#[tauri::command]
+pub async fn quarantine_usage(
+    quarantine_state: State<'_, QuarantineState>,
+) -> Result<Vec<QuarantineArea>, String> {
+    let manager = quarantine_state.read().await;
+    manager.areas()
+}
+
+/// Re-sync the quarantine index with the files in the quarantine areas
+#[tauri::command]
+pub async fn quarantine_rebuild_index(
+    quarantine_state: State<'_, QuarantineState>,
+) -> Result<RebuildStats, String> {
+    let manager = quarantine_state.read().await;
+    manager.rebuild_index()
+}
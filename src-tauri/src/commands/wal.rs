@@ -8,7 +8,10 @@ use crate::wal::recovery::{
     RecoveryInfo, RecoveryResult,
 };
 use crate::wal::{WALJournal, WALManager, WALOperationType};
-use crate::execution::{ExecutionBuilder, ExecutionEngine, ExecutionResult};
+use crate::execution::{
+    ConflictPolicy, ExecutionBuilder, ExecutionConfig, ExecutionEngine, ExecutionResult,
+    PreflightReport,
+};
 use std::path::PathBuf;
 
 /// Check if there are any interrupted jobs that need recovery
@@ -177,6 +180,23 @@ pub async fn wal_execute_journal(job_id: String) -> Result<ExecutionResult, Stri
     engine.execute_journal(&job_id).await
 }
 
+/// Dry-run a journal against the live filesystem
+///
+/// Predicts the outcome of each pending entry under the conflict policy
+/// (default: fail), checks permissions and free space, and touches nothing.
+#[tauri::command]
+pub async fn wal_preflight_journal(
+    job_id: String,
+    conflict: Option<ConflictPolicy>,
+) -> Result<PreflightReport, String> {
+    let config = ExecutionConfig {
+        on_destination_exists: conflict.unwrap_or_default(),
+    };
+    tokio::task::spawn_blocking(move || ExecutionEngine::new().preflight_journal(&job_id, &config))
+        .await
+        .map_err(|e| format!("Preflight task failed: {}", e))?
+}
+
 /// Execute operations with a new builder pattern
 ///
 /// Creates a new journal, adds operations, and executes them.
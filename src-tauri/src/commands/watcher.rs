@@ -1,8 +1,13 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::{AppHandle, State};
 
+use super::vector::VectorState;
+use crate::services::watch_config::WatchedFolder;
+use crate::services::watch_journal::{JournalEntry, JournalQuery, WatchJournal};
 use crate::services::watcher::{
-    self, is_watcher_running, get_watching_path, get_all_watching_paths, WatcherHandle,
+    self, is_watcher_running, get_watching_path, get_all_watching_paths, WatchContext,
+    WatchedFolderStatus, WatcherHandle,
 };
 
 /// System directories that should never be watched
@@ -83,6 +88,24 @@ fn validate_watch_path(path: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
+/// Validate that a path exists, is a directory and is safe to watch
+fn validate_watch_dir(path: &PathBuf) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path.display()));
+    }
+
+    // SECURITY: Validate the path is safe to watch
+    validate_watch_path(path)
+}
+
+fn watch_context(app: AppHandle, vectors: &VectorState) -> WatchContext {
+    WatchContext::new(Arc::new(app), Arc::clone(&vectors.0))
+}
+
 /// Watcher status response
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -90,6 +113,8 @@ pub struct WatcherStatus {
     pub enabled: bool,
     pub watching_path: Option<String>,
     pub watching_paths: Vec<String>,
+    /// Saved folders with their settings and runtime state
+    pub folders: Vec<WatchedFolderStatus>,
 }
 
 /// Start the downloads watcher (legacy single folder)
@@ -97,6 +122,7 @@ pub struct WatcherStatus {
 pub async fn start_downloads_watcher(
     app: AppHandle,
     handle: State<'_, WatcherHandle>,
+    vectors: State<'_, VectorState>,
     path: Option<String>,
 ) -> Result<(), String> {
     let watch_path = if let Some(p) = path {
@@ -105,18 +131,9 @@ pub async fn start_downloads_watcher(
         dirs::download_dir().ok_or("Could not determine downloads directory")?
     };
 
-    if !watch_path.exists() {
-        return Err(format!("Path does not exist: {}", watch_path.display()));
-    }
-
-    if !watch_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", watch_path.display()));
-    }
-
-    // SECURITY: Validate the path is safe to watch
-    validate_watch_path(&watch_path)?;
+    validate_watch_dir(&watch_path)?;
 
-    watcher::start_watcher(app, handle.inner().clone(), watch_path)?;
+    watcher::start_watcher(&watch_context(app, &vectors), handle.inner(), watch_path)?;
 
     Ok(())
 }
@@ -126,24 +143,43 @@ pub async fn start_downloads_watcher(
 pub async fn add_watched_folder(
     app: AppHandle,
     handle: State<'_, WatcherHandle>,
+    vectors: State<'_, VectorState>,
     path: String,
 ) -> Result<(), String> {
     let watch_path = PathBuf::from(&path);
+    validate_watch_dir(&watch_path)?;
 
-    if !watch_path.exists() {
-        return Err(format!("Path does not exist: {}", path));
-    }
+    watcher::add_watched_folder(&watch_context(app, &vectors), handle.inner(), watch_path)?;
 
-    if !watch_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", path));
-    }
+    Ok(())
+}
 
-    // SECURITY: Validate the path is safe to watch
-    validate_watch_path(&watch_path)?;
+/// Save a watched folder's settings (recursion, debounce, globs, action) and
+/// arm or disarm it
+#[tauri::command]
+pub async fn configure_watched_folder(
+    app: AppHandle,
+    handle: State<'_, WatcherHandle>,
+    vectors: State<'_, VectorState>,
+    folder: WatchedFolder,
+) -> Result<WatchedFolderStatus, String> {
+    validate_watch_dir(&PathBuf::from(&folder.path))?;
 
-    watcher::add_watched_folder(app, handle.inner().clone(), watch_path)?;
+    watcher::configure_watched_folder(&watch_context(app, &vectors), handle.inner(), folder)
+}
 
-    Ok(())
+/// List saved watched folders with their runtime state
+#[tauri::command]
+pub fn list_watched_folders(handle: State<'_, WatcherHandle>) -> Vec<WatchedFolderStatus> {
+    watcher::folder_statuses(handle.inner())
+}
+
+/// Query the journal of changes seen in watched folders (newest first)
+#[tauri::command]
+pub async fn query_watch_journal(query: JournalQuery) -> Result<Vec<JournalEntry>, String> {
+    tokio::task::spawn_blocking(move || WatchJournal::open_default()?.query(&query))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
 }
 
 /// Remove a folder from watching
@@ -152,7 +188,7 @@ pub async fn remove_watched_folder(
     handle: State<'_, WatcherHandle>,
     path: String,
 ) -> Result<(), String> {
-    watcher::remove_watched_folder(handle.inner().clone(), &path)
+    watcher::remove_watched_folder(handle.inner(), &path)
 }
 
 /// Stop the downloads watcher (stops all folders)
@@ -160,7 +196,7 @@ pub async fn remove_watched_folder(
 pub async fn stop_downloads_watcher(
     handle: State<'_, WatcherHandle>,
 ) -> Result<(), String> {
-    watcher::stop_watcher(handle.inner().clone())
+    watcher::stop_watcher(handle.inner())
 }
 
 /// Get watcher status
@@ -172,5 +208,6 @@ pub fn get_watcher_status(handle: State<'_, WatcherHandle>) -> WatcherStatus {
         watching_path: get_watching_path(handle.inner())
             .map(|p| p.to_string_lossy().to_string()),
         watching_paths,
+        folders: watcher::folder_statuses(handle.inner()),
     }
 }
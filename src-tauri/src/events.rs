@@ -0,0 +1,145 @@
+//! Event sinks - how core logic reports progress without depending on Tauri.
+//!
+//! Long-running flows (chat agent, watcher, plan execution) emit named events
+//! with JSON payloads. The desktop app forwards them to the webview through
+//! `impl EventSink for AppHandle` (see `commands`); headless users can log,
+//! collect or drop them.
+//!
+//! Event names are unchanged from the Tauri-only days (`chat:token`,
+//! `execution-progress`, `sentinel://file-created`, ...), so the frontend does
+//! not care which layer emitted them.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// Receiver for events emitted by core logic.
+///
+/// Implementations must be cheap and must not block: events are emitted from
+/// executor tasks and watcher threads. Delivery failures are the sink's
+/// business and are never returned to the emitter.
+pub trait EventSink: Send + Sync {
+    /// Deliver one event with an already-serialized payload
+    fn emit_json(&self, event: &str, payload: Value);
+}
+
+impl dyn EventSink + '_ {
+    /// Serialize `payload` and deliver it
+    pub fn emit<T: Serialize + ?Sized>(&self, event: &str, payload: &T) {
+        match serde_json::to_value(payload) {
+            Ok(value) => self.emit_json(event, value),
+            Err(e) => tracing::warn!(event, error = %e, "Failed to serialize event payload"),
+        }
+    }
+}
+
+/// Shared sink handle for code that outlives the caller (spawned tasks, watcher threads)
+pub type SharedEventSink = Arc<dyn EventSink>;
+
+/// Sink that drops every event
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopSink;
+
+impl EventSink for NoopSink {
+    fn emit_json(&self, _event: &str, _payload: Value) {}
+}
+
+/// Sink backed by a closure, e.g. to print progress in a CLI
+pub struct FnSink<F>(pub F);
+
+impl<F> EventSink for FnSink<F>
+where
+    F: Fn(&str, Value) + Send + Sync,
+{
+    fn emit_json(&self, event: &str, payload: Value) {
+        (self.0)(event, payload)
+    }
+}
+
+/// Sink that records every event in order (tests, batch reports)
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    events: Mutex<Vec<(String, Value)>>,
+}
+
+impl RecordingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All events received so far
+    pub fn events(&self) -> Vec<(String, Value)> {
+        self.events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Payloads of the events named `event`
+    pub fn payloads(&self, event: &str) -> Vec<Value> {
+        self.events()
+            .into_iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload)
+            .collect()
+    }
+}
+
+impl EventSink for RecordingSink {
+    fn emit_json(&self, event: &str, payload: Value) {
+        self.events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push((event.to_string(), payload));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Progress {
+        completed: usize,
+        total_ops: usize,
+    }
+
+    #[test]
+    fn test_emit_serializes_payload() {
+        let recorder = Arc::new(RecordingSink::new());
+        let sink: SharedEventSink = recorder.clone();
+
+        sink.emit("execution-progress", &Progress { completed: 1, total_ops: 4 });
+        sink.emit_json("chat:complete", json!({}));
+
+        assert_eq!(
+            recorder.events(),
+            vec![
+                ("execution-progress".to_string(), json!({"completed": 1, "totalOps": 4})),
+                ("chat:complete".to_string(), json!({})),
+            ]
+        );
+        assert_eq!(recorder.payloads("chat:complete"), vec![json!({})]);
+    }
+
+    #[test]
+    fn test_fn_sink() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let sink = FnSink(move |event: &str, _payload: Value| {
+            if event.starts_with("chat:") {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let sink: &dyn EventSink = &sink;
+
+        sink.emit("chat:token", &json!({"chunk": "hi"}));
+        sink.emit("execution-progress", &json!({}));
+        NoopSink.emit_json("chat:token", json!({}));
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
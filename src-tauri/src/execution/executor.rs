@@ -3,18 +3,21 @@
 //! Executes WAL operations using the DAG-based dependency graph.
 //! Operations at the same level are executed in parallel using tokio tasks.
 
+use crate::events::SharedEventSink;
+use crate::scan::ignore::{enclosing_repo, IgnoreRules};
 use crate::security::{cycle_detection, PathValidator};
 use crate::wal::entry::{WALEntry, WALJournal, WALOperationType, WALStatus};
 use crate::wal::journal::WALManager;
+use crate::wal::ops;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
 use tokio::sync::{Mutex, Semaphore};
 
 use super::dag::ExecutionDAG;
+use super::preflight::{self, PreflightReport};
 
 /// Extract parent directories affected by an operation for hot reload
 fn get_affected_directories(operation: &WALOperationType) -> Vec<String> {
@@ -73,7 +76,99 @@ fn get_affected_directories(operation: &WALOperationType) -> Vec<String> {
                 vec![]
             }
         }
+        WALOperationType::RemoveCopy { path, .. }
+        | WALOperationType::DeleteFile { path, .. }
+        | WALOperationType::RestoreFile { path, .. }
+        | WALOperationType::CreateSymlink { path, .. }
+        | WALOperationType::CreateAlias { path, .. }
+        | WALOperationType::RemoveLink { path, .. }
+        | WALOperationType::SetTimestamps { path, .. }
+        | WALOperationType::SetPermissions { path, .. }
+        | WALOperationType::SetTags { path, .. }
+        | WALOperationType::SetXattr { path, .. } => {
+            if let Some(parent) = path.parent() {
+                vec![parent.to_string_lossy().to_string()]
+            } else {
+                vec![]
+            }
+        }
+    }
+}
+
+/// Refuse plans that touch ignored paths or the inside of a git checkout.
+///
+/// Checked against the journal's roots before anything runs, each path under
+/// the innermost root containing it, so a plan built from a stale scan (or by
+/// hand) cannot slip past the planners' filters. A checkout can still be moved
+/// as a whole; link targets must also lie inside a root. Quarantine
+/// destinations and undo bookkeeping (folder deletions, restores, link and
+/// copy removal) are not checked.
+pub(crate) fn check_protected_paths(journal: &WALJournal, entries: &[WALEntry]) -> Result<(), String> {
+    let mut roots: Vec<IgnoreRules> = journal.roots().into_iter().map(IgnoreRules::for_root).collect();
+    roots.sort_by_key(|rules| std::cmp::Reverse(rules.root().components().count()));
+
+    let protected = |path: &Path, is_dir: bool| -> Option<String> {
+        let rules = roots.iter().find(|rules| path.starts_with(rules.root()))?;
+        if let Some(repo) = enclosing_repo(path, rules.root()) {
+            return Some(format!(
+                "{} is inside the git checkout {}",
+                path.display(),
+                repo.display()
+            ));
+        }
+        rules
+            .is_ignored(path, is_dir)
+            .then(|| format!("{} is excluded by ignore rules", path.display()))
+    };
+
+    let mut violations = Vec::new();
+    for entry in entries {
+        let problem = match &entry.operation {
+            WALOperationType::Move { source, destination }
+            | WALOperationType::Copy { source, destination } => {
+                protected(source, source.is_dir()).or_else(|| protected(destination, source.is_dir()))
+            }
+            WALOperationType::Rename { path, new_name } => {
+                let renamed = path.with_file_name(new_name);
+                protected(path, path.is_dir()).or_else(|| protected(&renamed, path.is_dir()))
+            }
+            WALOperationType::CreateFolder { path } => protected(path, true),
+            WALOperationType::Quarantine { path, .. }
+            | WALOperationType::DeleteFile { path, .. }
+            | WALOperationType::SetTimestamps { path, .. }
+            | WALOperationType::SetPermissions { path, .. }
+            | WALOperationType::SetTags { path, .. }
+            | WALOperationType::SetXattr { path, .. } => protected(path, path.is_dir()),
+            WALOperationType::CreateSymlink { path, target }
+            | WALOperationType::CreateAlias { path, target } => {
+                protected(path, false).or_else(|| {
+                    if !roots.iter().any(|rules| target.starts_with(rules.root())) {
+                        return Some(format!(
+                            "link target {} is outside the plan's folders",
+                            target.display()
+                        ));
+                    }
+                    protected(target, target.is_dir())
+                })
+            }
+            WALOperationType::DeleteFolder { .. }
+            | WALOperationType::RemoveCopy { .. }
+            | WALOperationType::RestoreFile { .. }
+            | WALOperationType::RemoveLink { .. } => None,
+        };
+        violations.extend(problem);
     }
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let shown: Vec<&str> = violations.iter().take(5).map(String::as_str).collect();
+    Err(format!(
+        "Refusing to execute plan: {} operation(s) touch protected paths: {}{}",
+        violations.len(),
+        shown.join("; "),
+        if violations.len() > shown.len() { "; ..." } else { "" }
+    ))
 }
 
 /// Maximum number of concurrent filesystem operations
@@ -85,7 +180,7 @@ const MAX_CONCURRENT_OPS: usize = 10;
 const PROGRESS_BATCH_SIZE: usize = 5;
 
 /// Policy for handling destination conflicts during execution
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ConflictPolicy {
     /// Return error (current/default behavior)
@@ -249,7 +344,7 @@ impl ExecutionEngine {
         job_id: &str,
         progress_callback: Option<Arc<ProgressCallback>>,
         config: ExecutionConfig,
-        app_handle: Option<AppHandle>,
+        events: Option<SharedEventSink>,
     ) -> Result<ExecutionResult, String> {
         let journal = self
             .wal_manager
@@ -268,6 +363,9 @@ impl ExecutionEngine {
             return Ok(ExecutionResult::success(0));
         }
 
+        // Hard guard: never reorganize ignored content or git checkouts
+        check_protected_paths(&journal, &pending_entries)?;
+
         // Build DAG from pending entries
         let dag = ExecutionDAG::from_entries(pending_entries)?;
 
@@ -277,10 +375,23 @@ impl ExecutionEngine {
             "Built execution DAG"
         );
 
-        self.execute_dag_with_config_and_events(&dag, job_id, progress_callback, config, app_handle)
+        self.execute_dag_with_config_and_events(&dag, job_id, progress_callback, config, events)
             .await
     }
 
+    /// Predict the outcome of a journal's pending entries without touching the disk
+    pub fn preflight_journal(
+        &self,
+        job_id: &str,
+        config: &ExecutionConfig,
+    ) -> Result<PreflightReport, String> {
+        let journal = self
+            .wal_manager
+            .load_journal(job_id)?
+            .ok_or_else(|| format!("Journal not found: {}", job_id))?;
+        preflight::preflight_journal(&journal, config)
+    }
+
     /// Execute operations organized by the DAG
     ///
     /// Each level is executed in parallel, but levels are executed sequentially.
@@ -324,7 +435,7 @@ impl ExecutionEngine {
         job_id: &str,
         progress_callback: Option<Arc<ProgressCallback>>,
         config: ExecutionConfig,
-        app_handle: Option<AppHandle>,
+        events: Option<SharedEventSink>,
     ) -> Result<ExecutionResult, String> {
         let levels = dag.get_levels_owned();
         let total_ops = dag.len();
@@ -353,7 +464,7 @@ impl ExecutionEngine {
                     progress_callback.clone(),
                     base_completed,
                     total_ops,
-                    app_handle.clone(),
+                    events.clone(),
                 )
                 .await?;
 
@@ -426,7 +537,7 @@ impl ExecutionEngine {
         progress_callback: Option<Arc<ProgressCallback>>,
         base_completed: usize,
         total_ops: usize,
-        app_handle: Option<AppHandle>,
+        events: Option<SharedEventSink>,
     ) -> Result<LevelResult, String> {
         if entries.is_empty() {
             return Ok(LevelResult::default());
@@ -465,7 +576,7 @@ impl ExecutionEngine {
             let level_processed = Arc::clone(&level_processed);
             let ops_since_emit = Arc::clone(&ops_since_emit);
             let progress_callback = progress_callback.clone();
-            let app_handle = app_handle.clone();
+            let events = events.clone();
 
             let handle = tokio::spawn(async move {
                 // Acquire semaphore permit to limit concurrency
@@ -520,10 +631,10 @@ impl ExecutionEngine {
 
                 // V7: Emit per-operation event for hot reload (only for successful ops)
                 if op_succeeded {
-                    if let Some(ref app) = app_handle {
+                    if let Some(ref events) = events {
                         let affected_dirs = get_affected_directories(&operation);
                         if !affected_dirs.is_empty() {
-                            let _ = app.emit(
+                            events.emit_json(
                                 "execution-op-complete",
                                 serde_json::json!({
                                     "affectedDirs": affected_dirs,
@@ -641,6 +752,11 @@ async fn execute_operation_with_config(
 
 /// Generate a unique path by appending a counter suffix
 fn generate_unique_path(original: &Path) -> PathBuf {
+    unique_path_with(original, |p| p.exists())
+}
+
+/// [`generate_unique_path`] against a custom notion of "taken" (used by preflight)
+pub(crate) fn unique_path_with(original: &Path, exists: impl Fn(&Path) -> bool) -> PathBuf {
     let parent = original.parent().unwrap_or(Path::new("."));
     let stem = original
         .file_stem()
@@ -654,7 +770,7 @@ fn generate_unique_path(original: &Path) -> PathBuf {
     let mut counter = 1;
     loop {
         let candidate = parent.join(format!("{}_{}{}", stem, counter, ext));
-        if !candidate.exists() {
+        if !exists(&candidate) {
             return candidate;
         }
         counter += 1;
@@ -832,6 +948,43 @@ fn execute_operation_sync_with_config(
             }
             Ok(ExecutionOutcome::Completed)
         }
+
+        WALOperationType::CreateSymlink { path, target }
+        | WALOperationType::CreateAlias { path, target }
+            if fs::symlink_metadata(path).is_ok()
+                && fs::read_link(path).ok().as_ref() != Some(target) =>
+        {
+            match config.on_destination_exists {
+                ConflictPolicy::Skip => Ok(ExecutionOutcome::Skipped(format!(
+                    "Link path exists: {}",
+                    path.display()
+                ))),
+                ConflictPolicy::AutoRename => {
+                    let unique_path = generate_unique_path(path);
+                    let renamed = match operation {
+                        WALOperationType::CreateAlias { .. } => WALOperationType::CreateAlias {
+                            path: unique_path.clone(),
+                            target: target.clone(),
+                        },
+                        _ => WALOperationType::CreateSymlink {
+                            path: unique_path.clone(),
+                            target: target.clone(),
+                        },
+                    };
+                    ops::execute(&renamed)?;
+                    Ok(ExecutionOutcome::CompletedWithRename(unique_path))
+                }
+                ConflictPolicy::Fail => {
+                    Err(format!("Link path already exists: {}", path.display()))
+                }
+            }
+        }
+
+        // Delete (via quarantine), link and metadata operations
+        other => {
+            ops::execute(other)?;
+            Ok(ExecutionOutcome::Completed)
+        }
     }
 }
 
@@ -1031,6 +1184,9 @@ fn execute_operation_sync(operation: &WALOperationType) -> Result<(), String> {
                     .map_err(|e| format!("Failed to delete folder {}: {}", path.display(), e))
             }
         }
+
+        // Delete (via quarantine), link and metadata operations
+        other => ops::execute(other),
     }
 }
 
@@ -1191,4 +1347,79 @@ mod tests {
         // Cleanup
         manager.discard_journal(job_id).unwrap();
     }
+
+    #[tokio::test]
+    async fn test_refuses_plans_touching_git_checkouts() {
+        let dir = tempdir().unwrap();
+        let wal_dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("code/app/.git")).unwrap();
+        fs::write(root.join("code/app/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("notes.txt"), "hi").unwrap();
+
+        let mut journal = WALJournal::new("test-guard".to_string(), root.to_path_buf());
+        for (i, operation) in [
+            WALOperationType::Move {
+                source: root.join("notes.txt"),
+                destination: root.join("Docs/notes.txt"),
+            },
+            WALOperationType::Move {
+                source: root.join("code/app/main.rs"),
+                destination: root.join("Code/main.rs"),
+            },
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            journal.add_entry(WALEntry::new(operation, i as u32).unwrap());
+        }
+        let manager = WALManager::with_dir(wal_dir.path().to_path_buf());
+        manager.save_journal(&journal).unwrap();
+
+        let engine = ExecutionEngine::with_manager(manager);
+        let err = engine.execute_journal("test-guard").await.unwrap_err();
+        assert!(err.contains("git checkout"));
+        // Nothing ran
+        assert!(root.join("notes.txt").exists());
+        assert!(root.join("code/app/main.rs").exists());
+
+        // Moving the checkout as a whole is fine
+        let entries = vec![WALEntry::new(
+            WALOperationType::Move {
+                source: root.join("code/app"),
+                destination: root.join("Projects/app"),
+            },
+            0,
+        )
+        .unwrap()];
+        assert!(check_protected_paths(&journal, &entries).is_ok());
+
+        let entries = vec![WALEntry::new(
+            WALOperationType::CreateFolder {
+                path: root.join("web/node_modules/new"),
+            },
+            0,
+        )
+        .unwrap()];
+        assert!(check_protected_paths(&journal, &entries).unwrap_err().contains("ignore rules"));
+
+        // Link targets must stay inside the plan's folders and out of checkouts
+        let link = |target: PathBuf| {
+            vec![WALEntry::new(
+                WALOperationType::CreateSymlink {
+                    path: root.join("Shortcuts/link"),
+                    target,
+                },
+                0,
+            )
+            .unwrap()]
+        };
+        assert!(check_protected_paths(&journal, &link(root.join("notes.txt"))).is_ok());
+        assert!(check_protected_paths(&journal, &link(PathBuf::from("/etc/passwd")))
+            .unwrap_err()
+            .contains("outside"));
+        assert!(check_protected_paths(&journal, &link(root.join("code/app/main.rs")))
+            .unwrap_err()
+            .contains("git checkout"));
+    }
 }
@@ -2,13 +2,16 @@
 //!
 //! Provides parallel execution of file operations using a DAG-based
 //! dependency graph. Operations at the same level (no dependencies between
-//! them) are executed in parallel for optimal performance.
+//! them) are executed in parallel for optimal performance. A preflight pass
+//! (`preflight`) predicts the outcome of a journal without touching the disk.
 
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
 pub mod dag;
 pub mod executor;
+pub mod preflight;
 
 pub use dag::*;
 pub use executor::*;
+pub use preflight::*;
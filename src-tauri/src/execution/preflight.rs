@@ -0,0 +1,928 @@
+//! Preflight (dry run) of a journal against the live filesystem
+//!
+//! Walks the execution DAG level by level, the way the executor would, and
+//! predicts the outcome of every entry under the active [`ConflictPolicy`]
+//! without touching the disk. Entries are applied to an in-memory overlay as
+//! they are predicted, so later levels see the folders, moves and deletes
+//! planned before them. Bytes that will be written (copies and moves across
+//! volumes) are totalled per volume and compared with the free space.
+
+use crate::quarantine::volume;
+use crate::security::PathValidator;
+use crate::wal::entry::{WALEntry, WALJournal, WALOperationType, WALStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::dag::ExecutionDAG;
+use super::executor::{check_protected_paths, unique_path_with, ConflictPolicy, ExecutionConfig};
+
+/// Predicted result of one journal entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum PredictedOutcome {
+    /// The operation will run as journaled (or is already done)
+    Proceed,
+    /// The destination is taken and will be auto-renamed to `path`
+    Rename { path: PathBuf },
+    /// The operation will be skipped
+    Skip { reason: String },
+    /// The operation will fail
+    Fail { reason: String },
+    /// A dependency is predicted to fail, so this entry never runs
+    Blocked { by: Uuid },
+}
+
+/// Kind of problem found for an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightIssueKind {
+    /// The source (or the path to change) does not exist
+    MissingSource,
+    /// Something already exists at the destination
+    DestinationExists,
+    /// The folder to write into is not writable
+    PermissionDenied,
+    /// The move crosses volumes and will copy then delete
+    CrossDevice,
+    /// The path is protected and will be refused
+    ProtectedPath,
+}
+
+/// A problem found for an entry (fatal or not, see the entry's outcome)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightIssue {
+    pub kind: PreflightIssueKind,
+    pub message: String,
+}
+
+/// Preflight result for one journal entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightEntry {
+    pub id: Uuid,
+    pub sequence: u32,
+    /// DAG level the entry runs in
+    pub level: usize,
+    pub description: String,
+    #[serde(flatten)]
+    pub outcome: PredictedOutcome,
+    pub issues: Vec<PreflightIssue>,
+    /// Bytes the entry writes (copies and moves across volumes)
+    pub bytes_written: u64,
+}
+
+/// Space needed versus space free on one volume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeSpace {
+    pub volume: String,
+    pub mount_point: Option<PathBuf>,
+    pub required_bytes: u64,
+    /// `None` when free space could not be read
+    pub available_bytes: Option<u64>,
+    pub sufficient: bool,
+}
+
+/// Structured preflight report for a journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub job_id: String,
+    pub policy: ConflictPolicy,
+    pub total: usize,
+    pub proceed_count: usize,
+    pub renamed_count: usize,
+    pub skipped_count: usize,
+    pub failed_count: usize,
+    pub blocked_count: usize,
+    /// The executor stops after the first level with a failure
+    pub stops_at_level: Option<usize>,
+    /// Set when the whole journal would be refused (protected paths)
+    pub refused: Option<String>,
+    pub entries: Vec<PreflightEntry>,
+    pub volumes: Vec<VolumeSpace>,
+    /// True when execution is predicted to finish without failures
+    pub ok: bool,
+}
+
+impl PreflightReport {
+    /// One-line summary for logs and the CLI
+    pub fn summary(&self) -> String {
+        format!(
+            "{} operations: {} ok, {} renamed, {} skipped, {} failing, {} blocked",
+            self.total,
+            self.proceed_count,
+            self.renamed_count,
+            self.skipped_count,
+            self.failed_count,
+            self.blocked_count
+        )
+    }
+
+    /// Human-readable problems (failures, refusal, low space)
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self.refused.iter().cloned().collect();
+        for entry in &self.entries {
+            if let PredictedOutcome::Fail { reason } = &entry.outcome {
+                problems.push(format!("{}: {}", entry.description, reason));
+            }
+        }
+        for volume in self.volumes.iter().filter(|v| !v.sufficient) {
+            problems.push(format!(
+                "Not enough space on {}: {} bytes needed, {} free",
+                volume
+                    .mount_point
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| volume.volume.clone()),
+                volume.required_bytes,
+                volume.available_bytes.unwrap_or(0)
+            ));
+        }
+        problems
+    }
+}
+
+/// Preflight the pending entries of a journal (the ones the executor would run)
+pub fn preflight_journal(
+    journal: &WALJournal,
+    config: &ExecutionConfig,
+) -> Result<PreflightReport, String> {
+    let pending: Vec<WALEntry> = journal
+        .entries
+        .iter()
+        .filter(|e| matches!(e.status, WALStatus::Pending | WALStatus::InProgress))
+        .cloned()
+        .collect();
+
+    let refused = check_protected_paths(journal, &pending).err();
+    let dag = ExecutionDAG::from_entries(pending)?;
+    let mut report = preflight_dag(&dag, &journal.job_id, config);
+    if refused.is_some() {
+        report.refused = refused;
+        report.ok = false;
+    }
+    Ok(report)
+}
+
+/// Preflight the entries of a DAG under `config`
+pub fn preflight_dag(
+    dag: &ExecutionDAG,
+    job_id: &str,
+    config: &ExecutionConfig,
+) -> PreflightReport {
+    let policy = config.on_destination_exists.clone();
+    let mut overlay = Overlay::default();
+    let mut failed: HashSet<Uuid> = HashSet::new();
+    let mut entries = Vec::new();
+    let mut stops_at_level = None;
+    let mut required: BTreeMap<String, (Option<PathBuf>, u64)> = BTreeMap::new();
+
+    for (level, level_entries) in dag.get_levels().into_iter().enumerate() {
+        for entry in level_entries {
+            let blocker = entry.depends_on.iter().find(|dep| failed.contains(dep));
+            let prediction = match blocker {
+                Some(&by) => Prediction::new(PredictedOutcome::Blocked { by }),
+                None => overlay.predict(&entry.operation, &policy),
+            };
+
+            if matches!(
+                prediction.outcome,
+                PredictedOutcome::Fail { .. } | PredictedOutcome::Blocked { .. }
+            ) {
+                failed.insert(entry.id);
+            }
+            if matches!(prediction.outcome, PredictedOutcome::Fail { .. })
+                && stops_at_level.is_none()
+            {
+                stops_at_level = Some(level);
+            }
+            if let Some((ref destination, bytes)) = prediction.writes {
+                if let Some(id) = volume::volume_id(destination) {
+                    let slot = required
+                        .entry(id)
+                        .or_insert_with(|| (volume::mount_point(destination), 0));
+                    slot.1 += bytes;
+                }
+            }
+
+            entries.push(PreflightEntry {
+                id: entry.id,
+                sequence: entry.sequence,
+                level,
+                description: entry.operation.description(),
+                bytes_written: prediction.writes.as_ref().map(|(_, b)| *b).unwrap_or(0),
+                outcome: prediction.outcome,
+                issues: prediction.issues,
+            });
+        }
+    }
+
+    let volumes: Vec<VolumeSpace> = required
+        .into_iter()
+        .filter(|(_, (_, bytes))| *bytes > 0)
+        .map(|(volume, (mount_point, required_bytes))| {
+            let available_bytes = mount_point
+                .as_deref()
+                .and_then(|m| fs2::available_space(m).ok());
+            VolumeSpace {
+                sufficient: available_bytes.is_none_or(|free| required_bytes <= free),
+                volume,
+                mount_point,
+                required_bytes,
+                available_bytes,
+            }
+        })
+        .collect();
+
+    let count = |f: fn(&PredictedOutcome) -> bool| entries.iter().filter(|e| f(&e.outcome)).count();
+    let failed_count = count(|o| matches!(o, PredictedOutcome::Fail { .. }));
+    let report = PreflightReport {
+        job_id: job_id.to_string(),
+        policy,
+        total: entries.len(),
+        proceed_count: count(|o| matches!(o, PredictedOutcome::Proceed)),
+        renamed_count: count(|o| matches!(o, PredictedOutcome::Rename { .. })),
+        skipped_count: count(|o| matches!(o, PredictedOutcome::Skip { .. })),
+        failed_count,
+        blocked_count: count(|o| matches!(o, PredictedOutcome::Blocked { .. })),
+        stops_at_level,
+        refused: None,
+        ok: failed_count == 0 && volumes.iter().all(|v| v.sufficient),
+        entries,
+        volumes,
+    };
+
+    tracing::debug!(job_id = %job_id, summary = %report.summary(), "Preflight complete");
+    report
+}
+
+/// Outcome, issues and bytes written (with their destination) for one entry
+struct Prediction {
+    outcome: PredictedOutcome,
+    issues: Vec<PreflightIssue>,
+    writes: Option<(PathBuf, u64)>,
+}
+
+impl Prediction {
+    fn new(outcome: PredictedOutcome) -> Self {
+        Self {
+            outcome,
+            issues: Vec::new(),
+            writes: None,
+        }
+    }
+
+    fn fail(kind: PreflightIssueKind, reason: String) -> Self {
+        Self {
+            outcome: PredictedOutcome::Fail {
+                reason: reason.clone(),
+            },
+            issues: vec![PreflightIssue {
+                kind,
+                message: reason,
+            }],
+            writes: None,
+        }
+    }
+
+    fn goes_ahead(&self) -> bool {
+        matches!(
+            self.outcome,
+            PredictedOutcome::Proceed | PredictedOutcome::Rename { .. }
+        )
+    }
+
+    fn issue(mut self, kind: PreflightIssueKind, message: String) -> Self {
+        self.issues.push(PreflightIssue { kind, message });
+        self
+    }
+}
+
+/// Planned state of a path, overriding what is on disk
+#[derive(Debug, Clone)]
+enum Node {
+    /// Removed (moved away, deleted or quarantined)
+    Gone,
+    /// Created empty by the plan
+    New { dir: bool },
+    /// Holds what is on disk at another path (moved or copied there)
+    From(PathBuf),
+}
+
+/// Where the content at a path lives before execution
+#[derive(Debug, Clone)]
+enum Lookup {
+    Missing,
+    New { dir: bool },
+    Disk(PathBuf),
+}
+
+impl Lookup {
+    fn exists(&self) -> bool {
+        !matches!(self, Lookup::Missing)
+    }
+
+    fn is_dir(&self) -> bool {
+        match self {
+            Lookup::Missing => false,
+            Lookup::New { dir } => *dir,
+            Lookup::Disk(p) => p.is_dir(),
+        }
+    }
+
+    /// Node to place at a destination receiving this content
+    fn node(&self) -> Node {
+        match self {
+            Lookup::Missing => Node::Gone,
+            Lookup::New { dir } => Node::New { dir: *dir },
+            Lookup::Disk(p) => Node::From(p.clone()),
+        }
+    }
+}
+
+/// The filesystem as it will look after the entries predicted so far
+#[derive(Debug, Default)]
+struct Overlay {
+    nodes: HashMap<PathBuf, Node>,
+}
+
+impl Overlay {
+    fn lookup(&self, path: &Path) -> Lookup {
+        for ancestor in path.ancestors() {
+            let Some(node) = self.nodes.get(ancestor) else {
+                continue;
+            };
+            let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            let itself = rest.as_os_str().is_empty();
+            return match node {
+                Node::Gone => Lookup::Missing,
+                Node::New { dir } if itself => Lookup::New { dir: *dir },
+                // Inside a folder the plan creates, and not planned itself
+                Node::New { .. } => Lookup::Missing,
+                // (joining an empty path would add a trailing slash)
+                Node::From(origin) if itself => on_disk(origin),
+                Node::From(origin) => on_disk(&origin.join(rest)),
+            };
+        }
+        on_disk(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.lookup(path).exists()
+    }
+
+    /// Record a folder (and any missing parents) as created
+    fn create_dir(&mut self, path: &Path) {
+        for ancestor in path.ancestors() {
+            if self.exists(ancestor) {
+                break;
+            }
+            self.nodes
+                .insert(ancestor.to_path_buf(), Node::New { dir: true });
+        }
+    }
+
+    fn place(&mut self, path: &Path, node: Node) {
+        if let Some(parent) = path.parent() {
+            self.create_dir(parent);
+        }
+        self.nodes.insert(path.to_path_buf(), node);
+    }
+
+    fn relocate(&mut self, source: &Path, destination: &Path) {
+        let node = self.lookup(source).node();
+        self.place(destination, node);
+        self.nodes.insert(source.to_path_buf(), Node::Gone);
+    }
+
+    /// Check that `dir` (or the nearest ancestor that exists) can be written
+    fn writable(&self, dir: &Path) -> Result<(), String> {
+        for ancestor in dir.ancestors() {
+            match self.lookup(ancestor) {
+                Lookup::Missing => continue,
+                // Created by this plan, so ours to write into
+                Lookup::New { .. } => return Ok(()),
+                Lookup::Disk(p) => {
+                    return if can_write(&p) {
+                        Ok(())
+                    } else {
+                        Err(format!("No write permission for {}", p.display()))
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the conflict policy to a taken destination
+    fn conflict(&self, destination: &Path, policy: &ConflictPolicy, what: &str) -> Prediction {
+        let message = format!("{} exists: {}", what, destination.display());
+        let prediction = match policy {
+            ConflictPolicy::Skip => Prediction::new(PredictedOutcome::Skip {
+                reason: message.clone(),
+            }),
+            ConflictPolicy::AutoRename => Prediction::new(PredictedOutcome::Rename {
+                path: unique_path_with(destination, |p| self.exists(p)),
+            }),
+            ConflictPolicy::Fail => {
+                return Prediction::fail(
+                    PreflightIssueKind::DestinationExists,
+                    format!("{} already exists: {}", what, destination.display()),
+                )
+            }
+        };
+        prediction.issue(PreflightIssueKind::DestinationExists, message)
+    }
+
+    /// Predict one operation and apply it to the overlay when it goes ahead
+    fn predict(&mut self, operation: &WALOperationType, policy: &ConflictPolicy) -> Prediction {
+        let prediction = self.check(operation, policy);
+        let applied = match &prediction.outcome {
+            PredictedOutcome::Proceed => Some(operation.clone()),
+            PredictedOutcome::Rename { path } => renamed(operation, path),
+            _ => None,
+        };
+        if let Some(op) = applied {
+            self.apply(&op);
+        }
+        prediction
+    }
+
+    fn check(&self, operation: &WALOperationType, policy: &ConflictPolicy) -> Prediction {
+        match operation {
+            WALOperationType::CreateFolder { path } => {
+                if self.exists(path) {
+                    return Prediction::new(PredictedOutcome::Proceed);
+                }
+                self.write_into(path, Prediction::new(PredictedOutcome::Proceed))
+            }
+
+            WALOperationType::Move {
+                source,
+                destination,
+            }
+            | WALOperationType::Quarantine {
+                path: source,
+                quarantine_path: destination,
+            } => {
+                let from = self.lookup(source);
+                if !from.exists() {
+                    if self.exists(destination) {
+                        return Prediction::new(PredictedOutcome::Skip {
+                            reason: "Source missing but destination exists".to_string(),
+                        });
+                    }
+                    return missing(source, "Source not found");
+                }
+                let prediction = if self.exists(destination) {
+                    self.conflict(destination, policy, "Destination")
+                } else if PathValidator::is_protected_path(source) {
+                    return protected(source, "move");
+                } else {
+                    Prediction::new(PredictedOutcome::Proceed)
+                };
+                let prediction = self.remove_from(source, prediction);
+                let target = final_path(&prediction, destination);
+                let mut prediction = self.write_into(&target, prediction);
+
+                if let (true, Lookup::Disk(ref disk)) = (prediction.goes_ahead(), &from) {
+                    if volume::volume_id(disk) != volume::volume_id(&target) {
+                        prediction.writes = Some((target.clone(), disk_size(disk)));
+                        prediction = prediction.issue(
+                            PreflightIssueKind::CrossDevice,
+                            format!(
+                                "{} moves to another volume and will be copied, then deleted",
+                                source.display()
+                            ),
+                        );
+                    }
+                }
+                prediction
+            }
+
+            WALOperationType::Rename { path, new_name } => {
+                if !self.exists(path) {
+                    return missing(path, "Path not found");
+                }
+                let new_path = path.with_file_name(new_name);
+                let prediction = if self.exists(&new_path) {
+                    self.conflict(&new_path, policy, "Target")
+                } else if PathValidator::is_protected_path(path) {
+                    return protected(path, "rename");
+                } else {
+                    Prediction::new(PredictedOutcome::Proceed)
+                };
+                self.remove_from(path, prediction)
+            }
+
+            WALOperationType::Copy {
+                source,
+                destination,
+            } => {
+                let from = self.lookup(source);
+                if !from.exists() {
+                    return missing(source, "Source not found");
+                }
+                let prediction = if self.exists(destination) {
+                    self.conflict(destination, policy, "Destination")
+                } else {
+                    Prediction::new(PredictedOutcome::Proceed)
+                };
+                let target = final_path(&prediction, destination);
+                let mut prediction = self.write_into(&target, prediction);
+                if let (true, Lookup::Disk(ref disk)) = (prediction.goes_ahead(), &from) {
+                    prediction.writes = Some((target, disk_size(disk)));
+                }
+                prediction
+            }
+
+            WALOperationType::DeleteFolder { path } | WALOperationType::RemoveCopy { path, .. } => {
+                if !self.exists(path) {
+                    return Prediction::new(PredictedOutcome::Proceed);
+                }
+                if PathValidator::is_protected_path(path) {
+                    return protected(path, "delete");
+                }
+                self.remove_from(path, Prediction::new(PredictedOutcome::Proceed))
+            }
+
+            WALOperationType::DeleteFile {
+                path,
+                quarantine_path,
+            } => {
+                if !self.exists(path) {
+                    if self.exists(quarantine_path) {
+                        return Prediction::new(PredictedOutcome::Proceed);
+                    }
+                    return missing(path, "Path not found");
+                }
+                if PathValidator::is_protected_path(path) {
+                    return protected(path, "delete");
+                }
+                self.remove_from(path, Prediction::new(PredictedOutcome::Proceed))
+            }
+
+            WALOperationType::RestoreFile {
+                quarantine_path,
+                path,
+            } => {
+                if self.exists(path) && !self.exists(quarantine_path) {
+                    return Prediction::new(PredictedOutcome::Proceed);
+                }
+                if !self.exists(quarantine_path) {
+                    return missing(quarantine_path, "Quarantined item not found");
+                }
+                if self.exists(path) {
+                    return Prediction::fail(
+                        PreflightIssueKind::DestinationExists,
+                        format!("Restore target already exists: {}", path.display()),
+                    );
+                }
+                self.write_into(path, Prediction::new(PredictedOutcome::Proceed))
+            }
+
+            WALOperationType::CreateSymlink { path, target }
+            | WALOperationType::CreateAlias { path, target } => {
+                let same_link = matches!(
+                    self.lookup(path),
+                    Lookup::Disk(ref p) if fs::read_link(p).ok().as_ref() == Some(target)
+                );
+                let prediction = if self.exists(path) && !same_link {
+                    self.conflict(path, policy, "Link path")
+                } else {
+                    Prediction::new(PredictedOutcome::Proceed)
+                };
+                let link = final_path(&prediction, path);
+                self.write_into(&link, prediction)
+            }
+
+            WALOperationType::RemoveLink { path, .. } => {
+                self.remove_from(path, Prediction::new(PredictedOutcome::Proceed))
+            }
+
+            WALOperationType::SetTimestamps { path, .. }
+            | WALOperationType::SetPermissions { path, .. }
+            | WALOperationType::SetTags { path, .. }
+            | WALOperationType::SetXattr { path, .. } => {
+                if !self.exists(path) {
+                    return missing(path, "Path not found");
+                }
+                Prediction::new(PredictedOutcome::Proceed)
+            }
+        }
+    }
+
+    /// Record the effect of an operation that goes ahead
+    fn apply(&mut self, operation: &WALOperationType) {
+        match operation {
+            WALOperationType::CreateFolder { path } => self.create_dir(path),
+            WALOperationType::Move {
+                source,
+                destination,
+            }
+            | WALOperationType::Quarantine {
+                path: source,
+                quarantine_path: destination,
+            }
+            | WALOperationType::DeleteFile {
+                path: source,
+                quarantine_path: destination,
+            }
+            | WALOperationType::RestoreFile {
+                quarantine_path: source,
+                path: destination,
+            } => {
+                if self.exists(source) {
+                    self.relocate(source, destination);
+                }
+            }
+            WALOperationType::Rename { path, new_name } => {
+                self.relocate(path, &path.with_file_name(new_name));
+            }
+            WALOperationType::Copy {
+                source,
+                destination,
+            } => {
+                let node = self.lookup(source).node();
+                self.place(destination, node);
+            }
+            WALOperationType::DeleteFolder { path }
+            | WALOperationType::RemoveCopy { path, .. }
+            | WALOperationType::RemoveLink { path, .. } => {
+                self.nodes.insert(path.clone(), Node::Gone);
+            }
+            WALOperationType::CreateSymlink { path, .. }
+            | WALOperationType::CreateAlias { path, .. } => {
+                if !self.exists(path) {
+                    self.place(path, Node::New { dir: false });
+                }
+            }
+            WALOperationType::SetTimestamps { .. }
+            | WALOperationType::SetPermissions { .. }
+            | WALOperationType::SetTags { .. }
+            | WALOperationType::SetXattr { .. } => {}
+        }
+    }
+
+    /// Fail the prediction if the parent of `path` cannot be written
+    fn write_into(&self, path: &Path, prediction: Prediction) -> Prediction {
+        if !prediction.goes_ahead() {
+            return prediction;
+        }
+        match path.parent().map(|parent| self.writable(parent)) {
+            Some(Err(message)) => Prediction::fail(PreflightIssueKind::PermissionDenied, message),
+            _ => prediction,
+        }
+    }
+
+    /// Removing an entry from a folder needs write access to that folder too
+    fn remove_from(&self, path: &Path, prediction: Prediction) -> Prediction {
+        self.write_into(path, prediction)
+    }
+}
+
+/// The operation as it will actually run after an auto-rename to `path`
+fn renamed(operation: &WALOperationType, path: &Path) -> Option<WALOperationType> {
+    let path = path.to_path_buf();
+    Some(match operation.clone() {
+        WALOperationType::Move { source, .. } => WALOperationType::Move {
+            source,
+            destination: path,
+        },
+        WALOperationType::Quarantine { path: source, .. } => WALOperationType::Move {
+            source,
+            destination: path,
+        },
+        WALOperationType::Copy { source, .. } => WALOperationType::Copy {
+            source,
+            destination: path,
+        },
+        WALOperationType::Rename { path: original, .. } => WALOperationType::Move {
+            source: original,
+            destination: path,
+        },
+        WALOperationType::CreateSymlink { target, .. } => {
+            WALOperationType::CreateSymlink { path, target }
+        }
+        WALOperationType::CreateAlias { target, .. } => {
+            WALOperationType::CreateAlias { path, target }
+        }
+        _ => return None,
+    })
+}
+
+/// Where the operation writes once the conflict policy is applied
+fn final_path(prediction: &Prediction, planned: &Path) -> PathBuf {
+    match &prediction.outcome {
+        PredictedOutcome::Rename { path } => path.clone(),
+        _ => planned.to_path_buf(),
+    }
+}
+
+fn missing(path: &Path, what: &str) -> Prediction {
+    Prediction::fail(
+        PreflightIssueKind::MissingSource,
+        format!("{}: {}", what, path.display()),
+    )
+}
+
+fn protected(path: &Path, action: &str) -> Prediction {
+    Prediction::fail(
+        PreflightIssueKind::ProtectedPath,
+        format!("Cannot {} protected path: {}", action, path.display()),
+    )
+}
+
+fn on_disk(path: &Path) -> Lookup {
+    if fs::symlink_metadata(path).is_ok() {
+        Lookup::Disk(path.to_path_buf())
+    } else {
+        Lookup::Missing
+    }
+}
+
+/// Total size of a file or folder (symlinks are not followed)
+fn disk_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+#[cfg(unix)]
+fn can_write(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string for the duration of the call
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn can_write(dir: &Path) -> bool {
+    fs::metadata(dir)
+        .map(|m| !m.permissions().readonly())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn journal(root: &Path, operations: Vec<WALOperationType>) -> WALJournal {
+        let mut journal = WALJournal::new("preflight-test".to_string(), root.to_path_buf());
+        let mut previous: Option<Uuid> = None;
+        for op in operations {
+            let id = match previous {
+                Some(dep) => journal.add_operation_with_deps(op, vec![dep]).unwrap(),
+                None => journal.add_operation(op).unwrap(),
+            };
+            previous = Some(id);
+        }
+        journal
+    }
+
+    fn config(policy: ConflictPolicy) -> ExecutionConfig {
+        ExecutionConfig {
+            on_destination_exists: policy,
+        }
+    }
+
+    #[test]
+    fn test_preflight_follows_planned_moves() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.pdf"), b"pdf").unwrap();
+
+        let docs = root.join("Docs");
+        let ops = vec![
+            WALOperationType::CreateFolder { path: docs.clone() },
+            WALOperationType::Move {
+                source: root.join("a.pdf"),
+                destination: docs.join("a.pdf"),
+            },
+            WALOperationType::Rename {
+                path: docs.join("a.pdf"),
+                new_name: "b.pdf".to_string(),
+            },
+            WALOperationType::Copy {
+                source: docs.join("b.pdf"),
+                destination: root.join("b-copy.pdf"),
+            },
+        ];
+        let report = preflight_journal(&journal(root, ops), &config(ConflictPolicy::Fail)).unwrap();
+
+        assert!(report.ok, "{:?}", report.problems());
+        assert_eq!(report.proceed_count, 4);
+        assert_eq!(report.entries[3].bytes_written, 3);
+        assert_eq!(report.volumes.len(), 1);
+        assert_eq!(report.volumes[0].required_bytes, 3);
+        // Nothing was touched
+        assert!(root.join("a.pdf").exists());
+        assert!(!docs.exists());
+    }
+
+    #[test]
+    fn test_preflight_conflicts_and_missing_sources() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.txt"), b"a").unwrap();
+        fs::write(root.join("taken.txt"), b"b").unwrap();
+
+        let ops = || {
+            vec![
+                WALOperationType::Move {
+                    source: root.join("a.txt"),
+                    destination: root.join("taken.txt"),
+                },
+                WALOperationType::SetPermissions {
+                    path: root.join("taken.txt"),
+                    mode: 0o600,
+                    previous_mode: 0o644,
+                },
+            ]
+        };
+
+        let report =
+            preflight_journal(&journal(root, ops()), &config(ConflictPolicy::AutoRename)).unwrap();
+        assert!(report.ok);
+        assert_eq!(
+            report.entries[0].outcome,
+            PredictedOutcome::Rename {
+                path: root.join("taken_1.txt")
+            }
+        );
+
+        let report =
+            preflight_journal(&journal(root, ops()), &config(ConflictPolicy::Skip)).unwrap();
+        assert_eq!(report.skipped_count, 1);
+        assert_eq!(report.proceed_count, 1);
+
+        let report =
+            preflight_journal(&journal(root, ops()), &config(ConflictPolicy::Fail)).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.stops_at_level, Some(0));
+        assert_eq!(
+            report.entries[0].issues[0].kind,
+            PreflightIssueKind::DestinationExists
+        );
+        assert_eq!(
+            report.entries[1].outcome,
+            PredictedOutcome::Blocked {
+                by: report.entries[0].id
+            }
+        );
+
+        let gone = vec![WALOperationType::Move {
+            source: root.join("missing.txt"),
+            destination: root.join("x.txt"),
+        }];
+        let report =
+            preflight_journal(&journal(root, gone), &config(ConflictPolicy::Skip)).unwrap();
+        assert_eq!(report.failed_count, 1);
+        assert_eq!(
+            report.entries[0].issues[0].kind,
+            PreflightIssueKind::MissingSource
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preflight_reports_unwritable_folders() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let locked = root.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(root.join("a.txt"), b"a").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+        if can_write(&locked) {
+            // Running as root: permissions are not enforced
+            return;
+        }
+
+        let ops = vec![WALOperationType::Move {
+            source: root.join("a.txt"),
+            destination: locked.join("sub/a.txt"),
+        }];
+        let report = preflight_journal(&journal(root, ops), &config(ConflictPolicy::Fail)).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(report.failed_count, 1);
+        assert_eq!(
+            report.entries[0].issues[0].kind,
+            PreflightIssueKind::PermissionDenied
+        );
+    }
+}
@@ -0,0 +1,743 @@
+//! Plan edits
+//!
+//! A generated plan is rarely wanted whole. [`PlanEdit`]s drop operations or
+//! whole destination folders, retarget or merge planned folders, and point
+//! single operations somewhere else. The job keeps the plan as generated
+//! (`base_plan`) and the list of edits; its `plan` is always the base plan
+//! with the edits replayed, so edits survive a restart and can be undone.
+
+use super::{OrganizeOperation, OrganizePlan};
+use crate::ai::v2::corrections::Correction;
+use crate::vfs::{simulate_plan, ShadowVFS};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// One change to a plan, applied on top of the generated plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlanEdit {
+    /// Leave one operation out
+    Exclude {
+        #[serde(rename = "opId")]
+        op_id: String,
+    },
+    /// Bring back an excluded operation
+    Include {
+        #[serde(rename = "opId")]
+        op_id: String,
+    },
+    /// Leave out a destination folder, its subfolders and everything planned into them
+    ExcludeFolder { folder: String },
+    /// Plan a destination folder somewhere else; operations into it follow
+    RetargetFolder { from: String, to: String },
+    /// Send everything planned for `from` into `into` (a planned or existing folder) instead
+    MergeFolders { from: String, into: String },
+    /// Point one move or rename at a different path
+    SetDestination {
+        #[serde(rename = "opId")]
+        op_id: String,
+        destination: String,
+    },
+}
+
+/// A plan after replaying edits
+#[derive(Debug, Clone)]
+pub struct EditedPlan {
+    pub plan: OrganizePlan,
+    /// Operations left out, in plan order
+    pub excluded: Vec<String>,
+}
+
+/// Replay `edits` over `base`.
+///
+/// Folder edits rewrite the destinations of every operation (excluded ones
+/// too, so including them later lands them in the edited folder). Duplicate
+/// folder creations left by a merge are dropped, and so are folders that
+/// only existed for operations that are now excluded, and links and
+/// metadata changes on anything left out.
+pub fn apply_edits(base: &OrganizePlan, edits: &[PlanEdit]) -> Result<EditedPlan, String> {
+    let target = Path::new(&base.target_folder);
+    let mut operations = base.operations.clone();
+    let mut excluded: HashSet<String> = HashSet::new();
+
+    // Folders the generated plan moves files into (rather than empty scaffolding)
+    let holds_files: HashSet<String> = operations
+        .iter()
+        .filter(|op| op.op_type == "create_folder")
+        .filter(|op| {
+            op.path
+                .as_deref()
+                .is_some_and(|dir| moves_into(&operations, Path::new(dir)))
+        })
+        .map(|op| op.op_id.clone())
+        .collect();
+
+    for edit in edits {
+        match edit {
+            PlanEdit::Exclude { op_id } => {
+                find(&operations, op_id)?;
+                excluded.insert(op_id.clone());
+            }
+            PlanEdit::Include { op_id } => {
+                find(&operations, op_id)?;
+                excluded.remove(op_id);
+            }
+            PlanEdit::ExcludeFolder { folder } => {
+                let folder = Path::new(folder);
+                require_planned(&operations, folder)?;
+                for op in &operations {
+                    if target_of(op).is_some_and(|t| t.starts_with(folder)) {
+                        excluded.insert(op.op_id.clone());
+                    }
+                }
+            }
+            PlanEdit::RetargetFolder { from, to } => {
+                let (from, to) = (Path::new(from), Path::new(to));
+                require_planned(&operations, from)?;
+                check_inside(target, to)?;
+                if planned_into(&operations, to) {
+                    return Err(format!(
+                        "{} is already a planned folder; merge the folders instead",
+                        to.display()
+                    ));
+                }
+                rebase(&mut operations, from, to);
+            }
+            PlanEdit::MergeFolders { from, into } => {
+                let (from, into) = (Path::new(from), Path::new(into));
+                require_planned(&operations, from)?;
+                check_inside(target, into)?;
+                if into.starts_with(from) {
+                    return Err(format!(
+                        "Cannot merge {} into its own subfolder {}",
+                        from.display(),
+                        into.display()
+                    ));
+                }
+                if !planned_into(&operations, into) && !into.is_dir() {
+                    return Err(format!(
+                        "{} is neither a planned folder nor an existing one; retarget the folder instead",
+                        into.display()
+                    ));
+                }
+                operations.retain(|op| {
+                    op.op_type != "create_folder" || op.path.as_deref().map(Path::new) != Some(from)
+                });
+                rebase(&mut operations, from, into);
+            }
+            PlanEdit::SetDestination { op_id, destination } => {
+                check_inside(target, Path::new(destination))?;
+                let idx = find(&operations, op_id)?;
+                set_destination(&mut operations[idx], destination)?;
+            }
+        }
+    }
+
+    // A merge or retarget can leave two creations of the same folder
+    let mut created = HashSet::new();
+    operations.retain(|op| op.op_type != "create_folder" || created.insert(op.path.clone()));
+
+    // Folders that only held excluded operations are not created either
+    let kept_targets: Vec<PathBuf> = operations
+        .iter()
+        .filter(|op| op.op_type != "create_folder" && !excluded.contains(&op.op_id))
+        .filter_map(target_of)
+        .collect();
+    let emptied: Vec<String> = operations
+        .iter()
+        .filter(|op| holds_files.contains(&op.op_id) && !excluded.contains(&op.op_id))
+        .filter(|op| {
+            let dir = Path::new(op.path.as_deref().unwrap_or_default());
+            !kept_targets.iter().any(|t| t.starts_with(dir))
+        })
+        .map(|op| op.op_id.clone())
+        .collect();
+    excluded.extend(emptied);
+    exclude_dependents(&operations, &mut excluded);
+
+    let (kept, dropped): (Vec<_>, Vec<_>) = operations
+        .into_iter()
+        .partition(|op| !excluded.contains(&op.op_id));
+
+    Ok(EditedPlan {
+        plan: OrganizePlan {
+            operations: kept,
+            ..base.clone()
+        },
+        excluded: dropped.into_iter().map(|op| op.op_id).collect(),
+    })
+}
+
+/// Check an edited plan against a scanned folder: every operation is
+/// simulated on a copy of `vfs`, then the staged set is checked with
+/// [`ShadowVFS::validate_staged`]. Returns the problems found.
+pub fn validate_plan(vfs: &ShadowVFS, plan: &OrganizePlan) -> Vec<String> {
+    let mut scratch = vfs.clone();
+    scratch.clear_staged();
+
+    let mut errors = simulate_plan(&mut scratch, plan.to_simulated_operations())
+        .err()
+        .unwrap_or_default();
+    if let Err(staged) = scratch.validate_staged() {
+        for error in staged {
+            let error = error.to_string();
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+    }
+    errors
+}
+
+/// What the edits taught about where files belong: every move or rename
+/// of `base` that now ends in a different folder, and every move left out
+/// (the file stays where it was). Folders are relative to the target.
+pub fn corrections_from_edits(
+    base: &OrganizePlan,
+    edited: &OrganizePlan,
+    excluded: &[String],
+) -> Vec<Correction> {
+    let target = Path::new(&base.target_folder);
+    let relative = |path: &Path| -> Option<String> {
+        let folder = path.parent()?.strip_prefix(target).ok()?;
+        Some(folder.to_string_lossy().replace('\\', "/"))
+    };
+    let recorded_at = Utc::now();
+
+    let mut corrections = Vec::new();
+    for op in &base.operations {
+        let source = match op.op_type.as_str() {
+            "move" => op.source.as_deref(),
+            "rename" => op.path.as_deref(),
+            _ => None,
+        };
+        let (Some(source), Some(planned)) = (source.map(Path::new), target_of(op)) else {
+            continue;
+        };
+        let Some(planned_folder) = relative(&planned) else {
+            continue;
+        };
+
+        let (chosen_folder, now_at) = if excluded.contains(&op.op_id) {
+            if op.op_type != "move" {
+                continue;
+            }
+            (None, source.to_path_buf())
+        } else {
+            let Some(now_at) = edited
+                .operations
+                .iter()
+                .find(|edited_op| edited_op.op_id == op.op_id)
+                .and_then(target_of)
+            else {
+                continue;
+            };
+            match relative(&now_at) {
+                Some(folder) if folder != planned_folder => (Some(folder), now_at),
+                _ => continue,
+            }
+        };
+
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        corrections.push(Correction {
+            id: 0,
+            target_folder: base.target_folder.clone(),
+            file_name: file_name.to_string_lossy().to_string(),
+            extension: source.extension().map(|e| e.to_string_lossy().to_string()),
+            size: std::fs::metadata(&now_at).ok().map(|m| m.len()),
+            planned_folder,
+            chosen_folder,
+            embedding: None,
+            recorded_at,
+        });
+    }
+    corrections
+}
+
+fn find(operations: &[OrganizeOperation], op_id: &str) -> Result<usize, String> {
+    operations
+        .iter()
+        .position(|op| op.op_id == op_id)
+        .ok_or_else(|| format!("Operation not found in plan: {}", op_id))
+}
+
+/// Where an operation puts something: the created folder, the move
+/// destination or the renamed path
+pub(super) fn target_of(op: &OrganizeOperation) -> Option<PathBuf> {
+    match op.op_type.as_str() {
+        "create_folder" => op.path.as_ref().map(PathBuf::from),
+        "move" => op.destination.as_ref().map(PathBuf::from),
+        "rename" => {
+            let path = Path::new(op.path.as_ref()?);
+            Some(path.parent()?.join(op.new_name.as_ref()?))
+        }
+        _ => None,
+    }
+}
+
+/// Operations on a path another operation may create
+const DEPENDENT_TYPES: &[&str] = &[
+    "symlink",
+    "alias",
+    "set_timestamps",
+    "set_permissions",
+    "set_tags",
+    "set_xattr",
+];
+
+/// Leave out links and metadata changes on what excluded operations would
+/// have created (a link counts as created at its own path, so a change to an
+/// excluded link goes too)
+fn exclude_dependents(operations: &[OrganizeOperation], excluded: &mut HashSet<String>) {
+    loop {
+        let gone: Vec<PathBuf> = operations
+            .iter()
+            .filter(|op| excluded.contains(&op.op_id))
+            .filter_map(|op| match op.op_type.as_str() {
+                "symlink" | "alias" => op.path.as_ref().map(PathBuf::from),
+                _ => target_of(op),
+            })
+            .collect();
+        let on_gone = |path: &Option<String>| {
+            path.as_deref()
+                .is_some_and(|p| gone.iter().any(|g| Path::new(p).starts_with(g)))
+        };
+        let dependents: Vec<String> = operations
+            .iter()
+            .filter(|op| !excluded.contains(&op.op_id) && DEPENDENT_TYPES.contains(&op.op_type.as_str()))
+            .filter(|op| on_gone(&op.path) || on_gone(&op.attributes.target))
+            .map(|op| op.op_id.clone())
+            .collect();
+        if dependents.is_empty() {
+            return;
+        }
+        excluded.extend(dependents);
+    }
+}
+
+/// Whether any create_folder, move or rename targets `folder` or below it
+fn planned_into(operations: &[OrganizeOperation], folder: &Path) -> bool {
+    operations
+        .iter()
+        .filter_map(target_of)
+        .any(|t| t.starts_with(folder))
+}
+
+/// Whether any move or rename targets something below `folder`
+pub(super) fn moves_into(operations: &[OrganizeOperation], folder: &Path) -> bool {
+    operations
+        .iter()
+        .filter(|op| op.op_type != "create_folder")
+        .filter_map(target_of)
+        .any(|t| t.starts_with(folder))
+}
+
+fn require_planned(operations: &[OrganizeOperation], folder: &Path) -> Result<(), String> {
+    if planned_into(operations, folder) {
+        Ok(())
+    } else {
+        Err(format!("No planned operations go to {}", folder.display()))
+    }
+}
+
+fn check_inside(target: &Path, path: &Path) -> Result<(), String> {
+    if path.starts_with(target)
+        && path != target
+        && !path.components().any(|c| c == Component::ParentDir)
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not inside the plan folder {}",
+            path.display(),
+            target.display()
+        ))
+    }
+}
+
+/// Rewrite destinations under `from` to the same place under `to`
+fn rebase(operations: &mut [OrganizeOperation], from: &Path, to: &Path) {
+    let moved = |path: &str| -> Option<String> {
+        let rest = Path::new(path).strip_prefix(from).ok()?;
+        Some(to.join(rest).to_string_lossy().to_string())
+    };
+
+    // Links and metadata changes follow the planned paths they refer to
+    let planned: Vec<String> = operations
+        .iter()
+        .filter_map(target_of)
+        .map(|t| t.to_string_lossy().to_string())
+        .collect();
+    let follow = |path: &mut Option<String>| {
+        if let Some(new_path) = path
+            .as_deref()
+            .filter(|p| planned.iter().any(|t| t == p))
+            .and_then(moved)
+        {
+            *path = Some(new_path);
+        }
+    };
+
+    for op in operations.iter_mut() {
+        match op.op_type.as_str() {
+            op_type if DEPENDENT_TYPES.contains(&op_type) => {
+                follow(&mut op.path);
+                follow(&mut op.attributes.target);
+            }
+            "create_folder" => {
+                if let Some(path) = op.path.as_deref().and_then(moved) {
+                    op.path = Some(path);
+                }
+            }
+            "move" => {
+                if let Some(destination) = op.destination.as_deref().and_then(moved) {
+                    op.destination = Some(destination);
+                }
+            }
+            "rename" => {
+                let renamed = target_of(op).and_then(|t| moved(&t.to_string_lossy()));
+                if let Some(destination) = renamed {
+                    set_destination(op, &destination).ok();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Point a move or rename at `destination`; a rename into another folder becomes a move
+fn set_destination(op: &mut OrganizeOperation, destination: &str) -> Result<(), String> {
+    let new_path = Path::new(destination);
+    let name = new_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid destination: {}", destination))?;
+
+    match op.op_type.as_str() {
+        "move" => op.destination = Some(destination.to_string()),
+        "rename" => {
+            let path = op
+                .path
+                .clone()
+                .ok_or_else(|| format!("Operation '{}' (rename) has no path", op.op_id))?;
+            if Path::new(&path).parent() == new_path.parent() {
+                op.new_name = Some(name);
+            } else {
+                op.op_type = "move".to_string();
+                op.source = Some(path);
+                op.destination = Some(destination.to_string());
+                op.path = None;
+                op.new_name = None;
+            }
+        }
+        other => {
+            return Err(format!(
+                "Operation '{}' ({}) has no destination to change",
+                op.op_id, other
+            ))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::FileNode;
+
+    fn op(op_id: &str, op_type: &str, a: &str, b: Option<&str>) -> OrganizeOperation {
+        let mut op = OrganizeOperation {
+            op_id: op_id.to_string(),
+            op_type: op_type.to_string(),
+            source: None,
+            destination: None,
+            path: None,
+            new_name: None,
+            attributes: Default::default(),
+        };
+        match op_type {
+            "move" => {
+                op.source = Some(a.to_string());
+                op.destination = b.map(String::from);
+            }
+            "rename" => {
+                op.path = Some(a.to_string());
+                op.new_name = b.map(String::from);
+            }
+            _ => op.path = Some(a.to_string()),
+        }
+        op
+    }
+
+    fn plan() -> OrganizePlan {
+        OrganizePlan {
+            plan_id: "plan-1".to_string(),
+            description: "Sort".to_string(),
+            operations: vec![
+                op("f1", "create_folder", "/data/Docs", None),
+                op("f2", "create_folder", "/data/Docs/PDF", None),
+                op("f3", "create_folder", "/data/Papers", None),
+                op("m1", "move", "/data/a.pdf", Some("/data/Docs/PDF/a.pdf")),
+                op("m2", "move", "/data/b.txt", Some("/data/Docs/b.txt")),
+                op("m3", "move", "/data/c.pdf", Some("/data/Papers/c.pdf")),
+                op("r1", "rename", "/data/scan.jpg", Some("receipt.jpg")),
+            ],
+            target_folder: "/data".to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
+    fn ids(plan: &OrganizePlan) -> Vec<&str> {
+        plan.operations.iter().map(|op| op.op_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_exclude_and_include() {
+        let base = plan();
+        let edited = apply_edits(
+            &base,
+            &[
+                PlanEdit::ExcludeFolder {
+                    folder: "/data/Docs".to_string(),
+                },
+                PlanEdit::Exclude {
+                    op_id: "m3".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        // Papers only held m3, so it is dropped too
+        assert_eq!(ids(&edited.plan), vec!["r1"]);
+        assert_eq!(edited.excluded, vec!["f1", "f2", "f3", "m1", "m2", "m3"]);
+
+        let edited = apply_edits(
+            &base,
+            &[
+                PlanEdit::ExcludeFolder {
+                    folder: "/data/Docs".to_string(),
+                },
+                PlanEdit::Include {
+                    op_id: "m2".to_string(),
+                },
+                PlanEdit::Include {
+                    op_id: "f1".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(ids(&edited.plan), vec!["f1", "f3", "m2", "m3", "r1"]);
+
+        assert!(apply_edits(
+            &base,
+            &[PlanEdit::Exclude {
+                op_id: "nope".to_string()
+            }]
+        )
+        .is_err());
+        assert!(apply_edits(
+            &base,
+            &[PlanEdit::ExcludeFolder {
+                folder: "/data/Music".to_string()
+            }]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_exclusion_cascades_to_links_and_metadata() {
+        let mut base = plan();
+        base.operations
+            .push(op("t1", "set_tags", "/data/Docs/PDF/a.pdf", None));
+        base.operations
+            .push(op("t2", "set_tags", "/data/old.txt", None));
+        let mut link = op("l1", "symlink", "/data/Latest.pdf", None);
+        link.attributes.target = Some("/data/Papers/c.pdf".to_string());
+        base.operations.push(link);
+        base.operations
+            .push(op("t3", "set_timestamps", "/data/Latest.pdf", None));
+
+        let edited = apply_edits(
+            &base,
+            &[PlanEdit::Exclude {
+                op_id: "m1".to_string(),
+            }],
+        )
+        .unwrap();
+        assert!(edited.excluded.contains(&"t1".to_string()));
+        assert!(ids(&edited.plan).contains(&"t2"));
+
+        // Excluding the folder drops the link into it, and changes to the link
+        let edited = apply_edits(
+            &base,
+            &[PlanEdit::ExcludeFolder {
+                folder: "/data/Papers".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(edited.excluded, vec!["f3", "m3", "l1", "t3"]);
+    }
+
+    #[test]
+    fn test_retarget_and_merge() {
+        let base = plan();
+        let edited = apply_edits(
+            &base,
+            &[PlanEdit::RetargetFolder {
+                from: "/data/Docs".to_string(),
+                to: "/data/Documents".to_string(),
+            }],
+        )
+        .unwrap();
+        let ops = &edited.plan.operations;
+        assert_eq!(ops[1].path.as_deref(), Some("/data/Documents/PDF"));
+        assert_eq!(
+            ops[3].destination.as_deref(),
+            Some("/data/Documents/PDF/a.pdf")
+        );
+        assert_eq!(ops[4].destination.as_deref(), Some("/data/Documents/b.txt"));
+
+        // Metadata on a planned file follows it; existing files stay put
+        let mut tagged = plan();
+        tagged
+            .operations
+            .push(op("t1", "set_tags", "/data/Docs/b.txt", None));
+        tagged
+            .operations
+            .push(op("t2", "set_tags", "/data/Docs/old.txt", None));
+        let edited = apply_edits(
+            &tagged,
+            &[PlanEdit::RetargetFolder {
+                from: "/data/Docs".to_string(),
+                to: "/data/Documents".to_string(),
+            }],
+        )
+        .unwrap();
+        let ops = &edited.plan.operations;
+        assert_eq!(ops[7].path.as_deref(), Some("/data/Documents/b.txt"));
+        assert_eq!(ops[8].path.as_deref(), Some("/data/Docs/old.txt"));
+
+        let retarget_onto_planned = PlanEdit::RetargetFolder {
+            from: "/data/Docs".to_string(),
+            to: "/data/Papers".to_string(),
+        };
+        assert!(apply_edits(&base, &[retarget_onto_planned]).is_err());
+
+        let edited = apply_edits(
+            &base,
+            &[PlanEdit::MergeFolders {
+                from: "/data/Papers".to_string(),
+                into: "/data/Docs/PDF".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(ids(&edited.plan), vec!["f1", "f2", "m1", "m2", "m3", "r1"]);
+        assert_eq!(
+            edited.plan.operations[4].destination.as_deref(),
+            Some("/data/Docs/PDF/c.pdf")
+        );
+
+        let outside = PlanEdit::MergeFolders {
+            from: "/data/Papers".to_string(),
+            into: "/etc".to_string(),
+        };
+        assert!(apply_edits(&base, &[outside]).is_err());
+
+        let nowhere = PlanEdit::MergeFolders {
+            from: "/data/Papers".to_string(),
+            into: "/data/Nowhere".to_string(),
+        };
+        assert!(apply_edits(&base, &[nowhere]).is_err());
+    }
+
+    #[test]
+    fn test_set_destination_and_validate() {
+        let base = plan();
+        let edited = apply_edits(
+            &base,
+            &[
+                PlanEdit::SetDestination {
+                    op_id: "r1".to_string(),
+                    destination: "/data/Docs/receipt.jpg".to_string(),
+                },
+                PlanEdit::SetDestination {
+                    op_id: "m1".to_string(),
+                    destination: "/data/Docs/b.txt".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        let r1 = edited
+            .plan
+            .operations
+            .iter()
+            .find(|op| op.op_id == "r1")
+            .unwrap();
+        assert_eq!(r1.op_type, "move");
+        assert_eq!(r1.source.as_deref(), Some("/data/scan.jpg"));
+        // Docs/PDF lost its only move
+        assert!(edited.excluded.contains(&"f2".to_string()));
+
+        let mut vfs = ShadowVFS::new(PathBuf::from("/data"));
+        for name in ["a.pdf", "b.txt", "c.pdf", "scan.jpg"] {
+            let path = PathBuf::from("/data").join(name);
+            let mut node = FileNode::file(path.clone());
+            node.parent = Some(PathBuf::from("/data"));
+            vfs.get_mut(&PathBuf::from("/data"))
+                .unwrap()
+                .add_child(path);
+            vfs.insert(node);
+        }
+        assert!(validate_plan(&vfs, &base).is_empty());
+        // a.pdf and b.txt now both go to Docs/b.txt
+        assert!(!validate_plan(&vfs, &edited.plan).is_empty());
+        assert!(vfs.staged_moves().is_empty());
+    }
+
+    #[test]
+    fn test_corrections_from_edits() {
+        let base = plan();
+        let edited = apply_edits(
+            &base,
+            &[
+                PlanEdit::SetDestination {
+                    op_id: "m1".to_string(),
+                    destination: "/data/Papers/a.pdf".to_string(),
+                },
+                PlanEdit::Exclude {
+                    op_id: "m2".to_string(),
+                },
+                PlanEdit::SetDestination {
+                    op_id: "r1".to_string(),
+                    destination: "/data/Docs/receipt.jpg".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+
+        let corrections = corrections_from_edits(&base, &edited.plan, &edited.excluded);
+        let summary: Vec<(&str, &str, Option<&str>)> = corrections
+            .iter()
+            .map(|c| (c.file_name.as_str(), c.planned_folder.as_str(), c.chosen_folder.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.pdf", "Docs/PDF", Some("Papers")),
+                ("b.txt", "Docs", None),
+                ("scan.jpg", "", Some("Docs")),
+            ]
+        );
+        assert_eq!(corrections[0].extension.as_deref(), Some("pdf"));
+        assert!(corrections.iter().all(|c| c.target_folder == "/data"));
+
+        // An unedited plan teaches nothing
+        assert!(corrections_from_edits(&base, &base, &[]).is_empty());
+    }
+}
@@ -1,6 +1,22 @@
+mod edits;
+mod rebase;
+
+pub use edits::*;
+pub use rebase::*;
+
+use crate::ai::v2::builder::MatchExplanation;
+use crate::ai::v2::corrections::Correction;
+use crate::ai::v2::vfs::OrganizationRule;
+use crate::quarantine::QuarantineManager;
+use crate::security::PathValidator;
+use crate::vfs::SimulatedOperation;
+use crate::wal::entry::{WALJournal, WALOperationType};
+use crate::wal::ops;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Status of an organize job
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -27,6 +43,33 @@ pub struct OrganizeOperation {
     pub destination: Option<String>,
     pub path: Option<String>,
     pub new_name: Option<String>,
+    /// Values for the link, timestamp, permission, tag and xattr operations
+    #[serde(flatten)]
+    pub attributes: OperationAttributes,
+}
+
+/// Extra fields used by some operation types. For `symlink`/`alias` the
+/// link is created at `path` pointing to `target`; `set_*` operations
+/// change `path`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationAttributes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessed: Option<DateTime<Utc>>,
+    /// Octal permission bits, e.g. "644"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xattr_name: Option<String>,
+    /// New attribute value (UTF-8); omitted removes the attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xattr_value: Option<String>,
 }
 
 /// The full organize plan
@@ -37,6 +80,275 @@ pub struct OrganizePlan {
     pub description: String,
     pub operations: Vec<OrganizeOperation>,
     pub target_folder: String,
+    /// Other folders the plan gathers files from into the target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_roots: Vec<String>,
+    /// Rules the plan was generated from, applied again to files added
+    /// before it runs (see [`rebase_plan`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<OrganizationRule>,
+    /// The folder as it was when the plan was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<PlanBaseline>,
+    /// Why the Builder sent each file where it did, by move operation id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub explanations: BTreeMap<String, MatchExplanation>,
+}
+
+impl OrganizePlan {
+    /// Every folder the plan works in, the target first
+    pub fn roots(&self) -> Vec<PathBuf> {
+        std::iter::once(&self.target_folder)
+            .chain(&self.source_roots)
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Convert the plan to a WAL journal (job ID = plan ID).
+    ///
+    /// Each operation depends on the latest earlier operation that touched
+    /// one of its paths or created the folder it works in, so the DAG
+    /// executor runs moves after their destination folder exists and
+    /// metadata changes after the move that brings the file in place.
+    pub fn to_wal_journal(&self) -> Result<WALJournal, String> {
+        let target_folder = PathBuf::from(&self.target_folder);
+        let mut journal = WALJournal::new(self.plan_id.clone(), target_folder);
+        journal.source_roots = self.source_roots.iter().map(PathBuf::from).collect();
+        let mut context = JournalContext::default();
+
+        // Latest entry touching each path
+        let mut last_touch: HashMap<PathBuf, uuid::Uuid> = HashMap::new();
+
+        for op in &self.operations {
+            let wal_op = op.wal_operation(&mut context)?;
+            let paths = wal_op.paths();
+
+            let mut depends_on = Vec::new();
+            for path in &paths {
+                for dep in [Some(path.as_path()), path.parent()].into_iter().flatten() {
+                    if let Some(&id) = last_touch.get(dep) {
+                        if !depends_on.contains(&id) {
+                            depends_on.push(id);
+                        }
+                    }
+                }
+            }
+            context.track(&wal_op);
+
+            let entry_id = if depends_on.is_empty() {
+                journal.add_operation(wal_op)
+            } else {
+                journal.add_operation_with_deps(wal_op, depends_on)
+            }
+            .map_err(|e| format!("Failed to add operation: {}", e))?;
+
+            for path in paths {
+                last_touch.insert(path, entry_id);
+            }
+        }
+
+        Ok(journal)
+    }
+
+    /// Operations to validate against the shadow VFS (`vfs::simulate_plan`).
+    /// Renames become moves within the same directory; trash, quarantine and
+    /// delete are simulated as deletes. Links and metadata changes don't
+    /// change the layout and, like malformed operations, are skipped.
+    pub fn to_simulated_operations(&self) -> Vec<SimulatedOperation> {
+        self.operations
+            .iter()
+            .filter_map(|op| match op.op_type.as_str() {
+                "move" => Some(SimulatedOperation::move_op(
+                    op.source.as_ref()?,
+                    op.destination.as_ref()?,
+                )),
+                "create_folder" => Some(SimulatedOperation::create_folder(op.path.as_ref()?)),
+                "delete" | "trash" | "quarantine" => Some(SimulatedOperation::delete(
+                    op.path.as_ref().or(op.source.as_ref())?,
+                )),
+                "rename" => {
+                    // Rename is a move to the same directory with a new name
+                    let path = op.path.as_ref()?;
+                    let new_name = op.new_name.as_ref()?;
+                    let new_path = PathBuf::from(path)
+                        .parent()
+                        .map(|p| p.join(new_name))
+                        .unwrap_or_else(|| PathBuf::from(new_name));
+                    Some(SimulatedOperation::move_op(
+                        path,
+                        new_path.to_string_lossy().to_string(),
+                    ))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// State carried across a plan's operations while it is journaled
+#[derive(Default)]
+struct JournalContext {
+    /// Where a path planned by an earlier move or rename is now, so the
+    /// `set_*` operations record the metadata the file has before the plan
+    origins: HashMap<PathBuf, PathBuf>,
+    /// Quarantine paths handed out to deletes in this plan
+    reserved: HashSet<PathBuf>,
+    quarantine: Option<QuarantineManager>,
+}
+
+impl JournalContext {
+    fn track(&mut self, operation: &WALOperationType) {
+        let (from, to) = match operation {
+            WALOperationType::Move {
+                source,
+                destination,
+            } => (source.clone(), destination.clone()),
+            WALOperationType::Rename { path, new_name } => {
+                (path.clone(), path.with_file_name(new_name))
+            }
+            _ => return,
+        };
+        let origin = self.origins.remove(&from).unwrap_or(from);
+        self.origins.insert(to, origin);
+    }
+
+    fn current(&self, path: &Path) -> PathBuf {
+        self.origins
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Quarantine path for deleting `path`, unique within the plan
+    fn reserve_quarantine(&mut self, path: &Path) -> Result<PathBuf, String> {
+        if self.quarantine.is_none() {
+            self.quarantine = Some(QuarantineManager::new()?);
+        }
+        let quarantine = self.quarantine.as_ref().expect("quarantine initialized");
+        quarantine.reserve_path(path, &mut self.reserved)
+    }
+}
+
+impl OrganizeOperation {
+    /// Convert to the WAL operation that executes it.
+    ///
+    /// `set_*` operations record the current value of what they change (for
+    /// undo) and `delete`/`trash` reserve a quarantine path, so this reads the disk.
+    pub fn to_wal_operation(&self) -> Result<WALOperationType, String> {
+        self.wal_operation(&mut JournalContext::default())
+    }
+
+    fn wal_operation(&self, context: &mut JournalContext) -> Result<WALOperationType, String> {
+        let required = |value: &Option<String>, field: &str| {
+            value.clone().ok_or_else(|| {
+                format!(
+                    "Operation '{}' ({}) missing required field '{}'",
+                    self.op_id, self.op_type, field
+                )
+            })
+        };
+        let attributes = &self.attributes;
+
+        match self.op_type.as_str() {
+            "create_folder" => Ok(WALOperationType::CreateFolder {
+                path: PathBuf::from(required(&self.path, "path")?),
+            }),
+            "move" => Ok(WALOperationType::Move {
+                source: PathBuf::from(required(&self.source, "source")?),
+                destination: PathBuf::from(required(&self.destination, "destination")?),
+            }),
+            "rename" => Ok(WALOperationType::Rename {
+                path: PathBuf::from(required(&self.path, "path")?),
+                new_name: required(&self.new_name, "newName")?,
+            }),
+            // Trashed items go to their volume's quarantine area like deletes,
+            // so they're indexed, quota-managed and restorable on undo
+            "trash" | "quarantine" | "delete" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                let quarantine_path = context.reserve_quarantine(&context.current(&path))?;
+                Ok(WALOperationType::DeleteFile {
+                    path,
+                    quarantine_path,
+                })
+            }
+            "symlink" | "alias" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                let target = PathBuf::from(required(&attributes.target, "target")?);
+                // The target may be planned by an earlier move; check where it is now
+                PathValidator::validate_for_read(&context.current(&target), None).map_err(|e| {
+                    format!("Operation '{}' ({}) has an invalid target: {}", self.op_id, self.op_type, e)
+                })?;
+                Ok(if self.op_type == "alias" {
+                    WALOperationType::CreateAlias { path, target }
+                } else {
+                    WALOperationType::CreateSymlink { path, target }
+                })
+            }
+            "set_timestamps" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                if attributes.modified.is_none() && attributes.accessed.is_none() {
+                    return Err(format!(
+                        "Operation '{}' (set_timestamps) needs 'modified' or 'accessed'",
+                        self.op_id
+                    ));
+                }
+                let (modified, accessed) = ops::file_times(&context.current(&path))?;
+                Ok(WALOperationType::SetTimestamps {
+                    path,
+                    modified: attributes.modified,
+                    accessed: attributes.accessed,
+                    previous_modified: attributes.modified.and(Some(modified)),
+                    previous_accessed: attributes.accessed.and(Some(accessed)),
+                })
+            }
+            "set_permissions" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                let mode = required(&attributes.mode, "mode")?;
+                let mode = u32::from_str_radix(&mode, 8)
+                    .ok()
+                    .filter(|m| *m <= 0o7777)
+                    .ok_or_else(|| {
+                        format!(
+                            "Operation '{}' has an invalid mode '{}' (expected octal, e.g. 644)",
+                            self.op_id, mode
+                        )
+                    })?;
+                Ok(WALOperationType::SetPermissions {
+                    previous_mode: ops::file_mode(&context.current(&path))?,
+                    path,
+                    mode,
+                })
+            }
+            "set_tags" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                let tags = attributes.tags.clone().ok_or_else(|| {
+                    format!(
+                        "Operation '{}' (set_tags) missing required field 'tags'",
+                        self.op_id
+                    )
+                })?;
+                Ok(WALOperationType::SetTags {
+                    previous_tags: ops::read_tags(&context.current(&path))?,
+                    path,
+                    tags,
+                })
+            }
+            "set_xattr" => {
+                let path = PathBuf::from(required(&self.path, "path")?);
+                let name = required(&attributes.xattr_name, "xattrName")?;
+                Ok(WALOperationType::SetXattr {
+                    previous_value: ops::read_xattr(&context.current(&path), &name)?,
+                    value: attributes.xattr_value.clone().map(String::into_bytes),
+                    path,
+                    name,
+                })
+            }
+            unknown_type => Err(format!(
+                "Operation '{}' has unknown type '{}'",
+                self.op_id, unknown_type
+            )),
+        }
+    }
 }
 
 /// Persistent state for an organize job
@@ -65,6 +377,18 @@ pub struct OrganizeJob {
     pub error: Option<String>,
     /// Total operations count
     pub total_ops: usize,
+    /// The plan as generated, before `plan_edits` (None until a plan is set)
+    #[serde(default)]
+    pub base_plan: Option<OrganizePlan>,
+    /// Edits replayed over `base_plan` to produce `plan`
+    #[serde(default)]
+    pub plan_edits: Vec<PlanEdit>,
+    /// Operations of `base_plan` the edits leave out
+    #[serde(default)]
+    pub excluded_ops: Vec<String>,
+    /// Problems found when the edited plan was last validated
+    #[serde(default)]
+    pub plan_errors: Vec<String>,
 }
 
 impl OrganizeJob {
@@ -93,14 +417,70 @@ impl OrganizeJob {
             last_updated_at: now,
             error: None,
             total_ops: 0,
+            base_plan: None,
+            plan_edits: Vec::new(),
+            excluded_ops: Vec::new(),
+            plan_errors: Vec::new(),
         }
     }
 
-    /// Update the job with a plan
+    /// Update the job with a plan (discards edits of the previous plan)
     pub fn set_plan(&mut self, plan: OrganizePlan) {
         self.total_ops = plan.operations.len();
+        self.base_plan = Some(plan.clone());
         self.plan = Some(plan);
+        self.plan_edits.clear();
+        self.excluded_ops.clear();
+        self.plan_errors.clear();
+        self.update_timestamp();
+    }
+
+    /// Apply an edit to the plan. A rejected edit leaves the job unchanged.
+    pub fn edit_plan(&mut self, edit: PlanEdit) -> Result<(), String> {
+        let mut edits = self.plan_edits.clone();
+        edits.push(edit);
+        self.replay_edits(edits)
+    }
+
+    /// Drop the last `count` edits (all of them when `None`)
+    pub fn revert_plan_edits(&mut self, count: Option<usize>) -> Result<(), String> {
+        let mut edits = self.plan_edits.clone();
+        let keep = count.map_or(0, |n| edits.len().saturating_sub(n));
+        edits.truncate(keep);
+        self.replay_edits(edits)
+    }
+
+    fn replay_edits(&mut self, edits: Vec<PlanEdit>) -> Result<(), String> {
+        if self.status != JobStatus::Running || !self.completed_ops.is_empty() {
+            return Err("The plan can only be edited before execution starts".to_string());
+        }
+        // Jobs saved before edits existed have no base plan yet
+        let base = self
+            .base_plan
+            .clone()
+            .or_else(|| self.plan.clone())
+            .ok_or("Job has no plan to edit")?;
+
+        let edited = apply_edits(&base, &edits)?;
+        self.total_ops = edited.plan.operations.len();
+        self.plan = Some(edited.plan);
+        self.base_plan = Some(base);
+        self.plan_edits = edits;
+        self.excluded_ops = edited.excluded;
+        self.plan_errors.clear();
         self.update_timestamp();
+        Ok(())
+    }
+
+    /// Corrections the plan edits made to the generated plan (see
+    /// [`corrections_from_edits`])
+    pub fn corrections(&self) -> Vec<Correction> {
+        match (&self.base_plan, &self.plan) {
+            (Some(base), Some(plan)) if !self.plan_edits.is_empty() => {
+                corrections_from_edits(base, plan, &self.excluded_ops)
+            }
+            _ => Vec::new(),
+        }
     }
 
     /// Mark an operation as completed
@@ -223,3 +603,188 @@ impl JobManager {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(op_id: &str, op_type: &str) -> OrganizeOperation {
+        OrganizeOperation {
+            op_id: op_id.to_string(),
+            op_type: op_type.to_string(),
+            source: None,
+            destination: None,
+            path: None,
+            new_name: None,
+            attributes: Default::default(),
+        }
+    }
+
+    fn sample_plan() -> OrganizePlan {
+        OrganizePlan {
+            plan_id: "plan-1".to_string(),
+            description: "test".to_string(),
+            operations: vec![
+                OrganizeOperation {
+                    path: Some("/data/Docs".to_string()),
+                    ..operation("op-1", "create_folder")
+                },
+                OrganizeOperation {
+                    source: Some("/data/a.pdf".to_string()),
+                    destination: Some("/data/Docs/a.pdf".to_string()),
+                    ..operation("op-2", "move")
+                },
+                OrganizeOperation {
+                    path: Some("/data/b.txt".to_string()),
+                    new_name: Some("c.txt".to_string()),
+                    ..operation("op-3", "rename")
+                },
+            ],
+            target_folder: "/data".to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_to_wal_journal_links_moves_to_folders() {
+        let journal = sample_plan().to_wal_journal().unwrap();
+        assert_eq!(journal.job_id, "plan-1");
+        assert_eq!(journal.entries.len(), 3);
+        assert_eq!(journal.entries[1].depends_on, vec![journal.entries[0].id]);
+        assert!(journal.entries[2].depends_on.is_empty());
+    }
+
+    #[test]
+    fn test_to_wal_journal_orders_metadata_and_links_after_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |p: &str| dir.path().join(p).to_string_lossy().to_string();
+        fs::write(dir.path().join("a.sh"), "echo").unwrap();
+        let before = ops::file_times(&dir.path().join("a.sh")).unwrap().0;
+        let backdated: DateTime<Utc> = "2020-01-01T00:00:00Z".parse().unwrap();
+
+        let plan = OrganizePlan {
+            plan_id: "plan-2".to_string(),
+            description: "test".to_string(),
+            operations: vec![
+                OrganizeOperation {
+                    path: Some(at("Scripts")),
+                    ..operation("op-1", "create_folder")
+                },
+                OrganizeOperation {
+                    source: Some(at("a.sh")),
+                    destination: Some(at("Scripts/a.sh")),
+                    ..operation("op-2", "move")
+                },
+                OrganizeOperation {
+                    path: Some(at("Scripts/a.sh")),
+                    attributes: OperationAttributes {
+                        modified: Some(backdated),
+                        ..Default::default()
+                    },
+                    ..operation("op-3", "set_timestamps")
+                },
+                OrganizeOperation {
+                    path: Some(at("a.sh")),
+                    attributes: OperationAttributes {
+                        target: Some(at("Scripts/a.sh")),
+                        ..Default::default()
+                    },
+                    ..operation("op-4", "symlink")
+                },
+            ],
+            target_folder: at(""),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        };
+
+        let journal = plan.to_wal_journal().unwrap();
+        let ids: Vec<_> = journal.entries.iter().map(|e| e.id).collect();
+        assert_eq!(journal.entries[2].depends_on, vec![ids[1], ids[0]]);
+        assert!(journal.entries[3].depends_on.contains(&ids[1]));
+        assert!(journal.entries[3].depends_on.contains(&ids[2]));
+
+        // The time being replaced is read from where the file is now
+        let WALOperationType::SetTimestamps {
+            previous_modified, ..
+        } = &journal.entries[2].operation
+        else {
+            panic!("Expected SetTimestamps");
+        };
+        assert_eq!(*previous_modified, Some(before));
+        assert!(matches!(
+            journal.entries[3].undo_operation,
+            WALOperationType::RemoveLink { alias: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_to_wal_journal_rejects_incomplete_operations() {
+        let mut plan = sample_plan();
+        plan.operations.push(operation("op-4", "move"));
+        let err = plan.to_wal_journal().unwrap_err();
+        assert!(err.contains("op-4") && err.contains("source"));
+
+        plan.operations.pop();
+        plan.operations.push(operation("op-5", "explode"));
+        assert!(plan.to_wal_journal().is_err());
+
+        // Link targets must exist (or be planned from something that does)
+        plan.operations.pop();
+        plan.operations.push(OrganizeOperation {
+            path: Some("/data/link".to_string()),
+            attributes: OperationAttributes {
+                target: Some("/data/missing.pdf".to_string()),
+                ..Default::default()
+            },
+            ..operation("op-6", "symlink")
+        });
+        let err = plan.to_wal_journal().unwrap_err();
+        assert!(err.contains("op-6") && err.contains("target"));
+    }
+
+    #[test]
+    fn test_job_plan_edits_round_trip() {
+        let mut job = OrganizeJob::new("/data");
+        job.set_plan(sample_plan());
+        job.edit_plan(PlanEdit::Exclude { op_id: "op-3".to_string() }).unwrap();
+        job.edit_plan(PlanEdit::RetargetFolder {
+            from: "/data/Docs".to_string(),
+            to: "/data/Documents".to_string(),
+        })
+        .unwrap();
+        assert!(job.edit_plan(PlanEdit::Exclude { op_id: "op-9".to_string() }).is_err());
+        assert_eq!(job.plan_edits.len(), 2);
+
+        // Edits survive a save and load of the job file
+        let json = serde_json::to_string(&job).unwrap();
+        let mut loaded: OrganizeJob = serde_json::from_str(&json).unwrap();
+        let plan = loaded.plan.as_ref().unwrap();
+        assert_eq!(plan.operations.len(), 2);
+        assert_eq!(plan.operations[1].destination.as_deref(), Some("/data/Documents/a.pdf"));
+        assert_eq!(loaded.excluded_ops, vec!["op-3"]);
+
+        loaded.revert_plan_edits(Some(1)).unwrap();
+        let plan = loaded.plan.as_ref().unwrap();
+        assert_eq!(plan.operations[1].destination.as_deref(), Some("/data/Docs/a.pdf"));
+        loaded.revert_plan_edits(None).unwrap();
+        assert_eq!(loaded.total_ops, 3);
+
+        loaded.complete_operation("op-1");
+        assert!(loaded.edit_plan(PlanEdit::Exclude { op_id: "op-2".to_string() }).is_err());
+    }
+
+    #[test]
+    fn test_to_simulated_operations() {
+        let ops = sample_plan().to_simulated_operations();
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(
+            &ops[2],
+            SimulatedOperation::Move { destination, .. } if destination == "/data/c.txt"
+        ));
+    }
+}
@@ -0,0 +1,813 @@
+//! Plan re-basing
+//!
+//! A plan can sit for minutes or hours before it runs while the folder keeps
+//! changing. A [`PlanBaseline`] records the folder as it was when the plan
+//! was made; [`rebase_plan`] re-scans it and brings the plan up to date:
+//! sources that were moved or renamed are followed by size and content hash,
+//! operations whose sources were deleted are dropped, and files added since
+//! are planned with the plan's rules, or next to the files the plan already
+//! moves from the same folder.
+
+use super::{moves_into, target_of, OrganizeOperation, OrganizePlan};
+use crate::ai::grok::ContentCache;
+use crate::execution::unique_path_with;
+use crate::scan::{ScanEntry, ScanOptions, ScanSnapshot};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Size, modification time and content hash of a planned source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileFingerprint {
+    pub size: u64,
+    /// Milliseconds since epoch
+    pub modified_at: Option<i64>,
+    /// [`ContentCache::sample_hash`] of the content (SHA-256 of the whole
+    /// content in baselines captured before sampling)
+    pub hash: String,
+}
+
+impl FileFingerprint {
+    /// Hash `path` the same way this fingerprint was hashed
+    fn hash_of(&self, path: &Path) -> Result<String, String> {
+        if self.hash.starts_with("sample-") {
+            ContentCache::sample_hash(path)
+        } else {
+            ContentCache::hash_file(path)
+        }
+    }
+}
+
+/// The plan folders as they were when the plan was made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanBaseline {
+    pub captured_at: DateTime<Utc>,
+    /// Every file below the plan folders
+    pub files: BTreeSet<String>,
+    /// Fingerprints of the files the plan operates on
+    pub sources: BTreeMap<String, FileFingerprint>,
+}
+
+impl PlanBaseline {
+    /// Scan the plan folders and fingerprint the sources of its operations
+    pub fn capture(plan: &OrganizePlan) -> Result<Self, String> {
+        let sources: Vec<PathBuf> = plan.operations.iter().filter_map(source_of).collect();
+        Self::capture_roots(&plan.roots(), &sources)
+    }
+
+    /// Scan `root` and fingerprint `sources` (files that are not there are left out)
+    pub fn capture_sources(root: &Path, sources: &[PathBuf]) -> Result<Self, String> {
+        Self::capture_roots(&[root.to_path_buf()], sources)
+    }
+
+    fn capture_roots(roots: &[PathBuf], sources: &[PathBuf]) -> Result<Self, String> {
+        let snapshots = scan(roots)?;
+        let on_disk: HashMap<&Path, &ScanEntry> =
+            files(&snapshots).map(|e| (e.path.as_path(), e)).collect();
+
+        let mut fingerprints = BTreeMap::new();
+        for source in sources {
+            let key = source.to_string_lossy().to_string();
+            let Some(entry) = on_disk.get(source.as_path()) else {
+                continue;
+            };
+            if fingerprints.contains_key(&key) {
+                continue;
+            }
+            // Sampled, so planning doesn't read every source in full
+            match ContentCache::sample_hash(&entry.path) {
+                Ok(hash) => {
+                    fingerprints.insert(
+                        key,
+                        FileFingerprint {
+                            size: entry.size,
+                            modified_at: entry.modified_at,
+                            hash,
+                        },
+                    );
+                }
+                // Still planned, it just can't be followed if it moves
+                Err(e) => eprintln!("[Rebase] {}: {}", source.display(), e),
+            }
+        }
+
+        Ok(Self {
+            captured_at: Utc::now(),
+            files: files(&snapshots)
+                .map(|e| e.path.to_string_lossy().to_string())
+                .collect(),
+            sources: fingerprints,
+        })
+    }
+}
+
+impl OrganizePlan {
+    /// Attach a baseline of the folder as it is now so the plan can be
+    /// re-based before it runs. A failed capture is logged and leaves the
+    /// plan without one.
+    pub fn with_baseline(mut self) -> Self {
+        match PlanBaseline::capture(&self) {
+            Ok(baseline) => self.baseline = Some(baseline),
+            Err(e) => tracing::warn!("[Rebase] Could not capture plan baseline: {}", e),
+        }
+        self
+    }
+}
+
+/// A source found somewhere else by its content
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocatedSource {
+    pub op_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// An operation left out of the re-based plan
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedOperation {
+    pub op_id: String,
+    pub reason: String,
+}
+
+/// What re-basing changed
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebaseSummary {
+    /// Whether the plan had a baseline; without one only operations on
+    /// missing sources are dropped
+    pub had_baseline: bool,
+    pub relocated: Vec<RelocatedSource>,
+    pub dropped: Vec<DroppedOperation>,
+    /// Sources still in place whose size or modification time changed
+    pub modified: Vec<String>,
+    /// Files added since planning that were planned
+    pub added: Vec<String>,
+    /// Files added since planning that no rule or earlier move places
+    pub unplaced: Vec<String>,
+}
+
+impl RebaseSummary {
+    /// Whether the plan still matches the folder
+    pub fn is_unchanged(&self) -> bool {
+        self.relocated.is_empty()
+            && self.dropped.is_empty()
+            && self.modified.is_empty()
+            && self.added.is_empty()
+            && self.unplaced.is_empty()
+    }
+}
+
+impl fmt::Display for RebaseSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_unchanged() {
+            return write!(f, "Plan is up to date");
+        }
+        let counts = [
+            (self.relocated.len(), "source", "followed"),
+            (self.dropped.len(), "operation", "dropped"),
+            (self.modified.len(), "source", "modified"),
+            (self.added.len(), "new file", "planned"),
+            (self.unplaced.len(), "new file", "unplaced"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(n, _, _)| *n > 0)
+            .map(|(n, noun, what)| {
+                format!("{} {}{} {}", n, noun, if *n == 1 { "" } else { "s" }, what)
+            })
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// A plan brought up to date with the folder
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebasedPlan {
+    pub plan: OrganizePlan,
+    pub summary: RebaseSummary,
+}
+
+/// Re-scan the plan folders and update the plan to match them.
+///
+/// Operations keep their order. A source that is gone is looked up among the
+/// files added since planning by size and content hash; operations that
+/// can't be followed are dropped, along with the operations that depend on
+/// what they would have produced and the folders only they filled. New files
+/// are planned with `plan.rules` when the plan has them, otherwise into the
+/// folder the plan moves every file of that kind from the same folder.
+/// The baseline is re-captured when anything changed.
+pub fn rebase_plan(plan: &OrganizePlan) -> Result<RebasedPlan, String> {
+    let root = Path::new(&plan.target_folder);
+    let snapshots = scan(&plan.roots())?;
+    let baseline = plan.baseline.as_ref();
+    let mut summary = RebaseSummary {
+        had_baseline: baseline.is_some(),
+        ..Default::default()
+    };
+
+    let on_disk: HashMap<&Path, &ScanEntry> =
+        files(&snapshots).map(|e| (e.path.as_path(), e)).collect();
+    // Files that appeared since the plan was made
+    let mut added: Vec<&ScanEntry> = match baseline {
+        Some(baseline) => {
+            let known: HashSet<&Path> = baseline.files.iter().map(Path::new).collect();
+            files(&snapshots)
+                .filter(|e| !known.contains(e.path.as_path()))
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let mut hashes: HashMap<PathBuf, Option<String>> = HashMap::new();
+
+    // Where followed sources are now
+    let mut relocations: HashMap<PathBuf, PathBuf> = HashMap::new();
+    // What kept and dropped moves, renames and links produce
+    let mut produced: HashSet<PathBuf> = HashSet::new();
+    let mut lost: HashSet<PathBuf> = HashSet::new();
+    let mut operations = Vec::new();
+
+    for op in &plan.operations {
+        let mut op = op.clone();
+        if let Some(mut source) = source_of(&op) {
+            if let Some(to) = relocations.get(&source) {
+                source = to.clone();
+                set_source(&mut op, &source);
+            }
+
+            let dropped = if is_under(&produced, &source) {
+                None
+            } else if source.symlink_metadata().is_ok() {
+                let fingerprint = baseline.and_then(|b| b.sources.get(&*source.to_string_lossy()));
+                if let (Some(was), Some(now)) = (fingerprint, on_disk.get(source.as_path())) {
+                    if was.size != now.size || was.modified_at != now.modified_at {
+                        summary.modified.push(source.to_string_lossy().to_string());
+                    }
+                }
+                None
+            } else if is_under(&lost, &source) {
+                Some("It works on the result of a dropped operation")
+            } else if let Some(found) = baseline
+                .and_then(|b| b.sources.get(&*source.to_string_lossy()))
+                .and_then(|fingerprint| find_by_content(fingerprint, &source, &added, &mut hashes))
+            {
+                added.retain(|e| e.path != found);
+                summary.relocated.push(RelocatedSource {
+                    op_id: op.op_id.clone(),
+                    from: source.to_string_lossy().to_string(),
+                    to: found.to_string_lossy().to_string(),
+                });
+                set_source(&mut op, &found);
+                relocations.insert(source, found);
+                None
+            } else {
+                Some("Its source no longer exists")
+            };
+
+            if let Some(reason) = dropped {
+                lost.extend(target_of(&op));
+                summary.dropped.push(DroppedOperation {
+                    op_id: op.op_id.clone(),
+                    reason: reason.to_string(),
+                });
+                continue;
+            }
+        }
+        if op.op_type != "create_folder" {
+            produced.extend(target_of(&op));
+        }
+        operations.push(op);
+    }
+
+    // Folders the plan filled that nothing is moved into anymore
+    let emptied: HashSet<String> = operations
+        .iter()
+        .filter(|op| op.op_type == "create_folder")
+        .filter(|op| {
+            let dir = Path::new(op.path.as_deref().unwrap_or_default());
+            moves_into(&plan.operations, dir) && !moves_into(&operations, dir)
+        })
+        .map(|op| op.op_id.clone())
+        .collect();
+    for op_id in &emptied {
+        summary.dropped.push(DroppedOperation {
+            op_id: op_id.clone(),
+            reason: "No files are planned into it anymore".to_string(),
+        });
+    }
+    operations.retain(|op| !emptied.contains(&op.op_id));
+
+    // New files already inside a destination folder are organized
+    let destinations: HashSet<PathBuf> = operations
+        .iter()
+        .filter_map(|op| match op.op_type.as_str() {
+            "create_folder" => op.path.as_ref().map(PathBuf::from),
+            "move" => Path::new(op.destination.as_deref()?)
+                .parent()
+                .map(Path::to_path_buf),
+            _ => None,
+        })
+        .filter(|dir| dir != root)
+        .collect();
+    added.retain(|e| !is_under(&destinations, &e.path));
+
+    if baseline.is_some() && !added.is_empty() {
+        let new_files: Vec<PathBuf> = added.iter().map(|e| e.path.clone()).collect();
+        let placed = if plan.rules.is_empty() {
+            place_like_plan(plan, &new_files)
+        } else {
+            place_with_rules(plan, &new_files)?
+        };
+        let placed_sources: HashSet<PathBuf> = placed.iter().filter_map(source_of).collect();
+        summary.added = new_files
+            .iter()
+            .filter(|f| placed_sources.contains(*f))
+            .map(|f| f.to_string_lossy().to_string())
+            .collect();
+        summary.unplaced = new_files
+            .iter()
+            .filter(|f| !placed_sources.contains(*f))
+            .map(|f| f.to_string_lossy().to_string())
+            .collect();
+        append_placed(root, &mut operations, placed);
+    }
+
+    let mut rebased = OrganizePlan {
+        operations,
+        ..plan.clone()
+    };
+    if summary.had_baseline && !summary.is_unchanged() {
+        rebased.baseline = Some(PlanBaseline::capture(&rebased)?);
+    }
+    if !summary.is_unchanged() {
+        eprintln!("[Rebase] {}: {}", plan.plan_id, summary);
+    }
+
+    Ok(RebasedPlan {
+        plan: rebased,
+        summary,
+    })
+}
+
+/// Scan each root once; a root inside another is covered by the outer scan
+fn scan(roots: &[PathBuf]) -> Result<Vec<ScanSnapshot>, String> {
+    roots
+        .iter()
+        .filter(|root| {
+            !roots
+                .iter()
+                .any(|other| other != *root && root.starts_with(other))
+        })
+        .map(|root| ScanOptions::new().scan(root))
+        .collect()
+}
+
+fn files(snapshots: &[ScanSnapshot]) -> impl Iterator<Item = &ScanEntry> {
+    snapshots.iter().flat_map(ScanSnapshot::files)
+}
+
+/// The existing file or folder an operation works on: what a move moves,
+/// what a link points to, or the path everything else changes
+fn source_of(op: &OrganizeOperation) -> Option<PathBuf> {
+    match op.op_type.as_str() {
+        "create_folder" => None,
+        "move" => op.source.as_ref().map(PathBuf::from),
+        "symlink" | "alias" => op.attributes.target.as_ref().map(PathBuf::from),
+        _ => op.path.as_ref().map(PathBuf::from),
+    }
+}
+
+fn set_source(op: &mut OrganizeOperation, source: &Path) {
+    let source = Some(source.to_string_lossy().to_string());
+    match op.op_type.as_str() {
+        "move" => op.source = source,
+        "symlink" | "alias" => op.attributes.target = source,
+        _ => op.path = source,
+    }
+}
+
+/// Whether `path` or a folder above it is in `paths`
+fn is_under(paths: &HashSet<PathBuf>, path: &Path) -> bool {
+    path.ancestors().any(|p| paths.contains(p))
+}
+
+/// A new file with the same content as the planned source, preferring one
+/// that kept its name. Only files of the same size are hashed.
+fn find_by_content(
+    fingerprint: &FileFingerprint,
+    source: &Path,
+    added: &[&ScanEntry],
+    hashes: &mut HashMap<PathBuf, Option<String>>,
+) -> Option<PathBuf> {
+    let mut candidates: Vec<&ScanEntry> = added
+        .iter()
+        .copied()
+        .filter(|e| e.size == fingerprint.size)
+        .collect();
+    candidates
+        .sort_by_key(|e| Some(e.name.as_str()) != source.file_name().and_then(|n| n.to_str()));
+
+    candidates
+        .into_iter()
+        .find(|e| {
+            hashes
+                .entry(e.path.clone())
+                .or_insert_with(|| fingerprint.hash_of(&e.path).ok())
+                .as_deref()
+                == Some(fingerprint.hash.as_str())
+        })
+        .map(|e| e.path.clone())
+}
+
+/// Plan `new_files` with the plan's rules; only their moves and renames are kept
+fn place_with_rules(
+    plan: &OrganizePlan,
+    new_files: &[PathBuf],
+) -> Result<Vec<OrganizeOperation>, String> {
+    let wanted: HashSet<&PathBuf> = new_files.iter().collect();
+    let sources: Vec<PathBuf> = plan.source_roots.iter().map(PathBuf::from).collect();
+    let planned = crate::api::plan_with_rules_across(
+        Path::new(&plan.target_folder),
+        &sources,
+        &plan.rules,
+        &plan.description,
+    )?;
+    Ok(planned
+        .plan
+        .operations
+        .into_iter()
+        .filter(|op| matches!(op.op_type.as_str(), "move" | "rename"))
+        .filter(|op| source_of(op).is_some_and(|s| wanted.contains(&s)))
+        .collect())
+}
+
+/// Move each new file where the plan moves every file with the same
+/// extension from the same folder (or, failing that, from anywhere)
+fn place_like_plan(plan: &OrganizePlan, new_files: &[PathBuf]) -> Vec<OrganizeOperation> {
+    let mut by_folder: HashMap<(PathBuf, String), BTreeSet<PathBuf>> = HashMap::new();
+    let mut by_extension: HashMap<String, BTreeSet<PathBuf>> = HashMap::new();
+    for op in plan.operations.iter().filter(|op| op.op_type == "move") {
+        let (Some(source), Some(destination)) = (&op.source, &op.destination) else {
+            continue;
+        };
+        let (source, destination) = (Path::new(source), Path::new(destination));
+        let (Some(from), Some(into)) = (source.parent(), destination.parent()) else {
+            continue;
+        };
+        let extension = extension_of(source);
+        by_folder
+            .entry((from.to_path_buf(), extension.clone()))
+            .or_default()
+            .insert(into.to_path_buf());
+        by_extension
+            .entry(extension)
+            .or_default()
+            .insert(into.to_path_buf());
+    }
+
+    let single = |folders: Option<&BTreeSet<PathBuf>>| {
+        folders
+            .filter(|f| f.len() == 1)
+            .and_then(|f| f.iter().next().cloned())
+    };
+    new_files
+        .iter()
+        .filter_map(|file| {
+            let extension = extension_of(file);
+            let from = file.parent()?.to_path_buf();
+            let into = single(by_folder.get(&(from, extension.clone())))
+                .or_else(|| single(by_extension.get(&extension)))?;
+            Some(OrganizeOperation {
+                op_id: String::new(),
+                op_type: "move".to_string(),
+                source: Some(file.to_string_lossy().to_string()),
+                destination: Some(into.join(file.file_name()?).to_string_lossy().to_string()),
+                path: None,
+                new_name: None,
+                attributes: Default::default(),
+            })
+        })
+        .collect()
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Append operations for new files with fresh ids, destinations that don't
+/// collide with the disk or the plan, and the folders they need
+fn append_placed(
+    root: &Path,
+    operations: &mut Vec<OrganizeOperation>,
+    placed: Vec<OrganizeOperation>,
+) {
+    let mut taken: HashSet<PathBuf> = operations.iter().filter_map(target_of).collect();
+    let ids: HashSet<String> = operations.iter().map(|op| op.op_id.clone()).collect();
+    let mut counter = 0;
+    let mut next_id = || loop {
+        counter += 1;
+        let id = format!("rebase-{}", counter);
+        if !ids.contains(&id) {
+            return id;
+        }
+    };
+
+    for mut op in placed {
+        let Some(mut target) = target_of(&op) else {
+            continue;
+        };
+        if taken.contains(&target) || target.exists() {
+            target = unique_path_with(&target, |p| taken.contains(p) || p.exists());
+            if op.op_type == "move" {
+                op.destination = Some(target.to_string_lossy().to_string());
+            } else {
+                op.new_name = target.file_name().map(|n| n.to_string_lossy().to_string());
+            }
+        }
+
+        // Folders between the plan folder and the destination
+        let mut folders: Vec<&Path> = target
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(root) && *dir != root)
+            .filter(|dir| !dir.exists() && !taken.contains(*dir))
+            .collect();
+        folders.reverse();
+        for dir in folders {
+            taken.insert(dir.to_path_buf());
+            operations.push(OrganizeOperation {
+                op_id: next_id(),
+                op_type: "create_folder".to_string(),
+                source: None,
+                destination: None,
+                path: Some(dir.to_string_lossy().to_string()),
+                new_name: None,
+                attributes: Default::default(),
+            });
+        }
+
+        taken.insert(target);
+        op.op_id = next_id();
+        operations.push(op);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn op(op_id: &str, op_type: &str) -> OrganizeOperation {
+        OrganizeOperation {
+            op_id: op_id.to_string(),
+            op_type: op_type.to_string(),
+            source: None,
+            destination: None,
+            path: None,
+            new_name: None,
+            attributes: Default::default(),
+        }
+    }
+
+    fn create_folder(op_id: &str, path: &Path) -> OrganizeOperation {
+        OrganizeOperation {
+            path: Some(path.to_string_lossy().to_string()),
+            ..op(op_id, "create_folder")
+        }
+    }
+
+    fn move_op(op_id: &str, source: &Path, destination: &Path) -> OrganizeOperation {
+        OrganizeOperation {
+            source: Some(source.to_string_lossy().to_string()),
+            destination: Some(destination.to_string_lossy().to_string()),
+            ..op(op_id, "move")
+        }
+    }
+
+    fn plan(root: &Path, operations: Vec<OrganizeOperation>) -> OrganizePlan {
+        OrganizePlan {
+            plan_id: "plan-rebase".to_string(),
+            description: "Test".to_string(),
+            operations,
+            target_folder: root.to_string_lossy().to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rebase_follows_moved_sources_and_drops_deleted() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("report.pdf"), b"quarterly report").unwrap();
+        fs::write(root.join("notes.txt"), b"notes").unwrap();
+
+        let (docs, text) = (root.join("Docs"), root.join("Text"));
+        let mut tag = op("tag", "set_tags");
+        tag.path = Some(text.join("notes.txt").to_string_lossy().to_string());
+        tag.attributes.tags = Some(vec!["notes".to_string()]);
+        let planned = plan(
+            root,
+            vec![
+                create_folder("docs", &docs),
+                create_folder("text", &text),
+                move_op("m1", &root.join("report.pdf"), &docs.join("report.pdf")),
+                move_op("m2", &root.join("notes.txt"), &text.join("notes.txt")),
+                tag,
+            ],
+        )
+        .with_baseline();
+        assert_eq!(planned.baseline.as_ref().unwrap().sources.len(), 2);
+
+        // Renamed by the user, and the notes deleted
+        fs::create_dir(root.join("inbox")).unwrap();
+        fs::rename(root.join("report.pdf"), root.join("inbox/q3.pdf")).unwrap();
+        fs::remove_file(root.join("notes.txt")).unwrap();
+
+        let rebased = rebase_plan(&planned).unwrap();
+        let summary = &rebased.summary;
+        assert_eq!(summary.relocated.len(), 1);
+        assert_eq!(
+            summary.relocated[0].to,
+            root.join("inbox/q3.pdf").to_string_lossy()
+        );
+
+        let dropped: Vec<&str> = summary.dropped.iter().map(|d| d.op_id.as_str()).collect();
+        assert_eq!(dropped, vec!["m2", "tag", "text"]);
+
+        let ids: Vec<&str> = rebased
+            .plan
+            .operations
+            .iter()
+            .map(|o| o.op_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["docs", "m1"]);
+        assert_eq!(
+            rebased.plan.operations[1].source.as_deref(),
+            Some(&*root.join("inbox/q3.pdf").to_string_lossy())
+        );
+        assert!(rebased
+            .plan
+            .baseline
+            .unwrap()
+            .files
+            .contains(&*root.join("inbox/q3.pdf").to_string_lossy()));
+    }
+
+    #[test]
+    fn test_rebase_follows_sampled_and_full_hashes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let report = root.join("report.pdf");
+        fs::write(&report, b"quarterly report").unwrap();
+
+        let docs = root.join("Docs");
+        let planned = plan(
+            root,
+            vec![
+                create_folder("docs", &docs),
+                move_op("m1", &report, &docs.join("report.pdf")),
+            ],
+        )
+        .with_baseline();
+        let key = report.to_string_lossy().to_string();
+        assert!(planned.baseline.as_ref().unwrap().sources[&key]
+            .hash
+            .starts_with("sample-"));
+
+        // Baselines saved before sampling hold the full content hash
+        let mut legacy = planned.clone();
+        legacy.baseline.as_mut().unwrap().sources.get_mut(&key).unwrap().hash =
+            ContentCache::hash_file(&report).unwrap();
+
+        fs::rename(&report, root.join("q3.pdf")).unwrap();
+        for plan in [&planned, &legacy] {
+            assert_eq!(rebase_plan(plan).unwrap().summary.relocated.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_rebase_places_new_files_like_the_plan() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.pdf"), b"a").unwrap();
+        fs::write(root.join("b.jpg"), b"b").unwrap();
+
+        let docs = root.join("Docs");
+        let planned = plan(
+            root,
+            vec![
+                create_folder("docs", &docs),
+                move_op("m1", &root.join("a.pdf"), &docs.join("a.pdf")),
+            ],
+        )
+        .with_baseline();
+
+        // Unchanged folder, unchanged plan
+        let rebased = rebase_plan(&planned).unwrap();
+        assert!(rebased.summary.is_unchanged());
+        assert_eq!(rebased.plan.operations.len(), 2);
+
+        fs::write(root.join("c.pdf"), b"new pdf").unwrap();
+        fs::write(root.join("d.mp3"), b"new audio").unwrap();
+        fs::create_dir(&docs).unwrap();
+        fs::write(docs.join("c.pdf"), b"already there").unwrap();
+
+        let rebased = rebase_plan(&planned).unwrap();
+        assert_eq!(
+            rebased.summary.added,
+            vec![root.join("c.pdf").to_string_lossy().to_string()]
+        );
+        assert_eq!(
+            rebased.summary.unplaced,
+            vec![root.join("d.mp3").to_string_lossy().to_string()]
+        );
+
+        let added = rebased.plan.operations.last().unwrap();
+        assert_eq!(added.op_id, "rebase-1");
+        assert_eq!(
+            added.destination.as_deref(),
+            Some(&*docs.join("c_1.pdf").to_string_lossy())
+        );
+        assert_eq!(
+            rebased.summary.to_string(),
+            "1 new file planned, 1 new file unplaced"
+        );
+    }
+
+    #[test]
+    fn test_rebase_follows_files_across_source_roots() {
+        let temp = TempDir::new().unwrap();
+        let (desktop, sorted) = (temp.path().join("Desktop"), temp.path().join("Sorted"));
+        fs::create_dir(&desktop).unwrap();
+        fs::create_dir(&sorted).unwrap();
+        fs::write(desktop.join("a.pdf"), b"a").unwrap();
+
+        let docs = sorted.join("Docs");
+        let mut planned = plan(
+            &sorted,
+            vec![
+                create_folder("docs", &docs),
+                move_op("m1", &desktop.join("a.pdf"), &docs.join("a.pdf")),
+            ],
+        );
+        planned.source_roots = vec![desktop.to_string_lossy().to_string()];
+        let planned = planned.with_baseline();
+        assert_eq!(planned.baseline.as_ref().unwrap().sources.len(), 1);
+
+        // Renamed on the Desktop, and a new PDF dropped next to it
+        fs::rename(desktop.join("a.pdf"), desktop.join("renamed.pdf")).unwrap();
+        fs::write(desktop.join("b.pdf"), b"b").unwrap();
+
+        let rebased = rebase_plan(&planned).unwrap();
+        assert_eq!(rebased.summary.relocated.len(), 1);
+        assert_eq!(
+            rebased.summary.added,
+            vec![desktop.join("b.pdf").to_string_lossy().to_string()]
+        );
+        assert_eq!(
+            rebased
+                .plan
+                .operations
+                .last()
+                .unwrap()
+                .destination
+                .as_deref(),
+            Some(&*docs.join("b.pdf").to_string_lossy())
+        );
+    }
+
+    #[test]
+    fn test_rebase_without_baseline_only_drops_missing_sources() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.txt"), b"a").unwrap();
+
+        let planned = plan(
+            root,
+            vec![
+                move_op("m1", &root.join("a.txt"), &root.join("b.txt")),
+                move_op("m2", &root.join("gone.txt"), &root.join("c.txt")),
+            ],
+        );
+        fs::write(root.join("new.txt"), b"new").unwrap();
+
+        let rebased = rebase_plan(&planned).unwrap();
+        assert!(!rebased.summary.had_baseline);
+        assert_eq!(rebased.summary.dropped.len(), 1);
+        assert!(rebased.summary.added.is_empty());
+        assert!(rebased.plan.baseline.is_none());
+        assert_eq!(rebased.plan.operations.len(), 1);
+    }
+}
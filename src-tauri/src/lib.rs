@@ -1,11 +1,20 @@
+// Without the desktop app, the modules only its commands use are unused
+#![cfg_attr(not(feature = "desktop"), allow(dead_code, unused_imports))]
+
 mod ai;
+pub mod api;
 mod billing;
+pub mod cli;
+#[cfg(feature = "desktop")]
 mod commands;
+pub mod events;
 mod execution;
 mod file_coordination;
 mod jobs;
+pub mod mcp;
 mod models;
 pub mod quarantine;
+pub mod scan;
 mod security;
 mod services;
 mod tree;
@@ -14,14 +23,18 @@ mod vector;
 pub mod vfs;
 mod wal;
 
-use billing::BillingState;
-use commands::*;
-use commands::grok::{GrokState, GrokAbortFlag};
-use services::watcher::create_watcher_handle;
-use tracing_subscriber::EnvFilter;
-
+/// Run the desktop app
+#[cfg(feature = "desktop")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    use billing::BillingState;
+    use commands::*;
+    use commands::grok::{GrokState, GrokAbortFlag};
+    use services::scheduler::{create_scheduler_handle, run_scheduler};
+    use services::watcher::{create_watcher_handle, restore_watched_folders, WatchContext};
+    use std::sync::Arc;
+    use tracing_subscriber::EnvFilter;
+
     // Load .env file - try multiple locations
     // During `tauri dev`, CWD is project root; check current dir first
     if dotenvy::dotenv().is_err() {
@@ -40,6 +53,7 @@ pub fn run() {
         .init();
 
     let watcher_handle = create_watcher_handle();
+    let scheduler_handle = create_scheduler_handle();
     let vector_state = VectorState::default();
     let tree_state = TreeState::default();
     let vfs_state = create_vfs_state();
@@ -50,6 +64,10 @@ pub fn run() {
     let grok_abort_flag = GrokAbortFlag::default();
     let billing_state = BillingState::default();
 
+    let restore_handle = watcher_handle.clone();
+    let restore_vectors = Arc::clone(&vector_state.0);
+    let scheduler_loop_handle = scheduler_handle.clone();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
@@ -57,6 +75,7 @@ pub fn run() {
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_deep_link::init())
         .manage(watcher_handle)
+        .manage(scheduler_handle)
         .manage(vector_state)
         .manage(tree_state)
         .manage(vfs_state)
@@ -65,6 +84,18 @@ pub fn run() {
         .manage(grok_state)
         .manage(grok_abort_flag)
         .manage(billing_state)
+        .setup(move |app| {
+            // Re-arm saved watched folders on the async runtime (actions need it)
+            let events = Arc::new(app.handle().clone());
+            let scheduler_events = events.clone();
+            tauri::async_runtime::spawn(async move {
+                let ctx = WatchContext::new(events, restore_vectors);
+                restore_watched_folders(&ctx, &restore_handle);
+            });
+            // Run scheduled organize jobs as they come due
+            tauri::async_runtime::spawn(run_scheduler(scheduler_loop_handle, scheduler_events));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Filesystem commands
             read_directory,
@@ -89,6 +120,15 @@ pub fn run() {
             get_watcher_status,
             add_watched_folder,
             remove_watched_folder,
+            configure_watched_folder,
+            list_watched_folders,
+            query_watch_journal,
+            // Scheduled job commands
+            list_scheduled_jobs,
+            save_scheduled_job,
+            remove_scheduled_job,
+            run_scheduled_job_now,
+            get_schedule_history,
             // AI commands
             set_api_key,
             delete_api_key,
@@ -97,11 +137,26 @@ pub fn run() {
             apply_rename,
             undo_rename,
             generate_organize_plan_agentic,
+            generate_organize_plan_across,
             suggest_naming_conventions,
             generate_organize_plan_with_convention,
+            // Blueprint library commands
+            list_blueprints,
+            save_blueprint,
+            delete_blueprint,
+            export_blueprint,
+            import_blueprint,
+            design_blueprint,
+            generate_organize_plan_from_blueprint,
+            // Learning from plan corrections
+            list_corrections,
+            clear_corrections,
+            evaluate_correction_priors,
             // Job persistence commands
             start_organize_job,
             set_job_plan,
+            edit_job_plan,
+            revert_job_plan_edits,
             complete_job_operation,
             complete_organize_job,
             fail_organize_job,
@@ -109,11 +164,13 @@ pub fn run() {
             get_current_job,
             clear_organize_job,
             resume_organize_job,
+            rebase_organize_plan,
             execute_plan_parallel,
             // Thumbnail commands
             get_thumbnail,
             clear_thumbnail_cache,
             get_thumbnail_cache_stats,
+            get_renderer_capabilities,
             // Permission commands
             check_path_permission,
             get_protected_directories,
@@ -146,6 +203,7 @@ pub fn run() {
             vfs_get_node,
             vfs_get_stats,
             vfs_simulate_plan,
+            vfs_render_plan_diff,
             vfs_stage_move,
             vfs_stage_create_folder,
             vfs_stage_delete,
@@ -153,6 +211,13 @@ pub fn run() {
             vfs_clear_staged,
             vfs_has_staged,
             vfs_clear,
+            // Snapshot commands
+            vfs_save_snapshot,
+            snapshot_capture,
+            snapshot_list,
+            snapshot_diff,
+            snapshot_changes_since,
+            snapshot_delete,
             // Quarantine commands
             quarantine_item,
             quarantine_restore,
@@ -160,6 +225,10 @@ pub fn run() {
             quarantine_cleanup,
             quarantine_permanent_delete,
             quarantine_check,
+            quarantine_get_policy,
+            quarantine_set_policy,
+            quarantine_usage,
+            quarantine_rebuild_index,
             // WAL commands
             wal_check_recovery,
             wal_resume_job,
@@ -170,6 +239,7 @@ pub fn run() {
             wal_create_journal,
             wal_add_operation,
             wal_execute_journal,
+            wal_preflight_journal,
             wal_execute_operations,
             wal_get_directory,
             // Chat commands
@@ -183,9 +253,16 @@ pub fn run() {
             grok_organize,
             grok_generate_plan,
             grok_execute_plan,
+            grok_set_job_plan,
+            grok_execute_job_plan,
             grok_analyze_file,
             grok_cache_stats,
             grok_clear_cache,
+            grok_cache_versions,
+            grok_invalidate_cache,
+            grok_export_cache,
+            grok_import_cache,
+            grok_export_fields_csv,
             grok_check_api_key,
             grok_set_api_key,
             grok_get_api_key,
@@ -0,0 +1,303 @@
+//! Model Context Protocol server (`sentinel mcp`).
+//!
+//! Exposes Sentinel's file tools to external agents (Claude Desktop, IDEs, ...)
+//! over JSON-RPC 2.0 on stdio, one message per line. stdout carries protocol
+//! messages only; logs go to stderr.
+//!
+//! Supported methods: `initialize`, `ping`, `tools/list`, `tools/call`.
+//! Tools and their safety rules live in `tools.rs`.
+
+mod tools;
+
+pub use tools::{SentinelTools, ToolOutput};
+
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Protocol revisions we speak, newest first
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const INSTRUCTIONS: &str = "Sentinel file tools. Browse with list_directory, read_file, grep and search_hybrid. \
+To reorganize a folder: open_folder, then apply_organization_rules / preview_operations, commit_plan, \
+and finally execute_plan with confirm: true. Executed plans can be rolled back with undo_plan.";
+
+/// One MCP connection
+pub struct McpServer {
+    tools: SentinelTools,
+}
+
+impl McpServer {
+    /// Create a server limited to `roots` (at least one)
+    pub fn new(roots: &[PathBuf]) -> Result<Self, String> {
+        Ok(Self {
+            tools: SentinelTools::new(roots)?,
+        })
+    }
+
+    /// Handle one line of input; returns the line to send back, if any
+    pub async fn handle_line(&mut self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {}", e),
+                ))
+            }
+        };
+
+        match message {
+            Value::Array(batch) => {
+                if batch.is_empty() {
+                    return Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch"));
+                }
+                let mut responses = Vec::new();
+                for message in batch {
+                    if let Some(response) = self.handle_message(message).await {
+                        responses.push(response);
+                    }
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_message(message).await,
+        }
+    }
+
+    async fn handle_message(&mut self, message: Value) -> Option<Value> {
+        let method = match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method.to_string(),
+            // Responses to server requests: we never send any, so ignore them
+            None if message.get("result").is_some() || message.get("error").is_some() => {
+                return None
+            }
+            None => {
+                let id = message.get("id").cloned().unwrap_or(Value::Null);
+                return Some(error_response(id, INVALID_REQUEST, "Missing method"));
+            }
+        };
+
+        // Notifications (initialized, cancelled, ...) get no response
+        let id = message.get("id").cloned()?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method.as_str() {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": SentinelTools::definitions() })),
+            "tools/call" => self.call_tool(&params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        // Echo the client's revision when we support it, otherwise offer our latest
+        let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+        let version = requested
+            .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+            .unwrap_or(SUPPORTED_PROTOCOL_VERSIONS[0]);
+
+        eprintln!(
+            "[MCP] Client connected ({}), protocol {}",
+            params
+                .pointer("/clientInfo/name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("unknown"),
+            version
+        );
+
+        json!({
+            "protocolVersion": version,
+            "capabilities": {
+                "tools": { "listChanged": false }
+            },
+            "serverInfo": {
+                "name": "sentinel",
+                "version": env!("CARGO_PKG_VERSION")
+            },
+            "instructions": INSTRUCTIONS
+        })
+    }
+
+    async fn call_tool(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        if !SentinelTools::has_tool(name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool: {}", name)));
+        }
+
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let output = self.tools.call(name, &arguments).await;
+
+        Ok(json!({
+            "content": [{ "type": "text", "text": output.text }],
+            "isError": output.is_error
+        }))
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+/// Serve MCP over any line-based transport until the input closes
+pub async fn serve<R, W>(roots: &[PathBuf], reader: R, mut writer: W) -> Result<(), String>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut server = McpServer::new(roots)?;
+    let mut lines = reader.lines();
+
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Failed to read request: {}", e))?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line).await {
+            let mut out = serde_json::to_string(&response)
+                .map_err(|e| format!("Failed to serialize response: {}", e))?;
+            out.push('\n');
+            writer
+                .write_all(out.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write response: {}", e))?;
+            writer
+                .flush()
+                .await
+                .map_err(|e| format!("Failed to write response: {}", e))?;
+        }
+    }
+
+    eprintln!("[MCP] Input closed, shutting down");
+    Ok(())
+}
+
+/// Serve MCP on stdin/stdout
+pub async fn serve_stdio(roots: &[PathBuf]) -> Result<(), String> {
+    eprintln!("[MCP] Sentinel MCP server ready on stdio");
+    serve(
+        roots,
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn server_in(dir: &TempDir) -> McpServer {
+        McpServer::new(&[dir.path().to_path_buf()]).unwrap()
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> String {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_version() {
+        let dir = TempDir::new().unwrap();
+        let mut server = server_in(&dir);
+
+        let response = server
+            .handle_line(&request(1, "initialize", json!({ "protocolVersion": "2024-11-05" })))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "sentinel");
+
+        let response = server
+            .handle_line(&request(2, "initialize", json!({ "protocolVersion": "1999-01-01" })))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], SUPPORTED_PROTOCOL_VERSIONS[0]);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let dir = TempDir::new().unwrap();
+        let mut server = server_in(&dir);
+
+        // Notifications are not answered
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server.handle_line(&notification.to_string()).await.is_none());
+
+        let response = server.handle_line("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = server.handle_line(&request(3, "resources/list", json!({}))).await.unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server
+            .handle_line(&request(4, "tools/call", json!({ "name": "rm_rf" })))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_over_stdio_transport() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+
+        let input = [
+            request(1, "tools/list", json!({})),
+            request(
+                2,
+                "tools/call",
+                json!({ "name": "list_directory", "arguments": { "path": dir.path() } }),
+            ),
+            request(
+                3,
+                "tools/call",
+                json!({ "name": "read_file", "arguments": { "path": "/etc/hosts" } }),
+            ),
+        ]
+        .join("\n");
+
+        let mut output = Vec::new();
+        serve(&[dir.path().to_path_buf()], input.as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert!(responses[0]["result"]["tools"].as_array().unwrap().len() >= 10);
+
+        assert_eq!(responses[1]["result"]["isError"], false);
+        let text = responses[1]["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("notes.txt"));
+
+        // Outside the roots: a tool error, not a protocol error
+        assert_eq!(responses[2]["result"]["isError"], true);
+    }
+}
@@ -0,0 +1,606 @@
+//! Tool catalog and dispatch for the MCP server.
+//!
+//! The chat agent's tools and the V2 organize tools are reused unchanged;
+//! this layer only adds the policy external agents run under:
+//! - every path argument is resolved with `PathValidator` and must sit under
+//!   one of the server roots (at least one is required)
+//! - the shell tool never runs in `force` mode, nor runs the commands the
+//!   user pre-approved in the app
+//! - planning happens in the rules VFS; the only tool that touches the disk is
+//!   `execute_plan`, which journals through the WAL (undo with `undo_plan`)
+
+use crate::ai::chat::tools_terminal::execute_shell_unapproved;
+use crate::ai::chat::{execute_chat_tool, get_chat_tools, ChatToolResult};
+use crate::ai::v2::tools::{execute_v2_tool, get_v2_organize_tools, V2ToolResult};
+use crate::ai::v2::vfs::ShadowVFS as RulesVFS;
+use crate::api::{self, ConflictPolicy, ExecutionConfig, NoopSink, OrganizePlan};
+use crate::security::PathValidator;
+use crate::wal::WALManager;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Chat tools exposed over MCP ("bash" is only a legacy alias of "shell")
+const CHAT_TOOLS: &[&str] = &[
+    "search_hybrid",
+    "read_file",
+    "inspect_pattern",
+    "list_directory",
+    "shell",
+    "grep",
+];
+
+/// V2 tools that work on the folder opened with `open_folder`
+const V2_TOOLS: &[&str] = &[
+    "query_semantic_index",
+    "apply_organization_rules",
+    "preview_operations",
+    "inspect_pattern_sample",
+    "commit_plan",
+];
+
+/// Chat tool arguments that name a path to read from
+const PATH_ARGS: &[&str] = &["path", "directory", "working_dir"];
+
+/// Result of one tool call
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolOutput {
+    pub text: String,
+    pub is_error: bool,
+}
+
+impl ToolOutput {
+    fn ok(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            is_error: false,
+        }
+    }
+
+    fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            is_error: true,
+        }
+    }
+}
+
+/// Folder opened for rule-based organization
+struct OpenFolder {
+    root: PathBuf,
+    vfs: RulesVFS,
+}
+
+/// Sentinel's tools plus the per-connection planning state
+pub struct SentinelTools {
+    /// Canonical folders the client may touch (never empty)
+    roots: Vec<PathBuf>,
+    folder: Option<OpenFolder>,
+    /// Plans committed in this session, by plan id
+    plans: HashMap<String, OrganizePlan>,
+}
+
+impl SentinelTools {
+    pub fn new(roots: &[PathBuf]) -> Result<Self, String> {
+        if roots.is_empty() {
+            return Err("The MCP server needs at least one --root folder".to_string());
+        }
+        let roots = roots
+            .iter()
+            .map(|root| api::validate_folder(root))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            roots,
+            folder: None,
+            plans: HashMap::new(),
+        })
+    }
+
+    /// Whether `name` is a tool this server exposes
+    pub fn has_tool(name: &str) -> bool {
+        CHAT_TOOLS.contains(&name)
+            || V2_TOOLS.contains(&name)
+            || matches!(name, "open_folder" | "execute_plan" | "undo_plan")
+    }
+
+    /// Tool definitions in MCP `tools/list` format
+    pub fn definitions() -> Vec<Value> {
+        let mut tools: Vec<Value> = get_chat_tools()
+            .into_iter()
+            .filter(|tool| {
+                tool.get("name")
+                    .and_then(|n| n.as_str())
+                    .is_some_and(|n| CHAT_TOOLS.contains(&n))
+            })
+            .map(|mut tool| {
+                let mut schema = tool
+                    .as_object_mut()
+                    .and_then(|t| t.remove("input_schema"))
+                    .unwrap_or_else(|| json!({"type": "object"}));
+                // Approval bypass is for the user in the app, never for remote agents
+                if let Some(props) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                    props.remove("force");
+                }
+                tool["inputSchema"] = schema;
+                tool
+            })
+            .collect();
+
+        tools.push(json!({
+            "name": "open_folder",
+            "description": "Open a folder for rule-based organization and build its semantic index. Required before query_semantic_index, apply_organization_rules, preview_operations, inspect_pattern_sample and commit_plan.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the folder to organize" }
+                },
+                "required": ["path"]
+            }
+        }));
+
+        tools.extend(get_v2_organize_tools().into_iter().map(|tool| {
+            let description = if tool.name == "commit_plan" {
+                "Finalize the planned operations. Returns a plan id for execute_plan; nothing is changed on disk.".to_string()
+            } else {
+                tool.description
+            };
+            json!({
+                "name": tool.name,
+                "description": description,
+                "inputSchema": tool.input_schema,
+            })
+        }));
+
+        tools.push(json!({
+            "name": "execute_plan",
+            "description": "Execute a plan from commit_plan. The plan is simulated and preflighted against the disk first, and journaled in Sentinel's write-ahead log, so it can be undone with undo_plan.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "plan_id": { "type": "string" },
+                    "confirm": { "type": "boolean", "description": "Must be true" },
+                    "conflict": {
+                        "type": "string",
+                        "enum": ["auto_rename", "skip", "fail"],
+                        "default": "auto_rename",
+                        "description": "What to do when a destination already exists"
+                    }
+                },
+                "required": ["plan_id", "confirm"]
+            }
+        }));
+        tools.push(json!({
+            "name": "undo_plan",
+            "description": "Roll back a plan executed in this session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "plan_id": { "type": "string" }
+                },
+                "required": ["plan_id"]
+            }
+        }));
+
+        tools
+    }
+
+    /// Run a tool. Tool failures are reported in the output, not as `Err`.
+    pub async fn call(&mut self, name: &str, args: &Value) -> ToolOutput {
+        eprintln!("[MCP] Tool call: {}", name);
+
+        match name {
+            n if CHAT_TOOLS.contains(&n) => self.call_chat_tool(n, args).await,
+            n if V2_TOOLS.contains(&n) => self.call_v2_tool(n, args),
+            "open_folder" => self.open_folder(args),
+            "execute_plan" => self.execute_plan(args).await,
+            "undo_plan" => self.undo_plan(args),
+            _ => ToolOutput::error(format!("Unknown tool: {}", name)),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Chat tools
+    // ------------------------------------------------------------------------
+
+    async fn call_chat_tool(&self, name: &str, args: &Value) -> ToolOutput {
+        let mut input = match args {
+            Value::Object(map) => map.clone(),
+            Value::Null => Map::new(),
+            _ => return ToolOutput::error("Arguments must be an object"),
+        };
+
+        if let Err(e) = self.resolve_path_args(name, &mut input) {
+            return ToolOutput::error(e);
+        }
+
+        let input = Value::Object(input);
+        let result = if name == "shell" {
+            // Commands the user pre-approved in the app stay approved only there
+            match execute_shell_unapproved(&input).await {
+                Ok(text) => ChatToolResult::Success(text),
+                Err(e) => ChatToolResult::Error(e),
+            }
+        } else {
+            execute_chat_tool(name, &input).await
+        };
+        match result {
+            ChatToolResult::Success(text) => ToolOutput::ok(text),
+            ChatToolResult::Error(e) => match e.strip_prefix("NEEDS_APPROVAL|") {
+                Some(details) => ToolOutput::error(format!(
+                    "Command needs approval in the Sentinel app first: {}",
+                    details
+                )),
+                None => ToolOutput::error(e),
+            },
+        }
+    }
+
+    /// Validate path arguments in place and default them to the first root
+    fn resolve_path_args(&self, name: &str, input: &mut Map<String, Value>) -> Result<(), String> {
+        input.remove("force");
+
+        for key in PATH_ARGS {
+            if let Some(value) = input.get(*key) {
+                let raw = value
+                    .as_str()
+                    .ok_or_else(|| format!("'{}' must be a string", key))?;
+                let resolved = self.resolve_path(raw)?;
+                input.insert(key.to_string(), json!(resolved.to_string_lossy()));
+            }
+        }
+
+        // Tools that default to the home folder or cwd stay inside the roots
+        let default_key = match name {
+            "search_hybrid" => Some("directory"),
+            "shell" => Some("working_dir"),
+            "grep" => Some("path"),
+            _ => None,
+        };
+        if let (Some(key), Some(root)) = (default_key, self.roots.first()) {
+            input
+                .entry(key)
+                .or_insert_with(|| json!(root.to_string_lossy()));
+        }
+        Ok(())
+    }
+
+    /// Canonicalize a client path and check it against the protected paths and roots
+    fn resolve_path(&self, raw: &str) -> Result<PathBuf, String> {
+        let path = PathValidator::validate_for_read(Path::new(raw), None)?;
+        if self.is_inside_roots(&path) {
+            Ok(path)
+        } else {
+            Err(format!(
+                "Access denied: {} is outside the folders this server may access",
+                path.display()
+            ))
+        }
+    }
+
+    fn is_inside_roots(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    // ------------------------------------------------------------------------
+    // Planning (V2 tools)
+    // ------------------------------------------------------------------------
+
+    fn open_folder(&mut self, args: &Value) -> ToolOutput {
+        let raw = match args.get("path").and_then(|p| p.as_str()) {
+            Some(p) => p,
+            None => return ToolOutput::error("Missing 'path' parameter"),
+        };
+        let root = match self.resolve_path(raw).and_then(|p| api::validate_folder(&p)) {
+            Ok(root) => root,
+            Err(e) => return ToolOutput::error(e),
+        };
+
+        match api::open_rules_vfs(&root, &[]) {
+            Ok(vfs) => {
+                let text = format!(
+                    "Opened {} ({} files indexed). Use apply_organization_rules to plan, then commit_plan.",
+                    root.display(),
+                    vfs.file_count()
+                );
+                self.folder = Some(OpenFolder { root, vfs });
+                ToolOutput::ok(text)
+            }
+            Err(e) => ToolOutput::error(e),
+        }
+    }
+
+    fn call_v2_tool(&mut self, name: &str, args: &Value) -> ToolOutput {
+        let folder = match self.folder.as_mut() {
+            Some(folder) => folder,
+            None => return ToolOutput::error("No folder open. Call open_folder first."),
+        };
+
+        match execute_v2_tool(name, args, &mut folder.vfs) {
+            V2ToolResult::Continue(text) => ToolOutput::ok(text),
+            V2ToolResult::Error(e) => ToolOutput::error(e),
+            V2ToolResult::Commit(plan) => {
+                if plan.operations.is_empty() {
+                    return ToolOutput::ok(format!(
+                        "{} is already organized: the plan has no operations.",
+                        folder.root.display()
+                    ));
+                }
+
+                let mut text = format!(
+                    "Committed plan {} with {} operations:\n",
+                    plan.plan_id,
+                    plan.operations.len()
+                );
+                for change in api::plan_changes(&plan) {
+                    text.push_str(&format!("  {}\n", change));
+                    if let Some(why) = &change.why {
+                        text.push_str(&format!("      {}\n", why));
+                    }
+                }
+                text.push_str(&format!(
+                    "\nCall execute_plan with plan_id \"{}\" and confirm: true to apply it.",
+                    plan.plan_id
+                ));
+                self.plans.insert(plan.plan_id.clone(), plan);
+                ToolOutput::ok(text)
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Execution (WAL)
+    // ------------------------------------------------------------------------
+
+    fn committed_plan(&self, args: &Value) -> Result<OrganizePlan, String> {
+        let plan_id = args
+            .get("plan_id")
+            .and_then(|p| p.as_str())
+            .ok_or("Missing 'plan_id' parameter")?;
+        self.plans
+            .get(plan_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown plan '{}'. Plans must come from commit_plan in this session.", plan_id))
+    }
+
+    async fn execute_plan(&mut self, args: &Value) -> ToolOutput {
+        let plan = match self.committed_plan(args) {
+            Ok(plan) => plan,
+            Err(e) => return ToolOutput::error(e),
+        };
+        if args.get("confirm").and_then(|c| c.as_bool()) != Some(true) {
+            return ToolOutput::error("Set confirm: true to execute the plan");
+        }
+        let policy = match args.get("conflict").and_then(|c| c.as_str()) {
+            Some("auto_rename") | None => ConflictPolicy::AutoRename,
+            Some("skip") => ConflictPolicy::Skip,
+            Some("fail") => ConflictPolicy::Fail,
+            Some(other) => return ToolOutput::error(format!("Unknown conflict policy: {}", other)),
+        };
+
+        if let Err(e) = self.check_plan(&plan) {
+            return ToolOutput::error(e);
+        }
+        match WALManager::new().load_journal(&plan.plan_id) {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                return ToolOutput::error(format!(
+                    "Plan {} was already executed. Use undo_plan to roll it back.",
+                    plan.plan_id
+                ))
+            }
+            Err(e) => return ToolOutput::error(e.message),
+        }
+
+        match api::preview_plan(&plan).await {
+            Ok(preview) if !preview.valid => {
+                return ToolOutput::error(format!(
+                    "Plan failed simulation, nothing was changed:\n  {}",
+                    preview.errors.join("\n  ")
+                ))
+            }
+            Ok(_) => {}
+            Err(e) => return ToolOutput::error(e),
+        }
+
+        let config = ExecutionConfig {
+            on_destination_exists: policy,
+        };
+        match api::preflight_plan(&plan, &config) {
+            Ok(report) => {
+                let problems = report.problems();
+                if !problems.is_empty() {
+                    return ToolOutput::error(format!(
+                        "Plan failed preflight, nothing was changed:\n  {}",
+                        problems.join("\n  ")
+                    ));
+                }
+            }
+            Err(e) => return ToolOutput::error(e),
+        }
+
+        let result = match api::execute_plan(&plan, config, Arc::new(NoopSink)).await {
+            Ok(result) => result,
+            Err(e) => return ToolOutput::error(e),
+        };
+
+        // The indexed folder no longer matches the disk
+        self.folder = None;
+
+        let mut text = format!(
+            "Executed plan {}: {} completed, {} renamed, {} skipped, {} failed.\n",
+            plan.plan_id,
+            result.completed_count,
+            result.renamed_count,
+            result.skipped_count,
+            result.failed_count
+        );
+        for error in &result.errors {
+            text.push_str(&format!("  failed: {}\n", error));
+        }
+        text.push_str("Undo with undo_plan. Call open_folder again before planning more changes.");
+
+        ToolOutput {
+            text,
+            is_error: !result.success,
+        }
+    }
+
+    fn undo_plan(&mut self, args: &Value) -> ToolOutput {
+        let plan = match self.committed_plan(args) {
+            Ok(plan) => plan,
+            Err(e) => return ToolOutput::error(e),
+        };
+
+        match api::rollback_journal(&plan.plan_id) {
+            Ok(result) => {
+                self.folder = None;
+                let mut text = format!(
+                    "Rolled back plan {}: {} operations undone, {} failed.",
+                    plan.plan_id, result.completed_count, result.failed_count
+                );
+                for error in &result.errors {
+                    text.push_str(&format!("\n  failed: {}", error));
+                }
+                ToolOutput {
+                    text,
+                    is_error: !result.success,
+                }
+            }
+            Err(e) => ToolOutput::error(e),
+        }
+    }
+
+    /// Every path of the plan must be absolute, unprotected and inside the roots
+    fn check_plan(&self, plan: &OrganizePlan) -> Result<(), String> {
+        for op in &plan.operations {
+            for p in [&op.source, &op.destination, &op.path].into_iter().flatten() {
+                let path = Path::new(p);
+                if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+                    return Err(format!("Operation '{}' has an invalid path: {}", op.op_id, p));
+                }
+                if PathValidator::is_protected_path(path) || !self.is_inside_roots(path) {
+                    return Err(format!(
+                        "Operation '{}' touches {} outside the allowed folders",
+                        op.op_id, p
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tools_in(dir: &TempDir) -> SentinelTools {
+        SentinelTools::new(&[dir.path().to_path_buf()]).unwrap()
+    }
+
+    #[test]
+    fn test_requires_a_root() {
+        assert!(SentinelTools::new(&[]).is_err());
+    }
+
+    #[test]
+    fn test_definitions() {
+        let defs = SentinelTools::definitions();
+        let names: Vec<&str> = defs.iter().filter_map(|t| t["name"].as_str()).collect();
+
+        for name in CHAT_TOOLS.iter().chain(V2_TOOLS) {
+            assert!(names.contains(name), "missing {}", name);
+        }
+        assert!(names.contains(&"execute_plan"));
+        assert!(!names.contains(&"bash"));
+        assert!(names.iter().all(|n| SentinelTools::has_tool(n)));
+
+        for tool in &defs {
+            assert!(tool["inputSchema"].is_object());
+            assert!(tool.get("input_schema").is_none());
+        }
+        let shell = defs.iter().find(|t| t["name"] == "shell").unwrap();
+        assert!(shell["inputSchema"]["properties"].get("force").is_none());
+    }
+
+    #[test]
+    fn test_path_args_confined_to_roots() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let tools = tools_in(&root);
+        std::fs::create_dir(root.path().join("docs")).unwrap();
+
+        let mut input = Map::new();
+        input.insert("path".into(), json!(root.path().join("docs/../docs")));
+        input.insert("force".into(), json!(true));
+        tools.resolve_path_args("list_directory", &mut input).unwrap();
+        let canonical = root.path().canonicalize().unwrap().join("docs");
+        assert_eq!(input["path"], json!(canonical.to_string_lossy()));
+        assert!(input.get("force").is_none());
+
+        let mut input = Map::new();
+        input.insert("path".into(), json!(outside.path()));
+        assert!(tools.resolve_path_args("read_file", &mut input).is_err());
+
+        // Tools that default to home/cwd are pinned to the first root
+        let mut input = Map::new();
+        tools.resolve_path_args("shell", &mut input).unwrap();
+        assert_eq!(
+            input["working_dir"],
+            json!(root.path().canonicalize().unwrap().to_string_lossy())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_requires_open_folder_and_committed_plan() {
+        let root = TempDir::new().unwrap();
+        let mut tools = tools_in(&root);
+
+        let out = tools.call("preview_operations", &json!({})).await;
+        assert!(out.is_error);
+        assert!(out.text.contains("open_folder"));
+
+        let out = tools
+            .call("execute_plan", &json!({"plan_id": "plan-1", "confirm": true}))
+            .await;
+        assert!(out.is_error);
+        assert!(out.text.contains("commit_plan"));
+    }
+
+    #[test]
+    fn test_check_plan() {
+        let root = TempDir::new().unwrap();
+        let tools = tools_in(&root);
+        let base = root.path().canonicalize().unwrap();
+        let inside = |p: &str| base.join(p).to_string_lossy().to_string();
+
+        let mut plan = OrganizePlan {
+            plan_id: "plan-1".to_string(),
+            description: String::new(),
+            operations: vec![crate::jobs::OrganizeOperation {
+                op_id: "op-1".to_string(),
+                op_type: "move".to_string(),
+                source: Some(inside("a.pdf")),
+                destination: Some(inside("Docs/a.pdf")),
+                path: None,
+                new_name: None,
+                attributes: Default::default(),
+            }],
+            target_folder: base.to_string_lossy().to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        };
+        assert!(tools.check_plan(&plan).is_ok());
+
+        plan.operations[0].destination = Some("/etc/a.pdf".to_string());
+        assert!(tools.check_plan(&plan).is_err());
+
+        plan.operations[0].destination = Some(inside("../a.pdf"));
+        assert!(tools.check_plan(&plan).is_err());
+    }
+}
@@ -123,4 +123,33 @@ impl FileEntry {
             is_hidden,
         })
     }
+
+    /// Create a FileEntry from a scan snapshot entry (symlinks are not resolved)
+    pub fn from_scan_entry(entry: &crate::scan::ScanEntry) -> Self {
+        let extension = if entry.is_file() {
+            entry.path.extension().map(|e| e.to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        let mime_type = extension.as_ref().and_then(|ext| {
+            mime_guess::from_ext(ext)
+                .first()
+                .map(|m| m.to_string())
+        });
+
+        Self {
+            name: entry.name.clone(),
+            path: entry.path.to_string_lossy().to_string(),
+            is_directory: entry.is_dir(),
+            is_file: entry.is_file(),
+            is_symlink: entry.kind == crate::scan::EntryKind::Symlink,
+            size: entry.size,
+            modified_at: entry.modified_at,
+            created_at: entry.created_at,
+            extension,
+            mime_type,
+            is_hidden: entry.name.starts_with('.'),
+        }
+    }
 }
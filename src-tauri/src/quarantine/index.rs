@@ -0,0 +1,211 @@
+//! SQLite index of quarantined items
+//!
+//! One index covers every quarantine area, so listing and lookups by
+//! original path don't touch the areas at all. Items on a volume that is
+//! not mounted stay listed until the volume comes back.
+
+use super::QuarantinedItem;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+/// Index database, kept in the home-volume area
+pub const INDEX_FILE: &str = "index.db";
+
+const SCHEMA_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS items (
+        path TEXT PRIMARY KEY,
+        area TEXT NOT NULL,
+        name TEXT NOT NULL,
+        original_path TEXT NOT NULL,
+        quarantined_at INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        is_directory INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_items_original ON items(original_path);
+    CREATE INDEX IF NOT EXISTS idx_items_area ON items(area, quarantined_at);
+
+    CREATE TABLE IF NOT EXISTS areas (
+        path TEXT PRIMARY KEY,
+        volume TEXT NOT NULL
+    );
+"#;
+
+const ITEM_COLUMNS: &str = "path, name, original_path, quarantined_at, size, is_directory";
+
+/// Items and bytes held by one area
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AreaTotals {
+    pub area: PathBuf,
+    pub volume: String,
+    pub items: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct QuarantineIndex {
+    db_path: PathBuf,
+}
+
+impl QuarantineIndex {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            db_path: dir.join(INDEX_FILE),
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.db_path.exists()
+    }
+
+    fn conn(&self) -> Result<rusqlite::Connection, String> {
+        if let Some(parent) = self.db_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+        }
+        let conn = rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open quarantine index: {}", e))?;
+        conn.execute_batch(SCHEMA_SQL)
+            .map_err(|e| format!("Failed to initialize quarantine index: {}", e))?;
+        Ok(conn)
+    }
+
+    pub fn insert(&self, item: &QuarantinedItem) -> Result<(), String> {
+        let area = item.path.parent().unwrap_or(&item.path);
+        self.conn()?
+            .execute(
+                "INSERT OR REPLACE INTO items (path, area, name, original_path, quarantined_at, size, is_directory)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    item.path.to_string_lossy(),
+                    area.to_string_lossy(),
+                    item.name,
+                    item.original_path.to_string_lossy(),
+                    item.quarantine_date.timestamp_millis(),
+                    item.size as i64,
+                    item.is_directory,
+                ],
+            )
+            .map_err(|e| format!("Failed to index quarantined item: {}", e))?;
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &Path) -> Result<bool, String> {
+        let removed = self
+            .conn()?
+            .execute(
+                "DELETE FROM items WHERE path = ?1",
+                params![path.to_string_lossy()],
+            )
+            .map_err(|e| format!("Failed to update quarantine index: {}", e))?;
+        Ok(removed > 0)
+    }
+
+    pub fn get(&self, path: &Path) -> Result<Option<QuarantinedItem>, String> {
+        self.conn()?
+            .query_row(
+                &format!("SELECT {} FROM items WHERE path = ?1", ITEM_COLUMNS),
+                params![path.to_string_lossy()],
+                row_to_item,
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query quarantine index: {}", e))
+    }
+
+    /// Most recent item quarantined from `original_path`
+    pub fn by_original(&self, original_path: &Path) -> Result<Option<QuarantinedItem>, String> {
+        self.conn()?
+            .query_row(
+                &format!(
+                    "SELECT {} FROM items WHERE original_path = ?1 ORDER BY quarantined_at DESC LIMIT 1",
+                    ITEM_COLUMNS
+                ),
+                params![original_path.to_string_lossy()],
+                row_to_item,
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query quarantine index: {}", e))
+    }
+
+    /// All items, newest first
+    pub fn list(&self) -> Result<Vec<QuarantinedItem>, String> {
+        self.query(
+            &format!(
+                "SELECT {} FROM items ORDER BY quarantined_at DESC",
+                ITEM_COLUMNS
+            ),
+            &[],
+        )
+    }
+
+    /// Items of one area, oldest first (eviction order)
+    pub fn in_area(&self, area: &Path) -> Result<Vec<QuarantinedItem>, String> {
+        let area = area.to_string_lossy().to_string();
+        self.query(
+            &format!(
+                "SELECT {} FROM items WHERE area = ?1 ORDER BY quarantined_at ASC",
+                ITEM_COLUMNS
+            ),
+            &[&area],
+        )
+    }
+
+    fn query(
+        &self,
+        sql: &str,
+        args: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<QuarantinedItem>, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to query quarantine index: {}", e);
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql).map_err(db_err)?;
+        let rows = stmt.query_map(args, row_to_item).map_err(db_err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+    }
+
+    pub fn add_area(&self, area: &Path, volume: &str) -> Result<(), String> {
+        self.conn()?
+            .execute(
+                "INSERT OR REPLACE INTO areas (path, volume) VALUES (?1, ?2)",
+                params![area.to_string_lossy(), volume],
+            )
+            .map_err(|e| format!("Failed to register quarantine area: {}", e))?;
+        Ok(())
+    }
+
+    /// Every registered area with its totals (areas without items included)
+    pub fn areas(&self) -> Result<Vec<AreaTotals>, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to query quarantine index: {}", e);
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT a.path, a.volume, COUNT(i.path), COALESCE(SUM(i.size), 0)
+                 FROM areas a LEFT JOIN items i ON i.area = a.path
+                 GROUP BY a.path ORDER BY a.path",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(AreaTotals {
+                    area: PathBuf::from(row.get::<_, String>(0)?),
+                    volume: row.get(1)?,
+                    items: row.get::<_, i64>(2)? as usize,
+                    bytes: row.get::<_, i64>(3)? as u64,
+                })
+            })
+            .map_err(db_err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+    }
+}
+
+fn row_to_item(row: &rusqlite::Row) -> rusqlite::Result<QuarantinedItem> {
+    let millis: i64 = row.get(3)?;
+    Ok(QuarantinedItem {
+        path: PathBuf::from(row.get::<_, String>(0)?),
+        name: row.get(1)?,
+        original_path: PathBuf::from(row.get::<_, String>(2)?),
+        quarantine_date: DateTime::<Utc>::from_timestamp_millis(millis).unwrap_or_default(),
+        size: row.get::<_, i64>(4)? as u64,
+        is_directory: row.get(5)?,
+    })
+}
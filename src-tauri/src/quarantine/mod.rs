@@ -1,14 +1,35 @@
 //! Quarantine Module
 //!
 //! Provides safe deletion functionality by moving files to a quarantine
-//! directory instead of permanently deleting them. Supports restoration
+//! area instead of permanently deleting them. Supports restoration
 //! and automatic cleanup of old entries.
+//!
+//! Each volume has its own area so quarantining is always a rename: the
+//! home volume uses `~/.sentinel/quarantine`, other volumes (external
+//! drives, network shares) a `.sentinel-quarantine` folder at their mount
+//! point. Items are tracked in a SQLite index (`index`) with a JSON sidecar
+//! next to each item, so the index can be rebuilt from the areas. Expiry
+//! and per-volume quotas follow a [`QuarantinePolicy`] (`policy`).
+
+mod index;
+mod policy;
+pub(crate) mod volume;
+
+pub use policy::{FolderRetention, QuarantinePolicy, DEFAULT_RETENTION_DAYS};
+pub use volume::VOLUME_AREA_DIR;
 
+use crate::wal::{WALManager, WALOperationType, WALStatus};
 use chrono::{DateTime, Duration, Utc};
+use index::{QuarantineIndex, INDEX_FILE};
+use policy::POLICY_FILE;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Suffix of the metadata sidecar written next to each item
+const SIDECAR_EXTENSION: &str = "quarantine.json";
+
 /// A quarantined file or directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -44,44 +65,98 @@ pub struct CleanupStats {
 
     /// Number of items that failed to delete
     pub errors: usize,
+
+    /// Of the removed items, how many were evicted to stay under a quota
+    #[serde(default)]
+    pub items_evicted: usize,
+}
+
+/// Space used by one volume's quarantine area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineArea {
+    pub path: PathBuf,
+    pub volume: String,
+    pub items: usize,
+    pub bytes: u64,
+    pub quota_bytes: Option<u64>,
+    /// False while the volume is not mounted
+    pub available: bool,
+}
+
+/// Result of [`QuarantineManager::rebuild_index`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildStats {
+    /// Items indexed from their sidecar
+    pub indexed: usize,
+    /// Items with no sidecar, indexed from the file itself (original path unknown)
+    pub orphans: usize,
+    /// Index entries whose item no longer exists
+    pub removed: usize,
 }
 
-/// Manages the quarantine directory for safe deletion
+/// Manages the quarantine areas for safe deletion
 ///
-/// Files are moved to a quarantine directory instead of being permanently
-/// deleted. They can be restored or are automatically cleaned up after
-/// the retention period expires.
+/// Files are moved to their volume's quarantine area instead of being
+/// permanently deleted. They can be restored, or are cleaned up when their
+/// retention expires or their area goes over its quota.
 #[derive(Debug, Clone)]
 pub struct QuarantineManager {
-    /// Base path for quarantine storage
+    /// Home-volume area; also holds the index and the policy
     base_path: PathBuf,
 
-    /// Number of days to retain quarantined items
-    retention_days: u32,
+    policy: QuarantinePolicy,
+
+    index: QuarantineIndex,
 }
 
 impl QuarantineManager {
     /// Create a new QuarantineManager with default settings
     ///
-    /// Uses ~/.sentinel/quarantine as the base path and 30 days retention
+    /// Uses ~/.sentinel/quarantine as the base path and the saved policy
+    /// (30 days retention when none is saved)
     pub fn new() -> Result<Self, String> {
         let base_path = dirs::home_dir()
             .ok_or("Could not determine home directory")?
             .join(".sentinel")
             .join("quarantine");
 
-        Ok(Self {
-            base_path,
-            retention_days: 30,
-        })
+        Ok(Self::with_config(base_path, DEFAULT_RETENTION_DAYS))
     }
 
     /// Create a QuarantineManager with custom settings
+    ///
+    /// `retention_days` applies when no policy has been saved in `base_path`.
     pub fn with_config(base_path: PathBuf, retention_days: u32) -> Self {
-        Self {
+        let fallback = QuarantinePolicy {
+            retention_days: Some(retention_days),
+            ..Default::default()
+        };
+        let policy = QuarantinePolicy::load_from(&base_path.join(POLICY_FILE), fallback.clone())
+            .unwrap_or_else(|e| {
+                eprintln!("[Quarantine] {}", e);
+                fallback
+            });
+
+        let manager = Self {
+            index: QuarantineIndex::new(&base_path),
             base_path,
-            retention_days,
+            policy,
+        };
+
+        // Items quarantined before the index existed only have sidecars
+        if !manager.index.exists() && manager.base_path.exists() {
+            match manager.rebuild_index() {
+                Ok(stats) => eprintln!(
+                    "[Quarantine] Indexed {} existing items ({} without metadata)",
+                    stats.indexed + stats.orphans,
+                    stats.orphans
+                ),
+                Err(e) => eprintln!("[Quarantine] Failed to build index: {}", e),
+            }
         }
+        manager
     }
 
     /// Get the base quarantine path
@@ -89,32 +164,88 @@ impl QuarantineManager {
         &self.base_path
     }
 
-    /// Get the retention period in days
-    pub fn retention_days(&self) -> u32 {
-        self.retention_days
+    /// Get the default retention period in days (`None` keeps items until evicted)
+    pub fn retention_days(&self) -> Option<u32> {
+        self.policy.retention_days
     }
 
-    /// Ensure the quarantine directory exists
-    fn ensure_quarantine_dir(&self) -> Result<(), String> {
-        if !self.base_path.exists() {
-            fs::create_dir_all(&self.base_path)
-                .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
-        }
+    pub fn policy(&self) -> &QuarantinePolicy {
+        &self.policy
+    }
+
+    /// Replace and save the retention and quota policy
+    pub fn set_policy(&mut self, policy: QuarantinePolicy) -> Result<(), String> {
+        policy.validate()?;
+        policy.save_to(&self.base_path.join(POLICY_FILE))?;
+        self.policy = policy;
         Ok(())
     }
 
-    /// Generate a unique quarantine path for an item
-    fn generate_quarantine_path(&self, original_path: &Path) -> PathBuf {
+    /// The quarantine area on the same volume as `path`
+    pub fn area_for(&self, path: &Path) -> Result<PathBuf, String> {
+        let parent = path.parent().unwrap_or(path);
+        let volume = volume::volume_id(parent)
+            .ok_or_else(|| format!("Could not determine the volume of {}", path.display()))?;
+
+        if volume::volume_id(&self.base_path).as_ref() == Some(&volume) {
+            return Ok(self.base_path.clone());
+        }
+        let mount = volume::mount_point(parent)
+            .ok_or_else(|| format!("Could not determine the mount point of {}", path.display()))?;
+        Ok(mount.join(VOLUME_AREA_DIR))
+    }
+
+    /// Create the area for `path` and register it in the index
+    fn ensure_area(&self, path: &Path) -> Result<PathBuf, String> {
+        let area = self.area_for(path)?;
+        if !area.exists() {
+            fs::create_dir_all(&area).map_err(|e| {
+                format!("Failed to create quarantine area {}: {}", area.display(), e)
+            })?;
+        }
+        let volume = volume::volume_id(&area).unwrap_or_default();
+        self.index.add_area(&area, &volume)?;
+        Ok(area)
+    }
+
+    /// Generate a unique quarantine path for an item, avoiding `reserved`
+    fn generate_quarantine_path(
+        &self,
+        area: &Path,
+        original_path: &Path,
+        reserved: &HashSet<PathBuf>,
+    ) -> PathBuf {
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f").to_string();
         let name = original_path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
-        self.base_path.join(format!("{}_{}", timestamp, name))
+        let taken = |p: &PathBuf| p.exists() || sidecar_path(p).exists() || reserved.contains(p);
+        let mut candidate = area.join(format!("{}_{}", timestamp, name));
+        let mut counter = 1;
+        while taken(&candidate) {
+            candidate = area.join(format!("{}_{}_{}", timestamp, counter, name));
+            counter += 1;
+        }
+        candidate
+    }
+
+    /// Pick the quarantine path for `path` without moving anything, so it
+    /// can be journaled ahead of [`quarantine_to`](Self::quarantine_to).
+    /// Paths already in `reserved` are avoided and the new one is added.
+    pub fn reserve_path(
+        &self,
+        path: &Path,
+        reserved: &mut HashSet<PathBuf>,
+    ) -> Result<PathBuf, String> {
+        let area = self.area_for(path)?;
+        let quarantine_path = self.generate_quarantine_path(&area, path, reserved);
+        reserved.insert(quarantine_path.clone());
+        Ok(quarantine_path)
     }
 
-    /// Move a file or directory to quarantine
+    /// Move a file or directory to its volume's quarantine area
     ///
     /// # Arguments
     /// * `path` - The path to quarantine
@@ -123,42 +254,59 @@ impl QuarantineManager {
     /// * `Ok(PathBuf)` - The path where the item was quarantined
     /// * `Err(String)` - Error message if quarantine failed
     pub fn quarantine(&self, path: &PathBuf) -> Result<PathBuf, String> {
-        self.ensure_quarantine_dir()?;
-
         // Validate path exists
-        if !path.exists() {
-            return Err(format!("Path does not exist: {}", path.display()));
-        }
+        fs::symlink_metadata(path)
+            .map_err(|_| format!("Path does not exist: {}", path.display()))?;
 
-        // Get metadata before move
-        let metadata = fs::metadata(path)
-            .map_err(|e| format!("Failed to get metadata: {}", e))?;
+        let area = self.area_for(path)?;
+        let quarantine_path = self.generate_quarantine_path(&area, path, &HashSet::new());
+        self.quarantine_to(path, &quarantine_path)?;
+        Ok(quarantine_path)
+    }
+
+    /// Move a file or directory to a path from [`reserve_path`](Self::reserve_path)
+    pub fn quarantine_to(&self, path: &Path, quarantine_path: &Path) -> Result<(), String> {
+        let metadata = fs::symlink_metadata(path)
+            .map_err(|_| format!("Path does not exist: {}", path.display()))?;
 
-        let quarantine_path = self.generate_quarantine_path(path);
+        // Only the area of the item's own volume keeps this a rename
+        let area = self.ensure_area(path)?;
+        if quarantine_path.parent() != Some(area.as_path()) {
+            return Err(format!(
+                "{} is not in the quarantine area for {}",
+                quarantine_path.display(),
+                path.display()
+            ));
+        }
+        if quarantine_path.exists() {
+            return Err(format!(
+                "Quarantine path already exists: {}",
+                quarantine_path.display()
+            ));
+        }
 
-        // Move the file/directory
-        fs::rename(path, &quarantine_path)
+        fs::rename(path, quarantine_path)
             .map_err(|e| format!("Failed to move to quarantine: {}", e))?;
 
         // Save metadata for restoration
         let item = QuarantinedItem {
-            path: quarantine_path.clone(),
+            path: quarantine_path.to_path_buf(),
             name: path
                 .file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default(),
-            original_path: path.clone(),
+            original_path: path.to_path_buf(),
             quarantine_date: Utc::now(),
             size: if metadata.is_dir() {
-                self.calculate_dir_size(&quarantine_path)
+                self.calculate_dir_size(quarantine_path)
             } else {
                 metadata.len()
             },
             is_directory: metadata.is_dir(),
         };
 
-        // Save item metadata
-        self.save_item_metadata(&quarantine_path, &item)?;
+        self.save_item_metadata(quarantine_path, &item)?;
+        self.index.insert(&item)?;
 
         eprintln!(
             "[Quarantine] Moved {} to {}",
@@ -166,29 +314,29 @@ impl QuarantineManager {
             quarantine_path.display()
         );
 
-        Ok(quarantine_path)
+        // Quotas are enforced by `cleanup`, never while a plan may still be
+        // quarantining (and later restoring) items
+        Ok(())
     }
 
     /// Save metadata for a quarantined item
-    fn save_item_metadata(&self, quarantine_path: &PathBuf, item: &QuarantinedItem) -> Result<(), String> {
-        let metadata_path = quarantine_path.with_extension("quarantine.json");
+    fn save_item_metadata(
+        &self,
+        quarantine_path: &Path,
+        item: &QuarantinedItem,
+    ) -> Result<(), String> {
         let json = serde_json::to_string_pretty(item)
             .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
-        fs::write(&metadata_path, json)
+        fs::write(sidecar_path(quarantine_path), json)
             .map_err(|e| format!("Failed to write metadata: {}", e))?;
         Ok(())
     }
 
-    /// Load metadata for a quarantined item
-    fn load_item_metadata(&self, quarantine_path: &PathBuf) -> Option<QuarantinedItem> {
-        let metadata_path = quarantine_path.with_extension("quarantine.json");
-        if metadata_path.exists() {
-            fs::read_to_string(&metadata_path)
-                .ok()
-                .and_then(|json| serde_json::from_str(&json).ok())
-        } else {
-            None
-        }
+    /// Load metadata for a quarantined item from its sidecar
+    fn load_item_metadata(&self, quarantine_path: &Path) -> Option<QuarantinedItem> {
+        fs::read_to_string(sidecar_path(quarantine_path))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
     }
 
     /// Restore a quarantined item to its original location
@@ -213,13 +361,15 @@ impl QuarantineManager {
         }
 
         // Determine restoration path
-        let restore_path = if let Some(path) = original_path {
-            path
-        } else {
-            // Try to load from metadata
-            self.load_item_metadata(quarantine_path)
+        let restore_path = match original_path {
+            Some(path) => path,
+            None => self
+                .index
+                .get(quarantine_path)?
+                .or_else(|| self.load_item_metadata(quarantine_path))
                 .map(|item| item.original_path)
-                .ok_or_else(|| "No original path found and none provided".to_string())?
+                .filter(|p| !p.as_os_str().is_empty())
+                .ok_or_else(|| "No original path found and none provided".to_string())?,
         };
 
         // Check if original location is available
@@ -242,11 +392,8 @@ impl QuarantineManager {
         fs::rename(quarantine_path, &restore_path)
             .map_err(|e| format!("Failed to restore from quarantine: {}", e))?;
 
-        // Clean up metadata file
-        let metadata_path = quarantine_path.with_extension("quarantine.json");
-        if metadata_path.exists() {
-            let _ = fs::remove_file(&metadata_path);
-        }
+        let _ = fs::remove_file(sidecar_path(quarantine_path));
+        self.index.remove(quarantine_path)?;
 
         eprintln!(
             "[Quarantine] Restored {} to {}",
@@ -257,190 +404,254 @@ impl QuarantineManager {
         Ok(())
     }
 
-    /// Clean up old quarantined items that have exceeded the retention period
+    /// Clean up quarantined items whose retention has expired, then evict
+    /// the oldest items of any area over its quota. Items that undoing a
+    /// saved WAL journal would restore are never evicted.
     ///
     /// # Returns
     /// * `Ok(CleanupStats)` - Statistics about the cleanup operation
     /// * `Err(String)` - Error message if cleanup failed
     pub fn cleanup(&self) -> Result<CleanupStats, String> {
+        self.cleanup_at(Utc::now(), &journaled_items())
+    }
+
+    fn cleanup_at(&self, now: DateTime<Utc>, keep: &HashSet<PathBuf>) -> Result<CleanupStats, String> {
+        // Pick up orphaned files so they expire too
+        self.rebuild_index()?;
+
         let mut stats = CleanupStats {
             items_removed: 0,
             bytes_freed: 0,
             errors: 0,
+            items_evicted: 0,
         };
 
-        if !self.base_path.exists() {
-            return Ok(stats);
-        }
-
-        let cutoff_date = Utc::now() - Duration::days(self.retention_days as i64);
-
-        let entries = fs::read_dir(&self.base_path)
-            .map_err(|e| format!("Failed to read quarantine directory: {}", e))?;
-
-        for entry_result in entries {
-            let entry = match entry_result {
-                Ok(e) => e,
-                Err(_) => {
-                    stats.errors += 1;
-                    continue;
-                }
+        for item in self.index.list()? {
+            // Items on unmounted volumes wait until the volume is back
+            if !area_of(&item).exists() {
+                continue;
+            }
+            let Some(days) = self.policy.retention_for(&item.original_path) else {
+                continue;
             };
-
-            let path = entry.path();
-
-            // Skip metadata files
-            if path
-                .extension()
-                .map(|e| e == "json")
-                .unwrap_or(false)
-            {
+            if item.quarantine_date >= now - Duration::days(days as i64) {
                 continue;
             }
 
-            // Check if item should be cleaned up
-            let should_cleanup = if let Some(item) = self.load_item_metadata(&path) {
-                item.quarantine_date < cutoff_date
-            } else {
-                // No metadata - check file modification time
-                entry
-                    .metadata()
-                    .ok()
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                    .map(|d| {
-                        let file_time = chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)
-                            .unwrap_or_else(Utc::now);
-                        file_time < cutoff_date
-                    })
-                    .unwrap_or(false)
-            };
+            match self.remove_item(&item) {
+                Ok(()) => {
+                    stats.items_removed += 1;
+                    stats.bytes_freed += item.size;
+                    eprintln!("[Quarantine] Cleaned up: {}", item.path.display());
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[Quarantine] Failed to clean up {}: {}",
+                        item.path.display(),
+                        e
+                    );
+                    stats.errors += 1;
+                }
+            }
+        }
 
-            if should_cleanup {
-                let size = if path.is_dir() {
-                    self.calculate_dir_size(&path)
-                } else {
-                    fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
-                };
+        if let Some(quota) = self.policy.quota_bytes {
+            for area in self.index.areas()? {
+                if area.area.exists() {
+                    let evicted = self.enforce_quota(&area.area, quota, keep);
+                    stats.items_removed += evicted.items_removed;
+                    stats.items_evicted += evicted.items_evicted;
+                    stats.bytes_freed += evicted.bytes_freed;
+                    stats.errors += evicted.errors;
+                }
+            }
+        }
 
-                let result = if path.is_dir() {
-                    fs::remove_dir_all(&path)
-                } else {
-                    fs::remove_file(&path)
-                };
+        eprintln!(
+            "[Quarantine] Cleanup complete: {} items removed ({} evicted), {} bytes freed",
+            stats.items_removed, stats.items_evicted, stats.bytes_freed
+        );
 
-                match result {
-                    Ok(_) => {
-                        stats.items_removed += 1;
-                        stats.bytes_freed += size;
+        Ok(stats)
+    }
 
-                        // Remove metadata file
-                        let metadata_path = path.with_extension("quarantine.json");
-                        let _ = fs::remove_file(&metadata_path);
+    /// Delete the oldest items of `area` until it fits in `quota` (never those in `keep`)
+    fn enforce_quota(&self, area: &Path, quota: u64, keep: &HashSet<PathBuf>) -> CleanupStats {
+        let mut stats = CleanupStats {
+            items_removed: 0,
+            bytes_freed: 0,
+            errors: 0,
+            items_evicted: 0,
+        };
+        let items = match self.index.in_area(area) {
+            Ok(items) => items,
+            Err(e) => {
+                eprintln!("[Quarantine] {}", e);
+                stats.errors += 1;
+                return stats;
+            }
+        };
 
-                        eprintln!("[Quarantine] Cleaned up: {}", path.display());
-                    }
-                    Err(e) => {
-                        eprintln!("[Quarantine] Failed to clean up {}: {}", path.display(), e);
-                        stats.errors += 1;
-                    }
+        let mut total: u64 = items.iter().map(|item| item.size).sum();
+        for item in items {
+            if total <= quota {
+                break;
+            }
+            if keep.contains(&item.path) {
+                continue;
+            }
+            match self.remove_item(&item) {
+                Ok(()) => {
+                    total = total.saturating_sub(item.size);
+                    stats.items_removed += 1;
+                    stats.items_evicted += 1;
+                    stats.bytes_freed += item.size;
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[Quarantine] Failed to evict {}: {}",
+                        item.path.display(),
+                        e
+                    );
+                    stats.errors += 1;
                 }
             }
         }
+        stats
+    }
 
-        eprintln!(
-            "[Quarantine] Cleanup complete: {} items removed, {} bytes freed",
-            stats.items_removed, stats.bytes_freed
-        );
+    /// Delete an item, its sidecar and its index entry
+    fn remove_item(&self, item: &QuarantinedItem) -> Result<(), String> {
+        let result = match fs::symlink_metadata(&item.path) {
+            Ok(m) if m.is_dir() => fs::remove_dir_all(&item.path),
+            Ok(_) => fs::remove_file(&item.path),
+            Err(_) => Ok(()),
+        };
+        result.map_err(|e| e.to_string())?;
 
-        Ok(stats)
+        let _ = fs::remove_file(sidecar_path(&item.path));
+        self.index.remove(&item.path)?;
+        Ok(())
     }
 
-    /// List all quarantined items
+    /// List all quarantined items, newest first
     ///
     /// # Returns
     /// * `Ok(Vec<QuarantinedItem>)` - List of quarantined items
     /// * `Err(String)` - Error message if listing failed
     pub fn list(&self) -> Result<Vec<QuarantinedItem>, String> {
-        let mut items = Vec::new();
+        self.index.list()
+    }
 
-        if !self.base_path.exists() {
-            return Ok(items);
-        }
+    /// Space used by each volume's area
+    pub fn areas(&self) -> Result<Vec<QuarantineArea>, String> {
+        Ok(self
+            .index
+            .areas()?
+            .into_iter()
+            .map(|area| QuarantineArea {
+                available: area.area.exists(),
+                path: area.area,
+                volume: area.volume,
+                items: area.items,
+                bytes: area.bytes,
+                quota_bytes: self.policy.quota_bytes,
+            })
+            .collect())
+    }
 
-        let entries = fs::read_dir(&self.base_path)
-            .map_err(|e| format!("Failed to read quarantine directory: {}", e))?;
+    /// Re-sync the index with the areas: index items that are missing from
+    /// it (from their sidecar, or from the file itself when the sidecar is
+    /// gone) and drop entries whose item was deleted outside Sentinel.
+    /// Areas on unmounted volumes are left alone.
+    pub fn rebuild_index(&self) -> Result<RebuildStats, String> {
+        let mut stats = RebuildStats::default();
+
+        let mut areas: Vec<PathBuf> = self.index.areas()?.into_iter().map(|a| a.area).collect();
+        if self.base_path.exists() && !areas.contains(&self.base_path) {
+            let volume = volume::volume_id(&self.base_path).unwrap_or_default();
+            self.index.add_area(&self.base_path, &volume)?;
+            areas.push(self.base_path.clone());
+        }
 
-        for entry_result in entries {
-            let entry = match entry_result {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
+        for area in areas.iter().filter(|a| a.exists()) {
+            let entries = fs::read_dir(area)
+                .map_err(|e| format!("Failed to read quarantine area {}: {}", area.display(), e))?;
 
-            let path = entry.path();
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if is_bookkeeping(&path) || self.index.get(&path)?.is_some() {
+                    continue;
+                }
 
-            // Skip metadata files
-            if path
-                .extension()
-                .map(|e| e == "json")
-                .unwrap_or(false)
-            {
-                continue;
+                let item = match self.load_item_metadata(&path) {
+                    Some(item) => {
+                        stats.indexed += 1;
+                        QuarantinedItem {
+                            path: path.clone(),
+                            ..item
+                        }
+                    }
+                    None => match self.orphan_item(&path) {
+                        Some(item) => {
+                            stats.orphans += 1;
+                            item
+                        }
+                        None => continue,
+                    },
+                };
+                self.index.insert(&item)?;
             }
+        }
 
-            // Try to load metadata
-            if let Some(item) = self.load_item_metadata(&path) {
-                items.push(item);
-            } else {
-                // Create item from filesystem metadata
-                if let Ok(metadata) = fs::metadata(&path) {
-                    let name = path
-                        .file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_else(|| "unknown".to_string());
-
-                    // Try to extract original name from quarantine name (timestamp_name)
-                    let original_name = name
-                        .split('_')
-                        .skip(3) // Skip timestamp parts
-                        .collect::<Vec<_>>()
-                        .join("_");
-
-                    let quarantine_date = metadata
-                        .modified()
-                        .ok()
-                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                        .and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0))
-                        .unwrap_or_else(Utc::now);
-
-                    items.push(QuarantinedItem {
-                        path: path.clone(),
-                        name: if original_name.is_empty() {
-                            name
-                        } else {
-                            original_name
-                        },
-                        original_path: PathBuf::new(), // Unknown
-                        quarantine_date,
-                        size: if metadata.is_dir() {
-                            self.calculate_dir_size(&path)
-                        } else {
-                            metadata.len()
-                        },
-                        is_directory: metadata.is_dir(),
-                    });
-                }
+        for item in self.index.list()? {
+            if area_of(&item).exists() && fs::symlink_metadata(&item.path).is_err() {
+                self.index.remove(&item.path)?;
+                stats.removed += 1;
             }
         }
 
-        // Sort by quarantine date, newest first
-        items.sort_by(|a, b| b.quarantine_date.cmp(&a.quarantine_date));
+        Ok(stats)
+    }
+
+    /// Best-effort item for a file in an area that has no sidecar
+    fn orphan_item(&self, path: &Path) -> Option<QuarantinedItem> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
 
-        Ok(items)
+        // Try to extract original name from quarantine name (timestamp_name)
+        let original_name = name.split('_').skip(3).collect::<Vec<_>>().join("_");
+
+        let quarantine_date = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0))
+            .unwrap_or_else(Utc::now);
+
+        Some(QuarantinedItem {
+            path: path.to_path_buf(),
+            name: if original_name.is_empty() {
+                name
+            } else {
+                original_name
+            },
+            original_path: PathBuf::new(), // Unknown
+            quarantine_date,
+            size: if metadata.is_dir() {
+                self.calculate_dir_size(path)
+            } else {
+                metadata.len()
+            },
+            is_directory: metadata.is_dir(),
+        })
     }
 
     /// Calculate the total size of a directory
-    fn calculate_dir_size(&self, path: &PathBuf) -> u64 {
+    fn calculate_dir_size(&self, path: &Path) -> u64 {
         walkdir::WalkDir::new(path)
             .into_iter()
             .filter_map(|e| e.ok())
@@ -451,26 +662,17 @@ impl QuarantineManager {
     }
 
     /// Check if an item is in quarantine
-    pub fn is_quarantined(&self, original_path: &PathBuf) -> bool {
-        self.list()
-            .map(|items| {
-                items
-                    .iter()
-                    .any(|item| item.original_path == *original_path)
-            })
-            .unwrap_or(false)
+    pub fn is_quarantined(&self, original_path: &Path) -> bool {
+        self.get_by_original_path(original_path).is_some()
     }
 
-    /// Get a quarantined item by its original path
-    pub fn get_by_original_path(&self, original_path: &PathBuf) -> Option<QuarantinedItem> {
-        self.list()
-            .ok()?
-            .into_iter()
-            .find(|item| item.original_path == *original_path)
+    /// Get the most recently quarantined item by its original path
+    pub fn get_by_original_path(&self, original_path: &Path) -> Option<QuarantinedItem> {
+        self.index.by_original(original_path).ok().flatten()
     }
 
     /// Permanently delete a quarantined item (bypassing retention)
-    pub fn permanent_delete(&self, quarantine_path: &PathBuf) -> Result<(), String> {
+    pub fn permanent_delete(&self, quarantine_path: &Path) -> Result<(), String> {
         if !quarantine_path.exists() {
             return Err(format!(
                 "Quarantine path does not exist: {}",
@@ -478,21 +680,13 @@ impl QuarantineManager {
             ));
         }
 
-        if !quarantine_path.starts_with(&self.base_path) {
-            return Err("Path is not in quarantine directory".to_string());
-        }
-
-        let result = if quarantine_path.is_dir() {
-            fs::remove_dir_all(quarantine_path)
-        } else {
-            fs::remove_file(quarantine_path)
-        };
-
-        result.map_err(|e| format!("Failed to permanently delete: {}", e))?;
+        let item = self
+            .index
+            .get(quarantine_path)?
+            .ok_or_else(|| "Path is not in quarantine".to_string())?;
 
-        // Remove metadata file
-        let metadata_path = quarantine_path.with_extension("quarantine.json");
-        let _ = fs::remove_file(&metadata_path);
+        self.remove_item(&item)
+            .map_err(|e| format!("Failed to permanently delete: {}", e))?;
 
         eprintln!(
             "[Quarantine] Permanently deleted: {}",
@@ -509,6 +703,49 @@ impl Default for QuarantineManager {
     }
 }
 
+/// Quarantine paths that rolling back a saved WAL journal would restore
+fn journaled_items() -> HashSet<PathBuf> {
+    let manager = WALManager::new();
+    let job_ids = manager.list_journals().unwrap_or_else(|e| {
+        eprintln!("[Quarantine] Cannot list journals: {}", e.message);
+        Vec::new()
+    });
+    job_ids
+        .iter()
+        .filter_map(|job_id| manager.load_journal(job_id).ok().flatten())
+        .flat_map(|journal| journal.entries)
+        .filter(|entry| entry.status != WALStatus::RolledBack)
+        .filter_map(|entry| match entry.operation {
+            WALOperationType::DeleteFile {
+                quarantine_path, ..
+            }
+            | WALOperationType::Quarantine {
+                quarantine_path, ..
+            } => Some(quarantine_path),
+            _ => None,
+        })
+        .collect()
+}
+
+fn sidecar_path(quarantine_path: &Path) -> PathBuf {
+    quarantine_path.with_extension(SIDECAR_EXTENSION)
+}
+
+fn area_of(item: &QuarantinedItem) -> &Path {
+    item.path.parent().unwrap_or(&item.path)
+}
+
+/// Sidecars, the index and the policy live in the areas next to items
+fn is_bookkeeping(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name.ends_with(&format!(".{}", SIDECAR_EXTENSION))
+        || name.starts_with(INDEX_FILE)
+        || name == POLICY_FILE
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -588,4 +825,90 @@ mod tests {
 
         assert!(!quarantine_path.exists());
     }
+
+    #[test]
+    fn test_quota_evicts_oldest_first() {
+        let (mut manager, temp_dir) = create_test_manager();
+        manager
+            .set_policy(QuarantinePolicy {
+                quota_bytes: Some(25),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut quarantined = Vec::new();
+        for i in 0..3 {
+            let test_file = temp_dir.path().join(format!("quota_{}.txt", i));
+            fs::write(&test_file, [b'x'; 10]).unwrap();
+            quarantined.push(manager.quarantine(&test_file).unwrap());
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        // Nothing is evicted while items are being quarantined
+        assert!(quarantined.iter().all(|path| path.exists()));
+
+        // 30 bytes > 25: the oldest item not held by a journal goes
+        let journaled: HashSet<PathBuf> = [quarantined[0].clone()].into();
+        let stats = manager.cleanup_at(Utc::now(), &journaled).unwrap();
+        assert_eq!(stats.items_evicted, 1);
+        assert!(quarantined[0].exists() && quarantined[2].exists());
+        assert!(!quarantined[1].exists());
+        assert_eq!(manager.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_folder_retention() {
+        let (mut manager, temp_dir) = create_test_manager();
+        let downloads = temp_dir.path().join("Downloads");
+        fs::create_dir(&downloads).unwrap();
+        manager
+            .set_policy(QuarantinePolicy {
+                retention_days: Some(30),
+                quota_bytes: None,
+                folders: vec![FolderRetention {
+                    folder: downloads.to_string_lossy().to_string(),
+                    retention_days: Some(7),
+                }],
+            })
+            .unwrap();
+
+        let download = downloads.join("setup.dmg");
+        let document = temp_dir.path().join("notes.txt");
+        fs::write(&download, b"dmg").unwrap();
+        fs::write(&document, b"notes").unwrap();
+        manager.quarantine(&download).unwrap();
+        manager.quarantine(&document).unwrap();
+
+        let stats = manager
+            .cleanup_at(Utc::now() + Duration::days(10), &HashSet::new())
+            .unwrap();
+        assert_eq!(stats.items_removed, 1);
+        assert!(!manager.is_quarantined(&download));
+        assert!(manager.is_quarantined(&document));
+    }
+
+    #[test]
+    fn test_rebuild_index_from_orphans() {
+        let (manager, temp_dir) = create_test_manager();
+        let test_file = temp_dir.path().join("indexed.txt");
+        fs::write(&test_file, b"indexed").unwrap();
+        let quarantine_path = manager.quarantine(&test_file).unwrap();
+
+        // Lose the index, and drop a file without a sidecar in the area
+        fs::remove_file(manager.base_path().join(INDEX_FILE)).unwrap();
+        let orphan = manager
+            .base_path()
+            .join("20240101_120000_000_orphan_file.txt");
+        fs::write(&orphan, b"orphan").unwrap();
+
+        let manager = QuarantineManager::with_config(manager.base_path().clone(), 30);
+        let items = manager.list().unwrap();
+        assert_eq!(items.len(), 2);
+
+        let restored = items.iter().find(|i| i.path == quarantine_path).unwrap();
+        assert_eq!(restored.original_path, test_file);
+        let orphaned = items.iter().find(|i| i.path == orphan).unwrap();
+        assert_eq!(orphaned.name, "orphan_file.txt");
+        assert!(orphaned.original_path.as_os_str().is_empty());
+    }
 }
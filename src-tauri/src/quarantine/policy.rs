@@ -0,0 +1,94 @@
+//! Quarantine retention and quota policy
+//!
+//! Items expire after `retentionDays`, or after the retention of the most
+//! specific `folders` entry covering their original path. Each volume's
+//! area is held under `quotaBytes` by evicting its oldest items. Saved as
+//! `policy.json` next to the index.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Policy file, kept in the home-volume area
+pub const POLICY_FILE: &str = "policy.json";
+
+/// Default retention when no policy is saved
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Retention override for items quarantined from one folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderRetention {
+    pub folder: String,
+    /// Days to keep items (`None` keeps them until evicted by a quota)
+    pub retention_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinePolicy {
+    /// Days to keep items (`None` keeps them until evicted by a quota)
+    #[serde(default = "default_retention")]
+    pub retention_days: Option<u32>,
+    /// Maximum bytes per volume area; the oldest items are evicted past it
+    #[serde(default)]
+    pub quota_bytes: Option<u64>,
+    #[serde(default)]
+    pub folders: Vec<FolderRetention>,
+}
+
+fn default_retention() -> Option<u32> {
+    Some(DEFAULT_RETENTION_DAYS)
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: default_retention(),
+            quota_bytes: None,
+            folders: Vec::new(),
+        }
+    }
+}
+
+impl QuarantinePolicy {
+    /// Retention for an item quarantined from `original_path`
+    pub fn retention_for(&self, original_path: &Path) -> Option<u32> {
+        self.folders
+            .iter()
+            .filter(|f| original_path.starts_with(&f.folder))
+            .max_by_key(|f| Path::new(&f.folder).components().count())
+            .map_or(self.retention_days, |f| f.retention_days)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.quota_bytes == Some(0) {
+            return Err("Quarantine quota must be greater than zero".to_string());
+        }
+        if let Some(folder) = self.folders.iter().find(|f| f.folder.trim().is_empty()) {
+            return Err(format!("Invalid retention folder: '{}'", folder.folder));
+        }
+        Ok(())
+    }
+
+    /// Load the saved policy; a missing file gives `fallback`
+    pub fn load_from(path: &Path, fallback: Self) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(fallback);
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize quarantine policy: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write quarantine policy: {}", e))
+    }
+}
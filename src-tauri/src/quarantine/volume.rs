@@ -0,0 +1,68 @@
+//! Volume detection for per-volume quarantine areas
+//!
+//! Quarantining has to be a rename, and renames only work within a volume,
+//! so every volume gets its own quarantine area. Volumes are identified by
+//! device id on Unix and by path prefix (drive or share) elsewhere.
+
+use std::path::{Path, PathBuf};
+
+/// Folder holding the quarantine area at the top of other volumes
+pub const VOLUME_AREA_DIR: &str = ".sentinel-quarantine";
+
+/// Nearest existing ancestor of `path` (itself included), canonicalized
+fn existing(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|p| p.exists())
+        .and_then(|p| p.canonicalize().ok())
+}
+
+#[cfg(unix)]
+fn device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|m| m.dev())
+}
+
+/// Identity of the volume holding `path`
+#[cfg(unix)]
+pub fn volume_id(path: &Path) -> Option<String> {
+    device(&existing(path)?).map(|dev| format!("dev:{}", dev))
+}
+
+/// Top-most folder on the same volume as `path` (its mount point)
+#[cfg(unix)]
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    let start = existing(path)?;
+    let dev = device(&start)?;
+    let top = start
+        .ancestors()
+        .take_while(|p| device(p) == Some(dev))
+        .last()
+        .unwrap_or(&start);
+    Some(top.to_path_buf())
+}
+
+#[cfg(not(unix))]
+pub fn volume_id(path: &Path) -> Option<String> {
+    mount_point(path).map(|root| root.to_string_lossy().to_lowercase())
+}
+
+#[cfg(not(unix))]
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    existing(path)?.ancestors().last().map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_volume_of_missing_path_uses_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("not/yet/created.txt");
+
+        assert_eq!(volume_id(&missing), volume_id(dir.path()));
+        let mount = mount_point(&missing).unwrap();
+        assert!(dir.path().canonicalize().unwrap().starts_with(&mount));
+    }
+}
@@ -0,0 +1,249 @@
+//! Ignore rules (gitignore syntax) shared by scanning and organizing.
+//!
+//! Rules come from three places, later ones taking precedence:
+//! 1. [`DEFAULT_PATTERNS`]: dependency folders, virtualenvs, build outputs
+//! 2. the global file `<config dir>/sentinel/ignore` (e.g. `!build/` re-includes)
+//! 3. `.gitignore` and `.sentinelignore` in any folder, for that subtree
+//!
+//! Separately, git checkouts are never reorganized: [`enclosing_repo`] finds the
+//! repository a path belongs to so planners and the executor can refuse it.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Per-folder ignore file read by Sentinel only
+pub const IGNORE_FILE_NAME: &str = ".sentinelignore";
+
+/// Per-folder ignore files, in increasing precedence
+const DIR_IGNORE_FILES: &[&str] = &[".gitignore", IGNORE_FILE_NAME];
+
+/// Built-in rules applied to every root
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    // Version control
+    ".git/",
+    ".hg/",
+    ".svn/",
+    // Dependencies
+    "node_modules/",
+    "bower_components/",
+    ".pnpm-store/",
+    // Python virtualenvs and caches
+    ".venv/",
+    "venv/",
+    "__pycache__/",
+    ".tox/",
+    ".mypy_cache/",
+    ".pytest_cache/",
+    // Build outputs
+    "target/",
+    "build/",
+    "dist/",
+    ".next/",
+    ".gradle/",
+    "DerivedData/",
+    // Sentinel's own quarantine area on external volumes
+    ".sentinel-quarantine/",
+];
+
+/// Location of the global ignore file
+pub fn global_ignore_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("sentinel").join("ignore"))
+}
+
+/// Compiled ignore rules for one root folder.
+///
+/// Per-folder files are loaded on first use and cached, so one instance can be
+/// shared by parallel scanner threads.
+#[derive(Debug)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    /// Defaults + global file, matched relative to the root
+    base: Gitignore,
+    /// Folder -> rules from its ignore files (None = no files)
+    dirs: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl IgnoreRules {
+    /// Default, global and per-folder rules for `root`
+    pub fn for_root(root: &Path) -> Self {
+        Self::with_global_file(root, global_ignore_path().as_deref())
+    }
+
+    /// Like [`IgnoreRules::for_root`] with an explicit global file
+    pub fn with_global_file(root: &Path, global: Option<&Path>) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in DEFAULT_PATTERNS {
+            if let Err(e) = builder.add_line(None, pattern) {
+                tracing::warn!(pattern = %pattern, error = %e, "Invalid default ignore pattern");
+            }
+        }
+        if let Some(global) = global.filter(|p| p.is_file()) {
+            if let Some(e) = builder.add(global) {
+                eprintln!("[Ignore] Problem in {}: {}", global.display(), e);
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            base: build(builder),
+            dirs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether `path` matches a rule. Enclosing folders are not checked; use
+    /// [`IgnoreRules::is_ignored`] unless they are known not to be ignored.
+    pub fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if !path.starts_with(&self.root) || path == self.root {
+            return false;
+        }
+
+        // The nearest ignore file decides, then the base rules
+        for dir in path.ancestors().skip(1) {
+            if let Some(rules) = self.dir_rules(dir) {
+                match rules.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir == self.root {
+                break;
+            }
+        }
+        self.base.matched(path, is_dir).is_ignore()
+    }
+
+    /// Whether `path` or any folder between it and the root is ignored
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if !path.starts_with(&self.root) {
+            return false;
+        }
+        let mut folders: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != self.root)
+            .collect();
+        folders.reverse();
+
+        folders.into_iter().any(|dir| self.matches(dir, true)) || self.matches(path, is_dir)
+    }
+
+    fn dir_rules(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        if let Some(cached) = self.dirs.lock().ok()?.get(dir) {
+            return cached.clone();
+        }
+
+        let files: Vec<PathBuf> = DIR_IGNORE_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|p| p.is_file())
+            .collect();
+        let rules = (!files.is_empty()).then(|| {
+            let mut builder = GitignoreBuilder::new(dir);
+            for file in &files {
+                if let Some(e) = builder.add(file) {
+                    eprintln!("[Ignore] Problem in {}: {}", file.display(), e);
+                }
+            }
+            Arc::new(build(builder))
+        });
+
+        if let Ok(mut dirs) = self.dirs.lock() {
+            dirs.insert(dir.to_path_buf(), rules.clone());
+        }
+        rules
+    }
+}
+
+fn build(builder: GitignoreBuilder) -> Gitignore {
+    builder.build().unwrap_or_else(|e| {
+        eprintln!("[Ignore] Failed to compile ignore rules: {}", e);
+        Gitignore::empty()
+    })
+}
+
+/// Whether `dir` is the top of a git checkout
+pub fn is_repo_root(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+/// The git checkout containing `path`, looking at its parent folders up to and
+/// including `root`. A checkout root itself is not "inside" a repository, so it
+/// can still be moved as a whole.
+pub fn enclosing_repo(path: &Path, root: &Path) -> Option<PathBuf> {
+    if !path.starts_with(root) {
+        return None;
+    }
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| is_repo_root(dir))
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_and_folder_rules() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web/node_modules/react")).unwrap();
+        fs::create_dir_all(root.join("notes/drafts")).unwrap();
+        fs::write(root.join("notes/.sentinelignore"), "drafts/\n*.tmp\n").unwrap();
+        fs::write(root.join("notes/.gitignore"), "*.log\n").unwrap();
+
+        let rules = IgnoreRules::with_global_file(root, None);
+
+        assert!(rules.matches(&root.join("web/node_modules"), true));
+        assert!(rules.is_ignored(&root.join("web/node_modules/react/index.js"), false));
+        assert!(!rules.is_ignored(&root.join("web/index.html"), false));
+
+        assert!(rules.matches(&root.join("notes/drafts"), true));
+        assert!(rules.matches(&root.join("notes/todo.tmp"), false));
+        assert!(rules.matches(&root.join("notes/app.log"), false));
+        assert!(!rules.matches(&root.join("notes/todo.md"), false));
+        // Folder rules only apply to their own subtree
+        assert!(!rules.matches(&root.join("todo.tmp"), false));
+        assert!(!rules.matches(root, true));
+    }
+
+    #[test]
+    fn test_global_file_overrides_defaults() {
+        let dir = TempDir::new().unwrap();
+        let global = dir.path().join("ignore");
+        fs::write(&global, "!build/\n*.bak\n").unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir(&root).unwrap();
+
+        let rules = IgnoreRules::with_global_file(&root, Some(&global));
+        assert!(!rules.matches(&root.join("build"), true));
+        assert!(rules.matches(&root.join("dist"), true));
+        assert!(rules.matches(&root.join("old.bak"), false));
+    }
+
+    #[test]
+    fn test_enclosing_repo() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("code/app/.git")).unwrap();
+        fs::create_dir_all(root.join("code/app/src")).unwrap();
+
+        let repo = root.join("code/app");
+        assert_eq!(enclosing_repo(&repo.join("src/main.rs"), root), Some(repo.clone()));
+        assert_eq!(enclosing_repo(&repo.join("README.md"), root), Some(repo.clone()));
+        // The checkout as a whole may be moved
+        assert_eq!(enclosing_repo(&repo, root), None);
+        assert_eq!(enclosing_repo(&root.join("code/notes.txt"), root), None);
+    }
+}
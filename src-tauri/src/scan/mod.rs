@@ -0,0 +1,574 @@
+//! Shared filesystem scanning core.
+//!
+//! Every view that walks a folder (graph VFS, rules VFS, folder digest, tree
+//! compressor, photo scan, chat context, search index) takes a [`ScanSnapshot`]
+//! from here, so they all agree on what a folder contains:
+//! - hidden entries (name starts with '.') are skipped and not descended into,
+//!   unless [`ScanOptions::with_hidden`] is set
+//! - symlinks are listed but never followed; looping links are detected with
+//!   `security::cycle_detection` and reported instead of listed
+//! - depth is counted from the root (its children are depth 1)
+//! - entries matching the ignore rules (built-in defaults, the global ignore
+//!   file, `.gitignore` / `.sentinelignore`) are skipped, and git checkouts
+//!   below the root are listed but not descended into; see [`ignore`]
+//!
+//! Directories are read and stat'ed in parallel with jwalk.
+
+pub mod ignore;
+
+use crate::security::cycle_detection::check_symlink_loop;
+use self::ignore::IgnoreRules;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, UNIX_EPOCH};
+
+/// What to scan and how
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Deepest entry level to include (0 = unlimited)
+    max_depth: usize,
+    /// Include hidden files and descend into hidden folders
+    include_hidden: bool,
+    /// Apply ignore rules and keep out of nested git checkouts
+    respect_ignores: bool,
+    /// Stop after this many entries (0 = unlimited)
+    max_entries: usize,
+    /// Number of threads reading directories
+    num_threads: usize,
+    /// Set to true to abort the scan
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 0,
+            include_hidden: false,
+            respect_ignores: true,
+            max_entries: 0,
+            num_threads: std::thread::available_parallelism()
+                .map(|p| p.get())
+                .unwrap_or(4)
+                .min(4),
+            cancel_flag: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the depth of listed entries (0 = unlimited)
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Include hidden entries
+    pub fn with_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Apply ignore rules and skip nested git checkouts (on by default)
+    pub fn with_ignore_rules(mut self, respect: bool) -> Self {
+        self.respect_ignores = respect;
+        self
+    }
+
+    /// Stop after `max` entries; the snapshot is marked truncated (0 = unlimited)
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+
+    /// Set the number of threads
+    pub fn with_num_threads(mut self, threads: usize) -> Self {
+        self.num_threads = threads.max(1);
+        self
+    }
+
+    /// Abort the scan when `flag` becomes true
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(flag);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Walk `root` and return a snapshot of everything below it.
+    ///
+    /// Fails if `root` is not a readable directory or the scan is cancelled.
+    /// Unreadable subfolders are recorded in [`ScanSnapshot::errors`].
+    pub fn scan(&self, root: &Path) -> Result<ScanSnapshot, String> {
+        let start = Instant::now();
+
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        std::fs::read_dir(root)
+            .map_err(|e| format!("Failed to read directory {}: {}", root.display(), e))?;
+
+        let hidden_skipped = Arc::new(AtomicUsize::new(0));
+        let symlink_loops = Arc::new(AtomicUsize::new(0));
+        let ignored_skipped = Arc::new(AtomicUsize::new(0));
+        let repos_skipped = Arc::new(AtomicUsize::new(0));
+        let rules = self
+            .respect_ignores
+            .then(|| Arc::new(IgnoreRules::for_root(root)));
+
+        let mut walker = jwalk::WalkDirGeneric::<((), Option<EntryMeta>)>::new(root)
+            .parallelism(if self.num_threads > 1 {
+                jwalk::Parallelism::RayonNewPool(self.num_threads)
+            } else {
+                jwalk::Parallelism::Serial
+            })
+            .sort(true)
+            .skip_hidden(false)
+            .follow_links(false);
+        if self.max_depth > 0 {
+            walker = walker.max_depth(self.max_depth);
+        }
+
+        let include_hidden = self.include_hidden;
+        let cancel_flag = self.cancel_flag.clone();
+        let hidden = Arc::clone(&hidden_skipped);
+        let loops = Arc::clone(&symlink_loops);
+        let ignored = Arc::clone(&ignored_skipped);
+        let repos = Arc::clone(&repos_skipped);
+        walker = walker.process_read_dir(move |depth, _dir, _state, children| {
+            // The pseudo-read that yields the root itself
+            let Some(depth) = depth else { return };
+
+            // A git checkout below the root is opaque: listed, never entered
+            if rules.is_some() && depth > 0 {
+                let is_repo = children
+                    .iter()
+                    .flatten()
+                    .any(|entry| entry.file_name == ".git");
+                if is_repo {
+                    repos.fetch_add(1, Ordering::Relaxed);
+                    children.clear();
+                    return;
+                }
+            }
+            let cancelled = cancel_flag
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed));
+
+            children.retain(|child| {
+                let Ok(entry) = child else { return true };
+                if !include_hidden && entry.file_name.to_string_lossy().starts_with('.') {
+                    hidden.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+                if entry.file_type.is_symlink() && check_symlink_loop(&entry.path()).is_err() {
+                    eprintln!("[Scan] Skipping looping symlink: {}", entry.path().display());
+                    loops.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+                // Parents were already checked, so only the entry itself is matched
+                if let Some(rules) = &rules {
+                    if rules.matches(&entry.path(), entry.file_type.is_dir()) {
+                        ignored.fetch_add(1, Ordering::Relaxed);
+                        return false;
+                    }
+                }
+                true
+            });
+
+            for entry in children.iter_mut().flatten() {
+                if cancelled {
+                    entry.read_children_path = None;
+                }
+                entry.client_state = entry.metadata().ok().map(|m| EntryMeta::from(&m));
+            }
+        });
+
+        let mut entries = Vec::new();
+        let mut errors = Vec::new();
+        let mut truncated = false;
+
+        for result in walker {
+            if self.is_cancelled() {
+                return Err(format!("Scan cancelled: {}", root.display()));
+            }
+
+            let entry = match result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    errors.push(ScanError {
+                        path: e.path().map(Path::to_path_buf),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if let Some(e) = &entry.read_children_error {
+                errors.push(ScanError {
+                    path: Some(entry.path()),
+                    message: e.to_string(),
+                });
+            }
+            if entry.depth == 0 {
+                continue;
+            }
+            if self.max_entries > 0 && entries.len() >= self.max_entries {
+                truncated = true;
+                break;
+            }
+
+            let path = entry.path();
+            let Some(meta) = entry.client_state else {
+                errors.push(ScanError {
+                    message: format!("Failed to read metadata: {}", path.display()),
+                    path: Some(path),
+                });
+                continue;
+            };
+            let kind = if entry.file_type.is_symlink() {
+                EntryKind::Symlink
+            } else if entry.file_type.is_dir() {
+                EntryKind::Directory
+            } else {
+                EntryKind::File
+            };
+
+            entries.push(ScanEntry {
+                name: entry.file_name.to_string_lossy().to_string(),
+                path,
+                kind,
+                depth: entry.depth,
+                size: if kind == EntryKind::File { meta.size } else { 0 },
+                modified_at: meta.modified_at,
+                created_at: meta.created_at,
+            });
+        }
+
+        let stats = SnapshotStats {
+            total_files: entries.iter().filter(|e| e.is_file()).count(),
+            total_dirs: entries.iter().filter(|e| e.is_dir()).count(),
+            total_symlinks: entries
+                .iter()
+                .filter(|e| e.kind == EntryKind::Symlink)
+                .count(),
+            total_size_bytes: entries.iter().map(|e| e.size).sum(),
+            hidden_skipped: hidden_skipped.load(Ordering::Relaxed),
+            symlink_loops: symlink_loops.load(Ordering::Relaxed),
+            ignored_skipped: ignored_skipped.load(Ordering::Relaxed),
+            repos_skipped: repos_skipped.load(Ordering::Relaxed),
+            errors: errors.len(),
+            truncated,
+            scan_duration_ms: start.elapsed().as_millis() as u64,
+        };
+
+        let mut children: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            if let Some(parent) = entry.path.parent() {
+                children.entry(parent.to_path_buf()).or_default().push(i);
+            }
+        }
+
+        Ok(ScanSnapshot {
+            root: root.to_path_buf(),
+            entries,
+            children,
+            errors,
+            stats,
+        })
+    }
+}
+
+/// Metadata captured on the walker threads
+#[derive(Debug, Clone, Copy, Default)]
+struct EntryMeta {
+    size: u64,
+    modified_at: Option<i64>,
+    created_at: Option<i64>,
+}
+
+impl From<&Metadata> for EntryMeta {
+    fn from(metadata: &Metadata) -> Self {
+        let millis = |t: std::io::Result<std::time::SystemTime>| {
+            t.ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+        };
+        Self {
+            size: metadata.len(),
+            modified_at: millis(metadata.modified()),
+            created_at: millis(metadata.created()),
+        }
+    }
+}
+
+/// Type of a scanned entry (symlinks are not resolved)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// One file, folder or symlink below the scan root
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanEntry {
+    pub path: PathBuf,
+    /// File name including extension
+    pub name: String,
+    pub kind: EntryKind,
+    /// Levels below the root (children of the root are 1)
+    pub depth: usize,
+    /// Size in bytes (0 for folders and symlinks)
+    pub size: u64,
+    /// Last modified timestamp (milliseconds since epoch)
+    pub modified_at: Option<i64>,
+    /// Created timestamp (milliseconds since epoch)
+    pub created_at: Option<i64>,
+}
+
+impl ScanEntry {
+    pub fn is_file(&self) -> bool {
+        self.kind == EntryKind::File
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+
+    /// Lowercase extension of files (None for folders and symlinks)
+    pub fn extension(&self) -> Option<String> {
+        if !self.is_file() {
+            return None;
+        }
+        self.path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+    }
+}
+
+/// A path that could not be read
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanError {
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+/// Counts for a snapshot
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotStats {
+    pub total_files: usize,
+    pub total_dirs: usize,
+    pub total_symlinks: usize,
+    pub total_size_bytes: u64,
+    /// Hidden entries left out (hidden folders count once)
+    pub hidden_skipped: usize,
+    /// Symlinks left out because they loop
+    pub symlink_loops: usize,
+    /// Entries left out by ignore rules (ignored folders count once)
+    pub ignored_skipped: usize,
+    /// Git checkouts below the root that were not descended into
+    pub repos_skipped: usize,
+    /// Unreadable folders and entries
+    pub errors: usize,
+    /// The entry limit was hit before the walk finished
+    pub truncated: bool,
+    pub scan_duration_ms: u64,
+}
+
+/// Everything below a root at one point in time.
+///
+/// Entries are in walk order: a folder always comes before its contents and
+/// siblings are sorted by name.
+#[derive(Debug, Clone)]
+pub struct ScanSnapshot {
+    root: PathBuf,
+    entries: Vec<ScanEntry>,
+    /// Parent path -> indices into `entries`
+    children: HashMap<PathBuf, Vec<usize>>,
+    errors: Vec<ScanError>,
+    stats: SnapshotStats,
+}
+
+impl ScanSnapshot {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn entries(&self) -> &[ScanEntry] {
+        &self.entries
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &ScanEntry> {
+        self.entries.iter().filter(|e| e.is_file())
+    }
+
+    pub fn directories(&self) -> impl Iterator<Item = &ScanEntry> {
+        self.entries.iter().filter(|e| e.is_dir())
+    }
+
+    /// Direct children of `dir` (empty if it was not scanned)
+    pub fn children(&self, dir: &Path) -> impl Iterator<Item = &ScanEntry> {
+        self.children
+            .get(dir)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.entries[i])
+    }
+
+    /// Whether reading the contents of `dir` failed
+    pub fn is_unreadable(&self, dir: &Path) -> bool {
+        self.errors.iter().any(|e| e.path.as_deref() == Some(dir))
+    }
+
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
+    pub fn stats(&self) -> &SnapshotStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs/2024")).unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join("a.txt"), "hello").unwrap();
+        fs::write(root.join(".DS_Store"), "x").unwrap();
+        fs::write(root.join(".git/config"), "x").unwrap();
+        fs::write(root.join("docs/report.PDF"), "pdf!").unwrap();
+        fs::write(root.join("docs/2024/deep.md"), "#").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_skips_hidden_by_default() {
+        let dir = create_test_dir();
+        let snapshot = ScanOptions::new().scan(dir.path()).unwrap();
+        let stats = snapshot.stats();
+
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.total_dirs, 2);
+        assert_eq!(stats.total_size_bytes, 10);
+        assert_eq!(stats.hidden_skipped, 2);
+        assert!(!stats.truncated);
+
+        // .git stays out through the default ignore rules
+        let with_hidden = ScanOptions::new().with_hidden(true).scan(dir.path()).unwrap();
+        assert_eq!(with_hidden.stats().total_files, 4);
+        assert_eq!(with_hidden.stats().hidden_skipped, 0);
+        assert_eq!(with_hidden.stats().ignored_skipped, 1);
+
+        let everything = ScanOptions::new()
+            .with_hidden(true)
+            .with_ignore_rules(false)
+            .scan(dir.path())
+            .unwrap();
+        assert_eq!(everything.stats().total_files, 5);
+    }
+
+    #[test]
+    fn test_ignore_rules_and_nested_repos() {
+        let dir = create_test_dir();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web/node_modules/react")).unwrap();
+        fs::write(root.join("web/node_modules/react/index.js"), "x").unwrap();
+        fs::write(root.join("docs/.sentinelignore"), "2024/\n*.PDF\n").unwrap();
+        fs::create_dir_all(root.join("code/app/.git")).unwrap();
+        fs::create_dir_all(root.join("code/app/src")).unwrap();
+        fs::write(root.join("code/app/src/main.rs"), "fn main() {}").unwrap();
+
+        let snapshot = ScanOptions::new().scan(root).unwrap();
+        let stats = snapshot.stats();
+        let paths: Vec<&Path> = snapshot.entries().iter().map(|e| e.path.as_path()).collect();
+
+        assert_eq!(stats.ignored_skipped, 3);
+        assert_eq!(stats.repos_skipped, 1);
+        assert!(!paths.contains(&root.join("web/node_modules").as_path()));
+        assert!(!paths.contains(&root.join("docs/report.PDF").as_path()));
+        // The checkout is listed but its contents are not
+        assert!(paths.contains(&root.join("code/app").as_path()));
+        assert_eq!(snapshot.children(&root.join("code/app")).count(), 0);
+        assert_eq!(snapshot.files().count(), 1);
+
+        let unfiltered = ScanOptions::new().with_ignore_rules(false).scan(root).unwrap();
+        assert!(unfiltered.files().any(|e| e.name == "main.rs"));
+        assert!(unfiltered.files().any(|e| e.name == "index.js"));
+    }
+
+    #[test]
+    fn test_depth_order_and_children() {
+        let dir = create_test_dir();
+        let root = dir.path();
+
+        let snapshot = ScanOptions::new().with_max_depth(2).scan(root).unwrap();
+        assert!(snapshot.entries().iter().all(|e| e.depth <= 2));
+        assert_eq!(snapshot.files().count(), 2);
+
+        let docs = root.join("docs");
+        let names: Vec<&str> = snapshot.children(&docs).map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["2024", "report.PDF"]);
+
+        let report = snapshot.files().find(|e| e.name == "report.PDF").unwrap();
+        assert_eq!(report.extension().as_deref(), Some("pdf"));
+        assert_eq!(report.depth, 2);
+
+        // Parents come before their contents
+        let pos = |p: &Path| snapshot.entries().iter().position(|e| e.path == p).unwrap();
+        assert!(pos(&docs) < pos(&docs.join("report.PDF")));
+    }
+
+    #[test]
+    fn test_max_entries_and_cancel() {
+        let dir = create_test_dir();
+
+        let snapshot = ScanOptions::new().with_max_entries(2).scan(dir.path()).unwrap();
+        assert_eq!(snapshot.entries().len(), 2);
+        assert!(snapshot.stats().truncated);
+
+        let flag = Arc::new(AtomicBool::new(true));
+        let result = ScanOptions::new().with_cancel_flag(flag).scan(dir.path());
+        assert!(result.unwrap_err().contains("cancelled"));
+
+        assert!(ScanOptions::new().scan(&dir.path().join("a.txt")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_not_followed() {
+        let dir = create_test_dir();
+        let root = dir.path();
+        std::os::unix::fs::symlink(root, root.join("docs/up")).unwrap();
+        std::os::unix::fs::symlink(root.join("loop_b"), root.join("loop_a")).unwrap();
+        std::os::unix::fs::symlink(root.join("loop_a"), root.join("loop_b")).unwrap();
+
+        let snapshot = ScanOptions::new().scan(root).unwrap();
+        let stats = snapshot.stats();
+
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.total_symlinks, 1);
+        assert_eq!(stats.symlink_loops, 2);
+        assert_eq!(snapshot.children(&root.join("docs/up")).count(), 0);
+    }
+}
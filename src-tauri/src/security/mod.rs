@@ -172,7 +172,6 @@ impl PathValidator {
     /// # Returns
     /// * `Ok(PathBuf)` - The validated path (canonicalized where possible)
     /// * `Err(String)` - Error message if validation fails
-    #[allow(dead_code)]
     pub fn validate_for_write(path: &Path, boundary: Option<&Path>) -> Result<PathBuf, String> {
         // For writes, parent must exist even if file doesn't yet
         let parent = path
@@ -381,6 +380,31 @@ impl PathValidator {
         }
         Ok(())
     }
+
+    /// Validate that a bare file name is safe (no path traversal)
+    pub fn validate_filename(name: &str) -> Result<(), String> {
+        // Reject path separators
+        if name.contains('/') || name.contains('\\') || name.contains("..") {
+            return Err("Invalid filename: path separators not allowed".to_string());
+        }
+
+        // Reject control characters and null bytes
+        if name.chars().any(|c| c.is_control() || c == '\0') {
+            return Err("Invalid filename: control characters not allowed".to_string());
+        }
+
+        // Reject empty or whitespace-only names
+        if name.trim().is_empty() {
+            return Err("Invalid filename: cannot be empty".to_string());
+        }
+
+        // Reject names that are too long (filesystem limit)
+        if name.len() > 255 {
+            return Err("Invalid filename: name too long".to_string());
+        }
+
+        Ok(())
+    }
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,424 @@
+//! Saved, versioned Blueprints
+//!
+//! A Blueprint designed by the Architect (or written by hand) can be saved
+//! under a name and reused, so later runs skip the Architect and slot files
+//! straight into the same structure (see
+//! [`crate::ai::v2::builder::organize_with_blueprint`]). Every save of an
+//! existing Blueprint adds a version; older versions stay available. The
+//! library is stored at `<config dir>/sentinel/blueprints.json`, and single
+//! Blueprints can be exported to and imported from JSON or TOML files.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::ai::v2::Blueprint;
+
+/// One saved revision of a Blueprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintVersion {
+    /// 1 for the first save, incremented by each later save
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// What changed in this version
+    #[serde(default)]
+    pub note: Option<String>,
+    pub blueprint: Blueprint,
+}
+
+/// A named Blueprint with all its versions (oldest first)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedBlueprint {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub versions: Vec<BlueprintVersion>,
+}
+
+impl SavedBlueprint {
+    pub fn latest(&self) -> Option<&BlueprintVersion> {
+        self.versions.last()
+    }
+
+    /// A specific version, or the latest when `version` is None
+    pub fn version(&self, version: Option<u32>) -> Result<&BlueprintVersion, String> {
+        match version {
+            Some(v) => self
+                .versions
+                .iter()
+                .find(|saved| saved.version == v)
+                .ok_or_else(|| format!("Blueprint '{}' has no version {}", self.name, v)),
+            None => self
+                .latest()
+                .ok_or_else(|| format!("Blueprint '{}' has no saved versions", self.name)),
+        }
+    }
+}
+
+/// Check a Blueprint before it is saved or used: it needs folders, and every
+/// folder path must be a unique path inside the organized folder
+pub fn validate_blueprint(blueprint: &Blueprint) -> Result<(), String> {
+    if blueprint.strategy_name.trim().is_empty() {
+        return Err("Blueprint needs a strategy name".to_string());
+    }
+    if blueprint.structure.is_empty() {
+        return Err("Blueprint needs at least one folder".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for folder in &blueprint.structure {
+        let path = Path::new(folder.path.trim());
+        if folder.path.trim().is_empty()
+            || !path.components().all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(format!(
+                "Blueprint folder '{}' must be a relative path inside the organized folder",
+                folder.path
+            ));
+        }
+        if !seen.insert(folder.path.trim().trim_end_matches('/')) {
+            return Err(format!(
+                "Blueprint folder '{}' is listed twice",
+                folder.path
+            ));
+        }
+    }
+    blueprint.thresholds.validate()
+}
+
+/// The saved Blueprint library
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlueprintLibrary {
+    #[serde(default)]
+    pub blueprints: Vec<SavedBlueprint>,
+}
+
+impl BlueprintLibrary {
+    /// Location of the library file
+    pub fn file_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("sentinel").join("blueprints.json"))
+    }
+
+    /// Load the library; a missing or unreadable file means no saved Blueprints
+    pub fn load() -> Self {
+        let Some(path) = Self::file_path() else {
+            return Self::default();
+        };
+        Self::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("[Blueprints] {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::file_path().ok_or("Could not determine config directory")?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize Blueprints: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write Blueprints: {}", e))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SavedBlueprint> {
+        self.blueprints.iter().find(|b| b.id == id)
+    }
+
+    /// A saved version of a Blueprint (the latest when `version` is None)
+    pub fn resolve(&self, id: &str, version: Option<u32>) -> Result<&BlueprintVersion, String> {
+        self.get(id)
+            .ok_or_else(|| format!("No saved Blueprint with id {}", id))?
+            .version(version)
+    }
+
+    /// Save a Blueprint: a new version of `id` when it exists, otherwise a
+    /// new library entry. Returns the updated entry.
+    pub fn save_version(
+        &mut self,
+        id: Option<&str>,
+        name: &str,
+        blueprint: Blueprint,
+        note: Option<String>,
+    ) -> Result<&SavedBlueprint, String> {
+        validate_blueprint(&blueprint)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Blueprint name cannot be empty".to_string());
+        }
+
+        let index = match id.and_then(|id| self.blueprints.iter().position(|b| b.id == id)) {
+            Some(index) => index,
+            None => {
+                self.blueprints.push(SavedBlueprint {
+                    id: id
+                        .filter(|id| !id.trim().is_empty())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                    name: name.to_string(),
+                    versions: Vec::new(),
+                });
+                self.blueprints.len() - 1
+            }
+        };
+
+        let entry = &mut self.blueprints[index];
+        let version = entry.latest().map_or(1, |latest| latest.version + 1);
+        entry.name = name.to_string();
+        entry.versions.push(BlueprintVersion {
+            version,
+            saved_at: Utc::now(),
+            note: note.filter(|n| !n.trim().is_empty()),
+            blueprint,
+        });
+        Ok(&self.blueprints[index])
+    }
+
+    /// Remove a Blueprint with all its versions; returns whether it was saved
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.blueprints.len();
+        self.blueprints.retain(|b| b.id != id);
+        self.blueprints.len() != before
+    }
+}
+
+/// File format for sharing Blueprints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlueprintFormat {
+    Json,
+    Toml,
+}
+
+impl BlueprintFormat {
+    /// Pick the format from a file extension (`.json` or `.toml`)
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("json") => Ok(BlueprintFormat::Json),
+            Some("toml") => Ok(BlueprintFormat::Toml),
+            _ => Err(format!(
+                "Unknown Blueprint format for {} (use .json or .toml)",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A Blueprint as shared between people: its name, the version it was
+/// exported from, and the Blueprint itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintDocument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    pub blueprint: Blueprint,
+}
+
+impl BlueprintDocument {
+    pub fn from_saved(saved: &SavedBlueprint, version: Option<u32>) -> Result<Self, String> {
+        let saved_version = saved.version(version)?;
+        Ok(Self {
+            name: saved.name.clone(),
+            version: Some(saved_version.version),
+            blueprint: saved_version.blueprint.clone(),
+        })
+    }
+
+    /// Serialize for sharing
+    pub fn export(&self, format: BlueprintFormat) -> Result<String, String> {
+        match format {
+            BlueprintFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to export Blueprint: {}", e)),
+            BlueprintFormat::Toml => {
+                toml::to_string(self).map_err(|e| format!("Failed to export Blueprint: {}", e))
+            }
+        }
+    }
+
+    /// Parse a shared Blueprint. A bare Blueprint (as the Architect outputs
+    /// it) is accepted too and named after its strategy.
+    pub fn import(text: &str, format: BlueprintFormat) -> Result<Self, String> {
+        fn parse<T: serde::de::DeserializeOwned>(
+            text: &str,
+            format: BlueprintFormat,
+        ) -> Result<T, String> {
+            match format {
+                BlueprintFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+                BlueprintFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+            }
+        }
+
+        let document = parse::<Self>(text, format).or_else(|document_err| {
+            parse::<Blueprint>(text, format)
+                .map(|blueprint| Self {
+                    name: blueprint.strategy_name.clone(),
+                    version: None,
+                    blueprint,
+                })
+                .map_err(|_| format!("Failed to import Blueprint: {}", document_err))
+        })?;
+        validate_blueprint(&document.blueprint)?;
+        Ok(document)
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        let text = self.export(BlueprintFormat::from_path(path)?)?;
+        fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, String> {
+        let format = BlueprintFormat::from_path(path)?;
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::import(&text, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::v2::BlueprintFolder;
+    use tempfile::TempDir;
+
+    fn blueprint(folders: &[&str]) -> Blueprint {
+        Blueprint {
+            strategy_name: "Client work".to_string(),
+            structure: folders
+                .iter()
+                .map(|path| BlueprintFolder {
+                    path: path.to_string(),
+                    semantic_description: format!("files for {}", path),
+                    expected_extensions: vec!["pdf".to_string()],
+                    embedding: None,
+                })
+                .collect(),
+            extraction_rules: "file.ext == 'pdf' -> Acme-Corp/Invoices".to_string(),
+            description: Some("One folder per client".to_string()),
+            confidence: 0.9,
+            thresholds: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("sentinel/blueprints.json");
+        let mut library = BlueprintLibrary::default();
+
+        let id = library
+            .save_version(None, "Clients", blueprint(&["Acme-Corp/Invoices"]), None)
+            .unwrap()
+            .id
+            .clone();
+        let saved = library
+            .save_version(
+                Some(&id),
+                "Clients",
+                blueprint(&["Acme-Corp/Invoices", "Acme-Corp/Contracts"]),
+                Some("Add contracts".to_string()),
+            )
+            .unwrap();
+        assert_eq!(saved.versions.len(), 2);
+        assert_eq!(saved.latest().unwrap().version, 2);
+        library.save_to(&file).unwrap();
+
+        let loaded = BlueprintLibrary::load_from(&file).unwrap();
+        assert_eq!(
+            loaded.resolve(&id, None).unwrap().blueprint.structure.len(),
+            2
+        );
+        assert_eq!(
+            loaded
+                .resolve(&id, Some(1))
+                .unwrap()
+                .blueprint
+                .structure
+                .len(),
+            1
+        );
+        assert!(loaded.resolve(&id, Some(3)).is_err());
+
+        assert!(library
+            .save_version(None, "Bad", blueprint(&["../Outside"]), None)
+            .is_err());
+        assert!(library
+            .save_version(None, "Twice", blueprint(&["Acme", "Acme/"]), None)
+            .is_err());
+        assert!(library.remove(&id));
+        assert!(library.get(&id).is_none());
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let mut library = BlueprintLibrary::default();
+        let saved = library
+            .save_version(
+                None,
+                "Clients",
+                blueprint(&["Acme-Corp/Invoices", "Beta-LLC"]),
+                None,
+            )
+            .unwrap();
+        let document = BlueprintDocument::from_saved(saved, None).unwrap();
+
+        for format in [BlueprintFormat::Json, BlueprintFormat::Toml] {
+            let text = document.export(format).unwrap();
+            let imported = BlueprintDocument::import(&text, format).unwrap();
+            assert_eq!(imported.name, "Clients");
+            assert_eq!(imported.version, Some(1));
+            assert_eq!(imported.blueprint.structure[0].path, "Acme-Corp/Invoices");
+            assert_eq!(
+                imported.blueprint.extraction_rules,
+                document.blueprint.extraction_rules
+            );
+        }
+
+        // A bare Blueprint, as the Architect writes it
+        let bare = r#"
+            strategyName = "By year"
+            extractionRules = ""
+
+            [[structure]]
+            path = "2024"
+            semanticDescription = "files from 2024"
+        "#;
+        let imported = BlueprintDocument::import(bare, BlueprintFormat::Toml).unwrap();
+        assert_eq!(imported.name, "By year");
+        assert_eq!(imported.version, None);
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("clients.toml");
+        document.write_to(&path).unwrap();
+        assert_eq!(
+            BlueprintDocument::read_from(&path)
+                .unwrap()
+                .blueprint
+                .structure
+                .len(),
+            2
+        );
+        assert!(BlueprintFormat::from_path(Path::new("clients.yaml")).is_err());
+    }
+}
@@ -1,2 +1,10 @@
+pub mod blueprint_library;
+pub mod pdf_raster;
+pub mod schedule_config;
+pub mod schedule_history;
+pub mod scheduler;
 pub mod thumbnails;
+pub mod watch_actions;
+pub mod watch_config;
+pub mod watch_journal;
 pub mod watcher;
@@ -0,0 +1,799 @@
+//! PDF Page Rasterization
+//!
+//! Turns a PDF page into an image for thumbnails, OCR and Vision analysis.
+//! Uses a fallback chain so most PDFs render even without system libraries:
+//!
+//! 1. **pdfium** - full fidelity (only with the `pdfium` feature + library)
+//! 2. **Embedded image** - scanned pages are usually one large JPEG/raw image
+//! 3. **Basic vector** - pure Rust render of paths, fills and greeked text
+//!
+//! Call [`capabilities`] to find out which backends are usable.
+
+use image::{DynamicImage, ImageBuffer, RgbaImage};
+use lopdf::content::Content;
+use lopdf::{Document, Object, ObjectId};
+use resvg::tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Target DPI for rendering (150 = good balance of quality and size)
+const RENDER_DPI: f32 = 150.0;
+
+/// Maximum page dimension in pixels
+const MAX_PAGE_DIMENSION: u32 = 1600;
+
+/// Embedded images smaller than this (shortest side) are logos, not scans
+const MIN_SCAN_IMAGE_DIMENSION: u32 = 300;
+
+/// Pages with more text-showing operators than this are not scans
+const MAX_SCAN_TEXT_OPS: usize = 5;
+
+/// US Letter, used when a page has no readable MediaBox
+const DEFAULT_MEDIA_BOX: [f32; 4] = [0.0, 0.0, 612.0, 792.0];
+
+/// A rasterization backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfRasterBackend {
+    /// pdfium library (full fidelity)
+    Pdfium,
+    /// Largest embedded page image (scanned documents)
+    EmbeddedImage,
+    /// Pure Rust render of vector content with greeked text
+    VectorBasic,
+}
+
+/// Which renderers are available, for the UI and the analysis pipeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RendererCapabilities {
+    /// pdfium is compiled in and its library loads
+    pub pdfium_available: bool,
+    /// ffmpeg is on PATH (video thumbnails, last-resort PDF thumbnails)
+    pub ffmpeg_available: bool,
+    /// PDF backends in the order they are tried
+    pub pdf_backends: Vec<PdfRasterBackend>,
+    /// Backend expected for most PDFs (first in the chain)
+    pub active_pdf_backend: PdfRasterBackend,
+}
+
+/// A rendered page and the backend that produced it
+#[derive(Debug, Clone)]
+pub struct RasterizedPage {
+    pub image: DynamicImage,
+    pub backend: PdfRasterBackend,
+}
+
+/// Report which renderers are available (probed once per process)
+pub fn capabilities() -> RendererCapabilities {
+    static CAPABILITIES: OnceLock<RendererCapabilities> = OnceLock::new();
+
+    CAPABILITIES
+        .get_or_init(|| {
+            let pdfium_available = pdfium_available();
+            let ffmpeg_available = std::process::Command::new("ffmpeg")
+                .arg("-version")
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false);
+
+            let mut pdf_backends = Vec::new();
+            if pdfium_available {
+                pdf_backends.push(PdfRasterBackend::Pdfium);
+            }
+            pdf_backends.push(PdfRasterBackend::EmbeddedImage);
+            pdf_backends.push(PdfRasterBackend::VectorBasic);
+
+            let caps = RendererCapabilities {
+                pdfium_available,
+                ffmpeg_available,
+                active_pdf_backend: pdf_backends[0],
+                pdf_backends,
+            };
+
+            tracing::info!(
+                "[PdfRaster] Backends: {:?} (ffmpeg: {})",
+                caps.pdf_backends,
+                caps.ffmpeg_available
+            );
+            caps
+        })
+        .clone()
+}
+
+fn pdfium_available() -> bool {
+    #[cfg(feature = "pdfium")]
+    {
+        pdfium_render::prelude::Pdfium::default().is_ok()
+    }
+    #[cfg(not(feature = "pdfium"))]
+    {
+        false
+    }
+}
+
+/// Rasterize a page (0-based) using the best available backend
+pub fn rasterize_page(path: &Path, page_index: usize) -> Result<RasterizedPage, String> {
+    #[cfg(feature = "pdfium")]
+    {
+        if capabilities().pdfium_available {
+            match rasterize_with_pdfium(path, page_index) {
+                Ok(image) => {
+                    return Ok(RasterizedPage {
+                        image,
+                        backend: PdfRasterBackend::Pdfium,
+                    })
+                }
+                Err(e) => tracing::warn!(
+                    "[PdfRaster] pdfium failed for {}, using built-in renderer: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+
+    // lopdf can panic on malformed files, same as pdf-extract
+    std::panic::catch_unwind(|| rasterize_page_builtin(&bytes, page_index))
+        .map_err(|_| "PDF rasterization panicked - malformed file".to_string())?
+}
+
+/// Number of pages in a PDF
+pub fn page_count(path: &Path) -> Result<usize, String> {
+    // lopdf can panic on malformed files
+    std::panic::catch_unwind(|| {
+        let doc = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+        Ok(doc.get_pages().len())
+    })
+    .map_err(|_| "PDF page count panicked - malformed file".to_string())?
+}
+
+/// Render using pdfium
+#[cfg(feature = "pdfium")]
+fn rasterize_with_pdfium(path: &Path, page_index: usize) -> Result<DynamicImage, String> {
+    use pdfium_render::prelude::*;
+
+    let pdfium = Pdfium::default().map_err(|e| format!("Failed to initialize pdfium: {}", e))?;
+
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| format!("Failed to load PDF: {}", e))?;
+
+    let page = document
+        .pages()
+        .get(page_index as u16)
+        .map_err(|e| format!("Failed to get page {}: {}", page_index, e))?;
+
+    let page_width = page.width().value;
+    let page_height = page.height().value;
+    let scale = render_scale(page_width, page_height);
+
+    let config = PdfRenderConfig::new()
+        .set_target_width((page_width * scale) as i32)
+        .set_target_height((page_height * scale) as i32)
+        .render_form_data(true)
+        .render_annotations(true);
+
+    let bitmap = page
+        .render_with_config(&config)
+        .map_err(|e| format!("Failed to render page: {}", e))?;
+
+    Ok(bitmap.as_image())
+}
+
+/// Built-in chain: embedded scan image, then basic vector render
+fn rasterize_page_builtin(bytes: &[u8], page_index: usize) -> Result<RasterizedPage, String> {
+    let doc = Document::load_mem(bytes).map_err(|e| format!("Failed to parse PDF: {}", e))?;
+
+    let page_id = *doc
+        .get_pages()
+        .get(&(page_index as u32 + 1))
+        .ok_or_else(|| format!("PDF has no page {}", page_index + 1))?;
+
+    let operations = doc
+        .get_page_content(page_id)
+        .ok()
+        .and_then(|content| Content::decode(&content).ok())
+        .map(|content| content.operations)
+        .unwrap_or_default();
+
+    let text_ops = operations
+        .iter()
+        .filter(|op| matches!(op.operator.as_str(), "Tj" | "TJ" | "'" | "\""))
+        .count();
+
+    if text_ops <= MAX_SCAN_TEXT_OPS {
+        if let Some(image) = largest_page_image(&doc, page_id) {
+            if image.width().min(image.height()) >= MIN_SCAN_IMAGE_DIMENSION {
+                return Ok(RasterizedPage {
+                    image: fit_to_max_dimension(image),
+                    backend: PdfRasterBackend::EmbeddedImage,
+                });
+            }
+        }
+    }
+
+    if operations.is_empty() {
+        return Err("PDF page has no renderable content".to_string());
+    }
+
+    let media_box = media_box(&doc, page_id);
+    let image = VectorRenderer::new(media_box)?.render(&operations);
+
+    Ok(RasterizedPage {
+        image,
+        backend: PdfRasterBackend::VectorBasic,
+    })
+}
+
+/// Scale factor from PDF points to pixels
+fn render_scale(width: f32, height: f32) -> f32 {
+    (MAX_PAGE_DIMENSION as f32 / width.max(height).max(1.0)).min(RENDER_DPI / 72.0)
+}
+
+fn fit_to_max_dimension(image: DynamicImage) -> DynamicImage {
+    if image.width().max(image.height()) <= MAX_PAGE_DIMENSION {
+        return image;
+    }
+    image.resize(
+        MAX_PAGE_DIMENSION,
+        MAX_PAGE_DIMENSION,
+        image::imageops::FilterType::Triangle,
+    )
+}
+
+/// Page MediaBox, following the Parent chain for inherited boxes
+fn media_box(doc: &Document, page_id: ObjectId) -> [f32; 4] {
+    let mut current = Some(page_id);
+    let mut depth = 0;
+
+    while let Some(id) = current {
+        depth += 1;
+        if depth > 32 {
+            break;
+        }
+        let Ok(dict) = doc.get_dictionary(id) else {
+            break;
+        };
+
+        if let Ok(values) = dict.get(b"MediaBox").and_then(|o| o.as_array()) {
+            let values: Vec<f32> = values.iter().filter_map(number).collect();
+            if values.len() == 4 && values[2] > values[0] && values[3] > values[1] {
+                return [values[0], values[1], values[2], values[3]];
+            }
+        }
+
+        current = dict.get(b"Parent").and_then(|o| o.as_reference()).ok();
+    }
+
+    DEFAULT_MEDIA_BOX
+}
+
+/// Decode the largest image XObject on a page
+fn largest_page_image(doc: &Document, page_id: ObjectId) -> Option<DynamicImage> {
+    let images = doc.get_page_images(page_id).ok()?;
+    let largest = images
+        .iter()
+        .max_by_key(|img| img.width.max(0) * img.height.max(0))?;
+
+    let filters = largest.filters.clone().unwrap_or_default();
+    let width = u32::try_from(largest.width).ok()?;
+    let height = u32::try_from(largest.height).ok()?;
+
+    if filters.iter().any(|f| f == "DCTDecode") {
+        return image::load_from_memory_with_format(largest.content, image::ImageFormat::Jpeg).ok();
+    }
+
+    // Raw pixels, optionally Flate-compressed
+    if !filters.iter().all(|f| f == "FlateDecode") {
+        tracing::debug!("[PdfRaster] Unsupported image filters: {:?}", filters);
+        return None;
+    }
+    if largest.bits_per_component.unwrap_or(8) != 8 {
+        return None;
+    }
+
+    let stream = doc.get_object(largest.id).ok()?.as_stream().ok()?;
+    let data = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        stream.decompressed_content().ok()?
+    };
+
+    let components = match largest.color_space.as_deref() {
+        Some("DeviceGray") | Some("CalGray") => 1,
+        Some("DeviceCMYK") => 4,
+        _ => 3,
+    };
+
+    raw_pixels_to_image(&data, width, height, components)
+}
+
+/// Build an image from raw 8-bit Gray/RGB/CMYK samples
+fn raw_pixels_to_image(data: &[u8], width: u32, height: u32, components: usize) -> Option<DynamicImage> {
+    let pixels = (width as usize).checked_mul(height as usize)?;
+    if data.len() < pixels * components {
+        return None;
+    }
+
+    let rgba: Vec<u8> = data
+        .chunks_exact(components)
+        .take(pixels)
+        .flat_map(|px| match components {
+            1 => [px[0], px[0], px[0], 255],
+            4 => {
+                let k = 255 - px[3] as u16;
+                let channel = |c: u8| ((255 - c as u16) * k / 255) as u8;
+                [channel(px[0]), channel(px[1]), channel(px[2]), 255]
+            }
+            _ => [px[0], px[1], px[2], 255],
+        })
+        .collect();
+
+    let buffer: RgbaImage = ImageBuffer::from_raw(width, height, rgba)?;
+    Some(DynamicImage::ImageRgba8(buffer))
+}
+
+fn number(object: &Object) -> Option<f32> {
+    match object {
+        Object::Integer(i) => Some(*i as f32),
+        Object::Real(r) => Some(*r as f32),
+        _ => None,
+    }
+}
+
+/// Graphics state tracked by the vector renderer
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Transform,
+    fill: [u8; 3],
+    stroke: [u8; 3],
+    line_width: f32,
+}
+
+/// Text state tracked between BT/ET
+struct TextState {
+    matrix: Transform,
+    line_matrix: Transform,
+    font_size: f32,
+    leading: f32,
+}
+
+/// Minimal content-stream renderer: paths, fills, strokes, image boxes and
+/// greeked text bars. Enough for a recognizable thumbnail or layout preview.
+struct VectorRenderer {
+    pixmap: Pixmap,
+    page_to_pixels: Transform,
+    state: GraphicsState,
+    stack: Vec<GraphicsState>,
+    text: TextState,
+    path: PathBuilder,
+    /// Current point in user space (needed by the `v` operator)
+    current_point: (f32, f32),
+}
+
+impl VectorRenderer {
+    fn new(media_box: [f32; 4]) -> Result<Self, String> {
+        let [x0, y0, x1, y1] = media_box;
+        let scale = render_scale(x1 - x0, y1 - y0);
+        let width = ((x1 - x0) * scale).ceil().max(1.0) as u32;
+        let height = ((y1 - y0) * scale).ceil().max(1.0) as u32;
+
+        let mut pixmap = Pixmap::new(width, height).ok_or("Failed to allocate page pixmap")?;
+        pixmap.fill(Color::WHITE);
+
+        Ok(Self {
+            pixmap,
+            // PDF space is bottom-up; flip into top-down pixel space
+            page_to_pixels: Transform::from_row(scale, 0.0, 0.0, -scale, -x0 * scale, y1 * scale),
+            state: GraphicsState {
+                ctm: Transform::identity(),
+                fill: [0, 0, 0],
+                stroke: [0, 0, 0],
+                line_width: 1.0,
+            },
+            stack: Vec::new(),
+            text: TextState {
+                matrix: Transform::identity(),
+                line_matrix: Transform::identity(),
+                font_size: 12.0,
+                leading: 0.0,
+            },
+            path: PathBuilder::new(),
+            current_point: (0.0, 0.0),
+        })
+    }
+
+    fn render(mut self, operations: &[lopdf::content::Operation]) -> DynamicImage {
+        for op in operations {
+            let nums: Vec<f32> = op.operands.iter().filter_map(number).collect();
+            self.apply(&op.operator, &nums, &op.operands);
+        }
+
+        let (width, height) = (self.pixmap.width(), self.pixmap.height());
+        let image = RgbaImage::from_raw(width, height, self.pixmap.take())
+            .unwrap_or_else(|| RgbaImage::new(width, height));
+        DynamicImage::ImageRgba8(image)
+    }
+
+    fn apply(&mut self, operator: &str, nums: &[f32], operands: &[Object]) {
+        match (operator, nums) {
+            // Graphics state
+            ("q", _) => self.stack.push(self.state.clone()),
+            ("Q", _) => {
+                if let Some(state) = self.stack.pop() {
+                    self.state = state;
+                }
+            }
+            ("cm", &[a, b, c, d, e, f]) => {
+                self.state.ctm = self.state.ctm.pre_concat(Transform::from_row(a, b, c, d, e, f));
+            }
+            ("w", &[width]) => self.state.line_width = width,
+
+            // Colors
+            ("g", &[gray]) => self.state.fill = gray_color(gray),
+            ("G", &[gray]) => self.state.stroke = gray_color(gray),
+            ("rg", &[r, g, b]) => self.state.fill = rgb_color(r, g, b),
+            ("RG", &[r, g, b]) => self.state.stroke = rgb_color(r, g, b),
+            ("k", &[c, m, y, k]) => self.state.fill = cmyk_color(c, m, y, k),
+            ("K", &[c, m, y, k]) => self.state.stroke = cmyk_color(c, m, y, k),
+
+            // Path construction
+            ("m", &[x, y]) => {
+                self.path.move_to(x, y);
+                self.current_point = (x, y);
+            }
+            ("l", &[x, y]) => {
+                self.path.line_to(x, y);
+                self.current_point = (x, y);
+            }
+            ("c", &[x1, y1, x2, y2, x3, y3]) => {
+                self.path.cubic_to(x1, y1, x2, y2, x3, y3);
+                self.current_point = (x3, y3);
+            }
+            ("v", &[x2, y2, x3, y3]) => {
+                let (x1, y1) = self.current_point;
+                self.path.cubic_to(x1, y1, x2, y2, x3, y3);
+                self.current_point = (x3, y3);
+            }
+            ("y", &[x1, y1, x3, y3]) => {
+                self.path.cubic_to(x1, y1, x3, y3, x3, y3);
+                self.current_point = (x3, y3);
+            }
+            ("h", _) => self.path.close(),
+            ("re", &[x, y, w, h]) => {
+                if let Some(rect) = Rect::from_xywh(x.min(x + w), y.min(y + h), w.abs(), h.abs()) {
+                    self.path.push_rect(rect);
+                }
+                self.current_point = (x, y);
+            }
+
+            // Path painting
+            ("f" | "F", _) => self.paint_path(true, false, FillRule::Winding),
+            ("f*", _) => self.paint_path(true, false, FillRule::EvenOdd),
+            ("S", _) => self.paint_path(false, true, FillRule::Winding),
+            ("s", _) => {
+                self.path.close();
+                self.paint_path(false, true, FillRule::Winding);
+            }
+            ("B", _) => self.paint_path(true, true, FillRule::Winding),
+            ("B*", _) => self.paint_path(true, true, FillRule::EvenOdd),
+            ("b", _) => {
+                self.path.close();
+                self.paint_path(true, true, FillRule::Winding);
+            }
+            ("b*", _) => {
+                self.path.close();
+                self.paint_path(true, true, FillRule::EvenOdd);
+            }
+            ("n", _) => self.path = PathBuilder::new(),
+
+            // Images and forms occupy the unit square in user space
+            ("Do", _) => {
+                if let Some(rect) = Rect::from_xywh(0.0, 0.0, 1.0, 1.0) {
+                    let transform = self.page_to_pixels.pre_concat(self.state.ctm);
+                    let paint = solid_paint([215, 215, 215]);
+                    self.pixmap.fill_rect(rect, &paint, transform, None);
+                }
+            }
+
+            // Text positioning
+            ("BT", _) => {
+                self.text.matrix = Transform::identity();
+                self.text.line_matrix = Transform::identity();
+            }
+            ("Tf", &[size]) => self.text.font_size = size,
+            ("TL", &[leading]) => self.text.leading = leading,
+            ("Td", &[tx, ty]) => self.move_text_line(tx, ty),
+            ("TD", &[tx, ty]) => {
+                self.text.leading = -ty;
+                self.move_text_line(tx, ty);
+            }
+            ("Tm", &[a, b, c, d, e, f]) => {
+                self.text.line_matrix = Transform::from_row(a, b, c, d, e, f);
+                self.text.matrix = self.text.line_matrix;
+            }
+            ("T*", _) => self.move_text_line(0.0, -self.text.leading),
+
+            // Text showing
+            ("Tj", _) => self.show_text(text_length(operands)),
+            ("'" | "\"", _) => {
+                self.move_text_line(0.0, -self.text.leading);
+                self.show_text(text_length(operands));
+            }
+            ("TJ", _) => {
+                let advance = operands
+                    .first()
+                    .and_then(|o| o.as_array().ok())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .map(|item| match item {
+                                Object::String(bytes, _) => bytes.len() as f32,
+                                other => -number(other).unwrap_or(0.0) / 1000.0 * 2.0,
+                            })
+                            .sum::<f32>()
+                    })
+                    .unwrap_or(0.0);
+                self.show_text(advance);
+            }
+
+            _ => {}
+        }
+    }
+
+    fn paint_path(&mut self, fill: bool, stroke: bool, rule: FillRule) {
+        let builder = std::mem::replace(&mut self.path, PathBuilder::new());
+        let Some(path) = builder.finish() else {
+            return;
+        };
+        let transform = self.page_to_pixels.pre_concat(self.state.ctm);
+
+        if fill {
+            let paint = solid_paint(self.state.fill);
+            self.pixmap.fill_path(&path, &paint, rule, transform, None);
+        }
+        if stroke {
+            let paint = solid_paint(self.state.stroke);
+            let stroke = Stroke {
+                width: self.state.line_width.max(0.5),
+                ..Stroke::default()
+            };
+            self.pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+        }
+    }
+
+    fn move_text_line(&mut self, tx: f32, ty: f32) {
+        self.text.line_matrix = self.text.line_matrix.pre_translate(tx, ty);
+        self.text.matrix = self.text.line_matrix;
+    }
+
+    /// Draw a bar where `chars` glyphs would be (average glyph = half an em)
+    fn show_text(&mut self, chars: f32) {
+        let size = self.text.font_size.abs().max(1.0);
+        let width = chars.max(0.0) * size * 0.5;
+
+        if let Some(rect) = Rect::from_xywh(0.0, 0.0, width, size * 0.6) {
+            let transform = self
+                .page_to_pixels
+                .pre_concat(self.state.ctm)
+                .pre_concat(self.text.matrix);
+            let [r, g, b] = self.state.fill;
+            // Lighten so greeked text reads as text rather than solid blocks
+            let paint = solid_paint([r / 2 + 100, g / 2 + 100, b / 2 + 100]);
+            self.pixmap.fill_rect(rect, &paint, transform, None);
+        }
+
+        self.text.matrix = self.text.matrix.pre_translate(width, 0.0);
+    }
+}
+
+fn text_length(operands: &[Object]) -> f32 {
+    operands
+        .iter()
+        .filter_map(|o| match o {
+            Object::String(bytes, _) => Some(bytes.len() as f32),
+            _ => None,
+        })
+        .sum()
+}
+
+fn solid_paint([r, g, b]: [u8; 3]) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn unit_to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn gray_color(gray: f32) -> [u8; 3] {
+    let v = unit_to_byte(gray);
+    [v, v, v]
+}
+
+fn rgb_color(r: f32, g: f32, b: f32) -> [u8; 3] {
+    [unit_to_byte(r), unit_to_byte(g), unit_to_byte(b)]
+}
+
+fn cmyk_color(c: f32, m: f32, y: f32, k: f32) -> [u8; 3] {
+    let k = 1.0 - k.clamp(0.0, 1.0);
+    [
+        unit_to_byte((1.0 - c) * k),
+        unit_to_byte((1.0 - m) * k),
+        unit_to_byte((1.0 - y) * k),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Operation;
+    use lopdf::{dictionary, Stream};
+    use std::io::Cursor;
+
+    /// Build a one-page PDF with the given content and optional image XObject
+    fn build_pdf(operations: Vec<Operation>, image: Option<Stream>) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+
+        let mut resources = dictionary! {};
+        if let Some(image) = image {
+            let image_id = doc.add_object(image);
+            resources.set("XObject", dictionary! { "Im0" => image_id });
+        }
+        let resources_id = doc.add_object(resources);
+
+        let content = Content { operations };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => resources_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 200.into(), 100.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn jpeg_xobject(width: u32, height: u32) -> Stream {
+        let img = DynamicImage::new_rgb8(width, height);
+        let mut jpeg = Vec::new();
+        img.write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .unwrap();
+
+        Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => width as i64,
+                "Height" => height as i64,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            },
+            jpeg,
+        )
+    }
+
+    #[test]
+    fn test_vector_render_filled_rect() {
+        let ops = vec![
+            Operation::new("rg", vec![1.into(), 0.into(), 0.into()]),
+            Operation::new("re", vec![50.into(), 25.into(), 100.into(), 50.into()]),
+            Operation::new("f", vec![]),
+        ];
+        let page = rasterize_page_builtin(&build_pdf(ops, None), 0).unwrap();
+        assert_eq!(page.backend, PdfRasterBackend::VectorBasic);
+
+        let rgba = page.image.to_rgba8();
+        let (w, h) = rgba.dimensions();
+        // Inherited MediaBox 200x100 keeps its aspect ratio
+        assert!((w as i64 - 2 * h as i64).abs() <= 2);
+
+        let center = rgba.get_pixel(w / 2, h / 2);
+        assert_eq!(center.0[0], 255);
+        assert!(center.0[1] < 10);
+
+        let corner = rgba.get_pixel(2, 2);
+        assert_eq!(corner.0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_vector_render_greeks_text() {
+        let ops = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![10.into(), 50.into()]),
+            Operation::new("Tj", vec![Object::string_literal("Hello world, this is text")]),
+            Operation::new("ET", vec![]),
+        ];
+        let page = rasterize_page_builtin(&build_pdf(ops, None), 0).unwrap();
+        let rgba = page.image.to_rgba8();
+
+        let non_white = rgba.pixels().filter(|p| p.0 != [255, 255, 255, 255]).count();
+        assert!(non_white > 0);
+    }
+
+    #[test]
+    fn test_scanned_page_uses_embedded_image() {
+        let ops = vec![
+            Operation::new("q", vec![]),
+            Operation::new("cm", vec![200.into(), 0.into(), 0.into(), 100.into(), 0.into(), 0.into()]),
+            Operation::new("Do", vec!["Im0".into()]),
+            Operation::new("Q", vec![]),
+        ];
+        let page = rasterize_page_builtin(&build_pdf(ops, Some(jpeg_xobject(640, 320))), 0).unwrap();
+
+        assert_eq!(page.backend, PdfRasterBackend::EmbeddedImage);
+        assert_eq!(page.image.width(), 640);
+        assert_eq!(page.image.height(), 320);
+    }
+
+    #[test]
+    fn test_small_logo_is_not_a_scan() {
+        let ops = vec![
+            Operation::new("re", vec![0.into(), 0.into(), 10.into(), 10.into()]),
+            Operation::new("f", vec![]),
+            Operation::new("Do", vec!["Im0".into()]),
+        ];
+        let page = rasterize_page_builtin(&build_pdf(ops, Some(jpeg_xobject(32, 32))), 0).unwrap();
+        assert_eq!(page.backend, PdfRasterBackend::VectorBasic);
+    }
+
+    #[test]
+    fn test_missing_page() {
+        let bytes = build_pdf(vec![Operation::new("n", vec![])], None);
+        assert!(rasterize_page_builtin(&bytes, 3).is_err());
+    }
+
+    #[test]
+    fn test_page_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("one.pdf");
+        std::fs::write(&pdf, build_pdf(vec![Operation::new("n", vec![])], None)).unwrap();
+        assert_eq!(page_count(&pdf).unwrap(), 1);
+
+        let broken = dir.path().join("broken.pdf");
+        std::fs::write(&broken, b"%PDF-1.5\n1 0 obj << /Type /Pages /Kids [1 0 R] >>").unwrap();
+        assert!(page_count(&broken).is_err());
+    }
+
+    #[test]
+    fn test_raw_pixels_gray_and_cmyk() {
+        let gray = raw_pixels_to_image(&[0, 255], 2, 1, 1).unwrap().to_rgba8();
+        assert_eq!(gray.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(gray.get_pixel(1, 0).0, [255, 255, 255, 255]);
+
+        let cmyk = raw_pixels_to_image(&[0, 0, 0, 0], 1, 1, 4).unwrap().to_rgba8();
+        assert_eq!(cmyk.get_pixel(0, 0).0, [255, 255, 255, 255]);
+
+        assert!(raw_pixels_to_image(&[0, 0], 2, 2, 1).is_none());
+    }
+
+    #[test]
+    fn test_capabilities_chain() {
+        let caps = capabilities();
+        assert_eq!(caps.active_pdf_backend, caps.pdf_backends[0]);
+        assert_eq!(caps.pdf_backends.last(), Some(&PdfRasterBackend::VectorBasic));
+        assert_eq!(caps.pdfium_available, caps.pdf_backends.contains(&PdfRasterBackend::Pdfium));
+    }
+}
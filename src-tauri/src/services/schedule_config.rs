@@ -0,0 +1,374 @@
+//! Persistent scheduled organize jobs
+//!
+//! A scheduled job organizes one folder with a rule set, a saved prompt, a
+//! Blueprint or a Blueprint from the library whenever its cron expression (`0 3 * * *`, `@weekly`, evaluated
+//! in local time) comes due. The list is stored at
+//! `<config dir>/sentinel/scheduled_jobs.json`; runs are recorded in
+//! [`super::schedule_history`].
+
+use chrono::{DateTime, Duration, Local, Utc};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai::v2::vfs::OrganizationRule;
+use crate::ai::v2::{Blueprint, BuilderOptions};
+use crate::execution::ConflictPolicy;
+
+/// Upper bound on occurrences walked when looking for the latest missed one
+const MAX_MISSED_OCCURRENCES: usize = 100_000;
+
+/// How a scheduled job plans the folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleSource {
+    /// Apply organization rules (DSL)
+    Rules { rules: Vec<OrganizationRule> },
+    /// Run the organize agent with a saved prompt
+    Prompt { prompt: String },
+    /// Run the organize agent with a fixed Blueprint instead of designing one
+    Blueprint {
+        blueprint: Blueprint,
+        /// Extra instructions for the agent
+        #[serde(default)]
+        instructions: Option<String>,
+    },
+    /// Slot files into a Blueprint from the library, without the agent
+    #[serde(rename_all = "camelCase")]
+    SavedBlueprint {
+        blueprint_id: String,
+        /// Pinned version (the latest when unset)
+        #[serde(default)]
+        version: Option<u32>,
+        #[serde(default)]
+        options: BuilderOptions,
+    },
+}
+
+impl ScheduleSource {
+    /// Stable name used in events and logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScheduleSource::Rules { .. } => "rules",
+            ScheduleSource::Prompt { .. } => "prompt",
+            ScheduleSource::Blueprint { .. } => "blueprint",
+            ScheduleSource::SavedBlueprint { .. } => "saved_blueprint",
+        }
+    }
+}
+
+/// What to do about runs missed while the app was closed or the machine slept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUp {
+    /// Record the missed run and wait for the next one
+    #[default]
+    Skip,
+    /// Run once as soon as possible, however many runs were missed
+    RunOnce,
+}
+
+/// Configuration for one scheduled job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    /// Folder to organize
+    pub folder: String,
+    pub source: ScheduleSource,
+    /// Cron expression (5 fields or @hourly/@daily/@weekly/@monthly), local time
+    pub schedule: String,
+    /// Unattended runs skip conflicting destinations by default
+    #[serde(default = "default_conflict_policy")]
+    pub conflict_policy: ConflictPolicy,
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    /// Only catch up on runs missed by less than this many hours (no limit when unset)
+    #[serde(default)]
+    pub catch_up_within_hours: Option<u32>,
+    /// Disabled jobs stay configured but never run on their own
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Runs due before this are not considered missed
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_conflict_policy() -> ConflictPolicy {
+    ConflictPolicy::Skip
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ScheduledJob {
+    pub fn new(
+        name: impl Into<String>,
+        folder: impl Into<String>,
+        source: ScheduleSource,
+        schedule: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.into(),
+            folder: folder.into(),
+            source,
+            schedule: schedule.into(),
+            conflict_policy: default_conflict_policy(),
+            catch_up: CatchUp::Skip,
+            catch_up_within_hours: None,
+            enabled: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    pub fn with_catch_up(mut self, catch_up: CatchUp, within_hours: Option<u32>) -> Self {
+        self.catch_up = catch_up;
+        self.catch_up_within_hours = within_hours;
+        self
+    }
+
+    /// Parse the cron expression
+    pub fn cron(&self) -> Result<Cron, String> {
+        Cron::new(self.schedule.trim())
+            .parse()
+            .map_err(|e| format!("Invalid schedule '{}': {}", self.schedule, e))
+    }
+
+    /// Check the schedule and source before the job is saved
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Scheduled job name cannot be empty".to_string());
+        }
+        if self.folder.trim().is_empty() {
+            return Err("Scheduled job folder cannot be empty".to_string());
+        }
+        match &self.source {
+            ScheduleSource::Rules { rules } if rules.is_empty() => {
+                return Err("A rules job needs at least one rule".to_string())
+            }
+            ScheduleSource::Prompt { prompt } if prompt.trim().is_empty() => {
+                return Err("A prompt job needs a prompt".to_string())
+            }
+            ScheduleSource::Blueprint { blueprint, .. } if blueprint.structure.is_empty() => {
+                return Err("A blueprint job needs a Blueprint with folders".to_string())
+            }
+            ScheduleSource::SavedBlueprint { blueprint_id, .. }
+                if blueprint_id.trim().is_empty() =>
+            {
+                return Err("A saved blueprint job needs a Blueprint id".to_string())
+            }
+            _ => {}
+        }
+        self.cron().map(|_| ())
+    }
+
+    /// First run strictly after `after`
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+        let cron = self.cron()?;
+        Ok(cron
+            .find_next_occurrence(&after.with_timezone(&Local), false)
+            .ok()
+            .map(|next| next.with_timezone(&Utc)))
+    }
+
+    /// Latest run that came due after `after` and no later than `now`
+    pub fn latest_due(
+        &self,
+        after: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, String> {
+        let cron = self.cron()?;
+        Ok(cron
+            .iter_after(after.with_timezone(&Local))
+            .take(MAX_MISSED_OCCURRENCES)
+            .map(|run| run.with_timezone(&Utc))
+            .take_while(|run| *run <= now)
+            .last())
+    }
+
+    /// Whether a run missed by `late` should still be caught up
+    pub fn catches_up(&self, late: Duration) -> bool {
+        self.catch_up == CatchUp::RunOnce
+            && self
+                .catch_up_within_hours
+                .is_none_or(|hours| late <= Duration::hours(i64::from(hours)))
+    }
+}
+
+/// The saved list of scheduled jobs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    pub jobs: Vec<ScheduledJob>,
+}
+
+impl ScheduleConfig {
+    /// Location of the config file
+    pub fn file_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("sentinel").join("scheduled_jobs.json"))
+    }
+
+    /// Load the saved jobs; a missing or unreadable file means none
+    pub fn load() -> Self {
+        let Some(path) = Self::file_path() else {
+            return Self::default();
+        };
+        Self::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("[Scheduler] {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::file_path().ok_or("Could not determine config directory")?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize scheduled jobs: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write scheduled jobs: {}", e))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ScheduledJob> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    /// Add a job or replace the existing entry with its id
+    pub fn upsert(&mut self, job: ScheduledJob) {
+        match self.jobs.iter_mut().find(|j| j.id == job.id) {
+            Some(existing) => *existing = job,
+            None => self.jobs.push(job),
+        }
+    }
+
+    /// Remove a job; returns whether it was configured
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.jobs.len();
+        self.jobs.retain(|j| j.id != id);
+        self.jobs.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn nightly() -> ScheduledJob {
+        ScheduledJob::new(
+            "Scans",
+            "/home/me/Scans",
+            ScheduleSource::Prompt {
+                prompt: "File scans by vendor".to_string(),
+            },
+            "0 2 * * *",
+        )
+    }
+
+    #[test]
+    fn test_schedule_times() {
+        let job = nightly();
+        assert_eq!(
+            job.next_run_after(local(2024, 3, 1, 2, 0)).unwrap(),
+            Some(local(2024, 3, 2, 2, 0))
+        );
+
+        // Three nights missed: only the latest counts
+        let due = job
+            .latest_due(local(2024, 3, 1, 2, 0), local(2024, 3, 4, 9, 30))
+            .unwrap();
+        assert_eq!(due, Some(local(2024, 3, 4, 2, 0)));
+        assert_eq!(
+            job.latest_due(local(2024, 3, 4, 2, 0), local(2024, 3, 4, 9, 30))
+                .unwrap(),
+            None
+        );
+
+        let weekly = ScheduledJob {
+            schedule: "@weekly".to_string(),
+            ..nightly()
+        };
+        assert!(weekly.validate().is_ok());
+        let broken = ScheduledJob {
+            schedule: "every night".to_string(),
+            ..nightly()
+        };
+        assert!(broken.validate().is_err());
+    }
+
+    #[test]
+    fn test_catch_up_policy() {
+        let job = nightly();
+        assert!(!job.catches_up(Duration::hours(1)));
+
+        let job = job.with_catch_up(CatchUp::RunOnce, Some(12));
+        assert!(job.catches_up(Duration::hours(11)));
+        assert!(!job.catches_up(Duration::hours(13)));
+        assert!(job
+            .with_catch_up(CatchUp::RunOnce, None)
+            .catches_up(Duration::days(30)));
+    }
+
+    #[test]
+    fn test_defaults_and_round_trip() {
+        let json = r#"{"id": "downloads", "name": "Downloads", "folder": "/home/me/Downloads",
+            "schedule": "0 9 * * 1",
+            "source": {"type": "rules", "rules": [{"name": "PDFs", "if": "file.ext == 'pdf'", "thenMoveTo": "Docs"}]}}"#;
+        let job: ScheduledJob = serde_json::from_str(json).unwrap();
+        assert!(job.enabled);
+        assert_eq!(job.catch_up, CatchUp::Skip);
+        assert_eq!(job.conflict_policy, ConflictPolicy::Skip);
+        assert_eq!(job.source.name(), "rules");
+
+        let empty = ScheduledJob {
+            source: ScheduleSource::Rules { rules: vec![] },
+            ..job.clone()
+        };
+        assert!(empty.validate().is_err());
+
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("sentinel/scheduled_jobs.json");
+        let mut config = ScheduleConfig::default();
+        config.upsert(job);
+        config.upsert(nightly());
+        config.save_to(&file).unwrap();
+
+        let mut loaded = ScheduleConfig::load_from(&file).unwrap();
+        assert_eq!(loaded.jobs.len(), 2);
+        assert_eq!(loaded.get("downloads").unwrap().schedule, "0 9 * * 1");
+        assert!(loaded.remove("downloads"));
+        assert!(!loaded.remove("downloads"));
+    }
+}
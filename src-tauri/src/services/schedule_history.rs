@@ -0,0 +1,343 @@
+//! Run history of scheduled organize jobs
+//!
+//! Every run (and every run skipped because it was missed) is stored in a
+//! small SQLite log with its outcome and WAL job id, so the UI can show what
+//! a schedule did last night and offer undo. The latest `scheduled_for` of a
+//! job is also how the scheduler knows which runs are still due.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Runs older than this are dropped
+const RETENTION_DAYS: i64 = 180;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS schedule_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        schedule_id TEXT NOT NULL,
+        schedule_name TEXT NOT NULL,
+        trigger TEXT NOT NULL,
+        status TEXT NOT NULL,
+        scheduled_for INTEGER,
+        started_at INTEGER NOT NULL,
+        finished_at INTEGER NOT NULL,
+        job_id TEXT,
+        operations INTEGER NOT NULL DEFAULT 0,
+        completed INTEGER NOT NULL DEFAULT 0,
+        failed INTEGER NOT NULL DEFAULT 0,
+        skipped INTEGER NOT NULL DEFAULT 0,
+        renamed INTEGER NOT NULL DEFAULT 0,
+        error TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON schedule_runs(schedule_id, started_at);
+"#;
+
+/// What started a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    /// The schedule came due
+    Scheduled,
+    /// A missed run, caught up after the app started or the machine woke
+    CatchUp,
+    /// Started by hand ("run now")
+    Manual,
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    /// Planning failed or some operations failed
+    Failed,
+    /// The plan had no operations
+    NothingToDo,
+    /// Missed and not caught up (see the job's catch-up policy)
+    Missed,
+}
+
+impl RunTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunTrigger::Scheduled => "scheduled",
+            RunTrigger::CatchUp => "catch_up",
+            RunTrigger::Manual => "manual",
+        }
+    }
+
+    fn parse(text: &str) -> Self {
+        match text {
+            "catch_up" => RunTrigger::CatchUp,
+            "manual" => RunTrigger::Manual,
+            _ => RunTrigger::Scheduled,
+        }
+    }
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::NothingToDo => "nothing_to_do",
+            RunStatus::Missed => "missed",
+        }
+    }
+
+    fn parse(text: &str) -> Self {
+        match text {
+            "succeeded" => RunStatus::Succeeded,
+            "nothing_to_do" => RunStatus::NothingToDo,
+            "missed" => RunStatus::Missed,
+            _ => RunStatus::Failed,
+        }
+    }
+}
+
+/// One run of a scheduled job
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    /// Row id (0 until recorded)
+    pub id: i64,
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    /// The schedule slot this run covers (none for manual runs)
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// WAL journal of the executed plan, for rollback
+    pub job_id: Option<String>,
+    pub operations: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub renamed: usize,
+    pub error: Option<String>,
+}
+
+impl ScheduleRun {
+    /// A run starting now; the caller fills in the outcome
+    pub fn start(
+        schedule_id: &str,
+        schedule_name: &str,
+        trigger: RunTrigger,
+        scheduled_for: Option<DateTime<Utc>>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: 0,
+            schedule_id: schedule_id.to_string(),
+            schedule_name: schedule_name.to_string(),
+            trigger,
+            status: RunStatus::NothingToDo,
+            scheduled_for,
+            started_at: now,
+            finished_at: now,
+            job_id: None,
+            operations: 0,
+            completed: 0,
+            failed: 0,
+            skipped: 0,
+            renamed: 0,
+            error: None,
+        }
+    }
+}
+
+/// SQLite-backed run history
+pub struct ScheduleHistory {
+    db_path: PathBuf,
+}
+
+impl ScheduleHistory {
+    /// Default history directory (next to the watch journal)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("com.sentinel.filemanager"))
+    }
+
+    /// Open the history in the default location
+    pub fn open_default() -> Result<Self, String> {
+        let dir = Self::default_dir().ok_or("Could not determine cache directory")?;
+        Self::open(&dir)
+    }
+
+    /// Open or create the history database in `dir`
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+
+        let history = Self {
+            db_path: dir.join("schedule_history.db"),
+        };
+        history
+            .conn()?
+            .execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize schedule history: {}", e))?;
+        Ok(history)
+    }
+
+    fn conn(&self) -> Result<rusqlite::Connection, String> {
+        rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open schedule history: {}", e))
+    }
+
+    /// Store a finished run and drop runs past the retention limit; returns its id
+    pub fn record(&self, run: &ScheduleRun) -> Result<i64, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to record scheduled run: {}", e);
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(db_err)?;
+        tx.execute(
+            "INSERT INTO schedule_runs (schedule_id, schedule_name, trigger, status, scheduled_for,
+                started_at, finished_at, job_id, operations, completed, failed, skipped, renamed, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                run.schedule_id,
+                run.schedule_name,
+                run.trigger.as_str(),
+                run.status.as_str(),
+                run.scheduled_for.map(|t| t.timestamp_millis()),
+                run.started_at.timestamp_millis(),
+                run.finished_at.timestamp_millis(),
+                run.job_id,
+                run.operations as i64,
+                run.completed as i64,
+                run.failed as i64,
+                run.skipped as i64,
+                run.renamed as i64,
+                run.error,
+            ],
+        )
+        .map_err(db_err)?;
+        let id = tx.last_insert_rowid();
+
+        let cutoff = (Utc::now() - Duration::days(RETENTION_DAYS)).timestamp_millis();
+        tx.execute(
+            "DELETE FROM schedule_runs WHERE started_at < ?1",
+            params![cutoff],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)?;
+        Ok(id)
+    }
+
+    /// Runs of one job (or all jobs), newest first
+    pub fn runs(
+        &self,
+        schedule_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ScheduleRun>, String> {
+        let db_err = |e: rusqlite::Error| format!("Failed to query schedule history: {}", e);
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, schedule_id, schedule_name, trigger, status, scheduled_for, started_at,
+                    finished_at, job_id, operations, completed, failed, skipped, renamed, error
+                 FROM schedule_runs WHERE ?1 IS NULL OR schedule_id = ?1
+                 ORDER BY started_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![schedule_id, limit as i64], |row| {
+                let trigger: String = row.get(3)?;
+                let status: String = row.get(4)?;
+                let scheduled_for: Option<i64> = row.get(5)?;
+                let count = |i: usize| row.get::<_, i64>(i).map(|n| n.max(0) as usize);
+                Ok(ScheduleRun {
+                    id: row.get(0)?,
+                    schedule_id: row.get(1)?,
+                    schedule_name: row.get(2)?,
+                    trigger: RunTrigger::parse(&trigger),
+                    status: RunStatus::parse(&status),
+                    scheduled_for: scheduled_for.map(from_millis),
+                    started_at: from_millis(row.get(6)?),
+                    finished_at: from_millis(row.get(7)?),
+                    job_id: row.get(8)?,
+                    operations: count(9)?,
+                    completed: count(10)?,
+                    failed: count(11)?,
+                    skipped: count(12)?,
+                    renamed: count(13)?,
+                    error: row.get(14)?,
+                })
+            })
+            .map_err(db_err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+    }
+
+    /// Latest schedule slot a job has run for or skipped
+    pub fn last_slot(&self, schedule_id: &str) -> Result<Option<DateTime<Utc>>, String> {
+        let slot: Option<i64> = self
+            .conn()?
+            .query_row(
+                "SELECT MAX(scheduled_for) FROM schedule_runs WHERE schedule_id = ?1",
+                params![schedule_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query schedule history: {}", e))?
+            .flatten();
+        Ok(slot.map(from_millis))
+    }
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_query_runs() {
+        let dir = TempDir::new().unwrap();
+        let history = ScheduleHistory::open(dir.path()).unwrap();
+        let slot = Utc.with_ymd_and_hms(2024, 3, 4, 2, 0, 0).unwrap();
+        assert_eq!(history.last_slot("scans").unwrap(), None);
+
+        let mut missed = ScheduleRun::start(
+            "scans",
+            "Scans",
+            RunTrigger::Scheduled,
+            Some(slot - Duration::days(1)),
+        );
+        missed.status = RunStatus::Missed;
+        history.record(&missed).unwrap();
+
+        let mut run = ScheduleRun::start("scans", "Scans", RunTrigger::CatchUp, Some(slot));
+        run.status = RunStatus::Succeeded;
+        run.job_id = Some("schedule-scans".to_string());
+        run.operations = 4;
+        run.completed = 3;
+        run.skipped = 1;
+        history.record(&run).unwrap();
+        history
+            .record(&ScheduleRun::start(
+                "downloads",
+                "Downloads",
+                RunTrigger::Manual,
+                None,
+            ))
+            .unwrap();
+
+        assert_eq!(history.last_slot("scans").unwrap(), Some(slot));
+        assert_eq!(history.last_slot("downloads").unwrap(), None);
+
+        let runs = history.runs(Some("scans"), 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].trigger, RunTrigger::CatchUp);
+        assert_eq!(runs[0].status, RunStatus::Succeeded);
+        assert_eq!((runs[0].completed, runs[0].skipped), (3, 1));
+        assert_eq!(runs[1].status, RunStatus::Missed);
+        assert_eq!(history.runs(None, 10).unwrap().len(), 3);
+        assert_eq!(history.runs(None, 1).unwrap().len(), 1);
+    }
+}
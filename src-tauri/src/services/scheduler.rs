@@ -0,0 +1,425 @@
+//! Background runner for scheduled organize jobs
+//!
+//! The scheduler wakes every [`TICK`], compares each enabled job's cron
+//! schedule with the last slot in its run history and runs the jobs that
+//! came due. Plans go through the WAL and the DAG executor like any other
+//! execution, and the journal is kept so a run can be rolled back with
+//! `wal_rollback_job`. A slot found more than [`GRACE`] late (the app was
+//! closed or the machine slept) is caught up or recorded as missed,
+//! depending on the job's catch-up policy. Every run is reported through
+//! `sentinel://schedule-run`.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::ai::{
+    organize_with_blueprint, run_v2_agentic_organize_with_blueprint, ExpandableDetail,
+    ProgressEvent,
+};
+use crate::api;
+use crate::events::{NoopSink, SharedEventSink};
+use crate::execution::ExecutionConfig;
+use crate::jobs::OrganizePlan;
+
+use super::blueprint_library::BlueprintLibrary;
+use super::schedule_config::{ScheduleConfig, ScheduleSource, ScheduledJob};
+use super::schedule_history::{RunStatus, RunTrigger, ScheduleHistory, ScheduleRun};
+
+/// How often due jobs are checked
+const TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// A slot found later than this counts as missed rather than on time
+const GRACE: Duration = Duration::minutes(5);
+
+/// Jobs currently running (a job never overlaps itself)
+#[derive(Default)]
+pub struct SchedulerState {
+    running: HashSet<String>,
+}
+
+pub type SchedulerHandle = Arc<Mutex<SchedulerState>>;
+
+/// Create a new scheduler handle
+pub fn create_scheduler_handle() -> SchedulerHandle {
+    Arc::new(Mutex::new(SchedulerState::default()))
+}
+
+fn lock_state(handle: &SchedulerHandle) -> MutexGuard<'_, SchedulerState> {
+    handle.lock().unwrap_or_else(|poisoned| {
+        eprintln!("[Scheduler] State mutex was poisoned, recovering...");
+        poisoned.into_inner()
+    })
+}
+
+/// Marks a job as running until dropped
+struct RunningGuard<'a> {
+    handle: &'a SchedulerHandle,
+    id: String,
+}
+
+impl<'a> RunningGuard<'a> {
+    fn acquire(handle: &'a SchedulerHandle, job: &ScheduledJob) -> Option<Self> {
+        lock_state(handle)
+            .running
+            .insert(job.id.clone())
+            .then(|| Self {
+                handle,
+                id: job.id.clone(),
+            })
+    }
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        lock_state(self.handle).running.remove(&self.id);
+    }
+}
+
+/// A saved job with its next run and latest outcome
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJobStatus {
+    #[serde(flatten)]
+    pub config: ScheduledJob,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<ScheduleRun>,
+    pub running: bool,
+    /// Why the schedule cannot be evaluated
+    pub error: Option<String>,
+}
+
+/// Saved jobs with their runtime state
+pub fn job_statuses(handle: &SchedulerHandle) -> Vec<ScheduledJobStatus> {
+    let config = ScheduleConfig::load();
+    let history = ScheduleHistory::open_default()
+        .map_err(|e| eprintln!("[Scheduler] {}", e))
+        .ok();
+    let now = Utc::now();
+
+    config
+        .jobs
+        .into_iter()
+        .map(|job| {
+            let (next_run, error) = match job.next_run_after(now) {
+                Ok(next) => (next.filter(|_| job.enabled), None),
+                Err(e) => (None, Some(e)),
+            };
+            let last_run = history
+                .as_ref()
+                .and_then(|h| h.runs(Some(&job.id), 1).ok())
+                .and_then(|runs| runs.into_iter().next());
+            ScheduledJobStatus {
+                running: lock_state(handle).running.contains(&job.id),
+                next_run,
+                last_run,
+                error,
+                config: job,
+            }
+        })
+        .collect()
+}
+
+/// What a job should do at a tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum Due {
+    Run {
+        trigger: RunTrigger,
+        slot: DateTime<Utc>,
+    },
+    /// The slot is too late to run under the job's catch-up policy
+    Missed { slot: DateTime<Utc> },
+}
+
+/// Decide whether `job` is due at `now`, given the last slot it ran for
+/// (or skipped). Only the latest due slot counts, so a job that missed a
+/// week of nightly runs catches up at most once.
+pub fn decide(
+    job: &ScheduledJob,
+    last_slot: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<Due>, String> {
+    let since = last_slot.unwrap_or(job.created_at);
+    let Some(slot) = job.latest_due(since, now)? else {
+        return Ok(None);
+    };
+
+    let late = now - slot;
+    Ok(Some(if late <= GRACE {
+        Due::Run {
+            trigger: RunTrigger::Scheduled,
+            slot,
+        }
+    } else if job.catches_up(late) {
+        Due::Run {
+            trigger: RunTrigger::CatchUp,
+            slot,
+        }
+    } else {
+        Due::Missed { slot }
+    }))
+}
+
+/// Run due jobs until the app exits
+pub async fn run_scheduler(handle: SchedulerHandle, events: SharedEventSink) {
+    eprintln!("[Scheduler] Started");
+    loop {
+        tick(&handle, &events, Utc::now()).await;
+        tokio::time::sleep(TICK).await;
+    }
+}
+
+/// Check every enabled job once and start the due ones
+async fn tick(handle: &SchedulerHandle, events: &SharedEventSink, now: DateTime<Utc>) {
+    let config = ScheduleConfig::load();
+    if !config.jobs.iter().any(|job| job.enabled) {
+        return;
+    }
+    let history = match ScheduleHistory::open_default() {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("[Scheduler] {}", e);
+            return;
+        }
+    };
+
+    for job in config.jobs.into_iter().filter(|job| job.enabled) {
+        if lock_state(handle).running.contains(&job.id) {
+            continue;
+        }
+        let due = history
+            .last_slot(&job.id)
+            .and_then(|last_slot| decide(&job, last_slot, now));
+        match due {
+            Ok(Some(Due::Run { trigger, slot })) => {
+                let handle = Arc::clone(handle);
+                let events = Arc::clone(events);
+                tokio::spawn(async move {
+                    if let Err(e) = run_job(&handle, &job, trigger, Some(slot), &events).await {
+                        eprintln!("[Scheduler] {}", e);
+                    }
+                });
+            }
+            Ok(Some(Due::Missed { slot })) => {
+                eprintln!("[Scheduler] Missed '{}' due at {}", job.name, slot);
+                let mut run =
+                    ScheduleRun::start(&job.id, &job.name, RunTrigger::Scheduled, Some(slot));
+                run.status = RunStatus::Missed;
+                match history.record(&run) {
+                    Ok(id) => {
+                        run.id = id;
+                        events.emit("sentinel://schedule-run", &run);
+                    }
+                    Err(e) => eprintln!("[Scheduler] {}", e),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("[Scheduler] '{}': {}", job.name, e),
+        }
+    }
+}
+
+/// Plan and execute a job, record the run and emit `sentinel://schedule-run`.
+/// Fails only when the job is already running or the run cannot be recorded;
+/// planning and execution errors end up in the returned run.
+pub async fn run_job(
+    handle: &SchedulerHandle,
+    job: &ScheduledJob,
+    trigger: RunTrigger,
+    scheduled_for: Option<DateTime<Utc>>,
+    events: &SharedEventSink,
+) -> Result<ScheduleRun, String> {
+    let Some(_guard) = RunningGuard::acquire(handle, job) else {
+        return Err(format!("Scheduled job '{}' is already running", job.name));
+    };
+    eprintln!("[Scheduler] Running '{}' ({})", job.name, trigger.as_str());
+
+    let mut run = ScheduleRun::start(&job.id, &job.name, trigger, scheduled_for);
+    match plan_job(job).await {
+        Ok(plan) if plan.operations.is_empty() => run.status = RunStatus::NothingToDo,
+        Ok(plan) => {
+            let config = ExecutionConfig {
+                on_destination_exists: job.conflict_policy.clone(),
+            };
+            run.operations = plan.operations.len();
+            run.job_id = Some(plan.plan_id.clone());
+            match api::execute_plan(&plan, config, Arc::new(NoopSink)).await {
+                Ok(result) => {
+                    run.completed = result.completed_count;
+                    run.failed = result.failed_count;
+                    run.skipped = result.skipped_count;
+                    run.renamed = result.renamed_count;
+                    run.status = if result.success {
+                        RunStatus::Succeeded
+                    } else {
+                        RunStatus::Failed
+                    };
+                    run.error = (!result.errors.is_empty()).then(|| result.errors.join("; "));
+                }
+                Err(e) => {
+                    run.status = RunStatus::Failed;
+                    run.error = Some(e);
+                }
+            }
+        }
+        Err(e) => {
+            run.status = RunStatus::Failed;
+            run.error = Some(e);
+        }
+    }
+    run.finished_at = Utc::now();
+
+    tracing::info!(
+        job = %job.name,
+        status = run.status.as_str(),
+        completed = run.completed,
+        failed = run.failed,
+        "Scheduled run finished"
+    );
+    run.id = ScheduleHistory::open_default()?.record(&run)?;
+    events.emit("sentinel://schedule-run", &run);
+    Ok(run)
+}
+
+/// Build the plan for one run of `job`
+async fn plan_job(job: &ScheduledJob) -> Result<OrganizePlan, String> {
+    let folder = api::validate_folder(Path::new(&job.folder))?;
+    let description = format!("Scheduled: {}", job.name);
+
+    let mut plan = match &job.source {
+        ScheduleSource::Rules { rules } => {
+            let rules = rules.clone();
+            tokio::task::spawn_blocking(move || api::plan_with_rules(&folder, &rules, &description))
+                .await
+                .map_err(|e| format!("Task failed: {}", e))??
+                .plan
+        }
+        ScheduleSource::Prompt { prompt } => organize_with_agent(&folder, prompt, None).await?,
+        ScheduleSource::Blueprint {
+            blueprint,
+            instructions,
+        } => {
+            let request = instructions
+                .clone()
+                .or_else(|| blueprint.description.clone())
+                .unwrap_or_else(|| {
+                    format!("Organize using the {} layout", blueprint.strategy_name)
+                });
+            organize_with_agent(&folder, &request, Some(blueprint.clone())).await?
+        }
+        ScheduleSource::SavedBlueprint {
+            blueprint_id,
+            version,
+            options,
+        } => {
+            let saved = BlueprintLibrary::load()
+                .resolve(blueprint_id, *version)?
+                .clone();
+            organize_with_blueprint(
+                &folder,
+                &saved.blueprint,
+                options,
+                |_: &str, _: &str, _: Option<Vec<ExpandableDetail>>| {},
+            )
+            .await?
+        }
+    };
+
+    // A fresh journal per run, so each run can be rolled back on its own
+    plan.plan_id = format!("schedule-{}-{}", job.id, Utc::now().timestamp_millis());
+    Ok(plan)
+}
+
+/// Run the organize agent without a UI to report to
+async fn organize_with_agent(
+    folder: &Path,
+    request: &str,
+    blueprint: Option<crate::ai::Blueprint>,
+) -> Result<OrganizePlan, String> {
+    run_v2_agentic_organize_with_blueprint(
+        folder,
+        request,
+        blueprint,
+        |_: &str, _: &str, _: Option<Vec<ExpandableDetail>>| {},
+        None::<fn(ProgressEvent)>,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::schedule_config::CatchUp;
+    use chrono::{Local, TimeZone};
+
+    fn local(d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(2024, 3, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn nightly() -> ScheduledJob {
+        let mut job = ScheduledJob::new(
+            "Downloads",
+            "/home/me/Downloads",
+            ScheduleSource::Prompt {
+                prompt: "Sort by type".to_string(),
+            },
+            "0 2 * * *",
+        );
+        job.created_at = local(1, 12, 0);
+        job
+    }
+
+    #[test]
+    fn test_decide_on_time_and_idle() {
+        let job = nightly();
+        assert_eq!(decide(&job, None, local(2, 1, 59)).unwrap(), None);
+        assert_eq!(
+            decide(&job, None, local(2, 2, 1)).unwrap(),
+            Some(Due::Run {
+                trigger: RunTrigger::Scheduled,
+                slot: local(2, 2, 0)
+            })
+        );
+        // Already ran for that slot
+        assert_eq!(
+            decide(&job, Some(local(2, 2, 0)), local(2, 2, 1)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decide_after_wake() {
+        // Woke at 9:30 after sleeping through three nightly runs
+        let now = local(5, 9, 30);
+        let last = Some(local(2, 2, 0));
+
+        let skip = nightly();
+        assert_eq!(
+            decide(&skip, last, now).unwrap(),
+            Some(Due::Missed {
+                slot: local(5, 2, 0)
+            })
+        );
+
+        let catch_up = nightly().with_catch_up(CatchUp::RunOnce, Some(12));
+        assert_eq!(
+            decide(&catch_up, last, now).unwrap(),
+            Some(Due::Run {
+                trigger: RunTrigger::CatchUp,
+                slot: local(5, 2, 0)
+            })
+        );
+
+        // Too late for the catch-up window
+        assert_eq!(
+            decide(&catch_up, last, local(5, 15, 0)).unwrap(),
+            Some(Due::Missed {
+                slot: local(5, 2, 0)
+            })
+        );
+    }
+}
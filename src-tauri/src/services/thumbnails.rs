@@ -1,3 +1,4 @@
+use super::pdf_raster;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use image::{imageops::FilterType, ImageFormat, RgbaImage};
 use resvg::tiny_skia::Pixmap;
@@ -138,11 +139,26 @@ fn generate_video_thumbnail(path: &Path, size: u32) -> Result<Vec<u8>, String> {
     Ok(buffer.into_inner())
 }
 
-/// Generate thumbnail for a PDF file using ffmpeg (it supports PDF)
+/// Generate thumbnail for a PDF file
+///
+/// Uses the built-in rasterizer chain (pdfium, embedded scan image, basic
+/// vector render) and only falls back to ffmpeg if all of them fail.
 fn generate_pdf_thumbnail(path: &Path, size: u32) -> Result<Vec<u8>, String> {
-    // ffmpeg can extract first page from PDF
-    // Alternatively, we could use a dedicated PDF library
-    generate_video_thumbnail(path, size)
+    match pdf_raster::rasterize_page(path, 0) {
+        Ok(page) => {
+            let thumbnail = page.image.resize(size, size, FilterType::Lanczos3);
+            let mut buffer = Cursor::new(Vec::new());
+            thumbnail
+                .write_to(&mut buffer, ImageFormat::Png)
+                .map_err(|e| format!("Failed to encode PDF thumbnail: {}", e))?;
+            Ok(buffer.into_inner())
+        }
+        Err(e) => {
+            tracing::debug!("PDF rasterization failed for {}: {}", path.display(), e);
+            // ffmpeg can extract first page from some PDFs
+            generate_video_thumbnail(path, size)
+        }
+    }
 }
 
 /// Generate thumbnail for an SVG file using resvg
@@ -0,0 +1,356 @@
+//! Per-folder actions for new files in watched folders
+//!
+//! `Notify` emits the original `sentinel://file-created` event. The other
+//! actions run on the async runtime and report each file through
+//! `sentinel://watch-action`, with enough detail for the UI to offer undo
+//! (`undo_rename` for renames, `wal_rollback_job` for rule runs).
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::ai::v2::vfs::OrganizationRule;
+use crate::ai::AnthropicClient;
+use crate::api;
+use crate::events::{EventSink, NoopSink};
+use crate::execution::{ConflictPolicy, ExecutionConfig};
+use crate::jobs::OrganizePlan;
+use crate::security::PathValidator;
+use crate::vector::{VectorConfig, VectorIndex};
+
+use super::watch_config::{WatchAction, WatchedFolder};
+use super::watch_journal::{ChangeEvent, ChangeKind, WatchJournal};
+use super::watcher::{FileChangeEvent, VectorHandle, WatchContext};
+
+/// Result of a watch action for one file
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchActionEvent {
+    pub watched_folder: String,
+    pub action: String,
+    pub path: String,
+    /// Where the file ended up after a rename or move
+    pub new_path: Option<String>,
+    /// WAL job of a rule run, for rollback
+    pub job_id: Option<String>,
+    pub error: Option<String>,
+}
+
+impl WatchActionEvent {
+    fn new(folder: &WatchedFolder, path: &str) -> Self {
+        Self {
+            watched_folder: folder.path.clone(),
+            action: folder.action.name().to_string(),
+            path: path.to_string(),
+            new_path: None,
+            job_id: None,
+            error: None,
+        }
+    }
+
+    fn emit(self, events: &dyn EventSink) {
+        if let Some(error) = &self.error {
+            eprintln!("[Watcher] {} failed for {}: {}", self.action, self.path, error);
+        }
+        events.emit("sentinel://watch-action", &self);
+    }
+}
+
+/// Run the folder's action for a batch of new files
+pub(crate) fn run(ctx: &WatchContext, folder: &WatchedFolder, files: Vec<FileChangeEvent>) {
+    if files.is_empty() {
+        return;
+    }
+
+    let events = Arc::clone(&ctx.events);
+    let folder = folder.clone();
+    match folder.action.clone() {
+        WatchAction::Notify => {
+            for file in &files {
+                events.emit("sentinel://file-created", file);
+            }
+        }
+        WatchAction::AutoRename => ctx.spawn(async move {
+            for file in files {
+                let mut report = WatchActionEvent::new(&folder, &file.path);
+                match auto_rename(&file).await {
+                    Ok(new_path) => report.new_path = Some(new_path.to_string_lossy().to_string()),
+                    Err(e) => report.error = Some(e),
+                }
+                report.emit(&*events);
+            }
+        }),
+        WatchAction::Index => {
+            let vectors = Arc::clone(&ctx.vectors);
+            ctx.spawn(async move {
+                let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+                let outcome = tokio::task::spawn_blocking(move || index_files(&vectors, &files))
+                    .await
+                    .map_err(|e| format!("Task failed: {}", e))
+                    .and_then(|result| result);
+                for path in paths {
+                    let mut report = WatchActionEvent::new(&folder, &path);
+                    report.error = outcome.as_ref().err().cloned();
+                    report.emit(&*events);
+                }
+            })
+        }
+        WatchAction::RunRules { rules } => ctx.spawn(async move {
+            let root = PathBuf::from(&folder.path);
+            match run_rules(&root, rules, &files).await {
+                Ok(Some((plan, error))) => {
+                    for op in &plan.operations {
+                        let (Some(path), new_path) = (operation_source(op), operation_target(op)) else {
+                            continue;
+                        };
+                        let mut report = WatchActionEvent::new(&folder, path);
+                        report.new_path = new_path;
+                        report.job_id = Some(plan.plan_id.clone());
+                        report.error = error.clone();
+                        report.emit(&*events);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    for file in &files {
+                        let mut report = WatchActionEvent::new(&folder, &file.path);
+                        report.error = Some(e.clone());
+                        report.emit(&*events);
+                    }
+                }
+            }
+        }),
+    }
+}
+
+/// Rename a file in place to the AI suggestion, keeping its extension
+async fn auto_rename(file: &FileChangeEvent) -> Result<PathBuf, String> {
+    let suggested = AnthropicClient::new()
+        .suggest_rename(
+            &file.file_name,
+            file.extension.as_deref(),
+            file.size,
+            file.content_preview.as_deref(),
+        )
+        .await?;
+
+    let path = Path::new(&file.path);
+    let target = rename_target(path, &suggested)?;
+    if target == path {
+        return Ok(target);
+    }
+    if target.exists() {
+        return Err(format!("File already exists: {}", target.display()));
+    }
+    if PathValidator::is_symlink(path) {
+        return Err("Cannot rename symbolic links".to_string());
+    }
+
+    std::fs::rename(path, &target).map_err(|e| format!("Failed to rename: {}", e))?;
+    tracing::info!(from = %path.display(), to = %target.display(), "Watcher auto-renamed file");
+    Ok(target)
+}
+
+/// Sibling path for a suggested name, re-adding the original extension when the
+/// suggestion dropped or changed it
+fn rename_target(path: &Path, suggested: &str) -> Result<PathBuf, String> {
+    let suggested = suggested.trim().trim_matches(|c| c == '"' || c == '`');
+    PathValidator::validate_filename(suggested)?;
+
+    let name = match path.extension().map(|e| e.to_string_lossy()) {
+        Some(ext) if !Path::new(suggested)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(ext.as_ref())) =>
+        {
+            format!("{}.{}", suggested, ext)
+        }
+        _ => suggested.to_string(),
+    };
+    PathValidator::validate_filename(&name)?;
+
+    let parent = path.parent().ok_or("Could not get parent directory")?;
+    Ok(parent.join(name))
+}
+
+/// Add files to the shared vector index, creating it on first use
+fn index_files(vectors: &VectorHandle, files: &[FileChangeEvent]) -> Result<(), String> {
+    let mut guard = vectors.write().map_err(|e| e.to_string())?;
+    if guard.is_none() {
+        eprintln!("[Watcher] Creating vector index for watched folder");
+        *guard = Some(VectorIndex::new(VectorConfig::default())?);
+    }
+    let Some(index) = guard.as_mut() else {
+        return Err("Vector index unavailable".to_string());
+    };
+
+    for file in files {
+        index.index_node(
+            &PathBuf::from(&file.path),
+            &file.file_name,
+            file.content_preview.as_deref(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Plan the watched folder with `rules` and execute the operations that touch
+/// the new files. Returns the executed plan and the failure summary, if any.
+async fn run_rules(
+    root: &Path,
+    rules: Vec<OrganizationRule>,
+    files: &[FileChangeEvent],
+) -> Result<Option<(OrganizePlan, Option<String>)>, String> {
+    let folder = root.to_path_buf();
+    let planned = tokio::task::spawn_blocking(move || {
+        api::plan_with_rules(&folder, &rules, "Watched folder rules")
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+
+    let plan = restrict_plan(planned.plan, files);
+    if plan.operations.is_empty() {
+        return Ok(None);
+    }
+
+    let config = ExecutionConfig {
+        on_destination_exists: ConflictPolicy::Skip,
+    };
+    let result = api::execute_plan(&plan, config, Arc::new(NoopSink)).await?;
+    record_moves(root, &plan);
+    let error = (!result.success).then(|| result.errors.join("; "));
+    Ok(Some((plan, error)))
+}
+
+/// Journal the files a rule run moved, so a move into an unwatched subfolder
+/// is not recorded as a removal
+fn record_moves(root: &Path, plan: &OrganizePlan) {
+    let watched_folder = root.to_string_lossy();
+    let moves: Vec<ChangeEvent> = plan
+        .operations
+        .iter()
+        .filter_map(|op| Some((PathBuf::from(operation_source(op)?), PathBuf::from(operation_target(op)?))))
+        .filter(|(from, to)| !from.exists() && to.is_file())
+        .map(|(from, to)| {
+            let size = std::fs::metadata(&to).ok().map(|m| m.len());
+            ChangeEvent::new(ChangeKind::Moved, &to, &watched_folder)
+                .with_from(&from)
+                .with_size(size)
+        })
+        .collect();
+
+    if let Err(e) = WatchJournal::open_default().and_then(|journal| journal.record(&moves)) {
+        eprintln!("[Watcher] {}", e);
+    }
+}
+
+/// Keep only the operations on `files`, plus the folders their moves need
+fn restrict_plan(mut plan: OrganizePlan, files: &[FileChangeEvent]) -> OrganizePlan {
+    let new_files: HashSet<PathBuf> = files.iter().map(|f| canonical(&f.path)).collect();
+
+    plan.operations
+        .retain(|op| op.op_type == "create_folder" || operation_source(op).is_some_and(|p| new_files.contains(&canonical(p))));
+
+    let targets: Vec<PathBuf> = plan
+        .operations
+        .iter()
+        .filter(|op| op.op_type != "create_folder")
+        .filter_map(operation_target)
+        .map(PathBuf::from)
+        .collect();
+    plan.operations.retain(|op| {
+        op.op_type != "create_folder"
+            || op
+                .path
+                .as_ref()
+                .is_some_and(|dir| targets.iter().any(|t| t.starts_with(dir)))
+    });
+    plan
+}
+
+/// The file an operation acts on (moves use `source`, renames use `path`)
+fn operation_source(op: &crate::jobs::OrganizeOperation) -> Option<&str> {
+    op.source.as_deref().or(op.path.as_deref()).filter(|_| op.op_type != "create_folder")
+}
+
+/// Where an operation leaves its file
+fn operation_target(op: &crate::jobs::OrganizeOperation) -> Option<String> {
+    match (&op.destination, &op.path, &op.new_name) {
+        (Some(destination), _, _) => Some(destination.clone()),
+        (None, Some(path), Some(new_name)) => Path::new(path)
+            .parent()
+            .map(|dir| dir.join(new_name).to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+fn canonical(path: &str) -> PathBuf {
+    Path::new(path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::OrganizeOperation;
+
+    fn file(path: &str) -> FileChangeEvent {
+        FileChangeEvent {
+            id: "1".to_string(),
+            event_type: "created".to_string(),
+            path: path.to_string(),
+            file_name: Path::new(path).file_name().unwrap().to_string_lossy().to_string(),
+            extension: None,
+            size: 1,
+            content_preview: None,
+            watched_folder: "/in".to_string(),
+        }
+    }
+
+    fn op(op_type: &str, source: Option<&str>, destination: Option<&str>, path: Option<&str>) -> OrganizeOperation {
+        OrganizeOperation {
+            op_id: format!("op-{}", op_type),
+            op_type: op_type.to_string(),
+            source: source.map(str::to_string),
+            destination: destination.map(str::to_string),
+            path: path.map(str::to_string),
+            new_name: None,
+            attributes: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rename_target_keeps_extension() {
+        let path = Path::new("/in/IMG_0042.pdf");
+        assert_eq!(rename_target(path, "march-invoice.pdf").unwrap(), Path::new("/in/march-invoice.pdf"));
+        assert_eq!(rename_target(path, " march-invoice \n").unwrap(), Path::new("/in/march-invoice.pdf"));
+        assert_eq!(rename_target(path, "march-invoice.PDF").unwrap(), Path::new("/in/march-invoice.PDF"));
+        assert!(rename_target(path, "../escape.pdf").is_err());
+        assert!(rename_target(path, "").is_err());
+    }
+
+    #[test]
+    fn test_restrict_plan_to_new_files() {
+        let plan = OrganizePlan {
+            plan_id: "plan-1".to_string(),
+            description: "rules".to_string(),
+            operations: vec![
+                op("create_folder", None, None, Some("/in/Docs")),
+                op("create_folder", None, None, Some("/in/Images")),
+                op("move", Some("/in/new.pdf"), Some("/in/Docs/new.pdf"), None),
+                op("move", Some("/in/old.png"), Some("/in/Images/old.png"), None),
+            ],
+            target_folder: "/in".to_string(),
+            source_roots: Vec::new(),
+            rules: Vec::new(),
+            baseline: None,
+            explanations: Default::default(),
+        };
+
+        let restricted = restrict_plan(plan, &[file("/in/new.pdf")]);
+        let ids: Vec<&str> = restricted.operations.iter().map(|o| o.op_id.as_str()).collect();
+        assert_eq!(restricted.operations.len(), 2);
+        assert_eq!(ids, ["op-create_folder", "op-move"]);
+        assert_eq!(restricted.operations[0].path.as_deref(), Some("/in/Docs"));
+    }
+}
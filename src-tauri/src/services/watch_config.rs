@@ -0,0 +1,294 @@
+//! Persistent watched-folder configuration
+//!
+//! Each watched folder has its own recursion, debounce, include/exclude globs
+//! and an action to run on new files. The list is stored at
+//! `<config dir>/sentinel/watched_folders.json` and re-armed on startup.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ai::v2::vfs::OrganizationRule;
+
+/// Default wait for file writes to settle before events fire
+pub const DEFAULT_DEBOUNCE_MS: u64 = 500;
+
+/// Accepted debounce range (shorter fires mid-download, longer feels stuck)
+const MIN_DEBOUNCE_MS: u64 = 100;
+const MAX_DEBOUNCE_MS: u64 = 60_000;
+
+/// What to do with new files in a watched folder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchAction {
+    /// Emit `sentinel://file-created` for the frontend (the original behavior)
+    #[default]
+    Notify,
+    /// Rename the file in place using the AI rename suggestion
+    AutoRename,
+    /// Add the file to the in-memory vector index
+    Index,
+    /// Plan the folder with these rules and execute moves of the new files
+    RunRules { rules: Vec<OrganizationRule> },
+}
+
+impl WatchAction {
+    /// Stable name used in events and logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchAction::Notify => "notify",
+            WatchAction::AutoRename => "auto_rename",
+            WatchAction::Index => "index",
+            WatchAction::RunRules { .. } => "run_rules",
+        }
+    }
+}
+
+/// Configuration for one watched folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolder {
+    pub path: String,
+    /// Watch subfolders too
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Only files matching one of these globs (all files when empty)
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Files matching any of these globs are ignored
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub action: WatchAction,
+    /// Disabled folders stay configured but are not armed
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_debounce_ms() -> u64 {
+    DEFAULT_DEBOUNCE_MS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WatchedFolder {
+    /// A non-recursive, notify-only folder (what `add_watched_folder` creates)
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            recursive: false,
+            debounce_ms: DEFAULT_DEBOUNCE_MS,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            action: WatchAction::Notify,
+            enabled: true,
+        }
+    }
+
+    pub fn with_action(mut self, action: WatchAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Debounce window, clamped to the accepted range
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms.clamp(MIN_DEBOUNCE_MS, MAX_DEBOUNCE_MS))
+    }
+
+    /// Check globs and action settings before the folder is saved or armed
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.trim().is_empty() {
+            return Err("Watched folder path cannot be empty".to_string());
+        }
+        if let WatchAction::RunRules { rules } = &self.action {
+            if rules.is_empty() {
+                return Err("The run_rules action needs at least one rule".to_string());
+            }
+        }
+        self.filter().map(|_| ())
+    }
+
+    /// Compile the include/exclude globs
+    pub fn filter(&self) -> Result<WatchFilter, String> {
+        Ok(WatchFilter {
+            root: PathBuf::from(&self.path),
+            include: if self.include.is_empty() {
+                None
+            } else {
+                Some(build_globs(&self.include)?)
+            },
+            exclude: build_globs(&self.exclude)?,
+        })
+    }
+}
+
+fn build_globs(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to compile globs: {}", e))
+}
+
+/// Compiled include/exclude globs for one watched folder.
+///
+/// Patterns are matched against the path relative to the folder and against
+/// the bare file name, so `*.pdf` works at any depth and `invoices/**` works too.
+#[derive(Debug, Clone)]
+pub struct WatchFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl WatchFilter {
+    /// Whether events for `path` should reach the folder's action
+    pub fn allows(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let name = path.file_name().map(Path::new).unwrap_or(relative);
+        let matches = |set: &GlobSet| set.is_match(relative) || set.is_match(name);
+
+        if matches(&self.exclude) {
+            return false;
+        }
+        self.include.as_ref().is_none_or(matches)
+    }
+}
+
+/// The saved list of watched folders
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchConfig {
+    #[serde(default)]
+    pub folders: Vec<WatchedFolder>,
+}
+
+impl WatchConfig {
+    /// Location of the config file
+    pub fn file_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("sentinel").join("watched_folders.json"))
+    }
+
+    /// Load the saved folders; a missing or unreadable file means none
+    pub fn load() -> Self {
+        let Some(path) = Self::file_path() else {
+            return Self::default();
+        };
+        Self::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("[Watcher] {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::file_path().ok_or("Could not determine config directory")?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize watched folders: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write watched folders: {}", e))
+    }
+
+    pub fn get(&self, path: &str) -> Option<&WatchedFolder> {
+        self.folders.iter().find(|f| f.path == path)
+    }
+
+    /// Add a folder or replace the existing entry for its path
+    pub fn upsert(&mut self, folder: WatchedFolder) {
+        match self.folders.iter_mut().find(|f| f.path == folder.path) {
+            Some(existing) => *existing = folder,
+            None => self.folders.push(folder),
+        }
+    }
+
+    /// Remove a folder; returns whether it was configured
+    pub fn remove(&mut self, path: &str) -> bool {
+        let before = self.folders.len();
+        self.folders.retain(|f| f.path != path);
+        self.folders.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_and_round_trip() {
+        let folder: WatchedFolder = serde_json::from_str(r#"{"path": "/home/me/Downloads"}"#).unwrap();
+        assert!(!folder.recursive);
+        assert!(folder.enabled);
+        assert_eq!(folder.debounce_ms, DEFAULT_DEBOUNCE_MS);
+        assert!(matches!(folder.action, WatchAction::Notify));
+
+        let json = r#"{"path": "/in", "recursive": true, "debounceMs": 5,
+            "action": {"type": "run_rules", "rules": [{"name": "PDFs", "if": "file.ext == 'pdf'", "thenMoveTo": "Docs"}]}}"#;
+        let rules_folder: WatchedFolder = serde_json::from_str(json).unwrap();
+        assert_eq!(rules_folder.debounce(), Duration::from_millis(MIN_DEBOUNCE_MS));
+        assert_eq!(rules_folder.action.name(), "run_rules");
+
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("sentinel/watched_folders.json");
+        let mut config = WatchConfig::default();
+        config.upsert(folder);
+        config.upsert(rules_folder);
+        config.upsert(WatchedFolder::new("/in").with_action(WatchAction::Index));
+        config.save_to(&file).unwrap();
+
+        let loaded = WatchConfig::load_from(&file).unwrap();
+        assert_eq!(loaded.folders.len(), 2);
+        assert!(matches!(loaded.get("/in").unwrap().action, WatchAction::Index));
+        assert!(WatchConfig::load_from(&dir.path().join("missing.json")).unwrap().folders.is_empty());
+    }
+
+    #[test]
+    fn test_filter_globs() {
+        let mut folder = WatchedFolder::new("/in").with_recursive(true);
+        folder.include = vec!["*.pdf".to_string(), "scans/**".to_string()];
+        folder.exclude = vec!["*draft*".to_string()];
+        let filter = folder.filter().unwrap();
+
+        assert!(filter.allows(Path::new("/in/invoice.pdf")));
+        assert!(filter.allows(Path::new("/in/2024/march/invoice.pdf")));
+        assert!(filter.allows(Path::new("/in/scans/page1.png")));
+        assert!(!filter.allows(Path::new("/in/photo.png")));
+        assert!(!filter.allows(Path::new("/in/draft-invoice.pdf")));
+
+        folder.include.clear();
+        assert!(folder.filter().unwrap().allows(Path::new("/in/photo.png")));
+
+        folder.exclude = vec!["[".to_string()];
+        assert!(folder.validate().is_err());
+        let empty_rules = WatchedFolder::new("/in").with_action(WatchAction::RunRules { rules: vec![] });
+        assert!(empty_rules.validate().is_err());
+    }
+}
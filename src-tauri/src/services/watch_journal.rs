@@ -0,0 +1,488 @@
+//! Rolling journal of filesystem changes seen in watched folders
+//!
+//! Watcher events are otherwise only forwarded to the frontend. Keeping them in
+//! a small SQLite log answers "where did the file I downloaded yesterday go?"
+//! after the fact. A removal followed by a creation of the same file name
+//! shortly after (or the reverse) is stored as one move, so files moved between
+//! watched folders or by a watch action show up as a single event.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::{params, params_from_iter, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Events older than this are dropped
+const RETENTION_DAYS: i64 = 90;
+
+/// Upper bound on stored events (oldest dropped first)
+const MAX_EVENTS: i64 = 100_000;
+
+/// A removal and creation of the same name this close together are one move
+const COALESCE_WINDOW_MS: i64 = 10_000;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS watch_events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind TEXT NOT NULL,
+        path TEXT NOT NULL,
+        from_path TEXT,
+        name TEXT NOT NULL,
+        size INTEGER,
+        watched_folder TEXT NOT NULL,
+        occurred_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_watch_events_time ON watch_events(occurred_at);
+    CREATE INDEX IF NOT EXISTS idx_watch_events_name ON watch_events(name);
+"#;
+
+/// Kind of change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Removed,
+    Moved,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Moved => "moved",
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_lowercase().as_str() {
+            "created" | "added" | "new" => Ok(ChangeKind::Created),
+            "removed" | "deleted" => Ok(ChangeKind::Removed),
+            "moved" | "renamed" => Ok(ChangeKind::Moved),
+            other => Err(format!("Unknown change kind: {} (use created, removed or moved)", other)),
+        }
+    }
+}
+
+/// A change to record
+#[derive(Debug, Clone)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+    /// Previous location, for moves
+    pub from_path: Option<PathBuf>,
+    pub size: Option<u64>,
+    pub watched_folder: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl ChangeEvent {
+    pub fn new(kind: ChangeKind, path: &Path, watched_folder: &str) -> Self {
+        Self {
+            kind,
+            path: path.to_path_buf(),
+            from_path: None,
+            size: None,
+            watched_folder: watched_folder.to_string(),
+            occurred_at: Utc::now(),
+        }
+    }
+
+    pub fn with_from(mut self, from: &Path) -> Self {
+        self.from_path = Some(from.to_path_buf());
+        self
+    }
+
+    pub fn with_size(mut self, size: Option<u64>) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn at(mut self, time: DateTime<Utc>) -> Self {
+        self.occurred_at = time;
+        self
+    }
+}
+
+/// A recorded change
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub id: i64,
+    pub kind: ChangeKind,
+    pub path: String,
+    pub from_path: Option<String>,
+    pub size: Option<u64>,
+    pub watched_folder: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Filters for [`WatchJournal::query`]; empty filters match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JournalQuery {
+    /// Changes in this watched folder, or moves into or out of it
+    pub folder: Option<String>,
+    pub kind: Option<ChangeKind>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Substring of the current or previous file name (case-insensitive)
+    pub name: Option<String>,
+    /// Maximum entries, newest first (default 100)
+    pub limit: Option<usize>,
+}
+
+/// SQLite-backed change journal
+pub struct WatchJournal {
+    db_path: PathBuf,
+}
+
+impl WatchJournal {
+    /// Default journal directory (next to the snapshot store)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("com.sentinel.filemanager"))
+    }
+
+    /// Open the journal in the default location
+    pub fn open_default() -> Result<Self, String> {
+        let dir = Self::default_dir().ok_or("Could not determine cache directory")?;
+        Self::open(&dir)
+    }
+
+    /// Open or create the journal database in `dir`
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create journal directory: {}", e))?;
+
+        let journal = Self {
+            db_path: dir.join("watch_journal.db"),
+        };
+        journal
+            .conn()?
+            .execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize watch journal: {}", e))?;
+        Ok(journal)
+    }
+
+    fn conn(&self) -> Result<rusqlite::Connection, String> {
+        rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open watch journal: {}", e))
+    }
+
+    /// Record a batch of changes, coalescing remove/create pairs into moves,
+    /// then drop events past the retention limits
+    pub fn record(&self, events: &[ChangeEvent]) -> Result<(), String> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let db_err = |e: rusqlite::Error| format!("Failed to record watch events: {}", e);
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(db_err)?;
+        for event in events {
+            record_one(&tx, event).map_err(db_err)?;
+        }
+
+        let cutoff = (Utc::now() - Duration::days(RETENTION_DAYS)).timestamp_millis();
+        tx.execute("DELETE FROM watch_events WHERE occurred_at < ?1", params![cutoff])
+            .map_err(db_err)?;
+        tx.execute(
+            "DELETE FROM watch_events WHERE id <= (SELECT MAX(id) FROM watch_events) - ?1",
+            params![MAX_EVENTS],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)
+    }
+
+    /// Recorded changes matching `query`, newest first
+    pub fn query(&self, query: &JournalQuery) -> Result<Vec<JournalEntry>, String> {
+        let mut sql = String::from(
+            "SELECT id, kind, path, from_path, size, watched_folder, occurred_at
+             FROM watch_events WHERE 1 = 1",
+        );
+        let mut args: Vec<rusqlite::types::Value> = Vec::new();
+
+        if let Some(folder) = &query.folder {
+            let folder = folder.trim_end_matches(['/', '\\']);
+            let below = format!("{}%", escape_like(&format!("{}{}", folder, std::path::MAIN_SEPARATOR)));
+            sql.push_str(
+                " AND (watched_folder = ? OR path LIKE ? ESCAPE '\\' OR from_path LIKE ? ESCAPE '\\')",
+            );
+            args.push(folder.to_string().into());
+            args.push(below.clone().into());
+            args.push(below.into());
+        }
+        if let Some(kind) = query.kind {
+            sql.push_str(" AND kind = ?");
+            args.push(kind.as_str().to_string().into());
+        }
+        if let Some(since) = query.since {
+            sql.push_str(" AND occurred_at >= ?");
+            args.push(since.timestamp_millis().into());
+        }
+        if let Some(until) = query.until {
+            sql.push_str(" AND occurred_at <= ?");
+            args.push(until.timestamp_millis().into());
+        }
+        if let Some(name) = query.name.as_deref().filter(|n| !n.trim().is_empty()) {
+            sql.push_str(" AND (name LIKE ? ESCAPE '\\' OR from_path LIKE ? ESCAPE '\\')");
+            let pattern = format!("%{}%", escape_like(name.trim()));
+            args.push(pattern.clone().into());
+            args.push(pattern.into());
+        }
+        sql.push_str(" ORDER BY occurred_at DESC, id DESC LIMIT ?");
+        args.push((query.limit.unwrap_or(100) as i64).into());
+
+        let db_err = |e: rusqlite::Error| format!("Failed to query watch journal: {}", e);
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql).map_err(db_err)?;
+        let rows = stmt
+            .query_map(params_from_iter(args), |row| {
+                let kind: String = row.get(1)?;
+                let size: Option<i64> = row.get(4)?;
+                Ok(JournalEntry {
+                    id: row.get(0)?,
+                    kind: ChangeKind::parse(&kind).unwrap_or(ChangeKind::Created),
+                    path: row.get(2)?,
+                    from_path: row.get(3)?,
+                    size: size.map(|s| s as u64),
+                    watched_folder: row.get(5)?,
+                    occurred_at: from_millis(row.get(6)?),
+                })
+            })
+            .map_err(db_err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+    }
+}
+
+fn record_one(tx: &rusqlite::Transaction, event: &ChangeEvent) -> rusqlite::Result<()> {
+    let path = event.path.to_string_lossy().to_string();
+    let from_path = event.from_path.as_ref().map(|p| p.to_string_lossy().to_string());
+    let name = event
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+    let time = event.occurred_at.timestamp_millis();
+    let window_start = time - COALESCE_WINDOW_MS;
+    let size = event.size.map(|s| s as i64);
+
+    match event.kind {
+        ChangeKind::Moved => {
+            // The source folder may already have reported the file as removed
+            if let Some(from) = &from_path {
+                tx.execute(
+                    "DELETE FROM watch_events WHERE kind = 'removed' AND path = ?1 AND occurred_at >= ?2",
+                    params![from, window_start],
+                )?;
+            }
+            // ...and the destination folder as created
+            tx.execute(
+                "DELETE FROM watch_events WHERE kind = 'created' AND path = ?1 AND occurred_at >= ?2",
+                params![path, window_start],
+            )?;
+        }
+        ChangeKind::Created => {
+            let already_moved: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM watch_events
+                     WHERE kind = 'moved' AND path = ?1 AND occurred_at >= ?2",
+                    params![path, window_start],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if already_moved.is_some() {
+                return Ok(());
+            }
+
+            let removed: Option<(i64, String)> = tx
+                .query_row(
+                    "SELECT id, path FROM watch_events
+                     WHERE kind = 'removed' AND name = ?1 AND path != ?2 AND occurred_at >= ?3
+                     ORDER BY occurred_at DESC, id DESC LIMIT 1",
+                    params![name, path, window_start],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            if let Some((id, old_path)) = removed {
+                tx.execute(
+                    "UPDATE watch_events
+                     SET kind = 'moved', from_path = ?1, path = ?2, size = ?3, watched_folder = ?4, occurred_at = ?5
+                     WHERE id = ?6",
+                    params![old_path, path, size, event.watched_folder, time, id],
+                )?;
+                return Ok(());
+            }
+        }
+        ChangeKind::Removed => {
+            let already_moved: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM watch_events
+                     WHERE kind = 'moved' AND from_path = ?1 AND occurred_at >= ?2",
+                    params![path, window_start],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if already_moved.is_some() {
+                return Ok(());
+            }
+
+            // The destination folder may have reported the file first
+            let created: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM watch_events
+                     WHERE kind = 'created' AND name = ?1 AND path != ?2 AND occurred_at >= ?3
+                     ORDER BY occurred_at DESC, id DESC LIMIT 1",
+                    params![name, path, window_start],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(id) = created {
+                tx.execute(
+                    "UPDATE watch_events SET kind = 'moved', from_path = ?1 WHERE id = ?2",
+                    params![path, id],
+                )?;
+                return Ok(());
+            }
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO watch_events (kind, path, from_path, name, size, watched_folder, occurred_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![event.kind.as_str(), path, from_path, name, size, event.watched_folder, time],
+    )?;
+    Ok(())
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_query() {
+        let dir = TempDir::new().unwrap();
+        let journal = WatchJournal::open(dir.path()).unwrap();
+        let now = Utc::now();
+        let downloads = "/home/me/Downloads";
+
+        journal
+            .record(&[
+                ChangeEvent::new(ChangeKind::Created, Path::new("/home/me/Downloads/old.zip"), downloads)
+                    .with_size(Some(10))
+                    .at(now - Duration::days(10)),
+                ChangeEvent::new(ChangeKind::Created, Path::new("/home/me/Downloads/report_q1.pdf"), downloads)
+                    .with_size(Some(2048))
+                    .at(now - Duration::hours(20)),
+                ChangeEvent::new(ChangeKind::Removed, Path::new("/home/me/Downloads/notes.txt"), downloads)
+                    .at(now - Duration::hours(1)),
+            ])
+            .unwrap();
+
+        let this_week = journal
+            .query(&JournalQuery {
+                folder: Some(format!("{}/", downloads)),
+                kind: Some(ChangeKind::Created),
+                since: Some(now - Duration::days(7)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(this_week.len(), 1);
+        assert_eq!(this_week[0].path, "/home/me/Downloads/report_q1.pdf");
+        assert_eq!(this_week[0].size, Some(2048));
+
+        // `_` is literal, not a wildcard
+        let by_name = journal
+            .query(&JournalQuery {
+                name: Some("REPORT_".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_name.len(), 1);
+        assert!(journal
+            .query(&JournalQuery {
+                name: Some("notes_txt".to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+            .is_empty());
+
+        let all = journal.query(&JournalQuery::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].kind, ChangeKind::Removed);
+    }
+
+    #[test]
+    fn test_coalesces_moves() {
+        let dir = TempDir::new().unwrap();
+        let journal = WatchJournal::open(dir.path()).unwrap();
+        let downloads = "/home/me/Downloads";
+        let documents = "/home/me/Documents";
+
+        // Removed from one watched folder, created in another
+        journal
+            .record(&[ChangeEvent::new(ChangeKind::Removed, Path::new("/home/me/Downloads/cv.pdf"), downloads)])
+            .unwrap();
+        journal
+            .record(&[ChangeEvent::new(ChangeKind::Created, Path::new("/home/me/Documents/cv.pdf"), documents)
+                .with_size(Some(5))])
+            .unwrap();
+
+        // Created reported before the removal
+        journal
+            .record(&[
+                ChangeEvent::new(ChangeKind::Created, Path::new("/home/me/Documents/tax.pdf"), documents),
+                ChangeEvent::new(ChangeKind::Removed, Path::new("/home/me/Downloads/tax.pdf"), downloads),
+            ])
+            .unwrap();
+
+        // A recorded move swallows the source folder's removal
+        journal
+            .record(&[
+                ChangeEvent::new(ChangeKind::Moved, Path::new("/home/me/Downloads/Docs/a.pdf"), downloads)
+                    .with_from(Path::new("/home/me/Downloads/a.pdf")),
+                ChangeEvent::new(ChangeKind::Removed, Path::new("/home/me/Downloads/a.pdf"), downloads),
+            ])
+            .unwrap();
+
+        let entries = journal.query(&JournalQuery::default()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.kind == ChangeKind::Moved));
+
+        let cv = journal
+            .query(&JournalQuery {
+                name: Some("cv".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(cv[0].from_path.as_deref(), Some("/home/me/Downloads/cv.pdf"));
+        assert_eq!(cv[0].path, "/home/me/Documents/cv.pdf");
+        assert_eq!(cv[0].size, Some(5));
+
+        // Moves show up for both folders, whichever watcher recorded them
+        let into_docs = journal
+            .query(&JournalQuery {
+                folder: Some(documents.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(into_docs.len(), 2);
+        let from_downloads = journal
+            .query(&JournalQuery {
+                folder: Some(downloads.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(from_downloads.len(), 3);
+    }
+}
@@ -1,10 +1,26 @@
+//! Folder watchers for new files
+//!
+//! Each watched folder is armed from its saved [`WatchedFolder`] config
+//! (recursion, debounce, globs) and hands new files to its action in
+//! [`super::watch_actions`]. Every change is also recorded in the
+//! [`super::watch_journal`]. Folders that disappear are reported and re-armed
+//! when they come back.
+
+use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
 use notify::{EventKind, RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{new_debouncer, DebouncedEvent, Debouncer, RecommendedCache};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+
+use super::watch_actions;
+use super::watch_config::{WatchConfig, WatchFilter, WatchedFolder};
+use super::watch_journal::{ChangeEvent, ChangeKind, WatchJournal};
+use crate::events::SharedEventSink;
+use crate::scan::ignore::IgnoreRules;
+use crate::vector::VectorIndex;
 
 /// Event payload sent to frontend
 #[derive(Clone, serde::Serialize)]
@@ -20,27 +36,92 @@ pub struct FileChangeEvent {
     pub watched_folder: String,
 }
 
+/// Folder availability change sent to frontend
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolderEvent {
+    pub path: String,
+    pub reason: Option<String>,
+}
+
+/// A configured folder and whether it is currently armed
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolderStatus {
+    #[serde(flatten)]
+    pub config: WatchedFolder,
+    pub active: bool,
+    /// Why an enabled folder is not armed (missing, unmounted, invalid)
+    pub error: Option<String>,
+}
+
+/// Shared vector index that the `Index` action writes to
+pub type VectorHandle = Arc<RwLock<Option<VectorIndex>>>;
+
+/// Everything watcher callbacks need to run folder actions
+#[derive(Clone)]
+pub struct WatchContext {
+    pub events: SharedEventSink,
+    pub vectors: VectorHandle,
+    runtime: Option<tokio::runtime::Handle>,
+}
+
+impl WatchContext {
+    /// Captures the current async runtime, which actions other than notify
+    /// and the re-arm loop run on
+    pub fn new(events: SharedEventSink, vectors: VectorHandle) -> Self {
+        Self {
+            events,
+            vectors,
+            runtime: tokio::runtime::Handle::try_current().ok(),
+        }
+    }
+
+    pub(crate) fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match &self.runtime {
+            Some(runtime) => {
+                runtime.spawn(task);
+            }
+            None => eprintln!("[Watcher] No async runtime available, skipping watch action"),
+        }
+    }
+}
+
+/// How often missing folders are re-checked
+const REARM_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Individual folder watcher
 struct FolderWatcher {
+    /// Dropping it stops the watch
+    #[allow(dead_code)]
     debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
     path: PathBuf,
+    config: WatchedFolder,
 }
 
 /// Watcher state - supports multiple folders
 pub struct WatcherState {
     /// Map of folder path -> watcher
     pub watchers: HashMap<String, FolderWatcher>,
+    /// Enabled folders that could not be armed, with the reason
+    pub unavailable: HashMap<String, String>,
     /// Legacy single watcher path (for backwards compatibility)
     pub watching_path: Option<PathBuf>,
     pub enabled: bool,
+    rearm_started: bool,
 }
 
 impl Default for WatcherState {
     fn default() -> Self {
         Self {
             watchers: HashMap::new(),
+            unavailable: HashMap::new(),
             watching_path: None,
             enabled: false,
+            rearm_started: false,
         }
     }
 }
@@ -53,34 +134,67 @@ pub fn create_watcher_handle() -> WatcherHandle {
     Arc::new(Mutex::new(WatcherState::default()))
 }
 
-/// Start watching a directory (legacy single-folder mode)
-pub fn start_watcher(
-    app: AppHandle,
-    handle: WatcherHandle,
-    path: PathBuf,
-) -> Result<(), String> {
-    let mut state = handle.lock().unwrap_or_else(|poisoned| {
+fn lock_state(handle: &WatcherHandle) -> MutexGuard<'_, WatcherState> {
+    handle.lock().unwrap_or_else(|poisoned| {
         eprintln!("Watcher state mutex was poisoned, recovering...");
         poisoned.into_inner()
-    });
+    })
+}
 
-    // Clear all existing watchers
-    state.watchers.clear();
+/// Arm a watcher for one configured folder, replacing any existing one
+pub fn watch_folder(
+    ctx: &WatchContext,
+    handle: &WatcherHandle,
+    config: WatchedFolder,
+) -> Result<(), String> {
+    config.validate()?;
+    let filter = config.filter()?;
+    let path = PathBuf::from(&config.path);
+    if !path.is_dir() {
+        return Err(format!("Folder not available: {}", config.path));
+    }
 
-    let path_str = path.to_string_lossy().to_string();
-    let watched_folder = path_str.clone();
-    let app_clone = app.clone();
+    // Recursive watches skip dependency and build folders like scans do
+    let ignore_rules = config.recursive.then(|| IgnoreRules::for_root(&path));
+    let journal = WatchJournal::open_default()
+        .map_err(|e| eprintln!("[Watcher] Change journal unavailable: {}", e))
+        .ok();
+    let callback_ctx = ctx.clone();
+    let callback_handle = Arc::clone(handle);
+    let folder = config.clone();
+    let root = path.clone();
 
-    // Create debounced watcher (waits 500ms for file writes to complete)
     let mut debouncer = new_debouncer(
-        Duration::from_millis(500),
+        config.debounce(),
         None,
         move |result: Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
             match result {
                 Ok(events) => {
-                    for event in events {
-                        handle_file_event(&app_clone, &event, &watched_folder);
+                    if !root.is_dir() {
+                        mark_unavailable(
+                            &callback_ctx,
+                            &callback_handle,
+                            &folder.path,
+                            "Folder was deleted or unmounted",
+                        );
+                        return;
                     }
+                    if let Some(journal) = &journal {
+                        let changes: Vec<ChangeEvent> = events
+                            .iter()
+                            .flat_map(|event| journal_changes(event, &folder.path, ignore_rules.as_ref()))
+                            .collect();
+                        if let Err(e) = journal.record(&changes) {
+                            eprintln!("[Watcher] {}", e);
+                        }
+                    }
+                    let files: Vec<FileChangeEvent> = events
+                        .iter()
+                        .flat_map(|event| {
+                            handle_file_event(event, &folder.path, &filter, ignore_rules.as_ref())
+                        })
+                        .collect();
+                    watch_actions::run(&callback_ctx, &folder, files);
                 }
                 Err(errors) => {
                     for error in errors {
@@ -92,104 +206,297 @@ pub fn start_watcher(
     )
     .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
-    // Start watching the path
+    let mode = if config.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
     debouncer
-        .watch(&path, RecursiveMode::NonRecursive)
+        .watch(&path, mode)
         .map_err(|e| format!("Failed to watch path: {}", e))?;
 
-    state.watchers.insert(path_str, FolderWatcher {
+    let mut state = lock_state(handle);
+    state.unavailable.remove(&config.path);
+    state.watchers.insert(config.path.clone(), FolderWatcher {
         debouncer,
-        path: path.clone(),
+        path,
+        config,
     });
-    state.watching_path = Some(path);
     state.enabled = true;
 
     Ok(())
 }
 
-/// Add a folder to watch (multi-folder mode)
-pub fn add_watched_folder(
-    app: AppHandle,
-    handle: WatcherHandle,
-    path: PathBuf,
-) -> Result<(), String> {
-    let mut state = handle.lock().unwrap_or_else(|poisoned| {
-        eprintln!("Watcher state mutex was poisoned, recovering...");
-        poisoned.into_inner()
-    });
+/// Drop the watcher for a folder that disappeared and tell the frontend once
+fn mark_unavailable(ctx: &WatchContext, handle: &WatcherHandle, path: &str, reason: &str) {
+    let newly_unavailable = {
+        let mut state = lock_state(handle);
+        state.watchers.remove(path);
+        if state.watchers.is_empty() {
+            state.enabled = false;
+        }
+        state
+            .unavailable
+            .insert(path.to_string(), reason.to_string())
+            .is_none()
+    };
+
+    if newly_unavailable {
+        eprintln!("[Watcher] {}: {}", path, reason);
+        ctx.events.emit(
+            "sentinel://watch-folder-unavailable",
+            &WatchFolderEvent {
+                path: path.to_string(),
+                reason: Some(reason.to_string()),
+            },
+        );
+    }
+}
 
-    let path_str = path.to_string_lossy().to_string();
+/// Arm an enabled folder, or record why it cannot be armed
+fn arm_or_mark(ctx: &WatchContext, handle: &WatcherHandle, folder: WatchedFolder) -> bool {
+    let path = folder.path.clone();
+    if !Path::new(&path).is_dir() {
+        mark_unavailable(ctx, handle, &path, "Folder not found");
+        return false;
+    }
+    match watch_folder(ctx, handle, folder) {
+        Ok(()) => true,
+        Err(e) => {
+            mark_unavailable(ctx, handle, &path, &e);
+            false
+        }
+    }
+}
 
-    // Skip if already watching
-    if state.watchers.contains_key(&path_str) {
-        return Ok(());
+fn disarm(handle: &WatcherHandle, path: &str) {
+    let mut state = lock_state(handle);
+    state.watchers.remove(path);
+    state.unavailable.remove(path);
+
+    if state.watchers.is_empty() {
+        state.enabled = false;
+        state.watching_path = None;
     }
+}
 
-    let watched_folder = path_str.clone();
-    let app_clone = app.clone();
+/// Re-arm the saved watched folders (called once on startup).
+///
+/// Folders that are missing are reported as unavailable and picked up again
+/// by a background check once they reappear. Returns the number armed.
+pub fn restore_watched_folders(ctx: &WatchContext, handle: &WatcherHandle) -> usize {
+    let config = WatchConfig::load();
+    let mut armed = 0;
+    for folder in config.folders.into_iter().filter(|f| f.enabled) {
+        if arm_or_mark(ctx, handle, folder) {
+            armed += 1;
+        }
+    }
+    start_rearm_loop(ctx, handle);
 
-    // Create debounced watcher
-    let mut debouncer = new_debouncer(
-        Duration::from_millis(500),
-        None,
-        move |result: Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
-            match result {
-                Ok(events) => {
-                    for event in events {
-                        handle_file_event(&app_clone, &event, &watched_folder);
-                    }
-                }
-                Err(errors) => {
-                    for error in errors {
-                        eprintln!("Watcher error: {:?}", error);
-                    }
-                }
-            }
-        },
-    )
-    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    tracing::info!(armed, "Restored watched folders");
+    armed
+}
 
-    debouncer
-        .watch(&path, RecursiveMode::NonRecursive)
-        .map_err(|e| format!("Failed to watch path: {}", e))?;
+fn start_rearm_loop(ctx: &WatchContext, handle: &WatcherHandle) {
+    if ctx.runtime.is_none() {
+        return;
+    }
+    {
+        let mut state = lock_state(handle);
+        if state.rearm_started {
+            return;
+        }
+        state.rearm_started = true;
+    }
 
-    state.watchers.insert(path_str, FolderWatcher {
-        debouncer,
-        path,
+    let loop_ctx = ctx.clone();
+    let loop_handle = Arc::clone(handle);
+    ctx.spawn(async move {
+        let mut interval = tokio::time::interval(REARM_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            check_watched_folders(&loop_ctx, &loop_handle);
+        }
     });
-    state.enabled = true;
+}
 
-    Ok(())
+/// Drop watchers whose folder vanished and re-arm enabled folders that came
+/// back. Returns the number of folders re-armed.
+pub fn check_watched_folders(ctx: &WatchContext, handle: &WatcherHandle) -> usize {
+    let vanished: Vec<String> = lock_state(handle)
+        .watchers
+        .iter()
+        .filter(|(_, watcher)| !watcher.path.is_dir())
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in vanished {
+        mark_unavailable(ctx, handle, &path, "Folder was deleted or unmounted");
+    }
+
+    let mut rearmed = 0;
+    for folder in WatchConfig::load().folders.into_iter().filter(|f| f.enabled) {
+        if lock_state(handle).watchers.contains_key(&folder.path) || !Path::new(&folder.path).is_dir() {
+            continue;
+        }
+        let path = folder.path.clone();
+        let was_unavailable = lock_state(handle).unavailable.contains_key(&path);
+        if watch_folder(ctx, handle, folder).is_ok() {
+            rearmed += 1;
+            if was_unavailable {
+                eprintln!("[Watcher] {} is available again", path);
+                ctx.events.emit(
+                    "sentinel://watch-folder-restored",
+                    &WatchFolderEvent { path, reason: None },
+                );
+            }
+        }
+    }
+    rearmed
 }
 
-/// Remove a folder from watching
-pub fn remove_watched_folder(
-    handle: WatcherHandle,
-    path: &str,
+/// Save a folder's configuration and arm or disarm it accordingly
+pub fn configure_watched_folder(
+    ctx: &WatchContext,
+    handle: &WatcherHandle,
+    folder: WatchedFolder,
+) -> Result<WatchedFolderStatus, String> {
+    folder.validate()?;
+
+    let mut config = WatchConfig::load();
+    config.upsert(folder.clone());
+    config.save()?;
+
+    let path = folder.path.clone();
+    if folder.enabled {
+        arm_or_mark(ctx, handle, folder);
+    } else {
+        disarm(handle, &path);
+    }
+    start_rearm_loop(ctx, handle);
+
+    folder_statuses(handle)
+        .into_iter()
+        .find(|status| status.config.path == path)
+        .ok_or_else(|| format!("Watched folder not saved: {}", path))
+}
+
+/// Configured folders with their runtime state
+pub fn folder_statuses(handle: &WatcherHandle) -> Vec<WatchedFolderStatus> {
+    let config = WatchConfig::load();
+    let state = lock_state(handle);
+
+    let mut statuses: Vec<WatchedFolderStatus> = config
+        .folders
+        .into_iter()
+        .map(|folder| WatchedFolderStatus {
+            active: state.watchers.contains_key(&folder.path),
+            error: state.unavailable.get(&folder.path).cloned(),
+            config: folder,
+        })
+        .collect();
+
+    // Armed but not (or no longer) in the saved file
+    for watcher in state.watchers.values() {
+        if !statuses.iter().any(|s| s.config.path == watcher.config.path) {
+            statuses.push(WatchedFolderStatus {
+                config: watcher.config.clone(),
+                active: true,
+                error: None,
+            });
+        }
+    }
+    statuses
+}
+
+/// Start watching a directory (legacy single-folder mode)
+///
+/// The folder becomes the only enabled one; other saved folders are disabled
+/// but keep their settings.
+pub fn start_watcher(
+    ctx: &WatchContext,
+    handle: &WatcherHandle,
+    path: PathBuf,
 ) -> Result<(), String> {
-    let mut state = handle.lock().unwrap_or_else(|poisoned| {
-        eprintln!("Watcher state mutex was poisoned, recovering...");
-        poisoned.into_inner()
-    });
+    let path_str = path.to_string_lossy().to_string();
 
-    state.watchers.remove(path);
+    let mut config = WatchConfig::load();
+    for folder in &mut config.folders {
+        folder.enabled = false;
+    }
+    let mut folder = config
+        .get(&path_str)
+        .cloned()
+        .unwrap_or_else(|| WatchedFolder::new(path_str.clone()));
+    folder.enabled = true;
+    config.upsert(folder.clone());
+    config.save()?;
 
-    // Update enabled state
-    if state.watchers.is_empty() {
-        state.enabled = false;
-        state.watching_path = None;
+    // Clear all existing watchers
+    {
+        let mut state = lock_state(handle);
+        state.watchers.clear();
+        state.unavailable.clear();
     }
 
+    watch_folder(ctx, handle, folder)?;
+    lock_state(handle).watching_path = Some(path);
+    start_rearm_loop(ctx, handle);
+
     Ok(())
 }
 
-/// Stop watching all folders
-pub fn stop_watcher(handle: WatcherHandle) -> Result<(), String> {
-    let mut state = handle.lock().unwrap_or_else(|poisoned| {
-        eprintln!("Watcher state mutex was poisoned, recovering...");
-        poisoned.into_inner()
-    });
+/// Add a folder to watch (multi-folder mode)
+///
+/// A folder that was configured before keeps its settings; new folders are
+/// watched non-recursively with the notify action.
+pub fn add_watched_folder(
+    ctx: &WatchContext,
+    handle: &WatcherHandle,
+    path: PathBuf,
+) -> Result<(), String> {
+    let path_str = path.to_string_lossy().to_string();
+
+    // Skip if already watching
+    if lock_state(handle).watchers.contains_key(&path_str) {
+        return Ok(());
+    }
+
+    let mut folder = WatchConfig::load()
+        .get(&path_str)
+        .cloned()
+        .unwrap_or_else(|| WatchedFolder::new(path_str));
+    folder.enabled = true;
+    configure_watched_folder(ctx, handle, folder).map(|_| ())
+}
+
+/// Remove a folder from watching and from the saved configuration
+pub fn remove_watched_folder(
+    handle: &WatcherHandle,
+    path: &str,
+) -> Result<(), String> {
+    let mut config = WatchConfig::load();
+    if config.remove(path) {
+        config.save()?;
+    }
+    disarm(handle, path);
+    Ok(())
+}
+
+/// Stop watching all folders (they stay configured, but disabled)
+pub fn stop_watcher(handle: &WatcherHandle) -> Result<(), String> {
+    let mut config = WatchConfig::load();
+    if config.folders.iter().any(|f| f.enabled) {
+        for folder in &mut config.folders {
+            folder.enabled = false;
+        }
+        config.save()?;
+    }
+
+    let mut state = lock_state(handle);
     state.watchers.clear();
+    state.unavailable.clear();
     state.watching_path = None;
     state.enabled = false;
     Ok(())
@@ -197,48 +504,46 @@ pub fn stop_watcher(handle: WatcherHandle) -> Result<(), String> {
 
 /// Check if watcher is running
 pub fn is_watcher_running(handle: &WatcherHandle) -> bool {
-    match handle.lock() {
-        Ok(state) => state.enabled && !state.watchers.is_empty(),
-        Err(poisoned) => {
-            eprintln!("Watcher state mutex was poisoned in is_watcher_running");
-            let state = poisoned.into_inner();
-            state.enabled && !state.watchers.is_empty()
-        }
-    }
+    let state = lock_state(handle);
+    state.enabled && !state.watchers.is_empty()
 }
 
 /// Get the path being watched (legacy - returns first path)
 pub fn get_watching_path(handle: &WatcherHandle) -> Option<PathBuf> {
-    match handle.lock() {
-        Ok(state) => state.watching_path.clone(),
-        Err(poisoned) => {
-            eprintln!("Watcher state mutex was poisoned in get_watching_path");
-            poisoned.into_inner().watching_path.clone()
-        }
-    }
+    lock_state(handle).watching_path.clone()
 }
 
 /// Get all paths being watched
 pub fn get_all_watching_paths(handle: &WatcherHandle) -> Vec<String> {
-    match handle.lock() {
-        Ok(state) => state.watchers.keys().cloned().collect(),
-        Err(poisoned) => {
-            eprintln!("Watcher state mutex was poisoned in get_all_watching_paths");
-            poisoned.into_inner().watchers.keys().cloned().collect()
-        }
-    }
+    lock_state(handle).watchers.keys().cloned().collect()
 }
 
-/// Handle a file event
-fn handle_file_event(app: &AppHandle, event: &DebouncedEvent, watched_folder: &str) {
-    // Only handle create events for new files
-    let is_create = matches!(event.kind, EventKind::Create(_));
-
-    if !is_create {
-        return;
-    }
+/// Turn a debounced event into new-file events that pass the folder's filters
+fn handle_file_event(
+    event: &DebouncedEvent,
+    watched_folder: &str,
+    filter: &WatchFilter,
+    ignore_rules: Option<&IgnoreRules>,
+) -> Vec<FileChangeEvent> {
+    // New files are created, or renamed into place: browsers finish a download
+    // by renaming it from its partial name, and files moved in from outside the
+    // watch arrive without a `from` (FSEvents and unmatched debouncer moves
+    // report those as `Any`, so an existing path counts). Renames between
+    // tracked names (including the actions' own renames and moves) are not new files.
+    let tracked = |path: &Path| is_tracked(path, watched_folder, ignore_rules);
+    let candidates: &[PathBuf] = match event.kind {
+        EventKind::Create(_) => &event.paths,
+        EventKind::Modify(ModifyKind::Name(mode)) => match (mode, event.paths.as_slice()) {
+            (RenameMode::Both, [from, to]) if !tracked(from) => std::slice::from_ref(to),
+            (RenameMode::To, paths) => paths,
+            (RenameMode::Any, [path]) if path.exists() => std::slice::from_ref(path),
+            _ => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
 
-    for path in &event.paths {
+    let mut files = Vec::new();
+    for path in candidates {
         // Skip directories
         if path.is_dir() {
             continue;
@@ -263,19 +568,17 @@ fn handle_file_event(app: &AppHandle, event: &DebouncedEvent, watched_folder: &s
             continue;
         }
 
-        // Skip temporary files and hidden files
+        // Skip hidden files, temp files, partial downloads and ignored subfolders
+        if !tracked(path) {
+            continue;
+        }
+
         let file_name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        // Skip hidden files, temp files, and partial downloads
-        if file_name.starts_with('.')
-            || file_name.ends_with(".tmp")
-            || file_name.ends_with(".crdownload")
-            || file_name.ends_with(".part")
-            || file_name.ends_with(".download")
-        {
+        if !filter.allows(path) {
             continue;
         }
 
@@ -302,7 +605,7 @@ fn handle_file_event(app: &AppHandle, event: &DebouncedEvent, watched_folder: &s
         // Read content preview (first 4KB for text files) - pass watched_folder for security check
         let content_preview = read_content_preview(path, &extension, watched_folder);
 
-        let file_event = FileChangeEvent {
+        files.push(FileChangeEvent {
             id: uuid::Uuid::new_v4().to_string(),
             event_type: "created".to_string(),
             path: path.to_string_lossy().to_string(),
@@ -311,12 +614,90 @@ fn handle_file_event(app: &AppHandle, event: &DebouncedEvent, watched_folder: &s
             size: metadata.len(),
             content_preview,
             watched_folder: watched_folder.to_string(),
-        };
+        });
+    }
+    files
+}
 
-        // Emit event to frontend
-        if let Err(e) = app.emit("sentinel://file-created", &file_event) {
-            eprintln!("Failed to emit file event: {}", e);
-        }
+/// Suffixes of files that are still being downloaded or written
+const PARTIAL_SUFFIXES: &[&str] = &[".tmp", ".crdownload", ".part", ".download"];
+
+fn is_partial(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    PARTIAL_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Whether the watcher reports `path`: not hidden (or in a hidden subfolder),
+/// not a partial download, and not excluded by ignore rules
+fn is_tracked(path: &Path, watched_folder: &str, ignore_rules: Option<&IgnoreRules>) -> bool {
+    let relative = path.strip_prefix(watched_folder).unwrap_or(path);
+    let hidden = relative
+        .components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+
+    !hidden && !is_partial(path) && !ignore_rules.is_some_and(|rules| rules.is_ignored(path, false))
+}
+
+/// Changes to record in the watch journal for one debounced event
+fn journal_changes(
+    event: &DebouncedEvent,
+    watched_folder: &str,
+    ignore_rules: Option<&IgnoreRules>,
+) -> Vec<ChangeEvent> {
+    let tracked = |path: &Path| is_tracked(path, watched_folder, ignore_rules);
+    let change = |kind: ChangeKind, path: &Path| {
+        let size = std::fs::symlink_metadata(path).ok().filter(|m| m.is_file()).map(|m| m.len());
+        ChangeEvent::new(kind, path, watched_folder).with_size(size)
+    };
+
+    match event.kind {
+        EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder) => Vec::new(),
+        EventKind::Create(_) => event
+            .paths
+            .iter()
+            .filter(|p| p.is_file() && tracked(p))
+            .map(|p| change(ChangeKind::Created, p))
+            .collect(),
+        EventKind::Remove(_) => event
+            .paths
+            .iter()
+            .filter(|p| tracked(p))
+            .map(|p| change(ChangeKind::Removed, p))
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(mode)) => match (mode, event.paths.as_slice()) {
+            (RenameMode::Both, [from, to]) => {
+                if to.is_dir() {
+                    Vec::new()
+                } else if !tracked(to) {
+                    // Renamed to a hidden or temporary name: gone as far as the user cares
+                    if tracked(from) {
+                        vec![change(ChangeKind::Removed, from)]
+                    } else {
+                        Vec::new()
+                    }
+                } else if is_partial(from) || !tracked(from) {
+                    // A finished download is renamed from its partial name
+                    vec![change(ChangeKind::Created, to)]
+                } else {
+                    vec![change(ChangeKind::Moved, to).with_from(from)]
+                }
+            }
+            (RenameMode::From, [from]) if tracked(from) => vec![change(ChangeKind::Removed, from)],
+            (RenameMode::To, [to]) if to.is_file() && tracked(to) => {
+                vec![change(ChangeKind::Created, to)]
+            }
+            // Platforms that report each side of a rename separately
+            (RenameMode::Any | RenameMode::Other, paths) => paths
+                .iter()
+                .filter(|p| tracked(p) && !p.is_dir())
+                .map(|p| {
+                    let kind = if p.exists() { ChangeKind::Created } else { ChangeKind::Removed };
+                    change(kind, p)
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
     }
 }
 
@@ -371,3 +752,41 @@ fn read_content_preview(path: &PathBuf, extension: &Option<String>, watched_fold
         Err(_) => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::Event;
+    use tempfile::TempDir;
+
+    fn rename_event(mode: RenameMode, paths: Vec<PathBuf>) -> DebouncedEvent {
+        let mut event = Event::new(EventKind::Modify(ModifyKind::Name(mode)));
+        event.paths = paths;
+        DebouncedEvent::new(event, std::time::Instant::now())
+    }
+
+    #[test]
+    fn test_renames_into_place_are_new_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let report = root.join("report.pdf");
+        std::fs::write(&report, "%PDF").unwrap();
+        let folder = root.to_string_lossy().to_string();
+        let filter = WatchedFolder::new(folder.clone()).filter().unwrap();
+        let new_files = |event: DebouncedEvent| handle_file_event(&event, &folder, &filter, None).len();
+
+        // Finished download vs. a rename between tracked names
+        let partial = root.join("report.pdf.crdownload");
+        assert_eq!(new_files(rename_event(RenameMode::Both, vec![partial, report.clone()])), 1);
+        let old = root.join("old.pdf");
+        assert_eq!(new_files(rename_event(RenameMode::Both, vec![old, report.clone()])), 0);
+
+        // Moved in from outside the watch
+        assert_eq!(new_files(rename_event(RenameMode::To, vec![report.clone()])), 1);
+        assert_eq!(new_files(rename_event(RenameMode::Any, vec![report])), 1);
+
+        // Moved out: the path is gone
+        let gone = root.join("gone.pdf");
+        assert_eq!(new_files(rename_event(RenameMode::Any, vec![gone])), 0);
+    }
+}
@@ -5,6 +5,7 @@
 
 use super::{format_date_range, CollapsedSummary, CompressedNode, TreeConfig};
 use crate::models::FileEntry;
+use crate::scan::{ScanOptions, ScanSnapshot};
 use crate::vector::VectorIndex;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -41,12 +42,18 @@ impl TreeCompressor {
             return Err(format!("Path is not a directory: {:?}", root));
         }
 
-        self.compress_node(root, 0, vector_index)
+        // One extra level so folders at the depth limit can still be summarized
+        let snapshot = ScanOptions::new()
+            .with_max_depth(self.config.max_depth.saturating_add(1))
+            .scan(root)?;
+
+        self.compress_node(&snapshot, root, 0, vector_index)
     }
 
     /// Recursively compress a node in the tree
     fn compress_node(
         &self,
+        snapshot: &ScanSnapshot,
         path: &PathBuf,
         depth: usize,
         vector_index: Option<&VectorIndex>,
@@ -58,36 +65,23 @@ impl TreeCompressor {
 
         // Check depth limit
         if depth >= self.config.max_depth {
-            let summary = self.create_depth_limit_summary(path)?;
+            let summary = self.create_depth_limit_summary(snapshot, path)?;
             let tags = self.get_tags_for_path(path, vector_index);
             return Ok(CompressedNode::collapsed(path.clone(), name, summary, tags));
         }
 
-        // Read directory contents
-        let entries = std::fs::read_dir(path)
-            .map_err(|e| format!("Failed to read directory {:?}: {}", path, e))?;
+        if snapshot.is_unreadable(path) {
+            return Err(format!("Failed to read directory {:?}", path));
+        }
 
         let mut files: Vec<FileEntry> = Vec::new();
         let mut dirs: Vec<PathBuf> = Vec::new();
 
-        for entry in entries.filter_map(|e| e.ok()) {
-            let entry_path = entry.path();
-            let file_type = entry.file_type().ok();
-
-            // Skip hidden files
-            let entry_name = entry.file_name().to_string_lossy().to_string();
-            if entry_name.starts_with('.') {
-                continue;
-            }
-
-            if let Some(ft) = file_type {
-                if ft.is_dir() {
-                    dirs.push(entry_path);
-                } else if ft.is_file() {
-                    if let Ok(file_entry) = FileEntry::from_path(&entry_path) {
-                        files.push(file_entry);
-                    }
-                }
+        for entry in snapshot.children(path) {
+            if entry.is_dir() {
+                dirs.push(entry.path.clone());
+            } else if entry.is_file() {
+                files.push(FileEntry::from_scan_entry(entry));
             }
         }
 
@@ -122,7 +116,7 @@ impl TreeCompressor {
 
         // Recursively process subdirectories
         for dir_path in dirs {
-            match self.compress_node(&dir_path, depth + 1, vector_index) {
+            match self.compress_node(snapshot, &dir_path, depth + 1, vector_index) {
                 Ok(node) => children.push(node),
                 Err(e) => {
                     eprintln!("[TreeCompressor] Warning: Failed to compress {:?}: {}", dir_path, e);
@@ -245,25 +239,25 @@ impl TreeCompressor {
     }
 
     /// Create summary when depth limit is reached
-    fn create_depth_limit_summary(&self, path: &PathBuf) -> Result<CollapsedSummary, String> {
-        // Quick count without full traversal
-        let entries = std::fs::read_dir(path)
-            .map_err(|e| format!("Failed to read directory: {}", e))?;
+    fn create_depth_limit_summary(
+        &self,
+        snapshot: &ScanSnapshot,
+        path: &PathBuf,
+    ) -> Result<CollapsedSummary, String> {
+        if snapshot.is_unreadable(path) {
+            return Err(format!("Failed to read directory {:?}", path));
+        }
 
         let mut file_count = 0;
         let mut dir_count = 0;
         let mut total_size: u64 = 0;
 
-        for entry in entries.filter_map(|e| e.ok()) {
-            if let Ok(ft) = entry.file_type() {
-                if ft.is_dir() {
-                    dir_count += 1;
-                } else if ft.is_file() {
-                    file_count += 1;
-                    if let Ok(meta) = entry.metadata() {
-                        total_size += meta.len();
-                    }
-                }
+        for entry in snapshot.children(path) {
+            if entry.is_dir() {
+                dir_count += 1;
+            } else if entry.is_file() {
+                file_count += 1;
+                total_size += entry.size;
             }
         }
 
@@ -3,13 +3,19 @@
 //! Provides an in-memory shadow filesystem that mirrors the real filesystem.
 //! This enables simulation of file operations before committing changes,
 //! allowing for validation, conflict detection, and undo/redo capabilities.
+//! Scans can be persisted as snapshots and diffed later (`snapshots`), and
+//! plans can be rendered as before/after reports for approval (`plan_diff`).
 
 pub mod graph;
 pub mod node;
+pub mod plan_diff;
 pub mod scanner;
 pub mod simulator;
+pub mod snapshots;
 
 pub use graph::*;
 pub use node::*;
+pub use plan_diff::*;
 pub use scanner::*;
 pub use simulator::*;
+pub use snapshots::*;
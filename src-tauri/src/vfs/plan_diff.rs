@@ -0,0 +1,1067 @@
+//! Plan diffs
+//!
+//! [`PlanDiff::build`] replays a plan's operations over a scanned [`ShadowVFS`]
+//! without staging anything and reports what would change: each operation
+//! with its outcome (applied, no-op or conflict), aggregate stats, and a
+//! before/after folder tree with per-folder file counts and sizes.
+//!
+//! A diff renders as an indented tree ([`PlanDiff::render_tree`]), as a
+//! Markdown or standalone HTML report for attaching to an approval ticket
+//! ([`PlanDiff::render_markdown`], [`PlanDiff::render_html`]), and serializes
+//! to JSON as-is.
+
+use super::graph::ShadowVFS;
+use super::node::VFSNodeType;
+use super::simulator::SimulatedOperation;
+use crate::utils::format_size;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Operations listed one by one in Markdown/HTML reports; the rest are counted
+const MAX_REPORT_OPERATIONS: usize = 500;
+
+/// Output format for [`PlanDiff::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Tree,
+    Json,
+    Markdown,
+    Html,
+}
+
+impl DiffFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "tree" => Ok(Self::Tree),
+            "json" => Ok(Self::Json),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "Unknown diff format: {} (expected tree, json, markdown or html)",
+                other
+            )),
+        }
+    }
+}
+
+/// What an operation does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffAction {
+    CreateFolder,
+    /// Move to another folder
+    Move,
+    /// Move within the same folder
+    Rename,
+    Remove,
+}
+
+impl DiffAction {
+    fn label(self) -> &'static str {
+        match self {
+            DiffAction::CreateFolder => "Create folder",
+            DiffAction::Move => "Move",
+            DiffAction::Rename => "Rename",
+            DiffAction::Remove => "Remove",
+        }
+    }
+}
+
+/// Whether an operation would change anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOutcome {
+    Applied,
+    /// Valid but changes nothing (same source and destination, folder exists)
+    NoOp,
+    /// Would fail at execution time
+    Conflict,
+}
+
+/// One plan operation, replayed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffOperation {
+    pub action: DiffAction,
+    pub outcome: DiffOutcome,
+    /// Path relative to the plan folder
+    pub path: String,
+    pub to: Option<String>,
+    pub is_dir: bool,
+    /// Files affected (the whole subtree for folders)
+    pub files: usize,
+    pub bytes: u64,
+    /// Why the operation is a no-op or a conflict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Aggregate numbers for a plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiffStats {
+    pub operations: usize,
+    pub files_moved: usize,
+    /// Bytes moved to another folder (renames excluded)
+    pub bytes_moved: u64,
+    pub files_renamed: usize,
+    pub files_removed: usize,
+    pub bytes_removed: u64,
+    pub folders_created: usize,
+    /// Folders that hold files now and none afterwards
+    pub folders_emptied: usize,
+    pub no_ops: usize,
+    pub conflicts: usize,
+}
+
+impl PlanDiffStats {
+    /// One-line summary, e.g. "12 moved (3.4MB), 2 renamed, 3 folders created"
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!(
+            "{} moved ({})",
+            self.files_moved,
+            format_size(self.bytes_moved)
+        )];
+        if self.files_renamed > 0 {
+            parts.push(format!("{} renamed", self.files_renamed));
+        }
+        if self.files_removed > 0 {
+            parts.push(format!(
+                "{} removed ({})",
+                self.files_removed,
+                format_size(self.bytes_removed)
+            ));
+        }
+        parts.push(format!("{} folders created", self.folders_created));
+        if self.folders_emptied > 0 {
+            parts.push(format!("{} folders emptied", self.folders_emptied));
+        }
+        if self.no_ops > 0 {
+            parts.push(format!("{} no-ops", self.no_ops));
+        }
+        if self.conflicts > 0 {
+            parts.push(format!("{} conflicts", self.conflicts));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Recursive file count and size of a folder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FolderCounts {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl std::fmt::Display for FolderCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.files {
+            0 => write!(f, "empty"),
+            1 => write!(f, "1 file, {}", format_size(self.bytes)),
+            n => write!(f, "{} files, {}", n, format_size(self.bytes)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderStatus {
+    Unchanged,
+    /// Counts or sizes change
+    Changed,
+    Created,
+    /// Holds files before and none after
+    Emptied,
+    /// Moved away or removed
+    Removed,
+}
+
+/// A folder in the before/after tree
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffFolder {
+    pub name: String,
+    /// Path relative to the plan folder ("" for the plan folder itself)
+    pub path: String,
+    pub status: FolderStatus,
+    pub before: FolderCounts,
+    pub after: FolderCounts,
+    pub children: Vec<DiffFolder>,
+}
+
+impl DiffFolder {
+    /// Whether this folder or anything below it changes
+    pub fn has_changes(&self) -> bool {
+        self.status != FolderStatus::Unchanged || self.children.iter().any(DiffFolder::has_changes)
+    }
+
+    fn count(&self) -> usize {
+        1 + self.children.iter().map(DiffFolder::count).sum::<usize>()
+    }
+}
+
+/// Everything a plan would change in its folder
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiff {
+    pub plan_id: String,
+    pub description: String,
+    pub root: String,
+    pub operations: Vec<DiffOperation>,
+    pub stats: PlanDiffStats,
+    pub tree: DiffFolder,
+}
+
+/// Files (path -> size) and folders of the plan folder while replaying
+struct Layout {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, u64>,
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl Layout {
+    fn from_vfs(vfs: &ShadowVFS) -> Self {
+        let root = vfs.root().clone();
+        let mut files = BTreeMap::new();
+        let mut dirs = BTreeSet::new();
+        dirs.insert(root.clone());
+
+        for node in vfs
+            .files()
+            .into_iter()
+            .chain(vfs.get_by_type(VFSNodeType::Symlink))
+        {
+            files.insert(node.path.clone(), node.size);
+        }
+        for node in vfs.directories() {
+            dirs.insert(node.path.clone());
+        }
+        Self { root, files, dirs }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.dirs.contains(path)
+    }
+
+    /// Files under a folder (ordered maps keep a subtree contiguous)
+    fn files_under<'a>(&'a self, dir: &'a Path) -> impl Iterator<Item = (&'a PathBuf, &'a u64)> {
+        self.files
+            .range(dir.to_path_buf()..)
+            .take_while(move |(path, _)| path.starts_with(dir))
+    }
+
+    fn dirs_under(&self, dir: &Path) -> Vec<PathBuf> {
+        self.dirs
+            .range(dir.to_path_buf()..)
+            .take_while(|path| path.starts_with(dir))
+            .cloned()
+            .collect()
+    }
+
+    /// Files and bytes a move or removal of `path` touches
+    fn weight(&self, path: &Path) -> (usize, u64) {
+        match self.files.get(path) {
+            Some(size) => (1, *size),
+            None => self
+                .files_under(path)
+                .fold((0, 0), |(n, bytes), (_, size)| (n + 1, bytes + size)),
+        }
+    }
+
+    fn relocate(&mut self, source: &Path, destination: &Path) {
+        let rebase = |path: &Path| destination.join(path.strip_prefix(source).unwrap_or(path));
+
+        if let Some(size) = self.files.remove(source) {
+            self.files.insert(destination.to_path_buf(), size);
+            return;
+        }
+        let moved: Vec<(PathBuf, u64)> = self
+            .files_under(source)
+            .map(|(path, size)| (path.clone(), *size))
+            .collect();
+        for (path, size) in moved {
+            self.files.remove(&path);
+            self.files.insert(rebase(&path), size);
+        }
+        for dir in self.dirs_under(source) {
+            self.dirs.remove(&dir);
+            self.dirs.insert(rebase(&dir));
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if self.files.remove(path).is_some() {
+            return;
+        }
+        let files: Vec<PathBuf> = self.files_under(path).map(|(p, _)| p.clone()).collect();
+        for file in files {
+            self.files.remove(&file);
+        }
+        for dir in self.dirs_under(path) {
+            self.dirs.remove(&dir);
+        }
+    }
+
+    /// Recursive counts for every folder
+    fn folder_counts(&self) -> HashMap<PathBuf, FolderCounts> {
+        let mut counts: HashMap<PathBuf, FolderCounts> = HashMap::new();
+        for (path, size) in &self.files {
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(&self.root) {
+                    break;
+                }
+                let entry = counts.entry(dir.to_path_buf()).or_default();
+                entry.files += 1;
+                entry.bytes += size;
+            }
+        }
+        counts
+    }
+}
+
+impl PlanDiff {
+    /// Replay `operations` over the files of `vfs` (which is not modified).
+    ///
+    /// Operations that would fail are recorded as conflicts and skipped, so
+    /// later operations are checked against the state the executor would see.
+    pub fn build(vfs: &ShadowVFS, operations: &[SimulatedOperation]) -> Self {
+        let before = Layout::from_vfs(vfs);
+        let mut after = Layout::from_vfs(vfs);
+        let root = before.root.clone();
+        let rel = |path: &Path| relative(&root, path);
+
+        let mut diffed = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let op = match operation {
+                SimulatedOperation::CreateFolder { path } => {
+                    let path = PathBuf::from(path);
+                    let (outcome, reason) = if after.dirs.contains(&path) {
+                        (DiffOutcome::NoOp, Some("folder already exists"))
+                    } else if after.files.contains_key(&path) {
+                        (DiffOutcome::Conflict, Some("a file exists at this path"))
+                    } else if !path.parent().is_some_and(|p| after.dirs.contains(p)) {
+                        (DiffOutcome::Conflict, Some("parent folder does not exist"))
+                    } else {
+                        after.dirs.insert(path.clone());
+                        (DiffOutcome::Applied, None)
+                    };
+                    DiffOperation {
+                        action: DiffAction::CreateFolder,
+                        outcome,
+                        path: rel(&path),
+                        to: None,
+                        is_dir: true,
+                        files: 0,
+                        bytes: 0,
+                        reason: reason.map(String::from),
+                    }
+                }
+                SimulatedOperation::Move {
+                    source,
+                    destination,
+                } => {
+                    let source = PathBuf::from(source);
+                    let destination = PathBuf::from(destination);
+                    let action = if source.parent() == destination.parent() {
+                        DiffAction::Rename
+                    } else {
+                        DiffAction::Move
+                    };
+                    let is_dir = after.dirs.contains(&source);
+                    let (files, bytes) = after.weight(&source);
+
+                    let reason = if source == destination {
+                        Some((DiffOutcome::NoOp, "source and destination are the same"))
+                    } else if source == root || !after.exists(&source) {
+                        Some((DiffOutcome::Conflict, "source does not exist"))
+                    } else if is_dir && destination.starts_with(&source) {
+                        Some((DiffOutcome::Conflict, "cannot move a folder into itself"))
+                    } else if after.exists(&destination) {
+                        Some((DiffOutcome::Conflict, "destination already exists"))
+                    } else if !destination.parent().is_some_and(|p| after.dirs.contains(p)) {
+                        Some((DiffOutcome::Conflict, "destination folder does not exist"))
+                    } else {
+                        None
+                    };
+                    if reason.is_none() {
+                        after.relocate(&source, &destination);
+                    }
+
+                    DiffOperation {
+                        action,
+                        outcome: reason.map_or(DiffOutcome::Applied, |(outcome, _)| outcome),
+                        path: rel(&source),
+                        to: Some(rel(&destination)),
+                        is_dir,
+                        files,
+                        bytes,
+                        reason: reason.map(|(_, reason)| reason.to_string()),
+                    }
+                }
+                SimulatedOperation::Delete { path } => {
+                    let path = PathBuf::from(path);
+                    let is_dir = after.dirs.contains(&path);
+                    let (files, bytes) = after.weight(&path);
+                    let reason = if path == root {
+                        Some("cannot remove the plan folder")
+                    } else if !after.exists(&path) {
+                        Some("path does not exist")
+                    } else {
+                        after.remove(&path);
+                        None
+                    };
+                    DiffOperation {
+                        action: DiffAction::Remove,
+                        outcome: if reason.is_some() {
+                            DiffOutcome::Conflict
+                        } else {
+                            DiffOutcome::Applied
+                        },
+                        path: rel(&path),
+                        to: None,
+                        is_dir,
+                        files,
+                        bytes,
+                        reason: reason.map(String::from),
+                    }
+                }
+            };
+            diffed.push(op);
+        }
+
+        let before_counts = before.folder_counts();
+        let after_counts = after.folder_counts();
+        let tree = build_tree(&root, &before, &after, &before_counts, &after_counts);
+        let stats = compute_stats(&diffed, &tree);
+
+        Self {
+            plan_id: String::new(),
+            description: String::new(),
+            root: root.to_string_lossy().to_string(),
+            operations: diffed,
+            stats,
+            tree,
+        }
+    }
+
+    /// Label the diff with the plan it was built from
+    pub fn with_plan(mut self, plan_id: impl Into<String>, description: impl Into<String>) -> Self {
+        self.plan_id = plan_id.into();
+        self.description = description.into();
+        self
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        self.stats.conflicts > 0
+    }
+
+    pub fn render(&self, format: DiffFormat) -> Result<String, String> {
+        match format {
+            DiffFormat::Tree => Ok(self.render_tree()),
+            DiffFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize plan diff: {}", e)),
+            DiffFormat::Markdown => Ok(self.render_markdown()),
+            DiffFormat::Html => Ok(self.render_html()),
+        }
+    }
+
+    /// Before/after folder tree; folders with no changes below them are hidden
+    ///
+    /// ```text
+    /// Inbox/                  14 files, 3.1MB
+    /// ├── Documents/ [new]    empty -> 12 files, 3.0MB
+    /// └── Downloads/ [emptied] 12 files, 3.0MB -> empty
+    /// ```
+    pub fn render_tree(&self) -> String {
+        let mut lines = Vec::new();
+        let root_name = Path::new(&self.root)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.root.clone());
+        lines.push((format!("{}/", root_name), folder_detail(&self.tree)));
+        tree_lines(&self.tree.children, "", &mut lines);
+
+        let width = lines
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(60);
+        let mut out = String::new();
+        for (label, detail) in &lines {
+            let pad = width.saturating_sub(label.chars().count());
+            out.push_str(&format!("{}{}  {}\n", label, " ".repeat(pad), detail));
+        }
+
+        let hidden = self.tree.count() - lines.len();
+        if hidden > 0 {
+            out.push_str(&format!("({} unchanged folders not shown)\n", hidden));
+        }
+        out
+    }
+
+    /// Markdown report: summary table, conflicts, folder tree and operations
+    pub fn render_markdown(&self) -> String {
+        let mut md = format!("# Plan diff: {}\n\n", md_text(self.title()));
+        if !self.plan_id.is_empty() {
+            md.push_str(&format!("- **Plan:** `{}`\n", self.plan_id));
+        }
+        md.push_str(&format!("- **Folder:** `{}`\n", self.root));
+        md.push_str(&format!("- **Operations:** {}\n\n", self.stats.operations));
+
+        if self.has_conflicts() {
+            md.push_str(&format!(
+                "> **Warning:** {} operations conflict with the current folder contents and would fail.\n\n",
+                self.stats.conflicts
+            ));
+        }
+
+        md.push_str("## Summary\n\n| | Count | Size |\n|---|---:|---:|\n");
+        for (label, count, bytes) in self.summary_rows() {
+            let size = bytes.map(format_size).unwrap_or_default();
+            md.push_str(&format!("| {} | {} | {} |\n", label, count, size));
+        }
+
+        for (title, outcome) in [
+            ("Conflicts", DiffOutcome::Conflict),
+            ("No-op operations", DiffOutcome::NoOp),
+        ] {
+            let ops: Vec<&DiffOperation> = self
+                .operations
+                .iter()
+                .filter(|op| op.outcome == outcome)
+                .collect();
+            if ops.is_empty() {
+                continue;
+            }
+            md.push_str(&format!(
+                "\n## {} ({})\n\n| Action | Path | To | Reason |\n|---|---|---|---|\n",
+                title,
+                ops.len()
+            ));
+            for op in ops {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    op.action.label(),
+                    md_code(&op.path),
+                    op.to.as_deref().map(md_code).unwrap_or_default(),
+                    op.reason.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+
+        md.push_str(&format!(
+            "\n## Folders\n\n```text\n{}```\n",
+            self.render_tree()
+        ));
+
+        let applied: Vec<&DiffOperation> = self.applied_operations().collect();
+        md.push_str(&format!(
+            "\n## Operations ({})\n\n| Action | Path | To | Size |\n|---|---|---|---:|\n",
+            applied.len()
+        ));
+        for op in applied.iter().take(MAX_REPORT_OPERATIONS) {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                op.action.label(),
+                md_code(&op.path),
+                op.to.as_deref().map(md_code).unwrap_or_default(),
+                operation_size(op)
+            ));
+        }
+        if applied.len() > MAX_REPORT_OPERATIONS {
+            md.push_str(&format!(
+                "\n_{} more operations not listed._\n",
+                applied.len() - MAX_REPORT_OPERATIONS
+            ));
+        }
+        md
+    }
+
+    /// Standalone HTML page with the same sections as the Markdown report
+    pub fn render_html(&self) -> String {
+        let title = format!("Plan diff: {}", self.title());
+        let mut body = format!("<h1>{}</h1>\n<ul>\n", html_escape(&title));
+        if !self.plan_id.is_empty() {
+            body.push_str(&format!(
+                "<li><b>Plan:</b> <code>{}</code></li>\n",
+                html_escape(&self.plan_id)
+            ));
+        }
+        body.push_str(&format!(
+            "<li><b>Folder:</b> <code>{}</code></li>\n<li><b>Operations:</b> {}</li>\n</ul>\n",
+            html_escape(&self.root),
+            self.stats.operations
+        ));
+
+        if self.has_conflicts() {
+            body.push_str(&format!(
+                "<p class=\"warning\"><b>Warning:</b> {} operations conflict with the current folder contents and would fail.</p>\n",
+                self.stats.conflicts
+            ));
+        }
+
+        body.push_str("<h2>Summary</h2>\n<table>\n<tr><th></th><th>Count</th><th>Size</th></tr>\n");
+        for (label, count, bytes) in self.summary_rows() {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                label,
+                count,
+                bytes.map(format_size).unwrap_or_default()
+            ));
+        }
+        body.push_str("</table>\n");
+
+        for (title, outcome) in [
+            ("Conflicts", DiffOutcome::Conflict),
+            ("No-op operations", DiffOutcome::NoOp),
+        ] {
+            let ops: Vec<&DiffOperation> = self
+                .operations
+                .iter()
+                .filter(|op| op.outcome == outcome)
+                .collect();
+            if ops.is_empty() {
+                continue;
+            }
+            body.push_str(&format!(
+                "<h2>{} ({})</h2>\n<table>\n<tr><th>Action</th><th>Path</th><th>To</th><th>Reason</th></tr>\n",
+                title,
+                ops.len()
+            ));
+            for op in ops {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>\n",
+                    op.action.label(),
+                    html_escape(&op.path),
+                    html_escape(op.to.as_deref().unwrap_or_default()),
+                    html_escape(op.reason.as_deref().unwrap_or_default())
+                ));
+            }
+            body.push_str("</table>\n");
+        }
+
+        body.push_str(&format!(
+            "<h2>Folders</h2>\n<pre>{}</pre>\n",
+            html_escape(&self.render_tree())
+        ));
+
+        let applied: Vec<&DiffOperation> = self.applied_operations().collect();
+        body.push_str(&format!(
+            "<h2>Operations ({})</h2>\n<table>\n<tr><th>Action</th><th>Path</th><th>To</th><th>Size</th></tr>\n",
+            applied.len()
+        ));
+        for op in applied.iter().take(MAX_REPORT_OPERATIONS) {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td class=\"num\">{}</td></tr>\n",
+                op.action.label(),
+                html_escape(&op.path),
+                html_escape(op.to.as_deref().unwrap_or_default()),
+                operation_size(op)
+            ));
+        }
+        body.push_str("</table>\n");
+        if applied.len() > MAX_REPORT_OPERATIONS {
+            body.push_str(&format!(
+                "<p><i>{} more operations not listed.</i></p>\n",
+                applied.len() - MAX_REPORT_OPERATIONS
+            ));
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            html_escape(&title),
+            HTML_STYLE,
+            body
+        )
+    }
+
+    fn title(&self) -> &str {
+        if !self.description.is_empty() {
+            &self.description
+        } else if !self.plan_id.is_empty() {
+            &self.plan_id
+        } else {
+            &self.root
+        }
+    }
+
+    fn applied_operations(&self) -> impl Iterator<Item = &DiffOperation> {
+        self.operations
+            .iter()
+            .filter(|op| op.outcome == DiffOutcome::Applied)
+    }
+
+    /// (label, count, bytes) rows shared by the Markdown and HTML reports
+    fn summary_rows(&self) -> Vec<(&'static str, usize, Option<u64>)> {
+        let s = &self.stats;
+        vec![
+            ("Files moved", s.files_moved, Some(s.bytes_moved)),
+            ("Files renamed", s.files_renamed, None),
+            ("Files removed", s.files_removed, Some(s.bytes_removed)),
+            ("Folders created", s.folders_created, None),
+            ("Folders emptied", s.folders_emptied, None),
+            ("No-op operations", s.no_ops, None),
+            ("Conflicts", s.conflicts, None),
+        ]
+    }
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: -apple-system, 'Segoe UI', sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+td.num { text-align: right; }
+pre { background: #f6f6f6; padding: 1em; overflow-x: auto; }
+.warning { background: #fff3cd; padding: 0.5em 1em; border-left: 4px solid #e0a800; }
+";
+
+fn relative(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+fn build_tree(
+    root: &Path,
+    before: &Layout,
+    after: &Layout,
+    before_counts: &HashMap<PathBuf, FolderCounts>,
+    after_counts: &HashMap<PathBuf, FolderCounts>,
+) -> DiffFolder {
+    let mut children: HashMap<&Path, Vec<&Path>> = HashMap::new();
+    let all: BTreeSet<&PathBuf> = before.dirs.iter().chain(after.dirs.iter()).collect();
+    for dir in &all {
+        if dir.as_path() != root {
+            if let Some(parent) = dir.parent() {
+                children.entry(parent).or_default().push(dir);
+            }
+        }
+    }
+
+    fn node(
+        dir: &Path,
+        root: &Path,
+        children: &HashMap<&Path, Vec<&Path>>,
+        before: &Layout,
+        after: &Layout,
+        before_counts: &HashMap<PathBuf, FolderCounts>,
+        after_counts: &HashMap<PathBuf, FolderCounts>,
+    ) -> DiffFolder {
+        let before_n = before_counts.get(dir).copied().unwrap_or_default();
+        let after_n = after_counts.get(dir).copied().unwrap_or_default();
+        let status = match (before.dirs.contains(dir), after.dirs.contains(dir)) {
+            (false, _) => FolderStatus::Created,
+            (true, false) => FolderStatus::Removed,
+            _ if before_n.files > 0 && after_n.files == 0 => FolderStatus::Emptied,
+            _ if before_n != after_n => FolderStatus::Changed,
+            _ => FolderStatus::Unchanged,
+        };
+        let kids = children
+            .get(dir)
+            .map(|kids| {
+                kids.iter()
+                    .map(|kid| {
+                        node(
+                            kid,
+                            root,
+                            children,
+                            before,
+                            after,
+                            before_counts,
+                            after_counts,
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        DiffFolder {
+            name: dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: if dir == root {
+                String::new()
+            } else {
+                relative(root, dir)
+            },
+            status,
+            before: before_n,
+            after: after_n,
+            children: kids,
+        }
+    }
+
+    node(
+        root,
+        root,
+        &children,
+        before,
+        after,
+        before_counts,
+        after_counts,
+    )
+}
+
+fn compute_stats(operations: &[DiffOperation], tree: &DiffFolder) -> PlanDiffStats {
+    let mut stats = PlanDiffStats {
+        operations: operations.len(),
+        ..Default::default()
+    };
+    for op in operations {
+        match op.outcome {
+            DiffOutcome::NoOp => stats.no_ops += 1,
+            DiffOutcome::Conflict => stats.conflicts += 1,
+            DiffOutcome::Applied => match op.action {
+                DiffAction::CreateFolder => stats.folders_created += 1,
+                DiffAction::Move => {
+                    stats.files_moved += op.files;
+                    stats.bytes_moved += op.bytes;
+                }
+                DiffAction::Rename => stats.files_renamed += op.files,
+                DiffAction::Remove => {
+                    stats.files_removed += op.files;
+                    stats.bytes_removed += op.bytes;
+                }
+            },
+        }
+    }
+
+    fn emptied(folder: &DiffFolder) -> usize {
+        let own = usize::from(folder.status == FolderStatus::Emptied);
+        own + folder.children.iter().map(emptied).sum::<usize>()
+    }
+    stats.folders_emptied = emptied(tree);
+    stats
+}
+
+fn folder_detail(folder: &DiffFolder) -> String {
+    if folder.before == folder.after {
+        folder.before.to_string()
+    } else {
+        format!("{} -> {}", folder.before, folder.after)
+    }
+}
+
+fn tree_lines(folders: &[DiffFolder], prefix: &str, lines: &mut Vec<(String, String)>) {
+    let shown: Vec<&DiffFolder> = folders.iter().filter(|f| f.has_changes()).collect();
+    for (i, folder) in shown.iter().enumerate() {
+        let last = i + 1 == shown.len();
+        let tag = match folder.status {
+            FolderStatus::Created => " [new]",
+            FolderStatus::Removed => " [removed]",
+            FolderStatus::Emptied => " [emptied]",
+            FolderStatus::Changed | FolderStatus::Unchanged => "",
+        };
+        lines.push((
+            format!(
+                "{}{}{}/{}",
+                prefix,
+                if last { "└── " } else { "├── " },
+                folder.name,
+                tag
+            ),
+            folder_detail(folder),
+        ));
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        tree_lines(&folder.children, &child_prefix, lines);
+    }
+}
+
+fn operation_size(op: &DiffOperation) -> String {
+    match (op.action, op.is_dir) {
+        (DiffAction::CreateFolder, _) => String::new(),
+        (_, true) => format!("{} files, {}", op.files, format_size(op.bytes)),
+        (_, false) => format_size(op.bytes),
+    }
+}
+
+/// Inline code span for a path in a Markdown table
+fn md_code(text: &str) -> String {
+    format!("`{}`", text.replace('`', "'").replace('|', "\\|"))
+}
+
+fn md_text(text: &str) -> String {
+    text.replace('\n', " ")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::FileNode;
+
+    fn add(vfs: &mut ShadowVFS, node: FileNode) {
+        let parent = node.path.parent().unwrap().to_path_buf();
+        let mut node = node;
+        node.parent = Some(parent.clone());
+        if let Some(parent) = vfs.get_mut(&parent) {
+            parent.add_child(node.path.clone());
+        }
+        vfs.insert(node);
+    }
+
+    fn inbox() -> ShadowVFS {
+        let mut vfs = ShadowVFS::new(PathBuf::from("/inbox"));
+        add(
+            &mut vfs,
+            FileNode::directory(PathBuf::from("/inbox/downloads")),
+        );
+        add(&mut vfs, FileNode::directory(PathBuf::from("/inbox/docs")));
+        add(
+            &mut vfs,
+            FileNode::file(PathBuf::from("/inbox/downloads/a.pdf")).with_size(1000),
+        );
+        add(
+            &mut vfs,
+            FileNode::file(PathBuf::from("/inbox/downloads/b.pdf")).with_size(500),
+        );
+        add(
+            &mut vfs,
+            FileNode::file(PathBuf::from("/inbox/docs/notes.txt")).with_size(20),
+        );
+        add(
+            &mut vfs,
+            FileNode::file(PathBuf::from("/inbox/old.log")).with_size(7),
+        );
+        vfs
+    }
+
+    #[test]
+    fn test_build_stats_and_conflicts() {
+        let vfs = inbox();
+        let ops = vec![
+            SimulatedOperation::create_folder("/inbox/docs/pdf"),
+            SimulatedOperation::create_folder("/inbox/docs"),
+            SimulatedOperation::move_op("/inbox/downloads/a.pdf", "/inbox/docs/pdf/a.pdf"),
+            SimulatedOperation::move_op("/inbox/downloads/b.pdf", "/inbox/docs/pdf/a.pdf"),
+            SimulatedOperation::move_op("/inbox/docs/notes.txt", "/inbox/docs/notes.txt"),
+            SimulatedOperation::move_op("/inbox/docs/notes.txt", "/inbox/docs/notes-2024.txt"),
+            SimulatedOperation::move_op("/inbox/missing.txt", "/inbox/docs/missing.txt"),
+            SimulatedOperation::move_op("/inbox/docs", "/inbox/docs/pdf/docs"),
+            SimulatedOperation::delete("/inbox/old.log"),
+        ];
+        let diff = PlanDiff::build(&vfs, &ops).with_plan("plan-1", "Sort inbox");
+
+        let s = &diff.stats;
+        assert_eq!((s.files_moved, s.bytes_moved), (1, 1000));
+        assert_eq!(s.files_renamed, 1);
+        assert_eq!((s.files_removed, s.bytes_removed), (1, 7));
+        assert_eq!(s.folders_created, 1);
+        assert_eq!(s.no_ops, 2);
+        assert_eq!(s.conflicts, 3);
+        assert_eq!(s.folders_emptied, 0);
+        assert_eq!(
+            diff.operations[3].reason.as_deref(),
+            Some("destination already exists")
+        );
+        assert_eq!(
+            diff.operations[7].reason.as_deref(),
+            Some("cannot move a folder into itself")
+        );
+        assert_eq!(
+            diff.operations[5].to.as_deref(),
+            Some("docs/notes-2024.txt")
+        );
+
+        // The vfs itself is untouched
+        assert!(vfs.get(&PathBuf::from("/inbox/downloads/a.pdf")).is_some());
+
+        let docs = diff
+            .tree
+            .children
+            .iter()
+            .find(|f| f.name == "docs")
+            .unwrap();
+        assert_eq!(
+            docs.before,
+            FolderCounts {
+                files: 1,
+                bytes: 20
+            }
+        );
+        assert_eq!(
+            docs.after,
+            FolderCounts {
+                files: 2,
+                bytes: 1020
+            }
+        );
+        assert_eq!(docs.children[0].status, FolderStatus::Created);
+        assert_eq!(diff.tree.before.files, 4);
+        assert_eq!(diff.tree.after.files, 3);
+    }
+
+    #[test]
+    fn test_folder_moves_and_renderers() {
+        let vfs = inbox();
+        let ops = vec![
+            SimulatedOperation::create_folder("/inbox/archive"),
+            SimulatedOperation::move_op("/inbox/downloads/a.pdf", "/inbox/archive/a.pdf"),
+            SimulatedOperation::move_op("/inbox/downloads/b.pdf", "/inbox/archive/<b>.pdf"),
+        ];
+        let diff = PlanDiff::build(&vfs, &ops);
+        assert_eq!(diff.stats.folders_emptied, 1);
+        assert!(!diff.has_conflicts());
+
+        let tree = diff.render_tree();
+        assert!(tree.contains("archive/ [new]"), "{}", tree);
+        assert!(tree.contains("downloads/ [emptied]"), "{}", tree);
+        assert!(
+            tree.contains("downloads/ [emptied]  2 files, 1KB -> empty"),
+            "{}",
+            tree
+        );
+        assert!(tree.contains("(1 unchanged folders not shown)"), "{}", tree);
+
+        let md = diff.render_markdown();
+        assert!(md.contains("| Files moved | 2 | 1KB |"), "{}", md);
+        assert!(
+            md.contains("| Move | `downloads/a.pdf` | `archive/a.pdf` | 1000B |"),
+            "{}",
+            md
+        );
+
+        let html = diff.render_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("archive/&lt;b&gt;.pdf"));
+        assert!(!html.contains("<b>.pdf"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&diff.render(DiffFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["stats"]["filesMoved"], 2);
+        assert_eq!(json["operations"][1]["outcome"], "applied");
+
+        // Moving a whole folder carries its files along
+        let moved = PlanDiff::build(
+            &vfs,
+            &[SimulatedOperation::move_op(
+                "/inbox/downloads",
+                "/inbox/docs/downloads",
+            )],
+        );
+        assert_eq!(
+            (moved.stats.files_moved, moved.stats.bytes_moved),
+            (2, 1500)
+        );
+        assert!(moved.operations[0].is_dir);
+        let docs = moved
+            .tree
+            .children
+            .iter()
+            .find(|f| f.name == "docs")
+            .unwrap();
+        assert_eq!(docs.after.files, 3);
+        assert_eq!(docs.children[0].status, FolderStatus::Created);
+    }
+}
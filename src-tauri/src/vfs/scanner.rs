@@ -12,6 +12,7 @@ use std::time::Instant;
 
 use super::graph::ShadowVFS;
 use super::node::{FileNode, VFSNodeType};
+use crate::scan::{EntryKind, ScanEntry, ScanOptions};
 
 /// Configuration for the VFS scanner
 #[derive(Debug, Clone)]
@@ -28,6 +29,9 @@ pub struct JWalkScanner {
     /// Whether to extract content previews
     extract_previews: bool,
 
+    /// Whether to include hidden files and folders
+    include_hidden: bool,
+
     /// File extensions to extract content from
     previewable_extensions: Vec<String>,
 }
@@ -39,6 +43,7 @@ impl Default for JWalkScanner {
             max_preview_size: 1024,
             num_threads: get_num_cpus().min(4),
             extract_previews: true,
+            include_hidden: false,
             previewable_extensions: vec![
                 "txt".to_string(),
                 "md".to_string(),
@@ -123,10 +128,16 @@ impl JWalkScanner {
         self
     }
 
+    /// Include hidden files and folders (skipped by default)
+    pub fn with_include_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
     /// Scan a directory and populate the VFS
     ///
-    /// Uses jwalk for parallel directory traversal, significantly
-    /// improving performance on large directories.
+    /// Walks the folder with the shared parallel scanner (`crate::scan`), so
+    /// hidden-file, symlink and depth rules match every other view.
     pub async fn scan(&self, root: &PathBuf, vfs: &mut ShadowVFS) -> Result<ScanStats, String> {
         let start = Instant::now();
         let mut stats = ScanStats {
@@ -147,51 +158,28 @@ impl JWalkScanner {
             return Err(format!("Path is not a directory: {}", root.display()));
         }
 
-        // Configure jwalk
-        let mut walker = jwalk::WalkDir::new(root)
-            .parallelism(jwalk::Parallelism::RayonNewPool(self.num_threads))
-            .skip_hidden(false)
-            .follow_links(false);
+        let snapshot = ScanOptions::new()
+            .with_max_depth(self.max_depth)
+            .with_hidden(self.include_hidden)
+            .with_num_threads(self.num_threads)
+            .scan(root)?;
 
-        if self.max_depth > 0 {
-            walker = walker.max_depth(self.max_depth);
+        for error in snapshot.errors() {
+            eprintln!("[VFS Scanner] Walk error: {}", error.message);
         }
+        stats.errors = snapshot.errors().len();
 
-        // Collect entries - jwalk handles parallelism internally
-        let entries: Vec<_> = walker.into_iter().collect();
-
-        for entry_result in entries {
-            match entry_result {
-                Ok(entry) => {
-                    let path = entry.path();
-
-                    // Skip the root itself (it's already in the VFS)
-                    if path == *root {
-                        continue;
-                    }
-
-                    match self.create_node_from_entry(&entry, &mut stats) {
-                        Ok(node) => {
-                            // Update parent's children list
-                            if let Some(parent_path) = &node.parent {
-                                if let Some(parent) = vfs.get_mut(parent_path) {
-                                    parent.add_child(node.path.clone());
-                                }
-                            }
-
-                            vfs.insert(node);
-                        }
-                        Err(e) => {
-                            eprintln!("[VFS Scanner] Error processing {}: {}", path.display(), e);
-                            stats.errors += 1;
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[VFS Scanner] Walk error: {}", e);
-                    stats.errors += 1;
+        // Entries come parents-first, so each parent is already in the VFS
+        for entry in snapshot.entries() {
+            let node = self.create_node_from_entry(entry, &mut stats);
+
+            if let Some(parent_path) = &node.parent {
+                if let Some(parent) = vfs.get_mut(parent_path) {
+                    parent.add_child(node.path.clone());
                 }
             }
+
+            vfs.insert(node);
         }
 
         // Update VFS scan time
@@ -207,26 +195,21 @@ impl JWalkScanner {
         Ok(stats)
     }
 
-    /// Create a FileNode from a jwalk entry
-    fn create_node_from_entry(
-        &self,
-        entry: &jwalk::DirEntry<((), ())>,
-        stats: &mut ScanStats,
-    ) -> Result<FileNode, String> {
-        let path = entry.path();
-        let metadata = entry
-            .metadata()
-            .map_err(|e| format!("Failed to get metadata: {}", e))?;
+    /// Create a FileNode from a snapshot entry
+    fn create_node_from_entry(&self, entry: &ScanEntry, stats: &mut ScanStats) -> FileNode {
+        let path = entry.path.clone();
 
         // Determine node type
-        let node_type = if metadata.is_symlink() {
-            VFSNodeType::Symlink
-        } else if metadata.is_dir() {
-            stats.total_dirs += 1;
-            VFSNodeType::Directory
-        } else {
-            stats.total_files += 1;
-            VFSNodeType::File
+        let node_type = match entry.kind {
+            EntryKind::Symlink => VFSNodeType::Symlink,
+            EntryKind::Directory => {
+                stats.total_dirs += 1;
+                VFSNodeType::Directory
+            }
+            EntryKind::File => {
+                stats.total_files += 1;
+                VFSNodeType::File
+            }
         };
 
         let mut node = FileNode::new(path.clone(), node_type.clone());
@@ -238,22 +221,17 @@ impl JWalkScanner {
 
         // Set size for files
         if node_type == VFSNodeType::File {
-            node.size = metadata.len();
+            node.size = entry.size;
             stats.total_size_bytes += node.size;
         }
 
         // Set timestamps
-        if let Ok(modified) = metadata.modified() {
-            if let Ok(duration) = modified.duration_since(std::time::UNIX_EPOCH) {
-                node.modified_at = Some(Utc.timestamp_opt(duration.as_secs() as i64, 0).unwrap());
-            }
-        }
-
-        if let Ok(created) = metadata.created() {
-            if let Ok(duration) = created.duration_since(std::time::UNIX_EPOCH) {
-                node.created_at = Some(Utc.timestamp_opt(duration.as_secs() as i64, 0).unwrap());
-            }
-        }
+        node.modified_at = entry
+            .modified_at
+            .and_then(|ms| Utc.timestamp_opt(ms / 1000, 0).single());
+        node.created_at = entry
+            .created_at
+            .and_then(|ms| Utc.timestamp_opt(ms / 1000, 0).single());
 
         // Set MIME type
         if let Some(ext) = &node.extension {
@@ -270,7 +248,7 @@ impl JWalkScanner {
             }
         }
 
-        Ok(node)
+        node
     }
 
     /// Get a content preview from a file
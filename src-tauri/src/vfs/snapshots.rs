@@ -0,0 +1,619 @@
+//! Persistent folder snapshots
+//!
+//! Saves the files of a scanned [`ShadowVFS`] (path, size, mtime, content hash,
+//! tags) to a local SQLite database next to the content cache, so scans
+//! survive `vfs_clear` and restarts and can be compared later.
+//!
+//! Hashing is incremental: a file whose size and mtime match the previous
+//! snapshot of the same root reuses that snapshot's hash instead of being
+//! read again. Hashes are SHA-256, the same keys the content cache uses.
+//!
+//! [`SnapshotStore::diff`] reports added, removed, modified and moved files
+//! between two snapshots; a move is a removed and an added path with the same
+//! content hash.
+
+use super::graph::ShadowVFS;
+use crate::ai::grok::ContentCache;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are stored without a hash (never detected as moved)
+pub const MAX_HASH_BYTES: u64 = 1024 * 1024 * 1024;
+
+const SCHEMA_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS snapshots (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        root TEXT NOT NULL,
+        label TEXT,
+        created_at INTEGER NOT NULL,
+        file_count INTEGER NOT NULL DEFAULT 0,
+        total_size INTEGER NOT NULL DEFAULT 0,
+        files_hashed INTEGER NOT NULL DEFAULT 0,
+        hashes_reused INTEGER NOT NULL DEFAULT 0
+    );
+
+    CREATE TABLE IF NOT EXISTS snapshot_files (
+        snapshot_id INTEGER NOT NULL REFERENCES snapshots(id) ON DELETE CASCADE,
+        path TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        modified_at INTEGER,
+        hash TEXT,
+        tags TEXT,
+        PRIMARY KEY (snapshot_id, path)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_snapshots_root ON snapshots(root, created_at);
+    CREATE INDEX IF NOT EXISTS idx_snapshot_files_hash ON snapshot_files(snapshot_id, hash);
+"#;
+
+/// A saved snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub root: String,
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub file_count: usize,
+    pub total_size: u64,
+    /// Files read and hashed when the snapshot was taken
+    pub files_hashed: usize,
+    /// Hashes carried over from the previous snapshot (unchanged files)
+    pub hashes_reused: usize,
+}
+
+/// One file in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    pub path: String,
+    pub size: u64,
+    /// Last modified timestamp (milliseconds since epoch)
+    pub modified_at: Option<i64>,
+    /// SHA-256 of the content (None if too large or unreadable)
+    pub hash: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// A file present in both snapshots whose content changed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModifiedFile {
+    pub path: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub old_modified_at: Option<i64>,
+    pub new_modified_at: Option<i64>,
+}
+
+/// A file that changed path but kept its content
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedFile {
+    pub from: String,
+    pub to: String,
+    pub size: u64,
+    pub hash: String,
+}
+
+/// Changes between two snapshots of a folder
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    pub from: SnapshotInfo,
+    pub to: SnapshotInfo,
+    pub added: Vec<SnapshotFile>,
+    pub removed: Vec<SnapshotFile>,
+    pub modified: Vec<ModifiedFile>,
+    pub moved: Vec<MovedFile>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.moved.is_empty()
+    }
+
+    /// One-line summary, e.g. "3 added, 1 removed, 2 modified, 5 moved"
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} modified, {} moved",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len(),
+            self.moved.len()
+        )
+    }
+}
+
+/// SQLite-backed snapshot store
+pub struct SnapshotStore {
+    db_path: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Default location, next to the content cache
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("com.sentinel.filemanager"))
+    }
+
+    /// Open the store in the default location
+    pub fn open_default() -> Result<Self, String> {
+        let dir = Self::default_dir().ok_or("Could not determine cache directory")?;
+        Self::open(&dir)
+    }
+
+    /// Open or create the snapshot database in `dir`
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+
+        let store = Self {
+            db_path: dir.join("snapshots.db"),
+        };
+        store
+            .conn()?
+            .execute_batch(SCHEMA_SQL)
+            .map_err(|e| format!("Failed to initialize snapshot database: {}", e))?;
+        Ok(store)
+    }
+
+    fn conn(&self) -> Result<rusqlite::Connection, String> {
+        let conn = rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open snapshot database: {}", e))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .map_err(|e| format!("Failed to open snapshot database: {}", e))?;
+        Ok(conn)
+    }
+
+    /// Save the files of a scanned VFS as a new snapshot.
+    ///
+    /// Fails if the VFS has staged operations, since it would no longer
+    /// describe what is on disk.
+    pub fn capture_vfs(&self, vfs: &ShadowVFS, label: Option<&str>) -> Result<SnapshotInfo, String> {
+        if vfs.has_staged_operations() {
+            return Err(
+                "Apply or clear staged operations before saving a snapshot".to_string(),
+            );
+        }
+
+        let files = vfs
+            .files()
+            .into_iter()
+            .map(|node| SnapshotFile {
+                path: node.path.to_string_lossy().to_string(),
+                size: node.size,
+                modified_at: node.modified_at.map(|t| t.timestamp_millis()),
+                hash: None,
+                tags: node.vector_tags.clone(),
+            })
+            .collect();
+        self.save(vfs.root(), label, files)
+    }
+
+    /// Save `files` as a new snapshot of `root`, hashing files that have no
+    /// hash yet (or reusing the previous snapshot's hash when unchanged)
+    pub fn save(
+        &self,
+        root: &Path,
+        label: Option<&str>,
+        mut files: Vec<SnapshotFile>,
+    ) -> Result<SnapshotInfo, String> {
+        let root = root_key(root);
+
+        let previous: HashMap<String, SnapshotFile> = match self.latest(Path::new(&root))? {
+            Some(info) => self
+                .files(info.id)?
+                .into_iter()
+                .map(|f| (f.path.clone(), f))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let mut files_hashed = 0;
+        let mut hashes_reused = 0;
+        for file in files.iter_mut().filter(|f| f.hash.is_none()) {
+            let unchanged = previous.get(&file.path).filter(|prev| {
+                prev.size == file.size
+                    && prev.modified_at.is_some()
+                    && prev.modified_at == file.modified_at
+            });
+            if let Some(hash) = unchanged.and_then(|prev| prev.hash.clone()) {
+                file.hash = Some(hash);
+                hashes_reused += 1;
+            } else if file.size <= MAX_HASH_BYTES {
+                match ContentCache::hash_file(Path::new(&file.path)) {
+                    Ok(hash) => {
+                        file.hash = Some(hash);
+                        files_hashed += 1;
+                    }
+                    Err(e) => eprintln!("[Snapshots] Could not hash {}: {}", file.path, e),
+                }
+            }
+        }
+
+        let created_at = Utc::now();
+        let total_size: u64 = files.iter().map(|f| f.size).sum();
+
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        tx.execute(
+            "INSERT INTO snapshots (root, label, created_at, file_count, total_size, files_hashed, hashes_reused)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                root,
+                label,
+                created_at.timestamp_millis(),
+                files.len() as i64,
+                total_size as i64,
+                files_hashed as i64,
+                hashes_reused as i64
+            ],
+        )
+        .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT OR REPLACE INTO snapshot_files (snapshot_id, path, size, modified_at, hash, tags)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+            for file in &files {
+                let tags = (!file.tags.is_empty())
+                    .then(|| serde_json::to_string(&file.tags).unwrap_or_default());
+                insert
+                    .execute(rusqlite::params![
+                        id,
+                        file.path,
+                        file.size as i64,
+                        file.modified_at,
+                        file.hash,
+                        tags
+                    ])
+                    .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+
+        eprintln!(
+            "[Snapshots] Saved snapshot {} of {} ({} files, {} hashed, {} reused)",
+            id,
+            root,
+            files.len(),
+            files_hashed,
+            hashes_reused
+        );
+
+        Ok(SnapshotInfo {
+            id,
+            root,
+            label: label.map(str::to_string),
+            created_at,
+            file_count: files.len(),
+            total_size,
+            files_hashed,
+            hashes_reused,
+        })
+    }
+
+    /// Snapshots, newest first (all roots when `root` is None)
+    pub fn list(&self, root: Option<&Path>) -> Result<Vec<SnapshotInfo>, String> {
+        let conn = self.conn()?;
+        let sql = format!(
+            "SELECT {} FROM snapshots WHERE (?1 IS NULL OR root = ?1) ORDER BY created_at DESC, id DESC",
+            INFO_COLUMNS
+        );
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to list snapshots: {}", e))?;
+        let rows = stmt
+            .query_map([root.map(root_key)], info_from_row)
+            .map_err(|e| format!("Failed to list snapshots: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to list snapshots: {}", e))
+    }
+
+    /// Look up a snapshot by id
+    pub fn get(&self, id: i64) -> Result<Option<SnapshotInfo>, String> {
+        let conn = self.conn()?;
+        let sql = format!("SELECT {} FROM snapshots WHERE id = ?", INFO_COLUMNS);
+        match conn.query_row(&sql, [id], info_from_row) {
+            Ok(info) => Ok(Some(info)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to load snapshot: {}", e)),
+        }
+    }
+
+    /// Newest snapshot of `root`
+    pub fn latest(&self, root: &Path) -> Result<Option<SnapshotInfo>, String> {
+        self.latest_before(root, Utc::now())
+    }
+
+    /// Newest snapshot of `root` taken at or before `at`
+    pub fn latest_before(
+        &self,
+        root: &Path,
+        at: DateTime<Utc>,
+    ) -> Result<Option<SnapshotInfo>, String> {
+        let conn = self.conn()?;
+        let sql = format!(
+            "SELECT {} FROM snapshots WHERE root = ? AND created_at <= ?
+             ORDER BY created_at DESC, id DESC LIMIT 1",
+            INFO_COLUMNS
+        );
+        match conn.query_row(
+            &sql,
+            rusqlite::params![root_key(root), at.timestamp_millis()],
+            info_from_row,
+        ) {
+            Ok(info) => Ok(Some(info)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to load snapshot: {}", e)),
+        }
+    }
+
+    /// Files of a snapshot, sorted by path
+    pub fn files(&self, id: i64) -> Result<Vec<SnapshotFile>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, size, modified_at, hash, tags FROM snapshot_files
+                 WHERE snapshot_id = ? ORDER BY path",
+            )
+            .map_err(|e| format!("Failed to load snapshot files: {}", e))?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let tags: Option<String> = row.get(4)?;
+                Ok(SnapshotFile {
+                    path: row.get(0)?,
+                    size: row.get::<_, i64>(1)? as u64,
+                    modified_at: row.get(2)?,
+                    hash: row.get(3)?,
+                    tags: tags
+                        .and_then(|t| serde_json::from_str(&t).ok())
+                        .unwrap_or_default(),
+                })
+            })
+            .map_err(|e| format!("Failed to load snapshot files: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to load snapshot files: {}", e))
+    }
+
+    /// Delete a snapshot. Returns false if it did not exist.
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        let deleted = self
+            .conn()?
+            .execute("DELETE FROM snapshots WHERE id = ?", [id])
+            .map_err(|e| format!("Failed to delete snapshot: {}", e))?;
+        Ok(deleted > 0)
+    }
+
+    /// Changes from snapshot `from` to snapshot `to`
+    pub fn diff(&self, from: i64, to: i64) -> Result<SnapshotDiff, String> {
+        let from_info = self
+            .get(from)?
+            .ok_or_else(|| format!("Snapshot not found: {}", from))?;
+        let to_info = self
+            .get(to)?
+            .ok_or_else(|| format!("Snapshot not found: {}", to))?;
+
+        let (added, removed, modified, moved) =
+            diff_files(self.files(from)?, self.files(to)?);
+
+        Ok(SnapshotDiff {
+            from: from_info,
+            to: to_info,
+            added,
+            removed,
+            modified,
+            moved,
+        })
+    }
+}
+
+const INFO_COLUMNS: &str =
+    "id, root, label, created_at, file_count, total_size, files_hashed, hashes_reused";
+
+fn info_from_row(row: &rusqlite::Row) -> rusqlite::Result<SnapshotInfo> {
+    let created_ms: i64 = row.get(3)?;
+    Ok(SnapshotInfo {
+        id: row.get(0)?,
+        root: row.get(1)?,
+        label: row.get(2)?,
+        created_at: Utc
+            .timestamp_millis_opt(created_ms)
+            .single()
+            .unwrap_or_default(),
+        file_count: row.get::<_, i64>(4)? as usize,
+        total_size: row.get::<_, i64>(5)? as u64,
+        files_hashed: row.get::<_, i64>(6)? as usize,
+        hashes_reused: row.get::<_, i64>(7)? as usize,
+    })
+}
+
+/// Snapshots are keyed by the canonical root path
+fn root_key(root: &Path) -> String {
+    std::fs::canonicalize(root)
+        .unwrap_or_else(|_| root.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+type FileChanges = (
+    Vec<SnapshotFile>,
+    Vec<SnapshotFile>,
+    Vec<ModifiedFile>,
+    Vec<MovedFile>,
+);
+
+/// Compare two file lists: (added, removed, modified, moved)
+fn diff_files(old: Vec<SnapshotFile>, new: Vec<SnapshotFile>) -> FileChanges {
+    let mut old: HashMap<String, SnapshotFile> =
+        old.into_iter().map(|f| (f.path.clone(), f)).collect();
+
+    let mut added = Vec::new();
+    let mut modified = Vec::new();
+    for file in new {
+        let Some(before) = old.remove(&file.path) else {
+            added.push(file);
+            continue;
+        };
+        let changed = match (&before.hash, &file.hash) {
+            (Some(a), Some(b)) => a != b,
+            _ => before.size != file.size || before.modified_at != file.modified_at,
+        };
+        if changed {
+            modified.push(ModifiedFile {
+                path: file.path,
+                old_size: before.size,
+                new_size: file.size,
+                old_modified_at: before.modified_at,
+                new_modified_at: file.modified_at,
+            });
+        }
+    }
+
+    // Pair removed and added files with the same content; prefer keeping the
+    // file name so duplicates are matched sensibly
+    let mut removed_by_hash: HashMap<String, Vec<SnapshotFile>> = HashMap::new();
+    let mut removed = Vec::new();
+    for file in old.into_values() {
+        match file.hash.clone() {
+            Some(hash) => removed_by_hash.entry(hash).or_default().push(file),
+            None => removed.push(file),
+        }
+    }
+
+    let mut moved = Vec::new();
+    added.sort_by(|a, b| a.path.cmp(&b.path));
+    added.retain(|file| {
+        let Some(candidates) = file.hash.as_ref().and_then(|h| removed_by_hash.get_mut(h)) else {
+            return true;
+        };
+        if candidates.is_empty() {
+            return true;
+        }
+        let name = Path::new(&file.path).file_name();
+        let pick = candidates
+            .iter()
+            .position(|c| Path::new(&c.path).file_name() == name)
+            .unwrap_or(0);
+        let source = candidates.remove(pick);
+        moved.push(MovedFile {
+            from: source.path,
+            to: file.path.clone(),
+            size: file.size,
+            hash: source.hash.unwrap_or_default(),
+        });
+        false
+    });
+
+    removed.extend(removed_by_hash.into_values().flatten());
+    removed.sort_by(|a, b| a.path.cmp(&b.path));
+    modified.sort_by(|a, b| a.path.cmp(&b.path));
+    moved.sort_by(|a, b| a.to.cmp(&b.to));
+
+    (added, removed, modified, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn file(path: &str, size: u64, hash: Option<&str>) -> SnapshotFile {
+        SnapshotFile {
+            path: path.to_string(),
+            size,
+            modified_at: Some(1_700_000_000_000),
+            hash: hash.map(str::to_string),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let old = vec![
+            file("/r/a.txt", 1, Some("h1")),
+            file("/r/b.txt", 2, Some("h2")),
+            file("/r/c.txt", 3, Some("h3")),
+            file("/r/big.iso", 9, None),
+        ];
+        let new = vec![
+            file("/r/a.txt", 1, Some("h1")),
+            file("/r/b.txt", 5, Some("h2b")),
+            file("/r/Docs/c.txt", 3, Some("h3")),
+            file("/r/d.txt", 4, Some("h4")),
+        ];
+
+        let (added, removed, modified, moved) = diff_files(old, new);
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].path, "/r/d.txt");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].path, "/r/big.iso");
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].path, "/r/b.txt");
+        assert_eq!(moved.len(), 1);
+        assert_eq!((moved[0].from.as_str(), moved[0].to.as_str()), ("/r/c.txt", "/r/Docs/c.txt"));
+    }
+
+    #[test]
+    fn test_save_reuses_hashes_and_diffs() {
+        let db = TempDir::new().unwrap();
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "alpha").unwrap();
+        fs::write(root.join("b.txt"), "beta").unwrap();
+
+        let store = SnapshotStore::open(db.path()).unwrap();
+        let listing = |paths: &[&str]| -> Vec<SnapshotFile> {
+            paths
+                .iter()
+                .map(|p| {
+                    let path = root.join(p);
+                    let meta = fs::metadata(&path).unwrap();
+                    SnapshotFile {
+                        path: path.to_string_lossy().to_string(),
+                        size: meta.len(),
+                        modified_at: Some(1_700_000_000_000),
+                        hash: None,
+                        tags: vec!["notes".to_string()],
+                    }
+                })
+                .collect()
+        };
+
+        let first = store.save(root, Some("before"), listing(&["a.txt", "b.txt"])).unwrap();
+        assert_eq!(first.files_hashed, 2);
+
+        fs::create_dir(root.join("archive")).unwrap();
+        fs::rename(root.join("b.txt"), root.join("archive/b.txt")).unwrap();
+        let second = store.save(root, None, listing(&["a.txt", "archive/b.txt"])).unwrap();
+        assert_eq!(second.hashes_reused, 1);
+        assert_eq!(second.files_hashed, 1);
+
+        let diff = store.diff(first.id, second.id).unwrap();
+        assert_eq!(diff.summary(), "0 added, 0 removed, 0 modified, 1 moved");
+        assert!(diff.moved[0].to.ends_with("archive/b.txt"));
+
+        let listed = store.list(Some(root)).unwrap();
+        assert_eq!(listed.iter().map(|s| s.id).collect::<Vec<_>>(), vec![second.id, first.id]);
+        assert_eq!(store.files(first.id).unwrap()[0].tags, vec!["notes".to_string()]);
+        assert_eq!(store.latest(root).unwrap().unwrap().id, second.id);
+        let before_first = first.created_at - chrono::Duration::milliseconds(1);
+        assert!(store.latest_before(root, before_first).unwrap().is_none());
+
+        assert!(store.delete(first.id).unwrap());
+        assert!(store.files(first.id).unwrap().is_empty());
+        assert!(store.diff(first.id, second.id).is_err());
+    }
+}
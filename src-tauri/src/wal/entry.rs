@@ -5,7 +5,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// Status of a WAL entry
@@ -55,6 +55,57 @@ pub enum WALOperationType {
     },
     /// Delete a folder (only empty folders, used for cleanup)
     DeleteFolder { path: PathBuf },
+    /// Remove a copy made by `Copy` (the undo of a copy)
+    RemoveCopy { source: PathBuf, path: PathBuf },
+    /// Delete a file or folder by moving it to the quarantine area of its
+    /// volume (so it stays restorable until the quarantine expires it)
+    DeleteFile {
+        path: PathBuf,
+        quarantine_path: PathBuf,
+    },
+    /// Bring a deleted item back from quarantine (the undo of `DeleteFile`)
+    RestoreFile {
+        quarantine_path: PathBuf,
+        path: PathBuf,
+    },
+    /// Create a symbolic link at `path` pointing to `target`
+    CreateSymlink { path: PathBuf, target: PathBuf },
+    /// Create a Finder alias at `path` (a symlink on other platforms)
+    CreateAlias { path: PathBuf, target: PathBuf },
+    /// Remove a link created by `CreateSymlink` or `CreateAlias`
+    RemoveLink {
+        path: PathBuf,
+        target: PathBuf,
+        alias: bool,
+    },
+    /// Set modification and/or access times (`None` leaves a time alone).
+    /// `previous_*` hold the replaced times, captured when journaled.
+    SetTimestamps {
+        path: PathBuf,
+        modified: Option<DateTime<Utc>>,
+        accessed: Option<DateTime<Utc>>,
+        previous_modified: Option<DateTime<Utc>>,
+        previous_accessed: Option<DateTime<Utc>>,
+    },
+    /// Set permission bits (Unix mode; only the read-only bit elsewhere)
+    SetPermissions {
+        path: PathBuf,
+        mode: u32,
+        previous_mode: u32,
+    },
+    /// Replace the user tags (Finder tags on macOS, `user.xdg.tags` elsewhere)
+    SetTags {
+        path: PathBuf,
+        tags: Vec<String>,
+        previous_tags: Vec<String>,
+    },
+    /// Set an extended attribute (`None` removes it)
+    SetXattr {
+        path: PathBuf,
+        name: String,
+        value: Option<Vec<u8>>,
+        previous_value: Option<Vec<u8>>,
+    },
 }
 
 impl WALOperationType {
@@ -110,13 +161,12 @@ impl WALOperationType {
                 })
             }
             WALOperationType::Copy {
-                source: _,
+                source,
                 destination,
             } => {
-                // Inverse of copy is delete the destination
-                // Note: We use DeleteFolder for directories, for files
-                // the executor should handle appropriately
-                Ok(WALOperationType::DeleteFolder {
+                // Inverse of copy is removing the copy (file or folder)
+                Ok(WALOperationType::RemoveCopy {
+                    source: source.clone(),
                     path: destination.clone(),
                 })
             }
@@ -125,6 +175,124 @@ impl WALOperationType {
                 // Return a no-op equivalent (create same folder)
                 Ok(WALOperationType::CreateFolder { path: path.clone() })
             }
+            WALOperationType::RemoveCopy { source, path } => Ok(WALOperationType::Copy {
+                source: source.clone(),
+                destination: path.clone(),
+            }),
+            WALOperationType::DeleteFile {
+                path,
+                quarantine_path,
+            } => Ok(WALOperationType::RestoreFile {
+                quarantine_path: quarantine_path.clone(),
+                path: path.clone(),
+            }),
+            WALOperationType::RestoreFile {
+                quarantine_path,
+                path,
+            } => Ok(WALOperationType::DeleteFile {
+                path: path.clone(),
+                quarantine_path: quarantine_path.clone(),
+            }),
+            WALOperationType::CreateSymlink { path, target } => Ok(WALOperationType::RemoveLink {
+                path: path.clone(),
+                target: target.clone(),
+                alias: false,
+            }),
+            WALOperationType::CreateAlias { path, target } => Ok(WALOperationType::RemoveLink {
+                path: path.clone(),
+                target: target.clone(),
+                alias: true,
+            }),
+            WALOperationType::RemoveLink {
+                path,
+                target,
+                alias,
+            } => {
+                let (path, target) = (path.clone(), target.clone());
+                Ok(if *alias {
+                    WALOperationType::CreateAlias { path, target }
+                } else {
+                    WALOperationType::CreateSymlink { path, target }
+                })
+            }
+            WALOperationType::SetTimestamps {
+                path,
+                modified,
+                accessed,
+                previous_modified,
+                previous_accessed,
+            } => {
+                // Only restore the times that were changed
+                Ok(WALOperationType::SetTimestamps {
+                    path: path.clone(),
+                    modified: modified.and(*previous_modified),
+                    accessed: accessed.and(*previous_accessed),
+                    previous_modified: *modified,
+                    previous_accessed: *accessed,
+                })
+            }
+            WALOperationType::SetPermissions {
+                path,
+                mode,
+                previous_mode,
+            } => Ok(WALOperationType::SetPermissions {
+                path: path.clone(),
+                mode: *previous_mode,
+                previous_mode: *mode,
+            }),
+            WALOperationType::SetTags {
+                path,
+                tags,
+                previous_tags,
+            } => Ok(WALOperationType::SetTags {
+                path: path.clone(),
+                tags: previous_tags.clone(),
+                previous_tags: tags.clone(),
+            }),
+            WALOperationType::SetXattr {
+                path,
+                name,
+                value,
+                previous_value,
+            } => Ok(WALOperationType::SetXattr {
+                path: path.clone(),
+                name: name.clone(),
+                value: previous_value.clone(),
+                previous_value: value.clone(),
+            }),
+        }
+    }
+
+    /// Paths this operation changes or relies on, used to order journal
+    /// entries that touch the same files
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            WALOperationType::CreateFolder { path }
+            | WALOperationType::DeleteFolder { path }
+            | WALOperationType::Quarantine { path, .. }
+            | WALOperationType::DeleteFile { path, .. }
+            | WALOperationType::RestoreFile { path, .. }
+            | WALOperationType::SetTimestamps { path, .. }
+            | WALOperationType::SetPermissions { path, .. }
+            | WALOperationType::SetTags { path, .. }
+            | WALOperationType::SetXattr { path, .. } => vec![path.clone()],
+            WALOperationType::Rename { path, new_name } => {
+                vec![path.clone(), path.with_file_name(new_name)]
+            }
+            WALOperationType::Move {
+                source,
+                destination,
+            }
+            | WALOperationType::Copy {
+                source,
+                destination,
+            } => vec![source.clone(), destination.clone()],
+            WALOperationType::RemoveCopy { source, path } => vec![path.clone(), source.clone()],
+            WALOperationType::CreateSymlink { path, target }
+            | WALOperationType::CreateAlias { path, target }
+            | WALOperationType::RemoveLink { path, target, .. } => {
+                vec![path.clone(), target.clone()]
+            }
         }
     }
 
@@ -171,6 +339,63 @@ impl WALOperationType {
             WALOperationType::DeleteFolder { path } => {
                 format!("Delete folder: {}", path.display())
             }
+            WALOperationType::RemoveCopy { path, .. } => {
+                format!("Remove copy: {}", path.display())
+            }
+            WALOperationType::DeleteFile { path, .. } => {
+                format!("Delete (to quarantine): {}", path.display())
+            }
+            WALOperationType::RestoreFile {
+                quarantine_path,
+                path,
+            } => {
+                format!(
+                    "Restore {} -> {}",
+                    quarantine_path.display(),
+                    path.display()
+                )
+            }
+            WALOperationType::CreateSymlink { path, target } => {
+                format!("Symlink {} -> {}", path.display(), target.display())
+            }
+            WALOperationType::CreateAlias { path, target } => {
+                format!("Alias {} -> {}", path.display(), target.display())
+            }
+            WALOperationType::RemoveLink { path, alias, .. } => {
+                let kind = if *alias { "alias" } else { "symlink" };
+                format!("Remove {}: {}", kind, path.display())
+            }
+            WALOperationType::SetTimestamps {
+                path,
+                modified,
+                accessed,
+                ..
+            } => {
+                let mut times = Vec::new();
+                if let Some(t) = modified {
+                    times.push(format!("modified {}", t.format("%Y-%m-%d %H:%M")));
+                }
+                if let Some(t) = accessed {
+                    times.push(format!("accessed {}", t.format("%Y-%m-%d %H:%M")));
+                }
+                format!("Set times of {}: {}", path.display(), times.join(", "))
+            }
+            WALOperationType::SetPermissions { path, mode, .. } => {
+                format!("Set permissions of {} to {:o}", path.display(), mode)
+            }
+            WALOperationType::SetTags { path, tags, .. } => {
+                if tags.is_empty() {
+                    format!("Clear tags of {}", path.display())
+                } else {
+                    format!("Tag {}: {}", path.display(), tags.join(", "))
+                }
+            }
+            WALOperationType::SetXattr {
+                path, name, value, ..
+            } => match value {
+                Some(_) => format!("Set attribute {} on {}", name, path.display()),
+                None => format!("Remove attribute {} from {}", name, path.display()),
+            },
         }
     }
 }
@@ -284,6 +509,9 @@ pub struct WALJournal {
     pub job_id: String,
     /// The target folder being organized
     pub target_folder: PathBuf,
+    /// Other folders the job moves files out of, when it spans several roots
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_roots: Vec<PathBuf>,
     /// When this journal was started
     pub started_at: DateTime<Utc>,
     /// All entries in this journal
@@ -301,12 +529,20 @@ impl WALJournal {
         Self {
             job_id,
             target_folder,
+            source_roots: Vec::new(),
             started_at: Utc::now(),
             entries: Vec::new(),
             version: Self::CURRENT_VERSION,
         }
     }
 
+    /// Every folder the journal works in, the target first
+    pub fn roots(&self) -> Vec<&Path> {
+        std::iter::once(self.target_folder.as_path())
+            .chain(self.source_roots.iter().map(PathBuf::as_path))
+            .collect()
+    }
+
     /// Add an entry to this journal
     pub fn add_entry(&mut self, entry: WALEntry) {
         self.entries.push(entry);
@@ -443,6 +679,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_copy_inverse_removes_copy() {
+        let op = WALOperationType::Copy {
+            source: PathBuf::from("/src/a.txt"),
+            destination: PathBuf::from("/dst/a.txt"),
+        };
+        assert_eq!(
+            op.inverse().unwrap(),
+            WALOperationType::RemoveCopy {
+                source: PathBuf::from("/src/a.txt"),
+                path: PathBuf::from("/dst/a.txt"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_operations_round_trip() {
+        let path = PathBuf::from("/test/report.pdf");
+        let t = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+        let ops = vec![
+            WALOperationType::DeleteFile {
+                path: path.clone(),
+                quarantine_path: PathBuf::from("/q/20240101_000000_000_report.pdf"),
+            },
+            WALOperationType::CreateSymlink {
+                path: PathBuf::from("/test/link.pdf"),
+                target: path.clone(),
+            },
+            WALOperationType::CreateAlias {
+                path: PathBuf::from("/test/alias.pdf"),
+                target: path.clone(),
+            },
+            WALOperationType::SetTimestamps {
+                path: path.clone(),
+                modified: t("2020-01-01T00:00:00Z"),
+                accessed: None,
+                previous_modified: t("2024-05-01T10:00:00Z"),
+                previous_accessed: None,
+            },
+            WALOperationType::SetPermissions {
+                path: path.clone(),
+                mode: 0o600,
+                previous_mode: 0o644,
+            },
+            WALOperationType::SetTags {
+                path: path.clone(),
+                tags: vec!["Work".to_string()],
+                previous_tags: vec![],
+            },
+            WALOperationType::SetXattr {
+                path: path.clone(),
+                name: "user.origin".to_string(),
+                value: Some(b"scanner".to_vec()),
+                previous_value: None,
+            },
+        ];
+
+        for op in ops {
+            let inverse = op.inverse().unwrap();
+            assert_ne!(inverse, op);
+            assert_eq!(inverse.inverse().unwrap(), op);
+        }
+    }
+
+    #[test]
+    fn test_set_timestamps_inverse_restores_changed_times_only() {
+        let t = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+        let op = WALOperationType::SetTimestamps {
+            path: PathBuf::from("/test/a.txt"),
+            modified: t("2020-01-01T00:00:00Z"),
+            accessed: None,
+            previous_modified: t("2024-05-01T10:00:00Z"),
+            previous_accessed: t("2024-05-02T10:00:00Z"),
+        };
+        let WALOperationType::SetTimestamps {
+            modified, accessed, ..
+        } = op.inverse().unwrap()
+        else {
+            panic!("Expected SetTimestamps inverse");
+        };
+        assert_eq!(modified, t("2024-05-01T10:00:00Z"));
+        assert_eq!(accessed, None);
+    }
+
     #[test]
     fn test_rename_inverse_error_no_filename() {
         // Root path has no filename
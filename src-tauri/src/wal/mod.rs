@@ -8,6 +8,7 @@
 //! - `entry` - WAL entry types and journal structure
 //! - `io` - Safe I/O utilities (atomic writes, fsync, symlink detection)
 //! - `journal` - Journal persistence with file locking
+//! - `ops` - Delete (via quarantine), link and metadata operations
 //! - `recovery` - Recovery operations for interrupted jobs
 
 #![allow(dead_code)]
@@ -16,6 +17,7 @@
 pub mod entry;
 pub mod io;
 pub mod journal;
+pub mod ops;
 pub mod recovery;
 
 pub use entry::*;
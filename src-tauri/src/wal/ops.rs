@@ -0,0 +1,651 @@
+//! Delete, link and metadata operations
+//!
+//! Filesystem effects of `DeleteFile`/`RestoreFile`, `RemoveCopy`, the link
+//! operations and the metadata operations, shared by the DAG executor and
+//! recovery. Also reads the current metadata that the `Set*` operations
+//! record as their `previous_*` values when a plan is journaled.
+//!
+//! All operations are idempotent where they can be (a resumed journal may
+//! replay an operation that already ran) and refuse to follow symlinks.
+
+use super::entry::WALOperationType;
+use super::io::is_symlink;
+use crate::quarantine::QuarantineManager;
+use crate::security::PathValidator;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+
+/// Extended attribute holding Finder tags (a binary plist of strings)
+#[cfg(target_os = "macos")]
+const TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// Extended attribute holding tags on other Unix systems (comma separated)
+#[cfg(all(unix, not(target_os = "macos")))]
+const TAGS_XATTR: &str = "user.xdg.tags";
+
+/// Whether `operation` is executed here rather than by the move/copy paths
+pub fn is_extended(operation: &WALOperationType) -> bool {
+    matches!(
+        operation,
+        WALOperationType::RemoveCopy { .. }
+            | WALOperationType::DeleteFile { .. }
+            | WALOperationType::RestoreFile { .. }
+            | WALOperationType::CreateSymlink { .. }
+            | WALOperationType::CreateAlias { .. }
+            | WALOperationType::RemoveLink { .. }
+            | WALOperationType::SetTimestamps { .. }
+            | WALOperationType::SetPermissions { .. }
+            | WALOperationType::SetTags { .. }
+            | WALOperationType::SetXattr { .. }
+    )
+}
+
+/// Execute an extended operation, using the default quarantine for deletes
+pub fn execute(operation: &WALOperationType) -> Result<(), String> {
+    match operation {
+        WALOperationType::DeleteFile { .. } | WALOperationType::RestoreFile { .. } => {
+            execute_with(operation, &QuarantineManager::new()?)
+        }
+        _ => execute_local(operation),
+    }
+}
+
+/// Execute an extended operation against a specific quarantine
+pub fn execute_with(
+    operation: &WALOperationType,
+    quarantine: &QuarantineManager,
+) -> Result<(), String> {
+    match operation {
+        WALOperationType::DeleteFile {
+            path,
+            quarantine_path,
+        } => delete_file(quarantine, path, quarantine_path),
+        WALOperationType::RestoreFile {
+            quarantine_path,
+            path,
+        } => restore_file(quarantine, quarantine_path, path),
+        _ => execute_local(operation),
+    }
+}
+
+/// Operations that don't involve the quarantine
+fn execute_local(operation: &WALOperationType) -> Result<(), String> {
+    match operation {
+        WALOperationType::RemoveCopy { path, .. } => remove_copy(path),
+        WALOperationType::CreateSymlink { path, target } => create_symlink(path, target),
+        WALOperationType::CreateAlias { path, target } => create_alias(path, target),
+        WALOperationType::RemoveLink { path, alias, .. } => remove_link(path, *alias),
+        WALOperationType::SetTimestamps {
+            path,
+            modified,
+            accessed,
+            ..
+        } => set_timestamps(path, *modified, *accessed),
+        WALOperationType::SetPermissions { path, mode, .. } => set_permissions(path, *mode),
+        WALOperationType::SetTags { path, tags, .. } => write_tags(path, tags),
+        WALOperationType::SetXattr {
+            path, name, value, ..
+        } => set_xattr(path, name, value.as_deref()),
+        other => Err(format!(
+            "Not an extended operation: {}",
+            other.description()
+        )),
+    }
+}
+
+/// True if anything (including a broken symlink) exists at `path`
+fn occupied(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn ensure_parent(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) if !parent.exists() => fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create destination directory: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+fn ensure_plain(path: &Path, operation: &str) -> Result<(), String> {
+    if !occupied(path) {
+        return Err(format!("Path not found: {}", path.display()));
+    }
+    if is_symlink(path) {
+        return Err(format!(
+            "Refusing to {} symlink: {}",
+            operation,
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn remove_copy(path: &Path) -> Result<(), String> {
+    if !occupied(path) {
+        return Ok(());
+    }
+    if is_symlink(path) {
+        return Err(format!("Refusing to remove symlink: {}", path.display()));
+    }
+    if PathValidator::is_protected_path(path) {
+        return Err(format!("Cannot delete protected path: {}", path.display()));
+    }
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .map_err(|e| format!("Failed to remove copy {}: {}", path.display(), e))
+}
+
+fn delete_file(
+    quarantine: &QuarantineManager,
+    path: &Path,
+    quarantine_path: &Path,
+) -> Result<(), String> {
+    if !occupied(path) && occupied(quarantine_path) {
+        // Already quarantined by an earlier attempt
+        return Ok(());
+    }
+    ensure_plain(path, "delete")?;
+    if PathValidator::is_protected_path(path) {
+        return Err(format!("Cannot delete protected path: {}", path.display()));
+    }
+    quarantine.quarantine_to(path, quarantine_path)
+}
+
+fn restore_file(
+    quarantine: &QuarantineManager,
+    quarantine_path: &Path,
+    path: &Path,
+) -> Result<(), String> {
+    if occupied(path) && !occupied(quarantine_path) {
+        // Already restored
+        return Ok(());
+    }
+    quarantine.restore(&quarantine_path.to_path_buf(), Some(path.to_path_buf()))
+}
+
+fn create_symlink(path: &Path, target: &Path) -> Result<(), String> {
+    if occupied(path) {
+        if fs::read_link(path).ok().as_deref() == Some(target) {
+            return Ok(());
+        }
+        return Err(format!("Link path already exists: {}", path.display()));
+    }
+    ensure_parent(path)?;
+
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(target, path);
+    #[cfg(windows)]
+    let result = if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, path)
+    } else {
+        std::os::windows::fs::symlink_file(target, path)
+    };
+
+    result.map_err(|e| {
+        format!(
+            "Failed to create symlink {} -> {}: {}",
+            path.display(),
+            target.display(),
+            e
+        )
+    })
+}
+
+/// Finder alias via AppleScript (aliases keep working when the target moves)
+#[cfg(target_os = "macos")]
+fn create_alias(path: &Path, target: &Path) -> Result<(), String> {
+    if occupied(path) {
+        return Err(format!("Alias path already exists: {}", path.display()));
+    }
+    if !target.exists() {
+        return Err(format!("Alias target not found: {}", target.display()));
+    }
+    ensure_parent(path)?;
+
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("Cannot determine parent of {}", path.display()))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Alias path has no name: {}", path.display()))?;
+
+    const SCRIPT: &str = r#"on run argv
+    tell application "Finder" to make new alias file at (POSIX file (item 1 of argv) as alias) to (POSIX file (item 2 of argv) as alias) with properties {name:(item 3 of argv)}
+end run"#;
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(SCRIPT)
+        .arg(parent)
+        .arg(target)
+        .arg(name)
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to create alias {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Aliases are a Finder feature; elsewhere they are plain symlinks
+#[cfg(not(target_os = "macos"))]
+fn create_alias(path: &Path, target: &Path) -> Result<(), String> {
+    create_symlink(path, target)
+}
+
+fn remove_link(path: &Path, alias: bool) -> Result<(), String> {
+    if !occupied(path) {
+        return Ok(());
+    }
+    // Finder aliases are regular files, so they are told apart by their alias
+    // flag or bookmark data; a real file that replaced the link is never removed
+    let is_link = is_symlink(path) || (alias && is_finder_alias(path));
+    if !is_link {
+        return Err(format!("Refusing to remove {}: not a link", path.display()));
+    }
+    fs::remove_file(path).map_err(|e| format!("Failed to remove link {}: {}", path.display(), e))
+}
+
+/// Whether `path` is a Finder alias file: the alias flag is set in its Finder
+/// info, or its data fork holds a bookmark (`book....mark` header)
+#[cfg(target_os = "macos")]
+fn is_finder_alias(path: &Path) -> bool {
+    /// `kIsAlias` in the big-endian Finder flags at offset 8 of FinderInfo
+    const IS_ALIAS: u16 = 0x8000;
+
+    if !path.is_file() {
+        return false;
+    }
+    if let Ok(Some(info)) = xattr::get(path, "com.apple.FinderInfo") {
+        if info.len() >= 10 && u16::from_be_bytes([info[8], info[9]]) & IS_ALIAS != 0 {
+            return true;
+        }
+    }
+    let mut header = [0u8; 16];
+    match fs::File::open(path).and_then(|mut file| {
+        use std::io::Read;
+        file.read_exact(&mut header)
+    }) {
+        Ok(()) => &header[0..4] == b"book" && &header[8..12] == b"mark",
+        Err(_) => false,
+    }
+}
+
+/// Aliases are plain symlinks off macOS
+#[cfg(not(target_os = "macos"))]
+fn is_finder_alias(_path: &Path) -> bool {
+    false
+}
+
+fn set_timestamps(
+    path: &Path,
+    modified: Option<DateTime<Utc>>,
+    accessed: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    ensure_plain(path, "change times of")?;
+    let to_filetime = |t: DateTime<Utc>| {
+        filetime::FileTime::from_unix_time(t.timestamp(), t.timestamp_subsec_nanos())
+    };
+
+    if let Some(t) = modified {
+        filetime::set_file_mtime(path, to_filetime(t))
+            .map_err(|e| format!("Failed to set modified time of {}: {}", path.display(), e))?;
+    }
+    if let Some(t) = accessed {
+        filetime::set_file_atime(path, to_filetime(t))
+            .map_err(|e| format!("Failed to set access time of {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn set_permissions(path: &Path, mode: u32) -> Result<(), String> {
+    ensure_plain(path, "change permissions of")?;
+    if PathValidator::is_protected_path(path) {
+        return Err(format!(
+            "Cannot change permissions of protected path: {}",
+            path.display()
+        ));
+    }
+
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        fs::Permissions::from_mode(mode & 0o7777)
+    };
+    #[cfg(not(unix))]
+    let permissions = {
+        let mut permissions = fs::metadata(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .permissions();
+        permissions.set_readonly(mode & 0o222 == 0);
+        permissions
+    };
+
+    fs::set_permissions(path, permissions)
+        .map_err(|e| format!("Failed to set permissions of {}: {}", path.display(), e))
+}
+
+#[cfg(unix)]
+fn set_xattr(path: &Path, name: &str, value: Option<&[u8]>) -> Result<(), String> {
+    ensure_plain(path, "change attributes of")?;
+    match value {
+        Some(value) => xattr::set(path, name, value),
+        None => match xattr::get(path, name) {
+            Ok(Some(_)) => xattr::remove(path, name),
+            _ => Ok(()),
+        },
+    }
+    .map_err(|e| {
+        format!(
+            "Failed to set attribute {} on {}: {}",
+            name,
+            path.display(),
+            e
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn set_xattr(path: &Path, name: &str, _value: Option<&[u8]>) -> Result<(), String> {
+    Err(format!(
+        "Extended attributes are not supported on this platform ({} on {})",
+        name,
+        path.display()
+    ))
+}
+
+// ============================================================================
+// Current state (recorded as the previous value when journaling)
+// ============================================================================
+
+/// Current modified and accessed times of `path`
+pub fn file_times(path: &Path) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let time = |t: std::io::Result<std::time::SystemTime>| {
+        t.map(DateTime::<Utc>::from)
+            .map_err(|e| format!("Failed to read times of {}: {}", path.display(), e))
+    };
+    Ok((time(metadata.modified())?, time(metadata.accessed())?))
+}
+
+/// Current permission bits of `path` (0o444/0o644 outside Unix)
+pub fn file_mode(path: &Path) -> Result<u32, String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(metadata.permissions().mode() & 0o7777)
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        })
+    }
+}
+
+/// Current value of an extended attribute (`None` when it is not set)
+#[cfg(unix)]
+pub fn read_xattr(path: &Path, name: &str) -> Result<Option<Vec<u8>>, String> {
+    xattr::get(path, name).map_err(|e| {
+        format!(
+            "Failed to read attribute {} of {}: {}",
+            name,
+            path.display(),
+            e
+        )
+    })
+}
+
+#[cfg(not(unix))]
+pub fn read_xattr(path: &Path, name: &str) -> Result<Option<Vec<u8>>, String> {
+    set_xattr(path, name, None).map(|_| None)
+}
+
+/// Current tags of `path`. Finder tags keep their colour suffix
+/// (`"Red\n6"`) so writing them back restores them exactly.
+#[cfg(unix)]
+pub fn read_tags(path: &Path) -> Result<Vec<String>, String> {
+    match read_xattr(path, TAGS_XATTR)? {
+        Some(bytes) => decode_tags(&bytes),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn read_tags(path: &Path) -> Result<Vec<String>, String> {
+    Err(format!(
+        "Tags are not supported on this platform ({})",
+        path.display()
+    ))
+}
+
+#[cfg(unix)]
+fn write_tags(path: &Path, tags: &[String]) -> Result<(), String> {
+    if tags.is_empty() {
+        return set_xattr(path, TAGS_XATTR, None);
+    }
+    set_xattr(path, TAGS_XATTR, Some(&encode_tags(tags)?))
+}
+
+#[cfg(not(unix))]
+fn write_tags(path: &Path, _tags: &[String]) -> Result<(), String> {
+    read_tags(path).map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn encode_tags(tags: &[String]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    plist::to_writer_binary(&mut bytes, &tags)
+        .map_err(|e| format!("Failed to encode tags: {}", e))?;
+    Ok(bytes)
+}
+
+#[cfg(target_os = "macos")]
+fn decode_tags(bytes: &[u8]) -> Result<Vec<String>, String> {
+    plist::from_bytes(bytes).map_err(|e| format!("Failed to decode tags: {}", e))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn encode_tags(tags: &[String]) -> Result<Vec<u8>, String> {
+    if let Some(tag) = tags.iter().find(|t| t.contains(',')) {
+        return Err(format!("Tag names cannot contain commas: {}", tag));
+    }
+    Ok(tags.join(",").into_bytes())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn decode_tags(bytes: &[u8]) -> Result<Vec<String>, String> {
+    Ok(String::from_utf8_lossy(bytes)
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Run `op`, check `done`, run its inverse, check `undone`
+    fn round_trip(
+        op: WALOperationType,
+        quarantine: &QuarantineManager,
+        done: impl Fn(),
+        undone: impl Fn(),
+    ) {
+        execute_with(&op, quarantine).unwrap();
+        done();
+        execute_with(&op.inverse().unwrap(), quarantine).unwrap();
+        undone();
+    }
+
+    #[test]
+    fn test_delete_file_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let quarantine = QuarantineManager::with_config(dir.path().join("quarantine"), 30);
+        let file = dir.path().join("old.log");
+        fs::write(&file, b"log").unwrap();
+
+        let quarantine_path = quarantine
+            .reserve_path(&file, &mut Default::default())
+            .unwrap();
+        let op = WALOperationType::DeleteFile {
+            path: file.clone(),
+            quarantine_path: quarantine_path.clone(),
+        };
+        round_trip(
+            op.clone(),
+            &quarantine,
+            || {
+                assert!(!file.exists() && quarantine_path.exists());
+                assert!(quarantine.is_quarantined(&file));
+            },
+            || {
+                assert_eq!(fs::read(&file).unwrap(), b"log");
+                assert!(!quarantine.is_quarantined(&file));
+            },
+        );
+
+        // Replaying a completed delete is a no-op
+        execute_with(&op, &quarantine).unwrap();
+        execute_with(&op, &quarantine).unwrap();
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_link_and_copy_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let quarantine = QuarantineManager::with_config(dir.path().join("quarantine"), 30);
+        let target = dir.path().join("Docs/report.pdf");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(&target, b"pdf").unwrap();
+        let link = dir.path().join("report.pdf");
+
+        for op in [
+            WALOperationType::CreateSymlink {
+                path: link.clone(),
+                target: target.clone(),
+            },
+            WALOperationType::CreateAlias {
+                path: link.clone(),
+                target: target.clone(),
+            },
+        ] {
+            round_trip(
+                op,
+                &quarantine,
+                || assert_eq!(fs::read(&link).unwrap(), b"pdf"),
+                || assert!(!occupied(&link) && target.exists()),
+            );
+        }
+
+        // A real file at the link path is never removed as a link
+        fs::write(&link, b"not a link").unwrap();
+        let remove = WALOperationType::RemoveLink {
+            path: link.clone(),
+            target: target.clone(),
+            alias: false,
+        };
+        assert!(execute_with(&remove, &quarantine).is_err());
+        assert!(link.exists());
+
+        // ...nor as an alias unless it really is a Finder alias
+        let remove = WALOperationType::RemoveLink {
+            path: link.clone(),
+            target: target.clone(),
+            alias: true,
+        };
+        assert!(execute_with(&remove, &quarantine).is_err());
+        assert!(link.exists());
+
+        // Undoing a copy of a file removes the file
+        let copy = dir.path().join("copy.pdf");
+        fs::copy(&target, &copy).unwrap();
+        let undo = WALOperationType::Copy {
+            source: target.clone(),
+            destination: copy.clone(),
+        }
+        .inverse()
+        .unwrap();
+        execute(&undo).unwrap();
+        assert!(!copy.exists() && target.exists());
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let quarantine = QuarantineManager::with_config(dir.path().join("quarantine"), 30);
+        let file = dir.path().join("scan.pdf");
+        fs::write(&file, b"scan").unwrap();
+
+        let (modified, _) = file_times(&file).unwrap();
+        let backdated: DateTime<Utc> = "2020-03-01T12:00:00Z".parse().unwrap();
+        round_trip(
+            WALOperationType::SetTimestamps {
+                path: file.clone(),
+                modified: Some(backdated),
+                accessed: None,
+                previous_modified: Some(modified),
+                previous_accessed: None,
+            },
+            &quarantine,
+            || assert_eq!(file_times(&file).unwrap().0, backdated),
+            || assert_eq!(file_times(&file).unwrap().0, modified),
+        );
+
+        let mode = file_mode(&file).unwrap();
+        round_trip(
+            WALOperationType::SetPermissions {
+                path: file.clone(),
+                mode: 0o444,
+                previous_mode: mode,
+            },
+            &quarantine,
+            || assert!(fs::metadata(&file).unwrap().permissions().readonly()),
+            || assert_eq!(file_mode(&file).unwrap(), mode),
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tags_round_trip() {
+        // The temp dir may be on tmpfs, which has no user attributes; the
+        // crate's own folder is on a real disk
+        let dir = TempDir::new_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let quarantine = QuarantineManager::with_config(dir.path().join("quarantine"), 30);
+        let file = dir.path().join("invoice.pdf");
+        fs::write(&file, b"invoice").unwrap();
+        assert!(
+            xattr::set(&file, "user.sentinel.probe", b"1").is_ok(),
+            "{} does not support extended attributes",
+            dir.path().display()
+        );
+
+        round_trip(
+            WALOperationType::SetTags {
+                path: file.clone(),
+                tags: vec!["Finance".to_string(), "2024".to_string()],
+                previous_tags: read_tags(&file).unwrap(),
+            },
+            &quarantine,
+            || assert_eq!(read_tags(&file).unwrap(), vec!["Finance", "2024"]),
+            || assert!(read_tags(&file).unwrap().is_empty()),
+        );
+    }
+}
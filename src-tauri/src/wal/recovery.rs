@@ -11,6 +11,7 @@
 use super::entry::{WALJournal, WALOperationType, WALStatus};
 use super::io::{copy_dir_safe, is_symlink};
 use super::journal::WALManager;
+use super::ops;
 use crate::security::PathValidator;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -455,6 +456,9 @@ fn execute_operation(operation: &WALOperationType) -> Result<(), String> {
                     .map_err(|e| format!("Failed to delete folder {}: {}", path.display(), e))
             }
         }
+
+        // Delete (via quarantine), link and metadata operations
+        other => ops::execute(other),
     }
 }
 
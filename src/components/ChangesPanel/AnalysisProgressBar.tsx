@@ -24,7 +24,8 @@ export function AnalysisProgressBar({
   const percentage = total > 0 ? (current / total) * 100 : 0;
   const isComplete = phase === 'complete';
   const isPlanning = phase === 'planning' || phase === 'summarizing';
-  const isAnalyzing = phase === 'analyzing' || phase === 'extracting' || phase === 'rendering';
+  const isAnalyzing =
+    phase === 'analyzing' || phase === 'analyzing_media' || phase === 'extracting' || phase === 'rendering';
   const isScanning = phase === 'scanning';
 
   // Get icon based on phase
@@ -47,6 +48,8 @@ export function AnalysisProgressBar({
         return 'Rendering PDFs';
       case 'analyzing':
         return 'Analyzing content';
+      case 'analyzing_media':
+        return 'Analyzing video and audio';
       case 'summarizing':
         return 'Summarizing results';
       case 'planning':